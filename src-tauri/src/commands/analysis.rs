@@ -0,0 +1,244 @@
+use crate::commands::cloud::{
+    hosted_api_key, openai_compatible_server, require_api_key, SessionKeyState,
+};
+use crate::commands::jobs::RunningJobs;
+use crate::error::{AppError, Result};
+use crate::services::action_items::{self, MeetingFollowUps};
+use crate::services::app_settings::AppSettings;
+use crate::services::database::Database;
+use crate::services::entities::{self, EntityMention};
+use crate::services::keywords::{self, Keyword, DEFAULT_MAX_KEYWORDS};
+use crate::services::minutes::{self, MeetingMinutes, MinutesTemplate};
+use crate::services::llm::{
+    self, ClaudeLlm, GeminiLlm, LlamaLlm, LlmProvider, OllamaLlm, OpenAIChatLlm,
+};
+use crate::services::metrics::MeasuredLlm;
+use crate::services::polish::{self, PolishedSegment};
+use crate::services::providers;
+use crate::services::publish_metadata::{self, PublishMetadata};
+use crate::services::sentiment::{self, SegmentSentiment};
+use crate::services::topics::{self, TopicSegmentation};
+use crate::services::usage;
+use crate::services::TranscriptionSegment;
+use tauri::State;
+
+/// Build an LLM provider for transcript analysis: Ollama, OpenAI, Claude, Gemini,
+/// Groq, the self-hosted OpenAI-compatible server or the embedded llama.cpp backend.
+/// Completions are timed for the local metrics.
+pub(crate) fn llm_provider(
+    provider: &str,
+    model: &str,
+    profile: Option<&str>,
+    session: &SessionKeyState,
+) -> Result<Box<dyn LlmProvider>> {
+    let llm: Result<Box<dyn LlmProvider>> = match provider {
+        llm::OLLAMA => Ok(Box::new(OllamaLlm::new(model))),
+        llm::LLAMA => Ok(Box::new(LlamaLlm::new(model)?)),
+        providers::OPENAI => {
+            let api_key = hosted_api_key(session, providers::OPENAI, profile)?;
+            Ok(Box::new(OpenAIChatLlm::openai(&api_key, model)))
+        }
+        providers::GROQ => {
+            let api_key = hosted_api_key(session, providers::GROQ, profile)?;
+            Ok(Box::new(OpenAIChatLlm::groq(&api_key, model)))
+        }
+        providers::OPENAI_COMPATIBLE => {
+            let (base_url, api_key) = openai_compatible_server(None, profile, session)?;
+            Ok(Box::new(OpenAIChatLlm::openai_compatible(
+                &base_url,
+                api_key.as_deref(),
+                model,
+            )?))
+        }
+        providers::CLAUDE => {
+            let api_key = require_api_key(session, providers::CLAUDE, profile)?;
+            Ok(Box::new(ClaudeLlm::new(&api_key, model)))
+        }
+        providers::GEMINI => {
+            let api_key = require_api_key(session, providers::GEMINI, profile)?;
+            Ok(Box::new(GeminiLlm::new(&api_key, model)))
+        }
+        other => Err(AppError::InvalidInput(format!("Unknown LLM provider: {}", other))),
+    };
+    Ok(Box::new(MeasuredLlm::new(llm?)))
+}
+
+/// Extract ranked keywords and key phrases from a transcript, with the segments
+/// they occur in, for tagging and SEO metadata.
+/// Pass a `job_id` to make it cancellable with `cancel_job`.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn extract_keywords(
+    segments: Vec<TranscriptionSegment>,
+    language: String,
+    provider: String,
+    model: String,
+    max_keywords: Option<usize>,
+    profile: Option<String>,
+    job_id: Option<String>,
+    session: State<'_, SessionKeyState>,
+    jobs: State<'_, RunningJobs>,
+) -> Result<Vec<Keyword>> {
+    let llm = llm_provider(&provider, &model, profile.as_deref(), &session)?;
+    let job = jobs.start(job_id);
+    let max_keywords = max_keywords.unwrap_or(DEFAULT_MAX_KEYWORDS).max(1);
+    keywords::extract_keywords(llm.as_ref(), &segments, &language, max_keywords, job.token()).await
+}
+
+/// Split a transcript into topical chapters (title, start, end, summary) for
+/// navigation. Also returns the YouTube chapter list when the chapters meet
+/// YouTube's rules. Pass a `job_id` to make it cancellable with `cancel_job`.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn detect_topics(
+    segments: Vec<TranscriptionSegment>,
+    language: String,
+    provider: String,
+    model: String,
+    profile: Option<String>,
+    job_id: Option<String>,
+    session: State<'_, SessionKeyState>,
+    jobs: State<'_, RunningJobs>,
+) -> Result<TopicSegmentation> {
+    let llm = llm_provider(&provider, &model, profile.as_deref(), &session)?;
+    let job = jobs.start(job_id);
+    topics::detect_topics(llm.as_ref(), &segments, &language, job.token()).await
+}
+
+/// Extract action items (owner, due date, source timestamp) and decisions from a
+/// meeting transcript. Pass a `job_id` to make it cancellable with `cancel_job`.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn extract_action_items(
+    segments: Vec<TranscriptionSegment>,
+    language: String,
+    provider: String,
+    model: String,
+    profile: Option<String>,
+    job_id: Option<String>,
+    session: State<'_, SessionKeyState>,
+    jobs: State<'_, RunningJobs>,
+) -> Result<MeetingFollowUps> {
+    let llm = llm_provider(&provider, &model, profile.as_deref(), &session)?;
+    let job = jobs.start(job_id);
+    action_items::extract_action_items(llm.as_ref(), &segments, &language, job.token()).await
+}
+
+/// Built-in meeting minutes templates
+#[tauri::command]
+pub fn get_minutes_templates() -> Vec<MinutesTemplate> {
+    minutes::builtin_templates()
+}
+
+/// Write meeting minutes (attendees, agenda, discussion, decisions, action items)
+/// with the sections of `template`, the standard one by default. Export them with
+/// `export_minutes_markdown` or `export_minutes_docx`.
+/// Pass a `job_id` to make it cancellable with `cancel_job`.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn generate_meeting_minutes(
+    segments: Vec<TranscriptionSegment>,
+    language: String,
+    provider: String,
+    model: String,
+    template: Option<MinutesTemplate>,
+    profile: Option<String>,
+    job_id: Option<String>,
+    session: State<'_, SessionKeyState>,
+    jobs: State<'_, RunningJobs>,
+) -> Result<MeetingMinutes> {
+    let llm = llm_provider(&provider, &model, profile.as_deref(), &session)?;
+    let job = jobs.start(job_id);
+    let template = template.unwrap_or_default();
+    minutes::generate_minutes(llm.as_ref(), &segments, &language, &template, job.token()).await
+}
+
+/// Score the sentiment (-1 to 1) and dominant emotion of every transcript segment,
+/// for a sentiment timeline. Pass a `job_id` to make it cancellable with `cancel_job`.
+#[tauri::command]
+pub async fn analyze_sentiment(
+    segments: Vec<TranscriptionSegment>,
+    provider: String,
+    model: String,
+    profile: Option<String>,
+    job_id: Option<String>,
+    session: State<'_, SessionKeyState>,
+    jobs: State<'_, RunningJobs>,
+) -> Result<Vec<SegmentSentiment>> {
+    let llm = llm_provider(&provider, &model, profile.as_deref(), &session)?;
+    let job = jobs.start(job_id);
+    sentiment::analyze_sentiment(llm.as_ref(), &segments, job.token()).await
+}
+
+/// Extract the people, organizations, places and products mentioned in a
+/// transcript. With a `media_path`, the mentions are saved in the project database
+/// (replacing earlier ones) for `list_entities`.
+/// Pass a `job_id` to make it cancellable with `cancel_job`.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn extract_entities(
+    segments: Vec<TranscriptionSegment>,
+    media_path: Option<String>,
+    provider: String,
+    model: String,
+    profile: Option<String>,
+    job_id: Option<String>,
+    session: State<'_, SessionKeyState>,
+    jobs: State<'_, RunningJobs>,
+) -> Result<Vec<EntityMention>> {
+    let llm = llm_provider(&provider, &model, profile.as_deref(), &session)?;
+    let job = jobs.start(job_id);
+    let extract = entities::extract_entities(llm.as_ref(), &segments, job.token());
+    let Some(path) = &media_path else {
+        return extract.await;
+    };
+    let mentions = usage::for_media(path, extract).await?;
+    Database::open()?.save_entity_mentions(path, &mentions)?;
+    Ok(mentions)
+}
+
+/// Suggest hashtags, categories and SEO keywords for publishing a recording,
+/// from its transcript and (when given) its summary.
+/// Pass a `job_id` to make it cancellable with `cancel_job`.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn suggest_publish_metadata(
+    segments: Vec<TranscriptionSegment>,
+    summary: Option<String>,
+    language: String,
+    provider: String,
+    model: String,
+    profile: Option<String>,
+    job_id: Option<String>,
+    session: State<'_, SessionKeyState>,
+    jobs: State<'_, RunningJobs>,
+) -> Result<PublishMetadata> {
+    let llm = llm_provider(&provider, &model, profile.as_deref(), &session)?;
+    let job = jobs.start(job_id);
+    publish_metadata::suggest_metadata(
+        llm.as_ref(),
+        &segments,
+        summary.as_deref(),
+        &language,
+        job.token(),
+    )
+    .await
+}
+
+/// Rewrite every transcript segment as a clean read (punctuation, casing, no
+/// filler words, obvious mis-hearings fixed), next to the verbatim text and
+/// with the same timestamps. Pass a `job_id` to make it cancellable with `cancel_job`.
+#[tauri::command]
+pub async fn polish_transcript(
+    segments: Vec<TranscriptionSegment>,
+    provider: String,
+    model: String,
+    profile: Option<String>,
+    job_id: Option<String>,
+    session: State<'_, SessionKeyState>,
+    jobs: State<'_, RunningJobs>,
+) -> Result<Vec<PolishedSegment>> {
+    let llm = llm_provider(&provider, &model, profile.as_deref(), &session)?;
+    let job = jobs.start(job_id);
+    polish::polish_transcript(llm.as_ref(), &segments, job.token()).await
+}
@@ -0,0 +1,138 @@
+use crate::commands::scope::scoped_path;
+use crate::error::{AppError, Result};
+use crate::services::app_settings::AppSettings;
+use crate::services::backup::{self, BackupInfo};
+use crate::services::database::{now, Database};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+/// How long the scheduler waits before looking at the settings again while
+/// scheduled backups are off
+const SCHEDULE_OFF_RECHECK: Duration = Duration::from_secs(300);
+
+/// Run file work of a backup off the async runtime
+async fn blocking<T: Send + 'static>(
+    work: impl FnOnce() -> Result<T> + Send + 'static,
+) -> Result<T> {
+    tokio::task::spawn_blocking(work)
+        .await
+        .map_err(|e| AppError::ProcessFailed(format!("Backup task failed: {}", e)))?
+}
+
+/// The folder given (which the user must have chosen), else the backup folder
+/// from the settings
+fn backup_dir(app: &AppHandle, dir: Option<String>) -> Result<PathBuf> {
+    match dir {
+        Some(dir) => scoped_path(app, &dir),
+        None => AppSettings::load()?
+            .backup_dir
+            .map(PathBuf::from)
+            .ok_or_else(|| AppError::InvalidInput("No backup folder chosen".to_string())),
+    }
+}
+
+/// Back up the project database and settings into `dir` (or the backup folder
+/// from the settings) now
+#[tauri::command]
+pub async fn backup_database(app: AppHandle, dir: Option<String>) -> Result<BackupInfo> {
+    let dir = backup_dir(&app, dir)?;
+    let info = blocking(move || {
+        backup::create_backup(
+            &Database::default_path()?,
+            &AppSettings::default_path()?,
+            &dir,
+            false,
+        )
+    })
+    .await?;
+    log::info!("[backup] Backed up the database to {}", info.path);
+    Ok(info)
+}
+
+/// List the backups in `dir` (or the backup folder from the settings), newest first
+#[tauri::command]
+pub async fn list_backups(app: AppHandle, dir: Option<String>) -> Result<Vec<BackupInfo>> {
+    let dir = backup_dir(&app, dir)?;
+    blocking(move || backup::list_backups(&dir)).await
+}
+
+/// Run SQLite's integrity check on a backup without restoring it
+#[tauri::command]
+pub async fn verify_backup(app: AppHandle, path: String) -> Result<()> {
+    let path = scoped_path(&app, &path)?;
+    blocking(move || backup::verify_backup(&path)).await
+}
+
+/// Replace the project database and settings with a backup after checking it.
+/// The frontend should reload its data afterwards.
+#[tauri::command]
+pub async fn restore_database(app: AppHandle, path: String) -> Result<()> {
+    let backup_dir = scoped_path(&app, &path)?;
+    blocking(move || {
+        backup::restore_backup(
+            &backup_dir,
+            &Database::default_path()?,
+            &AppSettings::default_path()?,
+        )
+    })
+    .await?;
+    crate::messages::init();
+    log::info!("[backup] Restored the database from {}", path);
+    let _ = app.emit("backup:restored", &path);
+    Ok(())
+}
+
+/// Back up now if the newest backup in the scheduled folder is at least an
+/// interval old. Returns how long until the next backup is due.
+async fn run_scheduled_backup(dir: &Path, interval: Duration, keep: usize) -> Result<Duration> {
+    let dir = dir.to_path_buf();
+    blocking(move || {
+        let last = backup::list_backups(&dir)?
+            .first()
+            .map(|backup| backup.created_at);
+        let age = Duration::from_secs(now().saturating_sub(last.unwrap_or(0)));
+        if last.is_some() && age < interval {
+            return Ok(interval - age);
+        }
+        let info = backup::create_backup(
+            &Database::default_path()?,
+            &AppSettings::default_path()?,
+            &dir,
+            true,
+        )?;
+        log::info!("[backup] Scheduled backup written to {}", info.path);
+        backup::prune_backups(&dir, keep.max(1))?;
+        Ok(interval)
+    })
+    .await
+}
+
+/// Start the background task that backs up the database on the interval set
+/// in the app settings
+pub fn start_backup_scheduler() {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let settings = AppSettings::load().unwrap_or_default();
+            let Some(dir) = settings
+                .backup_dir
+                .filter(|_| settings.backup_interval_hours > 0)
+            else {
+                tokio::time::sleep(SCHEDULE_OFF_RECHECK).await;
+                continue;
+            };
+
+            let interval = Duration::from_secs(settings.backup_interval_hours * 3600);
+            let wait =
+                match run_scheduled_backup(Path::new(&dir), interval, settings.backup_keep).await {
+                    Ok(due_in) => due_in,
+                    Err(e) => {
+                        log::error!("[backup] Scheduled backup failed: {}", e);
+                        interval
+                    }
+                };
+            // Pick up changed settings without waiting a whole interval
+            tokio::time::sleep(wait.min(SCHEDULE_OFF_RECHECK)).await;
+        }
+    });
+}
@@ -0,0 +1,111 @@
+use crate::commands::jobs::RunningJobs;
+use crate::commands::models::ServiceState;
+use crate::error::{AppError, Result};
+use crate::services::benchmark::{self, BenchmarkReport, SAMPLE_RESOURCE};
+use crate::services::database::{Benchmark, Database};
+use crate::services::{job_queue, FFmpegService, WhisperModel};
+use serde::Serialize;
+use std::path::Path;
+use std::time::Instant;
+use tauri::path::BaseDirectory;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+/// `benchmark:progress` event payload, sent before each model runs
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BenchmarkProgress {
+    model: String,
+    index: usize,
+    total: usize,
+}
+
+/// Write the bundled speech sample to `path`, returning its length in seconds.
+/// Fails when the build has no sample, rather than timing other audio.
+async fn write_sample(app: &AppHandle, path: &Path) -> Result<f64> {
+    let bundled = app
+        .path()
+        .resolve(SAMPLE_RESOURCE, BaseDirectory::Resource)
+        .ok()
+        .filter(|bundled| bundled.exists())
+        .ok_or_else(|| {
+            AppError::InvalidPath(format!("Benchmark sample {} is missing", SAMPLE_RESOURCE))
+        })?;
+    tokio::fs::copy(&bundled, path).await?;
+    FFmpegService::get_duration(path).await
+}
+
+/// Transcribe the bundled speech sample with each installed whisper model
+/// and record how fast it went. Pass a `job_id` to make it cancellable with
+/// `cancel_job`.
+#[tauri::command]
+pub async fn run_benchmark(
+    app: AppHandle,
+    job_id: Option<String>,
+    services: State<'_, ServiceState>,
+    jobs: State<'_, RunningJobs>,
+) -> Result<BenchmarkReport> {
+    let job = jobs.start(job_id);
+    let installed = services.download().get_installed_models().await?;
+    let models: Vec<String> = WhisperModel::available_models()
+        .into_iter()
+        .map(|model| model.id)
+        .filter(|id| installed.contains(id))
+        .collect();
+    if models.is_empty() {
+        return Err(AppError::ModelNotFound("No whisper model is installed".to_string()));
+    }
+
+    let temp_dir = job_queue::temp_dir();
+    tokio::fs::create_dir_all(&temp_dir).await?;
+    let sample = temp_dir.join(format!("benchmark-{}.wav", uuid::Uuid::new_v4()));
+    let sample_seconds = match write_sample(&app, &sample).await {
+        Ok(seconds) => seconds,
+        Err(e) => {
+            let _ = tokio::fs::remove_file(&sample).await;
+            return Err(e);
+        }
+    };
+
+    let whisper = services.whisper();
+    let mut timings = Vec::new();
+    for (index, model) in models.iter().enumerate() {
+        let progress = BenchmarkProgress {
+            model: model.clone(),
+            index,
+            total: models.len(),
+        };
+        let _ = app.emit("benchmark:progress", progress);
+
+        let started = Instant::now();
+        // A fixed language skips language detection, which would be timed too
+        let result = whisper
+            .transcribe(&sample, model, Some("en"), job.token(), |_| {})
+            .await;
+        if let Err(e) = result {
+            let _ = tokio::fs::remove_file(&sample).await;
+            return Err(e);
+        }
+        timings.push((model, started.elapsed().as_secs_f64()));
+    }
+    let _ = tokio::fs::remove_file(&sample).await;
+
+    let db = Database::open()?;
+    for (model, elapsed) in timings {
+        db.record_benchmark(model, sample_seconds, elapsed)?;
+    }
+    Ok(BenchmarkReport::new(db.latest_benchmarks()?))
+}
+
+/// The latest benchmark of each model and the model suggested for this machine
+#[tauri::command]
+pub fn get_benchmark_report() -> Result<BenchmarkReport> {
+    Ok(BenchmarkReport::new(Database::open()?.latest_benchmarks()?))
+}
+
+/// Seconds `model_id` should take to transcribe `media_seconds` of audio on
+/// this machine, or none if the model hasn't been benchmarked
+#[tauri::command]
+pub fn estimate_transcription_time(model_id: String, media_seconds: f64) -> Result<Option<f64>> {
+    let benchmarks: Vec<Benchmark> = Database::open()?.latest_benchmarks()?;
+    Ok(benchmark::estimate_seconds(&benchmarks, &model_id, media_seconds))
+}
@@ -1,10 +1,36 @@
+use crate::commands::analysis::llm_provider;
+use crate::commands::jobs::RunningJobs;
+use crate::commands::progress::ThrottledEmitter;
+use crate::commands::project::record_transcription;
+use crate::commands::prompts::{chapters_with_template, summarize_with_template};
+use crate::commands::scope::scoped_path;
+use crate::commands::transcribe::TranscriptionProgress;
 use crate::error::Result;
+use crate::redact;
 use crate::services::{
-    keychain::{ApiKeyType, KeychainService},
-    ClaudeModel, ClaudeService, OpenAIModel, OpenAIService,
+    app_settings::AppSettings,
+    assemblyai::{AssemblyAIFeatures, AssemblyAIService, AssemblyAITranscription},
+    cancellation::cancellable,
+    chapters::YouTubeChapters,
+    credential_bundle::CredentialBundle,
+    credential_profiles::CredentialProfiles,
+    gemini::GeminiMessage,
+    groq::GroqService,
+    key_validation::KeyValidator,
+    keychain::KeychainService,
+    openai::configured_base_url,
+    openai_compatible::OpenAICompatibleService,
+    providers::{self, SecretProvider},
+    secret_file::EncryptedFileStore,
+    usage,
+    ClaudeModel, ClaudeService, GeminiModel, GeminiService, OpenAIModel, OpenAIService,
+    StorySegment, TranscriptionResult,
 };
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, State};
 
 // ============================================================================
 // API Key Management Commands
@@ -14,74 +40,373 @@ use std::path::PathBuf;
 pub struct ApiKeyStatus {
     pub openai: bool,
     pub claude: bool,
+    pub gemini: bool,
+    pub groq: bool,
+    pub deepgram: bool,
+    pub assemblyai: bool,
+    pub openai_compatible: bool,
 }
 
-/// Store an API key securely
+/// API keys held only in memory for the current app session.
+/// Never written to the keychain; gone when the app quits.
+#[derive(Default)]
+pub struct SessionKeyState {
+    keys: Mutex<HashMap<String, String>>,
+}
+
+impl SessionKeyState {
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, String>> {
+        // A poisoned map of strings is still consistent
+        self.keys.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn get(&self, provider_id: &str) -> Option<String> {
+        self.lock().get(provider_id).cloned()
+    }
+
+    fn set(&self, provider_id: &str, api_key: &str) {
+        self.lock().insert(provider_id.to_string(), api_key.to_string());
+    }
+
+    fn remove(&self, provider_id: &str) {
+        self.lock().remove(provider_id);
+    }
+
+    fn contains(&self, provider_id: &str) -> bool {
+        self.lock().contains_key(provider_id)
+    }
+}
+
+/// Resolve a provider name against the registry of known providers
+fn parse_provider(provider: &str) -> Result<&'static SecretProvider> {
+    providers::find(provider).ok_or_else(|| {
+        crate::error::AppError::ProcessFailed(format!("Unknown provider: {}", provider))
+    })
+}
+
+/// Result of storing an API key, including the optional live validation
+#[derive(Debug, Clone, Serialize)]
+pub struct ApiKeyValidation {
+    /// Whether the key was saved (to secure storage, or memory if session-only)
+    pub stored: bool,
+    /// Whether the key is held in memory for this session only
+    pub session_only: bool,
+    /// Whether a live validation call was attempted
+    pub validated: bool,
+    /// Provider verdict; `None` if not validated or the check could not complete
+    pub valid: Option<bool>,
+    pub error: Option<String>,
+}
+
+/// Look up an API key: a session-only key first, then the stored key
+/// of the active profile (or the given profile)
+pub(crate) fn require_api_key(
+    session: &SessionKeyState,
+    provider_id: &str,
+    profile: Option<&str>,
+) -> Result<String> {
+    let provider = parse_provider(provider_id)?;
+    optional_api_key(session, provider.id, profile)?
+        .ok_or_else(|| crate::error::AppError::MissingApiKey(provider.display_name.to_string()))
+}
+
+/// Like [`require_api_key`], for providers that work without a key. Only a
+/// missing key is `None`; keychain errors are returned.
+pub(crate) fn optional_api_key(
+    session: &SessionKeyState,
+    provider_id: &str,
+    profile: Option<&str>,
+) -> Result<Option<String>> {
+    let provider = parse_provider(provider_id)?;
+    match profile {
+        Some(profile) => KeychainService::get_profile_secret(provider.id, profile),
+        None => match session.get(provider.id) {
+            Some(key) => Ok(Some(key)),
+            None => KeychainService::get_secret(provider.id),
+        },
+    }
+}
+
+/// API key of a hosted provider that speaks the OpenAI protocol. When
+/// `provider_base_urls` points it at another server, such as LM Studio or a
+/// llama.cpp server, the key may be left unset and no key is sent.
+pub(crate) fn hosted_api_key(
+    session: &SessionKeyState,
+    provider_id: &str,
+    profile: Option<&str>,
+) -> Result<String> {
+    if configured_base_url(provider_id).is_none() {
+        return require_api_key(session, provider_id, profile);
+    }
+    Ok(optional_api_key(session, provider_id, profile)?.unwrap_or_default())
+}
+
+/// Base URL and optional API key of the self-hosted OpenAI-compatible server.
+/// Uses `base_url` when given, otherwise the URL from the app settings.
+pub(crate) fn openai_compatible_server(
+    base_url: Option<String>,
+    profile: Option<&str>,
+    session: &SessionKeyState,
+) -> Result<(String, Option<String>)> {
+    let base_url = match base_url {
+        Some(url) => url,
+        None => AppSettings::load()?.openai_compatible_base_url.ok_or_else(|| {
+            crate::error::AppError::InvalidInput(
+                "No OpenAI-compatible server URL configured".to_string(),
+            )
+        })?,
+    };
+    let api_key = optional_api_key(session, providers::OPENAI_COMPATIBLE, profile)?;
+    Ok((base_url, api_key))
+}
+
+/// Store an API key securely.
+/// With `validate`, the key is first checked against the provider and
+/// rejected keys are not stored. With `session_only`, the key is kept in
+/// memory until the app quits and never touches the keychain.
 #[tauri::command]
-pub fn store_api_key(provider: &str, api_key: &str) -> Result<()> {
-    println!("[store_api_key] Called with provider: {}, key length: {}", provider, api_key.len());
-    let result = match provider.to_lowercase().as_str() {
-        "openai" => KeychainService::store_openai_key(api_key),
-        "claude" => KeychainService::store_claude_key(api_key),
-        _ => Err(crate::error::AppError::ProcessFailed(format!(
-            "Unknown provider: {}",
-            provider
-        ))),
+pub async fn store_api_key(
+    provider: String,
+    api_key: String,
+    validate: Option<bool>,
+    profile: Option<String>,
+    session_only: Option<bool>,
+    session: State<'_, SessionKeyState>,
+) -> Result<ApiKeyValidation> {
+    redact::register_secret(&api_key);
+    log::info!("[store_api_key] Called with provider: {}", provider);
+    let secret_provider = parse_provider(&provider)?;
+
+    let mut validation = ApiKeyValidation {
+        stored: false,
+        session_only: session_only.unwrap_or(false),
+        validated: false,
+        valid: None,
+        error: None,
     };
-    println!("[store_api_key] Store result: {:?}", result.is_ok());
 
-    // Verify storage immediately after
-    let verify = match provider.to_lowercase().as_str() {
-        "openai" => KeychainService::get_openai_key(),
-        "claude" => KeychainService::get_claude_key(),
-        _ => Ok(None),
+    if validate.unwrap_or(false) {
+        validation.validated = true;
+        match KeyValidator::validate(secret_provider, &api_key).await {
+            Ok(true) => validation.valid = Some(true),
+            Ok(false) => {
+                validation.valid = Some(false);
+                validation.error = Some(format!("The {} API key was rejected", secret_provider.display_name));
+                return Ok(validation);
+            }
+            // Inconclusive (offline, provider outage) - store anyway and report why
+            Err(e) => validation.error = Some(e.to_string()),
+        }
+    }
+
+    if validation.session_only {
+        session.set(secret_provider.id, &api_key);
+        validation.stored = true;
+        return Ok(validation);
+    }
+
+    // A persisted key replaces any session-only key for the provider
+    session.remove(secret_provider.id);
+
+    let result = match profile.as_deref() {
+        Some(profile) => KeychainService::store_profile_secret(secret_provider.id, profile, &api_key),
+        None => KeychainService::store_secret(secret_provider.id, &api_key),
     };
-    println!("[store_api_key] Verification - key exists: {:?}", verify.as_ref().map(|v| v.is_some()));
+    log::info!("[store_api_key] Store result: {:?}", result.is_ok());
+
+    // Verify storage immediately after
+    let verify = require_api_key(&session, secret_provider.id, profile.as_deref()).map(Some);
+    log::info!("[store_api_key] Verification - key exists: {:?}", verify.as_ref().map(|v| v.is_some()));
     if let Err(ref e) = verify {
-        println!("[store_api_key] Verification error: {:?}", e);
+        log::warn!("[store_api_key] Verification error: {:?}", e);
     }
 
-    result
+    result?;
+    validation.stored = true;
+    Ok(validation)
 }
 
 /// Get API key (returns masked version for UI)
 #[tauri::command]
-pub fn get_api_key_masked(provider: &str) -> Result<Option<String>> {
-    let key = match provider.to_lowercase().as_str() {
-        "openai" => KeychainService::get_openai_key()?,
-        "claude" => KeychainService::get_claude_key()?,
-        _ => None,
+pub fn get_api_key_masked(
+    provider: &str,
+    profile: Option<String>,
+    session: State<'_, SessionKeyState>,
+) -> Result<Option<String>> {
+    let key = match providers::find(provider) {
+        Some(provider) => optional_api_key(&session, provider.id, profile.as_deref())?,
+        None => None,
     };
+    Ok(key.as_deref().map(mask_key))
+}
 
-    // Return masked version (show only last 4 chars)
-    Ok(key.map(|k| {
-        if k.len() > 4 {
-            format!("{}...{}", &k[..4], &k[k.len() - 4..])
-        } else {
-            "****".to_string()
-        }
-    }))
+/// Masked version of a key for display (shows only the first and last 4 chars)
+fn mask_key(key: &str) -> String {
+    if key.len() > 4 {
+        format!("{}...{}", &key[..4], &key[key.len() - 4..])
+    } else {
+        "****".to_string()
+    }
 }
 
-/// Delete an API key
+/// Delete an API key (also forgets a session-only key for the provider)
 #[tauri::command]
-pub fn delete_api_key(provider: &str) -> Result<()> {
-    match provider.to_lowercase().as_str() {
-        "openai" => KeychainService::delete_api_key(ApiKeyType::OpenAI),
-        "claude" => KeychainService::delete_api_key(ApiKeyType::Claude),
-        _ => Err(crate::error::AppError::ProcessFailed(format!(
-            "Unknown provider: {}",
-            provider
-        ))),
+pub fn delete_api_key(
+    provider: &str,
+    profile: Option<String>,
+    session: State<'_, SessionKeyState>,
+) -> Result<()> {
+    let provider = parse_provider(provider)?;
+    match profile {
+        Some(profile) => KeychainService::delete_profile_secret(provider.id, &profile),
+        None => {
+            session.remove(provider.id);
+            KeychainService::delete_secret(provider.id)
+        }
     }
 }
 
+/// Credential profiles configured for a provider
+#[derive(Debug, Clone, Serialize)]
+pub struct CredentialProfileList {
+    pub active: String,
+    pub profiles: Vec<String>,
+}
+
+/// List the credential profiles of a provider
+#[tauri::command]
+pub fn list_credential_profiles(provider: &str) -> Result<CredentialProfileList> {
+    let provider = parse_provider(provider)?;
+    let profiles = CredentialProfiles::load()?;
+
+    Ok(CredentialProfileList {
+        active: profiles.active_profile(provider.id),
+        profiles: profiles.profiles(provider.id),
+    })
+}
+
+/// Select which credential profile a provider uses by default
+#[tauri::command]
+pub fn set_active_credential_profile(provider: &str, profile: &str) -> Result<()> {
+    let provider = parse_provider(provider)?;
+    let mut profiles = CredentialProfiles::load()?;
+    profiles.set_active(provider.id, profile)?;
+    profiles.save()
+}
+
 /// Check which API keys are configured
 #[tauri::command]
-pub fn get_api_key_status() -> Result<ApiKeyStatus> {
+pub fn get_api_key_status(session: State<'_, SessionKeyState>) -> Result<ApiKeyStatus> {
+    let has_key = |provider_id| -> Result<bool> {
+        Ok(session.contains(provider_id) || KeychainService::has_secret(provider_id)?)
+    };
+
     Ok(ApiKeyStatus {
-        openai: KeychainService::has_api_key(ApiKeyType::OpenAI)?,
-        claude: KeychainService::has_api_key(ApiKeyType::Claude)?,
+        openai: has_key(providers::OPENAI)?,
+        claude: has_key(providers::CLAUDE)?,
+        gemini: has_key(providers::GEMINI)?,
+        groq: has_key(providers::GROQ)?,
+        deepgram: has_key(providers::DEEPGRAM)?,
+        assemblyai: has_key(providers::ASSEMBLYAI)?,
+        openai_compatible: has_key(providers::OPENAI_COMPATIBLE)?,
+    })
+}
+
+/// A known provider and whether its secret is configured
+#[derive(Debug, Clone, Serialize)]
+pub struct SecretProviderStatus {
+    pub id: String,
+    pub display_name: String,
+    pub configured: bool,
+    /// The configured key is held in memory for this session only
+    pub session_only: bool,
+}
+
+/// List every provider in the registry with its configuration state
+#[tauri::command]
+pub fn list_secret_providers(
+    session: State<'_, SessionKeyState>,
+) -> Result<Vec<SecretProviderStatus>> {
+    providers::PROVIDERS
+        .iter()
+        .map(|provider| {
+            let session_only = session.contains(provider.id);
+            Ok(SecretProviderStatus {
+                id: provider.id.to_string(),
+                display_name: provider.display_name.to_string(),
+                configured: session_only || KeychainService::has_secret(provider.id)?,
+                session_only,
+            })
+        })
+        .collect()
+}
+
+/// Secret storage backend status
+#[derive(Debug, Clone, Serialize)]
+pub struct SecretStoreStatus {
+    pub keychain_available: bool,
+    pub file_store_exists: bool,
+    pub file_store_unlocked: bool,
+}
+
+/// Get which secret storage backend is in use
+#[tauri::command]
+pub fn get_secret_store_status() -> Result<SecretStoreStatus> {
+    Ok(SecretStoreStatus {
+        keychain_available: KeychainService::is_keychain_available(),
+        file_store_exists: EncryptedFileStore::new()?.exists(),
+        file_store_unlocked: EncryptedFileStore::is_unlocked(),
+    })
+}
+
+/// Unlock (or create) the encrypted secrets file used when no OS keychain is available
+#[tauri::command]
+pub fn unlock_secret_store(passphrase: &str) -> Result<()> {
+    EncryptedFileStore::unlock(passphrase)
+}
+
+/// Lock the encrypted secrets file for the rest of the session
+#[tauri::command]
+pub fn lock_secret_store() {
+    EncryptedFileStore::lock();
+}
+
+/// Result of importing a credential bundle
+#[derive(Debug, Clone, Serialize)]
+pub struct CredentialImport {
+    /// Number of API keys written to secure storage
+    pub imported_keys: usize,
+    /// Settings exported alongside the keys, for the frontend to restore
+    pub settings: Option<serde_json::Value>,
+}
+
+/// Export all stored API keys and the given settings into a passphrase-encrypted file
+#[tauri::command]
+pub fn export_credentials(
+    app: AppHandle,
+    path: String,
+    passphrase: String,
+    settings: Option<serde_json::Value>,
+) -> Result<usize> {
+    let output_path = scoped_path(&app, &path)?;
+    let bundle = CredentialBundle::collect(settings)?;
+    bundle.write_to(&output_path, &passphrase)?;
+
+    let exported: usize = bundle.providers.values().map(|p| p.keys.len()).sum();
+    log::info!("[export_credentials] Exported {} keys to {}", exported, path);
+    Ok(exported)
+}
+
+/// Import API keys and settings from a passphrase-encrypted file
+#[tauri::command]
+pub fn import_credentials(path: String, passphrase: String) -> Result<CredentialImport> {
+    let bundle = CredentialBundle::read_from(&PathBuf::from(&path), &passphrase)?;
+    let imported_keys = bundle.apply()?;
+    log::info!("[import_credentials] Imported {} keys from {}", imported_keys, path);
+
+    Ok(CredentialImport {
+        imported_keys,
+        settings: bundle.settings,
     })
 }
 
@@ -91,9 +416,11 @@ pub fn get_api_key_status() -> Result<ApiKeyStatus> {
 
 /// Validate OpenAI API key
 #[tauri::command]
-pub async fn validate_openai_key() -> Result<bool> {
-    let api_key = KeychainService::get_openai_key()?
-        .ok_or_else(|| crate::error::AppError::ProcessFailed("OpenAI API key not set".into()))?;
+pub async fn validate_openai_key(
+    profile: Option<String>,
+    session: State<'_, SessionKeyState>,
+) -> Result<bool> {
+    let api_key = require_api_key(&session, providers::OPENAI, profile.as_deref())?;
 
     let service = OpenAIService::new(&api_key);
     service.validate_api_key().await
@@ -103,21 +430,33 @@ pub async fn validate_openai_key() -> Result<bool> {
 /// Used when validating immediately after storing to avoid keychain sync delays
 #[tauri::command]
 pub async fn validate_openai_key_direct(api_key: String) -> Result<bool> {
+    redact::register_secret(&api_key);
     let service = OpenAIService::new(&api_key);
     service.validate_api_key().await
 }
 
-/// Transcribe audio using OpenAI Whisper API
+/// Transcribe audio using OpenAI Whisper API.
+/// Pass a `job_id` to make it cancellable with `cancel_job`.
 #[tauri::command]
-pub async fn openai_transcribe(audio_path: String, language: Option<String>, model: Option<String>) -> Result<OpenAITranscriptionResult> {
-    let api_key = KeychainService::get_openai_key()?
-        .ok_or_else(|| crate::error::AppError::ProcessFailed("OpenAI API key not set".into()))?;
+pub async fn openai_transcribe(
+    audio_path: String,
+    language: Option<String>,
+    model: Option<String>,
+    profile: Option<String>,
+    job_id: Option<String>,
+    session: State<'_, SessionKeyState>,
+    jobs: State<'_, RunningJobs>,
+) -> Result<OpenAITranscriptionResult> {
+    let job = jobs.start(job_id);
+    let api_key = hosted_api_key(&session, providers::OPENAI, profile.as_deref())?;
 
     let service = OpenAIService::new(&api_key);
     let path = PathBuf::from(&audio_path);
-    let result = service.transcribe(&path, language.as_deref(), model.as_deref()).await?;
+    let request = service.transcribe(&path, language.as_deref(), model.as_deref());
+    let result = cancellable(job.token(), request).await?;
+    let history_entry = TranscriptionResult::from(result.clone());
 
-    Ok(OpenAITranscriptionResult {
+    let result = OpenAITranscriptionResult {
         text: result.text,
         language: result.language,
         duration: result.duration,
@@ -128,22 +467,33 @@ pub async fn openai_transcribe(audio_path: String, language: Option<String>, mod
                     start: s.start,
                     end: s.end,
                     text: s.text,
+                    confidence: s.avg_logprob.map(f64::exp),
                 })
                 .collect()
         }),
-    })
+    };
+
+    record_transcription(&audio_path, &history_entry, model.as_deref().unwrap_or("whisper-1"));
+
+    Ok(result)
 }
 
-/// Chat with OpenAI GPT
+/// Chat with OpenAI GPT.
+/// Pass a `job_id` to make it cancellable with `cancel_job`.
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn openai_chat(
     model: String,
     messages: Vec<ChatMessageInput>,
     temperature: Option<f32>,
     max_tokens: Option<u32>,
+    profile: Option<String>,
+    job_id: Option<String>,
+    session: State<'_, SessionKeyState>,
+    jobs: State<'_, RunningJobs>,
 ) -> Result<String> {
-    let api_key = KeychainService::get_openai_key()?
-        .ok_or_else(|| crate::error::AppError::ProcessFailed("OpenAI API key not set".into()))?;
+    let job = jobs.start(job_id);
+    let api_key = hosted_api_key(&session, providers::OPENAI, profile.as_deref())?;
 
     let service = OpenAIService::new(&api_key);
     let msgs: Vec<crate::services::openai::ChatMessage> = messages
@@ -154,17 +504,129 @@ pub async fn openai_chat(
         })
         .collect();
 
-    service.chat(&model, msgs, temperature, max_tokens).await
+    let request = service.chat(&model, msgs, temperature, max_tokens);
+    cancellable(job.token(), request).await
+}
+
+/// `openai:chat-delta` event payload: the next piece of a streamed reply
+#[derive(Debug, Clone, Serialize)]
+pub struct ChatDeltaEvent {
+    pub request_id: String,
+    pub delta: String,
 }
 
-/// Summarize text using OpenAI GPT
+/// Chat with OpenAI GPT, emitting the reply as it is generated in
+/// `openai:chat-delta` events tagged with `request_id`. Returns the whole
+/// reply; `cancel_job` with the request id stops it.
 #[tauri::command]
-pub async fn openai_summarize(text: String, language: String, model: String) -> Result<String> {
-    let api_key = KeychainService::get_openai_key()?
-        .ok_or_else(|| crate::error::AppError::ProcessFailed("OpenAI API key not set".into()))?;
+#[allow(clippy::too_many_arguments)]
+pub async fn openai_chat_stream(
+    app: AppHandle,
+    request_id: String,
+    model: String,
+    messages: Vec<ChatMessageInput>,
+    temperature: Option<f32>,
+    max_tokens: Option<u32>,
+    profile: Option<String>,
+    session: State<'_, SessionKeyState>,
+    jobs: State<'_, RunningJobs>,
+) -> Result<String> {
+    let api_key = hosted_api_key(&session, providers::OPENAI, profile.as_deref())?;
 
     let service = OpenAIService::new(&api_key);
-    service.summarize(&model, &text, &language).await
+    let msgs: Vec<crate::services::openai::ChatMessage> = messages
+        .into_iter()
+        .map(|m| crate::services::openai::ChatMessage {
+            role: m.role,
+            content: m.content,
+        })
+        .collect();
+
+    let job = jobs.start(Some(request_id.clone()));
+    let reply = service.chat_stream(&model, msgs, temperature, max_tokens, |delta| {
+        let event = ChatDeltaEvent {
+            request_id: request_id.clone(),
+            delta: delta.to_string(),
+        };
+        let _ = app.emit("openai:chat-delta", event);
+    });
+    cancellable(job.token(), reply).await
+}
+
+/// Summarize text using OpenAI GPT, with the
+/// built-in prompt or a saved prompt template.
+/// Pass a `job_id` to make it cancellable with `cancel_job`.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn openai_summarize(
+    text: String,
+    language: String,
+    model: String,
+    profile: Option<String>,
+    template_id: Option<i64>,
+    job_id: Option<String>,
+    session: State<'_, SessionKeyState>,
+    jobs: State<'_, RunningJobs>,
+) -> Result<String> {
+    let job = jobs.start(job_id);
+    if let Some(template_id) = template_id {
+        let llm = llm_provider(providers::OPENAI, &model, profile.as_deref(), &session)?;
+        return summarize_with_template(llm.as_ref(), template_id, &text, &language, job.token())
+            .await;
+    }
+    let api_key = hosted_api_key(&session, providers::OPENAI, profile.as_deref())?;
+
+    let service = OpenAIService::new(&api_key);
+    let request = service.summarize(&model, &text, &language);
+    cancellable(job.token(), request).await
+}
+
+/// Extract story order from transcription segments using OpenAI GPT.
+/// Pass a `job_id` to make it cancellable with `cancel_job`.
+#[tauri::command]
+pub async fn openai_extract_story_order(
+    model: String,
+    segments: Vec<crate::services::TranscriptionSegment>,
+    profile: Option<String>,
+    job_id: Option<String>,
+    session: State<'_, SessionKeyState>,
+    jobs: State<'_, RunningJobs>,
+) -> Result<Vec<StorySegment>> {
+    let job = jobs.start(job_id);
+    let api_key = hosted_api_key(&session, providers::OPENAI, profile.as_deref())?;
+
+    let service = OpenAIService::new(&api_key);
+    let request = service.extract_story_order(&model, &segments);
+    cancellable(job.token(), request).await
+}
+
+/// Generate a YouTube chapter list from transcription segments using OpenAI GPT, with the
+/// built-in prompt or a saved prompt template.
+/// Pass a `job_id` to make it cancellable with `cancel_job`.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn openai_generate_youtube_chapters(
+    model: String,
+    segments: Vec<crate::services::TranscriptionSegment>,
+    language: String,
+    profile: Option<String>,
+    template_id: Option<i64>,
+    job_id: Option<String>,
+    session: State<'_, SessionKeyState>,
+    jobs: State<'_, RunningJobs>,
+) -> Result<YouTubeChapters> {
+    let job = jobs.start(job_id);
+    if let Some(template_id) = template_id {
+        let llm = llm_provider(providers::OPENAI, &model, profile.as_deref(), &session)?;
+        return chapters_with_template(llm.as_ref(), template_id, &segments, &language, job.token())
+            .await;
+    }
+    let api_key = hosted_api_key(&session, providers::OPENAI, profile.as_deref())?;
+
+    let service = OpenAIService::new(&api_key);
+    let request = service.generate_chapters(&model, &segments, &language);
+    let chapters = cancellable(job.token(), request).await?;
+    Ok(YouTubeChapters::new(chapters))
 }
 
 /// Get available OpenAI models (static list)
@@ -175,9 +637,11 @@ pub fn get_openai_models() -> Vec<OpenAIModel> {
 
 /// Fetch available OpenAI models from API (dynamic, sorted by newest)
 #[tauri::command]
-pub async fn fetch_openai_models() -> Result<Vec<OpenAIModel>> {
-    let api_key = KeychainService::get_openai_key()?
-        .ok_or_else(|| crate::error::AppError::ProcessFailed("OpenAI API key not set".into()))?;
+pub async fn fetch_openai_models(
+    profile: Option<String>,
+    session: State<'_, SessionKeyState>,
+) -> Result<Vec<OpenAIModel>> {
+    let api_key = hosted_api_key(&session, providers::OPENAI, profile.as_deref())?;
 
     let service = OpenAIService::new(&api_key);
     service.fetch_models().await
@@ -187,6 +651,7 @@ pub async fn fetch_openai_models() -> Result<Vec<OpenAIModel>> {
 /// Used when fetching immediately after storing to avoid keychain sync delays
 #[tauri::command]
 pub async fn fetch_openai_models_direct(api_key: String) -> Result<Vec<OpenAIModel>> {
+    redact::register_secret(&api_key);
     let service = OpenAIService::new(&api_key);
     service.fetch_models().await
 }
@@ -197,9 +662,11 @@ pub async fn fetch_openai_models_direct(api_key: String) -> Result<Vec<OpenAIMod
 
 /// Validate Claude API key (from keychain)
 #[tauri::command]
-pub async fn validate_claude_key() -> Result<bool> {
-    let api_key = KeychainService::get_claude_key()?
-        .ok_or_else(|| crate::error::AppError::ProcessFailed("Claude API key not set".into()))?;
+pub async fn validate_claude_key(
+    profile: Option<String>,
+    session: State<'_, SessionKeyState>,
+) -> Result<bool> {
+    let api_key = require_api_key(&session, providers::CLAUDE, profile.as_deref())?;
 
     let service = ClaudeService::new(&api_key);
     service.validate_api_key().await
@@ -209,21 +676,28 @@ pub async fn validate_claude_key() -> Result<bool> {
 /// Used when validating immediately after storing to avoid keychain sync delays
 #[tauri::command]
 pub async fn validate_claude_key_direct(api_key: String) -> Result<bool> {
+    redact::register_secret(&api_key);
     let service = ClaudeService::new(&api_key);
     service.validate_api_key().await
 }
 
-/// Chat with Claude
+/// Chat with Claude.
+/// Pass a `job_id` to make it cancellable with `cancel_job`.
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn claude_chat(
     model: String,
     messages: Vec<ChatMessageInput>,
     system: Option<String>,
     temperature: Option<f32>,
     max_tokens: Option<u32>,
+    profile: Option<String>,
+    job_id: Option<String>,
+    session: State<'_, SessionKeyState>,
+    jobs: State<'_, RunningJobs>,
 ) -> Result<String> {
-    let api_key = KeychainService::get_claude_key()?
-        .ok_or_else(|| crate::error::AppError::ProcessFailed("Claude API key not set".into()))?;
+    let job = jobs.start(job_id);
+    let api_key = require_api_key(&session, providers::CLAUDE, profile.as_deref())?;
 
     let service = ClaudeService::new(&api_key);
     let msgs: Vec<crate::services::claude::ClaudeMessage> = messages
@@ -234,19 +708,90 @@ pub async fn claude_chat(
         })
         .collect();
 
-    service
-        .message(&model, msgs, system.as_deref(), temperature, max_tokens.unwrap_or(1024))
-        .await
+    let request = service.message(
+        &model,
+        msgs,
+        system.as_deref(),
+        temperature,
+        max_tokens.unwrap_or(1024),
+    );
+    cancellable(job.token(), request).await
 }
 
-/// Summarize text using Claude
+/// Summarize text using Claude, with the
+/// built-in prompt or a saved prompt template.
+/// Pass a `job_id` to make it cancellable with `cancel_job`.
 #[tauri::command]
-pub async fn claude_summarize(text: String, language: String, model: String) -> Result<String> {
-    let api_key = KeychainService::get_claude_key()?
-        .ok_or_else(|| crate::error::AppError::ProcessFailed("Claude API key not set".into()))?;
+#[allow(clippy::too_many_arguments)]
+pub async fn claude_summarize(
+    text: String,
+    language: String,
+    model: String,
+    profile: Option<String>,
+    template_id: Option<i64>,
+    job_id: Option<String>,
+    session: State<'_, SessionKeyState>,
+    jobs: State<'_, RunningJobs>,
+) -> Result<String> {
+    let job = jobs.start(job_id);
+    if let Some(template_id) = template_id {
+        let llm = llm_provider(providers::CLAUDE, &model, profile.as_deref(), &session)?;
+        return summarize_with_template(llm.as_ref(), template_id, &text, &language, job.token())
+            .await;
+    }
+    let api_key = require_api_key(&session, providers::CLAUDE, profile.as_deref())?;
 
     let service = ClaudeService::new(&api_key);
-    service.summarize(&model, &text, &language).await
+    let request = service.summarize(&model, &text, &language);
+    cancellable(job.token(), request).await
+}
+
+/// Extract story order from transcription segments using Claude.
+/// Pass a `job_id` to make it cancellable with `cancel_job`.
+#[tauri::command]
+pub async fn claude_extract_story_order(
+    model: String,
+    segments: Vec<crate::services::TranscriptionSegment>,
+    profile: Option<String>,
+    job_id: Option<String>,
+    session: State<'_, SessionKeyState>,
+    jobs: State<'_, RunningJobs>,
+) -> Result<Vec<StorySegment>> {
+    let job = jobs.start(job_id);
+    let api_key = require_api_key(&session, providers::CLAUDE, profile.as_deref())?;
+
+    let service = ClaudeService::new(&api_key);
+    let request = service.extract_story_order(&model, &segments);
+    cancellable(job.token(), request).await
+}
+
+/// Generate a YouTube chapter list from transcription segments using Claude, with the
+/// built-in prompt or a saved prompt template.
+/// Pass a `job_id` to make it cancellable with `cancel_job`.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn claude_generate_youtube_chapters(
+    model: String,
+    segments: Vec<crate::services::TranscriptionSegment>,
+    language: String,
+    profile: Option<String>,
+    template_id: Option<i64>,
+    job_id: Option<String>,
+    session: State<'_, SessionKeyState>,
+    jobs: State<'_, RunningJobs>,
+) -> Result<YouTubeChapters> {
+    let job = jobs.start(job_id);
+    if let Some(template_id) = template_id {
+        let llm = llm_provider(providers::CLAUDE, &model, profile.as_deref(), &session)?;
+        return chapters_with_template(llm.as_ref(), template_id, &segments, &language, job.token())
+            .await;
+    }
+    let api_key = require_api_key(&session, providers::CLAUDE, profile.as_deref())?;
+
+    let service = ClaudeService::new(&api_key);
+    let request = service.generate_chapters(&model, &segments, &language);
+    let chapters = cancellable(job.token(), request).await?;
+    Ok(YouTubeChapters::new(chapters))
 }
 
 /// Get available Claude models (static list)
@@ -257,9 +802,11 @@ pub fn get_claude_models() -> Vec<ClaudeModel> {
 
 /// Fetch available Claude models from API (dynamic, sorted by newest)
 #[tauri::command]
-pub async fn fetch_claude_models() -> Result<Vec<ClaudeModel>> {
-    let api_key = KeychainService::get_claude_key()?
-        .ok_or_else(|| crate::error::AppError::ProcessFailed("Claude API key not set".into()))?;
+pub async fn fetch_claude_models(
+    profile: Option<String>,
+    session: State<'_, SessionKeyState>,
+) -> Result<Vec<ClaudeModel>> {
+    let api_key = require_api_key(&session, providers::CLAUDE, profile.as_deref())?;
 
     let service = ClaudeService::new(&api_key);
     service.fetch_models().await
@@ -269,10 +816,543 @@ pub async fn fetch_claude_models() -> Result<Vec<ClaudeModel>> {
 /// Used when fetching immediately after storing to avoid keychain sync delays
 #[tauri::command]
 pub async fn fetch_claude_models_direct(api_key: String) -> Result<Vec<ClaudeModel>> {
+    redact::register_secret(&api_key);
     let service = ClaudeService::new(&api_key);
     service.fetch_models().await
 }
 
+// ============================================================================
+// Gemini Commands
+// ============================================================================
+
+/// Validate Gemini API key (from keychain)
+#[tauri::command]
+pub async fn validate_gemini_key(
+    profile: Option<String>,
+    session: State<'_, SessionKeyState>,
+) -> Result<bool> {
+    let api_key = require_api_key(&session, providers::GEMINI, profile.as_deref())?;
+
+    let service = GeminiService::new(&api_key);
+    service.validate_api_key().await
+}
+
+/// Validate Gemini API key directly (bypasses keychain lookup)
+/// Used when validating immediately after storing to avoid keychain sync delays
+#[tauri::command]
+pub async fn validate_gemini_key_direct(api_key: String) -> Result<bool> {
+    redact::register_secret(&api_key);
+    let service = GeminiService::new(&api_key);
+    service.validate_api_key().await
+}
+
+/// Chat with Gemini.
+/// Pass a `job_id` to make it cancellable with `cancel_job`.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn gemini_chat(
+    model: String,
+    messages: Vec<ChatMessageInput>,
+    system: Option<String>,
+    temperature: Option<f32>,
+    max_tokens: Option<u32>,
+    profile: Option<String>,
+    job_id: Option<String>,
+    session: State<'_, SessionKeyState>,
+    jobs: State<'_, RunningJobs>,
+) -> Result<String> {
+    let job = jobs.start(job_id);
+    let api_key = require_api_key(&session, providers::GEMINI, profile.as_deref())?;
+
+    let service = GeminiService::new(&api_key);
+    let msgs: Vec<GeminiMessage> = messages
+        .into_iter()
+        .map(|m| GeminiMessage {
+            role: m.role,
+            content: m.content,
+        })
+        .collect();
+
+    let request = service.chat(
+        &model,
+        msgs,
+        system.as_deref(),
+        temperature,
+        max_tokens.unwrap_or(1024),
+    );
+    cancellable(job.token(), request).await
+}
+
+/// Summarize text using Gemini, with the
+/// built-in prompt or a saved prompt template.
+/// Pass a `job_id` to make it cancellable with `cancel_job`.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn gemini_summarize(
+    text: String,
+    language: String,
+    model: String,
+    profile: Option<String>,
+    template_id: Option<i64>,
+    job_id: Option<String>,
+    session: State<'_, SessionKeyState>,
+    jobs: State<'_, RunningJobs>,
+) -> Result<String> {
+    let job = jobs.start(job_id);
+    if let Some(template_id) = template_id {
+        let llm = llm_provider(providers::GEMINI, &model, profile.as_deref(), &session)?;
+        return summarize_with_template(llm.as_ref(), template_id, &text, &language, job.token())
+            .await;
+    }
+    let api_key = require_api_key(&session, providers::GEMINI, profile.as_deref())?;
+
+    let service = GeminiService::new(&api_key);
+    let request = service.summarize(&model, &text, &language);
+    cancellable(job.token(), request).await
+}
+
+/// Summarize an audio or video file directly with Gemini's media understanding,
+/// without transcribing it first.
+/// Pass a `job_id` to make it cancellable with `cancel_job`.
+#[tauri::command]
+pub async fn gemini_summarize_media(
+    media_path: String,
+    language: String,
+    model: String,
+    profile: Option<String>,
+    job_id: Option<String>,
+    session: State<'_, SessionKeyState>,
+    jobs: State<'_, RunningJobs>,
+) -> Result<String> {
+    let job = jobs.start(job_id);
+    let api_key = require_api_key(&session, providers::GEMINI, profile.as_deref())?;
+
+    let service = GeminiService::new(&api_key);
+    let summarize = service.summarize_media(&model, Path::new(&media_path), &language);
+    cancellable(job.token(), usage::for_media(&media_path, summarize)).await
+}
+
+/// Extract story order from transcription segments using Gemini.
+/// Pass a `job_id` to make it cancellable with `cancel_job`.
+#[tauri::command]
+pub async fn gemini_extract_story_order(
+    model: String,
+    segments: Vec<crate::services::TranscriptionSegment>,
+    profile: Option<String>,
+    job_id: Option<String>,
+    session: State<'_, SessionKeyState>,
+    jobs: State<'_, RunningJobs>,
+) -> Result<Vec<StorySegment>> {
+    let job = jobs.start(job_id);
+    let api_key = require_api_key(&session, providers::GEMINI, profile.as_deref())?;
+
+    let service = GeminiService::new(&api_key);
+    let request = service.extract_story_order(&model, &segments);
+    cancellable(job.token(), request).await
+}
+
+/// Generate a YouTube chapter list from transcription segments using Gemini, with the
+/// built-in prompt or a saved prompt template.
+/// Pass a `job_id` to make it cancellable with `cancel_job`.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn gemini_generate_youtube_chapters(
+    model: String,
+    segments: Vec<crate::services::TranscriptionSegment>,
+    language: String,
+    profile: Option<String>,
+    template_id: Option<i64>,
+    job_id: Option<String>,
+    session: State<'_, SessionKeyState>,
+    jobs: State<'_, RunningJobs>,
+) -> Result<YouTubeChapters> {
+    let job = jobs.start(job_id);
+    if let Some(template_id) = template_id {
+        let llm = llm_provider(providers::GEMINI, &model, profile.as_deref(), &session)?;
+        return chapters_with_template(llm.as_ref(), template_id, &segments, &language, job.token())
+            .await;
+    }
+    let api_key = require_api_key(&session, providers::GEMINI, profile.as_deref())?;
+
+    let service = GeminiService::new(&api_key);
+    let request = service.generate_chapters(&model, &segments, &language);
+    let chapters = cancellable(job.token(), request).await?;
+    Ok(YouTubeChapters::new(chapters))
+}
+
+/// Get available Gemini models (static list)
+#[tauri::command]
+pub fn get_gemini_models() -> Vec<GeminiModel> {
+    GeminiService::available_models()
+}
+
+/// Fetch available Gemini models from API (dynamic)
+#[tauri::command]
+pub async fn fetch_gemini_models(
+    profile: Option<String>,
+    session: State<'_, SessionKeyState>,
+) -> Result<Vec<GeminiModel>> {
+    let api_key = require_api_key(&session, providers::GEMINI, profile.as_deref())?;
+
+    let service = GeminiService::new(&api_key);
+    service.fetch_models().await
+}
+
+/// Fetch available Gemini models from API directly (bypasses keychain lookup)
+/// Used when fetching immediately after storing to avoid keychain sync delays
+#[tauri::command]
+pub async fn fetch_gemini_models_direct(api_key: String) -> Result<Vec<GeminiModel>> {
+    redact::register_secret(&api_key);
+    let service = GeminiService::new(&api_key);
+    service.fetch_models().await
+}
+
+// ============================================================================
+// Groq Commands
+// ============================================================================
+
+/// Validate Groq API key (from keychain)
+#[tauri::command]
+pub async fn validate_groq_key(
+    profile: Option<String>,
+    session: State<'_, SessionKeyState>,
+) -> Result<bool> {
+    let api_key = require_api_key(&session, providers::GROQ, profile.as_deref())?;
+
+    let service = GroqService::new(&api_key);
+    service.validate_api_key().await
+}
+
+/// Validate Groq API key directly (bypasses keychain lookup)
+/// Used when validating immediately after storing to avoid keychain sync delays
+#[tauri::command]
+pub async fn validate_groq_key_direct(api_key: String) -> Result<bool> {
+    redact::register_secret(&api_key);
+    let service = GroqService::new(&api_key);
+    service.validate_api_key().await
+}
+
+/// Chat with a Groq-hosted model.
+/// Pass a `job_id` to make it cancellable with `cancel_job`.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn groq_chat(
+    model: String,
+    messages: Vec<ChatMessageInput>,
+    temperature: Option<f32>,
+    max_tokens: Option<u32>,
+    profile: Option<String>,
+    job_id: Option<String>,
+    session: State<'_, SessionKeyState>,
+    jobs: State<'_, RunningJobs>,
+) -> Result<String> {
+    let job = jobs.start(job_id);
+    let api_key = hosted_api_key(&session, providers::GROQ, profile.as_deref())?;
+
+    let service = GroqService::new(&api_key);
+    let msgs: Vec<crate::services::openai::ChatMessage> = messages
+        .into_iter()
+        .map(|m| crate::services::openai::ChatMessage {
+            role: m.role,
+            content: m.content,
+        })
+        .collect();
+
+    let request = service.chat(&model, msgs, temperature, max_tokens);
+    cancellable(job.token(), request).await
+}
+
+/// Summarize text using a Groq-hosted model, with the
+/// built-in prompt or a saved prompt template.
+/// Pass a `job_id` to make it cancellable with `cancel_job`.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn groq_summarize(
+    text: String,
+    language: String,
+    model: String,
+    profile: Option<String>,
+    template_id: Option<i64>,
+    job_id: Option<String>,
+    session: State<'_, SessionKeyState>,
+    jobs: State<'_, RunningJobs>,
+) -> Result<String> {
+    let job = jobs.start(job_id);
+    if let Some(template_id) = template_id {
+        let llm = llm_provider(providers::GROQ, &model, profile.as_deref(), &session)?;
+        return summarize_with_template(llm.as_ref(), template_id, &text, &language, job.token())
+            .await;
+    }
+    let api_key = hosted_api_key(&session, providers::GROQ, profile.as_deref())?;
+
+    let service = GroqService::new(&api_key);
+    let request = service.summarize(&model, &text, &language);
+    cancellable(job.token(), request).await
+}
+
+/// Extract story order from transcription segments using a Groq-hosted model.
+/// Pass a `job_id` to make it cancellable with `cancel_job`.
+#[tauri::command]
+pub async fn groq_extract_story_order(
+    model: String,
+    segments: Vec<crate::services::TranscriptionSegment>,
+    profile: Option<String>,
+    job_id: Option<String>,
+    session: State<'_, SessionKeyState>,
+    jobs: State<'_, RunningJobs>,
+) -> Result<Vec<StorySegment>> {
+    let job = jobs.start(job_id);
+    let api_key = hosted_api_key(&session, providers::GROQ, profile.as_deref())?;
+
+    let service = GroqService::new(&api_key);
+    let request = service.extract_story_order(&model, &segments);
+    cancellable(job.token(), request).await
+}
+
+/// Generate a YouTube chapter list from transcription segments using a Groq-hosted model, with the
+/// built-in prompt or a saved prompt template.
+/// Pass a `job_id` to make it cancellable with `cancel_job`.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn groq_generate_youtube_chapters(
+    model: String,
+    segments: Vec<crate::services::TranscriptionSegment>,
+    language: String,
+    profile: Option<String>,
+    template_id: Option<i64>,
+    job_id: Option<String>,
+    session: State<'_, SessionKeyState>,
+    jobs: State<'_, RunningJobs>,
+) -> Result<YouTubeChapters> {
+    let job = jobs.start(job_id);
+    if let Some(template_id) = template_id {
+        let llm = llm_provider(providers::GROQ, &model, profile.as_deref(), &session)?;
+        return chapters_with_template(llm.as_ref(), template_id, &segments, &language, job.token())
+            .await;
+    }
+    let api_key = hosted_api_key(&session, providers::GROQ, profile.as_deref())?;
+
+    let service = GroqService::new(&api_key);
+    let request = service.generate_chapters(&model, &segments, &language);
+    let chapters = cancellable(job.token(), request).await?;
+    Ok(YouTubeChapters::new(chapters))
+}
+
+/// Get available Groq chat models (static list)
+#[tauri::command]
+pub fn get_groq_models() -> Vec<OpenAIModel> {
+    GroqService::available_models()
+}
+
+/// Get Groq transcription models, for `transcribe_media` with the `groq` provider
+#[tauri::command]
+pub fn get_groq_transcription_models() -> Vec<OpenAIModel> {
+    GroqService::transcription_models()
+}
+
+/// Fetch available Groq chat models from API (dynamic, sorted by newest)
+#[tauri::command]
+pub async fn fetch_groq_models(
+    profile: Option<String>,
+    session: State<'_, SessionKeyState>,
+) -> Result<Vec<OpenAIModel>> {
+    let api_key = hosted_api_key(&session, providers::GROQ, profile.as_deref())?;
+
+    let service = GroqService::new(&api_key);
+    service.fetch_models().await
+}
+
+/// Fetch available Groq chat models from API directly (bypasses keychain lookup)
+/// Used when fetching immediately after storing to avoid keychain sync delays
+#[tauri::command]
+pub async fn fetch_groq_models_direct(api_key: String) -> Result<Vec<OpenAIModel>> {
+    redact::register_secret(&api_key);
+    let service = GroqService::new(&api_key);
+    service.fetch_models().await
+}
+
+// ============================================================================
+// OpenAI-compatible Server Commands
+// ============================================================================
+
+/// Connect to the self-hosted OpenAI-compatible server.
+/// Uses `base_url` when given (to try a server before saving it), otherwise the
+/// URL from the app settings. The API key is optional for these servers.
+fn openai_compatible_service(
+    base_url: Option<String>,
+    profile: Option<&str>,
+    session: &SessionKeyState,
+) -> Result<OpenAICompatibleService> {
+    let (base_url, api_key) = openai_compatible_server(base_url, profile, session)?;
+    OpenAICompatibleService::new(&base_url, api_key.as_deref())
+}
+
+/// Check if the OpenAI-compatible server is reachable
+#[tauri::command]
+pub async fn check_openai_compatible_server(
+    base_url: Option<String>,
+    profile: Option<String>,
+    session: State<'_, SessionKeyState>,
+) -> Result<bool> {
+    let service = openai_compatible_service(base_url, profile.as_deref(), &session)?;
+    Ok(service.is_available().await)
+}
+
+/// Chat with a model on the OpenAI-compatible server.
+/// Pass a `job_id` to make it cancellable with `cancel_job`.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn openai_compatible_chat(
+    model: String,
+    messages: Vec<ChatMessageInput>,
+    temperature: Option<f32>,
+    max_tokens: Option<u32>,
+    profile: Option<String>,
+    job_id: Option<String>,
+    session: State<'_, SessionKeyState>,
+    jobs: State<'_, RunningJobs>,
+) -> Result<String> {
+    let job = jobs.start(job_id);
+    let service = openai_compatible_service(None, profile.as_deref(), &session)?;
+    let msgs: Vec<crate::services::openai::ChatMessage> = messages
+        .into_iter()
+        .map(|m| crate::services::openai::ChatMessage {
+            role: m.role,
+            content: m.content,
+        })
+        .collect();
+
+    let request = service.chat(&model, msgs, temperature, max_tokens);
+    cancellable(job.token(), request).await
+}
+
+/// Summarize text using a model on the OpenAI-compatible server, with the
+/// built-in prompt or a saved prompt template.
+/// Pass a `job_id` to make it cancellable with `cancel_job`.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn openai_compatible_summarize(
+    text: String,
+    language: String,
+    model: String,
+    profile: Option<String>,
+    template_id: Option<i64>,
+    job_id: Option<String>,
+    session: State<'_, SessionKeyState>,
+    jobs: State<'_, RunningJobs>,
+) -> Result<String> {
+    let job = jobs.start(job_id);
+    if let Some(template_id) = template_id {
+        let llm = llm_provider(providers::OPENAI_COMPATIBLE, &model, profile.as_deref(), &session)?;
+        return summarize_with_template(llm.as_ref(), template_id, &text, &language, job.token())
+            .await;
+    }
+    let service = openai_compatible_service(None, profile.as_deref(), &session)?;
+    let request = service.summarize(&model, &text, &language);
+    cancellable(job.token(), request).await
+}
+
+/// Extract story order from transcription segments using the OpenAI-compatible server.
+/// Pass a `job_id` to make it cancellable with `cancel_job`.
+#[tauri::command]
+pub async fn openai_compatible_extract_story_order(
+    model: String,
+    segments: Vec<crate::services::TranscriptionSegment>,
+    profile: Option<String>,
+    job_id: Option<String>,
+    session: State<'_, SessionKeyState>,
+    jobs: State<'_, RunningJobs>,
+) -> Result<Vec<StorySegment>> {
+    let job = jobs.start(job_id);
+    let service = openai_compatible_service(None, profile.as_deref(), &session)?;
+    let request = service.extract_story_order(&model, &segments);
+    cancellable(job.token(), request).await
+}
+
+/// Generate a YouTube chapter list using the OpenAI-compatible server, with the
+/// built-in prompt or a saved prompt template.
+/// Pass a `job_id` to make it cancellable with `cancel_job`.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn openai_compatible_generate_youtube_chapters(
+    model: String,
+    segments: Vec<crate::services::TranscriptionSegment>,
+    language: String,
+    profile: Option<String>,
+    template_id: Option<i64>,
+    job_id: Option<String>,
+    session: State<'_, SessionKeyState>,
+    jobs: State<'_, RunningJobs>,
+) -> Result<YouTubeChapters> {
+    let job = jobs.start(job_id);
+    if let Some(template_id) = template_id {
+        let llm = llm_provider(providers::OPENAI_COMPATIBLE, &model, profile.as_deref(), &session)?;
+        return chapters_with_template(llm.as_ref(), template_id, &segments, &language, job.token())
+            .await;
+    }
+    let service = openai_compatible_service(None, profile.as_deref(), &session)?;
+    let request = service.generate_chapters(&model, &segments, &language);
+    let chapters = cancellable(job.token(), request).await?;
+    Ok(YouTubeChapters::new(chapters))
+}
+
+/// Fetch the models the OpenAI-compatible server can serve
+#[tauri::command]
+pub async fn fetch_openai_compatible_models(
+    base_url: Option<String>,
+    profile: Option<String>,
+    session: State<'_, SessionKeyState>,
+) -> Result<Vec<OpenAIModel>> {
+    let service = openai_compatible_service(base_url, profile.as_deref(), &session)?;
+    service.list_models().await
+}
+
+// ============================================================================
+// AssemblyAI Commands
+// ============================================================================
+
+/// Transcribe an audio or video file with AssemblyAI, optionally with speaker
+/// labels, chapters, entity detection and sentiment analysis.
+/// Cancel it with `cancel_transcription` and the given `job_id`, or the
+/// generated one its progress events carry.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn assemblyai_transcribe(
+    app: AppHandle,
+    media_path: String,
+    language: Option<String>,
+    features: Option<AssemblyAIFeatures>,
+    profile: Option<String>,
+    job_id: Option<String>,
+    session: State<'_, SessionKeyState>,
+    jobs: State<'_, RunningJobs>,
+) -> Result<AssemblyAITranscription> {
+    let api_key = require_api_key(&session, providers::ASSEMBLYAI, profile.as_deref())?;
+    let job = jobs.start_with_id(job_id);
+    let progress_job_id = job.id().map(str::to_string);
+
+    let service = AssemblyAIService::new(&api_key);
+    let emitter = ThrottledEmitter::new(&app, "transcription:progress");
+    let transcribe = service.transcribe(
+        Path::new(&media_path),
+        language.as_deref(),
+        features.unwrap_or_default(),
+        job.token(),
+        Box::new(move |progress| {
+            emitter.emit(TranscriptionProgress {
+                stage: "transcribing".to_string(),
+                progress,
+                message: "Transcribing with AssemblyAI...".to_string(),
+                job_id: progress_job_id.clone(),
+            });
+        }),
+    );
+    let result = usage::for_media(&media_path, transcribe).await?;
+
+    record_transcription(&media_path, &result.transcription, providers::ASSEMBLYAI);
+    Ok(result)
+}
+
 // ============================================================================
 // Shared Types
 // ============================================================================
@@ -297,4 +1377,42 @@ pub struct TranscriptionSegment {
     pub start: f64,
     pub end: f64,
     pub text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_keys() {
+        let session = SessionKeyState::default();
+        assert!(!session.contains(providers::OPENAI));
+
+        session.set(providers::OPENAI, "sk-first");
+        session.set(providers::OPENAI, "sk-second");
+        assert!(session.contains(providers::OPENAI));
+        assert_eq!(session.get(providers::OPENAI).as_deref(), Some("sk-second"));
+        assert_eq!(session.get(providers::CLAUDE), None);
+
+        session.remove(providers::OPENAI);
+        assert!(!session.contains(providers::OPENAI));
+        assert_eq!(session.get(providers::OPENAI), None);
+    }
+
+    #[test]
+    fn test_session_key_is_used_before_keychain() {
+        let session = SessionKeyState::default();
+        session.set(providers::GROQ, "gsk_session");
+        let key = require_api_key(&session, providers::GROQ, None).unwrap();
+        assert_eq!(key, "gsk_session");
+        assert!(optional_api_key(&session, "not-a-provider", None).is_err());
+    }
+
+    #[test]
+    fn test_mask_key() {
+        assert_eq!(mask_key("sk-abcdefgh1234"), "sk-a...1234");
+        assert_eq!(mask_key("abcd"), "****");
+    }
 }
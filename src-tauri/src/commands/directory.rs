@@ -1,134 +1,777 @@
+use crate::commands::scope::scoped_path;
+use crate::error::{AppError, Result};
 use crate::services::directory_service::{
-    scan_directory, scan_directory_tree, DirectoryNode, FileEntry, FileEvent,
+    apply_scan_options, filter_excluded, library_stats, list_directory_children,
+    recent_media_files, rescan_directory_monitored, rewatch_delay, scan_directory_tree_monitored,
+    DirectoryNode, DirectoryPage, FileEntry, FileEvent, FileEventBatcher, LibraryStats,
+    RecentKind, ScanMonitor, ScanOptions, ScanProgress, WATCH_HEALTH_CHECK_INTERVAL,
+    WATCH_MAX_BATCH_DELAY, WATCH_QUIET_PERIOD,
 };
-use notify::{Config, Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
-use std::path::PathBuf;
-use std::sync::Mutex;
-use tauri::{AppHandle, Emitter, State};
+use crate::services::app_settings::AppSettings;
+use crate::services::database::Database;
+use crate::services::file_ops;
+use crate::services::media_probe::enrich_with_media_info;
+use crate::services::scan_index::{IndexedDir, RescanResult, ScanIndex};
+use crate::services::thumbnail::ThumbnailCache;
+use crate::services::volume;
+use notify::event::{ModifyKind, RenameMode};
+use serde::Serialize;
+use notify::{Config, Event, EventKind, PollWatcher, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use parking_lot::{Mutex, RwLock};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
 
-/// Global state for the file watcher
+/// Global state for the file watcher.
+/// The locks don't poison, so a panic in a watcher callback can't lock every
+/// directory command out, and no lock is held across a watcher being created
+/// or dropped (which can take a while on big trees).
 pub struct WatcherState {
-    watcher: Mutex<Option<RecommendedWatcher>>,
-    watched_path: Mutex<Option<String>>,
+    /// Native watcher, or a polling one for network and exFAT volumes
+    watcher: Mutex<Option<Box<dyn Watcher + Send>>>,
+    watched_path: RwLock<Option<String>>,
+    /// Subdirectories of the watched path whose changes are not reported
+    exclusions: Arc<RwLock<Vec<PathBuf>>>,
+    /// Bumped by every start and stop, so the supervisor of an older watch
+    /// knows to exit
+    generation: AtomicU64,
 }
 
 impl Default for WatcherState {
     fn default() -> Self {
         Self {
             watcher: Mutex::new(None),
-            watched_path: Mutex::new(None),
+            watched_path: RwLock::new(None),
+            exclusions: Arc::new(RwLock::new(Vec::new())),
+            generation: AtomicU64::new(0),
         }
     }
 }
 
-/// Scan directory and return flat list of media files
+impl WatcherState {
+    /// The directory currently being watched, if any
+    pub(crate) fn watched_path(&self) -> Option<String> {
+        self.watched_path.read().clone()
+    }
+
+    /// Swap in a new watcher (or none), returning the old one to drop
+    fn replace_watcher(
+        &self,
+        watcher: Option<Box<dyn Watcher + Send>>,
+    ) -> Option<Box<dyn Watcher + Send>> {
+        std::mem::replace(&mut *self.watcher.lock(), watcher)
+    }
+}
+
+/// Drop a watcher off the async runtime; native watchers join their event
+/// thread when dropped
+fn drop_watcher(watcher: Option<Box<dyn Watcher + Send>>) {
+    if let Some(watcher) = watcher {
+        tokio::task::spawn_blocking(move || drop(watcher));
+    }
+}
+
+/// Cancellation flags of running scans, keyed by the caller-chosen scan id,
+/// and the lock of the scan index they update
+#[derive(Default)]
+pub struct ScanState {
+    scans: Mutex<HashMap<String, Arc<AtomicBool>>>,
+    /// Held while the scan index is read or written, so scans of different
+    /// roots finishing together do not drop each other's entries
+    index: Mutex<()>,
+}
+
+impl ScanState {
+    fn register(&self, scan_id: &str) -> Arc<AtomicBool> {
+        let flag = Arc::new(AtomicBool::new(false));
+        let mut scans = self.scans.lock();
+        scans.insert(scan_id.to_string(), Arc::clone(&flag));
+        flag
+    }
+
+    fn unregister(&self, scan_id: &str) {
+        let mut scans = self.scans.lock();
+        scans.remove(scan_id);
+    }
+
+    pub(crate) fn cancel(&self, scan_id: &str) -> bool {
+        let scans = self.scans.lock();
+        scans
+            .get(scan_id)
+            .map(|flag| flag.store(true, Ordering::Relaxed))
+            .is_some()
+    }
+
+    /// Directories of `root` indexed by its last scan
+    fn indexed_dirs(&self, root: &str) -> Result<HashMap<String, IndexedDir>> {
+        let _index = self.index.lock();
+        Ok(ScanIndex::load()?.dirs(root))
+    }
+
+    /// Load the scan index, apply `update` and save it again
+    fn update_index<T>(&self, update: impl FnOnce(&mut ScanIndex) -> T) -> Result<T> {
+        let _index = self.index.lock();
+        let mut index = ScanIndex::load()?;
+        let result = update(&mut index);
+        index.save()?;
+        Ok(result)
+    }
+}
+
+/// `scan:progress` event payload
+#[derive(Clone, Serialize)]
+struct ScanProgressEvent {
+    scan_id: Option<String>,
+    #[serde(flatten)]
+    progress: ScanProgress,
+}
+
+/// Run a directory walk off the async runtime, so big folders do not stall it
+async fn blocking<T: Send + 'static>(
+    work: impl FnOnce() -> Result<T> + Send + 'static,
+) -> Result<T> {
+    tokio::task::spawn_blocking(work)
+        .await
+        .map_err(|e| AppError::ProcessFailed(format!("Scan task failed: {}", e)))?
+}
+
+/// Run a scan that emits `scan:progress` events and, when it has an id,
+/// can be stopped with `cancel_scan`
+async fn run_scan<T: Send + 'static>(
+    app: &AppHandle,
+    scans: &ScanState,
+    scan_id: Option<String>,
+    scan: impl FnOnce(&mut ScanMonitor) -> Result<T> + Send + 'static,
+) -> Result<T> {
+    let cancelled = scan_id.as_deref().map(|id| scans.register(id)).unwrap_or_default();
+
+    let result = {
+        let app = app.clone();
+        let scan_id = scan_id.clone();
+        blocking(move || {
+            let mut monitor = ScanMonitor::new(&cancelled, |progress| {
+                let event = ScanProgressEvent {
+                    scan_id: scan_id.clone(),
+                    progress,
+                };
+                let _ = app.emit("scan:progress", event);
+            });
+            scan(&mut monitor)
+        })
+        .await
+    };
+
+    if let Some(id) = &scan_id {
+        scans.unregister(id);
+    }
+    result
+}
+
+/// Resolve exclusions relative to the watched directory
+fn resolve_exclusions(root: &Path, exclude: Vec<String>) -> Vec<PathBuf> {
+    exclude
+        .into_iter()
+        .filter(|p| !p.trim().is_empty())
+        .map(|p| root.join(p))
+        .collect()
+}
+
+/// Scan directory and return flat list of media files,
+/// optionally filtered, sorted and enriched with duration/audio info
+#[tauri::command]
+pub async fn scan_media_directory(
+    app: AppHandle,
+    path: String,
+    options: Option<ScanOptions>,
+    max_depth: Option<usize>,
+    scan_id: Option<String>,
+    scans: State<'_, ScanState>,
+) -> Result<Vec<FileEntry>> {
+    let root = PathBuf::from(&path);
+    // Directories unchanged since the last scan are not read again
+    let known = scans.indexed_dirs(&path).unwrap_or_else(|e| {
+        log::warn!("[scan_media_directory] Failed to load scan index: {}", e);
+        HashMap::new()
+    });
+    let (mut files, dirs) = run_scan(&app, &scans, scan_id, move |monitor| {
+        rescan_directory_monitored(&root, max_depth, &known, monitor)
+    })
+    .await?;
+
+    // Seed the index so a later rescan only reports differences
+    let indexed = scans.update_index(|index| {
+        index.update(&path, &files);
+        index.set_dirs(&path, dirs);
+    });
+    if let Err(e) = indexed {
+        log::warn!("[scan_media_directory] Failed to update scan index: {}", e);
+    }
+
+    let Some(options) = options else {
+        return Ok(files);
+    };
+
+    // An empty tag list filters nothing, like no list
+    let tags = options.tags.as_deref().unwrap_or_default();
+    if !tags.is_empty() || options.collection_id.is_some() {
+        let matching = Database::open()?.matching_paths(tags, options.collection_id)?;
+        files.retain(|f| matching.contains(&f.path));
+    }
+
+    if options.with_media_info {
+        if let Err(e) = enrich_with_media_info(&mut files).await {
+            log::warn!("[scan_media_directory] Failed to probe media info: {}", e);
+        }
+    }
+
+    Ok(apply_scan_options(files, &options))
+}
+
+/// List the direct children of a directory, optionally one page at a time
 #[tauri::command]
-pub async fn scan_media_directory(path: String) -> Result<Vec<FileEntry>, String> {
+pub async fn scan_directory_children(
+    path: String,
+    offset: Option<usize>,
+    limit: Option<usize>,
+) -> Result<DirectoryPage> {
     let path = PathBuf::from(&path);
-    scan_directory(&path)
+    blocking(move || list_directory_children(&path, offset.unwrap_or(0), limit)).await
+}
+
+/// Get file count, total size, per-extension breakdown and date range of a library
+#[tauri::command]
+pub async fn get_library_stats(path: String) -> Result<LibraryStats> {
+    blocking(move || library_stats(&PathBuf::from(&path))).await
+}
+
+/// Number of files returned by the recent-files commands when no limit is given
+const DEFAULT_RECENT_LIMIT: usize = 50;
+
+/// Get the most recently added media files, newest first.
+/// Searches `paths` if given, otherwise the watched directory.
+#[tauri::command]
+pub async fn get_recently_added_files(
+    paths: Option<Vec<String>>,
+    limit: Option<usize>,
+    state: State<'_, WatcherState>,
+) -> Result<Vec<FileEntry>> {
+    let roots = recent_roots(paths, &state)?;
+    let limit = limit.unwrap_or(DEFAULT_RECENT_LIMIT);
+    blocking(move || recent_media_files(&roots, RecentKind::Added, limit)).await
+}
+
+/// Get the most recently modified media files, newest first.
+/// Searches `paths` if given, otherwise the watched directory.
+#[tauri::command]
+pub async fn get_recently_modified_files(
+    paths: Option<Vec<String>>,
+    limit: Option<usize>,
+    state: State<'_, WatcherState>,
+) -> Result<Vec<FileEntry>> {
+    let roots = recent_roots(paths, &state)?;
+    let limit = limit.unwrap_or(DEFAULT_RECENT_LIMIT);
+    blocking(move || recent_media_files(&roots, RecentKind::Modified, limit)).await
+}
+
+fn recent_roots(paths: Option<Vec<String>>, state: &WatcherState) -> Result<Vec<PathBuf>> {
+    if let Some(paths) = paths.filter(|p| !p.is_empty()) {
+        return Ok(paths.into_iter().map(PathBuf::from).collect());
+    }
+
+    state
+        .watched_path()
+        .map(|p| vec![PathBuf::from(p)])
+        .ok_or_else(|| AppError::InvalidInput("No directory is being watched".to_string()))
+}
+
+/// Rescan a directory and return only files added, changed or removed
+/// since the previous scan of the same directory
+#[tauri::command]
+pub async fn rescan_directory(
+    app: AppHandle,
+    path: String,
+    max_depth: Option<usize>,
+    scan_id: Option<String>,
+    scans: State<'_, ScanState>,
+) -> Result<RescanResult> {
+    let root = PathBuf::from(&path);
+    let known = scans.indexed_dirs(&path)?;
+    let (files, dirs) = run_scan(&app, &scans, scan_id, move |monitor| {
+        rescan_directory_monitored(&root, max_depth, &known, monitor)
+    })
+    .await?;
+
+    scans.update_index(|index| {
+        let result = index.diff_and_update(&path, &files);
+        index.set_dirs(&path, dirs);
+        result
+    })
 }
 
 /// Scan directory and return tree structure
 #[tauri::command]
-pub async fn scan_media_directory_tree(path: String) -> Result<DirectoryNode, String> {
+pub async fn scan_media_directory_tree(
+    app: AppHandle,
+    path: String,
+    max_depth: Option<usize>,
+    scan_id: Option<String>,
+    scans: State<'_, ScanState>,
+) -> Result<DirectoryNode> {
     let path = PathBuf::from(&path);
-    scan_directory_tree(&path)
+    run_scan(&app, &scans, scan_id, move |monitor| {
+        scan_directory_tree_monitored(&path, max_depth, monitor)
+    })
+    .await
+}
+
+/// Cancel a running scan started with the given `scan_id`.
+/// Returns false if no such scan is running.
+#[tauri::command]
+pub fn cancel_scan(scan_id: String, scans: State<'_, ScanState>) -> bool {
+    scans.cancel(&scan_id)
+}
+
+/// Move a media file into another directory, returning its new path
+#[tauri::command]
+pub async fn move_media_file(app: AppHandle, path: String, destination: String) -> Result<String> {
+    scoped_path(&app, &path)?;
+    scoped_path(&app, &destination)?;
+    let source = PathBuf::from(&path);
+    let moved = file_ops::move_file(&source, Path::new(&destination))?;
+    Ok(finish_relocation(&app, &source, &moved))
+}
+
+/// Rename a media file within its directory, returning its new path
+#[tauri::command]
+pub async fn rename_media_file(app: AppHandle, path: String, new_name: String) -> Result<String> {
+    scoped_path(&app, &path)?;
+    let source = PathBuf::from(&path);
+    let renamed = file_ops::rename_file(&source, &new_name)?;
+    Ok(finish_relocation(&app, &source, &renamed))
 }
 
-/// Start watching a directory for changes
+/// Delete a media file by moving it to the system trash
+#[tauri::command]
+pub async fn delete_media_file(app: AppHandle, path: String) -> Result<()> {
+    scoped_path(&app, &path)?;
+    let source = PathBuf::from(&path);
+    file_ops::trash_file(&source)?;
+
+    forget_cached(&source);
+    let indexed = app
+        .state::<ScanState>()
+        .update_index(|index| index.remove_path(&path));
+    if let Err(e) = indexed {
+        log::warn!("[delete_media_file] Failed to update scan index: {}", e);
+    }
+
+    let _ = app.emit("file-changes", vec![FileEvent::Removed(path)]);
+    Ok(())
+}
+
+/// Update the scan index, project database and thumbnails after a move or rename
+/// and report it.
+/// The event is emitted even outside the watched directory; a watcher that
+/// also sees the change just triggers another (idempotent) refresh.
+fn finish_relocation(app: &AppHandle, from: &Path, to: &Path) -> String {
+    let from = from.to_string_lossy().to_string();
+    let to = to.to_string_lossy().to_string();
+
+    forget_cached(Path::new(&from));
+    let indexed = app
+        .state::<ScanState>()
+        .update_index(|index| index.rename_path(&from, &to));
+    if let Err(e) = indexed {
+        log::warn!("[finish_relocation] Failed to update scan index: {}", e);
+    }
+    // Transcripts, tags and collections follow the file
+    if let Err(e) = Database::open().and_then(|db| db.rename_media_file(&from, &to)) {
+        log::warn!("[finish_relocation] Failed to update project database: {}", e);
+    }
+
+    let event = FileEvent::Renamed {
+        from,
+        to: to.clone(),
+    };
+    let _ = app.emit("file-changes", vec![event]);
+    to
+}
+
+/// Drop the cached thumbnail of a path that no longer exists
+fn forget_cached(path: &Path) {
+    if let Err(e) = ThumbnailCache::new().and_then(|cache| cache.invalidate(path)) {
+        log::warn!("[forget_cached] Failed to remove thumbnail: {}", e);
+    }
+}
+
+/// Start watching a directory for changes.
+/// `exclude` lists subdirectories (relative to `path`, or absolute) to ignore,
+/// such as a folder the app itself exports into.
+/// If the watch breaks later (watcher error, directory removed or unmounted),
+/// a `watcher:error` event is emitted and the directory is watched again with
+/// backoff; `watcher:recovered` follows once that succeeds.
 #[tauri::command]
 pub async fn start_watching_directory(
     app: AppHandle,
     path: String,
+    exclude: Option<Vec<String>>,
     state: State<'_, WatcherState>,
-) -> Result<(), String> {
+) -> Result<()> {
     let watch_path = PathBuf::from(&path);
 
     if !watch_path.exists() {
-        return Err(format!("Directory does not exist: {}", path));
+        return Err(AppError::InvalidPath(format!("Directory does not exist: {}", path)));
     }
 
-    // Stop any existing watcher
+    // Stop any existing watcher and its supervisor
+    let generation = state.generation.fetch_add(1, Ordering::SeqCst) + 1;
+    drop_watcher(state.replace_watcher(None));
+
+    *state.exclusions.write() = resolve_exclusions(&watch_path, exclude.unwrap_or_default());
+
+    let (failures_tx, failures_rx) = tokio::sync::mpsc::unbounded_channel();
+    let watcher = {
+        let app = app.clone();
+        let watch_path = watch_path.clone();
+        let exclusions = Arc::clone(&state.exclusions);
+        let failures_tx = failures_tx.clone();
+        blocking(move || create_watcher(&app, &watch_path, exclusions, failures_tx)).await?
+    };
+
+    // A start or stop that came in meanwhile wins
     {
-        let mut watcher_guard = state.watcher.lock().map_err(|e| e.to_string())?;
-        *watcher_guard = None;
+        let mut watcher_guard = state.watcher.lock();
+        if state.generation.load(Ordering::SeqCst) != generation {
+            drop(watcher_guard);
+            drop_watcher(Some(watcher));
+            return Ok(());
+        }
+        *watcher_guard = Some(watcher);
+        *state.watched_path.write() = Some(path);
     }
 
-    // Create new watcher
+    tauri::async_runtime::spawn(supervise_watcher(
+        app,
+        watch_path,
+        generation,
+        failures_tx,
+        failures_rx,
+    ));
+    Ok(())
+}
+
+/// Create a watcher for `watch_path` that reports changes as batched
+/// `file-changes` events and sends watcher errors and the removal of
+/// `watch_path` itself to `failures`
+fn create_watcher(
+    app: &AppHandle,
+    watch_path: &Path,
+    exclusions: Arc<RwLock<Vec<PathBuf>>>,
+    failures: UnboundedSender<String>,
+) -> Result<Box<dyn Watcher + Send>> {
     let app_handle = app.clone();
-    let watched_path_clone = path.clone();
-
-    let watcher = RecommendedWatcher::new(
-        move |res: Result<Event, notify::Error>| {
-            if let Ok(event) = res {
-                let file_events: Vec<FileEvent> = event
-                    .paths
-                    .iter()
-                    .filter_map(|p| {
-                        // Only emit events for supported media files
-                        if p.is_file()
-                            && !crate::services::directory_service::is_supported_media(p)
-                        {
-                            return None;
-                        }
+    let root = watch_path.to_path_buf();
+
+    // Raw events are forwarded to a batching thread; it exits once the
+    // watcher (and with it the sender) is dropped
+    let (tx, rx) = mpsc::channel::<FileEvent>();
+    std::thread::spawn(move || emit_batched_events(app_handle, rx, exclusions));
 
-                        let path_str = p.to_string_lossy().to_string();
+    // Renames may arrive as separate From/To events (always on Windows); pair them up.
+    // The old path is reported removed right away so files moved out of the
+    // library are not missed; the batcher folds it into the rename.
+    let mut pending_rename_from: Option<(PathBuf, Option<usize>)> = None;
 
-                        match event.kind {
-                            EventKind::Create(_) => Some(FileEvent::Created(path_str)),
-                            EventKind::Modify(_) => Some(FileEvent::Modified(path_str)),
-                            EventKind::Remove(_) => Some(FileEvent::Removed(path_str)),
-                            _ => None,
+    let handler = move |res: std::result::Result<Event, notify::Error>| {
+        let event = match res {
+            Ok(event) => event,
+            Err(e) => {
+                let _ = failures.send(e.to_string());
+                return;
+            }
+        };
+
+        if matches!(event.kind, EventKind::Remove(_)) && event.paths.contains(&root) {
+            let _ = failures.send("Watched directory was removed".to_string());
+            return;
+        }
+
+        if let EventKind::Modify(ModifyKind::Name(mode)) = event.kind {
+            let rename = match (mode, event.paths.as_slice()) {
+                (RenameMode::Both, [from, to]) => {
+                    pending_rename_from = None;
+                    FileEvent::rename(from, to)
+                }
+                (RenameMode::From, [from]) => {
+                    pending_rename_from = Some((from.clone(), event.attrs.tracker()));
+                    media_event(from, false)
+                }
+                (RenameMode::To, [to]) => match pending_rename_from.take() {
+                    Some((from, tracker)) if tracker == event.attrs.tracker() => {
+                        FileEvent::rename(&from, to)
+                    }
+                    _ => media_event(to, true),
+                },
+                // Unpaired rename (macOS): the path is either the old or the new name
+                (_, paths) => {
+                    for p in paths {
+                        if let Some(file_event) = media_event(p, p.exists()) {
+                            let _ = tx.send(file_event);
                         }
-                    })
-                    .collect();
+                    }
+                    None
+                }
+            };
+
+            if let Some(file_event) = rename {
+                let _ = tx.send(file_event);
+            }
+            return;
+        }
+        pending_rename_from = None;
+
+        for p in &event.paths {
+            // Only emit events for supported media files
+            if p.is_file() && !crate::services::directory_service::is_supported_media(p) {
+                continue;
+            }
+
+            let path_str = p.to_string_lossy().to_string();
+
+            let file_event = match event.kind {
+                EventKind::Create(_) => FileEvent::Created(path_str),
+                EventKind::Modify(_) => FileEvent::Modified(path_str),
+                EventKind::Remove(_) => FileEvent::Removed(path_str),
+                _ => continue,
+            };
+            let _ = tx.send(file_event);
+        }
+    };
 
-                for file_event in file_events {
-                    let _ = app_handle.emit("file-change", &file_event);
+    // Native notifications never fire for changes made through network
+    // shares (and are unreliable on exFAT), so those volumes are polled
+    let settings = AppSettings::load().unwrap_or_default();
+    let failed =
+        |e: notify::Error| AppError::ProcessFailed(format!("Failed to create watcher: {}", e));
+    let mut watcher: Box<dyn Watcher + Send> =
+        if settings.always_poll_watcher || volume::needs_polling(watch_path) {
+            let interval = Duration::from_secs(settings.watch_poll_interval_secs.max(1));
+            log::info!("[start_watching_directory] Polling {:?} every {:?}", watch_path, interval);
+            let config = Config::default().with_poll_interval(interval);
+            Box::new(PollWatcher::new(handler, config).map_err(failed)?)
+        } else {
+            Box::new(RecommendedWatcher::new(handler, Config::default()).map_err(failed)?)
+        };
+
+    watcher
+        .watch(watch_path, RecursiveMode::Recursive)
+        .map_err(|e| AppError::InvalidPath(format!("Failed to watch directory: {}", e)))?;
+    Ok(watcher)
+}
+
+/// Reported with the `watcher:error` event when a watch breaks
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct WatcherErrorEvent {
+    path: String,
+    message: String,
+    /// Seconds until the next attempt to watch the directory again
+    retry_in_secs: u64,
+}
+
+/// Watch for failures of the watch started as `generation` and re-create it
+/// with backoff until it works again. Exits once another directory is watched
+/// or watching is stopped.
+async fn supervise_watcher(
+    app: AppHandle,
+    root: PathBuf,
+    generation: u64,
+    failures_tx: UnboundedSender<String>,
+    mut failures: UnboundedReceiver<String>,
+) {
+    let state = app.state::<WatcherState>();
+    let is_current = || state.generation.load(Ordering::SeqCst) == generation;
+    let path = root.to_string_lossy().to_string();
+
+    loop {
+        let mut message = tokio::select! {
+            message = failures.recv() => match message {
+                Some(message) => message,
+                None => return,
+            },
+            _ = tokio::time::sleep(WATCH_HEALTH_CHECK_INTERVAL) => {
+                if tokio::fs::metadata(&root).await.is_ok_and(|m| m.is_dir()) {
+                    if !is_current() {
+                        return;
+                    }
+                    continue;
                 }
+                "Watched directory is no longer available".to_string()
             }
-        },
-        Config::default(),
-    )
-    .map_err(|e| format!("Failed to create watcher: {}", e))?;
+        };
+        if !is_current() {
+            return;
+        }
+        log::warn!("[watcher] Watch of {} failed: {}", path, message);
 
-    // Start watching
-    {
-        let mut watcher_guard = state.watcher.lock().map_err(|e| e.to_string())?;
+        // Drop the broken watcher; its batching thread flushes and exits
+        drop_watcher(state.replace_watcher(None));
 
-        let mut w = watcher;
-        w.watch(&watch_path, RecursiveMode::Recursive)
-            .map_err(|e| format!("Failed to watch directory: {}", e))?;
+        let mut attempt = 0;
+        loop {
+            let delay = rewatch_delay(attempt);
+            let event = WatcherErrorEvent {
+                path: path.clone(),
+                message: message.clone(),
+                retry_in_secs: delay.as_secs(),
+            };
+            let _ = app.emit("watcher:error", event);
+            tokio::time::sleep(delay).await;
+            if !is_current() {
+                return;
+            }
+
+            let watcher = {
+                let app = app.clone();
+                let root = root.clone();
+                let exclusions = Arc::clone(&state.exclusions);
+                let failures_tx = failures_tx.clone();
+                blocking(move || {
+                    if !root.is_dir() {
+                        let message = format!("Directory does not exist: {}", root.display());
+                        return Err(AppError::InvalidPath(message));
+                    }
+                    create_watcher(&app, &root, exclusions, failures_tx)
+                })
+                .await
+            };
+            match watcher {
+                Ok(watcher) => {
+                    let mut watcher_guard = state.watcher.lock();
+                    // Checked under the lock so a newer watch is never replaced
+                    if !is_current() {
+                        drop(watcher_guard);
+                        drop_watcher(Some(watcher));
+                        return;
+                    }
+                    *watcher_guard = Some(watcher);
+                    break;
+                }
+                Err(e) => {
+                    message = e.detail();
+                    attempt += 1;
+                }
+            }
+        }
 
-        *watcher_guard = Some(w);
+        // Errors the old watcher sent before it was dropped
+        while failures.try_recv().is_ok() {}
+        log::info!("[watcher] Watching {} again", path);
+        let _ = app.emit("watcher:recovered", &path);
     }
+}
 
-    // Store the watched path
-    {
-        let mut path_guard = state.watched_path.lock().map_err(|e| e.to_string())?;
-        *path_guard = Some(watched_path_clone);
+/// Created/removed event for one side of an unpaired rename, if it is a media file or directory
+fn media_event(path: &std::path::Path, exists: bool) -> Option<FileEvent> {
+    if !path.is_dir() && !crate::services::directory_service::is_supported_media(path) {
+        return None;
     }
 
-    Ok(())
+    let path_str = path.to_string_lossy().to_string();
+    Some(if exists {
+        FileEvent::Created(path_str)
+    } else {
+        FileEvent::Removed(path_str)
+    })
+}
+
+/// Collect watcher events and emit them as one deduplicated `file-changes`
+/// batch after a quiet period, instead of one `file-change` per raw event
+fn emit_batched_events(
+    app: AppHandle,
+    rx: Receiver<FileEvent>,
+    exclusions: Arc<RwLock<Vec<PathBuf>>>,
+) {
+    let mut batcher = FileEventBatcher::new();
+    let mut batch_started: Option<Instant> = None;
+
+    loop {
+        let disconnected = match rx.recv_timeout(WATCH_QUIET_PERIOD) {
+            Ok(event) => {
+                let excluded = exclusions.read();
+                let Some(event) = filter_excluded(event, &excluded) else {
+                    continue;
+                };
+                drop(excluded);
+
+                batcher.push(event);
+                batch_started.get_or_insert_with(Instant::now);
+
+                let overdue = batch_started.is_some_and(|t| t.elapsed() >= WATCH_MAX_BATCH_DELAY);
+                if !overdue {
+                    continue;
+                }
+                false
+            }
+            Err(RecvTimeoutError::Timeout) => false,
+            Err(RecvTimeoutError::Disconnected) => true,
+        };
+
+        if !batcher.is_empty() {
+            let _ = app.emit("file-changes", batcher.drain());
+        }
+        batch_started = None;
+
+        if disconnected {
+            break;
+        }
+    }
 }
 
 /// Stop watching the current directory
 #[tauri::command]
-pub async fn stop_watching_directory(state: State<'_, WatcherState>) -> Result<(), String> {
-    let mut watcher_guard = state.watcher.lock().map_err(|e| e.to_string())?;
-    *watcher_guard = None;
+pub async fn stop_watching_directory(state: State<'_, WatcherState>) -> Result<()> {
+    state.generation.fetch_add(1, Ordering::SeqCst);
+    drop_watcher(state.replace_watcher(None));
+    *state.watched_path.write() = None;
+    state.exclusions.write().clear();
+
+    Ok(())
+}
 
-    let mut path_guard = state.watched_path.lock().map_err(|e| e.to_string())?;
-    *path_guard = None;
+/// Replace the excluded subdirectories of the active watch
+#[tauri::command]
+pub async fn set_watch_exclusions(
+    exclude: Vec<String>,
+    state: State<'_, WatcherState>,
+) -> Result<()> {
+    let root = state
+        .watched_path()
+        .ok_or_else(|| AppError::InvalidInput("No directory is being watched".to_string()))?;
 
+    *state.exclusions.write() = resolve_exclusions(Path::new(&root), exclude);
     Ok(())
 }
 
+/// Get the excluded subdirectories of the active watch
+#[tauri::command]
+pub async fn get_watch_exclusions(state: State<'_, WatcherState>) -> Result<Vec<String>> {
+    let exclusions = state.exclusions.read();
+    Ok(exclusions
+        .iter()
+        .map(|p| p.to_string_lossy().to_string())
+        .collect())
+}
+
 /// Get the currently watched directory
 #[tauri::command]
-pub async fn get_watched_directory(state: State<'_, WatcherState>) -> Result<Option<String>, String> {
-    let path_guard = state.watched_path.lock().map_err(|e| e.to_string())?;
-    Ok(path_guard.clone())
+pub async fn get_watched_directory(state: State<'_, WatcherState>) -> Result<Option<String>> {
+    Ok(state.watched_path())
 }
 
 /// Check if a specific file is a supported media file
@@ -0,0 +1,101 @@
+use crate::commands::cloud::{hosted_api_key, openai_compatible_server, SessionKeyState};
+use crate::commands::jobs::RunningJobs;
+use crate::commands::progress::ThrottledEmitter;
+use crate::error::{AppError, Result};
+use crate::services::database::{Database, EmbeddingStoreStats};
+use crate::services::embeddings::{self, Embedder, IndexStats, OllamaEmbedder, OpenAIEmbedder};
+use crate::services::llm;
+use crate::services::providers;
+use serde::Serialize;
+use tauri::{AppHandle, State};
+
+/// Progress of `rebuild_embeddings`, emitted as `embeddings:progress`
+#[derive(Debug, Clone, Serialize)]
+pub struct EmbeddingProgress {
+    pub media_path: String,
+    /// Files done so far and in total
+    pub done: usize,
+    pub total: usize,
+}
+
+/// Build an embedding backend: Ollama, OpenAI or the self-hosted OpenAI-compatible server
+fn embedder(
+    provider: &str,
+    model: &str,
+    profile: Option<&str>,
+    session: &SessionKeyState,
+) -> Result<Box<dyn Embedder>> {
+    match provider {
+        llm::OLLAMA => Ok(Box::new(OllamaEmbedder::new(model))),
+        providers::OPENAI => {
+            let api_key = hosted_api_key(session, providers::OPENAI, profile)?;
+            Ok(Box::new(OpenAIEmbedder::openai(&api_key, model)))
+        }
+        providers::OPENAI_COMPATIBLE => {
+            let (base_url, api_key) = openai_compatible_server(None, profile, session)?;
+            Ok(Box::new(OpenAIEmbedder::openai_compatible(
+                &base_url,
+                api_key.as_deref(),
+                model,
+            )?))
+        }
+        other => Err(AppError::InvalidInput(format!("Unknown embedding provider: {}", other))),
+    }
+}
+
+/// Embed the saved transcripts of one media file, or of every transcribed file,
+/// into the embedding store. Segments embedded before with the same model are
+/// reused. Pass a `job_id` to make it cancellable with `cancel_job`.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn rebuild_embeddings(
+    app: AppHandle,
+    provider: String,
+    model: String,
+    path: Option<String>,
+    profile: Option<String>,
+    job_id: Option<String>,
+    session: State<'_, SessionKeyState>,
+    jobs: State<'_, RunningJobs>,
+) -> Result<IndexStats> {
+    let embedder = embedder(&provider, &model, profile.as_deref(), &session)?;
+    let job = jobs.start(job_id);
+    let paths = match path {
+        Some(path) => vec![path],
+        None => Database::open()?.transcribed_media_paths()?,
+    };
+    log::info!(
+        "[embeddings] Indexing {} file(s) with {}",
+        paths.len(),
+        embedder.store_key()
+    );
+
+    let emitter = ThrottledEmitter::new(&app, "embeddings:progress");
+    let mut stats = IndexStats::default();
+    for (i, media_path) in paths.iter().enumerate() {
+        let indexed = embeddings::index_media(embedder.as_ref(), media_path, job.token()).await?;
+        stats.files += indexed.files;
+        stats.segments += indexed.segments;
+        stats.embedded += indexed.embedded;
+        stats.cached += indexed.cached;
+        emitter.emit(EmbeddingProgress {
+            media_path: media_path.clone(),
+            done: i + 1,
+            total: paths.len(),
+        });
+    }
+    Ok(stats)
+}
+
+/// Number and size of the stored embeddings, per model
+#[tauri::command]
+pub fn get_embedding_store_stats() -> Result<EmbeddingStoreStats> {
+    Database::open()?.embedding_store_stats()
+}
+
+/// Delete the stored embeddings of one model (e.g. `ollama/nomic-embed-text`),
+/// or all of them. Returns how many vectors were deleted.
+#[tauri::command]
+pub fn clear_embeddings(model: Option<String>) -> Result<usize> {
+    Database::open()?.clear_embeddings(model.as_deref())
+}
@@ -0,0 +1,286 @@
+use crate::commands::notifications::{file_name, notify_outcome};
+use crate::commands::scope::scoped_path;
+use crate::error::{AppError, Result};
+use crate::services::database::Database;
+use crate::services::export::{
+    to_audacity_labels, to_audition_markers, to_csv, to_karaoke_ass, to_markdown,
+    to_segments_json, to_srt, to_text, to_vtt, to_words_json, KaraokeOptions, MarkdownOptions,
+    Moment, SrtOptions, VttOptions,
+};
+use crate::services::minutes::{to_minutes_docx, to_minutes_markdown, MeetingMinutes};
+use crate::services::pdf_export::{render_pdf_report, PdfReportOptions};
+use crate::services::timeline_export::{
+    to_edl, to_fcpxml, to_marker_csv, to_premiere_xml, to_resolve_marker_edl, to_resolve_xml,
+    TimelineOptions, TimelineSource,
+};
+use crate::services::{metrics, FFmpegService, TranscriptionSegment};
+use serde_json::json;
+use std::path::Path;
+use tauri::AppHandle;
+
+/// Bookmarks of a media file as export moments, none without a media file
+fn bookmark_moments(media_path: Option<&str>) -> Result<Vec<Moment>> {
+    let Some(media_path) = media_path else {
+        return Ok(Vec::new());
+    };
+    let bookmarks = Database::open()?.bookmarks(media_path)?;
+    Ok(bookmarks.iter().map(Moment::from).collect())
+}
+
+/// Write an export to a path the user chose and count it in the local metrics
+async fn write_export(
+    app: &AppHandle,
+    format: &str,
+    output_path: String,
+    contents: impl AsRef<[u8]>,
+) -> Result<String> {
+    let path = scoped_path(app, &output_path)?;
+    let written = tokio::fs::write(&path, contents).await.map_err(AppError::from);
+    notify_outcome(app, "Export", &file_name(&output_path), &written);
+    written?;
+    metrics::record_event("export", json!({ "format": format }));
+    Ok(output_path)
+}
+
+/// Export segments as a WebVTT subtitle file, returning the written path
+#[tauri::command]
+pub async fn export_vtt(
+    app: AppHandle,
+    segments: Vec<TranscriptionSegment>,
+    output_path: String,
+    options: Option<VttOptions>,
+) -> Result<String> {
+    let vtt = to_vtt(&segments, &options.unwrap_or_default());
+    write_export(&app, "vtt", output_path, vtt).await
+}
+
+/// Export segments as an SRT subtitle file, returning the written path
+#[tauri::command]
+pub async fn export_srt(
+    app: AppHandle,
+    segments: Vec<TranscriptionSegment>,
+    output_path: String,
+    options: Option<SrtOptions>,
+) -> Result<String> {
+    let srt = to_srt(&segments, &options.unwrap_or_default());
+    write_export(&app, "srt", output_path, srt).await
+}
+
+/// Export the transcript as plain text, returning the written path
+#[tauri::command]
+pub async fn export_text(
+    app: AppHandle,
+    segments: Vec<TranscriptionSegment>,
+    output_path: String,
+) -> Result<String> {
+    write_export(&app, "text", output_path, to_text(&segments)).await
+}
+
+/// Export the transcript (with optional title and summary) as Markdown,
+/// returning the written path. The bookmarks of `media_path` are listed too.
+#[tauri::command]
+pub async fn export_markdown(
+    app: AppHandle,
+    segments: Vec<TranscriptionSegment>,
+    output_path: String,
+    options: Option<MarkdownOptions>,
+    media_path: Option<String>,
+) -> Result<String> {
+    let mut options = options.unwrap_or_default();
+    options.bookmarks.extend(bookmark_moments(media_path.as_deref())?);
+    let markdown = to_markdown(&segments, &options);
+    write_export(&app, "markdown", output_path, markdown).await
+}
+
+/// Export the transcript and summary as a PDF report, returning the written path
+#[tauri::command]
+pub async fn export_pdf(
+    app: AppHandle,
+    segments: Vec<TranscriptionSegment>,
+    output_path: String,
+    options: Option<PdfReportOptions>,
+) -> Result<String> {
+    let pdf = render_pdf_report(&segments, &options.unwrap_or_default())?;
+    write_export(&app, "pdf", output_path, pdf).await
+}
+
+/// Export meeting minutes as Markdown, returning the written path
+#[tauri::command]
+pub async fn export_minutes_markdown(
+    app: AppHandle,
+    minutes: MeetingMinutes,
+    output_path: String,
+) -> Result<String> {
+    write_export(&app, "minutes_markdown", output_path, to_minutes_markdown(&minutes)).await
+}
+
+/// Export meeting minutes as a Word document, returning the written path
+#[tauri::command]
+pub async fn export_minutes_docx(
+    app: AppHandle,
+    minutes: MeetingMinutes,
+    output_path: String,
+) -> Result<String> {
+    write_export(&app, "minutes_docx", output_path, to_minutes_docx(&minutes)?).await
+}
+
+/// Export segments as CSV for spreadsheets, returning the written path
+#[tauri::command]
+pub async fn export_csv(
+    app: AppHandle,
+    segments: Vec<TranscriptionSegment>,
+    output_path: String,
+) -> Result<String> {
+    write_export(&app, "csv", output_path, to_csv(&segments)).await
+}
+
+/// Export segments as a JSON array for downstream tools, returning the written path
+#[tauri::command]
+pub async fn export_segments_json(
+    app: AppHandle,
+    segments: Vec<TranscriptionSegment>,
+    output_path: String,
+) -> Result<String> {
+    write_export(&app, "json", output_path, to_segments_json(&segments)?).await
+}
+
+/// Export segments as karaoke-style ASS subtitles that highlight each word as
+/// it is spoken, returning the written path
+#[tauri::command]
+pub async fn export_karaoke_ass(
+    app: AppHandle,
+    segments: Vec<TranscriptionSegment>,
+    output_path: String,
+    options: Option<KaraokeOptions>,
+) -> Result<String> {
+    let ass = to_karaoke_ass(&segments, &options.unwrap_or_default())?;
+    write_export(&app, "karaoke_ass", output_path, ass).await
+}
+
+/// Export segments with the timing of each word as JSON for animated captions,
+/// returning the written path
+#[tauri::command]
+pub async fn export_words_json(
+    app: AppHandle,
+    segments: Vec<TranscriptionSegment>,
+    output_path: String,
+) -> Result<String> {
+    write_export(&app, "words_json", output_path, to_words_json(&segments)?).await
+}
+
+/// Export segment boundaries and notable moments (plus the bookmarks of
+/// `media_path`) as an Audacity label track, returning the written path
+#[tauri::command]
+pub async fn export_audacity_labels(
+    app: AppHandle,
+    segments: Vec<TranscriptionSegment>,
+    output_path: String,
+    moments: Option<Vec<Moment>>,
+    media_path: Option<String>,
+) -> Result<String> {
+    let mut moments = moments.unwrap_or_default();
+    moments.extend(bookmark_moments(media_path.as_deref())?);
+    let labels = to_audacity_labels(&segments, &moments);
+    write_export(&app, "audacity_labels", output_path, labels).await
+}
+
+/// Export segment boundaries and notable moments (plus the bookmarks of
+/// `media_path`) as Adobe Audition markers, returning the written path
+#[tauri::command]
+pub async fn export_audition_markers(
+    app: AppHandle,
+    segments: Vec<TranscriptionSegment>,
+    output_path: String,
+    moments: Option<Vec<Moment>>,
+    media_path: Option<String>,
+) -> Result<String> {
+    let mut moments = moments.unwrap_or_default();
+    moments.extend(bookmark_moments(media_path.as_deref())?);
+    let markers = to_audition_markers(&segments, &moments);
+    write_export(&app, "audition_markers", output_path, markers).await
+}
+
+/// Probe the media a timeline export cuts from
+async fn timeline_source(media_path: &str) -> Result<TimelineSource> {
+    let info = FFmpegService::get_media_info(Path::new(media_path)).await?;
+    Ok(TimelineSource::new(media_path, &info))
+}
+
+/// Export segments (in timeline order) as a Final Cut Pro FCPXML project cut from
+/// the original media, returning the written path
+#[tauri::command]
+pub async fn export_fcpxml(
+    app: AppHandle,
+    media_path: String,
+    segments: Vec<TranscriptionSegment>,
+    output_path: String,
+    options: Option<TimelineOptions>,
+) -> Result<String> {
+    let source = timeline_source(&media_path).await?;
+    let fcpxml = to_fcpxml(&segments, &source, &options.unwrap_or_default());
+    write_export(&app, "fcpxml", output_path, fcpxml).await
+}
+
+/// Export segments (in timeline order) as a CMX3600 EDL, returning the written path
+#[tauri::command]
+pub async fn export_edl(
+    app: AppHandle,
+    media_path: String,
+    segments: Vec<TranscriptionSegment>,
+    output_path: String,
+    options: Option<TimelineOptions>,
+) -> Result<String> {
+    let source = timeline_source(&media_path).await?;
+    let edl = to_edl(&segments, &source, &options.unwrap_or_default());
+    write_export(&app, "edl", output_path, edl).await
+}
+
+/// Export segments (in timeline order) as Premiere-compatible XML, returning the written path
+#[tauri::command]
+pub async fn export_premiere_xml(
+    app: AppHandle,
+    media_path: String,
+    segments: Vec<TranscriptionSegment>,
+    output_path: String,
+    options: Option<TimelineOptions>,
+) -> Result<String> {
+    let source = timeline_source(&media_path).await?;
+    let xml = to_premiere_xml(&segments, &source, &options.unwrap_or_default());
+    write_export(&app, "premiere_xml", output_path, xml).await
+}
+
+/// Export segments (in timeline order) for DaVinci Resolve: an FCP7 XML timeline with
+/// transcript markers at `output_path`, plus `.markers.edl` and `.markers.csv` files
+/// next to it. Returns the written paths.
+#[tauri::command]
+pub async fn export_resolve_timeline(
+    app: AppHandle,
+    media_path: String,
+    segments: Vec<TranscriptionSegment>,
+    output_path: String,
+    options: Option<TimelineOptions>,
+) -> Result<Vec<String>> {
+    let source = timeline_source(&media_path).await?;
+    let options = options.unwrap_or_default();
+
+    let timeline = scoped_path(&app, &output_path)?;
+    let marker_edl = timeline.with_extension("markers.edl");
+    let marker_csv = timeline.with_extension("markers.csv");
+
+    let written: Result<()> = async {
+        tokio::fs::write(&timeline, to_resolve_xml(&segments, &source, &options)).await?;
+        tokio::fs::write(&marker_edl, to_resolve_marker_edl(&segments, &source, &options)).await?;
+        tokio::fs::write(&marker_csv, to_marker_csv(&segments, &source, &options)).await?;
+        Ok(())
+    }
+    .await;
+    notify_outcome(&app, "Export", &file_name(&output_path), &written);
+    written?;
+    metrics::record_event("export", json!({ "format": "resolve_timeline" }));
+
+    Ok(vec![
+        output_path,
+        marker_edl.to_string_lossy().into_owned(),
+        marker_csv.to_string_lossy().into_owned(),
+    ])
+}
@@ -0,0 +1,252 @@
+use crate::commands::jobs::{queue_job, RunningJobs};
+use crate::commands::progress::ThrottledEmitter;
+use crate::commands::scope::scoped_path;
+use crate::error::{AppError, Result};
+use crate::services::app_settings::AppSettings;
+use crate::services::database::{Database, Feed, FeedEpisode};
+use crate::services::job_queue::JobSpec;
+use crate::services::podcast::{
+    episode_file_name, safe_file_stem, unused_file_name, PodcastService,
+};
+use serde::Serialize;
+use std::path::PathBuf;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, State};
+use tokio_util::sync::CancellationToken;
+
+/// How long the poller waits before looking at the settings again while polling is off
+const POLLING_OFF_RECHECK: Duration = Duration::from_secs(300);
+
+/// Progress of an episode download, emitted as `feed:download-progress`
+#[derive(Debug, Clone, Serialize)]
+pub struct EpisodeDownloadProgress {
+    pub episode_id: i64,
+    pub downloaded: u64,
+    pub total: u64,
+    pub percent: f32,
+}
+
+/// New episodes found by a feed check, emitted as `feed:new-episodes`
+#[derive(Debug, Clone, Serialize)]
+pub struct NewEpisodes {
+    pub feed_id: i64,
+    pub episodes: Vec<FeedEpisode>,
+}
+
+/// Subscribe to a podcast RSS feed. Episodes already published are listed but
+/// not downloaded; with `auto_transcribe_model`, episodes published from now on
+/// are downloaded and queued for transcription with that Whisper model.
+#[tauri::command]
+pub async fn subscribe_feed(
+    app: AppHandle,
+    url: String,
+    download_dir: Option<String>,
+    auto_transcribe_model: Option<String>,
+) -> Result<Feed> {
+    let download_dir = chosen_dir(&app, download_dir)?;
+    let url = url.trim();
+    let parsed = PodcastService::new().fetch_feed(url).await?;
+    Database::open()?.add_feed(
+        url,
+        &parsed,
+        download_dir.as_deref(),
+        auto_transcribe_model.as_deref(),
+    )
+}
+
+/// Unsubscribe from a feed. Downloaded episode files are kept.
+#[tauri::command]
+pub fn unsubscribe_feed(feed_id: i64) -> Result<()> {
+    Database::open()?.delete_feed(feed_id)
+}
+
+/// List subscribed feeds
+#[tauri::command]
+pub fn list_feeds() -> Result<Vec<Feed>> {
+    Database::open()?.list_feeds()
+}
+
+/// Change where a feed's episodes are saved and whether new ones are transcribed
+#[tauri::command]
+pub fn update_feed(
+    app: AppHandle,
+    feed_id: i64,
+    download_dir: Option<String>,
+    auto_transcribe_model: Option<String>,
+) -> Result<Feed> {
+    let download_dir = chosen_dir(&app, download_dir)?;
+    Database::open()?.update_feed(
+        feed_id,
+        download_dir.as_deref(),
+        auto_transcribe_model.as_deref(),
+    )
+}
+
+/// List the episodes of a feed, newest first
+#[tauri::command]
+pub fn list_feed_episodes(feed_id: i64) -> Result<Vec<FeedEpisode>> {
+    Database::open()?.feed_episodes(feed_id)
+}
+
+/// Check a feed for new episodes now. Returns the episodes not seen before.
+#[tauri::command]
+pub async fn refresh_feed(app: AppHandle, feed_id: i64) -> Result<Vec<FeedEpisode>> {
+    let feed = Database::open()?.feed(feed_id)?;
+    check_feed(&app, &feed).await
+}
+
+/// Download the audio of an episode. Pass a `job_id` to make it cancellable with `cancel_job`.
+#[tauri::command]
+pub async fn download_feed_episode(
+    app: AppHandle,
+    episode_id: i64,
+    job_id: Option<String>,
+    jobs: State<'_, RunningJobs>,
+) -> Result<FeedEpisode> {
+    let job = jobs.start(job_id);
+    download_episode(&app, episode_id, job.token()).await
+}
+
+/// Start the background task that checks subscribed feeds on the interval
+/// set in the app settings
+pub fn start_feed_poller(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let minutes = AppSettings::load()
+                .map(|settings| settings.feed_poll_interval_minutes)
+                .unwrap_or_else(|_| AppSettings::default().feed_poll_interval_minutes);
+            if minutes == 0 {
+                tokio::time::sleep(POLLING_OFF_RECHECK).await;
+                continue;
+            }
+
+            if let Err(e) = check_all_feeds(&app).await {
+                log::error!("[feeds] Failed to check feeds: {}", e);
+            }
+            tokio::time::sleep(Duration::from_secs(minutes * 60)).await;
+        }
+    });
+}
+
+async fn check_all_feeds(app: &AppHandle) -> Result<()> {
+    let feeds = Database::open()?.list_feeds()?;
+    for feed in feeds {
+        // One unreachable feed must not stop the others from being checked
+        if let Err(e) = check_feed(app, &feed).await {
+            log::warn!("[feeds] Failed to check {}: {}", feed.url, e);
+        }
+    }
+    Ok(())
+}
+
+/// Fetch a feed, record its new episodes and, if the feed has auto-transcription
+/// on, download them and queue their transcription
+async fn check_feed(app: &AppHandle, feed: &Feed) -> Result<Vec<FeedEpisode>> {
+    let parsed = PodcastService::new().fetch_feed(&feed.url).await?;
+    let new_episodes = Database::open()?.add_feed_episodes(feed.id, &parsed.episodes)?;
+    if new_episodes.is_empty() {
+        return Ok(new_episodes);
+    }
+    log::info!("[feeds] {} new episode(s) in {}", new_episodes.len(), feed.title);
+    let _ = app.emit(
+        "feed:new-episodes",
+        NewEpisodes {
+            feed_id: feed.id,
+            episodes: new_episodes.clone(),
+        },
+    );
+
+    if let Some(model_id) = &feed.auto_transcribe_model {
+        // One failed episode must not keep the others from being transcribed
+        let mut failed = 0;
+        for episode in &new_episodes {
+            let downloaded = download_episode(app, episode.id, &CancellationToken::new()).await;
+            let file_path = match downloaded {
+                Ok(episode) => episode.local_path.unwrap_or_default(),
+                Err(e) => {
+                    log::warn!("[feeds] Failed to download {}: {}", episode.audio_url, e);
+                    failed += 1;
+                    continue;
+                }
+            };
+            let spec = JobSpec::Transcription {
+                file_path,
+                model_id: model_id.clone(),
+                language: None,
+                provider: None,
+                profile: None,
+                audio_filters: Default::default(),
+            };
+            if let Err(e) = queue_job(app, &spec) {
+                log::warn!("[feeds] Failed to queue {}: {}", episode.title, e);
+                failed += 1;
+            }
+        }
+        if failed > 0 {
+            log::warn!(
+                "[feeds] {} of {} new episode(s) of {} were not queued for transcription",
+                failed,
+                new_episodes.len(),
+                feed.title
+            );
+        }
+    }
+    Ok(new_episodes)
+}
+
+/// Download an episode into its feed's folder, stopping when `cancel` is triggered
+async fn download_episode(
+    app: &AppHandle,
+    episode_id: i64,
+    cancel: &CancellationToken,
+) -> Result<FeedEpisode> {
+    let (episode, output_dir) = {
+        let db = Database::open()?;
+        let episode = db.feed_episode(episode_id)?;
+        let feed = db.feed(episode.feed_id)?;
+        (episode, feed_download_dir(&feed)?)
+    };
+    let output_path = match &episode.local_path {
+        // Downloading again replaces the episode's own file
+        Some(path) => PathBuf::from(path),
+        None => {
+            let file_name = episode_file_name(&episode.title, &episode.audio_url);
+            output_dir.join(unused_file_name(&output_dir, &file_name))
+        }
+    };
+
+    let emitter = ThrottledEmitter::new(app, "feed:download-progress");
+    PodcastService::new()
+        .download_episode(&episode.audio_url, episode.size, &output_path, cancel, move |p| {
+            emitter.emit(EpisodeDownloadProgress {
+                episode_id,
+                downloaded: p.downloaded,
+                total: p.total,
+                percent: p.percent,
+            });
+        })
+        .await?;
+
+    Database::open()?.set_episode_downloaded(episode_id, &output_path.to_string_lossy())
+}
+
+/// Check a download folder from the webview, keeping it as the canonical path
+fn chosen_dir(app: &AppHandle, dir: Option<String>) -> Result<Option<String>> {
+    dir.map(|dir| Ok(scoped_path(app, &dir)?.to_string_lossy().into_owned()))
+        .transpose()
+}
+
+/// Folder a feed's episodes are saved in: its own setting, or a folder named
+/// after the feed in the app's podcast directory
+fn feed_download_dir(feed: &Feed) -> Result<PathBuf> {
+    if let Some(dir) = &feed.download_dir {
+        return Ok(PathBuf::from(dir));
+    }
+    let data_dir = dirs::data_local_dir()
+        .ok_or_else(|| AppError::InvalidPath("Cannot find data directory".to_string()))?;
+    let folder = match safe_file_stem(&feed.title) {
+        title if title.is_empty() => format!("feed-{}", feed.id),
+        title => title,
+    };
+    Ok(data_dir.join("clip-flow").join("podcasts").join(folder))
+}
@@ -1,7 +1,14 @@
+use crate::commands::jobs::RunningJobs;
+use crate::commands::progress::ThrottledEmitter;
+use crate::commands::scope::scoped_path;
 use crate::error::Result;
+use crate::services::audio_filters::AudioFilters;
+use crate::services::thumbnail::ThumbnailCache;
+use crate::services::{job_queue, path_scope};
 use crate::services::{FFmpegService, MediaInfo};
 use std::path::PathBuf;
-use tauri::{AppHandle, Emitter};
+use tauri::{AppHandle, State};
+use tokio_util::sync::CancellationToken;
 
 /// Check if FFmpeg is available
 #[tauri::command]
@@ -22,31 +29,50 @@ pub async fn get_media_info(path: String) -> Result<MediaInfo> {
     FFmpegService::get_media_info(&path).await
 }
 
-/// Extract audio from media file
+/// Extract audio from media file. Pass a `job_id` to make it cancellable with `cancel_job`.
 #[tauri::command]
 pub async fn extract_audio(
     app: AppHandle,
     input_path: String,
     output_path: Option<String>,
+    job_id: Option<String>,
+    jobs: State<'_, RunningJobs>,
 ) -> Result<String> {
-    let input = PathBuf::from(&input_path);
+    let job = jobs.start(job_id);
+    extract_audio_file(&app, &input_path, output_path, job.token()).await
+}
 
-    // Generate output path if not provided
-    let output = match output_path {
+/// Where `extract_audio_file` writes: `output_path` if given, otherwise a
+/// uniquely named WAV in the temp folder (of the queued job, if any), so
+/// extractions of files with the same name don't overwrite each other
+fn audio_output_path(output_path: Option<String>) -> PathBuf {
+    match output_path {
         Some(p) => PathBuf::from(p),
-        None => {
-            let temp_dir = std::env::temp_dir().join("clip-flow");
-            tokio::fs::create_dir_all(&temp_dir).await?;
-            let filename = input.file_stem()
-                .map(|s| s.to_string_lossy().to_string())
-                .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
-            temp_dir.join(format!("{}.wav", filename))
-        }
-    };
-
-    let app_handle = app.clone();
-    let result = FFmpegService::extract_audio(&input, &output, move |progress| {
-        let _ = app_handle.emit("ffmpeg:progress", progress);
+        None => job_queue::temp_dir().join(format!("{}.wav", uuid::Uuid::new_v4())),
+    }
+}
+
+/// Extract audio, stopping when `cancel` is triggered. An explicit output path
+/// must be inside a folder the user chose.
+pub(crate) async fn extract_audio_file(
+    app: &AppHandle,
+    input_path: &str,
+    output_path: Option<String>,
+    cancel: &CancellationToken,
+) -> Result<String> {
+    let input = path_scope::canonicalize(input_path)?;
+    if let Some(output_path) = &output_path {
+        scoped_path(app, output_path)?;
+    }
+    let output = audio_output_path(output_path);
+    if let Some(parent) = output.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+
+    let emitter = ThrottledEmitter::new(app, "ffmpeg:progress");
+    let filters = AudioFilters::default();
+    let result = FFmpegService::extract_audio(&input, &output, &filters, cancel, move |progress| {
+        emitter.emit(progress);
     }).await?;
 
     Ok(result.to_string_lossy().to_string())
@@ -58,3 +84,23 @@ pub async fn get_media_duration(path: String) -> Result<f64> {
     let path = PathBuf::from(path);
     FFmpegService::get_duration(&path).await
 }
+
+/// Get the poster thumbnail of a media file (generated and cached on first request).
+/// Returns `None` if the file has no frame to show.
+#[tauri::command]
+pub async fn get_thumbnail(path: String) -> Result<Option<String>> {
+    let thumbnail = ThumbnailCache::new()?.get_or_create(&PathBuf::from(path)).await?;
+    Ok(thumbnail.map(|p| p.to_string_lossy().to_string()))
+}
+
+/// Drop the cached thumbnail of a media file so it is regenerated on next request
+#[tauri::command]
+pub fn invalidate_thumbnail(path: String) -> Result<()> {
+    ThumbnailCache::new()?.invalidate(&PathBuf::from(path))
+}
+
+/// Delete all cached thumbnails
+#[tauri::command]
+pub fn clear_thumbnail_cache() -> Result<()> {
+    ThumbnailCache::new()?.clear()
+}
@@ -0,0 +1,557 @@
+use crate::commands::transcribe::TranscriptionOptions;
+use crate::commands::{
+    claude_summarize, discard_model_partial, download_model_file, extract_audio_file,
+    gemini_summarize, groq_summarize, llama_summarize_text, notify_job_finished,
+    openai_compatible_summarize, openai_summarize, summarize_text, transcribe_file,
+    transcription_provider, ScanState, ServiceState, SessionKeyState,
+};
+use crate::error::{AppError, Result};
+use crate::services::cancellation::cancellable;
+use crate::services::concurrency::{self, JobKind};
+use crate::services::database::{Database, JobRecord, SummaryInput};
+use crate::services::job_queue::{in_job, job_temp_dir, JobEvent, JobSpec, JobStatus, QueuedJob};
+use crate::services::{metrics, usage};
+use serde_json::json;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::sync::Notify;
+use tokio_util::sync::CancellationToken;
+
+/// How long the worker waits before retrying after the queue could not be read
+const WORKER_RETRY_DELAY: Duration = Duration::from_secs(5);
+
+/// Wakes the background worker when jobs are added or become runnable
+pub struct JobQueueState {
+    wake: Notify,
+    /// No new jobs start while set; running ones finish
+    paused: AtomicBool,
+}
+
+impl JobQueueState {
+    /// The queue, paused if it was paused when the app last quit
+    pub fn restore() -> Self {
+        let paused = Database::open()
+            .and_then(|db| db.queue_paused())
+            .unwrap_or_else(|e| {
+                log::warn!(
+                    "[job_queue] Failed to read whether the queue is paused: {}",
+                    e
+                );
+                false
+            });
+        Self {
+            wake: Notify::new(),
+            paused: AtomicBool::new(paused),
+        }
+    }
+}
+
+/// Cancellation tokens of running operations, by job id
+#[derive(Default)]
+pub struct RunningJobs {
+    tokens: Mutex<HashMap<String, CancellationToken>>,
+}
+
+impl RunningJobs {
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, CancellationToken>> {
+        self.tokens.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Register an operation under `job_id` (if given) so `cancel_job` can stop it.
+    /// It is unregistered when the returned guard is dropped.
+    pub fn start(&self, job_id: Option<String>) -> RunningJob<'_> {
+        let token = CancellationToken::new();
+        if let Some(id) = &job_id {
+            self.lock().insert(id.clone(), token.clone());
+        }
+        RunningJob {
+            jobs: self,
+            job_id,
+            token,
+        }
+    }
+
+    /// Like [`RunningJobs::start`], generating an id when none is given, so the
+    /// operation can always be cancelled by the id its progress events carry
+    pub fn start_with_id(&self, job_id: Option<String>) -> RunningJob<'_> {
+        self.start(Some(job_id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string())))
+    }
+
+    fn cancel(&self, job_id: &str) -> bool {
+        self.lock().get(job_id).map(CancellationToken::cancel).is_some()
+    }
+}
+
+/// A registered running operation
+pub struct RunningJob<'a> {
+    jobs: &'a RunningJobs,
+    job_id: Option<String>,
+    token: CancellationToken,
+}
+
+impl RunningJob<'_> {
+    pub fn token(&self) -> &CancellationToken {
+        &self.token
+    }
+
+    pub fn id(&self) -> Option<&str> {
+        self.job_id.as_deref()
+    }
+}
+
+impl Drop for RunningJob<'_> {
+    fn drop(&mut self) {
+        if let Some(id) = &self.job_id {
+            self.jobs.lock().remove(id);
+        }
+    }
+}
+
+/// Tell the frontend a job changed
+fn emit_job(app: &AppHandle, job: &QueuedJob) {
+    let _ = app.emit("job:updated", job);
+}
+
+/// Files a job leaves behind when it is interrupted: its temp folder (with
+/// any output it had no path for) and partial outputs. The partial file of a
+/// model download is kept, so resuming the job continues the download.
+fn interrupted_job_files(job: &QueuedJob) -> Vec<PathBuf> {
+    let mut files = vec![job_temp_dir(&job.id)];
+    if let JobSpec::AudioExtraction {
+        output_path: Some(output_path),
+        ..
+    } = &job.spec
+    {
+        files.push(PathBuf::from(output_path));
+    }
+    files
+}
+
+/// Mark the jobs that were running when the app quit or crashed as interrupted
+/// and remove their partial files. They stay in the queue until resumed
+/// (`resume_job`), requeued (`retry_job`) or cancelled.
+async fn recover_interrupted_jobs(app: &AppHandle) -> Result<()> {
+    let interrupted = Database::open()?.interrupt_running_jobs()?;
+    for job in &interrupted {
+        for path in interrupted_job_files(job) {
+            let removed = if path.is_dir() {
+                tokio::fs::remove_dir_all(&path).await
+            } else if path.exists() {
+                tokio::fs::remove_file(&path).await
+            } else {
+                continue;
+            };
+            if let Err(e) = removed {
+                log::warn!("[job_queue] Failed to remove {}: {}", path.display(), e);
+            }
+        }
+        log::warn!("[job_queue] {} job {} was interrupted", job.spec.kind(), job.id);
+        emit_job(app, job);
+    }
+    if !interrupted.is_empty() {
+        let _ = app.emit("jobs:interrupted", &interrupted);
+    }
+    Ok(())
+}
+
+/// Start the worker that starts queued jobs in queue order, running as many at
+/// once as the `queued_jobs` concurrency limit allows. Jobs that were running
+/// when the app last quit are recovered first.
+pub fn start_job_worker(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = recover_interrupted_jobs(&app).await {
+            log::error!("[job_queue] Failed to recover interrupted jobs: {}", e);
+        }
+
+        // The worker itself is never cancelled
+        let forever = CancellationToken::new();
+        loop {
+            let slot = match concurrency::acquire(JobKind::Queued, &forever).await {
+                Ok(slot) => slot,
+                Err(e) => {
+                    log::error!("[job_queue] {}", e);
+                    tokio::time::sleep(WORKER_RETRY_DELAY).await;
+                    continue;
+                }
+            };
+            let queue = app.state::<JobQueueState>();
+            if queue.paused.load(Ordering::SeqCst) {
+                drop(slot);
+                queue.wake.notified().await;
+                continue;
+            }
+
+            match Database::open().and_then(|db| db.claim_next_job()) {
+                Ok(Some(job)) => {
+                    let app = app.clone();
+                    tauri::async_runtime::spawn(async move {
+                        if let Err(e) = run_job(&app, job).await {
+                            log::error!("[job_queue] {}", e);
+                        }
+                        drop(slot);
+                        app.state::<JobQueueState>().wake.notify_one();
+                    });
+                }
+                Ok(None) => {
+                    drop(slot);
+                    queue.wake.notified().await;
+                }
+                Err(e) => {
+                    drop(slot);
+                    log::error!("[job_queue] {}", e);
+                    tokio::time::sleep(WORKER_RETRY_DELAY).await;
+                }
+            }
+        }
+    });
+}
+
+/// Run a job marked as running and record its outcome
+async fn run_job(app: &AppHandle, job: QueuedJob) -> Result<()> {
+    emit_job(app, &job);
+
+    let running = app.state::<RunningJobs>();
+    let running = running.start(Some(job.id.clone()));
+    let outcome = in_job(&job.id, execute_job(app, &job.id, &job.spec, running.token()))
+        .await
+        .map_err(|e| e.to_string());
+    drop(running);
+    // Succeeds only once the job's temp files are gone
+    let _ = tokio::fs::remove_dir(job_temp_dir(&job.id)).await;
+
+    let db = Database::open()?;
+    let Some(finished) = db.finish_job(&job.id, outcome)? else {
+        // Cancelled while running: the cancel already updated the frontend
+        return Ok(());
+    };
+    let history = JobRecord {
+        id: finished.id.clone(),
+        kind: finished.spec.kind().to_string(),
+        media_path: finished.spec.media_path().map(str::to_string),
+        status: finished.status.as_str().to_string(),
+        error: finished.error.clone(),
+        created_at: finished.created_at,
+        finished_at: finished.finished_at,
+    };
+    if let Err(e) = db.record_job(&history) {
+        log::warn!("[job_queue] Failed to record job {} in history: {}", finished.id, e);
+    }
+    emit_job(app, &finished);
+    notify_job_finished(app, &finished);
+    Ok(())
+}
+
+/// Do the work of a job, returning its output
+async fn execute_job(
+    app: &AppHandle,
+    id: &str,
+    spec: &JobSpec,
+    cancel: &CancellationToken,
+) -> Result<serde_json::Value> {
+    let output = match spec.clone() {
+        JobSpec::Transcription {
+            file_path,
+            model_id,
+            language,
+            provider,
+            profile,
+            audio_filters,
+        } => {
+            let session = app.state::<SessionKeyState>();
+            let provider = transcription_provider(
+                provider.as_deref(),
+                &model_id,
+                profile.as_deref(),
+                &session,
+                &app.state::<ServiceState>(),
+            )?;
+            let language = language.as_deref();
+            let result = transcribe_file(
+                app,
+                &file_path,
+                provider.as_ref(),
+                language,
+                &audio_filters,
+                Some(id),
+                cancel,
+            );
+            serde_json::to_value(result.await?)?
+        }
+        JobSpec::AudioExtraction {
+            input_path,
+            output_path,
+        } => extract_audio_file(app, &input_path, output_path, cancel).await?.into(),
+        JobSpec::ModelDownload { model_id } => {
+            download_model_file(app, &model_id, cancel).await?.into()
+        }
+        JobSpec::Summary {
+            provider,
+            model,
+            text,
+            language,
+            media_path,
+            profile,
+        } => {
+            let summarize =
+                summarize_with(app, &provider, &model, text, &language, profile, cancel);
+            let summary = match &media_path {
+                Some(media_path) => usage::for_media(media_path, summarize).await?,
+                None => summarize.await?,
+            };
+            if let Some(media_path) = media_path {
+                let input = SummaryInput {
+                    text: summary.clone(),
+                    language: Some(language),
+                    provider: Some(provider),
+                    model: Some(model),
+                };
+                Database::open()?.save_summary(&media_path, &input)?;
+            }
+            summary.into()
+        }
+    };
+    Ok(output)
+}
+
+/// Summarize text with an LLM provider ("ollama", "openai", "claude", "gemini",
+/// "groq", "openai_compatible" or the embedded "llama")
+pub(crate) async fn summarize_with(
+    app: &AppHandle,
+    provider: &str,
+    model: &str,
+    text: String,
+    language: &str,
+    profile: Option<String>,
+    cancel: &CancellationToken,
+) -> Result<String> {
+    let (lang, name) = (language.to_string(), model.to_string());
+    let started = Instant::now();
+    // Registered under no job id; `cancel` stops them
+    let (session, jobs) = (app.state(), app.state());
+    let summary = match provider {
+        "ollama" => {
+            let request = summarize_text(name, text, lang, None, None, session, jobs);
+            cancellable(cancel, request).await
+        }
+        "openai" => {
+            let request = openai_summarize(text, lang, name, profile, None, None, session, jobs);
+            cancellable(cancel, request).await
+        }
+        "claude" => {
+            let request = claude_summarize(text, lang, name, profile, None, None, session, jobs);
+            cancellable(cancel, request).await
+        }
+        "gemini" => {
+            let request = gemini_summarize(text, lang, name, profile, None, None, session, jobs);
+            cancellable(cancel, request).await
+        }
+        "groq" => {
+            let request = groq_summarize(text, lang, name, profile, None, None, session, jobs);
+            cancellable(cancel, request).await
+        }
+        "openai_compatible" => {
+            let request =
+                openai_compatible_summarize(text, lang, name, profile, None, None, session, jobs);
+            cancellable(cancel, request).await
+        }
+        "llama" => llama_summarize_text(model, &text, language, cancel).await,
+        other => Err(AppError::InvalidInput(format!("Unknown summary provider: {}", other))),
+    };
+    if summary.is_ok() {
+        let attributes = json!({ "provider": provider, "model": model });
+        metrics::record_timing("summary", started, attributes);
+    }
+    summary
+}
+
+/// Add a job to the end of the background queue
+#[tauri::command]
+pub fn enqueue_job(
+    app: AppHandle,
+    spec: JobSpec,
+    queue: State<'_, JobQueueState>,
+) -> Result<QueuedJob> {
+    let job = Database::open()?.enqueue_job(&spec)?;
+    emit_job(&app, &job);
+    queue.wake.notify_one();
+    Ok(job)
+}
+
+/// Queue a transcription job for each file, in the given order
+#[tauri::command]
+pub fn enqueue_transcription(
+    app: AppHandle,
+    file_paths: Vec<String>,
+    options: TranscriptionOptions,
+) -> Result<Vec<QueuedJob>> {
+    file_paths
+        .into_iter()
+        .map(|file_path| {
+            let spec = JobSpec::Transcription {
+                file_path,
+                model_id: options.model_id.clone(),
+                language: options.language.clone(),
+                provider: options.provider.clone(),
+                profile: options.profile.clone(),
+                audio_filters: options.audio_filters.clone(),
+            };
+            queue_job(&app, &spec)
+        })
+        .collect()
+}
+
+/// Add a job to the queue from the backend (e.g. an automatic transcription)
+pub(crate) fn queue_job(app: &AppHandle, spec: &JobSpec) -> Result<QueuedJob> {
+    enqueue_job(app.clone(), spec.clone(), app.state())
+}
+
+/// List the jobs of the background queue in queue order
+#[tauri::command]
+pub fn list_jobs() -> Result<Vec<QueuedJob>> {
+    Database::open()?.job_queue()
+}
+
+/// Status changes of a job in the queue, oldest first
+#[tauri::command]
+pub fn list_job_events(id: String) -> Result<Vec<JobEvent>> {
+    Database::open()?.job_events(&id)
+}
+
+/// Keep a waiting job in the queue without running it
+#[tauri::command]
+pub fn pause_job(app: AppHandle, id: String) -> Result<QueuedJob> {
+    let job = Database::open()?.pause_job(&id)?;
+    emit_job(&app, &job);
+    Ok(job)
+}
+
+/// Let a paused or interrupted job run again, at its place in the queue
+#[tauri::command]
+pub fn resume_job(
+    app: AppHandle,
+    id: String,
+    queue: State<'_, JobQueueState>,
+) -> Result<QueuedJob> {
+    let job = Database::open()?.resume_job(&id)?;
+    emit_job(&app, &job);
+    queue.wake.notify_one();
+    Ok(job)
+}
+
+/// Stop starting queued jobs until `resume_queue`, also after a restart;
+/// running jobs finish. Emits `queue:paused` with the new state.
+#[tauri::command]
+pub fn pause_queue(app: AppHandle, queue: State<'_, JobQueueState>) -> Result<()> {
+    Database::open()?.set_queue_paused(true)?;
+    queue.paused.store(true, Ordering::SeqCst);
+    let _ = app.emit("queue:paused", true);
+    Ok(())
+}
+
+/// Start queued jobs again after `pause_queue`
+#[tauri::command]
+pub fn resume_queue(app: AppHandle, queue: State<'_, JobQueueState>) -> Result<()> {
+    Database::open()?.set_queue_paused(false)?;
+    queue.paused.store(false, Ordering::SeqCst);
+    queue.wake.notify_one();
+    let _ = app.emit("queue:paused", false);
+    Ok(())
+}
+
+/// Whether the queue is paused with `pause_queue`
+#[tauri::command]
+pub fn is_queue_paused(queue: State<'_, JobQueueState>) -> bool {
+    queue.paused.load(Ordering::SeqCst)
+}
+
+/// Move a job to a new position in the queue, returning the reordered queue
+#[tauri::command]
+pub fn move_job(id: String, index: usize) -> Result<Vec<QueuedJob>> {
+    Database::open()?.move_job(&id, index)
+}
+
+/// Cancel a job by id: a queued job of the background queue, an operation
+/// started with a `job_id` (extraction, transcription, download) or a scan.
+/// Running work is interrupted and its partial output removed, as is the
+/// partial file of a model download job that was interrupted.
+/// Returns false if nothing with that id is waiting or running.
+#[tauri::command]
+pub fn cancel_job(
+    app: AppHandle,
+    id: String,
+    running: State<'_, RunningJobs>,
+    scans: State<'_, ScanState>,
+) -> Result<bool> {
+    // Mark a queued job first, so the worker discards the cancelled outcome
+    let db = Database::open()?;
+    let mut cancelled = false;
+    if let Ok(job) = db.queued_job(&id) {
+        if !job.status.is_finished() {
+            if let JobSpec::ModelDownload { model_id } = &job.spec {
+                if job.status != JobStatus::Running {
+                    let (app, model_id) = (app.clone(), model_id.clone());
+                    tauri::async_runtime::spawn(async move {
+                        if let Err(e) = discard_model_partial(&app, &model_id).await {
+                            log::warn!("[job_queue] Failed to remove partial {}: {}", model_id, e);
+                        }
+                    });
+                }
+            }
+            let job = db.cancel_queued_job(&id)?;
+            emit_job(&app, &job);
+            cancelled = true;
+        }
+    }
+
+    cancelled |= running.cancel(&id);
+    cancelled |= scans.cancel(&id);
+    Ok(cancelled)
+}
+
+/// Put a failed, cancelled or interrupted job back at the end of the queue
+#[tauri::command]
+pub fn retry_job(
+    app: AppHandle,
+    id: String,
+    queue: State<'_, JobQueueState>,
+) -> Result<QueuedJob> {
+    let job = Database::open()?.retry_job(&id)?;
+    emit_job(&app, &job);
+    queue.wake.notify_one();
+    Ok(job)
+}
+
+/// Remove completed, failed and cancelled jobs from the queue, returning how many were removed
+#[tauri::command]
+pub fn clear_finished_jobs() -> Result<usize> {
+    Database::open()?.clear_finished_jobs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_running_jobs_cancel_and_unregister() {
+        let jobs = RunningJobs::default();
+        let job = jobs.start(Some("a".to_string()));
+        assert!(!jobs.cancel("b"));
+        assert!(jobs.cancel("a"));
+
+        let pending = std::future::pending::<Result<()>>();
+        let result = cancellable(job.token(), pending).await;
+        assert!(matches!(result, Err(AppError::Cancelled)));
+
+        drop(job);
+        assert!(!jobs.cancel("a"));
+
+        let job = jobs.start_with_id(None);
+        let id = job.id().unwrap().to_string();
+        assert!(jobs.cancel(&id));
+        assert!(job.token().is_cancelled());
+    }
+}
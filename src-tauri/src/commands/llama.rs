@@ -0,0 +1,88 @@
+use crate::commands::analysis::llm_provider;
+use crate::commands::cloud::SessionKeyState;
+use crate::commands::jobs::RunningJobs;
+use crate::commands::progress::ThrottledEmitter;
+use crate::commands::prompts::summarize_with_template;
+use crate::error::Result;
+use crate::services::llm;
+use crate::services::llama::{GgufModel, GgufModelStatus, LlamaService};
+use tauri::{AppHandle, State};
+use tokio_util::sync::CancellationToken;
+
+/// Check if this build includes the embedded llama.cpp backend
+#[tauri::command]
+pub fn is_llama_available() -> bool {
+    LlamaService::is_available()
+}
+
+/// Get list of GGUF models that can be downloaded for offline summarization
+#[tauri::command]
+pub async fn get_available_llama_models() -> Result<Vec<GgufModel>> {
+    Ok(GgufModel::available_models())
+}
+
+/// Get status of all GGUF models (available + installed info)
+#[tauri::command]
+pub async fn get_llama_models_status() -> Result<Vec<GgufModelStatus>> {
+    let service = LlamaService::new()?;
+    service.get_models_status().await
+}
+
+/// Download a GGUF model. Pass a `job_id` to make it cancellable with `cancel_job`.
+#[tauri::command]
+pub async fn download_llama_model(
+    app: AppHandle,
+    model_id: String,
+    job_id: Option<String>,
+    jobs: State<'_, RunningJobs>,
+) -> Result<String> {
+    let job = jobs.start(job_id);
+    let service = LlamaService::new()?;
+
+    let emitter = ThrottledEmitter::new(&app, "llama:download-progress");
+    let result = service.download_model(&model_id, job.token(), move |progress| {
+        emitter.emit(progress);
+    }).await?;
+
+    Ok(result.to_string_lossy().to_string())
+}
+
+/// Delete a downloaded GGUF model
+#[tauri::command]
+pub async fn delete_llama_model(model_id: String) -> Result<()> {
+    let service = LlamaService::new()?;
+    service.delete_model(&model_id).await
+}
+
+/// Summarize text offline with a downloaded GGUF model, with the built-in
+/// prompt or a saved prompt template.
+/// Pass a `job_id` to make it cancellable with `cancel_job`.
+#[tauri::command]
+pub async fn llama_summarize(
+    text: String,
+    language: String,
+    model: String,
+    template_id: Option<i64>,
+    job_id: Option<String>,
+    session: State<'_, SessionKeyState>,
+    jobs: State<'_, RunningJobs>,
+) -> Result<String> {
+    let job = jobs.start(job_id);
+    if let Some(template_id) = template_id {
+        let llm = llm_provider(llm::LLAMA, &model, None, &session)?;
+        return summarize_with_template(llm.as_ref(), template_id, &text, &language, job.token())
+            .await;
+    }
+    llama_summarize_text(&model, &text, &language, job.token()).await
+}
+
+/// Summarize with llama.cpp, stopping when `cancel` is triggered
+pub(crate) async fn llama_summarize_text(
+    model: &str,
+    text: &str,
+    language: &str,
+    cancel: &CancellationToken,
+) -> Result<String> {
+    let service = LlamaService::new()?;
+    service.summarize(model, text, language, cancel).await
+}
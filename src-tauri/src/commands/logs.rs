@@ -0,0 +1,34 @@
+use crate::error::{AppError, Result};
+use crate::logging::{self, LogEntry};
+use log::Level;
+
+/// Entries returned by `get_recent_logs` when no limit is given
+const DEFAULT_RECENT_LOGS: usize = 500;
+
+/// The newest log entries, newest first, for bug reports. `min_level`
+/// ("error", "warn", "info", "debug" or "trace") leaves out less severe entries.
+#[tauri::command]
+pub fn get_recent_logs(limit: Option<usize>, min_level: Option<String>) -> Result<Vec<LogEntry>> {
+    let min_level = min_level
+        .map(|level| {
+            level
+                .parse::<Level>()
+                .map_err(|_| AppError::InvalidInput(format!("Unknown log level: {}", level)))
+        })
+        .transpose()?;
+    logging::recent_logs(
+        &logging::log_dir()?,
+        limit.unwrap_or(DEFAULT_RECENT_LOGS),
+        min_level,
+    )
+}
+
+/// Open the log folder in the file manager, returning its path
+#[tauri::command]
+pub fn open_log_folder() -> Result<String> {
+    let dir = logging::log_dir()?;
+    std::fs::create_dir_all(&dir)?;
+    tauri_plugin_opener::open_path(&dir, None::<&str>)
+        .map_err(|e| AppError::ProcessFailed(format!("Failed to open log folder: {}", e)))?;
+    Ok(dir.to_string_lossy().into_owned())
+}
@@ -0,0 +1,62 @@
+use crate::commands::jobs::queue_job;
+use crate::commands::transcribe::TranscriptionOptions;
+use crate::error::{AppError, Result};
+use crate::services::database::Database;
+use crate::services::job_queue::JobSpec;
+use crate::services::media_import::{import_file, ImportReport, ImportStatus};
+use tauri::AppHandle;
+
+/// Import a batch of files (e.g. dropped on the window): each supported media
+/// file that is not indexed yet, by path or by content, is added to the
+/// database. With `transcribe`, a transcription job is queued for every
+/// imported file. Returns what happened to each file, in the given order.
+#[tauri::command]
+pub async fn import_files(
+    app: AppHandle,
+    paths: Vec<String>,
+    transcribe: Option<TranscriptionOptions>,
+) -> Result<Vec<ImportReport>> {
+    // Hashing reads the files, so keep it off the async runtime
+    let mut reports = tokio::task::spawn_blocking(move || {
+        let db = Database::open()?;
+        Ok::<_, AppError>(
+            paths
+                .iter()
+                .map(|path| import_file(&db, path.trim()))
+                .collect::<Vec<_>>(),
+        )
+    })
+    .await
+    .map_err(|e| AppError::ProcessFailed(format!("Import task failed: {}", e)))??;
+
+    if let Some(options) = transcribe {
+        for report in reports
+            .iter_mut()
+            .filter(|report| report.status == ImportStatus::Imported)
+        {
+            let spec = JobSpec::Transcription {
+                file_path: report.path.clone(),
+                model_id: options.model_id.clone(),
+                language: options.language.clone(),
+                provider: options.provider.clone(),
+                profile: options.profile.clone(),
+                audio_filters: options.audio_filters.clone(),
+            };
+            match queue_job(&app, &spec) {
+                Ok(job) => report.job_id = Some(job.id),
+                Err(e) => report.message = Some(format!("Could not queue transcription: {}", e)),
+            }
+        }
+    }
+
+    let imported = reports
+        .iter()
+        .filter(|report| report.status == ImportStatus::Imported)
+        .count();
+    log::info!(
+        "[import] Imported {} of {} file(s)",
+        imported,
+        reports.len()
+    );
+    Ok(reports)
+}
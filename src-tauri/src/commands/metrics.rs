@@ -0,0 +1,31 @@
+use crate::commands::scope::scoped_path;
+use crate::error::Result;
+use crate::services::database::{Database, Metric, MetricSummary};
+use tauri::AppHandle;
+
+/// Count and timings of each recorded metric, per model
+#[tauri::command]
+pub fn get_metrics_summary() -> Result<Vec<MetricSummary>> {
+    Database::open()?.metrics_summary()
+}
+
+/// Recorded metrics, newest first, optionally of one name
+#[tauri::command]
+pub fn list_metrics(name: Option<String>, limit: Option<usize>) -> Result<Vec<Metric>> {
+    Database::open()?.list_metrics(name.as_deref(), limit)
+}
+
+/// Write all recorded metrics to a JSON file, returning the written path
+#[tauri::command]
+pub async fn export_metrics(app: AppHandle, output_path: String) -> Result<String> {
+    let path = scoped_path(&app, &output_path)?;
+    let metrics = Database::open()?.list_metrics(None, None)?;
+    tokio::fs::write(&path, serde_json::to_string_pretty(&metrics)?).await?;
+    Ok(output_path)
+}
+
+/// Delete all recorded metrics, returning how many were removed
+#[tauri::command]
+pub fn clear_metrics() -> Result<usize> {
+    Database::open()?.clear_metrics()
+}
@@ -1,13 +1,57 @@
+pub mod analysis;
+pub mod backup;
+pub mod benchmark;
 pub mod cloud;
 pub mod directory;
+pub mod embeddings;
+pub mod export;
+pub mod feeds;
 pub mod ffmpeg;
+pub mod jobs;
+pub mod llama;
+pub mod logs;
+pub mod media_import;
+pub mod metrics;
 pub mod models;
+pub mod notifications;
 pub mod ollama;
+pub mod pipeline;
+pub mod processes;
+pub mod progress;
+pub mod project;
+pub mod prompts;
+pub mod scope;
+pub mod settings;
 pub mod transcribe;
+pub mod transcript;
+pub mod updates;
+pub mod usage;
+pub mod ytdlp;
 
+pub use analysis::*;
+pub use backup::*;
+pub use benchmark::*;
 pub use cloud::*;
 pub use directory::*;
+pub use embeddings::*;
+pub use export::*;
+pub use feeds::*;
 pub use ffmpeg::*;
+pub use jobs::*;
+pub use llama::*;
+pub use logs::*;
+pub use media_import::*;
+pub use metrics::*;
 pub use models::*;
+pub use notifications::*;
 pub use ollama::*;
+pub use pipeline::*;
+pub use processes::*;
+pub use project::*;
+pub use prompts::*;
+pub use settings::*;
 pub use transcribe::*;
+pub use transcript::*;
+pub use updates::*;
+pub use usage::*;
+pub use ytdlp::*;
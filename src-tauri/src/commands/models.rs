@@ -1,6 +1,102 @@
-use crate::error::Result;
-use crate::services::{DownloadService, ModelStatus, WhisperModel};
-use tauri::{AppHandle, Emitter};
+use crate::commands::jobs::RunningJobs;
+use crate::commands::notifications::notify_outcome;
+use crate::commands::progress::ThrottledEmitter;
+use crate::error::{AppError, Result};
+use crate::services::{DownloadService, ModelStatus, WhisperModel, WhisperService};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, RwLock};
+use tauri::{AppHandle, Manager, State};
+use tokio_util::sync::CancellationToken;
+
+/// Local services built once at startup and shared by all commands, so the
+/// models folder and the whisper.cpp binary aren't looked up on every call
+pub struct ServiceState {
+    download: DownloadService,
+    whisper: RwLock<Arc<WhisperService>>,
+}
+
+impl ServiceState {
+    pub fn new() -> Self {
+        let download = DownloadService::new();
+        let whisper = WhisperService::new(download.clone());
+        Self {
+            download,
+            whisper: RwLock::new(Arc::new(whisper)),
+        }
+    }
+
+    pub fn download(&self) -> &DownloadService {
+        &self.download
+    }
+
+    pub fn whisper(&self) -> Arc<WhisperService> {
+        self.whisper.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Look for the whisper.cpp binary again, e.g. after installing it
+    pub fn reload_whisper(&self) {
+        let whisper = WhisperService::new(self.download.clone());
+        *self.whisper.write().unwrap_or_else(|e| e.into_inner()) = Arc::new(whisper);
+    }
+}
+
+/// Tokens of a model download in progress
+#[derive(Clone)]
+struct ActiveDownload {
+    cancel: CancellationToken,
+    pause: CancellationToken,
+}
+
+/// Model downloads in progress, by model id, so they can be paused or cancelled
+#[derive(Default)]
+pub struct ModelDownloads {
+    downloads: Mutex<HashMap<String, ActiveDownload>>,
+}
+
+impl ModelDownloads {
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, ActiveDownload>> {
+        self.downloads.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Register the download of `model_id`, stopped as well when `cancel` is.
+    /// It is unregistered when the returned guard is dropped.
+    fn start(&self, model_id: &str, cancel: &CancellationToken) -> Result<ModelDownload<'_>> {
+        let mut downloads = self.lock();
+        if downloads.contains_key(model_id) {
+            return Err(AppError::InvalidInput(format!(
+                "Model {} is already downloading",
+                model_id
+            )));
+        }
+        let download = ActiveDownload {
+            cancel: cancel.child_token(),
+            pause: CancellationToken::new(),
+        };
+        downloads.insert(model_id.to_string(), download.clone());
+        Ok(ModelDownload {
+            downloads: self,
+            model_id: model_id.to_string(),
+            download,
+        })
+    }
+
+    fn get(&self, model_id: &str) -> Option<ActiveDownload> {
+        self.lock().get(model_id).cloned()
+    }
+}
+
+/// A registered model download
+struct ModelDownload<'a> {
+    downloads: &'a ModelDownloads,
+    model_id: String,
+    download: ActiveDownload,
+}
+
+impl Drop for ModelDownload<'_> {
+    fn drop(&mut self) {
+        self.downloads.lock().remove(&self.model_id);
+    }
+}
 
 /// Get list of available Whisper models
 #[tauri::command]
@@ -10,15 +106,14 @@ pub async fn get_available_models() -> Result<Vec<WhisperModel>> {
 
 /// Get list of installed models
 #[tauri::command]
-pub async fn get_installed_models() -> Result<Vec<String>> {
-    let service = DownloadService::new()?;
-    service.get_installed_models().await
+pub async fn get_installed_models(services: State<'_, ServiceState>) -> Result<Vec<String>> {
+    services.download().get_installed_models().await
 }
 
 /// Get status of all models (available + installed info)
 #[tauri::command]
-pub async fn get_models_status() -> Result<Vec<ModelStatus>> {
-    let service = DownloadService::new()?;
+pub async fn get_models_status(services: State<'_, ServiceState>) -> Result<Vec<ModelStatus>> {
+    let service = services.download();
     let installed = service.get_installed_models().await?;
 
     let statuses: Vec<ModelStatus> = WhisperModel::available_models()
@@ -47,34 +142,99 @@ pub async fn get_models_status() -> Result<Vec<ModelStatus>> {
 
 /// Check if a specific model is installed
 #[tauri::command]
-pub async fn is_model_installed(model_id: String) -> Result<bool> {
-    let service = DownloadService::new()?;
-    service.is_model_installed(&model_id).await
+pub async fn is_model_installed(
+    model_id: String,
+    services: State<'_, ServiceState>,
+) -> Result<bool> {
+    services.download().is_model_installed(&model_id).await
 }
 
-/// Download a Whisper model
+/// Download a Whisper model. Pass a `job_id` to make it cancellable with `cancel_job`.
 #[tauri::command]
-pub async fn download_model(app: AppHandle, model_id: String) -> Result<String> {
-    let service = DownloadService::new()?;
+pub async fn download_model(
+    app: AppHandle,
+    model_id: String,
+    job_id: Option<String>,
+    jobs: State<'_, RunningJobs>,
+) -> Result<String> {
+    let job = jobs.start(job_id);
+    let result = download_model_file(&app, &model_id, job.token()).await;
+    // Pausing stops the download as cancelled, so it isn't reported as failed
+    notify_outcome(&app, "Model download", &model_id, &result);
+    result
+}
+
+/// Download a model, stopping when `cancel` is triggered. The download can
+/// also be stopped with `cancel_model_download` and `pause_model_download`.
+pub(crate) async fn download_model_file(
+    app: &AppHandle,
+    model_id: &str,
+    cancel: &CancellationToken,
+) -> Result<String> {
+    let services = app.state::<ServiceState>();
+    let service = services.download();
+    let downloads = app.state::<ModelDownloads>();
+    let active = downloads.start(model_id, cancel)?;
+    let download = &active.download;
 
-    let app_handle = app.clone();
-    let result = service.download_model(&model_id, move |progress| {
-        let _ = app_handle.emit("model:download-progress", progress);
-    }).await?;
+    let emitter = ThrottledEmitter::new(app, "model:download-progress");
+    let result = service
+        .download_model(model_id, &download.cancel, Some(&download.pause), move |progress| {
+            emitter.emit(progress);
+        })
+        .await?;
 
     Ok(result.to_string_lossy().to_string())
 }
 
-/// Delete a downloaded model
+/// Cancel a model download and remove the partial file. The download fails
+/// with a cancelled error. A paused download is discarded too. Returns false
+/// if the model is neither downloading nor paused.
+#[tauri::command]
+pub async fn cancel_model_download(
+    app: AppHandle,
+    model_id: String,
+    downloads: State<'_, ModelDownloads>,
+) -> Result<bool> {
+    if let Some(download) = downloads.get(&model_id) {
+        download.cancel.cancel();
+        return Ok(true);
+    }
+    discard_model_partial(&app, &model_id).await
+}
+
+/// Remove the partial file of a model download that isn't running, e.g. one
+/// that was paused or interrupted. Returns whether there was one.
+pub(crate) async fn discard_model_partial(app: &AppHandle, model_id: &str) -> Result<bool> {
+    if app.state::<ModelDownloads>().get(model_id).is_some() {
+        return Ok(false);
+    }
+    app.state::<ServiceState>()
+        .download()
+        .discard_partial(model_id)
+        .await
+}
+
+/// Pause a model download, keeping the partial file: downloading the model
+/// again resumes where it stopped. The paused download fails with a cancelled
+/// error. Returns false if the model is not downloading.
+#[tauri::command]
+pub fn pause_model_download(model_id: String, downloads: State<'_, ModelDownloads>) -> bool {
+    downloads
+        .get(&model_id)
+        .map(|download| download.pause.cancel())
+        .is_some()
+}
+
+/// Delete a downloaded model and the partial file of a paused download
 #[tauri::command]
-pub async fn delete_model(model_id: String) -> Result<()> {
-    let service = DownloadService::new()?;
-    service.delete_model(&model_id).await
+pub async fn delete_model(model_id: String, services: State<'_, ServiceState>) -> Result<()> {
+    services.download().delete_model(&model_id).await
 }
 
 /// Get models directory path
 #[tauri::command]
-pub async fn get_models_directory() -> Result<String> {
-    let path = DownloadService::get_models_directory()?;
+pub async fn get_models_directory(services: State<'_, ServiceState>) -> Result<String> {
+    let path = services.download().models_dir();
     Ok(path.to_string_lossy().to_string())
 }
@@ -0,0 +1,147 @@
+use crate::error::{AppError, Result};
+use crate::services::app_settings::AppSettings;
+use crate::services::job_queue::{JobSpec, JobStatus, QueuedJob};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tauri::{AppHandle, Manager, WindowEvent};
+use tauri_plugin_notification::NotificationExt;
+
+/// Whether the app is in the background (its window doesn't have focus)
+#[derive(Default)]
+pub struct AppFocusState {
+    background: AtomicBool,
+}
+
+impl AppFocusState {
+    /// Follow the main window's focus changes
+    pub fn on_window_event(&self, event: &WindowEvent) {
+        if let WindowEvent::Focused(focused) = event {
+            self.background.store(!focused, Ordering::Relaxed);
+        }
+    }
+
+    pub fn is_background(&self) -> bool {
+        self.background.load(Ordering::Relaxed)
+    }
+}
+
+/// Raise an OS notification about a finished long-running operation.
+/// Nothing is shown while the app has focus (the UI already reports it) or
+/// when job notifications are turned off in the settings.
+pub(crate) fn notify_in_background(app: &AppHandle, title: &str, body: &str) {
+    if !app.state::<AppFocusState>().is_background() {
+        return;
+    }
+    if !AppSettings::load().map(|s| s.job_notifications).unwrap_or(true) {
+        return;
+    }
+    if let Err(e) = app.notification().builder().title(title).body(body).show() {
+        log::warn!("[notifications] Failed to show notification: {}", e);
+    }
+}
+
+/// Notify about an operation run directly (not queued) that completed or
+/// failed, e.g. "Export complete". Cancelled operations get none.
+pub(crate) fn notify_outcome<T>(app: &AppHandle, label: &str, subject: &str, result: &Result<T>) {
+    match result {
+        Ok(_) => notify_in_background(app, &format!("{} complete", label), subject),
+        Err(AppError::Cancelled) => {}
+        Err(e) => {
+            let body = format!("{}: {}", subject, e);
+            notify_in_background(app, &format!("{} failed", label), &body)
+        }
+    }
+}
+
+/// Notify about a queued job that completed or failed
+pub(crate) fn notify_job_finished(app: &AppHandle, job: &QueuedJob) {
+    if let Some((title, body)) = job_notification(job) {
+        notify_in_background(app, &title, &body);
+    }
+}
+
+/// Title and body of the notification for a finished queued job.
+/// Cancelled jobs were stopped by the user and get none.
+fn job_notification(job: &QueuedJob) -> Option<(String, String)> {
+    let label = match &job.spec {
+        JobSpec::Transcription { .. } => "Transcription",
+        JobSpec::AudioExtraction { .. } => "Audio extraction",
+        JobSpec::ModelDownload { .. } => "Model download",
+        JobSpec::Summary { .. } => "Summary",
+    };
+    let subject = match &job.spec {
+        JobSpec::ModelDownload { model_id } => model_id.clone(),
+        JobSpec::Summary {
+            media_path: None,
+            model,
+            ..
+        } => format!("Summary with {}", model),
+        spec => spec.media_path().map(file_name).unwrap_or_default(),
+    };
+
+    match job.status {
+        JobStatus::Completed => Some((format!("{} complete", label), subject)),
+        JobStatus::Failed => {
+            let error = job.error.as_deref().unwrap_or("Unknown error");
+            Some((format!("{} failed", label), format!("{}: {}", subject, error)))
+        }
+        _ => None,
+    }
+}
+
+/// File name of a path, for notification bodies
+pub(crate) fn file_name(path: &str) -> String {
+    Path::new(path)
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job(spec: JobSpec, status: JobStatus, error: Option<&str>) -> QueuedJob {
+        QueuedJob {
+            id: "job-1".to_string(),
+            spec,
+            status,
+            position: 0,
+            attempts: 1,
+            error: error.map(str::to_string),
+            result: None,
+            created_at: 0,
+            started_at: None,
+            finished_at: None,
+        }
+    }
+
+    #[test]
+    fn test_job_notification() {
+        let transcription = JobSpec::Transcription {
+            file_path: "/media/interview.mp4".to_string(),
+            model_id: "base".to_string(),
+            language: None,
+            provider: None,
+            profile: None,
+            audio_filters: Default::default(),
+        };
+        assert_eq!(
+            job_notification(&job(transcription.clone(), JobStatus::Completed, None)),
+            Some(("Transcription complete".to_string(), "interview.mp4".to_string()))
+        );
+        assert_eq!(
+            job_notification(&job(transcription.clone(), JobStatus::Failed, Some("No audio"))),
+            Some(("Transcription failed".to_string(), "interview.mp4: No audio".to_string()))
+        );
+        assert_eq!(job_notification(&job(transcription, JobStatus::Cancelled, None)), None);
+
+        let download = JobSpec::ModelDownload {
+            model_id: "large-v3".to_string(),
+        };
+        assert_eq!(
+            job_notification(&job(download, JobStatus::Completed, None)),
+            Some(("Model download complete".to_string(), "large-v3".to_string()))
+        );
+    }
+}
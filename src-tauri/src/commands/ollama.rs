@@ -1,5 +1,13 @@
+use crate::commands::analysis::llm_provider;
+use crate::commands::cloud::SessionKeyState;
+use crate::commands::jobs::RunningJobs;
+use crate::commands::prompts::{chapters_with_template, summarize_with_template};
 use crate::error::Result;
+use crate::services::chapters::YouTubeChapters;
+use crate::services::cancellation::cancellable;
+use crate::services::llm;
 use crate::services::{ChatMessage, OllamaModel, OllamaService, StorySegment, TranscriptionSegment};
+use tauri::State;
 
 /// Check if Ollama is running
 #[tauri::command]
@@ -29,21 +37,67 @@ pub async fn ollama_chat(model: String, messages: Vec<ChatMessage>) -> Result<St
     service.chat(&model, messages).await
 }
 
-/// Summarize text using Ollama
+/// Summarize text using Ollama, with the built-in prompt or a saved prompt template.
+/// Pass a `job_id` to make it cancellable with `cancel_job`.
 #[tauri::command]
-pub async fn summarize_text(model: String, text: String, language: String) -> Result<String> {
+pub async fn summarize_text(
+    model: String,
+    text: String,
+    language: String,
+    template_id: Option<i64>,
+    job_id: Option<String>,
+    session: State<'_, SessionKeyState>,
+    jobs: State<'_, RunningJobs>,
+) -> Result<String> {
+    let job = jobs.start(job_id);
+    if let Some(template_id) = template_id {
+        let llm = llm_provider(llm::OLLAMA, &model, None, &session)?;
+        return summarize_with_template(llm.as_ref(), template_id, &text, &language, job.token())
+            .await;
+    }
     let service = OllamaService::new();
-    service.summarize(&model, &text, &language).await
+    let request = service.summarize(&model, &text, &language);
+    cancellable(job.token(), request).await
 }
 
-/// Extract story order from transcription segments
+/// Extract story order from transcription segments.
+/// Pass a `job_id` to make it cancellable with `cancel_job`.
 #[tauri::command]
 pub async fn extract_story_order(
     model: String,
     segments: Vec<TranscriptionSegment>,
+    job_id: Option<String>,
+    jobs: State<'_, RunningJobs>,
 ) -> Result<Vec<StorySegment>> {
+    let job = jobs.start(job_id);
+    let service = OllamaService::new();
+    let request = service.extract_story_order(&model, &segments);
+    cancellable(job.token(), request).await
+}
+
+/// Generate a YouTube chapter list from transcription segments, with the
+/// built-in prompt or a saved prompt template.
+/// Pass a `job_id` to make it cancellable with `cancel_job`.
+#[tauri::command]
+pub async fn generate_youtube_chapters(
+    model: String,
+    segments: Vec<TranscriptionSegment>,
+    language: String,
+    template_id: Option<i64>,
+    job_id: Option<String>,
+    session: State<'_, SessionKeyState>,
+    jobs: State<'_, RunningJobs>,
+) -> Result<YouTubeChapters> {
+    let job = jobs.start(job_id);
+    if let Some(template_id) = template_id {
+        let llm = llm_provider(llm::OLLAMA, &model, None, &session)?;
+        return chapters_with_template(llm.as_ref(), template_id, &segments, &language, job.token())
+            .await;
+    }
     let service = OllamaService::new();
-    service.extract_story_order(&model, &segments).await
+    let request = service.generate_chapters(&model, &segments, &language);
+    let chapters = cancellable(job.token(), request).await?;
+    Ok(YouTubeChapters::new(chapters))
 }
 
 /// Pull/download an Ollama model
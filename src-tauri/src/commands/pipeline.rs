@@ -0,0 +1,398 @@
+use crate::commands::cloud::SessionKeyState;
+use crate::commands::jobs::{summarize_with, RunningJobs};
+use crate::commands::models::ServiceState;
+use crate::commands::notifications::{file_name, notify_in_background};
+use crate::commands::progress::ThrottledEmitter;
+use crate::commands::project::record_transcription;
+use crate::commands::scope::scoped_path;
+use crate::commands::transcribe::{transcribe_file_with_progress, transcription_provider};
+use crate::error::{AppError, Result};
+use crate::services::app_settings::AppSettings;
+use crate::services::audio_filters::AudioFilters;
+use crate::services::database::{Database, SummaryInput};
+use crate::services::export::{
+    to_csv, to_markdown, to_segments_json, to_text, to_vtt, MarkdownOptions, VttOptions,
+};
+use crate::services::hooks::{has_hooks, run_hooks, HookPayload, HookStage};
+use crate::services::pdf_export::{render_pdf_report, PdfReportOptions};
+use crate::services::transcription_provider::TranscriptionProvider;
+use crate::services::{metrics, usage};
+use crate::services::TranscriptionResult;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Instant;
+use tauri::{AppHandle, State};
+use tokio::io::AsyncWriteExt;
+use tokio_util::sync::CancellationToken;
+
+/// Share of the overall progress taken by audio extraction and transcription
+/// when a summary follows, and without one
+const TRANSCRIBE_SHARE_WITH_SUMMARY: f32 = 75.0;
+const TRANSCRIBE_SHARE: f32 = 95.0;
+
+/// Where the summary stage ends; exports take the rest
+const SUMMARY_END: f32 = 95.0;
+
+/// A file `process_media` writes after transcription
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PipelineExport {
+    Text,
+    Markdown,
+    Vtt,
+    Csv,
+    Json,
+    Pdf,
+}
+
+impl PipelineExport {
+    fn extension(self) -> &'static str {
+        match self {
+            Self::Text => "txt",
+            Self::Markdown => "md",
+            Self::Vtt => "vtt",
+            Self::Csv => "csv",
+            Self::Json => "json",
+            Self::Pdf => "pdf",
+        }
+    }
+}
+
+/// Summary stage of `process_media`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PipelineSummary {
+    /// LLM provider, as for summary jobs ("ollama", "openai", "claude", ...)
+    pub provider: String,
+    pub model: String,
+    /// Summary language; by default the language of the transcript
+    #[serde(default)]
+    pub language: Option<String>,
+}
+
+/// What `process_media` does after extracting the audio
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PipelineOptions {
+    /// Whisper model id, or a model of the transcription provider
+    pub model_id: String,
+    #[serde(default)]
+    pub language: Option<String>,
+    /// Transcription provider (local whisper.cpp by default)
+    #[serde(default)]
+    pub provider: Option<String>,
+    /// Credential profile used for the cloud providers
+    #[serde(default)]
+    pub profile: Option<String>,
+    /// Cleanup of the audio before transcription
+    #[serde(default)]
+    pub audio_filters: AudioFilters,
+    /// Summarize the transcript (and save the summary) when set
+    #[serde(default)]
+    pub summary: Option<PipelineSummary>,
+    #[serde(default)]
+    pub exports: Vec<PipelineExport>,
+    /// Folder the exports are written to; by default next to the media file
+    #[serde(default)]
+    pub output_dir: Option<String>,
+    /// Replace exports that already exist; otherwise the pipeline fails
+    /// before doing any work
+    #[serde(default)]
+    pub overwrite: bool,
+}
+
+/// Everything `process_media` produced
+#[derive(Debug, Clone, Serialize)]
+pub struct PipelineResult {
+    pub transcription: TranscriptionResult,
+    pub summary: Option<String>,
+    /// Paths of the written exports
+    pub exports: Vec<String>,
+}
+
+/// Progress of `process_media`, emitted as `pipeline:progress`
+#[derive(Debug, Clone, Serialize)]
+pub struct PipelineProgress {
+    pub file_path: String,
+    /// "extracting", "transcribing", "summarizing", "exporting", "post_processing"
+    /// or "complete"
+    pub stage: String,
+    /// Overall progress, 0-100
+    pub progress: f32,
+    pub message: String,
+}
+
+/// Extract the audio of a media file, transcribe it, optionally summarize it and
+/// write the requested exports, reporting all stages as `pipeline:progress`.
+/// The post-processing hooks from the settings run after their stages.
+/// Pass a `job_id` to make it cancellable with `cancel_job`.
+#[tauri::command]
+pub async fn process_media(
+    app: AppHandle,
+    file_path: String,
+    options: PipelineOptions,
+    job_id: Option<String>,
+    session: State<'_, SessionKeyState>,
+    services: State<'_, ServiceState>,
+    jobs: State<'_, RunningJobs>,
+) -> Result<PipelineResult> {
+    let provider = transcription_provider(
+        options.provider.as_deref(),
+        &options.model_id,
+        options.profile.as_deref(),
+        &session,
+        &services,
+    )?;
+    let job = jobs.start(job_id);
+    let started = Instant::now();
+    let result = run_pipeline(&app, &file_path, &options, provider.as_ref(), job.token()).await;
+    let name = file_name(&file_path);
+    match &result {
+        Ok(_) => {
+            let attributes = json!({
+                "provider": provider.id(),
+                "model": provider.model(),
+                "summary": options.summary.is_some(),
+                "exports": options.exports,
+            });
+            metrics::record_timing("process_media", started, attributes);
+            notify_in_background(&app, "Processing complete", &name)
+        }
+        Err(AppError::Cancelled) => {}
+        Err(e) => notify_in_background(&app, "Processing failed", &format!("{}: {}", name, e)),
+    }
+    result
+}
+
+async fn run_pipeline(
+    app: &AppHandle,
+    file_path: &str,
+    options: &PipelineOptions,
+    provider: &dyn TranscriptionProvider,
+    cancel: &CancellationToken,
+) -> Result<PipelineResult> {
+    let hooks = AppSettings::load()?.post_processing_hooks;
+    let export_paths = export_paths(app, file_path, options)?;
+    let emit = {
+        let emitter = ThrottledEmitter::new(app, "pipeline:progress");
+        let file_path = file_path.to_string();
+        move |stage: &str, progress: f32, message: &str| {
+            emitter.emit(PipelineProgress {
+                file_path: file_path.clone(),
+                stage: stage.to_string(),
+                progress,
+                message: message.to_string(),
+            });
+        }
+    };
+
+    // Stages 1 and 2: extract audio and transcribe
+    let share = match options.summary {
+        Some(_) => TRANSCRIBE_SHARE_WITH_SUMMARY,
+        None => TRANSCRIBE_SHARE,
+    };
+    let transcribe_emit = emit.clone();
+    let report = Arc::new(move |stage: &str, progress: f32, message: &str| {
+        // The transcription's own "complete" is only the end of this stage
+        let stage = if stage == "complete" { "transcribing" } else { stage };
+        transcribe_emit(stage, progress * share / 100.0, message);
+    });
+    let transcription = transcribe_file_with_progress(
+        file_path,
+        provider,
+        options.language.as_deref(),
+        &options.audio_filters,
+        cancel,
+        report,
+    )
+    .await?;
+    let transcription = if has_hooks(&hooks, HookStage::Transcription) {
+        emit("post_processing", share, "Running post-processing hooks...");
+        let payload = HookPayload {
+            stage: HookStage::Transcription,
+            file_path: file_path.to_string(),
+            transcription,
+            summary: None,
+            exports: Vec::new(),
+        };
+        let processed = run_hooks(&hooks, payload, cancel).await?.transcription;
+        // Keep the raw run in the history and make the processed one the latest
+        let model = format!("{} (post-processed)", provider.model());
+        record_transcription(file_path, &processed, &model);
+        processed
+    } else {
+        transcription
+    };
+
+    // Stage 3: summarize
+    let summary = match &options.summary {
+        Some(step) => {
+            emit("summarizing", share, &format!("Summarizing with {}...", step.model));
+            let language = step
+                .language
+                .clone()
+                .or_else(|| transcription.language.clone())
+                .unwrap_or_else(|| "auto".to_string());
+            let text = transcription.full_text.clone();
+            let summarize = summarize_with(
+                app,
+                &step.provider,
+                &step.model,
+                text,
+                &language,
+                options.profile.clone(),
+                cancel,
+            );
+            let summary = usage::for_media(file_path, summarize).await?;
+            let summary = if has_hooks(&hooks, HookStage::Summary) {
+                emit("post_processing", share, "Running post-processing hooks...");
+                let payload = HookPayload {
+                    stage: HookStage::Summary,
+                    file_path: file_path.to_string(),
+                    transcription: transcription.clone(),
+                    summary: Some(summary),
+                    exports: Vec::new(),
+                };
+                run_hooks(&hooks, payload, cancel).await?.summary.unwrap_or_default()
+            } else {
+                summary
+            };
+            let input = SummaryInput {
+                text: summary.clone(),
+                language: Some(language),
+                provider: Some(step.provider.clone()),
+                model: Some(step.model.clone()),
+            };
+            Database::open()?.save_summary(file_path, &input)?;
+            Some(summary)
+        }
+        None => None,
+    };
+
+    // Stage 4: export
+    let mut exports = Vec::new();
+    if !export_paths.is_empty() {
+        emit("exporting", SUMMARY_END, "Writing exports...");
+        for (export, path) in export_paths {
+            if let Some(dir) = path.parent() {
+                tokio::fs::create_dir_all(dir).await?;
+            }
+            let bytes = render_export(export, file_path, &transcription, summary.as_deref())?;
+            // Also refuses a file that appeared while the pipeline ran
+            let mut file = tokio::fs::OpenOptions::new()
+                .write(true)
+                .create(true)
+                .truncate(true)
+                .create_new(!options.overwrite)
+                .open(&path)
+                .await?;
+            file.write_all(&bytes).await?;
+            exports.push(path.to_string_lossy().into_owned());
+        }
+    }
+    if has_hooks(&hooks, HookStage::Export) {
+        emit("post_processing", SUMMARY_END, "Running post-processing hooks...");
+        let payload = HookPayload {
+            stage: HookStage::Export,
+            file_path: file_path.to_string(),
+            transcription: transcription.clone(),
+            summary: summary.clone(),
+            exports: exports.clone(),
+        };
+        run_hooks(&hooks, payload, cancel).await?;
+    }
+
+    emit("complete", 100.0, "Processing complete");
+    Ok(PipelineResult {
+        transcription,
+        summary,
+        exports,
+    })
+}
+
+/// Where the requested exports are written. Without `overwrite`, an existing
+/// file fails the pipeline here, before the long stages run.
+fn export_paths(
+    app: &AppHandle,
+    file_path: &str,
+    options: &PipelineOptions,
+) -> Result<Vec<(PipelineExport, PathBuf)>> {
+    if options.exports.is_empty() {
+        return Ok(Vec::new());
+    }
+    let output_dir = match &options.output_dir {
+        Some(dir) => scoped_path(app, dir)?,
+        None => Path::new(file_path)
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_default(),
+    };
+    let paths: Vec<_> = options
+        .exports
+        .iter()
+        .map(|&export| (export, export_path(&output_dir, file_path, export)))
+        .collect();
+    if !options.overwrite {
+        if let Some((_, path)) = paths.iter().find(|(_, path)| path.exists()) {
+            return Err(AppError::InvalidPath(format!(
+                "Export already exists: {} (set overwrite to replace it)",
+                path.display()
+            )));
+        }
+    }
+    Ok(paths)
+}
+
+/// `<output_dir>/<media file stem>.<extension>`
+fn export_path(output_dir: &Path, file_path: &str, export: PipelineExport) -> PathBuf {
+    let stem = Path::new(file_path)
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "transcript".to_string());
+    output_dir.join(format!("{}.{}", stem, export.extension()))
+}
+
+fn render_export(
+    export: PipelineExport,
+    file_path: &str,
+    transcription: &TranscriptionResult,
+    summary: Option<&str>,
+) -> Result<Vec<u8>> {
+    let segments = &transcription.segments;
+    let title = Path::new(file_path)
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned());
+    Ok(match export {
+        PipelineExport::Text => to_text(segments).into_bytes(),
+        PipelineExport::Markdown => {
+            let options = MarkdownOptions {
+                title,
+                summary: summary.map(str::to_string),
+                ..Default::default()
+            };
+            to_markdown(segments, &options).into_bytes()
+        }
+        PipelineExport::Vtt => to_vtt(segments, &VttOptions::default()).into_bytes(),
+        PipelineExport::Csv => to_csv(segments).into_bytes(),
+        PipelineExport::Json => to_segments_json(segments)?.into_bytes(),
+        PipelineExport::Pdf => {
+            let options = PdfReportOptions {
+                title,
+                summary: summary.map(str::to_string),
+                ..Default::default()
+            };
+            render_pdf_report(segments, &options)?
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_export_path() {
+        let path = export_path(Path::new("/out"), "/media/talk.final.mp4", PipelineExport::Vtt);
+        assert_eq!(path, Path::new("/out/talk.final.vtt"));
+    }
+}
@@ -0,0 +1,15 @@
+use crate::services::processes::{self, ActiveProcess};
+
+/// The ffmpeg, whisper.cpp, yt-dlp and hook processes running right now,
+/// longest-running first
+#[tauri::command]
+pub fn list_active_processes() -> Vec<ActiveProcess> {
+    processes::active_processes()
+}
+
+/// Stop a running process (and the job it belongs to) by its pid.
+/// Returns false if no such process is running.
+#[tauri::command]
+pub fn terminate_process(pid: u32) -> bool {
+    processes::terminate(pid)
+}
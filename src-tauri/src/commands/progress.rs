@@ -0,0 +1,195 @@
+use serde::Serialize;
+use std::sync::{Arc, Mutex, Weak};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+/// Minimum time between two progress events of one operation (10 per second)
+pub(crate) const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+/// What to do with a progress update offered to a [`Throttle`]
+#[derive(Debug, PartialEq)]
+enum Offer<T> {
+    /// Send this payload now
+    Send(T),
+    /// Held back; send the latest held payload after this delay
+    Schedule(Duration),
+    /// Held back; a flush is already scheduled
+    Hold,
+}
+
+/// Rate limiting of one operation's progress updates that never loses the
+/// latest one: updates arriving too soon are held and the newest is sent once
+/// the interval has passed
+#[derive(Debug)]
+struct Throttle<T> {
+    last_sent: Option<Instant>,
+    pending: Option<T>,
+    flush_scheduled: bool,
+}
+
+impl<T> Default for Throttle<T> {
+    fn default() -> Self {
+        Self {
+            last_sent: None,
+            pending: None,
+            flush_scheduled: false,
+        }
+    }
+}
+
+impl<T> Throttle<T> {
+    fn offer(&mut self, payload: T, now: Instant) -> Offer<T> {
+        let elapsed = self.last_sent.map(|sent| now.duration_since(sent));
+        match elapsed {
+            Some(elapsed) if elapsed < PROGRESS_INTERVAL => {
+                self.pending = Some(payload);
+                if self.flush_scheduled {
+                    Offer::Hold
+                } else {
+                    self.flush_scheduled = true;
+                    Offer::Schedule(PROGRESS_INTERVAL - elapsed)
+                }
+            }
+            _ => {
+                // Anything held is older than this update
+                self.pending = None;
+                self.last_sent = Some(now);
+                Offer::Send(payload)
+            }
+        }
+    }
+
+    /// The held payload, once its scheduled flush is due
+    fn flush(&mut self, now: Instant) -> Option<T> {
+        self.flush_scheduled = false;
+        let payload = self.pending.take()?;
+        self.last_sent = Some(now);
+        Some(payload)
+    }
+
+    /// The held payload, sent early because the operation is over
+    fn finish(&mut self) -> Option<T> {
+        self.flush_scheduled = false;
+        self.pending.take()
+    }
+}
+
+struct Inner<T: Serialize + Clone> {
+    app: AppHandle,
+    event: &'static str,
+    throttle: Mutex<Throttle<T>>,
+}
+
+impl<T: Serialize + Clone> Inner<T> {
+    /// Called with the throttle locked, so a flush can't overtake a newer update
+    fn send(&self, payload: T) {
+        let _ = self.app.emit(self.event, payload);
+    }
+}
+
+impl<T: Serialize + Clone> Drop for Inner<T> {
+    /// Scheduled flushes only hold a weak reference, so once the last emitter
+    /// is gone the held update is sent here, before the operation's result
+    fn drop(&mut self) {
+        let throttle = self.throttle.get_mut().unwrap_or_else(|e| e.into_inner());
+        if let Some(payload) = throttle.finish() {
+            self.send(payload);
+        }
+    }
+}
+
+/// Emits one operation's progress events at most every [`PROGRESS_INTERVAL`],
+/// so chunk- and line-level callbacks don't flood the IPC bridge.
+/// The last update is always delivered, at most one interval late and no
+/// later than the drop of the last clone. Clones share the same throttle.
+pub(crate) struct ThrottledEmitter<T: Serialize + Clone> {
+    inner: Arc<Inner<T>>,
+}
+
+impl<T: Serialize + Clone> Clone for ThrottledEmitter<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+impl<T: Serialize + Clone + Send + 'static> ThrottledEmitter<T> {
+    pub(crate) fn new(app: &AppHandle, event: &'static str) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                app: app.clone(),
+                event,
+                throttle: Mutex::new(Throttle::default()),
+            }),
+        }
+    }
+
+    pub(crate) fn emit(&self, payload: T) {
+        let mut throttle = self.inner.throttle.lock().unwrap_or_else(|e| e.into_inner());
+        match throttle.offer(payload, Instant::now()) {
+            Offer::Send(payload) => self.inner.send(payload),
+            Offer::Schedule(delay) => {
+                let inner = Arc::downgrade(&self.inner);
+                tauri::async_runtime::spawn(async move {
+                    tokio::time::sleep(delay).await;
+                    // The drop of the last emitter already sent the held update
+                    let Some(inner) = Weak::upgrade(&inner) else {
+                        return;
+                    };
+                    let mut throttle = inner.throttle.lock().unwrap_or_else(|e| e.into_inner());
+                    if let Some(payload) = throttle.flush(Instant::now()) {
+                        inner.send(payload);
+                    }
+                });
+            }
+            Offer::Hold => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_updates_within_interval_are_held() {
+        let mut throttle = Throttle::default();
+        let start = Instant::now();
+        assert_eq!(throttle.offer(1, start), Offer::Send(1));
+
+        let soon = start + Duration::from_millis(40);
+        assert_eq!(throttle.offer(2, soon), Offer::Schedule(Duration::from_millis(60)));
+        assert_eq!(throttle.offer(3, soon), Offer::Hold);
+
+        // Only the newest held update is sent
+        assert_eq!(throttle.flush(start + PROGRESS_INTERVAL), Some(3));
+        assert_eq!(throttle.flush(start + PROGRESS_INTERVAL), None);
+    }
+
+    #[test]
+    fn test_newer_update_replaces_held_one() {
+        let mut throttle = Throttle::default();
+        let start = Instant::now();
+        assert_eq!(throttle.offer(1, start), Offer::Send(1));
+        assert!(matches!(throttle.offer(2, start), Offer::Schedule(_)));
+
+        // An update after the interval goes out directly; the stale one is dropped
+        let later = start + PROGRESS_INTERVAL;
+        assert_eq!(throttle.offer(3, later), Offer::Send(3));
+        assert_eq!(throttle.flush(later), None);
+    }
+
+    #[test]
+    fn test_finish_takes_held_update() {
+        let mut throttle = Throttle::default();
+        let start = Instant::now();
+        assert_eq!(throttle.offer(1, start), Offer::Send(1));
+        assert!(matches!(throttle.offer(2, start), Offer::Schedule(_)));
+
+        // The scheduled flush finds nothing left to send
+        assert_eq!(throttle.finish(), Some(2));
+        assert_eq!(throttle.flush(start + PROGRESS_INTERVAL), None);
+        assert_eq!(throttle.finish(), None);
+    }
+}
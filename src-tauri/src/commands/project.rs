@@ -0,0 +1,224 @@
+use crate::error::Result;
+use crate::services::assemblyai::DetectedEntity;
+use crate::services::database::{
+    Bookmark, BookmarkInput, Collection, Database, Entity, JobRecord, StoredMention,
+    StoredStoryOrder, StoredSummary, StoredTranscription, SummaryInput, Tag, TranscriptionRun,
+};
+use crate::services::entities::from_detected;
+use crate::services::{StorySegment, TranscriptionResult};
+
+/// Number of jobs returned by `list_job_history` when no limit is given
+const DEFAULT_JOB_HISTORY_LIMIT: usize = 100;
+
+/// Number of runs returned by `list_transcription_history` when no limit is given
+const DEFAULT_TRANSCRIPTION_HISTORY_LIMIT: usize = 100;
+
+/// Add a finished transcription run to the history.
+/// Failures are only logged; they must not fail the transcription itself.
+pub(crate) fn record_transcription(path: &str, result: &TranscriptionResult, model: &str) {
+    let saved = Database::open().and_then(|db| db.save_transcription(path, result, Some(model)));
+    if let Err(e) = saved {
+        log::warn!("[record_transcription] Failed to save history for {}: {}", path, e);
+    }
+}
+
+/// Save a transcription result for a media file
+#[tauri::command]
+pub fn save_transcription(
+    path: String,
+    result: TranscriptionResult,
+    model: Option<String>,
+) -> Result<i64> {
+    Database::open()?.save_transcription(&path, &result, model.as_deref())
+}
+
+/// Load the most recent saved transcription of a media file
+#[tauri::command]
+pub fn load_transcription(path: String) -> Result<Option<StoredTranscription>> {
+    Database::open()?.latest_transcription(&path)
+}
+
+/// List past transcription runs, newest first, of one file or of all files
+#[tauri::command]
+pub fn list_transcription_history(
+    path: Option<String>,
+    limit: Option<usize>,
+) -> Result<Vec<TranscriptionRun>> {
+    Database::open()?.transcription_history(
+        path.as_deref(),
+        limit.unwrap_or(DEFAULT_TRANSCRIPTION_HISTORY_LIMIT),
+    )
+}
+
+/// Load a previous transcription run by id instead of transcribing again
+#[tauri::command]
+pub fn load_transcription_run(id: i64) -> Result<Option<StoredTranscription>> {
+    Database::open()?.transcription(id)
+}
+
+/// Save a summary for a media file
+#[tauri::command]
+pub fn save_summary(path: String, summary: SummaryInput) -> Result<i64> {
+    Database::open()?.save_summary(&path, &summary)
+}
+
+/// Load the most recent saved summary of a media file
+#[tauri::command]
+pub fn load_summary(path: String) -> Result<Option<StoredSummary>> {
+    Database::open()?.latest_summary(&path)
+}
+
+/// Save a story order for a media file
+#[tauri::command]
+pub fn save_story_order(path: String, segments: Vec<StorySegment>) -> Result<i64> {
+    Database::open()?.save_story_order(&path, &segments)
+}
+
+/// Load the most recent saved story order of a media file
+#[tauri::command]
+pub fn load_story_order(path: String) -> Result<Option<StoredStoryOrder>> {
+    Database::open()?.latest_story_order(&path)
+}
+
+/// Add or update an entry in the job history
+#[tauri::command]
+pub fn save_job_record(job: JobRecord) -> Result<()> {
+    Database::open()?.record_job(&job)
+}
+
+/// Get the most recent jobs, newest first
+#[tauri::command]
+pub fn list_job_history(limit: Option<usize>) -> Result<Vec<JobRecord>> {
+    Database::open()?.list_jobs(limit.unwrap_or(DEFAULT_JOB_HISTORY_LIMIT))
+}
+
+/// Create a tag (or get the existing one with the same name)
+#[tauri::command]
+pub fn create_tag(name: String, color: Option<String>) -> Result<Tag> {
+    Database::open()?.create_tag(&name, color.as_deref())
+}
+
+/// Rename or recolor a tag
+#[tauri::command]
+pub fn update_tag(id: i64, name: String, color: Option<String>) -> Result<Tag> {
+    Database::open()?.update_tag(id, &name, color.as_deref())
+}
+
+/// Delete a tag and remove it from all files
+#[tauri::command]
+pub fn delete_tag(id: i64) -> Result<()> {
+    Database::open()?.delete_tag(id)
+}
+
+/// List all tags with their file counts
+#[tauri::command]
+pub fn list_tags() -> Result<Vec<Tag>> {
+    Database::open()?.list_tags()
+}
+
+/// Get the tags of a media file
+#[tauri::command]
+pub fn get_file_tags(path: String) -> Result<Vec<Tag>> {
+    Database::open()?.file_tags(&path)
+}
+
+/// Attach a tag to a media file
+#[tauri::command]
+pub fn tag_media_file(path: String, tag_id: i64) -> Result<()> {
+    Database::open()?.tag_file(&path, tag_id)
+}
+
+/// Remove a tag from a media file
+#[tauri::command]
+pub fn untag_media_file(path: String, tag_id: i64) -> Result<()> {
+    Database::open()?.untag_file(&path, tag_id)
+}
+
+/// Create a collection
+#[tauri::command]
+pub fn create_collection(name: String, description: Option<String>) -> Result<Collection> {
+    Database::open()?.create_collection(&name, description.as_deref())
+}
+
+/// Rename a collection or change its description
+#[tauri::command]
+pub fn update_collection(
+    id: i64,
+    name: String,
+    description: Option<String>,
+) -> Result<Collection> {
+    Database::open()?.update_collection(id, &name, description.as_deref())
+}
+
+/// Delete a collection (its files are kept)
+#[tauri::command]
+pub fn delete_collection(id: i64) -> Result<()> {
+    Database::open()?.delete_collection(id)
+}
+
+/// List all collections with their file counts
+#[tauri::command]
+pub fn list_collections() -> Result<Vec<Collection>> {
+    Database::open()?.list_collections()
+}
+
+/// Get the paths of the files in a collection
+#[tauri::command]
+pub fn get_collection_files(id: i64) -> Result<Vec<String>> {
+    Database::open()?.collection_files(id)
+}
+
+/// Add a media file to a collection
+#[tauri::command]
+pub fn add_to_collection(id: i64, path: String) -> Result<()> {
+    Database::open()?.add_to_collection(id, &path)
+}
+
+/// Remove a media file from a collection
+#[tauri::command]
+pub fn remove_from_collection(id: i64, path: String) -> Result<()> {
+    Database::open()?.remove_from_collection(id, &path)
+}
+
+/// Bookmark a moment (or highlight a range) of a media file
+#[tauri::command]
+pub fn add_bookmark(path: String, bookmark: BookmarkInput) -> Result<Bookmark> {
+    Database::open()?.add_bookmark(&path, &bookmark)
+}
+
+/// Move a bookmark or change its note or color
+#[tauri::command]
+pub fn update_bookmark(id: i64, bookmark: BookmarkInput) -> Result<Bookmark> {
+    Database::open()?.update_bookmark(id, &bookmark)
+}
+
+/// Delete a bookmark
+#[tauri::command]
+pub fn delete_bookmark(id: i64) -> Result<()> {
+    Database::open()?.delete_bookmark(id)
+}
+
+/// Get the bookmarks of a media file in timeline order
+#[tauri::command]
+pub fn list_bookmarks(path: String) -> Result<Vec<Bookmark>> {
+    Database::open()?.bookmarks(&path)
+}
+
+/// Save the entities AssemblyAI detected in a media file, replacing earlier mentions
+#[tauri::command]
+pub fn save_detected_entities(path: String, entities: Vec<DetectedEntity>) -> Result<()> {
+    Database::open()?.save_entity_mentions(&path, &from_detected(&entities))
+}
+
+/// List named entities by number of mentions, optionally of one kind
+/// (`person`, `organization`, `location`, `product`) or one media file
+#[tauri::command]
+pub fn list_entities(kind: Option<String>, path: Option<String>) -> Result<Vec<Entity>> {
+    Database::open()?.list_entities(kind.as_deref(), path.as_deref())
+}
+
+/// List where an entity is mentioned, to jump to each mention
+#[tauri::command]
+pub fn get_entity_mentions(entity_id: i64) -> Result<Vec<StoredMention>> {
+    Database::open()?.entity_mentions(entity_id)
+}
@@ -0,0 +1,154 @@
+use crate::commands::analysis::llm_provider;
+use crate::commands::cloud::SessionKeyState;
+use crate::commands::jobs::RunningJobs;
+use crate::error::Result;
+use crate::services::chapters::{numbered_segments, parse_chapters_response, YouTubeChapters};
+use crate::services::database::{Database, PromptTemplate, PromptTemplateInput};
+use crate::services::llm::LlmProvider;
+use crate::services::prompt_templates::{
+    self, render, transcript_variables, SEGMENTS_VARIABLE, TEXT_VARIABLE, TRANSCRIPT_VARIABLE,
+};
+use crate::services::TranscriptionSegment;
+use std::collections::HashMap;
+use tauri::State;
+use tokio_util::sync::CancellationToken;
+
+/// List prompt templates, optionally of one kind ("summary", "chapters", ...)
+#[tauri::command]
+pub fn list_prompt_templates(kind: Option<String>) -> Result<Vec<PromptTemplate>> {
+    Database::open()?.list_prompt_templates(kind.as_deref())
+}
+
+/// Create a prompt template
+#[tauri::command]
+pub fn create_prompt_template(template: PromptTemplateInput) -> Result<PromptTemplate> {
+    Database::open()?.create_prompt_template(&template)
+}
+
+/// Edit a prompt template
+#[tauri::command]
+pub fn update_prompt_template(id: i64, template: PromptTemplateInput) -> Result<PromptTemplate> {
+    Database::open()?.update_prompt_template(id, &template)
+}
+
+/// Delete a prompt template
+#[tauri::command]
+pub fn delete_prompt_template(id: i64) -> Result<()> {
+    Database::open()?.delete_prompt_template(id)
+}
+
+/// Rendered system instructions and prompt of a template
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct RenderedPrompt {
+    pub system: Option<String>,
+    pub prompt: String,
+}
+
+/// Fill in a template. `{{transcript}}`, `{{text}}`, `{{segments}}` and
+/// `{{language}}` come from the segments and language; `variables` sets the
+/// others (or overrides these).
+fn render_template(
+    template: &PromptTemplate,
+    variables: Option<HashMap<String, String>>,
+    segments: Option<&[TranscriptionSegment]>,
+    language: Option<&str>,
+) -> Result<RenderedPrompt> {
+    let segments = segments.unwrap_or_default();
+    let mut values = transcript_variables(segments, language);
+    values.insert(SEGMENTS_VARIABLE.to_string(), numbered_segments(segments));
+    values.extend(variables.unwrap_or_default());
+    render_values(template, &values)
+}
+
+fn render_values(
+    template: &PromptTemplate,
+    values: &HashMap<String, String>,
+) -> Result<RenderedPrompt> {
+    let system = match &template.system {
+        Some(system) => Some(render(system, values)?),
+        None => None,
+    };
+    Ok(RenderedPrompt {
+        system,
+        prompt: render(&template.prompt, values)?,
+    })
+}
+
+/// Summarize with a saved template in place of the built-in summary prompt.
+/// `{{text}}` and `{{transcript}}` are both the text to summarize.
+pub(crate) async fn summarize_with_template(
+    llm: &dyn LlmProvider,
+    template_id: i64,
+    text: &str,
+    language: &str,
+    cancel: &CancellationToken,
+) -> Result<String> {
+    let template = Database::open()?.prompt_template(template_id)?;
+    let mut values = transcript_variables(&[], Some(language));
+    values.insert(TEXT_VARIABLE.to_string(), text.to_string());
+    values.insert(TRANSCRIPT_VARIABLE.to_string(), text.to_string());
+    let rendered = render_values(&template, &values)?;
+    prompt_templates::run_prompt(llm, rendered.system.as_deref(), &rendered.prompt, cancel).await
+}
+
+/// Generate YouTube chapters with a saved template in place of the built-in
+/// chapters prompt. The template lists the segments with `{{segments}}` and
+/// must ask for the same `[{"index": 0, "title": "..."}]` reply.
+pub(crate) async fn chapters_with_template(
+    llm: &dyn LlmProvider,
+    template_id: i64,
+    segments: &[TranscriptionSegment],
+    language: &str,
+    cancel: &CancellationToken,
+) -> Result<YouTubeChapters> {
+    let template = Database::open()?.prompt_template(template_id)?;
+    let mut values = transcript_variables(segments, Some(language));
+    values.insert(SEGMENTS_VARIABLE.to_string(), numbered_segments(segments));
+    let rendered = render_values(&template, &values)?;
+    let response =
+        prompt_templates::run_prompt(llm, rendered.system.as_deref(), &rendered.prompt, cancel)
+            .await?;
+    Ok(YouTubeChapters::new(parse_chapters_response(&response, segments)?))
+}
+
+/// Preview a template with its variables filled in
+#[tauri::command]
+pub fn render_prompt_template(
+    id: i64,
+    variables: Option<HashMap<String, String>>,
+    segments: Option<Vec<TranscriptionSegment>>,
+    language: Option<String>,
+) -> Result<RenderedPrompt> {
+    let template = Database::open()?.prompt_template(id)?;
+    render_template(&template, variables, segments.as_deref(), language.as_deref())
+}
+
+/// Run a prompt template with any LLM provider and return the reply.
+/// Pass a `job_id` to make it cancellable with `cancel_job`.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn run_prompt_template(
+    id: i64,
+    variables: Option<HashMap<String, String>>,
+    segments: Option<Vec<TranscriptionSegment>>,
+    language: Option<String>,
+    provider: String,
+    model: String,
+    profile: Option<String>,
+    job_id: Option<String>,
+    session: State<'_, SessionKeyState>,
+    jobs: State<'_, RunningJobs>,
+) -> Result<String> {
+    let template = Database::open()?.prompt_template(id)?;
+    let rendered =
+        render_template(&template, variables, segments.as_deref(), language.as_deref())?;
+    let llm = llm_provider(&provider, &model, profile.as_deref(), &session)?;
+    let job = jobs.start(job_id);
+    prompt_templates::run_prompt(
+        llm.as_ref(),
+        rendered.system.as_deref(),
+        &rendered.prompt,
+        job.token(),
+    )
+    .await
+}
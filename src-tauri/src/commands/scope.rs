@@ -0,0 +1,32 @@
+use crate::commands::directory::WatcherState;
+use crate::error::{AppError, Result};
+use crate::services::path_scope;
+use crate::services::temp_files;
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_fs::FsExt;
+
+/// Canonicalize a path from the webview and make sure the user chose it: it
+/// must be in the fs scope (which the dialogs add picked files and folders
+/// to), inside the watched directory, or in the app's temp folder.
+/// Commands that delete, move or write files check their paths with this.
+pub(crate) fn scoped_path(app: &AppHandle, raw: &str) -> Result<PathBuf> {
+    let path = path_scope::canonicalize(raw)?;
+    if app.fs_scope().is_allowed(&path) {
+        return Ok(path);
+    }
+
+    let mut roots = vec![temp_files::root()];
+    if let Some(watched) = app.state::<WatcherState>().watched_path() {
+        roots.push(PathBuf::from(watched));
+    }
+    if path_scope::is_within(&path, &roots) {
+        return Ok(path);
+    }
+
+    log::warn!("[scope] Rejected path outside the allowed folders: {}", path.display());
+    Err(AppError::InvalidPath(format!(
+        "Not inside a folder chosen in the app: {}",
+        path.display()
+    )))
+}
@@ -0,0 +1,23 @@
+use crate::error::Result;
+use crate::messages;
+use crate::services::app_settings::AppSettings;
+use crate::services::{concurrency, http, whisper_server};
+
+/// Get the backend settings
+#[tauri::command]
+pub fn get_app_settings() -> Result<AppSettings> {
+    AppSettings::load()
+}
+
+/// Replace the backend settings
+#[tauri::command]
+pub fn save_app_settings(settings: AppSettings) -> Result<()> {
+    settings.save()?;
+    messages::set_locale(&settings.locale);
+    http::configure(&settings);
+    concurrency::configure(&settings);
+    if !settings.whisper_server_mode {
+        tauri::async_runtime::spawn(whisper_server::stop());
+    }
+    Ok(())
+}
@@ -1,7 +1,36 @@
-use crate::error::Result;
+use crate::commands::cloud::{
+    hosted_api_key, openai_compatible_server, require_api_key, SessionKeyState,
+};
+use crate::commands::directory::ScanState;
+use crate::commands::jobs::{cancel_job, RunningJobs};
+use crate::commands::models::ServiceState;
+use crate::commands::notifications::{file_name, notify_outcome};
+use crate::commands::progress::ThrottledEmitter;
+use crate::commands::project::record_transcription;
+use crate::error::{AppError, Result};
+use crate::services::app_settings::AppSettings;
+use crate::services::assemblyai::AssemblyAIProvider;
+use crate::services::audio_filters::AudioFilters;
+use crate::services::database::Database;
+use crate::services::directory_service::scan_directory;
+use crate::services::providers;
+use crate::services::transcript_diff::{diff_transcripts, TranscriptDiff};
+use crate::services::transcription_provider::{
+    self, LocalWhisperProvider, OpenAIWhisperProvider, TranscriptionProvider,
+};
+use crate::services::whisper_server::{self, ServerStatus};
+use crate::services::{job_queue, metrics, usage};
 use crate::services::{FFmpegService, TranscriptionResult, WhisperService};
-use std::path::PathBuf;
-use tauri::{AppHandle, Emitter};
+use serde_json::json;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Instant;
+use tauri::{AppHandle, Manager, State};
+use tokio_util::sync::CancellationToken;
+
+/// Receives progress as (stage, percent, message)
+pub(crate) type ProgressFn = Arc<dyn Fn(&str, f32, &str) + Send + Sync>;
 
 /// Transcription progress event payload
 #[derive(Clone, serde::Serialize)]
@@ -9,112 +38,534 @@ pub struct TranscriptionProgress {
     pub stage: String,
     pub progress: f32,
     pub message: String,
+    /// Id to pass to `cancel_transcription`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub job_id: Option<String>,
+}
+
+/// How to transcribe media, for commands that transcribe as part of other work
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct TranscriptionOptions {
+    /// Whisper model id, or a model of the transcription provider
+    pub model_id: String,
+    #[serde(default)]
+    pub language: Option<String>,
+    /// Transcription provider (local whisper.cpp by default)
+    #[serde(default)]
+    pub provider: Option<String>,
+    /// Credential profile used for the cloud providers
+    #[serde(default)]
+    pub profile: Option<String>,
+    /// Cleanup of the audio before transcription
+    #[serde(default)]
+    pub audio_filters: AudioFilters,
+}
+
+/// Build a transcription provider: local whisper.cpp (the default), OpenAI, Groq,
+/// a self-hosted OpenAI-compatible server or AssemblyAI.
+/// `model_id` is a local model id or a model of the cloud provider.
+pub(crate) fn transcription_provider(
+    provider: Option<&str>,
+    model_id: &str,
+    profile: Option<&str>,
+    session: &SessionKeyState,
+    services: &ServiceState,
+) -> Result<Box<dyn TranscriptionProvider>> {
+    match provider.unwrap_or(transcription_provider::LOCAL) {
+        transcription_provider::LOCAL => Ok(Box::new(LocalWhisperProvider::new(
+            services.whisper(),
+            model_id,
+        ))),
+        providers::OPENAI => {
+            let api_key = hosted_api_key(session, providers::OPENAI, profile)?;
+            Ok(Box::new(OpenAIWhisperProvider::new(&api_key, model_id)))
+        }
+        providers::GROQ => {
+            let api_key = hosted_api_key(session, providers::GROQ, profile)?;
+            Ok(Box::new(OpenAIWhisperProvider::groq(&api_key, model_id)))
+        }
+        providers::OPENAI_COMPATIBLE => {
+            let (base_url, api_key) = openai_compatible_server(None, profile, session)?;
+            Ok(Box::new(OpenAIWhisperProvider::openai_compatible(
+                &base_url,
+                api_key.as_deref(),
+                model_id,
+            )?))
+        }
+        providers::ASSEMBLYAI => {
+            let api_key = require_api_key(session, providers::ASSEMBLYAI, profile)?;
+            Ok(Box::new(AssemblyAIProvider::new(&api_key)))
+        }
+        other => Err(AppError::InvalidInput(format!(
+            "Unknown transcription provider: {}",
+            other
+        ))),
+    }
 }
 
-/// Transcribe a media file
+/// Transcribe a media file with the given provider (local whisper.cpp by default),
+/// optionally cleaning up the audio with `audio_filters` first.
+/// Cancel it with `cancel_transcription` and the given `job_id`, or the
+/// generated one its progress events carry.
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn transcribe_media(
     app: AppHandle,
     file_path: String,
     model_id: String,
     language: Option<String>,
+    provider: Option<String>,
+    profile: Option<String>,
+    audio_filters: Option<AudioFilters>,
+    job_id: Option<String>,
+    session: State<'_, SessionKeyState>,
+    services: State<'_, ServiceState>,
+    jobs: State<'_, RunningJobs>,
 ) -> Result<TranscriptionResult> {
-    let input_path = PathBuf::from(&file_path);
+    let provider = transcription_provider(
+        provider.as_deref(),
+        &model_id,
+        profile.as_deref(),
+        &session,
+        &services,
+    )?;
+    let job = jobs.start_with_id(job_id);
+    let result = transcribe_file(
+        &app,
+        &file_path,
+        provider.as_ref(),
+        language.as_deref(),
+        &audio_filters.unwrap_or_default(),
+        job.id(),
+        job.token(),
+    )
+    .await;
+    notify_outcome(&app, "Transcription", &file_name(&file_path), &result);
+    result
+}
+
+/// Transcribe a media file, stopping when `cancel` is triggered. Progress
+/// events carry `job_id`.
+pub(crate) async fn transcribe_file(
+    app: &AppHandle,
+    file_path: &str,
+    provider: &dyn TranscriptionProvider,
+    language: Option<&str>,
+    filters: &AudioFilters,
+    job_id: Option<&str>,
+    cancel: &CancellationToken,
+) -> Result<TranscriptionResult> {
+    let report = progress_emitter(app, job_id);
+    transcribe_file_with_progress(file_path, provider, language, filters, cancel, report).await
+}
+
+/// Transcribe a media file, reporting progress to `report` instead of emitting
+/// `transcription:progress`
+pub(crate) async fn transcribe_file_with_progress(
+    file_path: &str,
+    provider: &dyn TranscriptionProvider,
+    language: Option<&str>,
+    filters: &AudioFilters,
+    cancel: &CancellationToken,
+    report: ProgressFn,
+) -> Result<TranscriptionResult> {
+    let input_path = PathBuf::from(file_path);
 
     // Check if the media file has an audio stream
     let media_info = FFmpegService::get_media_info(&input_path).await?;
     if !media_info.has_audio {
-        return Err(crate::error::AppError::FFmpeg(
-            "This video does not contain an audio stream".to_string(),
-        ));
+        log::warn!("[transcribe] No audio stream in {}", file_path);
+        return Err(AppError::NoAudioStream(file_path.to_string()));
     }
 
     // Stage 1: Extract audio
-    emit_progress(&app, "extracting", 0.0, "Extracting audio...");
+    report("extracting", 0.0, "Extracting audio...");
 
-    let temp_dir = std::env::temp_dir().join("clip-flow");
+    let temp_dir = job_queue::temp_dir();
     tokio::fs::create_dir_all(&temp_dir).await?;
 
     let audio_filename = format!("{}.wav", uuid::Uuid::new_v4());
     let audio_path = temp_dir.join(&audio_filename);
 
-    let app_handle = app.clone();
-    FFmpegService::extract_audio(&input_path, &audio_path, move |progress| {
-        emit_progress(&app_handle, "extracting", progress * 0.3, "Extracting audio...");
+    let extract_report = report.clone();
+    let started = Instant::now();
+    FFmpegService::extract_audio(&input_path, &audio_path, filters, cancel, move |progress| {
+        extract_report("extracting", progress * 0.3, "Extracting audio...");
     }).await?;
 
-    emit_progress(&app, "extracting", 30.0, "Audio extraction complete");
+    report("extracting", 30.0, "Audio extraction complete");
+    metrics::record_timing(
+        "audio_extraction",
+        started,
+        json!({ "mediaSeconds": media_info.duration }),
+    );
 
-    // Stage 2: Transcribe with Whisper
-    emit_progress(&app, "transcribing", 30.0, "Starting transcription...");
+    // Stage 2: Transcribe, mapped onto 30-100% of the overall progress
+    let started = Instant::now();
+    let transcribe = run_provider(&report, provider, &audio_path, language, cancel, 30.0);
+    let result = usage::for_media(file_path, transcribe).await;
 
-    let whisper_service = WhisperService::new()?;
-
-    let app_handle = app.clone();
-    let model_name = model_id.clone();
-    let result = whisper_service.transcribe(
-        &audio_path,
-        &model_id,
-        language.as_deref(),
-        move |progress| {
-            let overall_progress = 30.0 + (progress * 0.7);
-            emit_progress(
-                &app_handle,
-                "transcribing",
-                overall_progress,
-                &format!("Transcribing with {}...", model_name),
-            );
-        },
-    ).await?;
-
-    // Cleanup temp audio file
+    // Cleanup temp audio file, also when transcription failed or was cancelled
     let _ = tokio::fs::remove_file(&audio_path).await;
+    let result = result?;
+    metrics::record_timing(
+        "transcription",
+        started,
+        json!({
+            "provider": provider.id(),
+            "model": provider.model(),
+            "audioSeconds": result.duration,
+        }),
+    );
 
-    emit_progress(&app, "complete", 100.0, "Transcription complete");
+    record_transcription(file_path, &result, provider.model());
+    report("complete", 100.0, "Transcription complete");
 
     Ok(result)
 }
 
-/// Transcribe audio file directly (already WAV format)
+/// Transcribe a WAV file with a provider, reporting its progress from `offset` to 100%
+async fn run_provider(
+    report: &ProgressFn,
+    provider: &dyn TranscriptionProvider,
+    audio_path: &Path,
+    language: Option<&str>,
+    cancel: &CancellationToken,
+    offset: f32,
+) -> Result<TranscriptionResult> {
+    report("transcribing", offset, "Starting transcription...");
+
+    let provider_report = report.clone();
+    let message = format!("Transcribing with {}...", provider.model());
+    let scale = (100.0 - offset) / 100.0;
+    provider
+        .transcribe(
+            audio_path,
+            language,
+            cancel,
+            Box::new(move |progress| {
+                let overall_progress = offset + progress * scale;
+                provider_report("transcribing", overall_progress, &message);
+            }),
+        )
+        .await
+}
+
+/// Transcribe audio file directly (already WAV format) with the given provider.
+/// Cancel it with `cancel_transcription` and the given `job_id`, or the
+/// generated one its progress events carry.
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn transcribe_audio(
     app: AppHandle,
     audio_path: String,
     model_id: String,
     language: Option<String>,
+    provider: Option<String>,
+    profile: Option<String>,
+    job_id: Option<String>,
+    session: State<'_, SessionKeyState>,
+    services: State<'_, ServiceState>,
+    jobs: State<'_, RunningJobs>,
 ) -> Result<TranscriptionResult> {
+    let provider = transcription_provider(
+        provider.as_deref(),
+        &model_id,
+        profile.as_deref(),
+        &session,
+        &services,
+    )?;
+    let job = jobs.start_with_id(job_id);
     let audio_path = PathBuf::from(audio_path);
 
-    emit_progress(&app, "transcribing", 0.0, "Starting transcription...");
-
-    let whisper_service = WhisperService::new()?;
-
-    let app_handle = app.clone();
-    let model_name = model_id.clone();
-    let result = whisper_service.transcribe(
+    let report = progress_emitter(&app, job.id());
+    let result = run_provider(
+        &report,
+        provider.as_ref(),
         &audio_path,
-        &model_id,
         language.as_deref(),
-        move |progress| {
-            emit_progress(
-                &app_handle,
-                "transcribing",
-                progress,
-                &format!("Transcribing with {}...", model_name),
-            );
-        },
-    ).await?;
-
-    emit_progress(&app, "complete", 100.0, "Transcription complete");
+        job.token(),
+        0.0,
+    )
+    .await?;
+
+    report("complete", 100.0, "Transcription complete");
 
     Ok(result)
 }
 
+/// One transcription of a comparison, with the time it took
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ComparedTranscription {
+    pub provider: String,
+    pub model: String,
+    pub elapsed_seconds: f64,
+    pub result: TranscriptionResult,
+}
+
+/// Two transcriptions of the same media and where they disagree
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct TranscriptionComparison {
+    pub first: ComparedTranscription,
+    pub second: ComparedTranscription,
+    pub diff: TranscriptDiff,
+}
+
+/// Transcribe a media file with two models or providers (e.g. a local model
+/// and a cloud one), one after the other, and align their words to show where
+/// they disagree. Both transcriptions are kept in the project history.
+/// Cancel it with `cancel_transcription` and the given `job_id`, or the
+/// generated one its progress events carry.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn compare_transcriptions(
+    app: AppHandle,
+    file_path: String,
+    first: TranscriptionOptions,
+    second: TranscriptionOptions,
+    job_id: Option<String>,
+    session: State<'_, SessionKeyState>,
+    services: State<'_, ServiceState>,
+    jobs: State<'_, RunningJobs>,
+) -> Result<TranscriptionComparison> {
+    let provider = |options: &TranscriptionOptions| {
+        transcription_provider(
+            options.provider.as_deref(),
+            &options.model_id,
+            options.profile.as_deref(),
+            &session,
+            &services,
+        )
+    };
+    // Fail on a missing API key before spending time on the first transcription
+    let first_provider = provider(&first)?;
+    let second_provider = provider(&second)?;
+    let job = jobs.start_with_id(job_id);
+    let report = progress_emitter(&app, job.id());
+
+    let first = transcribe_for_comparison(
+        &file_path,
+        &first,
+        first_provider.as_ref(),
+        job.token(),
+        &report,
+        0.0,
+    )
+    .await?;
+    let second = transcribe_for_comparison(
+        &file_path,
+        &second,
+        second_provider.as_ref(),
+        job.token(),
+        &report,
+        50.0,
+    )
+    .await?;
+
+    let diff = diff_transcripts(&first.result.segments, &second.result.segments);
+    Ok(TranscriptionComparison { first, second, diff })
+}
+
+/// Transcribe one side of a comparison, reporting its progress as half of the
+/// overall progress from `offset`
+async fn transcribe_for_comparison(
+    file_path: &str,
+    options: &TranscriptionOptions,
+    provider: &dyn TranscriptionProvider,
+    cancel: &CancellationToken,
+    report: &ProgressFn,
+    offset: f32,
+) -> Result<ComparedTranscription> {
+    let report = report.clone();
+    let half: ProgressFn = Arc::new(move |stage, progress, message| {
+        report(stage, offset + progress / 2.0, message)
+    });
+
+    let started = Instant::now();
+    let result = transcribe_file_with_progress(
+        file_path,
+        provider,
+        options.language.as_deref(),
+        &options.audio_filters,
+        cancel,
+        half,
+    )
+    .await?;
+    Ok(ComparedTranscription {
+        provider: provider.id().to_string(),
+        model: provider.model().to_string(),
+        elapsed_seconds: started.elapsed().as_secs_f64(),
+        result,
+    })
+}
+
+/// Progress of `transcribe_directory`, emitted as `transcription:batch-progress`
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct BatchTranscriptionProgress {
+    /// Id to pass to `cancel_transcription`
+    pub job_id: Option<String>,
+    pub file_path: String,
+    /// Position of the current file (from 1) and number of files to transcribe
+    pub file: usize,
+    pub total: usize,
+    pub stage: String,
+    /// Progress of the current file (0-100)
+    pub file_progress: f32,
+}
+
+/// A file of `transcribe_directory` that could not be transcribed
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct FailedTranscription {
+    pub file_path: String,
+    pub error: String,
+}
+
+/// What `transcribe_directory` did with each media file of the folder
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct DirectoryTranscription {
+    pub transcribed: Vec<String>,
+    /// Files that already had a saved transcription
+    pub skipped: Vec<String>,
+    pub failed: Vec<FailedTranscription>,
+}
+
+/// Transcribe every media file in a folder and its subfolders, one after the
+/// other, skipping files that already have a saved transcription. A file that
+/// fails is reported and the batch goes on. Each result is saved to the
+/// project history. Cancel it with `cancel_transcription` and the given
+/// `job_id`, or the generated one its progress events carry.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn transcribe_directory(
+    app: AppHandle,
+    path: String,
+    options: TranscriptionOptions,
+    max_depth: Option<usize>,
+    job_id: Option<String>,
+    session: State<'_, SessionKeyState>,
+    services: State<'_, ServiceState>,
+    jobs: State<'_, RunningJobs>,
+) -> Result<DirectoryTranscription> {
+    let provider = transcription_provider(
+        options.provider.as_deref(),
+        &options.model_id,
+        options.profile.as_deref(),
+        &session,
+        &services,
+    )?;
+    let job = jobs.start_with_id(job_id);
+
+    let root = PathBuf::from(&path);
+    let files = tokio::task::spawn_blocking(move || scan_directory(&root, max_depth))
+        .await
+        .map_err(|e| AppError::ProcessFailed(format!("Scan task failed: {}", e)))??;
+    let transcribed: HashSet<String> = Database::open()?
+        .transcribed_media_paths()?
+        .into_iter()
+        .collect();
+
+    let mut outcome = DirectoryTranscription::default();
+    let (skipped, pending): (Vec<_>, Vec<_>) = files
+        .into_iter()
+        .map(|file| file.path)
+        .partition(|file_path| transcribed.contains(file_path));
+    outcome.skipped = skipped;
+    log::info!(
+        "[transcribe_directory] Transcribing {} file(s) in {}, {} already transcribed",
+        pending.len(),
+        path,
+        outcome.skipped.len()
+    );
+
+    let emitter = ThrottledEmitter::new(&app, "transcription:batch-progress");
+    let total = pending.len();
+    for (i, file_path) in pending.into_iter().enumerate() {
+        let report: ProgressFn = {
+            let emitter = emitter.clone();
+            let job_id = job.id().map(str::to_string);
+            let file_path = file_path.clone();
+            Arc::new(move |stage, progress, _message| {
+                emitter.emit(BatchTranscriptionProgress {
+                    job_id: job_id.clone(),
+                    file_path: file_path.clone(),
+                    file: i + 1,
+                    total,
+                    stage: stage.to_string(),
+                    file_progress: progress,
+                });
+            })
+        };
+        let result = transcribe_file_with_progress(
+            &file_path,
+            provider.as_ref(),
+            options.language.as_deref(),
+            &options.audio_filters,
+            job.token(),
+            report,
+        )
+        .await;
+        match result {
+            Ok(_) => outcome.transcribed.push(file_path),
+            Err(AppError::Cancelled) => return Err(AppError::Cancelled),
+            Err(e) => {
+                log::warn!("[transcribe_directory] Failed to transcribe {}: {}", file_path, e);
+                outcome.failed.push(FailedTranscription {
+                    file_path,
+                    error: e.to_string(),
+                });
+            }
+        }
+    }
+    Ok(outcome)
+}
+
+/// Cancel a transcription by its job id: ffmpeg or whisper.cpp is killed,
+/// a cloud request dropped and the temp audio removed. A queued transcription
+/// is taken off the queue. Returns false if no transcription has that id.
+#[tauri::command]
+pub fn cancel_transcription(
+    app: AppHandle,
+    job_id: String,
+    running: State<'_, RunningJobs>,
+    scans: State<'_, ScanState>,
+) -> Result<bool> {
+    cancel_job(app, job_id, running, scans)
+}
+
 /// Check if Whisper service is available
 #[tauri::command]
-pub async fn check_whisper_available() -> Result<bool> {
-    let service = WhisperService::new()?;
-    Ok(service.is_available())
+pub async fn check_whisper_available(services: State<'_, ServiceState>) -> Result<bool> {
+    Ok(services.whisper().is_available())
+}
+
+/// Start the background whisper-server with `model_id`, so the first
+/// transcription in server mode doesn't wait for the model to load
+#[tauri::command]
+pub async fn start_whisper_server(
+    model_id: String,
+    services: State<'_, ServiceState>,
+) -> Result<ServerStatus> {
+    let whisper = services.whisper();
+    let server_path = whisper
+        .server_path()
+        .ok_or_else(|| AppError::MissingBinary("whisper-server".to_string()))?;
+    let model_path = whisper.installed_model_path(&model_id).await?;
+    whisper_server::preload(&server_path, &model_path, &model_id).await?;
+    whisper_server::status()
+        .await
+        .ok_or_else(|| AppError::Whisper("whisper-server stopped right after starting".to_string()))
+}
+
+/// Stop the background whisper-server. Returns false if it wasn't running.
+#[tauri::command]
+pub async fn stop_whisper_server() -> bool {
+    whisper_server::stop().await
+}
+
+/// The background whisper-server, if it is running
+#[tauri::command]
+pub async fn get_whisper_server_status() -> Option<ServerStatus> {
+    whisper_server::status().await
 }
 
 /// Install whisper.cpp progress event payload
@@ -128,19 +579,17 @@ pub struct InstallProgress {
 #[tauri::command]
 pub async fn install_whisper_cpp(app: AppHandle) -> Result<String> {
     log::info!("[install_whisper_cpp] Starting installation...");
-    let app_handle = app.clone();
+    let emitter = ThrottledEmitter::new(&app, "whisper:install-progress");
 
     let result = WhisperService::install_whisper_cpp(move |percent, message| {
         log::info!("[install_whisper_cpp] Progress: {}% - {}", percent, message);
-        let _ = app_handle.emit("whisper:install-progress", InstallProgress {
-            percent,
-            message,
-        });
+        emitter.emit(InstallProgress { percent, message });
     }).await;
 
     match result {
         Ok(path) => {
             log::info!("[install_whisper_cpp] Installation successful: {:?}", path);
+            app.state::<ServiceState>().reload_whisper();
             Ok(path.to_string_lossy().to_string())
         }
         Err(e) => {
@@ -150,10 +599,16 @@ pub async fn install_whisper_cpp(app: AppHandle) -> Result<String> {
     }
 }
 
-fn emit_progress(app: &AppHandle, stage: &str, progress: f32, message: &str) {
-    let _ = app.emit("transcription:progress", TranscriptionProgress {
-        stage: stage.to_string(),
-        progress,
-        message: message.to_string(),
-    });
+/// Report progress as throttled `transcription:progress` events
+fn progress_emitter(app: &AppHandle, job_id: Option<&str>) -> ProgressFn {
+    let emitter = ThrottledEmitter::new(app, "transcription:progress");
+    let job_id = job_id.map(str::to_string);
+    Arc::new(move |stage, progress, message| {
+        emitter.emit(TranscriptionProgress {
+            stage: stage.to_string(),
+            progress,
+            message: message.to_string(),
+            job_id: job_id.clone(),
+        });
+    })
 }
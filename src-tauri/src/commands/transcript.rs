@@ -0,0 +1,230 @@
+use crate::commands::cloud::SessionKeyState;
+use crate::commands::jobs::RunningJobs;
+use crate::commands::models::ServiceState;
+use crate::commands::transcribe::{transcription_provider, TranscriptionOptions};
+use crate::error::{AppError, Result};
+use crate::services::audio_filters::AudioFilters;
+use crate::services::database::{
+    Database, SegmentChange, Speaker, StoredTranscription, TranscriptEdit,
+};
+use crate::services::segment_ops::{merge_segments, split_segment};
+use crate::services::transcription_provider::TranscriptionProvider;
+use crate::services::{job_queue, FFmpegService};
+use std::path::Path;
+use tauri::State;
+use tokio_util::sync::CancellationToken;
+
+/// Correct the text or timing of segments of a saved transcription, keeping
+/// each change in its edit history. Returns the updated transcription.
+#[tauri::command]
+pub fn edit_transcript_segments(
+    transcription_id: i64,
+    changes: Vec<SegmentChange>,
+    author: Option<String>,
+) -> Result<StoredTranscription> {
+    let db = Database::open()?;
+    db.edit_segments(transcription_id, &changes, author.as_deref())?;
+    db.existing_transcription(transcription_id)
+}
+
+/// Edit history of a saved transcription, newest first
+#[tauri::command]
+pub fn list_transcript_edits(transcription_id: i64) -> Result<Vec<TranscriptEdit>> {
+    Database::open()?.transcript_edits(transcription_id)
+}
+
+/// Undo the latest edit of a saved transcription, returning the transcription
+#[tauri::command]
+pub fn undo_transcript_edit(transcription_id: i64) -> Result<StoredTranscription> {
+    Database::open()?.undo_transcript_edit(transcription_id)
+}
+
+/// Revert a saved transcription to before edit `edit_id`, undoing that edit
+/// and all later ones. Returns the transcription.
+#[tauri::command]
+pub fn revert_transcript_edits(transcription_id: i64, edit_id: i64) -> Result<StoredTranscription> {
+    Database::open()?.revert_transcript_edits(transcription_id, edit_id)
+}
+
+/// Transcribe `start..end` of a media file, returning the trimmed text
+async fn transcribe_range(
+    provider: &dyn TranscriptionProvider,
+    media_path: &Path,
+    start: f64,
+    end: f64,
+    language: Option<&str>,
+    filters: &AudioFilters,
+    cancel: &CancellationToken,
+) -> Result<String> {
+    let temp_dir = job_queue::temp_dir();
+    tokio::fs::create_dir_all(&temp_dir).await?;
+    let clip_path = temp_dir.join(format!("{}.wav", uuid::Uuid::new_v4()));
+
+    FFmpegService::extract_audio_clip(media_path, &clip_path, start, end, filters, cancel).await?;
+    let result = provider
+        .transcribe(&clip_path, language, cancel, Box::new(|_| {}))
+        .await;
+    let _ = tokio::fs::remove_file(&clip_path).await;
+    Ok(result?.full_text.trim().to_string())
+}
+
+/// Split segment `index` of a saved transcription in two at `at` seconds.
+/// The texts of the halves are `first_text`/`second_text`, else transcribed
+/// again when `retranscribe` is given, else the words divided by time. Pass a
+/// `job_id` to make the transcription cancellable with `cancel_job`.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn split_transcript_segment(
+    transcription_id: i64,
+    index: usize,
+    at: f64,
+    first_text: Option<String>,
+    second_text: Option<String>,
+    retranscribe: Option<TranscriptionOptions>,
+    author: Option<String>,
+    job_id: Option<String>,
+    session: State<'_, SessionKeyState>,
+    services: State<'_, ServiceState>,
+    jobs: State<'_, RunningJobs>,
+) -> Result<StoredTranscription> {
+    let stored = Database::open()?.existing_transcription(transcription_id)?;
+    let segment = stored
+        .result
+        .segments
+        .get(index)
+        .cloned()
+        .ok_or_else(|| AppError::InvalidInput(format!("Segment {} is out of range", index)))?;
+    // Fail on a bad split point before transcribing anything
+    let (mut first, mut second) =
+        split_segment(&segment, at, first_text.clone(), second_text.clone())?;
+
+    if let Some(options) = retranscribe {
+        let provider = transcription_provider(
+            options.provider.as_deref(),
+            &options.model_id,
+            options.profile.as_deref(),
+            &session,
+            &services,
+        )?;
+        let job = jobs.start(job_id);
+        let media_path = Path::new(&stored.media_path);
+        let language = options.language.as_deref();
+        for (half, text) in [(&mut first, &first_text), (&mut second, &second_text)] {
+            if text.is_some() {
+                continue;
+            }
+            let transcribed = transcribe_range(
+                provider.as_ref(),
+                media_path,
+                half.start,
+                half.end,
+                language,
+                &options.audio_filters,
+                job.token(),
+            )
+            .await?;
+            // Keep the divided words when Whisper hears nothing
+            if !transcribed.is_empty() {
+                half.text = transcribed;
+            }
+        }
+    }
+
+    let db = Database::open()?;
+    // Fails when the segment was edited while it was transcribed again
+    db.splice_segments(
+        transcription_id,
+        "split",
+        index,
+        &[segment],
+        vec![first, second],
+        author.as_deref(),
+    )?;
+    db.existing_transcription(transcription_id)
+}
+
+/// Merge `count` (default 2) adjacent segments of a saved transcription,
+/// starting at `index`, into one. Returns the transcription.
+#[tauri::command]
+pub fn merge_transcript_segments(
+    transcription_id: i64,
+    index: usize,
+    count: Option<usize>,
+    author: Option<String>,
+) -> Result<StoredTranscription> {
+    let db = Database::open()?;
+    let segments = db.existing_transcription(transcription_id)?.result.segments;
+    let count = count.unwrap_or(2);
+    let replaced = segments
+        .get(index..index.saturating_add(count))
+        .ok_or_else(|| {
+            AppError::InvalidInput(format!(
+                "Segments {}..{} are out of range",
+                index,
+                index.saturating_add(count)
+            ))
+        })?;
+    let merged = merge_segments(replaced)?;
+    db.splice_segments(
+        transcription_id,
+        "merge",
+        index,
+        replaced,
+        vec![merged],
+        author.as_deref(),
+    )?;
+    db.existing_transcription(transcription_id)
+}
+
+/// Speakers of a saved transcription, including labels already on its segments
+#[tauri::command]
+pub fn list_transcript_speakers(transcription_id: i64) -> Result<Vec<Speaker>> {
+    Database::open()?.speakers(transcription_id)
+}
+
+/// Define a speaker (e.g. "Host") for a saved transcription
+#[tauri::command]
+pub fn add_transcript_speaker(
+    transcription_id: i64,
+    name: String,
+    color: Option<String>,
+) -> Result<Speaker> {
+    Database::open()?.add_speaker(transcription_id, &name, color.as_deref())
+}
+
+/// Rename or recolor a speaker; renaming relabels its segments
+#[tauri::command]
+pub fn update_transcript_speaker(
+    speaker_id: i64,
+    name: String,
+    color: Option<String>,
+    author: Option<String>,
+) -> Result<Speaker> {
+    Database::open()?.update_speaker(speaker_id, &name, color.as_deref(), author.as_deref())
+}
+
+/// Delete a speaker, clearing the label of its segments
+#[tauri::command]
+pub fn delete_transcript_speaker(speaker_id: i64, author: Option<String>) -> Result<()> {
+    Database::open()?.delete_speaker(speaker_id, author.as_deref())
+}
+
+/// Label segments of a saved transcription with a speaker, or clear their label
+/// when `speaker_id` is not given. Returns the transcription, whose segments
+/// carry the labels into exports.
+#[tauri::command]
+pub fn assign_transcript_speaker(
+    transcription_id: i64,
+    segment_indices: Vec<usize>,
+    speaker_id: Option<i64>,
+    author: Option<String>,
+) -> Result<StoredTranscription> {
+    let db = Database::open()?;
+    db.assign_speaker(
+        transcription_id,
+        &segment_indices,
+        speaker_id,
+        author.as_deref(),
+    )?;
+    db.existing_transcription(transcription_id)
+}
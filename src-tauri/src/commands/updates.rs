@@ -0,0 +1,19 @@
+use crate::error::Result;
+use crate::services::app_settings::AppSettings;
+use crate::services::update_check::{self, UpdateInfo};
+
+/// Check GitHub for a newer release, returning its notes and download URL.
+/// Returns `None` without a request when update checks are turned off in the
+/// settings, unless `force` is set (e.g. a "Check now" button).
+/// Installing goes through the updater plugin on the frontend.
+#[tauri::command]
+pub async fn check_for_updates(force: Option<bool>) -> Result<Option<UpdateInfo>> {
+    if !force.unwrap_or(false) && !AppSettings::load()?.update_checks {
+        return Ok(None);
+    }
+    let info = update_check::check_latest_release(env!("CARGO_PKG_VERSION")).await?;
+    if info.update_available {
+        log::info!("[updates] Version {} is available", info.latest_version);
+    }
+    Ok(Some(info))
+}
@@ -0,0 +1,29 @@
+use crate::error::Result;
+use crate::services::database::{
+    ApiUsageEntry, Database, UsageFilter, UsageGrouping, UsageReportRow,
+};
+
+/// Calls listed by `list_api_usage` when no limit is given
+const DEFAULT_USAGE_LIMIT: usize = 200;
+
+/// Cloud API usage and estimated cost per month, provider, model, file or
+/// collection, e.g. the cost of transcribing a series kept in one collection
+#[tauri::command]
+pub fn get_usage_report(
+    group_by: UsageGrouping,
+    filter: Option<UsageFilter>,
+) -> Result<Vec<UsageReportRow>> {
+    Database::open()?.usage_report(group_by, &filter.unwrap_or_default())
+}
+
+/// Recorded cloud API calls, newest first
+#[tauri::command]
+pub fn list_api_usage(
+    filter: Option<UsageFilter>,
+    limit: Option<usize>,
+) -> Result<Vec<ApiUsageEntry>> {
+    Database::open()?.list_api_usage(
+        &filter.unwrap_or_default(),
+        limit.unwrap_or(DEFAULT_USAGE_LIMIT),
+    )
+}
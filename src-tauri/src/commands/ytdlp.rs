@@ -0,0 +1,67 @@
+use crate::commands::directory::WatcherState;
+use crate::commands::jobs::RunningJobs;
+use crate::commands::notifications::{file_name, notify_in_background};
+use crate::commands::progress::ThrottledEmitter;
+use crate::commands::scope::scoped_path;
+use crate::error::{AppError, Result};
+use crate::services::ytdlp::YtDlpService;
+use std::path::PathBuf;
+use tauri::{AppHandle, State};
+
+/// Check if yt-dlp is available
+#[tauri::command]
+pub async fn check_ytdlp() -> Result<bool> {
+    YtDlpService::check_availability().await
+}
+
+/// Get yt-dlp version
+#[tauri::command]
+pub async fn get_ytdlp_version() -> Result<String> {
+    YtDlpService::get_version().await
+}
+
+/// Download media from a YouTube, Vimeo, podcast or other yt-dlp supported URL.
+/// Saves into `output_dir`, or the watched directory by default, so the file
+/// shows up in the library and can be transcribed like any other.
+/// Returns the path of the saved file. Progress is emitted as `ytdlp:progress`.
+/// Pass a `job_id` to make it cancellable with `cancel_job`.
+#[tauri::command]
+pub async fn download_media_from_url(
+    app: AppHandle,
+    url: String,
+    output_dir: Option<String>,
+    audio_only: Option<bool>,
+    job_id: Option<String>,
+    watcher: State<'_, WatcherState>,
+    jobs: State<'_, RunningJobs>,
+) -> Result<String> {
+    let output_dir = match output_dir {
+        Some(dir) => scoped_path(&app, &dir)?,
+        None => watcher.watched_path().map(PathBuf::from).ok_or_else(|| {
+            AppError::InvalidPath(
+                "No output directory given and no directory is being watched".to_string(),
+            )
+        })?,
+    };
+    let job = jobs.start(job_id);
+
+    let emitter = ThrottledEmitter::new(&app, "ytdlp:progress");
+    let result = YtDlpService::download(
+        url.trim(),
+        &output_dir,
+        audio_only.unwrap_or(false),
+        job.token(),
+        move |progress| emitter.emit(progress),
+    )
+    .await;
+    match &result {
+        Ok(path) => {
+            let name = file_name(&path.to_string_lossy());
+            notify_in_background(&app, "Download complete", &name);
+        }
+        Err(AppError::Cancelled) => {}
+        Err(e) => notify_in_background(&app, "Download failed", &format!("{}: {}", url.trim(), e)),
+    }
+
+    Ok(result?.to_string_lossy().to_string())
+}
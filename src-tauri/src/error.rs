@@ -1,3 +1,4 @@
+use serde::ser::SerializeStruct;
 use serde::Serialize;
 use thiserror::Error;
 
@@ -9,17 +10,23 @@ pub enum AppError {
     #[error("Whisper error: {0}")]
     Whisper(String),
 
+    #[error("LLM error: {0}")]
+    Llm(String),
+
+    #[error("No audio stream: {0}")]
+    NoAudioStream(String),
+
     #[error("Download error: {0}")]
     Download(String),
 
     #[error("IO error: {0}")]
-    Io(#[from] std::io::Error),
+    Io(#[source] std::io::Error),
 
     #[error("Network error: {0}")]
-    Network(#[from] reqwest::Error),
+    Network(#[source] reqwest::Error),
 
     #[error("JSON error: {0}")]
-    Json(#[from] serde_json::Error),
+    Json(#[source] serde_json::Error),
 
     #[error("Model not found: {0}")]
     ModelNotFound(String),
@@ -27,20 +34,157 @@ pub enum AppError {
     #[error("Invalid path: {0}")]
     InvalidPath(String),
 
+    #[error("Invalid input: {0}")]
+    InvalidInput(String),
+
     #[error("Process failed: {0}")]
     ProcessFailed(String),
 
     #[error("Keychain error: {0}")]
     Keychain(String),
+
+    #[error("Export error: {0}")]
+    Export(String),
+
+    #[error("Database error: {0}")]
+    Database(#[source] rusqlite::Error),
+
+    #[error("Program not installed: {0}")]
+    MissingBinary(String),
+
+    #[error("API key not set: {0}")]
+    MissingApiKey(String),
+
+    #[error("Rate limited: {0}")]
+    RateLimited(String),
+
+    #[error("Operation cancelled")]
+    Cancelled,
+}
+
+impl AppError {
+    /// Stable identifier of the error kind, e.g. "model_not_found"
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::FFmpeg(_) => "ffmpeg",
+            AppError::Whisper(_) => "whisper",
+            AppError::Llm(_) => "llm",
+            AppError::NoAudioStream(_) => "no_audio_stream",
+            AppError::Download(_) => "download",
+            AppError::Io(_) => "io",
+            AppError::Network(_) => "network",
+            AppError::Json(_) => "json",
+            AppError::ModelNotFound(_) => "model_not_found",
+            AppError::InvalidPath(_) => "invalid_path",
+            AppError::InvalidInput(_) => "invalid_input",
+            AppError::ProcessFailed(_) => "process_failed",
+            AppError::Keychain(_) => "keychain",
+            AppError::Export(_) => "export",
+            AppError::Database(_) => "database",
+            AppError::MissingBinary(_) => "missing_binary",
+            AppError::MissingApiKey(_) => "missing_api_key",
+            AppError::RateLimited(_) => "rate_limited",
+            AppError::Cancelled => "cancelled",
+        }
+    }
+
+    /// The technical detail of the error, without the kind
+    pub fn detail(&self) -> String {
+        match self {
+            AppError::FFmpeg(detail)
+            | AppError::Whisper(detail)
+            | AppError::Llm(detail)
+            | AppError::NoAudioStream(detail)
+            | AppError::Download(detail)
+            | AppError::ModelNotFound(detail)
+            | AppError::InvalidPath(detail)
+            | AppError::InvalidInput(detail)
+            | AppError::ProcessFailed(detail)
+            | AppError::Keychain(detail)
+            | AppError::Export(detail)
+            | AppError::MissingBinary(detail)
+            | AppError::MissingApiKey(detail)
+            | AppError::RateLimited(detail) => detail.clone(),
+            AppError::Io(e) => e.to_string(),
+            AppError::Network(e) => e.to_string(),
+            AppError::Json(e) => e.to_string(),
+            AppError::Database(e) => e.to_string(),
+            AppError::Cancelled => String::new(),
+        }
+    }
+
+    /// Error for a program that could not be started: `MissingBinary` when it
+    /// isn't installed, else `fallback` with the reason
+    pub fn spawn_failed(
+        program: &str,
+        error: std::io::Error,
+        fallback: fn(String) -> AppError,
+    ) -> AppError {
+        log::warn!("[error] Failed to start {}: {}", program, error);
+        if error.kind() == std::io::ErrorKind::NotFound {
+            AppError::MissingBinary(program.to_string())
+        } else {
+            fallback(format!("Failed to start {}: {}", program, error))
+        }
+    }
+
+    /// Error for a failed API response: `RateLimited` for HTTP 429, else `fallback`
+    pub fn for_status(status: reqwest::StatusCode, fallback: AppError) -> AppError {
+        log::warn!("[error] HTTP {}: {}", status, fallback);
+        if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            AppError::RateLimited(fallback.detail())
+        } else {
+            fallback
+        }
+    }
+}
+
+// Errors converted from library errors are logged where they are produced,
+// since their technical text only reaches the frontend as `detail`
+impl From<std::io::Error> for AppError {
+    fn from(error: std::io::Error) -> Self {
+        log::warn!("[error] io: {}", error);
+        AppError::Io(error)
+    }
+}
+
+impl From<reqwest::Error> for AppError {
+    fn from(error: reqwest::Error) -> Self {
+        log::warn!("[error] network: {}", crate::redact::redact(&error.to_string()));
+        AppError::Network(error)
+    }
+}
+
+impl From<serde_json::Error> for AppError {
+    fn from(error: serde_json::Error) -> Self {
+        log::warn!("[error] json: {}", error);
+        AppError::Json(error)
+    }
 }
 
-// Make AppError serializable for Tauri commands
+impl From<rusqlite::Error> for AppError {
+    fn from(error: rusqlite::Error) -> Self {
+        log::warn!("[error] database: {}", error);
+        AppError::Database(error)
+    }
+}
+
+// Commands return errors as `{ code, message, detail }`: the code to branch
+// on, a message in the user's language and the (redacted) technical detail
 impl Serialize for AppError {
     fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
     where
         S: serde::Serializer,
     {
-        serializer.serialize_str(&self.to_string())
+        let detail = Some(self.detail())
+            .filter(|detail| !detail.is_empty())
+            .map(|detail| crate::redact::redact(&detail));
+
+        let mut payload = serializer.serialize_struct("AppError", 3)?;
+        payload.serialize_field("code", self.code())?;
+        payload.serialize_field("message", &crate::messages::user_message(self))?;
+        payload.serialize_field("detail", &detail)?;
+        payload.end()
     }
 }
 
@@ -96,7 +240,50 @@ mod tests {
     #[test]
     fn test_error_serialization() {
         let error = AppError::FFmpeg("test error".to_string());
-        let serialized = serde_json::to_string(&error).unwrap();
-        assert_eq!(serialized, "\"FFmpeg error: test error\"");
+        let serialized = serde_json::to_value(&error).unwrap();
+        assert_eq!(serialized["code"], "ffmpeg");
+        assert_eq!(serialized["message"], "Media processing failed: test error");
+        assert_eq!(serialized["detail"], "test error");
+
+        let error = AppError::InvalidInput("Unknown log level: loud".to_string());
+        let serialized = serde_json::to_value(&error).unwrap();
+        assert_eq!(serialized["message"], "Invalid input: Unknown log level: loud");
+
+        let serialized = serde_json::to_value(AppError::Cancelled).unwrap();
+        assert_eq!(serialized["code"], "cancelled");
+        assert!(serialized["detail"].is_null());
+    }
+
+    #[test]
+    fn test_error_detail_is_redacted() {
+        let error = AppError::ProcessFailed("Authorization: Bearer abcdefgh12345678".to_string());
+        let serialized = serde_json::to_value(&error).unwrap();
+        assert!(!serialized["detail"].as_str().unwrap().contains("abcdefgh12345678"));
+    }
+
+    #[test]
+    fn test_spawn_failed() {
+        let missing = std::io::Error::from(std::io::ErrorKind::NotFound);
+        let error = AppError::spawn_failed("ffmpeg", missing, AppError::FFmpeg);
+        assert_eq!(error.code(), "missing_binary");
+
+        let denied = std::io::Error::from(std::io::ErrorKind::PermissionDenied);
+        let error = AppError::spawn_failed("ffmpeg", denied, AppError::FFmpeg);
+        assert_eq!(error.code(), "ffmpeg");
+    }
+
+    #[test]
+    fn test_for_status() {
+        let error = AppError::for_status(
+            reqwest::StatusCode::TOO_MANY_REQUESTS,
+            AppError::ProcessFailed("slow down".to_string()),
+        );
+        assert!(matches!(error, AppError::RateLimited(ref detail) if detail == "slow down"));
+
+        let error = AppError::for_status(
+            reqwest::StatusCode::BAD_REQUEST,
+            AppError::ProcessFailed("bad".to_string()),
+        );
+        assert_eq!(error.code(), "process_failed");
     }
 }
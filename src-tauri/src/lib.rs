@@ -1,11 +1,20 @@
 mod commands;
 mod error;
+mod logging;
+mod messages;
+mod redact;
 mod services;
 
 use commands::*;
+use tauri::Manager;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    logging::init();
+    messages::init();
+    services::http::init();
+    services::concurrency::init();
+
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_dialog::init())
@@ -13,7 +22,25 @@ pub fn run() {
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_updater::Builder::new().build())
         .plugin(tauri_plugin_process::init())
+        .plugin(tauri_plugin_notification::init())
         .manage(WatcherState::default())
+        .manage(ScanState::default())
+        .manage(SessionKeyState::default())
+        .manage(JobQueueState::restore())
+        .manage(RunningJobs::default())
+        .manage(ModelDownloads::default())
+        .manage(AppFocusState::default())
+        .setup(|app| {
+            app.manage(ServiceState::new());
+            start_job_worker(app.handle().clone());
+            start_feed_poller(app.handle().clone());
+            start_backup_scheduler();
+            services::temp_files::start_startup_sweep();
+            Ok(())
+        })
+        .on_window_event(|window, event| {
+            window.app_handle().state::<AppFocusState>().on_window_event(event);
+        })
         .invoke_handler(tauri::generate_handler![
             // FFmpeg commands
             check_ffmpeg,
@@ -21,19 +48,43 @@ pub fn run() {
             get_media_info,
             extract_audio,
             get_media_duration,
+            get_thumbnail,
+            invalidate_thumbnail,
+            clear_thumbnail_cache,
+            // URL download commands
+            check_ytdlp,
+            get_ytdlp_version,
+            download_media_from_url,
+            // Podcast feed commands
+            subscribe_feed,
+            unsubscribe_feed,
+            list_feeds,
+            update_feed,
+            list_feed_episodes,
+            refresh_feed,
+            download_feed_episode,
             // Model commands
             get_available_models,
             get_installed_models,
             get_models_status,
             is_model_installed,
             download_model,
+            pause_model_download,
+            cancel_model_download,
             delete_model,
             get_models_directory,
             // Transcription commands
             transcribe_media,
             transcribe_audio,
+            compare_transcriptions,
+            transcribe_directory,
+            cancel_transcription,
             check_whisper_available,
             install_whisper_cpp,
+            start_whisper_server,
+            stop_whisper_server,
+            get_whisper_server_status,
+            process_media,
             // Ollama commands
             check_ollama,
             list_ollama_models,
@@ -41,18 +92,37 @@ pub fn run() {
             ollama_chat,
             summarize_text,
             extract_story_order,
+            generate_youtube_chapters,
             pull_ollama_model,
             delete_ollama_model,
+            // llama.cpp commands
+            is_llama_available,
+            get_available_llama_models,
+            get_llama_models_status,
+            download_llama_model,
+            delete_llama_model,
+            llama_summarize,
             // Cloud API commands
             store_api_key,
             get_api_key_masked,
             delete_api_key,
             get_api_key_status,
+            list_secret_providers,
+            list_credential_profiles,
+            set_active_credential_profile,
+            get_secret_store_status,
+            unlock_secret_store,
+            lock_secret_store,
+            export_credentials,
+            import_credentials,
             validate_openai_key,
             validate_openai_key_direct,
             openai_transcribe,
             openai_chat,
+            openai_chat_stream,
             openai_summarize,
+            openai_extract_story_order,
+            openai_generate_youtube_chapters,
             get_openai_models,
             fetch_openai_models,
             fetch_openai_models_direct,
@@ -60,17 +130,189 @@ pub fn run() {
             validate_claude_key_direct,
             claude_chat,
             claude_summarize,
+            claude_extract_story_order,
+            claude_generate_youtube_chapters,
             get_claude_models,
             fetch_claude_models,
             fetch_claude_models_direct,
+            validate_gemini_key,
+            validate_gemini_key_direct,
+            gemini_chat,
+            gemini_summarize,
+            gemini_summarize_media,
+            gemini_extract_story_order,
+            gemini_generate_youtube_chapters,
+            get_gemini_models,
+            fetch_gemini_models,
+            fetch_gemini_models_direct,
+            validate_groq_key,
+            validate_groq_key_direct,
+            groq_chat,
+            groq_summarize,
+            groq_extract_story_order,
+            groq_generate_youtube_chapters,
+            get_groq_models,
+            get_groq_transcription_models,
+            fetch_groq_models,
+            fetch_groq_models_direct,
+            check_openai_compatible_server,
+            openai_compatible_chat,
+            openai_compatible_summarize,
+            openai_compatible_extract_story_order,
+            openai_compatible_generate_youtube_chapters,
+            fetch_openai_compatible_models,
+            assemblyai_transcribe,
+            // Transcript analysis commands
+            extract_keywords,
+            detect_topics,
+            extract_action_items,
+            get_minutes_templates,
+            generate_meeting_minutes,
+            analyze_sentiment,
+            extract_entities,
+            suggest_publish_metadata,
+            polish_transcript,
+            // Prompt template commands
+            list_prompt_templates,
+            create_prompt_template,
+            update_prompt_template,
+            delete_prompt_template,
+            render_prompt_template,
+            run_prompt_template,
+            // Embedding store commands
+            rebuild_embeddings,
+            get_embedding_store_stats,
+            clear_embeddings,
             // Directory commands
             scan_media_directory,
             scan_media_directory_tree,
+            scan_directory_children,
+            rescan_directory,
+            cancel_scan,
+            get_library_stats,
+            get_recently_added_files,
+            get_recently_modified_files,
+            move_media_file,
+            rename_media_file,
+            delete_media_file,
             start_watching_directory,
             stop_watching_directory,
             get_watched_directory,
+            set_watch_exclusions,
+            get_watch_exclusions,
             is_media_file,
+            // Project database commands
+            save_transcription,
+            load_transcription,
+            list_transcription_history,
+            load_transcription_run,
+            save_summary,
+            load_summary,
+            save_story_order,
+            load_story_order,
+            save_job_record,
+            list_job_history,
+            create_tag,
+            update_tag,
+            delete_tag,
+            list_tags,
+            get_file_tags,
+            tag_media_file,
+            untag_media_file,
+            create_collection,
+            update_collection,
+            delete_collection,
+            list_collections,
+            get_collection_files,
+            add_to_collection,
+            remove_from_collection,
+            add_bookmark,
+            update_bookmark,
+            delete_bookmark,
+            list_bookmarks,
+            import_files,
+            save_detected_entities,
+            list_entities,
+            get_entity_mentions,
+            // Transcript editing commands
+            edit_transcript_segments,
+            list_transcript_edits,
+            undo_transcript_edit,
+            revert_transcript_edits,
+            split_transcript_segment,
+            merge_transcript_segments,
+            list_transcript_speakers,
+            add_transcript_speaker,
+            update_transcript_speaker,
+            delete_transcript_speaker,
+            assign_transcript_speaker,
+            // Export commands
+            export_vtt,
+            export_srt,
+            export_text,
+            export_markdown,
+            export_pdf,
+            export_minutes_markdown,
+            export_minutes_docx,
+            export_csv,
+            export_segments_json,
+            export_karaoke_ass,
+            export_words_json,
+            export_audacity_labels,
+            export_audition_markers,
+            export_fcpxml,
+            export_edl,
+            export_premiere_xml,
+            export_resolve_timeline,
+            // Job queue commands
+            enqueue_job,
+            enqueue_transcription,
+            list_jobs,
+            list_job_events,
+            pause_job,
+            resume_job,
+            move_job,
+            cancel_job,
+            retry_job,
+            clear_finished_jobs,
+            pause_queue,
+            resume_queue,
+            is_queue_paused,
+            // Process commands
+            list_active_processes,
+            terminate_process,
+            // Usage ledger commands
+            get_usage_report,
+            list_api_usage,
+            // Metrics commands
+            get_metrics_summary,
+            list_metrics,
+            export_metrics,
+            clear_metrics,
+            // Benchmark commands
+            run_benchmark,
+            get_benchmark_report,
+            estimate_transcription_time,
+            // Log commands
+            get_recent_logs,
+            open_log_folder,
+            // Update commands
+            check_for_updates,
+            // Backup commands
+            backup_database,
+            list_backups,
+            verify_backup,
+            restore_database,
+            // Settings commands
+            get_app_settings,
+            save_app_settings,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|_app, event| {
+            // Don't leave ffmpeg or whisper.cpp running after quitting
+            if let tauri::RunEvent::Exit = event {
+                services::processes::kill_all();
+            }
+        });
 }
@@ -0,0 +1,293 @@
+//! Application logging: redacted records go to stderr and, as JSON lines, to
+//! rotated files in the log folder so bug reports can include them.
+
+use crate::error::{AppError, Result};
+use crate::redact::redact;
+use log::{Level, Log, Metadata, Record};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Name of the current log file; rotated files get a `.1`, `.2`, ... suffix
+const LOG_FILE: &str = "clip-flow.log";
+
+/// The current log file is rotated once it would grow past this size
+const MAX_FILE_SIZE: u64 = 5 * 1024 * 1024;
+
+/// Log files kept, including the current one
+const MAX_FILES: usize = 5;
+
+/// One log record, as written to the log files
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LogEntry {
+    /// Unix milliseconds
+    pub timestamp: u64,
+    pub level: String,
+    /// Module the record was logged from
+    pub target: String,
+    /// Subsystem named by a leading `[scope]` in the message, e.g. "job_queue"
+    #[serde(default)]
+    pub scope: Option<String>,
+    pub message: String,
+}
+
+impl LogEntry {
+    fn new(record: &Record, timestamp: u64) -> Self {
+        let message = redact(&record.args().to_string());
+        let (scope, message) = split_scope(&message);
+        Self {
+            timestamp,
+            level: record.level().to_string(),
+            target: record.target().to_string(),
+            scope: scope.map(str::to_string),
+            message: message.to_string(),
+        }
+    }
+}
+
+/// Split the `[scope] ` prefix the log messages start with from the message
+fn split_scope(message: &str) -> (Option<&str>, &str) {
+    let scoped = message
+        .strip_prefix('[')
+        .and_then(|rest| rest.split_once("] "));
+    match scoped {
+        Some((scope, rest)) if !scope.is_empty() && !scope.contains(char::is_whitespace) => {
+            (Some(scope), rest)
+        }
+        _ => (None, message),
+    }
+}
+
+/// Folder the log files are written to
+pub fn log_dir() -> Result<PathBuf> {
+    let data_dir = dirs::data_local_dir()
+        .ok_or_else(|| AppError::InvalidPath("Cannot find data directory".to_string()))?;
+    Ok(data_dir.join("clip-flow").join("logs"))
+}
+
+fn log_file(dir: &Path, index: usize) -> PathBuf {
+    match index {
+        0 => dir.join(LOG_FILE),
+        _ => dir.join(format!("{}.{}", LOG_FILE, index)),
+    }
+}
+
+/// The current log file, rotated by size
+struct RotatingFile {
+    dir: PathBuf,
+    file: Option<File>,
+    size: u64,
+}
+
+impl RotatingFile {
+    fn open(dir: &Path) -> io::Result<Self> {
+        fs::create_dir_all(dir)?;
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(log_file(dir, 0))?;
+        let size = file.metadata()?.len();
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            file: Some(file),
+            size,
+        })
+    }
+
+    fn write_line(&mut self, line: &str) -> io::Result<()> {
+        let len = line.len() as u64 + 1;
+        if self.size > 0 && self.size + len > MAX_FILE_SIZE {
+            self.rotate()?;
+        }
+        let file = self
+            .file
+            .as_mut()
+            .ok_or_else(|| io::Error::other("log file closed"))?;
+        writeln!(file, "{}", line)?;
+        self.size += len;
+        Ok(())
+    }
+
+    /// Shift the files one suffix up, dropping the oldest, and start a new current file
+    fn rotate(&mut self) -> io::Result<()> {
+        // Windows can't rename a file that is still open
+        self.file = None;
+        for index in (1..MAX_FILES).rev() {
+            let from = log_file(&self.dir, index - 1);
+            if from.exists() {
+                fs::rename(&from, log_file(&self.dir, index))?;
+            }
+        }
+        *self = Self::open(&self.dir)?;
+        Ok(())
+    }
+}
+
+/// Logs every record to stderr and the log files
+struct AppLogger {
+    console: env_logger::Logger,
+    file: Option<Mutex<RotatingFile>>,
+}
+
+impl Log for AppLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.console.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.console.matches(record) {
+            return;
+        }
+        self.console.log(record);
+
+        let Some(file) = &self.file else {
+            return;
+        };
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_millis() as u64)
+            .unwrap_or_default();
+        if let Ok(line) = serde_json::to_string(&LogEntry::new(record, timestamp)) {
+            let mut file = file.lock().unwrap_or_else(|e| e.into_inner());
+            // Nowhere left to report a failing log file
+            let _ = file.write_line(&line);
+        }
+    }
+
+    fn flush(&self) {
+        self.console.flush();
+        if let Some(file) = &self.file {
+            let mut file = file.lock().unwrap_or_else(|e| e.into_inner());
+            if let Some(file) = file.file.as_mut() {
+                let _ = file.flush();
+            }
+        }
+    }
+}
+
+/// Initialize the global logger. Every record is redacted; `RUST_LOG` sets the
+/// level, defaulting to `info`. Without a writable log folder, records only go
+/// to stderr.
+pub fn init() {
+    let console =
+        env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info"))
+            .format(|buf, record| {
+                writeln!(
+                    buf,
+                    "[{} {}] {}",
+                    record.level(),
+                    record.target(),
+                    redact(&record.args().to_string())
+                )
+            })
+            .build();
+    let max_level = console.filter();
+    let file = log_dir()
+        .ok()
+        .and_then(|dir| RotatingFile::open(&dir).ok())
+        .map(Mutex::new);
+
+    if log::set_boxed_logger(Box::new(AppLogger { console, file })).is_ok() {
+        log::set_max_level(max_level);
+    }
+}
+
+/// The newest entries in the log files of `dir`, newest first, optionally only
+/// those at least as severe as `min_level`
+pub fn recent_logs(dir: &Path, limit: usize, min_level: Option<Level>) -> Result<Vec<LogEntry>> {
+    let mut entries = Vec::new();
+    for index in 0..MAX_FILES {
+        let path = log_file(dir, index);
+        if !path.exists() {
+            continue;
+        }
+        let content = fs::read_to_string(&path)?;
+        let newest_first = content
+            .lines()
+            .rev()
+            .filter_map(|line| serde_json::from_str::<LogEntry>(line).ok())
+            .filter(|entry| {
+                min_level.is_none_or(|min| entry.level.parse::<Level>().is_ok_and(|l| l <= min))
+            });
+        for entry in newest_first {
+            if entries.len() == limit {
+                return Ok(entries);
+            }
+            entries.push(entry);
+        }
+    }
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn entry(level: Level, message: &str, timestamp: u64) -> String {
+        let entry = LogEntry::new(
+            &Record::builder()
+                .args(format_args!("{}", message))
+                .level(level)
+                .target("clip_flow_lib::test")
+                .build(),
+            timestamp,
+        );
+        serde_json::to_string(&entry).unwrap()
+    }
+
+    #[test]
+    fn test_entry_scope_and_redaction() {
+        let line = entry(
+            Level::Warn,
+            "[job_queue] Key sk-abcdefghijklmnopqrstu rejected",
+            1,
+        );
+        let entry: LogEntry = serde_json::from_str(&line).unwrap();
+        assert_eq!(entry.scope.as_deref(), Some("job_queue"));
+        assert_eq!(entry.message, "Key [REDACTED] rejected");
+        assert_eq!(entry.level, "WARN");
+
+        assert_eq!(
+            split_scope("[not a scope] text"),
+            (None, "[not a scope] text")
+        );
+        assert_eq!(split_scope("plain"), (None, "plain"));
+    }
+
+    #[test]
+    fn test_rotation_and_recent_logs() {
+        let dir = TempDir::new().unwrap();
+        let mut file = RotatingFile::open(dir.path()).unwrap();
+        file.write_line(&entry(Level::Error, "first", 1)).unwrap();
+        file.write_line(&entry(Level::Info, "second", 2)).unwrap();
+        // Pretend the current file is full
+        file.size = MAX_FILE_SIZE;
+        file.write_line(&entry(Level::Debug, "third", 3)).unwrap();
+        assert!(log_file(dir.path(), 1).exists());
+
+        let all = recent_logs(dir.path(), 10, None).unwrap();
+        let messages: Vec<_> = all.iter().map(|entry| entry.message.as_str()).collect();
+        assert_eq!(messages, ["third", "second", "first"]);
+
+        assert_eq!(recent_logs(dir.path(), 2, None).unwrap().len(), 2);
+        let info = recent_logs(dir.path(), 10, Some(Level::Info)).unwrap();
+        assert_eq!(info.len(), 2);
+        assert_eq!(info[1].message, "first");
+    }
+
+    #[test]
+    fn test_rotation_keeps_max_files() {
+        let dir = TempDir::new().unwrap();
+        let mut file = RotatingFile::open(dir.path()).unwrap();
+        for _ in 0..MAX_FILES + 2 {
+            file.write_line("{}").unwrap();
+            file.rotate().unwrap();
+        }
+        assert!(log_file(dir.path(), MAX_FILES - 1).exists());
+        assert!(!log_file(dir.path(), MAX_FILES).exists());
+    }
+}
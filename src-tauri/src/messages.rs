@@ -0,0 +1,254 @@
+//! Translated, user-facing messages for backend errors. Commands return these
+//! instead of the technical error text, which goes to the log; messages of
+//! errors without a more specific code keep the (redacted) detail.
+
+use crate::error::AppError;
+use crate::services::app_settings::AppSettings;
+use std::sync::RwLock;
+
+/// Languages with a translated catalog; anything else falls back to English
+const LOCALES: [&str; 3] = ["en", "ko", "ja"];
+
+/// Messages per error code, in the order of `LOCALES`.
+/// `{detail}` is replaced with the error's detail (a model id, a path, ...).
+const CATALOG: &[(&str, [&str; 3])] = &[
+    (
+        "ffmpeg",
+        [
+            "Media processing failed: {detail}",
+            "미디어 처리에 실패했습니다: {detail}",
+            "メディアの処理に失敗しました: {detail}",
+        ],
+    ),
+    (
+        "whisper",
+        [
+            "Transcription failed: {detail}",
+            "음성 인식에 실패했습니다: {detail}",
+            "文字起こしに失敗しました: {detail}",
+        ],
+    ),
+    (
+        "llm",
+        [
+            "The language model request failed: {detail}",
+            "언어 모델 요청에 실패했습니다: {detail}",
+            "言語モデルへのリクエストに失敗しました: {detail}",
+        ],
+    ),
+    (
+        "no_audio_stream",
+        [
+            "This video does not contain an audio stream.",
+            "이 영상에는 오디오 스트림이 없습니다.",
+            "この動画には音声ストリームがありません。",
+        ],
+    ),
+    (
+        "download",
+        [
+            "Download failed: {detail}",
+            "다운로드에 실패했습니다: {detail}",
+            "ダウンロードに失敗しました: {detail}",
+        ],
+    ),
+    (
+        "io",
+        [
+            "A file could not be read or written.",
+            "파일을 읽거나 쓸 수 없습니다.",
+            "ファイルを読み書きできませんでした。",
+        ],
+    ),
+    (
+        "network",
+        [
+            "Could not reach the server. Check your internet connection.",
+            "서버에 연결할 수 없습니다. 인터넷 연결을 확인하세요.",
+            "サーバーに接続できませんでした。インターネット接続を確認してください。",
+        ],
+    ),
+    (
+        "json",
+        [
+            "Received data in an unexpected format.",
+            "예상하지 못한 형식의 데이터를 받았습니다.",
+            "予期しない形式のデータを受信しました。",
+        ],
+    ),
+    (
+        "model_not_found",
+        [
+            "Model not found: {detail}",
+            "모델을 찾을 수 없습니다: {detail}",
+            "モデルが見つかりません: {detail}",
+        ],
+    ),
+    (
+        "invalid_path",
+        [
+            "Invalid path: {detail}",
+            "잘못된 경로입니다: {detail}",
+            "無効なパスです: {detail}",
+        ],
+    ),
+    (
+        "invalid_input",
+        [
+            "Invalid input: {detail}",
+            "잘못된 입력입니다: {detail}",
+            "無効な入力です: {detail}",
+        ],
+    ),
+    (
+        "process_failed",
+        [
+            "An external program failed: {detail}",
+            "외부 프로그램 실행에 실패했습니다: {detail}",
+            "外部プログラムの実行に失敗しました: {detail}",
+        ],
+    ),
+    (
+        "keychain",
+        [
+            "Could not access the system keychain.",
+            "시스템 키체인에 접근할 수 없습니다.",
+            "システムのキーチェーンにアクセスできませんでした。",
+        ],
+    ),
+    (
+        "export",
+        [
+            "Export failed: {detail}",
+            "내보내기에 실패했습니다: {detail}",
+            "エクスポートに失敗しました: {detail}",
+        ],
+    ),
+    (
+        "database",
+        [
+            "The library database could not be accessed.",
+            "라이브러리 데이터베이스에 접근할 수 없습니다.",
+            "ライブラリのデータベースにアクセスできませんでした。",
+        ],
+    ),
+    (
+        "missing_binary",
+        [
+            "{detail} is not installed.",
+            "{detail}이(가) 설치되어 있지 않습니다.",
+            "{detail}がインストールされていません。",
+        ],
+    ),
+    (
+        "missing_api_key",
+        [
+            "No API key is set for {detail}. Add one in the settings.",
+            "{detail} API 키가 설정되지 않았습니다. 설정에서 추가하세요.",
+            "{detail}のAPIキーが設定されていません。設定で追加してください。",
+        ],
+    ),
+    (
+        "rate_limited",
+        [
+            "The provider's rate limit was reached. Wait a moment and try again.",
+            "제공자의 요청 한도에 도달했습니다. 잠시 후 다시 시도하세요.",
+            "プロバイダーのレート制限に達しました。しばらく待ってから、もう一度お試しください。",
+        ],
+    ),
+    (
+        "cancelled",
+        [
+            "The operation was cancelled.",
+            "작업이 취소되었습니다.",
+            "操作はキャンセルされました。",
+        ],
+    ),
+];
+
+/// Language error messages are shown in, as set in the settings
+static LOCALE: RwLock<Option<String>> = RwLock::new(None);
+
+/// Show error messages in `locale` (a language tag such as "ko" or "ja-JP")
+pub fn set_locale(locale: &str) {
+    *LOCALE.write().unwrap_or_else(|e| e.into_inner()) = Some(locale.to_string());
+}
+
+/// Use the language from the saved settings
+pub fn init() {
+    if let Ok(settings) = AppSettings::load() {
+        set_locale(&settings.locale);
+    }
+}
+
+/// Index into the catalog for a language tag, English when unsupported
+fn locale_index(locale: &str) -> usize {
+    let language = locale.split(['-', '_']).next().unwrap_or_default().to_lowercase();
+    LOCALES.iter().position(|l| *l == language).unwrap_or(0)
+}
+
+/// The message for an error in `locale`
+pub fn localized(error: &AppError, locale: &str) -> String {
+    let code = error.code();
+    let Some((_, messages)) = CATALOG.iter().find(|(c, _)| *c == code) else {
+        return error.to_string();
+    };
+    messages[locale_index(locale)].replace("{detail}", &crate::redact::redact(&error.detail()))
+}
+
+/// The message for an error in the language set with `set_locale`
+pub fn user_message(error: &AppError) -> String {
+    let locale = LOCALE.read().unwrap_or_else(|e| e.into_inner());
+    localized(error, locale.as_deref().unwrap_or(LOCALES[0]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_localized() {
+        let error = AppError::ModelNotFound("large-v3".to_string());
+        assert_eq!(localized(&error, "en"), "Model not found: large-v3");
+        assert_eq!(localized(&error, "ko-KR"), "모델을 찾을 수 없습니다: large-v3");
+        assert_eq!(localized(&error, "ja"), "モデルが見つかりません: large-v3");
+        // Unsupported languages fall back to English
+        assert_eq!(localized(&AppError::Cancelled, "fr"), "The operation was cancelled.");
+        // The detail says what actually went wrong
+        let error = AppError::FFmpeg("Invalid data found when processing input".to_string());
+        assert_eq!(
+            localized(&error, "en"),
+            "Media processing failed: Invalid data found when processing input"
+        );
+        let error = AppError::NoAudioStream("/m/silent.mp4".to_string());
+        assert_eq!(localized(&error, "ko"), "이 영상에는 오디오 스트림이 없습니다.");
+    }
+
+    #[test]
+    fn test_catalog_covers_every_code() {
+        let errors = [
+            AppError::FFmpeg(String::new()),
+            AppError::Whisper(String::new()),
+            AppError::Llm(String::new()),
+            AppError::NoAudioStream(String::new()),
+            AppError::Download(String::new()),
+            AppError::Io(std::io::Error::other("")),
+            AppError::Json(serde_json::from_str::<u8>("").unwrap_err()),
+            AppError::ModelNotFound(String::new()),
+            AppError::InvalidPath(String::new()),
+            AppError::InvalidInput(String::new()),
+            AppError::ProcessFailed(String::new()),
+            AppError::Keychain(String::new()),
+            AppError::Export(String::new()),
+            AppError::Database(rusqlite::Error::InvalidQuery),
+            AppError::MissingBinary(String::new()),
+            AppError::MissingApiKey(String::new()),
+            AppError::RateLimited(String::new()),
+            AppError::Cancelled,
+        ];
+        for error in errors {
+            assert!(CATALOG.iter().any(|(code, _)| *code == error.code()), "{}", error.code());
+        }
+        assert!(CATALOG.iter().any(|(code, _)| *code == "network"));
+    }
+}
@@ -0,0 +1,143 @@
+//! Scrubs API keys and bearer tokens from log output.
+
+use regex::Regex;
+use std::collections::HashSet;
+use std::sync::{Mutex, OnceLock};
+
+const REDACTED: &str = "[REDACTED]";
+
+/// Secrets seen at runtime (e.g. keys loaded from the keychain) that have no recognizable prefix
+static KNOWN_SECRETS: Mutex<Option<HashSet<String>>> = Mutex::new(None);
+
+/// Shortest secret registered for exact-match scrubbing, to avoid mangling ordinary words
+const MIN_SECRET_LEN: usize = 8;
+
+/// Patterns for well-known key formats and credential-carrying headers/parameters
+fn patterns() -> &'static [(Regex, &'static str)] {
+    static PATTERNS: OnceLock<Vec<(Regex, &'static str)>> = OnceLock::new();
+    PATTERNS.get_or_init(|| {
+        [
+            // Authorization: Bearer <token> (the scheme is kept)
+            (
+                r#"(?i)(\bauthorization["']?\s*[:=]\s*["']?(?:bearer\s+|basic\s+)?)[A-Za-z0-9\-._~+/]+=*"#,
+                "${1}[REDACTED]",
+            ),
+            // x-api-key: <key>, api_key=<key>, "access_token": "<key>"
+            (
+                r#"(?i)(\b(?:x-api-key|x-goog-api-key|api[_-]?key|access[_-]?token)["']?\s*[:=]\s*["']?)[A-Za-z0-9\-._~+/]{8,}"#,
+                "${1}[REDACTED]",
+            ),
+            // ?key=<key> query parameters (Gemini)
+            (r"([?&]key=)[A-Za-z0-9\-._~+/]{8,}", "${1}[REDACTED]"),
+            // OpenAI / Anthropic (sk-..., sk-proj-..., sk-ant-...)
+            (r"\bsk-[A-Za-z0-9_\-]{16,}", REDACTED),
+            // Groq
+            (r"\bgsk_[A-Za-z0-9]{20,}", REDACTED),
+            // Google (Gemini)
+            (r"\bAIza[0-9A-Za-z\-_]{30,}", REDACTED),
+        ]
+        .into_iter()
+        .map(|(pattern, replacement)| (Regex::new(pattern).expect("valid redaction pattern"), replacement))
+        .collect()
+    })
+}
+
+/// Register a secret so it is scrubbed verbatim from all later log output
+pub fn register_secret(secret: &str) {
+    let secret = secret.trim();
+    if secret.len() < MIN_SECRET_LEN {
+        return;
+    }
+    let mut guard = KNOWN_SECRETS.lock().unwrap_or_else(|e| e.into_inner());
+    guard.get_or_insert_with(HashSet::new).insert(secret.to_string());
+}
+
+/// Replace every API key, bearer token and registered secret in `text`
+pub fn redact(text: &str) -> String {
+    let mut output = text.to_string();
+
+    {
+        let guard = KNOWN_SECRETS.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(secrets) = guard.as_ref() {
+            for secret in secrets {
+                if output.contains(secret.as_str()) {
+                    output = output.replace(secret.as_str(), REDACTED);
+                }
+            }
+        }
+    }
+
+    for (pattern, replacement) in patterns() {
+        if pattern.is_match(&output) {
+            output = pattern.replace_all(&output, *replacement).into_owned();
+        }
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redacts_openai_and_claude_keys() {
+        let text = "key=sk-proj-abcdefghijklmnop1234 and sk-ant-REDACTED";
+        let redacted = redact(text);
+        assert!(!redacted.contains("abcdefghijklmnop1234"));
+        assert!(!redacted.contains("ABCDEFGHIJKLMNOPQRST"));
+    }
+
+    #[test]
+    fn test_redacts_bearer_token() {
+        let redacted = redact("Authorization: Bearer abc.def-123_456");
+        assert_eq!(redacted, "Authorization: Bearer [REDACTED]");
+    }
+
+    #[test]
+    fn test_redacts_header_and_query_values() {
+        assert_eq!(redact("x-api-key: 0123456789abcdef"), "x-api-key: [REDACTED]");
+        assert_eq!(
+            redact("GET /models?key=AIzaSyA1234567890abcdefghijklmnopqrstu"),
+            "GET /models?key=[REDACTED]"
+        );
+    }
+
+    #[test]
+    fn test_redacts_registered_secrets() {
+        register_secret("dg-0f1e2d3c4b5a69788796a5b4");
+        let redacted = redact("Deepgram key dg-0f1e2d3c4b5a69788796a5b4 rejected");
+        assert_eq!(redacted, "Deepgram key [REDACTED] rejected");
+    }
+
+    #[test]
+    fn test_short_secrets_are_not_registered() {
+        register_secret("abc");
+        assert_eq!(redact("abc def"), "abc def");
+    }
+
+    #[test]
+    fn test_redacts_json_authorization_header() {
+        let redacted = redact(r#"{"Authorization": "Bearer abc.def-123_456"}"#);
+        assert_eq!(redacted, r#"{"Authorization": "Bearer [REDACTED]"}"#);
+    }
+
+    #[test]
+    fn test_only_anchored_matches_are_redacted() {
+        for text in [
+            "task-0123456789abcdefghij finished",
+            "disk_gsk_0123456789abcdefghijkl",
+            "monkey: bananas12345678",
+            "sort_key=created_at_descending",
+            "the bearer of bad news",
+        ] {
+            assert_eq!(redact(text), text);
+        }
+    }
+
+    #[test]
+    fn test_plain_text_is_untouched() {
+        let text = "[KeychainService] Storing key for service: clip-flow, account: openai_api_key";
+        assert_eq!(redact(text), text);
+    }
+}
@@ -0,0 +1,223 @@
+use crate::error::Result;
+use crate::services::llm::{parse_json_response, LlmProvider};
+use crate::services::ollama::language_code_to_name;
+use crate::services::whisper::TranscriptionSegment;
+use serde::{Deserialize, Serialize};
+use tokio_util::sync::CancellationToken;
+
+const ACTION_ITEMS_SYSTEM: &str = "You are a meeting assistant who writes down the follow-ups \
+                                   agreed in a recording. Respond with the JSON object only, \
+                                   without any explanation.";
+
+/// Reply budget: a task list rarely runs longer than this
+const ACTION_ITEMS_MAX_TOKENS: u32 = 3000;
+
+/// A task someone agreed to do
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ActionItem {
+    pub task: String,
+    /// Who takes the task, as named in the recording
+    pub owner: Option<String>,
+    /// When it is due, as said in the recording (e.g. "by Friday")
+    pub due: Option<String>,
+    /// Segment the task was agreed in and its start time
+    pub segment: Option<usize>,
+    pub start: Option<f64>,
+}
+
+/// A decision taken in the recording
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Decision {
+    pub decision: String,
+    pub segment: Option<usize>,
+    pub start: Option<f64>,
+}
+
+/// Follow-ups of a meeting recording
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MeetingFollowUps {
+    pub action_items: Vec<ActionItem>,
+    pub decisions: Vec<Decision>,
+}
+
+/// Follow-ups as returned by the LLM
+#[derive(Debug, Default, Deserialize)]
+pub(crate) struct RawFollowUps {
+    #[serde(default)]
+    action_items: Vec<RawActionItem>,
+    #[serde(default)]
+    decisions: Vec<RawDecision>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawActionItem {
+    task: String,
+    #[serde(default)]
+    owner: Option<String>,
+    #[serde(default)]
+    due: Option<String>,
+    #[serde(default)]
+    segment: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawDecision {
+    decision: String,
+    #[serde(default)]
+    segment: Option<usize>,
+}
+
+/// Extract action items (owner, due date, source timestamp) and decisions from
+/// transcript segments
+pub async fn extract_action_items(
+    llm: &dyn LlmProvider,
+    segments: &[TranscriptionSegment],
+    language: &str,
+    cancel: &CancellationToken,
+) -> Result<MeetingFollowUps> {
+    log::info!("[action_items] Extracting follow-ups with {} ({})", llm.id(), llm.model());
+    let prompt = build_action_items_prompt(segments, language);
+    let response = llm
+        .complete(ACTION_ITEMS_SYSTEM, &prompt, ACTION_ITEMS_MAX_TOKENS, cancel)
+        .await?;
+    parse_action_items_response(&response, segments)
+}
+
+fn build_action_items_prompt(segments: &[TranscriptionSegment], language: &str) -> String {
+    let segments_text: Vec<String> = segments
+        .iter()
+        .enumerate()
+        .map(|(i, s)| match &s.speaker {
+            Some(speaker) => format!("[{}] {}: {}", i, speaker, s.text.trim()),
+            None => format!("[{}] {}", i, s.text.trim()),
+        })
+        .collect();
+
+    format!(
+        "List the action items and decisions of this meeting transcript, in {}.\n\n\
+         Rules:\n\
+         - An action item is a task someone agreed to do; skip ideas nobody took on\n\
+         - owner is the person taking the task and due is the deadline as said, \
+         or null when not mentioned\n\
+         - segment is the index of the segment where the item was agreed\n\
+         - Return empty lists when there are none\n\n\
+         Segments:\n{}\n\n\
+         Response format: {{\"action_items\": [{{\"task\": \"Send the budget draft\", \
+         \"owner\": \"Maria\", \"due\": \"Friday\", \"segment\": 12}}], \
+         \"decisions\": [{{\"decision\": \"Launch moves to May\", \"segment\": 30}}]}}",
+        language_code_to_name(language),
+        segments_text.join("\n")
+    )
+}
+
+/// Parse the LLM's follow-ups: drop empty entries, resolve segment indexes to
+/// timestamps and keep transcript order
+fn parse_action_items_response(
+    response: &str,
+    segments: &[TranscriptionSegment],
+) -> Result<MeetingFollowUps> {
+    let raw: RawFollowUps = parse_json_response(response, "action items")?;
+    Ok(resolve_follow_ups(raw, segments))
+}
+
+/// Drop empty entries, resolve segment indexes to timestamps and keep transcript order
+pub(crate) fn resolve_follow_ups(
+    raw: RawFollowUps,
+    segments: &[TranscriptionSegment],
+) -> MeetingFollowUps {
+    let source = |segment: Option<usize>| {
+        let segment = segment.filter(|&i| i < segments.len());
+        (segment, segment.map(|i| segments[i].start))
+    };
+
+    let mut action_items: Vec<ActionItem> = raw
+        .action_items
+        .into_iter()
+        .filter_map(|item| {
+            let task = clean(&item.task)?;
+            let (segment, start) = source(item.segment);
+            Some(ActionItem {
+                task,
+                owner: item.owner.as_deref().and_then(clean),
+                due: item.due.as_deref().and_then(clean),
+                segment,
+                start,
+            })
+        })
+        .collect();
+    let mut decisions: Vec<Decision> = raw
+        .decisions
+        .into_iter()
+        .filter_map(|item| {
+            let decision = clean(&item.decision)?;
+            let (segment, start) = source(item.segment);
+            Some(Decision {
+                decision,
+                segment,
+                start,
+            })
+        })
+        .collect();
+
+    // Items without a source sort last; the sort is stable so the model's order stays
+    action_items.sort_by_key(|item| item.segment.unwrap_or(usize::MAX));
+    decisions.sort_by_key(|item| item.segment.unwrap_or(usize::MAX));
+    MeetingFollowUps {
+        action_items,
+        decisions,
+    }
+}
+
+/// Collapse whitespace; `None` for empty text and the "null" some models write out
+pub(crate) fn clean(text: &str) -> Option<String> {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    match text.to_lowercase().as_str() {
+        "" | "null" | "none" | "n/a" => None,
+        _ => Some(text),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_action_items_response() {
+        let segments = vec![
+            TranscriptionSegment::new(0.0, 5.0, "Let's move the launch to May."),
+            TranscriptionSegment::new(5.0, 10.0, "Maria, can you send the budget by Friday?"),
+            TranscriptionSegment::new(10.0, 15.0, "I'll update the roadmap."),
+        ];
+        let response = r#"```json
+{
+  "action_items": [
+    {"task": "Update the  roadmap", "owner": null, "due": "N/A", "segment": 2},
+    {"task": "Send the budget", "owner": "Maria", "due": "Friday", "segment": 1},
+    {"task": "Book a room", "segment": 42},
+    {"task": " "}
+  ],
+  "decisions": [{"decision": "Launch moves to May", "segment": 0}]
+}
+```"#;
+
+        let follow_ups = parse_action_items_response(response, &segments).unwrap();
+        let tasks: Vec<&str> = follow_ups.action_items.iter().map(|a| a.task.as_str()).collect();
+        assert_eq!(tasks, ["Send the budget", "Update the roadmap", "Book a room"]);
+
+        let budget = &follow_ups.action_items[0];
+        assert_eq!(budget.owner.as_deref(), Some("Maria"));
+        assert_eq!(budget.start, Some(5.0));
+        assert_eq!(follow_ups.action_items[1].due, None);
+        // An out-of-range segment leaves the item without a timestamp
+        assert_eq!(follow_ups.action_items[2].start, None);
+
+        assert_eq!(follow_ups.decisions.len(), 1);
+        assert_eq!(follow_ups.decisions[0].start, Some(0.0));
+    }
+
+    #[test]
+    fn test_parse_action_items_response_without_follow_ups() {
+        let follow_ups = parse_action_items_response("{}", &[]).unwrap();
+        assert_eq!(follow_ups, MeetingFollowUps::default());
+    }
+}
@@ -0,0 +1,208 @@
+use crate::error::{AppError, Result};
+use crate::services::concurrency::ConcurrencyLimits;
+use crate::services::hooks::PostProcessingHook;
+use crate::services::http::NetworkTimeouts;
+use crate::services::rate_limit::RateLimit;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Settings the backend needs to know about.
+/// UI preferences stay in the frontend; only options that change how
+/// commands behave are kept here.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AppSettings {
+    /// Use `OPENAI_API_KEY`-style environment variables (or a `.env` file)
+    /// when no key is stored for a provider
+    pub env_key_fallback: bool,
+    /// Poll the watched directory even on local disks (for volumes that are
+    /// not detected as network or exFAT but still miss change events)
+    pub always_poll_watcher: bool,
+    /// Seconds between scans when the watcher falls back to polling
+    pub watch_poll_interval_secs: u64,
+    /// Base URL of a self-hosted OpenAI-compatible server
+    /// (LM Studio, text-generation-webui, vLLM), e.g. `http://localhost:1234/v1`
+    pub openai_compatible_base_url: Option<String>,
+    /// Base URLs replacing the built-in API address of the OpenAI-protocol
+    /// providers, by provider id (`openai`, `groq`), e.g. to send `openai`
+    /// requests through a proxy or to a local llama.cpp server
+    pub provider_base_urls: BTreeMap<String, String>,
+    /// Minutes between checks of subscribed podcast feeds (0 turns polling off)
+    pub feed_poll_interval_minutes: u64,
+    /// Show an OS notification when a long job (transcription, download,
+    /// processing) finishes while the app is in the background
+    pub job_notifications: bool,
+    /// External scripts `process_media` runs after its stages
+    pub post_processing_hooks: Vec<PostProcessingHook>,
+    /// Client-side request limits per cloud provider id (`openai`, `claude`, ...)
+    pub rate_limits: BTreeMap<String, RateLimit>,
+    /// Record feature use and stage timings in the local database (opt-in;
+    /// nothing is sent anywhere)
+    pub metrics_enabled: bool,
+    /// Let `check_for_updates` look up new releases on GitHub
+    pub update_checks: bool,
+    /// Language of the error messages commands return ("en", "ko", "ja");
+    /// the UI sets it along with its own language
+    pub locale: String,
+    /// Folder scheduled backups of the database and settings go to (none
+    /// turns them off)
+    pub backup_dir: Option<String>,
+    /// Hours between scheduled backups (0 turns them off)
+    pub backup_interval_hours: u64,
+    /// Scheduled backups kept in the backup folder; older ones are deleted
+    pub backup_keep: usize,
+    /// Proxy for all network requests, e.g. `http://proxy.local:3128` or
+    /// `socks5://127.0.0.1:1080`
+    pub http_proxy: Option<String>,
+    /// How many extractions, whisper processes, downloads and queue jobs run at once
+    pub concurrency_limits: ConcurrencyLimits,
+    /// Connect, read and overall timeouts of network requests
+    pub network_timeouts: NetworkTimeouts,
+    /// Hours after which leftover temp audio and whisper output is deleted
+    /// on launch (0 keeps it)
+    pub temp_file_max_age_hours: u64,
+    /// Transcribe through a background `whisper-server` that keeps the model
+    /// loaded, instead of starting whisper.cpp for every file
+    pub whisper_server_mode: bool,
+}
+
+impl Default for AppSettings {
+    fn default() -> Self {
+        Self {
+            env_key_fallback: false,
+            always_poll_watcher: false,
+            watch_poll_interval_secs: 5,
+            openai_compatible_base_url: None,
+            provider_base_urls: BTreeMap::new(),
+            feed_poll_interval_minutes: 60,
+            job_notifications: true,
+            post_processing_hooks: Vec::new(),
+            rate_limits: BTreeMap::new(),
+            metrics_enabled: false,
+            update_checks: true,
+            locale: "en".to_string(),
+            backup_dir: None,
+            backup_interval_hours: 24,
+            backup_keep: 7,
+            http_proxy: None,
+            concurrency_limits: ConcurrencyLimits::default(),
+            network_timeouts: NetworkTimeouts::default(),
+            temp_file_max_age_hours: 24,
+            whisper_server_mode: false,
+        }
+    }
+}
+
+impl AppSettings {
+    /// Get the default settings file path
+    pub fn default_path() -> Result<PathBuf> {
+        let data_dir = dirs::data_local_dir()
+            .ok_or_else(|| AppError::InvalidPath("Cannot find data directory".to_string()))?;
+        Ok(data_dir.join("clip-flow").join("settings.json"))
+    }
+
+    /// Load settings from the default location
+    pub fn load() -> Result<Self> {
+        Self::load_from(&Self::default_path()?)
+    }
+
+    /// Load settings from a file (defaults if missing)
+    pub fn load_from(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    /// Save settings to the default location
+    pub fn save(&self) -> Result<()> {
+        self.save_to(&Self::default_path()?)
+    }
+
+    /// Save settings to a file
+    pub fn save_to(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_missing_file_uses_defaults() {
+        let temp_dir = TempDir::new().unwrap();
+        let settings = AppSettings::load_from(&temp_dir.path().join("settings.json")).unwrap();
+        assert_eq!(settings, AppSettings::default());
+        assert!(!settings.env_key_fallback);
+    }
+
+    #[test]
+    fn test_save_and_load_roundtrip() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("nested").join("settings.json");
+
+        let settings = AppSettings {
+            env_key_fallback: true,
+            always_poll_watcher: true,
+            watch_poll_interval_secs: 30,
+            openai_compatible_base_url: Some("http://localhost:1234/v1".to_string()),
+            provider_base_urls: BTreeMap::from([(
+                "openai".to_string(),
+                "http://localhost:8080/v1".to_string(),
+            )]),
+            feed_poll_interval_minutes: 15,
+            job_notifications: false,
+            post_processing_hooks: Vec::new(),
+            rate_limits: BTreeMap::from([(
+                "openai".to_string(),
+                RateLimit {
+                    requests_per_minute: Some(60),
+                    tokens_per_minute: None,
+                },
+            )]),
+            metrics_enabled: true,
+            update_checks: false,
+            locale: "ko".to_string(),
+            backup_dir: Some("/backups/clip-flow".to_string()),
+            backup_interval_hours: 12,
+            backup_keep: 3,
+            http_proxy: Some("http://proxy.local:3128".to_string()),
+            concurrency_limits: ConcurrencyLimits {
+                extractions: 4,
+                transcriptions: 2,
+                downloads: 1,
+                queued_jobs: 4,
+            },
+            network_timeouts: NetworkTimeouts {
+                connect_secs: 5,
+                read_secs: 60,
+                request_secs: 120,
+            },
+            temp_file_max_age_hours: 6,
+            whisper_server_mode: true,
+        };
+        settings.save_to(&path).unwrap();
+
+        assert_eq!(AppSettings::load_from(&path).unwrap(), settings);
+    }
+
+    #[test]
+    fn test_unknown_fields_are_ignored() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("settings.json");
+        std::fs::write(&path, r#"{"envKeyFallback": true, "removedOption": 3}"#).unwrap();
+
+        let settings = AppSettings::load_from(&path).unwrap();
+        assert!(settings.env_key_fallback);
+        // Options missing from older files keep their defaults
+        assert_eq!(settings.watch_poll_interval_secs, 5);
+    }
+}
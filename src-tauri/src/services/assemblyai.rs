@@ -0,0 +1,468 @@
+use crate::error::{AppError, Result};
+use crate::services::chapters::Chapter;
+use crate::services::http;
+use crate::services::cancellation::cancellable;
+use crate::services::providers;
+use crate::services::transcription_provider::{ProgressFn, TranscriptionProvider};
+use crate::services::usage::{record_usage, ApiUsage};
+use crate::services::whisper::{TranscriptionResult, TranscriptionSegment, TranscriptionWord};
+use async_trait::async_trait;
+use reqwest::{Client, RequestBuilder, Response};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+
+const ASSEMBLYAI_API_BASE: &str = "https://api.assemblyai.com/v2";
+
+/// How often a submitted transcript is checked
+const POLL_INTERVAL: Duration = Duration::from_secs(3);
+
+/// AssemblyAI API service for transcription and audio intelligence
+pub struct AssemblyAIService {
+    client: Client,
+    api_key: String,
+}
+
+/// Audio intelligence features to run along with the transcription
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AssemblyAIFeatures {
+    /// Label who speaks each segment
+    pub speaker_labels: bool,
+    /// Split the recording into chapters with headlines (auto chapters)
+    pub chapters: bool,
+    /// Detect named entities (people, organizations, locations, ...)
+    pub entities: bool,
+    /// Classify the sentiment of each sentence
+    pub sentiment: bool,
+}
+
+/// A named entity mentioned in the recording
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DetectedEntity {
+    /// Entity type, e.g. `person_name` or `location`
+    pub kind: String,
+    pub text: String,
+    pub start: f64,
+    pub end: f64,
+}
+
+/// Sentiment of a sentence
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SentenceSentiment {
+    pub start: f64,
+    pub end: f64,
+    pub text: String,
+    /// `positive`, `neutral` or `negative`
+    pub sentiment: String,
+    pub confidence: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub speaker: Option<String>,
+}
+
+/// A transcription with the requested audio intelligence results
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AssemblyAITranscription {
+    pub transcription: TranscriptionResult,
+    pub chapters: Vec<Chapter>,
+    pub entities: Vec<DetectedEntity>,
+    pub sentiments: Vec<SentenceSentiment>,
+}
+
+// ============================================================================
+// AssemblyAI API Types
+// ============================================================================
+
+#[derive(Debug, Clone, Deserialize)]
+struct UploadResponse {
+    upload_url: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct TranscriptRequest {
+    audio_url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    language_code: Option<String>,
+    language_detection: bool,
+    speaker_labels: bool,
+    auto_chapters: bool,
+    entity_detection: bool,
+    sentiment_analysis: bool,
+}
+
+/// Times in the API are in milliseconds
+#[derive(Debug, Clone, Deserialize)]
+struct Transcript {
+    id: String,
+    status: String,
+    #[serde(default)]
+    error: Option<String>,
+    #[serde(default)]
+    text: Option<String>,
+    #[serde(default)]
+    language_code: Option<String>,
+    #[serde(default)]
+    audio_duration: Option<f64>,
+    #[serde(default)]
+    chapters: Option<Vec<ApiChapter>>,
+    #[serde(default)]
+    entities: Option<Vec<ApiEntity>>,
+    #[serde(default)]
+    sentiment_analysis_results: Option<Vec<ApiSentiment>>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct ApiChapter {
+    headline: String,
+    start: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct ApiEntity {
+    entity_type: String,
+    text: String,
+    start: u64,
+    end: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct ApiSentiment {
+    text: String,
+    start: u64,
+    end: u64,
+    sentiment: String,
+    confidence: f64,
+    #[serde(default)]
+    speaker: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct SentencesResponse {
+    sentences: Vec<ApiSentence>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct ApiSentence {
+    text: String,
+    start: u64,
+    end: u64,
+    #[serde(default)]
+    confidence: Option<f64>,
+    #[serde(default)]
+    speaker: Option<String>,
+    #[serde(default)]
+    words: Vec<ApiWord>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct ApiWord {
+    text: String,
+    start: u64,
+    end: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct ApiErrorResponse {
+    error: String,
+}
+
+// ============================================================================
+// AssemblyAI Service Implementation
+// ============================================================================
+
+impl AssemblyAIService {
+    /// Create a new AssemblyAI service with API key
+    pub fn new(api_key: &str) -> Self {
+        Self {
+            client: http::client(),
+            api_key: api_key.to_string(),
+        }
+    }
+
+    /// Send an authenticated request and parse its JSON response
+    async fn send<T: serde::de::DeserializeOwned>(&self, request: RequestBuilder) -> Result<T> {
+        let response = request.header("Authorization", &self.api_key).send().await?;
+        if !response.status().is_success() {
+            return Err(api_error(response).await);
+        }
+        Ok(response.json().await?)
+    }
+
+    /// Transcribe an audio or video file (AssemblyAI extracts the audio itself),
+    /// running the requested audio intelligence features.
+    /// `language` is an ISO code; `None` or `auto` detects the language.
+    pub async fn transcribe(
+        &self,
+        media_path: &Path,
+        language: Option<&str>,
+        features: AssemblyAIFeatures,
+        cancel: &CancellationToken,
+        on_progress: ProgressFn,
+    ) -> Result<AssemblyAITranscription> {
+        on_progress(0.0);
+        let upload_url = cancellable(cancel, self.upload(media_path)).await?;
+        on_progress(30.0);
+
+        let language_code = language.filter(|l| *l != "auto").map(str::to_string);
+        let request = TranscriptRequest {
+            audio_url: upload_url,
+            language_detection: language_code.is_none(),
+            language_code,
+            speaker_labels: features.speaker_labels,
+            auto_chapters: features.chapters,
+            entity_detection: features.entities,
+            sentiment_analysis: features.sentiment,
+        };
+        let submitted: Transcript = self
+            .send(self.client.post(format!("{}/transcript", ASSEMBLYAI_API_BASE)).json(&request))
+            .await?;
+
+        let transcript = self.wait_for_transcript(&submitted.id, cancel, &on_progress).await?;
+        let seconds = transcript.audio_duration.unwrap_or_default();
+        record_usage(ApiUsage::audio(
+            providers::ASSEMBLYAI,
+            providers::ASSEMBLYAI,
+            seconds,
+        ))
+        .await;
+        let url = format!("{}/transcript/{}/sentences", ASSEMBLYAI_API_BASE, transcript.id);
+        let sentences: SentencesResponse = self.send(self.client.get(url)).await?;
+        on_progress(100.0);
+
+        Ok(map_transcript(transcript, sentences.sentences))
+    }
+
+    /// Upload a local file, returning the URL AssemblyAI transcribes from
+    async fn upload(&self, path: &Path) -> Result<String> {
+        let file = tokio::fs::File::open(path).await?;
+        let response: UploadResponse = self
+            .send(self.client.post(format!("{}/upload", ASSEMBLYAI_API_BASE)).body(file))
+            .await?;
+        Ok(response.upload_url)
+    }
+
+    /// Poll a transcript until it is completed or failed
+    async fn wait_for_transcript(
+        &self,
+        id: &str,
+        cancel: &CancellationToken,
+        on_progress: &ProgressFn,
+    ) -> Result<Transcript> {
+        let url = format!("{}/transcript/{}", ASSEMBLYAI_API_BASE, id);
+        loop {
+            let transcript: Transcript = self.send(self.client.get(&url)).await?;
+            match transcript.status.as_str() {
+                "completed" => return Ok(transcript),
+                "error" => {
+                    return Err(AppError::ProcessFailed(format!(
+                        "AssemblyAI transcription failed: {}",
+                        transcript.error.unwrap_or_default()
+                    )))
+                }
+                "processing" => on_progress(60.0),
+                _ => on_progress(40.0),
+            }
+
+            tokio::select! {
+                _ = cancel.cancelled() => return Err(AppError::Cancelled),
+                _ = tokio::time::sleep(POLL_INTERVAL) => {}
+            }
+        }
+    }
+}
+
+/// Speech-to-text through AssemblyAI, without audio intelligence features
+pub struct AssemblyAIProvider {
+    service: AssemblyAIService,
+}
+
+impl AssemblyAIProvider {
+    pub fn new(api_key: &str) -> Self {
+        Self {
+            service: AssemblyAIService::new(api_key),
+        }
+    }
+}
+
+#[async_trait]
+impl TranscriptionProvider for AssemblyAIProvider {
+    fn id(&self) -> &'static str {
+        providers::ASSEMBLYAI
+    }
+
+    fn model(&self) -> &str {
+        providers::ASSEMBLYAI
+    }
+
+    async fn transcribe(
+        &self,
+        audio_path: &Path,
+        language: Option<&str>,
+        cancel: &CancellationToken,
+        on_progress: ProgressFn,
+    ) -> Result<TranscriptionResult> {
+        let features = AssemblyAIFeatures::default();
+        let result = self
+            .service
+            .transcribe(audio_path, language, features, cancel, on_progress)
+            .await?;
+        Ok(result.transcription)
+    }
+}
+
+async fn api_error(response: Response) -> AppError {
+    let status = response.status();
+    let body = response.text().await.unwrap_or_default();
+    let message = serde_json::from_str::<ApiErrorResponse>(&body)
+        .map(|e| e.error)
+        .unwrap_or_else(|_| format!("HTTP {}", status));
+    AppError::for_status(
+        status,
+        AppError::ProcessFailed(format!("AssemblyAI API error: {}", message)),
+    )
+}
+
+fn seconds(millis: u64) -> f64 {
+    millis as f64 / 1000.0
+}
+
+/// Speaker labels come back as `A`, `B`, ...
+fn speaker_name(label: String) -> String {
+    format!("Speaker {}", label)
+}
+
+/// Map an AssemblyAI transcript onto the app's segment and chapter model,
+/// using the transcript's sentences as segments
+fn map_transcript(transcript: Transcript, sentences: Vec<ApiSentence>) -> AssemblyAITranscription {
+    let segments: Vec<TranscriptionSegment> = sentences
+        .into_iter()
+        .map(|s| TranscriptionSegment {
+            start: seconds(s.start),
+            end: seconds(s.end),
+            text: s.text,
+            speaker: s.speaker.map(speaker_name),
+            confidence: s.confidence,
+            words: s
+                .words
+                .into_iter()
+                .map(|w| TranscriptionWord {
+                    start: seconds(w.start),
+                    end: seconds(w.end),
+                    text: w.text,
+                })
+                .collect(),
+        })
+        .collect();
+    let duration = transcript
+        .audio_duration
+        .or_else(|| segments.last().map(|s| s.end))
+        .unwrap_or(0.0);
+
+    let chapters = transcript
+        .chapters
+        .unwrap_or_default()
+        .into_iter()
+        .map(|c| Chapter {
+            start: seconds(c.start),
+            title: c.headline,
+        })
+        .collect();
+    let entities = transcript
+        .entities
+        .unwrap_or_default()
+        .into_iter()
+        .map(|e| DetectedEntity {
+            kind: e.entity_type,
+            text: e.text,
+            start: seconds(e.start),
+            end: seconds(e.end),
+        })
+        .collect();
+    let sentiments = transcript
+        .sentiment_analysis_results
+        .unwrap_or_default()
+        .into_iter()
+        .map(|s| SentenceSentiment {
+            start: seconds(s.start),
+            end: seconds(s.end),
+            text: s.text,
+            sentiment: s.sentiment.to_lowercase(),
+            confidence: s.confidence,
+            speaker: s.speaker.map(speaker_name),
+        })
+        .collect();
+
+    AssemblyAITranscription {
+        transcription: TranscriptionResult {
+            segments,
+            full_text: transcript.text.unwrap_or_default(),
+            language: transcript.language_code,
+            duration,
+        },
+        chapters,
+        entities,
+        sentiments,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_map_transcript() {
+        let transcript: Transcript = serde_json::from_str(
+            r#"{
+                "id": "t1", "status": "completed", "text": "Hi Ann. Great news.",
+                "language_code": "en", "audio_duration": 12.0,
+                "chapters": [{"headline": "Greeting", "gist": "hi", "summary": "s",
+                              "start": 0, "end": 4000}],
+                "entities": [{"entity_type": "person_name", "text": "Ann",
+                              "start": 300, "end": 700}],
+                "sentiment_analysis_results": [{"text": "Great news.", "start": 1500,
+                    "end": 2500, "sentiment": "POSITIVE", "confidence": 0.9, "speaker": "B"}]
+            }"#,
+        )
+        .unwrap();
+        let sentences: SentencesResponse = serde_json::from_str(
+            r#"{"sentences": [
+                {"text": "Hi Ann.", "start": 0, "end": 1200, "confidence": 0.97, "speaker": "A",
+                 "words": [{"text": "Hi", "start": 0, "end": 400},
+                           {"text": "Ann.", "start": 450, "end": 1200}]},
+                {"text": "Great news.", "start": 1500, "end": 2500, "confidence": 0.9,
+                 "speaker": "B"}
+            ]}"#,
+        )
+        .unwrap();
+
+        let result = map_transcript(transcript, sentences.sentences);
+        let segments = &result.transcription.segments;
+        assert_eq!(segments.len(), 2);
+        assert_eq!((segments[0].start, segments[0].end), (0.0, 1.2));
+        assert_eq!(segments[1].speaker.as_deref(), Some("Speaker B"));
+        assert_eq!(segments[0].words[1].start, 0.45);
+        assert!(segments[1].words.is_empty());
+        assert_eq!(result.transcription.duration, 12.0);
+        assert_eq!(result.chapters[0].title, "Greeting");
+        assert_eq!(result.entities[0].kind, "person_name");
+        assert_eq!(result.entities[0].start, 0.3);
+        assert_eq!(result.sentiments[0].sentiment, "positive");
+    }
+
+    #[test]
+    fn test_request_detects_language_when_not_given() {
+        let request = TranscriptRequest {
+            audio_url: "https://cdn.example/a".to_string(),
+            language_code: None,
+            language_detection: true,
+            speaker_labels: false,
+            auto_chapters: true,
+            entity_detection: false,
+            sentiment_analysis: false,
+        };
+        let json = serde_json::to_value(&request).unwrap();
+        assert!(json.get("language_code").is_none());
+        assert_eq!(json["auto_chapters"], true);
+    }
+}
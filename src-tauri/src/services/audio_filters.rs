@@ -0,0 +1,127 @@
+//! Optional ffmpeg filters applied while extracting audio for transcription,
+//! to clean up noisy room recordings before whisper hears them.
+
+use crate::error::{AppError, Result};
+use serde::{Deserialize, Serialize};
+
+/// Noise reduction filter
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum Denoise {
+    /// FFT denoiser (`afftdn`), available in every ffmpeg build
+    Fft {
+        /// Noise reduction in dB (0.01-97, ffmpeg's default is 12)
+        #[serde(default)]
+        reduction_db: Option<f32>,
+    },
+    /// Recurrent neural network denoiser (`arnndn`) with an RNNoise model file
+    Rnn { model_path: String },
+}
+
+/// Filters to run before transcription, in the order high-pass, denoise,
+/// compressor. All off by default.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AudioFilters {
+    pub denoise: Option<Denoise>,
+    /// Cut rumble and hum below this frequency (e.g. 80-120 Hz)
+    pub highpass_hz: Option<u32>,
+    /// Even out quiet and loud speakers with `acompressor`
+    pub compressor: bool,
+}
+
+impl AudioFilters {
+    /// Whether no filter is on
+    pub fn is_off(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// The ffmpeg `-af` filter chain, or none when no filter is on
+    pub fn filter_chain(&self) -> Result<Option<String>> {
+        let mut filters = Vec::new();
+        if let Some(hz) = self.highpass_hz.filter(|hz| *hz > 0) {
+            filters.push(format!("highpass=f={}", hz));
+        }
+        match &self.denoise {
+            Some(Denoise::Fft { reduction_db: Some(db) }) => {
+                if !(0.01..=97.0).contains(db) {
+                    return Err(AppError::InvalidInput(format!("Invalid noise reduction: {} dB", db)));
+                }
+                filters.push(format!("afftdn=nr={}", db));
+            }
+            Some(Denoise::Fft { reduction_db: None }) => filters.push("afftdn".to_string()),
+            Some(Denoise::Rnn { model_path }) => {
+                filters.push(format!("arnndn=m='{}'", escape_filter_path(model_path)?));
+            }
+            None => {}
+        }
+        if self.compressor {
+            filters.push("acompressor".to_string());
+        }
+        Ok((!filters.is_empty()).then(|| filters.join(",")))
+    }
+}
+
+/// A file path as a quoted filter option value. The quotes protect it from
+/// the filtergraph parser; the option parser still needs `:` escaped.
+fn escape_filter_path(path: &str) -> Result<String> {
+    if path.contains('\'') {
+        return Err(AppError::InvalidPath(format!(
+            "Denoise model path can't contain quotes: {}",
+            path
+        )));
+    }
+    Ok(path.replace('\\', "/").replace(':', "\\:"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filter_chain() {
+        assert_eq!(AudioFilters::default().filter_chain().unwrap(), None);
+
+        let filters = AudioFilters {
+            denoise: Some(Denoise::Fft { reduction_db: Some(20.0) }),
+            highpass_hz: Some(100),
+            compressor: true,
+        };
+        assert_eq!(
+            filters.filter_chain().unwrap().unwrap(),
+            "highpass=f=100,afftdn=nr=20,acompressor"
+        );
+
+        let rnn = AudioFilters {
+            denoise: Some(Denoise::Rnn {
+                model_path: r"C:\models\sh.rnnn".to_string(),
+            }),
+            ..Default::default()
+        };
+        assert_eq!(rnn.filter_chain().unwrap().unwrap(), r"arnndn=m='C\:/models/sh.rnnn'");
+
+        let quoted = AudioFilters {
+            denoise: Some(Denoise::Rnn {
+                model_path: "/models/it's.rnnn".to_string(),
+            }),
+            ..Default::default()
+        };
+        assert!(quoted.filter_chain().is_err());
+        let too_much = AudioFilters {
+            denoise: Some(Denoise::Fft { reduction_db: Some(120.0) }),
+            ..Default::default()
+        };
+        assert!(too_much.filter_chain().is_err());
+    }
+
+    #[test]
+    fn test_deserialize() {
+        let filters: AudioFilters = serde_json::from_str(
+            r#"{"denoise": {"kind": "fft"}, "highpass_hz": 80}"#,
+        )
+        .unwrap();
+        assert_eq!(filters.denoise, Some(Denoise::Fft { reduction_db: None }));
+        assert_eq!(filters.highpass_hz, Some(80));
+        assert!(!filters.compressor);
+    }
+}
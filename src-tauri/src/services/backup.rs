@@ -0,0 +1,307 @@
+//! Backups of the project database and the settings file. Each backup is a
+//! folder holding a consistent copy of both, so a restore brings back the
+//! transcripts, history and settings together.
+
+use crate::error::{AppError, Result};
+use crate::services::database::{now, Database};
+use rusqlite::backup::Progress;
+use rusqlite::{Connection, DatabaseName, OpenFlags};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+/// Backup folders are named `clip-flow-backup-YYYYMMDD-HHMMSS` (UTC)
+const BACKUP_PREFIX: &str = "clip-flow-backup-";
+const DATABASE_FILE: &str = "clip-flow.db";
+const SETTINGS_FILE: &str = "settings.json";
+/// Marks a backup made by the scheduler; only those are pruned
+const AUTOMATIC_MARKER: &str = "automatic";
+
+/// How long a restore waits for other connections to finish their writes
+const RESTORE_BUSY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A backup on disk
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BackupInfo {
+    /// The backup folder
+    pub path: String,
+    /// Unix seconds
+    pub created_at: u64,
+    /// Size of the database copy in bytes
+    pub size: u64,
+    pub has_settings: bool,
+    /// Made by the scheduler rather than by the user
+    pub automatic: bool,
+}
+
+fn backup_info(dir: &Path) -> Option<BackupInfo> {
+    let name = dir.file_name()?.to_str()?;
+    let stamp = name.strip_prefix(BACKUP_PREFIX)?;
+    // A suffix tells apart backups made within the same second
+    let created_at = chrono::NaiveDateTime::parse_from_str(stamp.get(..15)?, "%Y%m%d-%H%M%S")
+        .ok()?
+        .and_utc()
+        .timestamp()
+        .max(0) as u64;
+    let size = fs::metadata(dir.join(DATABASE_FILE)).ok()?.len();
+    Some(BackupInfo {
+        path: dir.to_string_lossy().into_owned(),
+        created_at,
+        size,
+        has_settings: dir.join(SETTINGS_FILE).exists(),
+        automatic: dir.join(AUTOMATIC_MARKER).exists(),
+    })
+}
+
+/// Check a database file with SQLite's integrity check
+pub fn check_integrity(path: &Path) -> Result<()> {
+    let damaged = |detail: String| {
+        AppError::InvalidInput(format!("{} is damaged: {}", path.display(), detail))
+    };
+    let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .map_err(|e| damaged(e.to_string()))?;
+    let result: String = conn
+        .query_row("PRAGMA integrity_check", [], |row| row.get(0))
+        .map_err(|e| damaged(e.to_string()))?;
+    if result != "ok" {
+        return Err(damaged(result));
+    }
+    Ok(())
+}
+
+/// Copy a live database to `dest` (which must not exist) in one consistent snapshot
+fn snapshot_database(source: &Path, dest: &Path) -> Result<()> {
+    let conn = Connection::open_with_flags(source, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    conn.execute("VACUUM INTO ?1", [dest.to_string_lossy()])?;
+    Ok(())
+}
+
+/// Back up the database at `db_path` (and the settings file, if there is one)
+/// into a new folder in `dest_dir`. The copy is checked before it is reported.
+/// `automatic` backups are the ones the scheduler made, which it may prune.
+pub fn create_backup(
+    db_path: &Path,
+    settings_path: &Path,
+    dest_dir: &Path,
+    automatic: bool,
+) -> Result<BackupInfo> {
+    if !db_path.exists() {
+        return Err(AppError::InvalidPath(format!(
+            "No database to back up at {}",
+            db_path.display()
+        )));
+    }
+    fs::create_dir_all(dest_dir)?;
+
+    let stamp = chrono::DateTime::from_timestamp(now() as i64, 0)
+        .unwrap_or_default()
+        .format("%Y%m%d-%H%M%S")
+        .to_string();
+    let mut dir = dest_dir.join(format!("{}{}", BACKUP_PREFIX, stamp));
+    let mut n = 1;
+    while dir.exists() {
+        n += 1;
+        dir = dest_dir.join(format!("{}{}-{}", BACKUP_PREFIX, stamp, n));
+    }
+    fs::create_dir(&dir)?;
+
+    let written = snapshot_database(db_path, &dir.join(DATABASE_FILE))
+        .and_then(|()| check_integrity(&dir.join(DATABASE_FILE)))
+        .and_then(|()| {
+            if settings_path.exists() {
+                fs::copy(settings_path, dir.join(SETTINGS_FILE))?;
+            }
+            if automatic {
+                fs::write(dir.join(AUTOMATIC_MARKER), "")?;
+            }
+            Ok(())
+        });
+    if let Err(e) = written {
+        let _ = fs::remove_dir_all(&dir);
+        return Err(e);
+    }
+
+    backup_info(&dir)
+        .ok_or_else(|| AppError::InvalidPath(format!("Backup not found: {}", dir.display())))
+}
+
+/// Backups in `dir`, newest first
+pub fn list_backups(dir: &Path) -> Result<Vec<BackupInfo>> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut backups: Vec<BackupInfo> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| backup_info(&entry.path()))
+        .collect();
+    backups.sort_by(|a, b| b.created_at.cmp(&a.created_at).then(b.path.cmp(&a.path)));
+    Ok(backups)
+}
+
+/// Delete all but the newest `keep` automatic backups in `dir`. Backups the
+/// user made are kept.
+pub fn prune_backups(dir: &Path, keep: usize) -> Result<()> {
+    let automatic = list_backups(dir)?
+        .into_iter()
+        .filter(|backup| backup.automatic);
+    for old in automatic.skip(keep) {
+        fs::remove_dir_all(&old.path)?;
+    }
+    Ok(())
+}
+
+/// Check that a backup folder holds an intact database
+pub fn verify_backup(backup_dir: &Path) -> Result<()> {
+    let backup_db = backup_dir.join(DATABASE_FILE);
+    if !backup_db.exists() {
+        return Err(AppError::InvalidPath(format!(
+            "Not a backup folder: {}",
+            backup_dir.display()
+        )));
+    }
+    check_integrity(&backup_db)
+}
+
+/// Replace the database (and the settings, if the backup has them) with a
+/// backup. The backup is checked first, and the current database is kept next
+/// to it as `clip-flow.db.before-restore`. The backup is written through
+/// SQLite, so connections other commands have open wait for it to finish
+/// instead of reading a half-copied file.
+pub fn restore_backup(backup_dir: &Path, db_path: &Path, settings_path: &Path) -> Result<()> {
+    verify_backup(backup_dir)?;
+    let backup_db = backup_dir.join(DATABASE_FILE);
+    let version: usize = Connection::open_with_flags(&backup_db, OpenFlags::SQLITE_OPEN_READ_ONLY)?
+        .pragma_query_value(None, "user_version", |row| row.get(0))?;
+    if version > Database::schema_version() {
+        return Err(AppError::InvalidInput(
+            "The backup was made by a newer version of the app".to_string(),
+        ));
+    }
+
+    if db_path.exists() {
+        let previous = db_path.with_extension("db.before-restore");
+        if previous.exists() {
+            fs::remove_file(&previous)?;
+        }
+        snapshot_database(db_path, &previous)?;
+    }
+    if let Some(parent) = db_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut conn = Connection::open(db_path)?;
+    conn.busy_timeout(RESTORE_BUSY_TIMEOUT)?;
+    conn.restore(DatabaseName::Main, &backup_db, None::<fn(Progress)>)?;
+    drop(conn);
+    // Bring an older backup up to the current schema
+    Database::open_at(db_path)?;
+
+    let backup_settings = backup_dir.join(SETTINGS_FILE);
+    if backup_settings.exists() {
+        fs::copy(&backup_settings, settings_path)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::database::SummaryInput;
+    use tempfile::TempDir;
+
+    fn summary(text: &str) -> SummaryInput {
+        SummaryInput {
+            text: text.to_string(),
+            language: None,
+            provider: None,
+            model: None,
+        }
+    }
+
+    #[test]
+    fn test_backup_and_restore() {
+        let temp = TempDir::new().unwrap();
+        let db_path = temp.path().join("data").join(DATABASE_FILE);
+        let settings_path = temp.path().join("data").join(SETTINGS_FILE);
+        let backups = temp.path().join("backups");
+
+        Database::open_at(&db_path)
+            .unwrap()
+            .save_summary("/m/a.mp4", &summary("before"))
+            .unwrap();
+        fs::write(&settings_path, r#"{"locale":"ko"}"#).unwrap();
+
+        let first = create_backup(&db_path, &settings_path, &backups, true).unwrap();
+        assert!(first.has_settings && first.size > 0 && first.automatic);
+        let second = create_backup(&db_path, &settings_path, &backups, true).unwrap();
+        assert_ne!(first.path, second.path);
+        assert_eq!(list_backups(&backups).unwrap().len(), 2);
+
+        // A connection stays open across the restore and sees the restored data
+        let open = Database::open_at(&db_path).unwrap();
+        open.save_summary("/m/a.mp4", &summary("after")).unwrap();
+        fs::write(&settings_path, r#"{"locale":"ja"}"#).unwrap();
+
+        restore_backup(Path::new(&first.path), &db_path, &settings_path).unwrap();
+        let restored = open.latest_summary("/m/a.mp4").unwrap();
+        assert_eq!(restored.map(|s| s.summary.text).as_deref(), Some("before"));
+        assert_eq!(
+            fs::read_to_string(&settings_path).unwrap(),
+            r#"{"locale":"ko"}"#
+        );
+        assert!(db_path.with_extension("db.before-restore").exists());
+
+        prune_backups(&backups, 1).unwrap();
+        assert_eq!(list_backups(&backups).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_prune_keeps_manual_backups() {
+        let temp = TempDir::new().unwrap();
+        let db_path = temp.path().join(DATABASE_FILE);
+        let settings_path = temp.path().join(SETTINGS_FILE);
+        let backups = temp.path().join("backups");
+        Database::open_at(&db_path).unwrap();
+
+        let manual = create_backup(&db_path, &settings_path, &backups, false).unwrap();
+        assert!(!manual.automatic);
+        for _ in 0..3 {
+            create_backup(&db_path, &settings_path, &backups, true).unwrap();
+        }
+
+        prune_backups(&backups, 1).unwrap();
+        let left = list_backups(&backups).unwrap();
+        assert_eq!(left.len(), 2);
+        assert!(left.iter().any(|backup| backup.path == manual.path));
+    }
+
+    #[test]
+    fn test_short_folder_name_is_not_a_backup() {
+        let temp = TempDir::new().unwrap();
+        // Cut at 15 bytes, the stamp would end inside a character
+        let dir = temp
+            .path()
+            .join(format!("{}20260101-00000é", BACKUP_PREFIX));
+        fs::create_dir(&dir).unwrap();
+        fs::write(dir.join(DATABASE_FILE), "").unwrap();
+        assert!(list_backups(temp.path()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_damaged_backup_is_rejected() {
+        let temp = TempDir::new().unwrap();
+        let backup = temp
+            .path()
+            .join(format!("{}20260101-000000", BACKUP_PREFIX));
+        fs::create_dir(&backup).unwrap();
+        fs::write(backup.join(DATABASE_FILE), "not a database").unwrap();
+
+        let db_path = temp.path().join(DATABASE_FILE);
+        let settings_path = temp.path().join(SETTINGS_FILE);
+        assert!(verify_backup(&backup).is_err());
+        assert!(restore_backup(&backup, &db_path, &settings_path).is_err());
+        assert!(!db_path.exists());
+        assert!(restore_backup(temp.path(), &db_path, &settings_path).is_err());
+    }
+}
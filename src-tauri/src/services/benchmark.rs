@@ -0,0 +1,104 @@
+//! Transcription speed of the installed whisper models on this machine, used
+//! to estimate how long a file takes and which model to suggest.
+
+use crate::services::database::Benchmark;
+use crate::services::WhisperModel;
+use serde::Serialize;
+
+/// Recording of read speech each model transcribes, in the app's resources.
+/// Tones or noise would let whisper skip most of the decoding work.
+pub const SAMPLE_RESOURCE: &str = "resources/benchmark/speech-sample.wav";
+
+/// Realtime factor up to which a model counts as fast enough to suggest
+pub const RECOMMENDED_MAX_REALTIME_FACTOR: f64 = 0.5;
+
+/// The latest benchmark of each model and the model to suggest
+#[derive(Debug, Clone, Serialize)]
+pub struct BenchmarkReport {
+    pub benchmarks: Vec<Benchmark>,
+    pub recommended_model: Option<String>,
+}
+
+impl BenchmarkReport {
+    pub fn new(benchmarks: Vec<Benchmark>) -> Self {
+        let recommended_model = recommend_model(&benchmarks);
+        Self {
+            benchmarks,
+            recommended_model,
+        }
+    }
+}
+
+/// Seconds `model` should take to transcribe `media_seconds` of audio, if it
+/// has been benchmarked
+pub fn estimate_seconds(benchmarks: &[Benchmark], model: &str, media_seconds: f64) -> Option<f64> {
+    benchmarks
+        .iter()
+        .find(|b| b.model == model)
+        .map(|b| b.realtime_factor * media_seconds)
+}
+
+/// The most accurate benchmarked model that transcribes at least twice as
+/// fast as realtime, else the fastest one
+pub fn recommend_model(benchmarks: &[Benchmark]) -> Option<String> {
+    // Available models are listed from smallest (least accurate) to largest
+    let rank = |model: &str| {
+        WhisperModel::available_models()
+            .iter()
+            .position(|m| m.id == model)
+            .unwrap_or(0)
+    };
+
+    benchmarks
+        .iter()
+        .filter(|b| b.realtime_factor <= RECOMMENDED_MAX_REALTIME_FACTOR)
+        .max_by_key(|b| rank(&b.model))
+        .or_else(|| {
+            benchmarks
+                .iter()
+                .min_by(|a, b| a.realtime_factor.total_cmp(&b.realtime_factor))
+        })
+        .map(|b| b.model.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_SECONDS: f64 = 30.0;
+
+    fn benchmark(model: &str, realtime_factor: f64) -> Benchmark {
+        Benchmark {
+            id: 0,
+            model: model.to_string(),
+            audio_seconds: SAMPLE_SECONDS,
+            elapsed_seconds: realtime_factor * SAMPLE_SECONDS,
+            realtime_factor,
+            created_at: 0,
+        }
+    }
+
+    #[test]
+    fn test_estimate_seconds() {
+        let benchmarks = vec![benchmark("base", 0.1)];
+        assert_eq!(estimate_seconds(&benchmarks, "base", 600.0), Some(60.0));
+        assert_eq!(estimate_seconds(&benchmarks, "small", 600.0), None);
+    }
+
+    #[test]
+    fn test_recommend_model() {
+        assert_eq!(recommend_model(&[]), None);
+
+        let fast_enough = vec![
+            benchmark("tiny", 0.05),
+            benchmark("base", 0.1),
+            benchmark("small", 0.4),
+            benchmark("medium", 1.2),
+        ];
+        assert_eq!(recommend_model(&fast_enough).as_deref(), Some("small"));
+
+        // Nothing fast enough: the fastest one
+        let slow = vec![benchmark("small", 1.5), benchmark("base", 0.8)];
+        assert_eq!(recommend_model(&slow).as_deref(), Some("base"));
+    }
+}
@@ -0,0 +1,30 @@
+use crate::error::{AppError, Result};
+use std::future::Future;
+use tokio_util::sync::CancellationToken;
+
+/// Run a future (e.g. an API request) until it completes or the token is cancelled
+pub async fn cancellable<T>(
+    cancel: &CancellationToken,
+    future: impl Future<Output = Result<T>>,
+) -> Result<T> {
+    tokio::select! {
+        biased;
+        _ = cancel.cancelled() => Err(AppError::Cancelled),
+        result = future => result,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_cancellable() {
+        let cancel = CancellationToken::new();
+        assert_eq!(cancellable(&cancel, async { Ok(1) }).await.unwrap(), 1);
+
+        cancel.cancel();
+        let result = cancellable(&cancel, async { Ok(2) }).await;
+        assert!(matches!(result, Err(AppError::Cancelled)));
+    }
+}
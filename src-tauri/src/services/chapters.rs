@@ -0,0 +1,236 @@
+use crate::error::{AppError, Result};
+use crate::services::ollama::language_code_to_name;
+use crate::services::whisper::TranscriptionSegment;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+
+/// YouTube only shows chapters when there are at least this many
+pub const MIN_CHAPTERS: usize = 3;
+/// Shortest chapter YouTube accepts, in seconds
+pub const MIN_CHAPTER_SECONDS: f64 = 10.0;
+/// Longest chapter title kept
+pub const MAX_TITLE_CHARS: usize = 100;
+
+/// A chapter of a video description
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Chapter {
+    pub start: f64,
+    pub title: String,
+}
+
+/// Chapters plus the ready-to-paste description block
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct YouTubeChapters {
+    pub chapters: Vec<Chapter>,
+    pub text: String,
+}
+
+impl YouTubeChapters {
+    pub fn new(chapters: Vec<Chapter>) -> Self {
+        let text = format_chapters(&chapters);
+        Self { chapters, text }
+    }
+}
+
+/// A chapter boundary as returned by the LLM
+#[derive(Debug, Deserialize)]
+struct ChapterBoundary {
+    index: usize,
+    title: String,
+}
+
+/// One `[index] (start): text` line per segment, the indices chapter
+/// responses refer to
+pub(crate) fn numbered_segments(segments: &[TranscriptionSegment]) -> String {
+    let lines: Vec<String> = segments
+        .iter()
+        .enumerate()
+        .map(|(i, s)| format!("[{}] ({:.1}s): {}", i, s.start, s.text.trim()))
+        .collect();
+    lines.join("\n")
+}
+
+/// Build the chapter segmentation prompt shared by all LLM providers
+pub(crate) fn build_chapters_prompt(segments: &[TranscriptionSegment], language: &str) -> String {
+    format!(
+        "Split this video transcript into chapters by topic, the way YouTube chapters work. \
+         Return a JSON array with the index of the segment where each chapter starts and a \
+         short, descriptive chapter title in {}.\n\n\
+         Rules:\n\
+         - The first chapter starts at segment 0\n\
+         - Use at least {} chapters, each at least {} seconds long\n\
+         - Titles are at most 60 characters, with no timestamps, numbering or quotes\n\n\
+         Segments:\n{}\n\n\
+         Response format: [{{\"index\": 0, \"title\": \"Introduction\"}}, ...]",
+        language_code_to_name(language),
+        MIN_CHAPTERS,
+        MIN_CHAPTER_SECONDS,
+        numbered_segments(segments)
+    )
+}
+
+/// Parse the LLM's chapter boundaries and bring them in line with YouTube's rules.
+/// Like story order responses, only the outermost JSON array is parsed.
+pub(crate) fn parse_chapters_response(
+    response: &str,
+    segments: &[TranscriptionSegment],
+) -> Result<Vec<Chapter>> {
+    let json = match (response.find('['), response.rfind(']')) {
+        (Some(start), Some(end)) if start < end => &response[start..=end],
+        _ => response.trim(),
+    };
+    let boundaries: Vec<ChapterBoundary> = serde_json::from_str(json)
+        .map_err(|_| AppError::ProcessFailed("Failed to parse chapters response".to_string()))?;
+
+    let chapters = boundaries
+        .into_iter()
+        .filter_map(|boundary| {
+            let segment = segments.get(boundary.index)?;
+            Some(Chapter {
+                start: segment.start,
+                title: boundary.title,
+            })
+        })
+        .collect();
+    let duration = segments.iter().map(|s| s.end).fold(0.0, f64::max);
+
+    enforce_youtube_rules(chapters, duration)
+}
+
+/// Clean up a chapter title: one line, no leading timestamp or list marker, capped length
+fn clean_title(title: &str) -> String {
+    static PREFIX: OnceLock<Regex> = OnceLock::new();
+    let prefix = PREFIX.get_or_init(|| {
+        Regex::new(r"^(\d{1,2}:)?\d{1,2}:\d{2}\s*-?\s*|^\d+[.)]\s+")
+            .expect("valid title prefix pattern")
+    });
+
+    let title = title.split_whitespace().collect::<Vec<_>>().join(" ");
+    let title = prefix
+        .replace(&title, "")
+        .trim_matches(|c| matches!(c, '"' | '\''))
+        .trim()
+        .to_string();
+
+    if title.chars().count() <= MAX_TITLE_CHARS {
+        return title;
+    }
+    let short: String = title.chars().take(MAX_TITLE_CHARS - 1).collect();
+    format!("{}…", short.trim_end())
+}
+
+/// Apply YouTube's chapter rules: ascending, the first at 0:00, every chapter at
+/// least ten seconds long and at least three chapters. Too-short chapters are
+/// merged into the one before them.
+pub fn enforce_youtube_rules(mut chapters: Vec<Chapter>, duration: f64) -> Result<Vec<Chapter>> {
+    chapters.sort_by(|a, b| a.start.total_cmp(&b.start));
+
+    let mut valid: Vec<Chapter> = Vec::new();
+    for chapter in chapters {
+        let title = clean_title(&chapter.title);
+        if title.is_empty() {
+            continue;
+        }
+        match valid.last() {
+            None => valid.push(Chapter { start: 0.0, title }),
+            Some(last) if chapter.start - last.start >= MIN_CHAPTER_SECONDS => {
+                valid.push(Chapter {
+                    start: chapter.start,
+                    title,
+                })
+            }
+            Some(_) => {}
+        }
+    }
+
+    // The last chapter also needs ten seconds before the video ends
+    if valid.len() > 1 && valid.last().is_some_and(|c| duration - c.start < MIN_CHAPTER_SECONDS) {
+        valid.pop();
+    }
+
+    if valid.len() < MIN_CHAPTERS {
+        return Err(AppError::InvalidInput(format!(
+            "YouTube needs at least {} chapters of {} seconds or more, but only {} could be made",
+            MIN_CHAPTERS,
+            MIN_CHAPTER_SECONDS,
+            valid.len()
+        )));
+    }
+    Ok(valid)
+}
+
+/// Format a chapter start (`00:00`, or `0:00:00` from an hour on)
+fn chapter_timestamp(seconds: f64) -> String {
+    let total = seconds.max(0.0) as u64;
+    let (hours, minutes, secs) = (total / 3600, (total / 60) % 60, total % 60);
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, secs)
+    } else {
+        format!("{:02}:{:02}", minutes, secs)
+    }
+}
+
+/// Render chapters as `00:00 Title` lines for a video description
+pub fn format_chapters(chapters: &[Chapter]) -> String {
+    chapters
+        .iter()
+        .map(|c| format!("{} {}\n", chapter_timestamp(c.start), c.title))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chapter(start: f64, title: &str) -> Chapter {
+        Chapter {
+            start,
+            title: title.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_enforce_youtube_rules() {
+        let chapters = vec![
+            chapter(95.0, "2024 wrap-up"),
+            chapter(3.0, "01:05 - \"Intro\""),
+            chapter(40.0, "3. Setup"),
+            chapter(45.0, "Too close"),
+            chapter(295.0, "Outro"),
+        ];
+
+        let valid = enforce_youtube_rules(chapters, 300.0).unwrap();
+        assert_eq!(
+            valid,
+            vec![chapter(0.0, "Intro"), chapter(40.0, "Setup"), chapter(95.0, "2024 wrap-up")]
+        );
+        assert_eq!(format_chapters(&valid), "00:00 Intro\n00:40 Setup\n01:35 2024 wrap-up\n");
+    }
+
+    #[test]
+    fn test_enforce_youtube_rules_needs_three_chapters() {
+        let chapters = vec![chapter(0.0, "Intro"), chapter(5.0, "Main")];
+        assert!(enforce_youtube_rules(chapters, 600.0).is_err());
+    }
+
+    #[test]
+    fn test_parse_chapters_response() {
+        let segments: Vec<TranscriptionSegment> = (0..10)
+            .map(|i| {
+                TranscriptionSegment::new(i as f64 * 30.0, i as f64 * 30.0 + 30.0, &format!("Segment {}", i))
+            })
+            .collect();
+        let response = r#"```json
+[{"index": 0, "title": "Intro"}, {"index": 4, "title": "Demo"},
+ {"index": 42, "title": "Bogus"}, {"index": 8, "title": "Q&A"}]
+```"#;
+
+        let chapters = parse_chapters_response(response, &segments).unwrap();
+        assert_eq!(
+            chapters,
+            vec![chapter(0.0, "Intro"), chapter(120.0, "Demo"), chapter(240.0, "Q&A")]
+        );
+        assert_eq!(chapter_timestamp(3725.0), "1:02:05");
+    }
+}
@@ -1,4 +1,10 @@
 use crate::error::{AppError, Result};
+use crate::services::chapters::{build_chapters_prompt, parse_chapters_response, Chapter};
+use crate::services::http;
+use crate::services::ollama::{build_story_order_prompt, parse_story_order_response, StorySegment};
+use crate::services::providers;
+use crate::services::rate_limit::{self, estimate_tokens};
+use crate::services::usage::{record_usage, ApiUsage};
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 
@@ -79,7 +85,7 @@ impl ClaudeService {
     /// Create a new Claude service with API key
     pub fn new(api_key: &str) -> Self {
         Self {
-            client: Client::new(),
+            client: http::client(),
             api_key: api_key.to_string(),
         }
     }
@@ -94,6 +100,10 @@ impl ClaudeService {
         max_tokens: u32,
     ) -> Result<String> {
         let url = format!("{}/messages", CLAUDE_API_BASE);
+        let prompt_chars = messages.iter().map(|m| m.content.len()).sum::<usize>()
+            + system.map_or(0, str::len);
+        let tokens = estimate_tokens(prompt_chars, Some(max_tokens));
+        rate_limit::acquire(providers::CLAUDE, tokens).await;
 
         let request = ClaudeRequest {
             model: model.to_string(),
@@ -115,6 +125,14 @@ impl ClaudeService {
 
         if response.status().is_success() {
             let result: ClaudeResponse = response.json().await?;
+            record_usage(ApiUsage::tokens(
+                providers::CLAUDE,
+                model,
+                "chat",
+                result.usage.input_tokens.into(),
+                result.usage.output_tokens.into(),
+            ))
+            .await;
             let text = result
                 .content
                 .iter()
@@ -123,11 +141,12 @@ impl ClaudeService {
                 .join("");
             Ok(text)
         } else {
+            let status = response.status();
             let error_response: ClaudeErrorResponse = response.json().await?;
-            Err(AppError::Whisper(format!(
-                "Claude API error: {}",
-                error_response.error.message
-            )))
+            Err(AppError::for_status(
+                status,
+                AppError::Llm(format!("Claude API error: {}", error_response.error.message)),
+            ))
         }
     }
 
@@ -163,6 +182,50 @@ impl ClaudeService {
             .await
     }
 
+    /// Extract story order / timeline from transcription segments
+    pub async fn extract_story_order(
+        &self,
+        model: &str,
+        segments: &[super::whisper::TranscriptionSegment],
+    ) -> Result<Vec<StorySegment>> {
+        let system = "You are an expert video editor who restructures interview and \
+                      vlog footage into a compelling narrative. \
+                      Respond with the JSON array only, without any explanation.";
+
+        let messages = vec![ClaudeMessage {
+            role: "user".to_string(),
+            content: build_story_order_prompt(segments),
+        }];
+
+        let response = self
+            .message(model, messages, Some(system), Some(0.2), 4096)
+            .await?;
+
+        parse_story_order_response(&response)
+    }
+
+    /// Split transcription segments into YouTube chapters with generated titles
+    pub async fn generate_chapters(
+        &self,
+        model: &str,
+        segments: &[super::whisper::TranscriptionSegment],
+        language: &str,
+    ) -> Result<Vec<Chapter>> {
+        let system = "You are an expert YouTube editor who writes clear, searchable \
+                      chapter titles. Respond with the JSON array only, without any explanation.";
+
+        let messages = vec![ClaudeMessage {
+            role: "user".to_string(),
+            content: build_chapters_prompt(segments, language),
+        }];
+
+        let response = self
+            .message(model, messages, Some(system), Some(0.3), 2048)
+            .await?;
+
+        parse_chapters_response(&response, segments)
+    }
+
     /// Check if API key is valid
     pub async fn validate_api_key(&self) -> Result<bool> {
         // Send a minimal request to check if key is valid
@@ -239,7 +302,7 @@ impl ClaudeService {
             Ok(models)
         } else {
             let error_text = response.text().await.unwrap_or_default();
-            Err(AppError::Whisper(format!(
+            Err(AppError::Llm(format!(
                 "Failed to fetch Claude models: {}",
                 error_text
             )))
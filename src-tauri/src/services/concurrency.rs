@@ -0,0 +1,161 @@
+//! Limits on how many heavy jobs of each kind run at once, so batches queue
+//! instead of pinning every core.
+
+use crate::error::{AppError, Result};
+use crate::services::app_settings::AppSettings;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, RwLock};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio_util::sync::CancellationToken;
+
+/// Kinds of work that are limited separately
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobKind {
+    /// ffmpeg audio extraction
+    Extraction,
+    /// whisper.cpp processes
+    Transcription,
+    /// Model, podcast and yt-dlp downloads
+    Download,
+    /// Jobs of the background queue
+    Queued,
+}
+
+/// How many jobs of each kind may run at the same time (at least one each)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ConcurrencyLimits {
+    pub extractions: usize,
+    pub transcriptions: usize,
+    pub downloads: usize,
+    /// Background queue jobs; their steps still wait for the limits above,
+    /// so the next file's audio is extracted while one is transcribed
+    pub queued_jobs: usize,
+}
+
+impl Default for ConcurrencyLimits {
+    fn default() -> Self {
+        Self {
+            extractions: 2,
+            transcriptions: 1,
+            downloads: 3,
+            queued_jobs: 2,
+        }
+    }
+}
+
+impl ConcurrencyLimits {
+    fn limit(&self, kind: JobKind) -> usize {
+        let limit = match kind {
+            JobKind::Extraction => self.extractions,
+            JobKind::Transcription => self.transcriptions,
+            JobKind::Download => self.downloads,
+            JobKind::Queued => self.queued_jobs,
+        };
+        limit.max(1)
+    }
+}
+
+/// One semaphore per job kind
+struct Slots {
+    extraction: Arc<Semaphore>,
+    transcription: Arc<Semaphore>,
+    download: Arc<Semaphore>,
+    queued: Arc<Semaphore>,
+}
+
+impl Slots {
+    fn new(limits: &ConcurrencyLimits) -> Self {
+        let semaphore = |kind| Arc::new(Semaphore::new(limits.limit(kind)));
+        Self {
+            extraction: semaphore(JobKind::Extraction),
+            transcription: semaphore(JobKind::Transcription),
+            download: semaphore(JobKind::Download),
+            queued: semaphore(JobKind::Queued),
+        }
+    }
+
+    fn get(&self, kind: JobKind) -> Arc<Semaphore> {
+        let semaphore = match kind {
+            JobKind::Extraction => &self.extraction,
+            JobKind::Transcription => &self.transcription,
+            JobKind::Download => &self.download,
+            JobKind::Queued => &self.queued,
+        };
+        Arc::clone(semaphore)
+    }
+}
+
+static SLOTS: RwLock<Option<Slots>> = RwLock::new(None);
+
+/// Apply changed limits. Jobs already running keep their slot; new jobs are
+/// counted against the new limits.
+pub fn configure(settings: &AppSettings) {
+    let slots = Slots::new(&settings.concurrency_limits);
+    *SLOTS.write().unwrap_or_else(|e| e.into_inner()) = Some(slots);
+}
+
+/// Configure the limits from the saved settings
+pub fn init() {
+    configure(&AppSettings::load().unwrap_or_default());
+}
+
+fn semaphore(kind: JobKind) -> Arc<Semaphore> {
+    if let Some(slots) = SLOTS.read().unwrap_or_else(|e| e.into_inner()).as_ref() {
+        return slots.get(kind);
+    }
+    let mut slots = SLOTS.write().unwrap_or_else(|e| e.into_inner());
+    slots
+        .get_or_insert_with(|| Slots::new(&ConcurrencyLimits::default()))
+        .get(kind)
+}
+
+/// Wait for a free slot for a job of `kind`; the slot is released when the
+/// permit is dropped. Fails with `Cancelled` if the token fires while waiting.
+pub async fn acquire(kind: JobKind, cancel: &CancellationToken) -> Result<OwnedSemaphorePermit> {
+    let semaphore = semaphore(kind);
+    if semaphore.available_permits() == 0 {
+        log::debug!("[concurrency] Waiting for a free {:?} slot", kind);
+    }
+    tokio::select! {
+        biased;
+        _ = cancel.cancelled() => Err(AppError::Cancelled),
+        permit = semaphore.acquire_owned() => {
+            permit.map_err(|e| AppError::ProcessFailed(format!("Job slots closed: {}", e)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_slots_limit_running_jobs() {
+        let slots = Slots::new(&ConcurrencyLimits {
+            extractions: 1,
+            transcriptions: 0,
+            downloads: 2,
+            queued_jobs: 3,
+        });
+
+        let extraction = slots.get(JobKind::Extraction);
+        let held = Arc::clone(&extraction).acquire_owned().await.unwrap();
+        assert!(Arc::clone(&extraction).try_acquire_owned().is_err());
+        drop(held);
+        assert!(extraction.try_acquire_owned().is_ok());
+
+        // A limit of zero still lets one job through
+        assert_eq!(slots.get(JobKind::Transcription).available_permits(), 1);
+        assert_eq!(slots.get(JobKind::Download).available_permits(), 2);
+        assert_eq!(slots.get(JobKind::Queued).available_permits(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_job_gets_no_slot() {
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+        let result = acquire(JobKind::Download, &cancel).await;
+        assert!(matches!(result, Err(AppError::Cancelled)));
+    }
+}
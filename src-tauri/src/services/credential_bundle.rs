@@ -0,0 +1,180 @@
+use crate::error::{AppError, Result};
+use crate::services::credential_profiles::CredentialProfiles;
+use crate::services::keychain::KeychainService;
+use crate::services::providers::{self, PROVIDERS};
+use crate::services::secret_file::{open, restrict_permissions, seal};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+const BUNDLE_VERSION: u32 = 1;
+
+/// Stored keys of one provider
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ProviderCredentials {
+    /// Active credential profile
+    pub active: String,
+    /// API key per profile name
+    pub keys: HashMap<String, String>,
+}
+
+/// Everything needed to move credentials and settings to another machine.
+/// Written to disk only in passphrase-encrypted form.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CredentialBundle {
+    pub version: u32,
+    /// Credentials per provider id
+    pub providers: HashMap<String, ProviderCredentials>,
+    /// Frontend settings, passed through untouched
+    #[serde(default)]
+    pub settings: Option<serde_json::Value>,
+}
+
+impl CredentialBundle {
+    /// Collect every stored key (all profiles of all known providers)
+    pub fn collect(settings: Option<serde_json::Value>) -> Result<Self> {
+        let profiles = CredentialProfiles::load()?;
+        let mut bundle = Self {
+            version: BUNDLE_VERSION,
+            providers: HashMap::new(),
+            settings,
+        };
+
+        for provider in PROVIDERS {
+            let mut keys = HashMap::new();
+            for profile in profiles.profiles(provider.id) {
+                if let Some(key) = KeychainService::get_profile_secret(provider.id, &profile)? {
+                    keys.insert(profile, key);
+                }
+            }
+
+            if !keys.is_empty() {
+                bundle.providers.insert(
+                    provider.id.to_string(),
+                    ProviderCredentials {
+                        active: profiles.active_profile(provider.id),
+                        keys,
+                    },
+                );
+            }
+        }
+
+        Ok(bundle)
+    }
+
+    /// Store the bundled keys in secure storage and restore the active profiles.
+    /// Returns the number of keys imported; unknown providers are skipped.
+    pub fn apply(&self) -> Result<usize> {
+        let mut imported = 0;
+
+        for (provider_id, credentials) in &self.providers {
+            let Some(provider) = providers::find(provider_id) else {
+                log::warn!("[CredentialBundle::apply] Skipping unknown provider: {}", provider_id);
+                continue;
+            };
+
+            for (profile, key) in &credentials.keys {
+                KeychainService::store_profile_secret(provider.id, profile, key)?;
+                imported += 1;
+            }
+
+            let mut profiles = CredentialProfiles::load()?;
+            if profiles.set_active(provider.id, &credentials.active).is_ok() {
+                profiles.save()?;
+            }
+        }
+
+        Ok(imported)
+    }
+
+    /// Encrypt the bundle with a passphrase and write it to a file
+    pub fn write_to(&self, path: &Path, passphrase: &str) -> Result<()> {
+        check_passphrase(passphrase)?;
+        let sealed = seal(passphrase, &serde_json::to_vec(self)?)?;
+        std::fs::write(path, sealed)?;
+        restrict_permissions(path)
+    }
+
+    /// Read and decrypt a bundle file
+    pub fn read_from(path: &Path, passphrase: &str) -> Result<Self> {
+        check_passphrase(passphrase)?;
+        let content = std::fs::read(path)?;
+        let bundle: Self = serde_json::from_slice(&open(passphrase, &content)?)?;
+
+        if bundle.version != BUNDLE_VERSION {
+            return Err(AppError::Keychain(format!(
+                "Unsupported credential bundle version: {}",
+                bundle.version
+            )));
+        }
+        Ok(bundle)
+    }
+}
+
+fn check_passphrase(passphrase: &str) -> Result<()> {
+    if passphrase.is_empty() {
+        return Err(AppError::Keychain("Passphrase must not be empty".to_string()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn sample_bundle() -> CredentialBundle {
+        let mut keys = HashMap::new();
+        keys.insert("default".to_string(), "sk-test-123".to_string());
+        keys.insert("work".to_string(), "sk-work-456".to_string());
+
+        let mut providers = HashMap::new();
+        providers.insert(
+            "openai".to_string(),
+            ProviderCredentials {
+                active: "work".to_string(),
+                keys,
+            },
+        );
+
+        CredentialBundle {
+            version: BUNDLE_VERSION,
+            providers,
+            settings: Some(serde_json::json!({ "transcriptionProvider": "openai" })),
+        }
+    }
+
+    #[test]
+    fn test_bundle_roundtrip() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("clip-flow.bundle");
+
+        sample_bundle().write_to(&path, "correct horse").unwrap();
+        let loaded = CredentialBundle::read_from(&path, "correct horse").unwrap();
+
+        assert_eq!(loaded, sample_bundle());
+    }
+
+    #[test]
+    fn test_bundle_is_encrypted() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("clip-flow.bundle");
+
+        sample_bundle().write_to(&path, "correct horse").unwrap();
+
+        let raw = std::fs::read_to_string(&path).unwrap();
+        assert!(!raw.contains("sk-test-123"));
+        assert!(!raw.contains("transcriptionProvider"));
+    }
+
+    #[test]
+    fn test_bundle_wrong_passphrase_fails() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("clip-flow.bundle");
+
+        sample_bundle().write_to(&path, "correct horse").unwrap();
+
+        assert!(CredentialBundle::read_from(&path, "battery staple").is_err());
+        assert!(CredentialBundle::read_from(&path, "").is_err());
+    }
+}
@@ -0,0 +1,212 @@
+use crate::error::{AppError, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Profile name used for keys stored before profiles existed
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// Registry of named credential profiles per provider.
+/// Only profile names and the active selection are kept here; the keys
+/// themselves live in the keychain under one account per profile.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CredentialProfiles {
+    /// Active profile per provider id
+    #[serde(default)]
+    active: HashMap<String, String>,
+    /// Named (non-default) profiles per provider id
+    #[serde(default)]
+    profiles: HashMap<String, Vec<String>>,
+}
+
+impl CredentialProfiles {
+    /// Get the default registry file path
+    pub fn default_path() -> Result<PathBuf> {
+        let data_dir = dirs::data_local_dir()
+            .ok_or_else(|| AppError::InvalidPath("Cannot find data directory".to_string()))?;
+        Ok(data_dir.join("clip-flow").join("credential_profiles.json"))
+    }
+
+    /// Load the registry from the default location
+    pub fn load() -> Result<Self> {
+        Self::load_from(&Self::default_path()?)
+    }
+
+    /// Load the registry from a file (empty registry if missing)
+    pub fn load_from(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    /// Save the registry to the default location
+    pub fn save(&self) -> Result<()> {
+        self.save_to(&Self::default_path()?)
+    }
+
+    /// Save the registry to a file
+    pub fn save_to(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Get the active profile for a provider
+    pub fn active_profile(&self, provider: &str) -> String {
+        self.active
+            .get(provider)
+            .cloned()
+            .unwrap_or_else(|| DEFAULT_PROFILE.to_string())
+    }
+
+    /// List all profiles for a provider, default first
+    pub fn profiles(&self, provider: &str) -> Vec<String> {
+        let mut names = vec![DEFAULT_PROFILE.to_string()];
+        if let Some(named) = self.profiles.get(provider) {
+            names.extend(named.iter().cloned());
+        }
+        names
+    }
+
+    /// Check if a profile exists for a provider
+    pub fn has_profile(&self, provider: &str, profile: &str) -> bool {
+        profile == DEFAULT_PROFILE
+            || self
+                .profiles
+                .get(provider)
+                .is_some_and(|named| named.iter().any(|p| p == profile))
+    }
+
+    /// Register a profile name for a provider
+    pub fn add_profile(&mut self, provider: &str, profile: &str) -> Result<()> {
+        validate_profile_name(profile)?;
+        if !self.has_profile(provider, profile) {
+            self.profiles
+                .entry(provider.to_string())
+                .or_default()
+                .push(profile.to_string());
+        }
+        Ok(())
+    }
+
+    /// Remove a profile; the provider falls back to the default profile if it was active
+    pub fn remove_profile(&mut self, provider: &str, profile: &str) {
+        if let Some(named) = self.profiles.get_mut(provider) {
+            named.retain(|p| p != profile);
+        }
+        if self.active.get(provider).is_some_and(|p| p == profile) {
+            self.active.remove(provider);
+        }
+    }
+
+    /// Select the active profile for a provider
+    pub fn set_active(&mut self, provider: &str, profile: &str) -> Result<()> {
+        if !self.has_profile(provider, profile) {
+            return Err(AppError::Keychain(format!(
+                "Unknown credential profile '{}' for {}",
+                profile, provider
+            )));
+        }
+        if profile == DEFAULT_PROFILE {
+            self.active.remove(provider);
+        } else {
+            self.active.insert(provider.to_string(), profile.to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Check that a profile name is safe to embed in a keychain account name
+pub fn validate_profile_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && name.len() <= 64
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_' || c == ' ');
+
+    if valid {
+        Ok(())
+    } else {
+        Err(AppError::Keychain(format!(
+            "Invalid profile name '{}': use letters, digits, spaces, '-' or '_'",
+            name
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_default_profile_is_active_initially() {
+        let profiles = CredentialProfiles::default();
+        assert_eq!(profiles.active_profile("openai"), DEFAULT_PROFILE);
+        assert_eq!(profiles.profiles("openai"), vec![DEFAULT_PROFILE.to_string()]);
+    }
+
+    #[test]
+    fn test_add_and_activate_profile() {
+        let mut profiles = CredentialProfiles::default();
+        profiles.add_profile("openai", "work").unwrap();
+        profiles.set_active("openai", "work").unwrap();
+
+        assert_eq!(profiles.active_profile("openai"), "work");
+        assert_eq!(profiles.active_profile("claude"), DEFAULT_PROFILE);
+        assert_eq!(profiles.profiles("openai"), vec!["default", "work"]);
+    }
+
+    #[test]
+    fn test_add_profile_is_idempotent() {
+        let mut profiles = CredentialProfiles::default();
+        profiles.add_profile("openai", "work").unwrap();
+        profiles.add_profile("openai", "work").unwrap();
+        assert_eq!(profiles.profiles("openai").len(), 2);
+    }
+
+    #[test]
+    fn test_set_active_unknown_profile_fails() {
+        let mut profiles = CredentialProfiles::default();
+        assert!(profiles.set_active("openai", "client-a").is_err());
+    }
+
+    #[test]
+    fn test_remove_active_profile_resets_to_default() {
+        let mut profiles = CredentialProfiles::default();
+        profiles.add_profile("claude", "personal").unwrap();
+        profiles.set_active("claude", "personal").unwrap();
+
+        profiles.remove_profile("claude", "personal");
+
+        assert_eq!(profiles.active_profile("claude"), DEFAULT_PROFILE);
+        assert!(!profiles.has_profile("claude", "personal"));
+    }
+
+    #[test]
+    fn test_invalid_profile_names() {
+        assert!(validate_profile_name("").is_err());
+        assert!(validate_profile_name("a:b").is_err());
+        assert!(validate_profile_name("../etc").is_err());
+        assert!(validate_profile_name("Client A_2024-q1").is_ok());
+    }
+
+    #[test]
+    fn test_save_and_load_roundtrip() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("profiles.json");
+
+        let mut profiles = CredentialProfiles::default();
+        profiles.add_profile("openai", "work").unwrap();
+        profiles.set_active("openai", "work").unwrap();
+        profiles.save_to(&path).unwrap();
+
+        let loaded = CredentialProfiles::load_from(&path).unwrap();
+        assert_eq!(loaded.active_profile("openai"), "work");
+        assert_eq!(loaded.profiles("openai"), vec!["default", "work"]);
+    }
+}
@@ -0,0 +1,85 @@
+use super::{now, Database};
+use crate::error::Result;
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+
+/// How fast a whisper model transcribed the benchmark sample on this machine
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Benchmark {
+    pub id: i64,
+    pub model: String,
+    pub audio_seconds: f64,
+    pub elapsed_seconds: f64,
+    /// Seconds of processing per second of audio; below 1 is faster than
+    /// realtime
+    pub realtime_factor: f64,
+    /// Unix seconds
+    pub created_at: u64,
+}
+
+impl Database {
+    pub fn record_benchmark(
+        &self,
+        model: &str,
+        audio_seconds: f64,
+        elapsed_seconds: f64,
+    ) -> Result<Benchmark> {
+        let created_at = now();
+        self.conn.execute(
+            "INSERT INTO benchmarks (model, audio_seconds, elapsed_seconds, created_at)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![model, audio_seconds, elapsed_seconds, created_at],
+        )?;
+        Ok(Benchmark {
+            id: self.conn.last_insert_rowid(),
+            model: model.to_string(),
+            audio_seconds,
+            elapsed_seconds,
+            realtime_factor: elapsed_seconds / audio_seconds,
+            created_at,
+        })
+    }
+
+    /// The latest benchmark of each model
+    pub fn latest_benchmarks(&self) -> Result<Vec<Benchmark>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, model, audio_seconds, elapsed_seconds, created_at FROM benchmarks
+             WHERE id IN (SELECT MAX(id) FROM benchmarks GROUP BY model)
+             ORDER BY model",
+        )?;
+        let benchmarks = stmt
+            .query_map([], |row| {
+                let audio_seconds: f64 = row.get(2)?;
+                let elapsed_seconds: f64 = row.get(3)?;
+                Ok(Benchmark {
+                    id: row.get(0)?,
+                    model: row.get(1)?,
+                    audio_seconds,
+                    elapsed_seconds,
+                    realtime_factor: elapsed_seconds / audio_seconds,
+                    created_at: row.get(4)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(benchmarks)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_latest_benchmarks() {
+        let db = Database::open_in_memory().unwrap();
+        assert!(db.latest_benchmarks().unwrap().is_empty());
+
+        db.record_benchmark("base", 30.0, 6.0).unwrap();
+        let rerun = db.record_benchmark("base", 30.0, 3.0).unwrap();
+        let tiny = db.record_benchmark("tiny", 30.0, 1.5).unwrap();
+        assert_eq!(rerun.realtime_factor, 0.1);
+
+        // Only the newest run of each model counts
+        assert_eq!(db.latest_benchmarks().unwrap(), vec![rerun, tiny]);
+    }
+}
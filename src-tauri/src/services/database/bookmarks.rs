@@ -0,0 +1,218 @@
+use super::{now, Database};
+use crate::error::{AppError, Result};
+use crate::services::export::Moment;
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+
+/// A moment of a media file marked while reviewing it: a point in time, or a
+/// range to highlight
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Bookmark {
+    pub id: i64,
+    pub media_path: String,
+    /// Seconds from the start of the media
+    pub start: f64,
+    /// End of a highlighted range; point bookmarks have none
+    pub end: Option<f64>,
+    pub note: Option<String>,
+    /// CSS color of the marker on the timeline
+    pub color: Option<String>,
+    /// Unix seconds
+    pub created_at: u64,
+}
+
+/// Fields of a bookmark to create or update
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BookmarkInput {
+    pub start: f64,
+    #[serde(default)]
+    pub end: Option<f64>,
+    #[serde(default)]
+    pub note: Option<String>,
+    #[serde(default)]
+    pub color: Option<String>,
+}
+
+impl BookmarkInput {
+    fn check(&self) -> Result<()> {
+        if !self.start.is_finite() || self.start < 0.0 {
+            return Err(AppError::InvalidInput(format!(
+                "Invalid bookmark time: {}",
+                self.start
+            )));
+        }
+        if self
+            .end
+            .is_some_and(|end| !end.is_finite() || end <= self.start)
+        {
+            return Err(AppError::InvalidInput(
+                "A bookmark range must end after it starts".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    fn note(&self) -> Option<&str> {
+        self.note
+            .as_deref()
+            .map(str::trim)
+            .filter(|note| !note.is_empty())
+    }
+}
+
+impl From<&Bookmark> for Moment {
+    fn from(bookmark: &Bookmark) -> Self {
+        Moment {
+            start: bookmark.start,
+            end: bookmark.end,
+            label: bookmark
+                .note
+                .clone()
+                .unwrap_or_else(|| "Bookmark".to_string()),
+        }
+    }
+}
+
+impl Database {
+    /// Bookmark a moment of a media file
+    pub fn add_bookmark(&self, media_path: &str, input: &BookmarkInput) -> Result<Bookmark> {
+        input.check()?;
+        let media_id = self.media_file_id(media_path)?;
+        self.conn.execute(
+            "INSERT INTO bookmarks (media_id, start_seconds, end_seconds, note, color, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                media_id,
+                input.start,
+                input.end,
+                input.note(),
+                input.color,
+                now()
+            ],
+        )?;
+        self.bookmark(self.conn.last_insert_rowid())
+    }
+
+    /// Move a bookmark or change its note or color
+    pub fn update_bookmark(&self, id: i64, input: &BookmarkInput) -> Result<Bookmark> {
+        input.check()?;
+        let updated = self.conn.execute(
+            "UPDATE bookmarks SET start_seconds = ?1, end_seconds = ?2, note = ?3, color = ?4
+             WHERE id = ?5",
+            params![input.start, input.end, input.note(), input.color, id],
+        )?;
+        if updated == 0 {
+            return Err(AppError::InvalidInput(format!(
+                "Bookmark not found: {}",
+                id
+            )));
+        }
+        self.bookmark(id)
+    }
+
+    pub fn delete_bookmark(&self, id: i64) -> Result<()> {
+        self.conn
+            .execute("DELETE FROM bookmarks WHERE id = ?1", [id])?;
+        Ok(())
+    }
+
+    fn bookmark(&self, id: i64) -> Result<Bookmark> {
+        self.query_bookmarks("b.id = ?1", [id])?
+            .pop()
+            .ok_or_else(|| AppError::InvalidInput(format!("Bookmark not found: {}", id)))
+    }
+
+    /// Bookmarks of a media file in timeline order
+    pub fn bookmarks(&self, media_path: &str) -> Result<Vec<Bookmark>> {
+        self.query_bookmarks("m.path = ?1", [media_path])
+    }
+
+    fn query_bookmarks(
+        &self,
+        condition: &str,
+        params: impl rusqlite::Params,
+    ) -> Result<Vec<Bookmark>> {
+        let sql = format!(
+            "SELECT b.id, m.path, b.start_seconds, b.end_seconds, b.note, b.color, b.created_at
+             FROM bookmarks b JOIN media_files m ON m.id = b.media_id
+             WHERE {} ORDER BY b.start_seconds, b.id",
+            condition
+        );
+        let mut stmt = self.conn.prepare(&sql)?;
+        let bookmarks = stmt
+            .query_map(params, |row| {
+                Ok(Bookmark {
+                    id: row.get(0)?,
+                    media_path: row.get(1)?,
+                    start: row.get(2)?,
+                    end: row.get(3)?,
+                    note: row.get(4)?,
+                    color: row.get(5)?,
+                    created_at: row.get(6)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(bookmarks)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input(start: f64, end: Option<f64>, note: Option<&str>) -> BookmarkInput {
+        BookmarkInput {
+            start,
+            end,
+            note: note.map(str::to_string),
+            color: None,
+        }
+    }
+
+    #[test]
+    fn test_bookmark_crud() {
+        let db = Database::open_in_memory().unwrap();
+        let later = db
+            .add_bookmark("/m/a.mp4", &input(90.0, Some(95.5), Some(" Best quote ")))
+            .unwrap();
+        assert_eq!(later.note.as_deref(), Some("Best quote"));
+        let earlier = db
+            .add_bookmark("/m/a.mp4", &input(12.0, None, Some(" ")))
+            .unwrap();
+        assert_eq!(earlier.note, None);
+        db.add_bookmark("/m/b.mp4", &input(1.0, None, None))
+            .unwrap();
+
+        let ids: Vec<_> = db
+            .bookmarks("/m/a.mp4")
+            .unwrap()
+            .iter()
+            .map(|b| b.id)
+            .collect();
+        assert_eq!(ids, [earlier.id, later.id]);
+
+        let mut moved = input(100.0, None, Some("Moved"));
+        moved.color = Some("#fc0".to_string());
+        let updated = db.update_bookmark(later.id, &moved).unwrap();
+        assert_eq!((updated.start, updated.end), (100.0, None));
+        assert_eq!(updated.color.as_deref(), Some("#fc0"));
+        assert!(db.update_bookmark(999, &moved).is_err());
+
+        db.delete_bookmark(earlier.id).unwrap();
+        assert_eq!(db.bookmarks("/m/a.mp4").unwrap(), [updated]);
+    }
+
+    #[test]
+    fn test_invalid_bookmarks() {
+        let db = Database::open_in_memory().unwrap();
+        assert!(db
+            .add_bookmark("/m/a.mp4", &input(-1.0, None, None))
+            .is_err());
+        assert!(db
+            .add_bookmark("/m/a.mp4", &input(5.0, Some(5.0), None))
+            .is_err());
+        assert!(db
+            .add_bookmark("/m/a.mp4", &input(f64::NAN, None, None))
+            .is_err());
+    }
+}
@@ -0,0 +1,209 @@
+use super::{now, Database};
+use crate::error::Result;
+use crate::services::embeddings::{decode_vector, encode_vector};
+use rusqlite::{params, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+/// A transcript segment indexed in the embedding store
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IndexedSegment {
+    /// Index into the transcript segments
+    pub segment: usize,
+    pub start: f64,
+    pub end: f64,
+    pub content_hash: String,
+}
+
+/// Vectors stored for one embedding model
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EmbeddingModelStats {
+    /// Provider and model, e.g. `ollama/nomic-embed-text`
+    pub model: String,
+    pub vectors: usize,
+    pub dimensions: usize,
+    pub bytes: u64,
+    pub indexed_files: usize,
+}
+
+/// Size of the embedding store
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EmbeddingStoreStats {
+    pub vectors: usize,
+    pub bytes: u64,
+    pub models: Vec<EmbeddingModelStats>,
+}
+
+impl Database {
+    /// Whether a vector is stored for a text hash and model
+    pub fn has_embedding(&self, content_hash: &str, model: &str) -> Result<bool> {
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT 1 FROM embeddings WHERE content_hash = ?1 AND model = ?2",
+                params![content_hash, model],
+                |_| Ok(()),
+            )
+            .optional()?
+            .is_some())
+    }
+
+    /// Get the vector stored for a text hash and model
+    pub fn embedding(&self, content_hash: &str, model: &str) -> Result<Option<Vec<f32>>> {
+        let bytes: Option<Vec<u8>> = self
+            .conn
+            .query_row(
+                "SELECT vector FROM embeddings WHERE content_hash = ?1 AND model = ?2",
+                params![content_hash, model],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(bytes.map(|b| decode_vector(&b)))
+    }
+
+    /// Store the vector of a text hash for a model
+    pub fn save_embedding(&self, content_hash: &str, model: &str, vector: &[f32]) -> Result<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO embeddings (content_hash, model, dimensions, vector, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![content_hash, model, vector.len(), encode_vector(vector), now()],
+        )?;
+        Ok(())
+    }
+
+    /// Replace the segments of a media file indexed with a model
+    pub fn set_indexed_segments(
+        &self,
+        media_path: &str,
+        model: &str,
+        segments: &[IndexedSegment],
+    ) -> Result<()> {
+        let media_id = self.media_file_id(media_path)?;
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute(
+            "DELETE FROM embedding_segments WHERE media_id = ?1 AND model = ?2",
+            params![media_id, model],
+        )?;
+        for s in segments {
+            tx.execute(
+                "INSERT INTO embedding_segments
+                    (media_id, model, segment, start, end, content_hash)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![media_id, model, s.segment, s.start, s.end, s.content_hash],
+            )?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Segments of a media file indexed with a model, in transcript order
+    pub fn indexed_segments(&self, media_path: &str, model: &str) -> Result<Vec<IndexedSegment>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT es.segment, es.start, es.end, es.content_hash
+             FROM embedding_segments es JOIN media_files m ON m.id = es.media_id
+             WHERE m.path = ?1 AND es.model = ?2
+             ORDER BY es.segment",
+        )?;
+        let segments = stmt
+            .query_map(params![media_path, model], |row| {
+                Ok(IndexedSegment {
+                    segment: row.get(0)?,
+                    start: row.get(1)?,
+                    end: row.get(2)?,
+                    content_hash: row.get(3)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(segments)
+    }
+
+    /// Media files with a saved transcription
+    pub fn transcribed_media_paths(&self) -> Result<Vec<String>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT m.path FROM media_files m
+             WHERE EXISTS (SELECT 1 FROM transcriptions t WHERE t.media_id = m.id)
+             ORDER BY m.path",
+        )?;
+        let paths = stmt
+            .query_map([], |row| row.get(0))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(paths)
+    }
+
+    /// Number and size of the stored vectors, per model
+    pub fn embedding_store_stats(&self) -> Result<EmbeddingStoreStats> {
+        let mut stmt = self.conn.prepare(
+            "SELECT e.model, COUNT(*), MAX(e.dimensions), SUM(LENGTH(e.vector)),
+                    (SELECT COUNT(DISTINCT es.media_id) FROM embedding_segments es
+                     WHERE es.model = e.model)
+             FROM embeddings e GROUP BY e.model ORDER BY e.model",
+        )?;
+        let models = stmt
+            .query_map([], |row| {
+                Ok(EmbeddingModelStats {
+                    model: row.get(0)?,
+                    vectors: row.get(1)?,
+                    dimensions: row.get(2)?,
+                    bytes: row.get(3)?,
+                    indexed_files: row.get(4)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(EmbeddingStoreStats {
+            vectors: models.iter().map(|m| m.vectors).sum(),
+            bytes: models.iter().map(|m| m.bytes).sum(),
+            models,
+        })
+    }
+
+    /// Delete the stored vectors of one model, or of all models. Returns how many were deleted.
+    pub fn clear_embeddings(&self, model: Option<&str>) -> Result<usize> {
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute("DELETE FROM embedding_segments WHERE ?1 IS NULL OR model = ?1", [model])?;
+        let deleted = tx.execute("DELETE FROM embeddings WHERE ?1 IS NULL OR model = ?1", [model])?;
+        tx.commit()?;
+        Ok(deleted)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn indexed(segment: usize, hash: &str) -> IndexedSegment {
+        IndexedSegment {
+            segment,
+            start: segment as f64,
+            end: segment as f64 + 1.0,
+            content_hash: hash.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_embedding_store() {
+        let db = Database::open_in_memory().unwrap();
+        let model = "ollama/nomic-embed-text";
+
+        assert!(!db.has_embedding("h1", model).unwrap());
+        db.save_embedding("h1", model, &[0.25, 0.5]).unwrap();
+        db.save_embedding("h2", model, &[1.0, 0.0]).unwrap();
+        db.save_embedding("h1", "openai/text-embedding-3-small", &[0.1; 4])
+            .unwrap();
+        assert!(db.has_embedding("h1", model).unwrap());
+        assert_eq!(db.embedding("h1", model).unwrap(), Some(vec![0.25, 0.5]));
+
+        db.set_indexed_segments("/m/a.mp4", model, &[indexed(0, "h1"), indexed(1, "h2")])
+            .unwrap();
+        db.set_indexed_segments("/m/a.mp4", model, &[indexed(1, "h2")]).unwrap();
+        assert_eq!(db.indexed_segments("/m/a.mp4", model).unwrap(), [indexed(1, "h2")]);
+
+        let stats = db.embedding_store_stats().unwrap();
+        assert_eq!((stats.vectors, stats.bytes), (3, 32));
+        let ollama = stats.models.iter().find(|m| m.model == model).unwrap();
+        assert_eq!((ollama.vectors, ollama.dimensions, ollama.indexed_files), (2, 2, 1));
+
+        assert_eq!(db.clear_embeddings(Some(model)).unwrap(), 2);
+        assert!(db.indexed_segments("/m/a.mp4", model).unwrap().is_empty());
+        assert_eq!(db.clear_embeddings(None).unwrap(), 1);
+        assert_eq!(db.embedding_store_stats().unwrap(), EmbeddingStoreStats::default());
+    }
+}
@@ -0,0 +1,148 @@
+use super::{now, Database};
+use crate::error::Result;
+use crate::services::entities::EntityMention;
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+
+/// A person, organization, place or product mentioned in the project's transcripts
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Entity {
+    pub id: i64,
+    pub name: String,
+    pub kind: String,
+    pub mention_count: usize,
+    pub file_count: usize,
+}
+
+/// Where an entity is mentioned
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StoredMention {
+    pub media_path: String,
+    pub start: f64,
+    pub end: f64,
+}
+
+impl Database {
+    /// Replace the entity mentions recorded for a media file
+    pub fn save_entity_mentions(&self, media_path: &str, mentions: &[EntityMention]) -> Result<()> {
+        let media_id = self.media_file_id(media_path)?;
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute("DELETE FROM entity_mentions WHERE media_id = ?1", [media_id])?;
+        for mention in mentions {
+            tx.execute(
+                "INSERT INTO entities (name, kind, created_at) VALUES (?1, ?2, ?3)
+                 ON CONFLICT(name, kind) DO NOTHING",
+                params![mention.name, mention.kind, now()],
+            )?;
+            tx.execute(
+                "INSERT INTO entity_mentions (entity_id, media_id, start, end)
+                 SELECT id, ?3, ?4, ?5 FROM entities WHERE name = ?1 AND kind = ?2",
+                params![mention.name, mention.kind, media_id, mention.start, mention.end],
+            )?;
+        }
+        // Entities no transcript mentions any more
+        tx.execute(
+            "DELETE FROM entities
+             WHERE id NOT IN (SELECT DISTINCT entity_id FROM entity_mentions)",
+            [],
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// List entities by number of mentions, optionally of one kind or one media file
+    pub fn list_entities(
+        &self,
+        kind: Option<&str>,
+        media_path: Option<&str>,
+    ) -> Result<Vec<Entity>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT e.id, e.name, e.kind, COUNT(*), COUNT(DISTINCT em.media_id)
+             FROM entities e
+             JOIN entity_mentions em ON em.entity_id = e.id
+             JOIN media_files m ON m.id = em.media_id
+             WHERE (?1 IS NULL OR e.kind = ?1) AND (?2 IS NULL OR m.path = ?2)
+             GROUP BY e.id
+             ORDER BY COUNT(*) DESC, e.name COLLATE NOCASE",
+        )?;
+        let entities = stmt
+            .query_map(params![kind, media_path], |row| {
+                Ok(Entity {
+                    id: row.get(0)?,
+                    name: row.get(1)?,
+                    kind: row.get(2)?,
+                    mention_count: row.get(3)?,
+                    file_count: row.get(4)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(entities)
+    }
+
+    /// Mentions of an entity across all media files, by file and time
+    pub fn entity_mentions(&self, entity_id: i64) -> Result<Vec<StoredMention>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT m.path, em.start, em.end
+             FROM entity_mentions em JOIN media_files m ON m.id = em.media_id
+             WHERE em.entity_id = ?1
+             ORDER BY m.path, em.start",
+        )?;
+        let mentions = stmt
+            .query_map([entity_id], |row| {
+                Ok(StoredMention {
+                    media_path: row.get(0)?,
+                    start: row.get(1)?,
+                    end: row.get(2)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(mentions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mention(name: &str, kind: &str, start: f64) -> EntityMention {
+        EntityMention {
+            name: name.to_string(),
+            kind: kind.to_string(),
+            start,
+            end: start + 5.0,
+        }
+    }
+
+    #[test]
+    fn test_entity_mentions() {
+        let db = Database::open_in_memory().unwrap();
+        db.save_entity_mentions(
+            "/m/a.mp4",
+            &[
+                mention("Ada Lovelace", "person", 0.0),
+                mention("ada lovelace", "person", 30.0),
+                mention("London", "location", 10.0),
+            ],
+        )
+        .unwrap();
+        db.save_entity_mentions("/m/b.mp4", &[mention("Ada Lovelace", "person", 4.0)])
+            .unwrap();
+
+        let entities = db.list_entities(None, None).unwrap();
+        assert_eq!(entities.len(), 2);
+        // Names match case-insensitively
+        assert_eq!((entities[0].mention_count, entities[0].file_count), (3, 2));
+        assert_eq!(db.list_entities(Some("location"), None).unwrap().len(), 1);
+        assert_eq!(db.list_entities(None, Some("/m/b.mp4")).unwrap().len(), 1);
+
+        let mentions = db.entity_mentions(entities[0].id).unwrap();
+        assert_eq!(mentions[0].media_path, "/m/a.mp4");
+        assert_eq!(mentions[2].start, 4.0);
+
+        // Re-extracting a file replaces its mentions and drops orphaned entities
+        db.save_entity_mentions("/m/a.mp4", &[]).unwrap();
+        let entities = db.list_entities(None, None).unwrap();
+        assert_eq!(entities.len(), 1);
+        assert_eq!(entities[0].mention_count, 1);
+    }
+}
@@ -0,0 +1,275 @@
+use super::{now, Database};
+use crate::error::{AppError, Result};
+use crate::services::podcast::{FeedItem, ParsedFeed};
+use rusqlite::{params, Row};
+use serde::{Deserialize, Serialize};
+
+/// A subscribed podcast feed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Feed {
+    pub id: i64,
+    pub url: String,
+    pub title: String,
+    pub description: Option<String>,
+    /// Where episodes are saved (the app's podcast folder when `None`)
+    pub download_dir: Option<String>,
+    /// Whisper model used to transcribe new episodes automatically; `None` turns it off
+    pub auto_transcribe_model: Option<String>,
+    pub episode_count: usize,
+    /// Unix seconds
+    pub last_checked_at: Option<u64>,
+    pub created_at: u64,
+}
+
+/// An episode of a subscribed feed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FeedEpisode {
+    pub id: i64,
+    pub feed_id: i64,
+    pub guid: String,
+    pub title: String,
+    pub description: Option<String>,
+    pub audio_url: String,
+    /// Size announced by the feed, in bytes (0 if unknown)
+    pub size: u64,
+    /// Unix seconds
+    pub published_at: Option<u64>,
+    /// Downloaded audio file, if the episode has been downloaded
+    pub local_path: Option<String>,
+    pub created_at: u64,
+}
+
+const EPISODE_COLUMNS: &str = "id, feed_id, guid, title, description, audio_url, size, \
+                               published_at, local_path, created_at";
+
+fn episode_from_row(row: &Row<'_>) -> rusqlite::Result<FeedEpisode> {
+    Ok(FeedEpisode {
+        id: row.get(0)?,
+        feed_id: row.get(1)?,
+        guid: row.get(2)?,
+        title: row.get(3)?,
+        description: row.get(4)?,
+        audio_url: row.get(5)?,
+        size: row.get(6)?,
+        published_at: row.get(7)?,
+        local_path: row.get(8)?,
+        created_at: row.get(9)?,
+    })
+}
+
+impl Database {
+    /// Subscribe to a feed (or update the title of an existing subscription).
+    /// The episodes already in the feed are recorded without being downloaded.
+    pub fn add_feed(
+        &self,
+        url: &str,
+        parsed: &ParsedFeed,
+        download_dir: Option<&str>,
+        auto_transcribe_model: Option<&str>,
+    ) -> Result<Feed> {
+        self.conn.execute(
+            "INSERT INTO feeds (url, title, description, download_dir, auto_transcribe_model,
+                                created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+             ON CONFLICT(url) DO UPDATE SET title = excluded.title,
+                                            description = excluded.description",
+            params![
+                url,
+                parsed.title,
+                parsed.description,
+                download_dir,
+                auto_transcribe_model,
+                now()
+            ],
+        )?;
+        let id = self.conn.query_row("SELECT id FROM feeds WHERE url = ?1", [url], |row| {
+            row.get(0)
+        })?;
+        self.add_feed_episodes(id, &parsed.episodes)?;
+        self.feed(id)
+    }
+
+    /// Change where a feed's episodes are saved and whether they are transcribed
+    pub fn update_feed(
+        &self,
+        id: i64,
+        download_dir: Option<&str>,
+        auto_transcribe_model: Option<&str>,
+    ) -> Result<Feed> {
+        self.conn.execute(
+            "UPDATE feeds SET download_dir = ?1, auto_transcribe_model = ?2 WHERE id = ?3",
+            params![download_dir, auto_transcribe_model, id],
+        )?;
+        self.feed(id)
+    }
+
+    /// Unsubscribe from a feed and forget its episodes (downloaded files are kept)
+    pub fn delete_feed(&self, id: i64) -> Result<()> {
+        self.conn.execute("DELETE FROM feeds WHERE id = ?1", [id])?;
+        Ok(())
+    }
+
+    /// Get a subscribed feed
+    pub fn feed(&self, id: i64) -> Result<Feed> {
+        self.query_feeds("WHERE f.id = ?1", [id])?
+            .pop()
+            .ok_or_else(|| AppError::InvalidInput(format!("Feed not found: {}", id)))
+    }
+
+    /// List subscribed feeds by title
+    pub fn list_feeds(&self) -> Result<Vec<Feed>> {
+        self.query_feeds("", [])
+    }
+
+    fn query_feeds(&self, condition: &str, params: impl rusqlite::Params) -> Result<Vec<Feed>> {
+        let sql = format!(
+            "SELECT f.id, f.url, f.title, f.description, f.download_dir,
+                    f.auto_transcribe_model, f.last_checked_at, f.created_at,
+                    (SELECT COUNT(*) FROM feed_episodes e WHERE e.feed_id = f.id)
+             FROM feeds f {} ORDER BY f.title COLLATE NOCASE",
+            condition
+        );
+        let mut stmt = self.conn.prepare(&sql)?;
+        let feeds = stmt
+            .query_map(params, |row| {
+                Ok(Feed {
+                    id: row.get(0)?,
+                    url: row.get(1)?,
+                    title: row.get(2)?,
+                    description: row.get(3)?,
+                    download_dir: row.get(4)?,
+                    auto_transcribe_model: row.get(5)?,
+                    last_checked_at: row.get(6)?,
+                    created_at: row.get(7)?,
+                    episode_count: row.get(8)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(feeds)
+    }
+
+    /// Record the episodes of a feed check. Returns the episodes not seen before.
+    pub fn add_feed_episodes(&self, feed_id: i64, items: &[FeedItem]) -> Result<Vec<FeedEpisode>> {
+        let tx = self.conn.unchecked_transaction()?;
+        let mut new_ids = Vec::new();
+        for item in items {
+            let inserted = tx.execute(
+                "INSERT OR IGNORE INTO feed_episodes
+                    (feed_id, guid, title, description, audio_url, size, published_at, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                params![
+                    feed_id,
+                    item.guid,
+                    item.title,
+                    item.description,
+                    item.audio_url,
+                    item.size,
+                    item.published_at,
+                    now()
+                ],
+            )?;
+            if inserted > 0 {
+                new_ids.push(tx.last_insert_rowid());
+            }
+        }
+        tx.execute(
+            "UPDATE feeds SET last_checked_at = ?1 WHERE id = ?2",
+            params![now(), feed_id],
+        )?;
+        tx.commit()?;
+
+        new_ids.into_iter().map(|id| self.feed_episode(id)).collect()
+    }
+
+    /// Episodes of a feed, newest first
+    pub fn feed_episodes(&self, feed_id: i64) -> Result<Vec<FeedEpisode>> {
+        let sql = format!(
+            "SELECT {} FROM feed_episodes WHERE feed_id = ?1
+             ORDER BY published_at DESC, id DESC",
+            EPISODE_COLUMNS
+        );
+        let mut stmt = self.conn.prepare(&sql)?;
+        let episodes = stmt
+            .query_map([feed_id], episode_from_row)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(episodes)
+    }
+
+    /// Get an episode of a subscribed feed
+    pub fn feed_episode(&self, id: i64) -> Result<FeedEpisode> {
+        let sql = format!("SELECT {} FROM feed_episodes WHERE id = ?1", EPISODE_COLUMNS);
+        self.conn
+            .query_row(&sql, [id], episode_from_row)
+            .map_err(|e| match e {
+                rusqlite::Error::QueryReturnedNoRows => {
+                    AppError::InvalidInput(format!("Episode not found: {}", id))
+                }
+                e => e.into(),
+            })
+    }
+
+    /// Remember where an episode was downloaded
+    pub fn set_episode_downloaded(&self, id: i64, local_path: &str) -> Result<FeedEpisode> {
+        self.conn.execute(
+            "UPDATE feed_episodes SET local_path = ?1 WHERE id = ?2",
+            params![local_path, id],
+        )?;
+        self.feed_episode(id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(guid: &str, published_at: u64) -> FeedItem {
+        FeedItem {
+            guid: guid.to_string(),
+            title: format!("Episode {}", guid),
+            description: None,
+            audio_url: format!("https://cdn.example.com/{}.mp3", guid),
+            size: 1024,
+            published_at: Some(published_at),
+        }
+    }
+
+    fn parsed(items: Vec<FeedItem>) -> ParsedFeed {
+        ParsedFeed {
+            title: "Clip Talk".to_string(),
+            description: None,
+            episodes: items,
+        }
+    }
+
+    #[test]
+    fn test_feed_subscription_and_new_episodes() {
+        let db = Database::open_in_memory().unwrap();
+        let url = "https://example.com/feed.xml";
+
+        let feed = db.add_feed(url, &parsed(vec![item("1", 100)]), None, None).unwrap();
+        assert_eq!(feed.episode_count, 1);
+        assert!(feed.last_checked_at.is_some());
+
+        // Only episodes not seen before are reported
+        let new = db.add_feed_episodes(feed.id, &[item("1", 100), item("2", 200)]).unwrap();
+        assert_eq!(new.len(), 1);
+        assert_eq!(new[0].guid, "2");
+
+        let episodes = db.feed_episodes(feed.id).unwrap();
+        assert_eq!(episodes.iter().map(|e| e.guid.as_str()).collect::<Vec<_>>(), ["2", "1"]);
+
+        let downloaded = db.set_episode_downloaded(new[0].id, "/podcasts/2.mp3").unwrap();
+        assert_eq!(downloaded.local_path.as_deref(), Some("/podcasts/2.mp3"));
+
+        // Subscribing again keeps the same feed
+        let again = db.add_feed(url, &parsed(vec![]), None, None).unwrap();
+        assert_eq!((again.id, again.episode_count), (feed.id, 2));
+
+        let updated = db.update_feed(feed.id, Some("/podcasts"), Some("base")).unwrap();
+        assert_eq!(updated.auto_transcribe_model.as_deref(), Some("base"));
+
+        db.delete_feed(feed.id).unwrap();
+        assert!(db.list_feeds().unwrap().is_empty());
+        assert!(db.feed_episode(new[0].id).is_err());
+    }
+}
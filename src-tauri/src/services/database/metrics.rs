@@ -0,0 +1,141 @@
+use super::{now, Database};
+use crate::error::Result;
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// A recorded feature use or timing
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Metric {
+    pub id: i64,
+    /// What was used or timed, e.g. "transcription" or "export"
+    pub name: String,
+    pub duration_ms: Option<u64>,
+    /// Provider, model, format, sizes, ...
+    pub attributes: Value,
+    /// Unix seconds
+    pub recorded_at: u64,
+}
+
+/// Totals of one metric, per model where the metric records one
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MetricSummary {
+    pub name: String,
+    pub model: Option<String>,
+    pub count: u64,
+    pub avg_ms: Option<f64>,
+    pub min_ms: Option<u64>,
+    pub max_ms: Option<u64>,
+    pub last_recorded_at: u64,
+}
+
+impl Database {
+    pub fn record_metric(
+        &self,
+        name: &str,
+        duration_ms: Option<u64>,
+        attributes: &Value,
+    ) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO metrics (name, duration_ms, attributes, recorded_at)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![name, duration_ms, attributes.to_string(), now()],
+        )?;
+        Ok(())
+    }
+
+    /// Recorded metrics, newest first, optionally of one name
+    pub fn list_metrics(&self, name: Option<&str>, limit: Option<usize>) -> Result<Vec<Metric>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, name, duration_ms, attributes, recorded_at FROM metrics
+             WHERE ?1 IS NULL OR name = ?1
+             ORDER BY recorded_at DESC, id DESC
+             LIMIT ?2",
+        )?;
+        let limit = limit.map_or(-1, |limit| limit as i64);
+        let rows = stmt
+            .query_map(params![name, limit], |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, Option<u64>>(2)?,
+                    row.get::<_, String>(3)?,
+                    row.get::<_, u64>(4)?,
+                ))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        rows.into_iter()
+            .map(|(id, name, duration_ms, attributes, recorded_at)| {
+                Ok(Metric {
+                    id,
+                    name,
+                    duration_ms,
+                    attributes: serde_json::from_str(&attributes)?,
+                    recorded_at,
+                })
+            })
+            .collect()
+    }
+
+    /// Count and timings of each metric, per model
+    pub fn metrics_summary(&self) -> Result<Vec<MetricSummary>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT name, json_extract(attributes, '$.model'), COUNT(*),
+                    AVG(duration_ms), MIN(duration_ms), MAX(duration_ms), MAX(recorded_at)
+             FROM metrics
+             GROUP BY 1, 2
+             ORDER BY 1, 2",
+        )?;
+        let summaries = stmt
+            .query_map([], |row| {
+                Ok(MetricSummary {
+                    name: row.get(0)?,
+                    model: row.get(1)?,
+                    count: row.get(2)?,
+                    avg_ms: row.get(3)?,
+                    min_ms: row.get(4)?,
+                    max_ms: row.get(5)?,
+                    last_recorded_at: row.get(6)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(summaries)
+    }
+
+    /// Delete all recorded metrics
+    pub fn clear_metrics(&self) -> Result<usize> {
+        Ok(self.conn.execute("DELETE FROM metrics", [])?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_metrics() {
+        let db = Database::open_in_memory().unwrap();
+        let base = json!({ "provider": "local", "model": "base" });
+        db.record_metric("transcription", Some(1000), &base).unwrap();
+        db.record_metric("transcription", Some(3000), &base).unwrap();
+        db.record_metric("transcription", Some(9000), &json!({ "model": "large-v3" }))
+            .unwrap();
+        db.record_metric("export", None, &json!({ "format": "vtt" })).unwrap();
+
+        let summary = db.metrics_summary().unwrap();
+        assert_eq!(summary.len(), 3);
+        assert_eq!((summary[0].name.as_str(), summary[0].model.as_deref()), ("export", None));
+        assert_eq!(summary[0].avg_ms, None);
+        assert_eq!(summary[1].model.as_deref(), Some("base"));
+        assert_eq!((summary[1].count, summary[1].avg_ms), (2, Some(2000.0)));
+
+        let exports = db.list_metrics(Some("export"), None).unwrap();
+        assert_eq!(exports[0].attributes["format"], "vtt");
+        assert_eq!(db.list_metrics(None, Some(2)).unwrap().len(), 2);
+
+        assert_eq!(db.clear_metrics().unwrap(), 4);
+        assert!(db.metrics_summary().unwrap().is_empty());
+    }
+}
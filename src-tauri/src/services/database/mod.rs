@@ -0,0 +1,858 @@
+use crate::error::{AppError, Result};
+use crate::services::ollama::StorySegment;
+use crate::services::whisper::{TranscriptionResult, TranscriptionSegment};
+use rusqlite::{params, Connection, OptionalExtension, ToSql};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+mod benchmarks;
+mod bookmarks;
+mod embeddings;
+mod entities;
+mod feeds;
+mod metrics;
+mod prompts;
+mod queue;
+mod speakers;
+mod tags;
+mod transcript_edits;
+mod usage;
+
+pub use benchmarks::Benchmark;
+pub use bookmarks::{Bookmark, BookmarkInput};
+pub use embeddings::{EmbeddingStoreStats, IndexedSegment};
+pub use entities::{Entity, StoredMention};
+pub use feeds::{Feed, FeedEpisode};
+pub use metrics::{Metric, MetricSummary};
+pub use prompts::{PromptTemplate, PromptTemplateInput};
+pub use speakers::Speaker;
+pub use tags::{Collection, Tag};
+pub use transcript_edits::{SegmentChange, TranscriptEdit};
+pub use usage::{ApiUsageEntry, ApiUsageRecord, UsageFilter, UsageGrouping, UsageReportRow};
+
+/// Schema migrations, applied in order. `PRAGMA user_version` records how many have run,
+/// so new tables or columns are added by appending a migration, never by editing one.
+const MIGRATIONS: &[&str] = &[
+    "CREATE TABLE media_files (
+        id INTEGER PRIMARY KEY,
+        path TEXT NOT NULL UNIQUE,
+        name TEXT NOT NULL,
+        size INTEGER,
+        modified INTEGER,
+        duration REAL,
+        added_at INTEGER NOT NULL
+    );
+    CREATE TABLE transcriptions (
+        id INTEGER PRIMARY KEY,
+        media_id INTEGER NOT NULL REFERENCES media_files(id) ON DELETE CASCADE,
+        full_text TEXT NOT NULL,
+        segments TEXT NOT NULL,
+        language TEXT,
+        duration REAL NOT NULL,
+        model TEXT,
+        created_at INTEGER NOT NULL
+    );
+    CREATE INDEX transcriptions_media ON transcriptions(media_id, created_at);
+    CREATE TABLE summaries (
+        id INTEGER PRIMARY KEY,
+        media_id INTEGER NOT NULL REFERENCES media_files(id) ON DELETE CASCADE,
+        text TEXT NOT NULL,
+        language TEXT,
+        provider TEXT,
+        model TEXT,
+        created_at INTEGER NOT NULL
+    );
+    CREATE INDEX summaries_media ON summaries(media_id, created_at);
+    CREATE TABLE story_orders (
+        id INTEGER PRIMARY KEY,
+        media_id INTEGER NOT NULL REFERENCES media_files(id) ON DELETE CASCADE,
+        segments TEXT NOT NULL,
+        created_at INTEGER NOT NULL
+    );
+    CREATE INDEX story_orders_media ON story_orders(media_id, created_at);
+    CREATE TABLE jobs (
+        id TEXT PRIMARY KEY,
+        kind TEXT NOT NULL,
+        media_path TEXT,
+        status TEXT NOT NULL,
+        error TEXT,
+        created_at INTEGER NOT NULL,
+        finished_at INTEGER
+    );
+    CREATE INDEX jobs_created ON jobs(created_at);",
+    "CREATE TABLE tags (
+        id INTEGER PRIMARY KEY,
+        name TEXT NOT NULL UNIQUE COLLATE NOCASE,
+        color TEXT,
+        created_at INTEGER NOT NULL
+    );
+    CREATE TABLE media_tags (
+        media_id INTEGER NOT NULL REFERENCES media_files(id) ON DELETE CASCADE,
+        tag_id INTEGER NOT NULL REFERENCES tags(id) ON DELETE CASCADE,
+        PRIMARY KEY (media_id, tag_id)
+    );
+    CREATE INDEX media_tags_tag ON media_tags(tag_id);
+    CREATE TABLE collections (
+        id INTEGER PRIMARY KEY,
+        name TEXT NOT NULL,
+        description TEXT,
+        created_at INTEGER NOT NULL
+    );
+    CREATE TABLE collection_items (
+        collection_id INTEGER NOT NULL REFERENCES collections(id) ON DELETE CASCADE,
+        media_id INTEGER NOT NULL REFERENCES media_files(id) ON DELETE CASCADE,
+        added_at INTEGER NOT NULL,
+        PRIMARY KEY (collection_id, media_id)
+    );",
+    "CREATE TABLE job_queue (
+        id TEXT PRIMARY KEY,
+        spec TEXT NOT NULL,
+        status TEXT NOT NULL,
+        position INTEGER NOT NULL,
+        attempts INTEGER NOT NULL DEFAULT 0,
+        error TEXT,
+        result TEXT,
+        created_at INTEGER NOT NULL,
+        started_at INTEGER,
+        finished_at INTEGER
+    );
+    CREATE INDEX job_queue_status ON job_queue(status, position);",
+    "CREATE TABLE feeds (
+        id INTEGER PRIMARY KEY,
+        url TEXT NOT NULL UNIQUE,
+        title TEXT NOT NULL,
+        description TEXT,
+        download_dir TEXT,
+        auto_transcribe_model TEXT,
+        last_checked_at INTEGER,
+        created_at INTEGER NOT NULL
+    );
+    CREATE TABLE feed_episodes (
+        id INTEGER PRIMARY KEY,
+        feed_id INTEGER NOT NULL REFERENCES feeds(id) ON DELETE CASCADE,
+        guid TEXT NOT NULL,
+        title TEXT NOT NULL,
+        description TEXT,
+        audio_url TEXT NOT NULL,
+        size INTEGER NOT NULL,
+        published_at INTEGER,
+        local_path TEXT,
+        created_at INTEGER NOT NULL,
+        UNIQUE (feed_id, guid)
+    );
+    CREATE INDEX feed_episodes_published ON feed_episodes(feed_id, published_at);",
+    "CREATE TABLE entities (
+        id INTEGER PRIMARY KEY,
+        name TEXT NOT NULL COLLATE NOCASE,
+        kind TEXT NOT NULL,
+        created_at INTEGER NOT NULL,
+        UNIQUE (name, kind)
+    );
+    CREATE TABLE entity_mentions (
+        entity_id INTEGER NOT NULL REFERENCES entities(id) ON DELETE CASCADE,
+        media_id INTEGER NOT NULL REFERENCES media_files(id) ON DELETE CASCADE,
+        start REAL NOT NULL,
+        end REAL NOT NULL
+    );
+    CREATE INDEX entity_mentions_entity ON entity_mentions(entity_id);
+    CREATE INDEX entity_mentions_media ON entity_mentions(media_id);",
+    "CREATE TABLE embeddings (
+        content_hash TEXT NOT NULL,
+        model TEXT NOT NULL,
+        dimensions INTEGER NOT NULL,
+        vector BLOB NOT NULL,
+        created_at INTEGER NOT NULL,
+        PRIMARY KEY (content_hash, model)
+    );
+    CREATE TABLE embedding_segments (
+        media_id INTEGER NOT NULL REFERENCES media_files(id) ON DELETE CASCADE,
+        model TEXT NOT NULL,
+        segment INTEGER NOT NULL,
+        start REAL NOT NULL,
+        end REAL NOT NULL,
+        content_hash TEXT NOT NULL,
+        PRIMARY KEY (media_id, model, segment)
+    );",
+    "CREATE TABLE prompt_templates (
+        id INTEGER PRIMARY KEY,
+        name TEXT NOT NULL UNIQUE COLLATE NOCASE,
+        kind TEXT NOT NULL,
+        system TEXT,
+        prompt TEXT NOT NULL,
+        created_at INTEGER NOT NULL,
+        updated_at INTEGER NOT NULL
+    );",
+    "CREATE TABLE api_usage (
+        id INTEGER PRIMARY KEY,
+        provider TEXT NOT NULL,
+        model TEXT NOT NULL,
+        operation TEXT NOT NULL,
+        input_tokens INTEGER NOT NULL,
+        output_tokens INTEGER NOT NULL,
+        audio_seconds REAL NOT NULL,
+        cost REAL,
+        media_path TEXT,
+        created_at INTEGER NOT NULL
+    );
+    CREATE INDEX api_usage_created ON api_usage(created_at);
+    CREATE INDEX api_usage_media ON api_usage(media_path);",
+    "CREATE TABLE metrics (
+        id INTEGER PRIMARY KEY,
+        name TEXT NOT NULL,
+        duration_ms INTEGER,
+        attributes TEXT NOT NULL,
+        recorded_at INTEGER NOT NULL
+    );
+    CREATE INDEX metrics_name ON metrics(name);",
+    "CREATE TABLE job_events (
+        id INTEGER PRIMARY KEY,
+        job_id TEXT NOT NULL REFERENCES job_queue(id) ON DELETE CASCADE,
+        status TEXT NOT NULL,
+        detail TEXT,
+        created_at INTEGER NOT NULL
+    );
+    CREATE INDEX job_events_job ON job_events(job_id);",
+    "CREATE TABLE transcript_edits (
+        id INTEGER PRIMARY KEY,
+        transcription_id INTEGER NOT NULL REFERENCES transcriptions(id) ON DELETE CASCADE,
+        kind TEXT NOT NULL,
+        segment_index INTEGER NOT NULL,
+        before TEXT NOT NULL,
+        after TEXT NOT NULL,
+        author TEXT,
+        created_at INTEGER NOT NULL,
+        reverted_at INTEGER
+    );
+    CREATE INDEX transcript_edits_transcription ON transcript_edits(transcription_id);",
+    "CREATE TABLE transcript_speakers (
+        id INTEGER PRIMARY KEY,
+        transcription_id INTEGER NOT NULL REFERENCES transcriptions(id) ON DELETE CASCADE,
+        name TEXT NOT NULL COLLATE NOCASE,
+        color TEXT,
+        UNIQUE (transcription_id, name)
+    );",
+    "CREATE TABLE bookmarks (
+        id INTEGER PRIMARY KEY,
+        media_id INTEGER NOT NULL REFERENCES media_files(id) ON DELETE CASCADE,
+        start_seconds REAL NOT NULL,
+        end_seconds REAL,
+        note TEXT,
+        color TEXT,
+        created_at INTEGER NOT NULL
+    );
+    CREATE INDEX bookmarks_media ON bookmarks(media_id);",
+    "ALTER TABLE media_files ADD COLUMN content_hash TEXT;
+    CREATE INDEX media_files_hash ON media_files(content_hash);",
+    "CREATE TABLE benchmarks (
+        id INTEGER PRIMARY KEY,
+        model TEXT NOT NULL,
+        audio_seconds REAL NOT NULL,
+        elapsed_seconds REAL NOT NULL,
+        created_at INTEGER NOT NULL
+    );
+    CREATE INDEX benchmarks_model ON benchmarks(model);",
+    "INSERT OR IGNORE INTO transcript_speakers (transcription_id, name)
+    SELECT t.id, json_extract(s.value, '$.speaker')
+    FROM transcriptions t, json_each(t.segments) s
+    WHERE json_extract(s.value, '$.speaker') IS NOT NULL;",
+    "CREATE TABLE queue_state (paused INTEGER NOT NULL);
+    INSERT INTO queue_state (paused) VALUES (0);",
+];
+
+/// A transcription saved for a media file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredTranscription {
+    pub id: i64,
+    pub media_path: String,
+    pub model: Option<String>,
+    /// Unix seconds
+    pub created_at: u64,
+    #[serde(flatten)]
+    pub result: TranscriptionResult,
+}
+
+/// One transcription run in the history (without the transcript itself)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscriptionRun {
+    pub id: i64,
+    pub media_path: String,
+    pub model: Option<String>,
+    pub language: Option<String>,
+    /// Media duration in seconds
+    pub duration: f64,
+    /// Unix seconds
+    pub created_at: u64,
+}
+
+/// Summary text to save for a media file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SummaryInput {
+    pub text: String,
+    pub language: Option<String>,
+    pub provider: Option<String>,
+    pub model: Option<String>,
+}
+
+/// A summary saved for a media file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredSummary {
+    pub id: i64,
+    pub media_path: String,
+    pub created_at: u64,
+    #[serde(flatten)]
+    pub summary: SummaryInput,
+}
+
+/// A story order saved for a media file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredStoryOrder {
+    pub id: i64,
+    pub media_path: String,
+    pub created_at: u64,
+    pub segments: Vec<StorySegment>,
+}
+
+/// One entry of the job history
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JobRecord {
+    pub id: String,
+    /// What the job did, e.g. "transcription" or "summary"
+    pub kind: String,
+    pub media_path: Option<String>,
+    /// e.g. "running", "completed", "failed"
+    pub status: String,
+    pub error: Option<String>,
+    pub created_at: u64,
+    pub finished_at: Option<u64>,
+}
+
+/// Project database (SQLite) holding media files and everything produced from them
+pub struct Database {
+    conn: Connection,
+}
+
+impl Database {
+    /// Get the default database file path
+    pub fn default_path() -> Result<PathBuf> {
+        let data_dir = dirs::data_local_dir()
+            .ok_or_else(|| AppError::InvalidPath("Cannot find data directory".to_string()))?;
+        Ok(data_dir.join("clip-flow").join("clip-flow.db"))
+    }
+
+    /// Schema version of databases this build creates (their `user_version`)
+    pub fn schema_version() -> usize {
+        MIGRATIONS.len()
+    }
+
+    /// Open the database at the default location
+    pub fn open() -> Result<Self> {
+        Self::open_at(&Self::default_path()?)
+    }
+
+    /// Open (creating and migrating if needed) a database file
+    pub fn open_at(path: &Path) -> Result<Self> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        Self::init(Connection::open(path)?)
+    }
+
+    /// Open a private in-memory database
+    pub fn open_in_memory() -> Result<Self> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(conn: Connection) -> Result<Self> {
+        conn.pragma_update(None, "foreign_keys", true)?;
+        let db = Self { conn };
+        db.migrate()?;
+        Ok(db)
+    }
+
+    fn migrate(&self) -> Result<()> {
+        let version: usize = self
+            .conn
+            .pragma_query_value(None, "user_version", |row| row.get(0))?;
+
+        for (i, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+            let tx = self.conn.unchecked_transaction()?;
+            tx.execute_batch(migration)?;
+            tx.pragma_update(None, "user_version", i + 1)?;
+            tx.commit()?;
+        }
+        Ok(())
+    }
+
+    /// Get the id of a media file, adding it (with current size/mtime) if unknown
+    pub fn media_file_id(&self, path: &str) -> Result<i64> {
+        let metadata = std::fs::metadata(path).ok();
+        let modified = metadata
+            .as_ref()
+            .and_then(|m| m.modified().ok())
+            .and_then(|t| t.duration_since(SystemTime::UNIX_EPOCH).ok())
+            .map(|d| d.as_secs());
+        let name = Path::new(path)
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| path.to_string());
+
+        self.conn.execute(
+            "INSERT INTO media_files (path, name, size, modified, added_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)
+             ON CONFLICT(path) DO UPDATE SET size = excluded.size, modified = excluded.modified,
+                content_hash = CASE WHEN size IS excluded.size AND modified IS excluded.modified
+                    THEN content_hash END",
+            params![path, name, metadata.map(|m| m.len()), modified, now()],
+        )?;
+        Ok(self.conn.query_row(
+            "SELECT id FROM media_files WHERE path = ?1",
+            [path],
+            |row| row.get(0),
+        )?)
+    }
+
+    /// Whether a media file is already in the database
+    pub fn has_media_file(&self, path: &str) -> Result<bool> {
+        Ok(self
+            .conn
+            .query_row("SELECT 1 FROM media_files WHERE path = ?1", [path], |_| Ok(()))
+            .optional()?
+            .is_some())
+    }
+
+    /// Path of a media file in the database with the given content hash
+    pub fn media_path_with_hash(&self, content_hash: &str) -> Result<Option<String>> {
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT path FROM media_files WHERE content_hash = ?1 ORDER BY id LIMIT 1",
+                [content_hash],
+                |row| row.get(0),
+            )
+            .optional()?)
+    }
+
+    /// Add a media file (if unknown) and record the hash of its content
+    pub fn register_media_file(&self, path: &str, content_hash: &str) -> Result<i64> {
+        let id = self.media_file_id(path)?;
+        self.set_content_hash(id, content_hash)?;
+        Ok(id)
+    }
+
+    /// Record the hash of a media file's content
+    pub fn set_content_hash(&self, id: i64, content_hash: &str) -> Result<()> {
+        self.conn.execute(
+            "UPDATE media_files SET content_hash = ?1 WHERE id = ?2",
+            params![content_hash, id],
+        )?;
+        Ok(())
+    }
+
+    /// Ids and paths of the media files of `size` bytes whose content hash is
+    /// not known yet, e.g. ones added by a transcription rather than an import
+    pub fn unhashed_media_files(&self, size: u64) -> Result<Vec<(i64, String)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, path FROM media_files WHERE content_hash IS NULL AND size = ?1",
+        )?;
+        let files = stmt
+            .query_map([size], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(files)
+    }
+
+    /// Point the records of a media file at its new path after a move or rename
+    pub fn rename_media_file(&self, from: &str, to: &str) -> Result<()> {
+        let name = Path::new(to)
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| to.to_string());
+        self.conn.execute(
+            "UPDATE media_files SET path = ?1, name = ?2 WHERE path = ?3",
+            params![to, name, from],
+        )?;
+        Ok(())
+    }
+
+    /// Save a transcription result for a media file
+    pub fn save_transcription(
+        &self,
+        media_path: &str,
+        result: &TranscriptionResult,
+        model: Option<&str>,
+    ) -> Result<i64> {
+        let tx = self.conn.unchecked_transaction()?;
+        let media_id = self.media_file_id(media_path)?;
+        self.conn.execute(
+            "UPDATE media_files SET duration = ?1 WHERE id = ?2",
+            params![result.duration, media_id],
+        )?;
+        self.conn.execute(
+            "INSERT INTO transcriptions
+                (media_id, full_text, segments, language, duration, model, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                media_id,
+                result.full_text,
+                serde_json::to_string(&result.segments)?,
+                result.language,
+                result.duration,
+                model,
+                now()
+            ],
+        )?;
+        let id = self.conn.last_insert_rowid();
+        self.add_segment_speakers(id, &result.segments)?;
+        tx.commit()?;
+        Ok(id)
+    }
+
+    /// Get the most recent transcription of a media file
+    pub fn latest_transcription(&self, media_path: &str) -> Result<Option<StoredTranscription>> {
+        self.find_transcription(
+            "m.path = ?1 ORDER BY t.created_at DESC, t.id DESC LIMIT 1",
+            &media_path,
+        )
+    }
+
+    /// Get a transcription by id
+    pub fn transcription(&self, id: i64) -> Result<Option<StoredTranscription>> {
+        self.find_transcription("t.id = ?1", &id)
+    }
+
+    fn find_transcription(
+        &self,
+        condition: &str,
+        param: &dyn ToSql,
+    ) -> Result<Option<StoredTranscription>> {
+        let sql = format!(
+            "SELECT t.id, m.path, t.full_text, t.segments, t.language, t.duration, t.model,
+                    t.created_at
+             FROM transcriptions t JOIN media_files m ON m.id = t.media_id
+             WHERE {}",
+            condition
+        );
+        let row = self
+            .conn
+            .query_row(&sql, [param], |row| {
+                Ok((
+                    StoredTranscription {
+                        id: row.get(0)?,
+                        media_path: row.get(1)?,
+                        model: row.get(6)?,
+                        created_at: row.get(7)?,
+                        result: TranscriptionResult {
+                            segments: Vec::new(),
+                            full_text: row.get(2)?,
+                            language: row.get(4)?,
+                            duration: row.get(5)?,
+                        },
+                    },
+                    row.get::<_, String>(3)?,
+                ))
+            })
+            .optional()?;
+
+        let Some((mut stored, segments)) = row else {
+            return Ok(None);
+        };
+        stored.result.segments = serde_json::from_str::<Vec<TranscriptionSegment>>(&segments)?;
+        Ok(Some(stored))
+    }
+
+    /// List transcription runs (without their text), newest first,
+    /// either of one media file or of all files
+    pub fn transcription_history(
+        &self,
+        media_path: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<TranscriptionRun>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT t.id, m.path, t.model, t.language, t.duration, t.created_at
+             FROM transcriptions t JOIN media_files m ON m.id = t.media_id
+             WHERE ?1 IS NULL OR m.path = ?1
+             ORDER BY t.created_at DESC, t.id DESC LIMIT ?2",
+        )?;
+        let runs = stmt
+            .query_map(params![media_path, limit as i64], |row| {
+                Ok(TranscriptionRun {
+                    id: row.get(0)?,
+                    media_path: row.get(1)?,
+                    model: row.get(2)?,
+                    language: row.get(3)?,
+                    duration: row.get(4)?,
+                    created_at: row.get(5)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(runs)
+    }
+
+    /// Save a summary for a media file
+    pub fn save_summary(&self, media_path: &str, summary: &SummaryInput) -> Result<i64> {
+        let media_id = self.media_file_id(media_path)?;
+        self.conn.execute(
+            "INSERT INTO summaries (media_id, text, language, provider, model, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                media_id,
+                summary.text,
+                summary.language,
+                summary.provider,
+                summary.model,
+                now()
+            ],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    /// Get the most recent summary of a media file
+    pub fn latest_summary(&self, media_path: &str) -> Result<Option<StoredSummary>> {
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT s.id, s.text, s.language, s.provider, s.model, s.created_at
+                 FROM summaries s JOIN media_files m ON m.id = s.media_id
+                 WHERE m.path = ?1
+                 ORDER BY s.created_at DESC, s.id DESC LIMIT 1",
+                [media_path],
+                |row| {
+                    Ok(StoredSummary {
+                        id: row.get(0)?,
+                        media_path: media_path.to_string(),
+                        created_at: row.get(5)?,
+                        summary: SummaryInput {
+                            text: row.get(1)?,
+                            language: row.get(2)?,
+                            provider: row.get(3)?,
+                            model: row.get(4)?,
+                        },
+                    })
+                },
+            )
+            .optional()?)
+    }
+
+    /// Save a story order for a media file
+    pub fn save_story_order(&self, media_path: &str, segments: &[StorySegment]) -> Result<i64> {
+        let media_id = self.media_file_id(media_path)?;
+        self.conn.execute(
+            "INSERT INTO story_orders (media_id, segments, created_at) VALUES (?1, ?2, ?3)",
+            params![media_id, serde_json::to_string(segments)?, now()],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    /// Get the most recent story order of a media file
+    pub fn latest_story_order(&self, media_path: &str) -> Result<Option<StoredStoryOrder>> {
+        let row = self
+            .conn
+            .query_row(
+                "SELECT o.id, o.segments, o.created_at
+                 FROM story_orders o JOIN media_files m ON m.id = o.media_id
+                 WHERE m.path = ?1
+                 ORDER BY o.created_at DESC, o.id DESC LIMIT 1",
+                [media_path],
+                |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?, row.get(2)?)),
+            )
+            .optional()?;
+
+        let Some((id, segments, created_at)) = row else {
+            return Ok(None);
+        };
+        Ok(Some(StoredStoryOrder {
+            id,
+            media_path: media_path.to_string(),
+            created_at,
+            segments: serde_json::from_str(&segments)?,
+        }))
+    }
+
+    /// Insert a job, or update it if a job with the same id was recorded before
+    pub fn record_job(&self, job: &JobRecord) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO jobs (id, kind, media_path, status, error, created_at, finished_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+             ON CONFLICT(id) DO UPDATE SET
+                status = excluded.status,
+                error = excluded.error,
+                finished_at = excluded.finished_at",
+            params![
+                job.id,
+                job.kind,
+                job.media_path,
+                job.status,
+                job.error,
+                job.created_at,
+                job.finished_at
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Get the most recent jobs, newest first
+    pub fn list_jobs(&self, limit: usize) -> Result<Vec<JobRecord>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, kind, media_path, status, error, created_at, finished_at
+             FROM jobs ORDER BY created_at DESC, rowid DESC LIMIT ?1",
+        )?;
+        let jobs = stmt
+            .query_map([limit as i64], |row| {
+                Ok(JobRecord {
+                    id: row.get(0)?,
+                    kind: row.get(1)?,
+                    media_path: row.get(2)?,
+                    status: row.get(3)?,
+                    error: row.get(4)?,
+                    created_at: row.get(5)?,
+                    finished_at: row.get(6)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(jobs)
+    }
+}
+
+/// Current time in Unix seconds
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn sample_result(text: &str) -> TranscriptionResult {
+        TranscriptionResult {
+            segments: vec![TranscriptionSegment::new(0.0, 1.5, text)],
+            full_text: text.to_string(),
+            language: Some("en".to_string()),
+            duration: 1.5,
+        }
+    }
+
+    #[test]
+    fn test_transcription_roundtrip() {
+        let db = Database::open_in_memory().unwrap();
+        assert!(db.latest_transcription("/m/a.mp4").unwrap().is_none());
+
+        db.save_transcription("/m/a.mp4", &sample_result("first"), Some("base"))
+            .unwrap();
+        db.save_transcription("/m/a.mp4", &sample_result("second"), None)
+            .unwrap();
+
+        let stored = db.latest_transcription("/m/a.mp4").unwrap().unwrap();
+        assert_eq!(stored.result.full_text, "second");
+        assert_eq!(stored.result.segments.len(), 1);
+        assert_eq!(stored.result.language.as_deref(), Some("en"));
+        assert!(db.latest_transcription("/m/b.mp4").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_transcription_history() {
+        let db = Database::open_in_memory().unwrap();
+        let first = db
+            .save_transcription("/m/a.mp4", &sample_result("first"), Some("base"))
+            .unwrap();
+        db.save_transcription("/m/b.mp4", &sample_result("other"), Some("small"))
+            .unwrap();
+        let last = db
+            .save_transcription("/m/a.mp4", &sample_result("second"), Some("large-v3"))
+            .unwrap();
+
+        let history = db.transcription_history(Some("/m/a.mp4"), 10).unwrap();
+        assert_eq!(history.iter().map(|r| r.id).collect::<Vec<_>>(), [last, first]);
+        assert_eq!(history[0].model.as_deref(), Some("large-v3"));
+        assert_eq!(history[0].language.as_deref(), Some("en"));
+
+        assert_eq!(db.transcription_history(None, 10).unwrap().len(), 3);
+        assert_eq!(db.transcription_history(None, 1).unwrap()[0].id, last);
+
+        // Earlier runs stay loadable
+        let stored = db.transcription(first).unwrap().unwrap();
+        assert_eq!(stored.result.full_text, "first");
+        assert_eq!(stored.media_path, "/m/a.mp4");
+        assert!(db.transcription(9999).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_summary_and_story_order_roundtrip() {
+        let db = Database::open_in_memory().unwrap();
+        let summary = SummaryInput {
+            text: "A short summary".to_string(),
+            language: Some("en".to_string()),
+            provider: Some("ollama".to_string()),
+            model: Some("llama3".to_string()),
+        };
+        db.save_summary("/m/a.mp4", &summary).unwrap();
+        db.save_story_order(
+            "/m/a.mp4",
+            &[StorySegment {
+                index: 2,
+                reason: "Hook".to_string(),
+            }],
+        )
+        .unwrap();
+
+        let stored = db.latest_summary("/m/a.mp4").unwrap().unwrap();
+        assert_eq!(stored.summary.text, "A short summary");
+        assert_eq!(stored.summary.provider.as_deref(), Some("ollama"));
+
+        let order = db.latest_story_order("/m/a.mp4").unwrap().unwrap();
+        assert_eq!(order.segments[0].index, 2);
+    }
+
+    #[test]
+    fn test_record_job_updates_existing() {
+        let db = Database::open_in_memory().unwrap();
+        let mut job = JobRecord {
+            id: "job-1".to_string(),
+            kind: "transcription".to_string(),
+            media_path: Some("/m/a.mp4".to_string()),
+            status: "running".to_string(),
+            error: None,
+            created_at: 10,
+            finished_at: None,
+        };
+        db.record_job(&job).unwrap();
+
+        job.status = "completed".to_string();
+        job.finished_at = Some(20);
+        db.record_job(&job).unwrap();
+
+        assert_eq!(db.list_jobs(10).unwrap(), vec![job]);
+    }
+
+    #[test]
+    fn test_rename_media_file_keeps_records() {
+        let db = Database::open_in_memory().unwrap();
+        db.save_transcription("/m/a.mp4", &sample_result("moved"), None)
+            .unwrap();
+
+        db.rename_media_file("/m/a.mp4", "/m/sorted/a.mp4").unwrap();
+
+        assert!(db.latest_transcription("/m/a.mp4").unwrap().is_none());
+        let stored = db.latest_transcription("/m/sorted/a.mp4").unwrap().unwrap();
+        assert_eq!(stored.result.full_text, "moved");
+    }
+
+    #[test]
+    fn test_data_persists_across_opens() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("nested").join("clip-flow.db");
+
+        Database::open_at(&path)
+            .unwrap()
+            .save_transcription("/m/a.mp4", &sample_result("kept"), None)
+            .unwrap();
+
+        // Reopening must not re-run migrations
+        let db = Database::open_at(&path).unwrap();
+        let stored = db.latest_transcription("/m/a.mp4").unwrap().unwrap();
+        assert_eq!(stored.result.full_text, "kept");
+    }
+}
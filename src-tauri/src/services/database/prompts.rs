@@ -0,0 +1,216 @@
+use super::{now, Database};
+use crate::error::{AppError, Result};
+use crate::services::prompt_templates::template_variables;
+use rusqlite::{params, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+/// A named LLM prompt with `{{variable}}` placeholders
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PromptTemplate {
+    pub id: i64,
+    pub name: String,
+    /// What the prompt is for, e.g. "summary", "chapters" or "extraction"
+    pub kind: String,
+    /// System instructions; a generic assistant prompt when not set
+    pub system: Option<String>,
+    pub prompt: String,
+    /// Variables the prompt and system instructions use
+    pub variables: Vec<String>,
+    /// Unix seconds
+    pub created_at: u64,
+    pub updated_at: u64,
+}
+
+/// A prompt template as created or edited by the user
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptTemplateInput {
+    pub name: String,
+    #[serde(default = "default_kind")]
+    pub kind: String,
+    #[serde(default)]
+    pub system: Option<String>,
+    pub prompt: String,
+}
+
+fn default_kind() -> String {
+    "custom".to_string()
+}
+
+/// Variables of a template's system instructions and prompt
+fn variables(system: Option<&str>, prompt: &str) -> Result<Vec<String>> {
+    let mut names = template_variables(system.unwrap_or_default())?;
+    for name in template_variables(prompt)? {
+        if !names.contains(&name) {
+            names.push(name);
+        }
+    }
+    Ok(names)
+}
+
+impl PromptTemplateInput {
+    /// Check the name, prompt and placeholder syntax
+    fn validate(&self) -> Result<()> {
+        if self.name.trim().is_empty() {
+            return Err(AppError::InvalidInput("Name must not be empty".to_string()));
+        }
+        if self.prompt.trim().is_empty() {
+            return Err(AppError::InvalidInput("Prompt must not be empty".to_string()));
+        }
+        variables(self.system.as_deref(), &self.prompt)?;
+        Ok(())
+    }
+}
+
+impl Database {
+    /// Create a prompt template. Names are unique (case-insensitive).
+    pub fn create_prompt_template(&self, input: &PromptTemplateInput) -> Result<PromptTemplate> {
+        input.validate()?;
+        self.check_prompt_template_name(input.name.trim(), None)?;
+        let now = now();
+        self.conn.execute(
+            "INSERT INTO prompt_templates (name, kind, system, prompt, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?5)",
+            params![input.name.trim(), input.kind.trim(), input.system, input.prompt, now],
+        )?;
+        self.prompt_template(self.conn.last_insert_rowid())
+    }
+
+    /// Replace a prompt template's fields
+    pub fn update_prompt_template(
+        &self,
+        id: i64,
+        input: &PromptTemplateInput,
+    ) -> Result<PromptTemplate> {
+        input.validate()?;
+        self.check_prompt_template_name(input.name.trim(), Some(id))?;
+        self.conn.execute(
+            "UPDATE prompt_templates
+             SET name = ?1, kind = ?2, system = ?3, prompt = ?4, updated_at = ?5
+             WHERE id = ?6",
+            params![input.name.trim(), input.kind.trim(), input.system, input.prompt, now(), id],
+        )?;
+        self.prompt_template(id)
+    }
+
+    pub fn delete_prompt_template(&self, id: i64) -> Result<()> {
+        self.conn.execute("DELETE FROM prompt_templates WHERE id = ?1", [id])?;
+        Ok(())
+    }
+
+    pub fn prompt_template(&self, id: i64) -> Result<PromptTemplate> {
+        self.query_prompt_templates("WHERE id = ?1", [id])?
+            .pop()
+            .ok_or_else(|| AppError::InvalidInput(format!("Prompt template not found: {}", id)))
+    }
+
+    /// List prompt templates by name, optionally of one kind
+    pub fn list_prompt_templates(&self, kind: Option<&str>) -> Result<Vec<PromptTemplate>> {
+        self.query_prompt_templates("WHERE ?1 IS NULL OR kind = ?1", [kind])
+    }
+
+    fn check_prompt_template_name(&self, name: &str, id: Option<i64>) -> Result<()> {
+        let existing: Option<i64> = self
+            .conn
+            .query_row(
+                "SELECT id FROM prompt_templates WHERE name = ?1 COLLATE NOCASE",
+                [name],
+                |row| row.get(0),
+            )
+            .optional()?;
+        match existing {
+            Some(existing) if Some(existing) != id => Err(AppError::InvalidInput(format!(
+                "A prompt template named \"{}\" already exists",
+                name
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    fn query_prompt_templates(
+        &self,
+        condition: &str,
+        params: impl rusqlite::Params,
+    ) -> Result<Vec<PromptTemplate>> {
+        let sql = format!(
+            "SELECT id, name, kind, system, prompt, created_at, updated_at
+             FROM prompt_templates {} ORDER BY name COLLATE NOCASE",
+            condition
+        );
+        let mut stmt = self.conn.prepare(&sql)?;
+        let rows = stmt
+            .query_map(params, |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, Option<String>>(3)?,
+                    row.get::<_, String>(4)?,
+                    row.get::<_, u64>(5)?,
+                    row.get::<_, u64>(6)?,
+                ))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        rows.into_iter()
+            .map(|(id, name, kind, system, prompt, created_at, updated_at)| {
+                Ok(PromptTemplate {
+                    variables: variables(system.as_deref(), &prompt)?,
+                    id,
+                    name,
+                    kind,
+                    system,
+                    prompt,
+                    created_at,
+                    updated_at,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input(name: &str, kind: &str, prompt: &str) -> PromptTemplateInput {
+        PromptTemplateInput {
+            name: name.to_string(),
+            kind: kind.to_string(),
+            system: Some("Write in {{language}}.".to_string()),
+            prompt: prompt.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_prompt_template_crud() {
+        let db = Database::open_in_memory().unwrap();
+        let created = db
+            .create_prompt_template(&input("Tweet", "summary", "Summarize: {{text}}"))
+            .unwrap();
+        assert_eq!(created.variables, ["language", "text"]);
+
+        db.create_prompt_template(&input("Chapters", "chapters", "{{transcript}}"))
+            .unwrap();
+        // Names are unique regardless of case
+        assert!(db.create_prompt_template(&input("tweet", "summary", "x")).is_err());
+        // Broken placeholders are rejected
+        assert!(db.create_prompt_template(&input("Broken", "custom", "{{text")).is_err());
+
+        let updated = db
+            .update_prompt_template(created.id, &input("Tweet", "summary", "Shorter: {{text}}"))
+            .unwrap();
+        assert_eq!(updated.prompt, "Shorter: {{text}}");
+
+        let names: Vec<String> = db
+            .list_prompt_templates(None)
+            .unwrap()
+            .into_iter()
+            .map(|t| t.name)
+            .collect();
+        assert_eq!(names, ["Chapters", "Tweet"]);
+        assert_eq!(db.list_prompt_templates(Some("summary")).unwrap().len(), 1);
+
+        db.delete_prompt_template(created.id).unwrap();
+        assert!(db.prompt_template(created.id).is_err());
+    }
+}
@@ -0,0 +1,399 @@
+use super::{now, Database};
+use crate::error::{AppError, Result};
+use crate::services::job_queue::{JobEvent, JobSpec, JobStatus, QueuedJob};
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ToSqlOutput, Type, ValueRef};
+use rusqlite::{params, Row, ToSql};
+use serde::de::DeserializeOwned;
+
+impl ToSql for JobStatus {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(self.as_str().into())
+    }
+}
+
+impl FromSql for JobStatus {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        JobStatus::parse(value.as_str()?).ok_or(FromSqlError::InvalidType)
+    }
+}
+
+/// Parse a nullable JSON text column
+fn json_column<T: DeserializeOwned>(row: &Row<'_>, index: usize) -> rusqlite::Result<Option<T>> {
+    let Some(text) = row.get::<_, Option<String>>(index)? else {
+        return Ok(None);
+    };
+    serde_json::from_str(&text)
+        .map(Some)
+        .map_err(|e| rusqlite::Error::FromSqlConversionFailure(index, Type::Text, Box::new(e)))
+}
+
+const JOB_COLUMNS: &str = "id, spec, status, position, attempts, error, result, \
+                           created_at, started_at, finished_at";
+
+impl Database {
+    /// Add a job to the end of the queue
+    pub fn enqueue_job(&self, spec: &JobSpec) -> Result<QueuedJob> {
+        let id = uuid::Uuid::new_v4().to_string();
+        self.conn.execute(
+            "INSERT INTO job_queue (id, spec, status, position, created_at)
+             VALUES (?1, ?2, ?3, (SELECT COALESCE(MAX(position), 0) + 1 FROM job_queue), ?4)",
+            params![id, serde_json::to_string(spec)?, JobStatus::Queued, now()],
+        )?;
+        self.record_job_event(&id, JobStatus::Queued, None)?;
+        self.queued_job(&id)
+    }
+
+    /// Add a status change to the history of a job
+    fn record_job_event(&self, id: &str, status: JobStatus, detail: Option<&str>) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO job_events (job_id, status, detail, created_at) VALUES (?1, ?2, ?3, ?4)",
+            params![id, status, detail, now()],
+        )?;
+        Ok(())
+    }
+
+    /// Status changes of a job, oldest first
+    pub fn job_events(&self, id: &str) -> Result<Vec<JobEvent>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT status, detail, created_at FROM job_events WHERE job_id = ?1 ORDER BY id",
+        )?;
+        let events = stmt
+            .query_map([id], |row| {
+                Ok(JobEvent {
+                    status: row.get(0)?,
+                    detail: row.get(1)?,
+                    created_at: row.get(2)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(events)
+    }
+
+    fn read_jobs(&self, condition: &str, params: &[&dyn ToSql]) -> Result<Vec<QueuedJob>> {
+        let sql = format!(
+            "SELECT {} FROM job_queue WHERE {} ORDER BY position",
+            JOB_COLUMNS, condition
+        );
+        let mut stmt = self.conn.prepare(&sql)?;
+        let jobs = stmt
+            .query_map(params, |row| {
+                Ok(QueuedJob {
+                    id: row.get(0)?,
+                    spec: json_column(row, 1)?.ok_or_else(|| {
+                        rusqlite::Error::InvalidColumnType(1, "spec".to_string(), Type::Null)
+                    })?,
+                    status: row.get(2)?,
+                    position: row.get(3)?,
+                    attempts: row.get(4)?,
+                    error: row.get(5)?,
+                    result: json_column(row, 6)?,
+                    created_at: row.get(7)?,
+                    started_at: row.get(8)?,
+                    finished_at: row.get(9)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(jobs)
+    }
+
+    /// Get a job of the queue
+    pub fn queued_job(&self, id: &str) -> Result<QueuedJob> {
+        self.read_jobs("id = ?1", &[&id])?
+            .pop()
+            .ok_or_else(|| AppError::InvalidInput(format!("Job not found: {}", id)))
+    }
+
+    /// All jobs of the queue (waiting, running and finished) in queue order
+    pub fn job_queue(&self) -> Result<Vec<QueuedJob>> {
+        self.read_jobs("1", &[])
+    }
+
+    /// The job that should run next, if any is waiting
+    pub fn next_queued_job(&self) -> Result<Option<QueuedJob>> {
+        Ok(self.read_jobs("status = ?1", &[&JobStatus::Queued])?.into_iter().next())
+    }
+
+    /// Mark the next waiting job as running. Returns `None` if the queue has
+    /// nothing to run.
+    pub fn claim_next_job(&self) -> Result<Option<QueuedJob>> {
+        while let Some(next) = self.next_queued_job()? {
+            // None when paused or cancelled in the meantime
+            if let Some(job) = self.start_job(&next.id)? {
+                return Ok(Some(job));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Change the status of a job that currently has one of the `from` statuses
+    fn transition_job(&self, id: &str, from: &[JobStatus], to: JobStatus) -> Result<QueuedJob> {
+        let job = self.queued_job(id)?;
+        if !from.contains(&job.status) {
+            return Err(AppError::InvalidInput(format!(
+                "Cannot change a {} job to {}",
+                job.status.as_str(),
+                to.as_str()
+            )));
+        }
+        let finished_at = to.is_finished().then(now);
+        self.conn.execute(
+            "UPDATE job_queue SET status = ?1, finished_at = ?2 WHERE id = ?3",
+            params![to, finished_at, id],
+        )?;
+        self.record_job_event(id, to, None)?;
+        self.queued_job(id)
+    }
+
+    /// Mark a waiting job as running. Returns `None` if it is no longer waiting.
+    pub fn start_job(&self, id: &str) -> Result<Option<QueuedJob>> {
+        let changed = self.conn.execute(
+            "UPDATE job_queue SET status = ?1, attempts = attempts + 1, started_at = ?2
+             WHERE id = ?3 AND status = ?4",
+            params![JobStatus::Running, now(), id, JobStatus::Queued],
+        )?;
+        if changed == 0 {
+            return Ok(None);
+        }
+        self.record_job_event(id, JobStatus::Running, None)?;
+        self.queued_job(id).map(Some)
+    }
+
+    /// Record the outcome of a running job. A job cancelled while it ran keeps
+    /// its cancelled status and the outcome is dropped; `None` is returned then.
+    pub fn finish_job(
+        &self,
+        id: &str,
+        outcome: std::result::Result<serde_json::Value, String>,
+    ) -> Result<Option<QueuedJob>> {
+        let (status, result, error) = match outcome {
+            Ok(result) => (JobStatus::Completed, Some(serde_json::to_string(&result)?), None),
+            Err(error) => (JobStatus::Failed, None, Some(error)),
+        };
+        let changed = self.conn.execute(
+            "UPDATE job_queue SET status = ?1, result = ?2, error = ?3, finished_at = ?4
+             WHERE id = ?5 AND status = ?6",
+            params![status, result, error, now(), id, JobStatus::Running],
+        )?;
+        if changed == 0 {
+            return Ok(None);
+        }
+        self.record_job_event(id, status, error.as_deref())?;
+        self.queued_job(id).map(Some)
+    }
+
+    /// Keep a waiting job in the queue without running it
+    pub fn pause_job(&self, id: &str) -> Result<QueuedJob> {
+        self.transition_job(id, &[JobStatus::Queued], JobStatus::Paused)
+    }
+
+    /// Let a paused or interrupted job run again, at its place in the queue
+    pub fn resume_job(&self, id: &str) -> Result<QueuedJob> {
+        self.transition_job(id, &[JobStatus::Paused, JobStatus::Interrupted], JobStatus::Queued)
+    }
+
+    /// Cancel a job that has not finished yet
+    pub fn cancel_queued_job(&self, id: &str) -> Result<QueuedJob> {
+        self.transition_job(
+            id,
+            &[
+                JobStatus::Queued,
+                JobStatus::Paused,
+                JobStatus::Running,
+                JobStatus::Interrupted,
+            ],
+            JobStatus::Cancelled,
+        )
+    }
+
+    /// Put a failed, cancelled or interrupted job back at the end of the queue
+    pub fn retry_job(&self, id: &str) -> Result<QueuedJob> {
+        self.transition_job(
+            id,
+            &[JobStatus::Failed, JobStatus::Cancelled, JobStatus::Interrupted],
+            JobStatus::Queued,
+        )?;
+        self.conn.execute(
+            "UPDATE job_queue SET error = NULL, result = NULL, started_at = NULL,
+                position = (SELECT MAX(position) + 1 FROM job_queue)
+             WHERE id = ?1",
+            [id],
+        )?;
+        self.queued_job(id)
+    }
+
+    /// Move a job to `index` in the queue order (clamped to the end)
+    pub fn move_job(&self, id: &str, index: usize) -> Result<Vec<QueuedJob>> {
+        let mut ids: Vec<String> = self.job_queue()?.into_iter().map(|job| job.id).collect();
+        let from = ids
+            .iter()
+            .position(|job_id| job_id == id)
+            .ok_or_else(|| AppError::InvalidInput(format!("Job not found: {}", id)))?;
+        let moved = ids.remove(from);
+        ids.insert(index.min(ids.len()), moved);
+
+        let tx = self.conn.unchecked_transaction()?;
+        for (position, job_id) in ids.iter().enumerate() {
+            tx.execute(
+                "UPDATE job_queue SET position = ?1 WHERE id = ?2",
+                params![position as i64 + 1, job_id],
+            )?;
+        }
+        tx.commit()?;
+        self.job_queue()
+    }
+
+    /// Mark jobs that were still running when the app quit or crashed as
+    /// interrupted, so they wait to be resumed or requeued. Returns those jobs.
+    pub fn interrupt_running_jobs(&self) -> Result<Vec<QueuedJob>> {
+        let running = self.read_jobs("status = ?1", &[&JobStatus::Running])?;
+        for job in &running {
+            self.transition_job(&job.id, &[JobStatus::Running], JobStatus::Interrupted)?;
+        }
+        running.iter().map(|job| self.queued_job(&job.id)).collect()
+    }
+
+    /// Whether the whole queue is paused, kept across app restarts
+    pub fn queue_paused(&self) -> Result<bool> {
+        Ok(self
+            .conn
+            .query_row("SELECT paused FROM queue_state", [], |row| row.get(0))?)
+    }
+
+    pub fn set_queue_paused(&self, paused: bool) -> Result<()> {
+        self.conn
+            .execute("UPDATE queue_state SET paused = ?1", params![paused])?;
+        Ok(())
+    }
+
+    /// Remove completed, failed and cancelled jobs from the queue.
+    /// Returns how many were removed.
+    pub fn clear_finished_jobs(&self) -> Result<usize> {
+        Ok(self.conn.execute(
+            "DELETE FROM job_queue WHERE status IN (?1, ?2, ?3)",
+            params![JobStatus::Completed, JobStatus::Failed, JobStatus::Cancelled],
+        )?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn download(model_id: &str) -> JobSpec {
+        JobSpec::ModelDownload {
+            model_id: model_id.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_queue_order_and_lifecycle() {
+        let db = Database::open_in_memory().unwrap();
+        let a = db.enqueue_job(&download("a")).unwrap();
+        let b = db.enqueue_job(&download("b")).unwrap();
+        assert_eq!(a.status, JobStatus::Queued);
+        assert!(a.position < b.position);
+
+        // Paused jobs are skipped
+        db.pause_job(&a.id).unwrap();
+        assert_eq!(db.next_queued_job().unwrap().unwrap().id, b.id);
+        db.resume_job(&a.id).unwrap();
+
+        let next = db.next_queued_job().unwrap().unwrap();
+        assert_eq!(next.id, a.id);
+        let running = db.start_job(&a.id).unwrap().unwrap();
+        assert_eq!((running.status, running.attempts), (JobStatus::Running, 1));
+        assert!(db.start_job(&a.id).unwrap().is_none());
+
+        let done = db
+            .finish_job(&a.id, Ok(serde_json::json!("/models/a.bin")))
+            .unwrap()
+            .unwrap();
+        assert_eq!(done.status, JobStatus::Completed);
+        assert_eq!(done.result, Some(serde_json::json!("/models/a.bin")));
+        assert!(db.pause_job(&a.id).is_err());
+    }
+
+    #[test]
+    fn test_cancel_running_job_discards_outcome() {
+        let db = Database::open_in_memory().unwrap();
+        let job = db.enqueue_job(&download("a")).unwrap();
+        db.start_job(&job.id).unwrap();
+
+        db.cancel_queued_job(&job.id).unwrap();
+        assert!(db.finish_job(&job.id, Err("boom".to_string())).unwrap().is_none());
+        assert_eq!(db.queued_job(&job.id).unwrap().status, JobStatus::Cancelled);
+
+        let retried = db.retry_job(&job.id).unwrap();
+        assert_eq!(retried.status, JobStatus::Queued);
+        assert_eq!(retried.error, None);
+
+        let statuses: Vec<_> = db
+            .job_events(&job.id)
+            .unwrap()
+            .into_iter()
+            .map(|event| event.status)
+            .collect();
+        assert_eq!(
+            statuses,
+            [JobStatus::Queued, JobStatus::Running, JobStatus::Cancelled, JobStatus::Queued]
+        );
+    }
+
+    #[test]
+    fn test_move_requeue_and_clear() {
+        let db = Database::open_in_memory().unwrap();
+        let ids: Vec<String> = ["a", "b", "c"]
+            .iter()
+            .map(|m| db.enqueue_job(&download(m)).unwrap().id)
+            .collect();
+
+        let moved = db.move_job(&ids[2], 0).unwrap();
+        let order: Vec<&str> = moved.iter().map(|job| job.id.as_str()).collect();
+        assert_eq!(order, [&ids[2], &ids[0], &ids[1]]);
+
+        db.start_job(&ids[2]).unwrap();
+        let interrupted = db.interrupt_running_jobs().unwrap();
+        assert_eq!(interrupted.len(), 1);
+        assert_eq!(interrupted[0].status, JobStatus::Interrupted);
+        // Interrupted jobs wait for the user
+        assert_eq!(db.next_queued_job().unwrap().unwrap().id, ids[0]);
+        assert_eq!(db.resume_job(&ids[2]).unwrap().status, JobStatus::Queued);
+        assert_eq!(db.next_queued_job().unwrap().unwrap().id, ids[2]);
+
+        db.start_job(&ids[0]).unwrap();
+        db.finish_job(&ids[0], Err("failed".to_string())).unwrap();
+        assert_eq!(db.clear_finished_jobs().unwrap(), 1);
+        assert!(db.queued_job(&ids[0]).is_err());
+        assert!(db.job_events(&ids[0]).unwrap().is_empty());
+        assert_eq!(db.job_queue().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_claim_next_job() {
+        let db = Database::open_in_memory().unwrap();
+        assert!(db.claim_next_job().unwrap().is_none());
+        let a = db.enqueue_job(&download("a")).unwrap();
+        let b = db.enqueue_job(&download("b")).unwrap();
+        let c = db.enqueue_job(&download("c")).unwrap();
+        db.pause_job(&b.id).unwrap();
+
+        let claimed = db.claim_next_job().unwrap().unwrap();
+        assert_eq!((claimed.id, claimed.status), (a.id, JobStatus::Running));
+        // Running and paused jobs are not claimed again
+        assert_eq!(db.claim_next_job().unwrap().unwrap().id, c.id);
+        assert!(db.claim_next_job().unwrap().is_none());
+
+        db.resume_job(&b.id).unwrap();
+        assert_eq!(db.claim_next_job().unwrap().unwrap().id, b.id);
+    }
+
+    #[test]
+    fn test_queue_paused() {
+        let db = Database::open_in_memory().unwrap();
+        assert!(!db.queue_paused().unwrap());
+        db.set_queue_paused(true).unwrap();
+        assert!(db.queue_paused().unwrap());
+        db.set_queue_paused(false).unwrap();
+        assert!(!db.queue_paused().unwrap());
+    }
+}
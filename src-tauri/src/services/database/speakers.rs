@@ -0,0 +1,394 @@
+use super::Database;
+use crate::error::{AppError, Result};
+use crate::services::whisper::TranscriptionSegment;
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+
+/// A speaker label of a transcription, e.g. "Host" or "Guest"
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Speaker {
+    pub id: i64,
+    pub transcription_id: i64,
+    pub name: String,
+    /// CSS color used for the speaker's segments
+    pub color: Option<String>,
+    pub segment_count: usize,
+}
+
+fn check_name(name: &str) -> Result<&str> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(AppError::InvalidInput(
+            "Speaker name must not be empty".to_string(),
+        ));
+    }
+    Ok(name)
+}
+
+/// Whether a segment label names a speaker, compared like the NOCASE collation
+/// of `transcript_speakers.name`
+fn same_name(label: Option<&str>, name: &str) -> bool {
+    label.is_some_and(|label| label.eq_ignore_ascii_case(name))
+}
+
+impl Database {
+    /// Speakers of a transcription by name
+    pub fn speakers(&self, transcription_id: i64) -> Result<Vec<Speaker>> {
+        let segments = self
+            .existing_transcription(transcription_id)?
+            .result
+            .segments;
+        let mut stmt = self.conn.prepare(
+            "SELECT id, name, color FROM transcript_speakers
+             WHERE transcription_id = ?1 ORDER BY name",
+        )?;
+        let speakers = stmt
+            .query_map([transcription_id], |row| {
+                Ok(Speaker {
+                    id: row.get(0)?,
+                    transcription_id,
+                    name: row.get(1)?,
+                    color: row.get(2)?,
+                    segment_count: 0,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(speakers
+            .into_iter()
+            .map(|speaker| Speaker {
+                segment_count: segments
+                    .iter()
+                    .filter(|segment| same_name(segment.speaker.as_deref(), &speaker.name))
+                    .count(),
+                ..speaker
+            })
+            .collect())
+    }
+
+    /// Add the labels on `segments`, e.g. from diarization, as speakers of a
+    /// transcription
+    pub(super) fn add_segment_speakers(
+        &self,
+        transcription_id: i64,
+        segments: &[TranscriptionSegment],
+    ) -> Result<()> {
+        for name in segments
+            .iter()
+            .filter_map(|segment| segment.speaker.as_deref())
+        {
+            self.conn.execute(
+                "INSERT OR IGNORE INTO transcript_speakers (transcription_id, name)
+                 VALUES (?1, ?2)",
+                params![transcription_id, name],
+            )?;
+        }
+        Ok(())
+    }
+
+    fn speaker(&self, id: i64) -> Result<Speaker> {
+        let transcription_id: i64 = self
+            .conn
+            .query_row(
+                "SELECT transcription_id FROM transcript_speakers WHERE id = ?1",
+                [id],
+                |row| row.get(0),
+            )
+            .map_err(|_| AppError::InvalidInput(format!("Speaker not found: {}", id)))?;
+        self.speakers(transcription_id)?
+            .into_iter()
+            .find(|speaker| speaker.id == id)
+            .ok_or_else(|| AppError::InvalidInput(format!("Speaker not found: {}", id)))
+    }
+
+    /// Define a speaker for a transcription, or return the existing one with the
+    /// same name (case-insensitive)
+    pub fn add_speaker(
+        &self,
+        transcription_id: i64,
+        name: &str,
+        color: Option<&str>,
+    ) -> Result<Speaker> {
+        let name = check_name(name)?;
+        let tx = self.conn.unchecked_transaction()?;
+        self.existing_transcription(transcription_id)?;
+        self.conn.execute(
+            "INSERT INTO transcript_speakers (transcription_id, name, color) VALUES (?1, ?2, ?3)
+             ON CONFLICT(transcription_id, name) DO NOTHING",
+            params![transcription_id, name, color],
+        )?;
+        let id = self.conn.query_row(
+            "SELECT id FROM transcript_speakers WHERE transcription_id = ?1 AND name = ?2",
+            params![transcription_id, name],
+            |row| row.get(0),
+        )?;
+        let speaker = self.speaker(id)?;
+        tx.commit()?;
+        Ok(speaker)
+    }
+
+    /// Set the speaker of segments to speaker `speaker_id`, or clear it with
+    /// `None`. Each run of adjacent changed segments becomes one "speaker" edit in
+    /// the transcription's history.
+    pub fn assign_speaker(
+        &self,
+        transcription_id: i64,
+        segment_indices: &[usize],
+        speaker_id: Option<i64>,
+        author: Option<&str>,
+    ) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        let name = match speaker_id {
+            Some(id) => {
+                let speaker = self.speaker(id)?;
+                if speaker.transcription_id != transcription_id {
+                    return Err(AppError::InvalidInput(format!(
+                        "Speaker {} belongs to another transcription",
+                        id
+                    )));
+                }
+                Some(speaker.name)
+            }
+            None => None,
+        };
+        self.relabel_segments(transcription_id, segment_indices, name.as_deref(), author)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Relabel segments within the caller's transaction
+    fn relabel_segments(
+        &self,
+        transcription_id: i64,
+        segment_indices: &[usize],
+        name: Option<&str>,
+        author: Option<&str>,
+    ) -> Result<()> {
+        let segments = self
+            .existing_transcription(transcription_id)?
+            .result
+            .segments;
+        if let Some(index) = segment_indices.iter().find(|i| **i >= segments.len()) {
+            return Err(AppError::InvalidInput(format!(
+                "Segment {} is out of range",
+                index
+            )));
+        }
+
+        let mut changed: Vec<usize> = segment_indices
+            .iter()
+            .copied()
+            .filter(|i| segments[*i].speaker.as_deref() != name)
+            .collect();
+        changed.sort_unstable();
+        changed.dedup();
+
+        // Runs of adjacent indices, as (first index, length)
+        let mut runs: Vec<(usize, usize)> = Vec::new();
+        for index in changed {
+            match runs.last_mut() {
+                Some((start, len)) if *start + *len == index => *len += 1,
+                _ => runs.push((index, 1)),
+            }
+        }
+        for (start, len) in runs {
+            let relabeled = segments[start..start + len]
+                .iter()
+                .map(|segment| {
+                    let mut segment = segment.clone();
+                    segment.speaker = name.map(str::to_string);
+                    segment
+                })
+                .collect();
+            let replaced = &segments[start..start + len];
+            self.splice(transcription_id, "speaker", start, replaced, relabeled, author)?;
+        }
+        Ok(())
+    }
+
+    /// Rename or recolor a speaker. Renaming relabels its segments.
+    pub fn update_speaker(
+        &self,
+        id: i64,
+        name: &str,
+        color: Option<&str>,
+        author: Option<&str>,
+    ) -> Result<Speaker> {
+        let name = check_name(name)?;
+        let tx = self.conn.unchecked_transaction()?;
+        let speaker = self.speaker(id)?;
+        if self
+            .speakers(speaker.transcription_id)?
+            .iter()
+            .any(|other| other.id != id && other.name.eq_ignore_ascii_case(name))
+        {
+            return Err(AppError::InvalidInput(format!(
+                "Speaker {} already exists",
+                name
+            )));
+        }
+
+        let indices = self.speaker_segments(&speaker)?;
+        self.relabel_segments(speaker.transcription_id, &indices, Some(name), author)?;
+        self.conn.execute(
+            "UPDATE transcript_speakers SET name = ?1, color = ?2 WHERE id = ?3",
+            params![name, color, id],
+        )?;
+        let speaker = self.speaker(id)?;
+        tx.commit()?;
+        Ok(speaker)
+    }
+
+    /// Delete a speaker, clearing the label of its segments
+    pub fn delete_speaker(&self, id: i64, author: Option<&str>) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        let speaker = self.speaker(id)?;
+        let indices = self.speaker_segments(&speaker)?;
+        self.relabel_segments(speaker.transcription_id, &indices, None, author)?;
+        self.conn
+            .execute("DELETE FROM transcript_speakers WHERE id = ?1", [id])?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn speaker_segments(&self, speaker: &Speaker) -> Result<Vec<usize>> {
+        let segments = self
+            .existing_transcription(speaker.transcription_id)?
+            .result
+            .segments;
+        Ok(segments
+            .iter()
+            .enumerate()
+            .filter(|(_, segment)| same_name(segment.speaker.as_deref(), &speaker.name))
+            .map(|(index, _)| index)
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::whisper::TranscriptionResult;
+
+    fn saved(db: &Database) -> i64 {
+        let segments = (0..4)
+            .map(|i| {
+                let segment = TranscriptionSegment::new(i as f64, i as f64 + 1.0, &format!("line {}", i));
+                if i == 3 {
+                    segment.with_speaker("SPEAKER_00")
+                } else {
+                    segment
+                }
+            })
+            .collect();
+        let result = TranscriptionResult {
+            full_text: String::new(),
+            segments,
+            language: None,
+            duration: 4.0,
+        };
+        db.save_transcription("/m/a.mp4", &result, None).unwrap()
+    }
+
+    fn labels(db: &Database, id: i64) -> Vec<Option<String>> {
+        let stored = db.existing_transcription(id).unwrap();
+        stored
+            .result
+            .segments
+            .into_iter()
+            .map(|s| s.speaker)
+            .collect()
+    }
+
+    #[test]
+    fn test_assign_speakers() {
+        let db = Database::open_in_memory().unwrap();
+        let id = saved(&db);
+
+        // Diarization labels show up as speakers
+        let speakers = db.speakers(id).unwrap();
+        assert_eq!(speakers.len(), 1);
+        assert_eq!(speakers[0].segment_count, 1);
+
+        let host = db.add_speaker(id, " Host ", Some("#f00")).unwrap();
+        assert_eq!(host.name, "Host");
+        assert_eq!(db.add_speaker(id, "host", None).unwrap().id, host.id);
+        assert!(db.add_speaker(id, "  ", None).is_err());
+
+        db.assign_speaker(id, &[2, 0, 1], Some(host.id), Some("editor"))
+            .unwrap();
+        let host_label = Some("Host".to_string());
+        assert_eq!(
+            labels(&db, id)[..3],
+            [host_label.clone(), host_label.clone(), host_label]
+        );
+        // One history entry for the run of adjacent segments
+        let history = db.transcript_edits(id).unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(
+            (history[0].kind.as_str(), history[0].after.len()),
+            ("speaker", 3)
+        );
+
+        db.assign_speaker(id, &[1], None, None).unwrap();
+        assert_eq!(labels(&db, id)[1], None);
+        assert!(db.assign_speaker(id, &[9], Some(host.id), None).is_err());
+
+        db.undo_transcript_edit(id).unwrap();
+        assert_eq!(labels(&db, id)[1].as_deref(), Some("Host"));
+    }
+
+    #[test]
+    fn test_rename_and_delete_speaker() {
+        let db = Database::open_in_memory().unwrap();
+        let id = saved(&db);
+        let guest = db.speakers(id).unwrap().remove(0);
+        let host = db.add_speaker(id, "Host", None).unwrap();
+
+        assert!(db.update_speaker(guest.id, "HOST", None, None).is_err());
+        let guest = db
+            .update_speaker(guest.id, "Guest", Some("#00f"), None)
+            .unwrap();
+        assert_eq!((guest.name.as_str(), guest.segment_count), ("Guest", 1));
+        assert_eq!(labels(&db, id)[3].as_deref(), Some("Guest"));
+
+        db.delete_speaker(guest.id, None).unwrap();
+        assert_eq!(labels(&db, id)[3], None);
+        let remaining = db.speakers(id).unwrap();
+        assert_eq!(remaining, [host]);
+    }
+
+    #[test]
+    fn test_speaker_matches_labels_in_any_case() {
+        let db = Database::open_in_memory().unwrap();
+        let id = saved(&db);
+        let original = db.existing_transcription(id).unwrap().result.segments[0].clone();
+        let mut segment = original.clone();
+        segment.speaker = Some("speaker_00".to_string());
+        db.splice_segments(id, "speaker", 0, &[original], vec![segment], None)
+            .unwrap();
+
+        let speaker = db.speakers(id).unwrap().remove(0);
+        assert_eq!(speaker.segment_count, 2);
+        db.update_speaker(speaker.id, "Guest", None, None).unwrap();
+        let labels = labels(&db, id);
+        assert_eq!(labels[0].as_deref(), Some("Guest"));
+        assert_eq!(labels[3].as_deref(), Some("Guest"));
+    }
+
+    #[test]
+    fn test_undoing_a_delete_restores_the_speaker() {
+        let db = Database::open_in_memory().unwrap();
+        let id = saved(&db);
+        let speaker = db.speakers(id).unwrap().remove(0);
+        db.delete_speaker(speaker.id, None).unwrap();
+        assert!(db.speakers(id).unwrap().is_empty());
+
+        db.undo_transcript_edit(id).unwrap();
+        let speakers = db.speakers(id).unwrap();
+        assert_eq!(speakers.len(), 1);
+        assert_eq!(
+            (speakers[0].name.as_str(), speakers[0].segment_count),
+            ("SPEAKER_00", 1)
+        );
+    }
+}
@@ -0,0 +1,344 @@
+use super::{now, Database};
+use crate::error::{AppError, Result};
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// A label attached to media files, e.g. a client or a status like "needs review"
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Tag {
+    pub id: i64,
+    pub name: String,
+    /// CSS color used for the tag chip
+    pub color: Option<String>,
+    pub file_count: usize,
+}
+
+/// A named group of media files, e.g. a project
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Collection {
+    pub id: i64,
+    pub name: String,
+    pub description: Option<String>,
+    pub file_count: usize,
+    pub created_at: u64,
+}
+
+fn check_name(name: &str) -> Result<&str> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(AppError::InvalidInput("Name must not be empty".to_string()));
+    }
+    Ok(name)
+}
+
+impl Database {
+    /// Create a tag, or return the existing tag with the same name (case-insensitive)
+    pub fn create_tag(&self, name: &str, color: Option<&str>) -> Result<Tag> {
+        let name = check_name(name)?;
+        self.conn.execute(
+            "INSERT INTO tags (name, color, created_at) VALUES (?1, ?2, ?3)
+             ON CONFLICT(name) DO NOTHING",
+            params![name, color, now()],
+        )?;
+        let id = self.conn.query_row("SELECT id FROM tags WHERE name = ?1", [name], |row| {
+            row.get(0)
+        })?;
+        self.tag(id)
+    }
+
+    /// Rename or recolor a tag
+    pub fn update_tag(&self, id: i64, name: &str, color: Option<&str>) -> Result<Tag> {
+        let name = check_name(name)?;
+        self.conn.execute(
+            "UPDATE tags SET name = ?1, color = ?2 WHERE id = ?3",
+            params![name, color, id],
+        )?;
+        self.tag(id)
+    }
+
+    /// Delete a tag and remove it from all files
+    pub fn delete_tag(&self, id: i64) -> Result<()> {
+        self.conn.execute("DELETE FROM tags WHERE id = ?1", [id])?;
+        Ok(())
+    }
+
+    fn tag(&self, id: i64) -> Result<Tag> {
+        self.query_tags("WHERE t.id = ?1", [id])?
+            .pop()
+            .ok_or_else(|| AppError::InvalidInput(format!("Tag not found: {}", id)))
+    }
+
+    /// List all tags with the number of files carrying each, by name
+    pub fn list_tags(&self) -> Result<Vec<Tag>> {
+        self.query_tags("", [])
+    }
+
+    /// Tags attached to a media file
+    pub fn file_tags(&self, media_path: &str) -> Result<Vec<Tag>> {
+        self.query_tags(
+            "WHERE t.id IN (SELECT mt.tag_id FROM media_tags mt
+                            JOIN media_files m ON m.id = mt.media_id WHERE m.path = ?1)",
+            [media_path],
+        )
+    }
+
+    fn query_tags(&self, condition: &str, params: impl rusqlite::Params) -> Result<Vec<Tag>> {
+        let sql = format!(
+            "SELECT t.id, t.name, t.color,
+                    (SELECT COUNT(*) FROM media_tags mt WHERE mt.tag_id = t.id)
+             FROM tags t {} ORDER BY t.name COLLATE NOCASE",
+            condition
+        );
+        let mut stmt = self.conn.prepare(&sql)?;
+        let tags = stmt
+            .query_map(params, |row| {
+                Ok(Tag {
+                    id: row.get(0)?,
+                    name: row.get(1)?,
+                    color: row.get(2)?,
+                    file_count: row.get(3)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(tags)
+    }
+
+    /// Attach a tag to a media file
+    pub fn tag_file(&self, media_path: &str, tag_id: i64) -> Result<()> {
+        let media_id = self.media_file_id(media_path)?;
+        self.conn.execute(
+            "INSERT OR IGNORE INTO media_tags (media_id, tag_id) VALUES (?1, ?2)",
+            params![media_id, tag_id],
+        )?;
+        Ok(())
+    }
+
+    /// Remove a tag from a media file
+    pub fn untag_file(&self, media_path: &str, tag_id: i64) -> Result<()> {
+        self.conn.execute(
+            "DELETE FROM media_tags
+             WHERE tag_id = ?1 AND media_id = (SELECT id FROM media_files WHERE path = ?2)",
+            params![tag_id, media_path],
+        )?;
+        Ok(())
+    }
+
+    /// Create a collection
+    pub fn create_collection(&self, name: &str, description: Option<&str>) -> Result<Collection> {
+        let name = check_name(name)?;
+        self.conn.execute(
+            "INSERT INTO collections (name, description, created_at) VALUES (?1, ?2, ?3)",
+            params![name, description, now()],
+        )?;
+        self.collection(self.conn.last_insert_rowid())
+    }
+
+    /// Rename a collection or change its description
+    pub fn update_collection(
+        &self,
+        id: i64,
+        name: &str,
+        description: Option<&str>,
+    ) -> Result<Collection> {
+        let name = check_name(name)?;
+        self.conn.execute(
+            "UPDATE collections SET name = ?1, description = ?2 WHERE id = ?3",
+            params![name, description, id],
+        )?;
+        self.collection(id)
+    }
+
+    /// Delete a collection (its files are kept)
+    pub fn delete_collection(&self, id: i64) -> Result<()> {
+        self.conn.execute("DELETE FROM collections WHERE id = ?1", [id])?;
+        Ok(())
+    }
+
+    fn collection(&self, id: i64) -> Result<Collection> {
+        self.query_collections("WHERE c.id = ?1", [id])?
+            .pop()
+            .ok_or_else(|| AppError::InvalidInput(format!("Collection not found: {}", id)))
+    }
+
+    /// List all collections by name
+    pub fn list_collections(&self) -> Result<Vec<Collection>> {
+        self.query_collections("", [])
+    }
+
+    fn query_collections(
+        &self,
+        condition: &str,
+        params: impl rusqlite::Params,
+    ) -> Result<Vec<Collection>> {
+        let sql = format!(
+            "SELECT c.id, c.name, c.description, c.created_at,
+                    (SELECT COUNT(*) FROM collection_items ci WHERE ci.collection_id = c.id)
+             FROM collections c {} ORDER BY c.name COLLATE NOCASE",
+            condition
+        );
+        let mut stmt = self.conn.prepare(&sql)?;
+        let collections = stmt
+            .query_map(params, |row| {
+                Ok(Collection {
+                    id: row.get(0)?,
+                    name: row.get(1)?,
+                    description: row.get(2)?,
+                    created_at: row.get(3)?,
+                    file_count: row.get(4)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(collections)
+    }
+
+    /// Add a media file to a collection
+    pub fn add_to_collection(&self, collection_id: i64, media_path: &str) -> Result<()> {
+        let media_id = self.media_file_id(media_path)?;
+        self.conn.execute(
+            "INSERT OR IGNORE INTO collection_items (collection_id, media_id, added_at)
+             VALUES (?1, ?2, ?3)",
+            params![collection_id, media_id, now()],
+        )?;
+        Ok(())
+    }
+
+    /// Remove a media file from a collection
+    pub fn remove_from_collection(&self, collection_id: i64, media_path: &str) -> Result<()> {
+        self.conn.execute(
+            "DELETE FROM collection_items
+             WHERE collection_id = ?1 AND media_id = (SELECT id FROM media_files WHERE path = ?2)",
+            params![collection_id, media_path],
+        )?;
+        Ok(())
+    }
+
+    /// Paths of the files in a collection, in the order they were added
+    pub fn collection_files(&self, collection_id: i64) -> Result<Vec<String>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT m.path FROM collection_items ci JOIN media_files m ON m.id = ci.media_id
+             WHERE ci.collection_id = ?1 ORDER BY ci.added_at, m.path",
+        )?;
+        let paths = stmt
+            .query_map([collection_id], |row| row.get(0))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(paths)
+    }
+
+    /// Paths of the files carrying every tag in `tags` (by name, case-insensitive)
+    /// and, if given, belonging to the collection
+    pub fn matching_paths(
+        &self,
+        tags: &[String],
+        collection_id: Option<i64>,
+    ) -> Result<HashSet<String>> {
+        let mut matching: Option<HashSet<String>> = match collection_id {
+            Some(id) => Some(self.collection_files(id)?.into_iter().collect()),
+            None => None,
+        };
+
+        let mut stmt = self.conn.prepare(
+            "SELECT m.path FROM media_tags mt
+             JOIN media_files m ON m.id = mt.media_id
+             JOIN tags t ON t.id = mt.tag_id
+             WHERE t.name = ?1",
+        )?;
+        for tag in tags {
+            let tagged = stmt
+                .query_map([tag.trim()], |row| row.get::<_, String>(0))?
+                .collect::<rusqlite::Result<HashSet<_>>>()?;
+            matching = Some(match matching {
+                Some(paths) => paths.intersection(&tagged).cloned().collect(),
+                None => tagged,
+            });
+        }
+
+        Ok(matching.unwrap_or_default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tag_crud() {
+        let db = Database::open_in_memory().unwrap();
+
+        let review = db.create_tag("Needs review", Some("#f59e0b")).unwrap();
+        // Same name (any case) returns the existing tag
+        assert_eq!(db.create_tag("needs REVIEW", None).unwrap().id, review.id);
+        assert!(db.create_tag("  ", None).is_err());
+
+        db.tag_file("/m/a.mp4", review.id).unwrap();
+        db.tag_file("/m/a.mp4", review.id).unwrap();
+        assert_eq!(db.list_tags().unwrap()[0].file_count, 1);
+        assert_eq!(db.file_tags("/m/a.mp4").unwrap()[0].name, "Needs review");
+
+        let renamed = db.update_tag(review.id, "Reviewed", None).unwrap();
+        assert_eq!(renamed.name, "Reviewed");
+        assert_eq!(db.create_tag("reviewed", None).unwrap().id, review.id);
+
+        db.untag_file("/m/a.mp4", review.id).unwrap();
+        assert!(db.file_tags("/m/a.mp4").unwrap().is_empty());
+
+        db.delete_tag(review.id).unwrap();
+        assert!(db.list_tags().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_collection_crud() {
+        let db = Database::open_in_memory().unwrap();
+
+        let project = db.create_collection("Client A", Some("Spring campaign")).unwrap();
+        db.add_to_collection(project.id, "/m/a.mp4").unwrap();
+        db.add_to_collection(project.id, "/m/b.mp4").unwrap();
+        db.remove_from_collection(project.id, "/m/a.mp4").unwrap();
+
+        assert_eq!(db.collection_files(project.id).unwrap(), ["/m/b.mp4"]);
+        assert_eq!(db.list_collections().unwrap()[0].file_count, 1);
+
+        let updated = db.update_collection(project.id, "Client B", None).unwrap();
+        assert_eq!(updated.name, "Client B");
+        assert!(updated.description.is_none());
+
+        db.delete_collection(project.id).unwrap();
+        assert!(db.list_collections().unwrap().is_empty());
+        // Deleting a tag or collection never deletes media records
+        assert!(db.media_file_id("/m/b.mp4").is_ok());
+    }
+
+    #[test]
+    fn test_matching_paths() {
+        let db = Database::open_in_memory().unwrap();
+        let client = db.create_tag("client-a", None).unwrap();
+        let published = db.create_tag("published", None).unwrap();
+        let project = db.create_collection("Launch", None).unwrap();
+
+        db.tag_file("/m/a.mp4", client.id).unwrap();
+        db.tag_file("/m/a.mp4", published.id).unwrap();
+        db.tag_file("/m/b.mp4", client.id).unwrap();
+        db.add_to_collection(project.id, "/m/b.mp4").unwrap();
+
+        let tags = |names: &[&str]| names.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+        let paths = |set: HashSet<String>| {
+            let mut paths: Vec<_> = set.into_iter().collect();
+            paths.sort();
+            paths
+        };
+
+        assert_eq!(
+            paths(db.matching_paths(&tags(&["CLIENT-A"]), None).unwrap()),
+            ["/m/a.mp4", "/m/b.mp4"]
+        );
+        assert_eq!(
+            paths(db.matching_paths(&tags(&["client-a", "published"]), None).unwrap()),
+            ["/m/a.mp4"]
+        );
+        assert_eq!(
+            paths(db.matching_paths(&tags(&["client-a"]), Some(project.id)).unwrap()),
+            ["/m/b.mp4"]
+        );
+        assert!(db.matching_paths(&tags(&["unknown"]), None).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,501 @@
+use super::{now, Database, StoredTranscription};
+use crate::error::{AppError, Result};
+use crate::services::whisper::TranscriptionSegment;
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+
+/// A change to the segments of a saved transcription: the `before` segments,
+/// starting at `segment_index`, were replaced with the `after` segments
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscriptEdit {
+    pub id: i64,
+    pub transcription_id: i64,
+    /// What was done, e.g. "edit" for text and timing corrections
+    pub kind: String,
+    pub segment_index: usize,
+    pub before: Vec<TranscriptionSegment>,
+    pub after: Vec<TranscriptionSegment>,
+    /// Who made the change, as given by the UI
+    pub author: Option<String>,
+    /// Unix seconds
+    pub created_at: u64,
+    /// When the change was reverted
+    pub reverted_at: Option<u64>,
+}
+
+/// A correction of one segment; fields left unset keep their value
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SegmentChange {
+    pub index: usize,
+    #[serde(default)]
+    pub text: Option<String>,
+    #[serde(default)]
+    pub start: Option<f64>,
+    #[serde(default)]
+    pub end: Option<f64>,
+}
+
+/// Transcript text of segments, as stored alongside them
+fn full_text(segments: &[TranscriptionSegment]) -> String {
+    segments
+        .iter()
+        .map(|segment| segment.text.trim())
+        .filter(|text| !text.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn same_segment(a: &TranscriptionSegment, b: &TranscriptionSegment) -> bool {
+    a.start == b.start && a.end == b.end && a.text == b.text && a.speaker == b.speaker
+}
+
+impl Database {
+    /// Get a transcription by id, failing if there is none
+    pub fn existing_transcription(&self, id: i64) -> Result<StoredTranscription> {
+        self.transcription(id)?
+            .ok_or_else(|| AppError::InvalidInput(format!("Transcription not found: {}", id)))
+    }
+
+    fn write_segments(
+        &self,
+        transcription_id: i64,
+        segments: &[TranscriptionSegment],
+    ) -> Result<()> {
+        self.conn.execute(
+            "UPDATE transcriptions SET segments = ?1, full_text = ?2 WHERE id = ?3",
+            params![
+                serde_json::to_string(segments)?,
+                full_text(segments),
+                transcription_id
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Add a change to the edit history of a transcription
+    fn record_edit(
+        &self,
+        transcription_id: i64,
+        kind: &str,
+        index: usize,
+        before: Vec<TranscriptionSegment>,
+        after: Vec<TranscriptionSegment>,
+        author: Option<&str>,
+    ) -> Result<TranscriptEdit> {
+        let created_at = now();
+        self.conn.execute(
+            "INSERT INTO transcript_edits
+                (transcription_id, kind, segment_index, before, after, author, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                transcription_id,
+                kind,
+                index as i64,
+                serde_json::to_string(&before)?,
+                serde_json::to_string(&after)?,
+                author,
+                created_at
+            ],
+        )?;
+
+        Ok(TranscriptEdit {
+            id: self.conn.last_insert_rowid(),
+            transcription_id,
+            kind: kind.to_string(),
+            segment_index: index,
+            before,
+            after,
+            author: author.map(str::to_string),
+            created_at,
+            reverted_at: None,
+        })
+    }
+
+    /// Replace the segments at `index` of a transcription with `insert`,
+    /// recording the change in its edit history. `replaced` are the segments
+    /// as the caller read them; the splice fails when they changed since.
+    pub fn splice_segments(
+        &self,
+        transcription_id: i64,
+        kind: &str,
+        index: usize,
+        replaced: &[TranscriptionSegment],
+        insert: Vec<TranscriptionSegment>,
+        author: Option<&str>,
+    ) -> Result<TranscriptEdit> {
+        let tx = self.conn.unchecked_transaction()?;
+        let edit = self.splice(transcription_id, kind, index, replaced, insert, author)?;
+        tx.commit()?;
+        Ok(edit)
+    }
+
+    /// [`Database::splice_segments`] within the caller's transaction
+    pub(super) fn splice(
+        &self,
+        transcription_id: i64,
+        kind: &str,
+        index: usize,
+        replaced: &[TranscriptionSegment],
+        insert: Vec<TranscriptionSegment>,
+        author: Option<&str>,
+    ) -> Result<TranscriptEdit> {
+        let mut segments = self
+            .existing_transcription(transcription_id)?
+            .result
+            .segments;
+        let range = index..index + replaced.len();
+        if range.end > segments.len() {
+            return Err(AppError::InvalidInput(format!(
+                "Segment {} is out of range",
+                range.end.max(index + 1) - 1
+            )));
+        }
+        // E.g. edited while the segment was being transcribed again
+        if !segments[range.clone()]
+            .iter()
+            .zip(replaced)
+            .all(|(current, replaced)| same_segment(current, replaced))
+        {
+            return Err(AppError::InvalidInput(format!(
+                "Segment {} changed meanwhile, reload the transcript and try again",
+                index
+            )));
+        }
+        let before: Vec<_> = segments.splice(range, insert.clone()).collect();
+
+        self.write_segments(transcription_id, &segments)?;
+        self.record_edit(transcription_id, kind, index, before, insert, author)
+    }
+
+    /// Correct the text or timing of segments. Segments a change leaves as they
+    /// are get no history entry. Either all changes are applied or, when one of
+    /// them is invalid, none.
+    pub fn edit_segments(
+        &self,
+        transcription_id: i64,
+        changes: &[SegmentChange],
+        author: Option<&str>,
+    ) -> Result<Vec<TranscriptEdit>> {
+        if changes.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let tx = self.conn.unchecked_transaction()?;
+        let mut segments = self
+            .existing_transcription(transcription_id)?
+            .result
+            .segments;
+        let mut edits = Vec::new();
+        for change in changes {
+            let current = segments.get_mut(change.index).ok_or_else(|| {
+                AppError::InvalidInput(format!("Segment {} is out of range", change.index))
+            })?;
+            let mut edited = current.clone();
+            if let Some(text) = &change.text {
+                edited.text = text.trim().to_string();
+            }
+            edited.start = change.start.unwrap_or(edited.start);
+            edited.end = change.end.unwrap_or(edited.end);
+            if edited.start < 0.0 || edited.end < edited.start {
+                return Err(AppError::InvalidInput(format!(
+                    "Segment {} would end before it starts",
+                    change.index
+                )));
+            }
+            if same_segment(current, &edited) {
+                continue;
+            }
+            let before = std::mem::replace(current, edited.clone());
+            edits.push(self.record_edit(
+                transcription_id,
+                "edit",
+                change.index,
+                vec![before],
+                vec![edited],
+                author,
+            )?);
+        }
+
+        if !edits.is_empty() {
+            self.write_segments(transcription_id, &segments)?;
+        }
+        tx.commit()?;
+        Ok(edits)
+    }
+
+    /// Edit history of a transcription, newest first
+    pub fn transcript_edits(&self, transcription_id: i64) -> Result<Vec<TranscriptEdit>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, kind, segment_index, before, after, author, created_at, reverted_at
+             FROM transcript_edits WHERE transcription_id = ?1
+             ORDER BY id DESC",
+        )?;
+        let rows = stmt
+            .query_map([transcription_id], |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, i64>(2)?,
+                    row.get::<_, String>(3)?,
+                    row.get::<_, String>(4)?,
+                    row.get::<_, Option<String>>(5)?,
+                    row.get::<_, u64>(6)?,
+                    row.get::<_, Option<u64>>(7)?,
+                ))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        rows.into_iter()
+            .map(
+                |(id, kind, index, before, after, author, created_at, reverted_at)| {
+                    Ok(TranscriptEdit {
+                        id,
+                        transcription_id,
+                        kind,
+                        segment_index: index as usize,
+                        before: serde_json::from_str(&before)?,
+                        after: serde_json::from_str(&after)?,
+                        author,
+                        created_at,
+                        reverted_at,
+                    })
+                },
+            )
+            .collect()
+    }
+
+    /// Undo edit `edit_id` and every later edit of the transcription, newest
+    /// first. The reverted edits stay in the history.
+    pub fn revert_transcript_edits(
+        &self,
+        transcription_id: i64,
+        edit_id: i64,
+    ) -> Result<StoredTranscription> {
+        let tx = self.conn.unchecked_transaction()?;
+        let edits = self.transcript_edits(transcription_id)?;
+        if !edits
+            .iter()
+            .any(|edit| edit.id == edit_id && edit.reverted_at.is_none())
+        {
+            return Err(AppError::InvalidInput(format!(
+                "No edit {} to revert in transcription {}",
+                edit_id, transcription_id
+            )));
+        }
+
+        let mut segments = self
+            .existing_transcription(transcription_id)?
+            .result
+            .segments;
+        let undone: Vec<_> = edits
+            .iter()
+            .filter(|edit| edit.id >= edit_id && edit.reverted_at.is_none())
+            .collect();
+        for edit in &undone {
+            let range = edit.segment_index..edit.segment_index + edit.after.len();
+            let matches = segments.get(range.clone()).is_some_and(|current| {
+                current
+                    .iter()
+                    .zip(&edit.after)
+                    .all(|(current, after)| same_segment(current, after))
+            });
+            if !matches {
+                return Err(AppError::InvalidInput(format!(
+                    "Edit {} no longer matches the transcript",
+                    edit.id
+                )));
+            }
+            segments.splice(range, edit.before.iter().cloned());
+        }
+
+        self.write_segments(transcription_id, &segments)?;
+        // Reverting can bring back the label of a deleted speaker
+        self.add_segment_speakers(transcription_id, &segments)?;
+        let reverted_at = now();
+        for edit in &undone {
+            tx.execute(
+                "UPDATE transcript_edits SET reverted_at = ?1 WHERE id = ?2",
+                params![reverted_at, edit.id],
+            )?;
+        }
+        tx.commit()?;
+        self.existing_transcription(transcription_id)
+    }
+
+    /// Undo the latest edit of a transcription that is not reverted yet
+    pub fn undo_transcript_edit(&self, transcription_id: i64) -> Result<StoredTranscription> {
+        let latest = self
+            .transcript_edits(transcription_id)?
+            .into_iter()
+            .find(|edit| edit.reverted_at.is_none())
+            .ok_or_else(|| AppError::InvalidInput("Nothing to undo".to_string()))?;
+        self.revert_transcript_edits(transcription_id, latest.id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::whisper::TranscriptionResult;
+
+    fn saved(db: &Database) -> i64 {
+        let segments = vec![
+            TranscriptionSegment::new(0.0, 1.0, "helo"),
+            TranscriptionSegment::new(1.0, 2.0, "wrld"),
+            TranscriptionSegment::new(2.0, 3.0, "again"),
+        ];
+        let result = TranscriptionResult {
+            full_text: full_text(&segments),
+            segments,
+            language: Some("en".to_string()),
+            duration: 3.0,
+        };
+        db.save_transcription("/m/a.mp4", &result, Some("base"))
+            .unwrap()
+    }
+
+    fn texts(db: &Database, id: i64) -> Vec<String> {
+        let stored = db.transcription(id).unwrap().unwrap();
+        stored
+            .result
+            .segments
+            .into_iter()
+            .map(|segment| segment.text)
+            .collect()
+    }
+
+    #[test]
+    fn test_edit_history_and_undo() {
+        let db = Database::open_in_memory().unwrap();
+        let id = saved(&db);
+
+        let changes = [
+            SegmentChange {
+                index: 0,
+                text: Some("hello".to_string()),
+                ..Default::default()
+            },
+            // Unchanged: no history entry
+            SegmentChange {
+                index: 2,
+                text: Some("again".to_string()),
+                ..Default::default()
+            },
+        ];
+        let edits = db.edit_segments(id, &changes, Some("editor")).unwrap();
+        assert_eq!(edits.len(), 1);
+        assert_eq!(edits[0].before[0].text, "helo");
+
+        let fix = SegmentChange {
+            index: 1,
+            text: Some("world".to_string()),
+            end: Some(2.5),
+            ..Default::default()
+        };
+        db.edit_segments(id, std::slice::from_ref(&fix), None).unwrap();
+        let stored = db.transcription(id).unwrap().unwrap();
+        assert_eq!(stored.result.full_text, "hello world again");
+        assert_eq!(stored.result.segments[1].end, 2.5);
+
+        let history = db.transcript_edits(id).unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[1].author.as_deref(), Some("editor"));
+
+        db.undo_transcript_edit(id).unwrap();
+        assert_eq!(texts(&db, id), ["hello", "wrld", "again"]);
+
+        // Reverting the first edit also undoes everything after it
+        db.edit_segments(id, &[fix], None).unwrap();
+        let restored = db.revert_transcript_edits(id, edits[0].id).unwrap();
+        assert_eq!(restored.result.full_text, "helo wrld again");
+        assert!(db
+            .transcript_edits(id)
+            .unwrap()
+            .iter()
+            .all(|e| e.reverted_at.is_some()));
+        assert!(db.undo_transcript_edit(id).is_err());
+    }
+
+    #[test]
+    fn test_invalid_edits() {
+        let db = Database::open_in_memory().unwrap();
+        let id = saved(&db);
+        let out_of_range = SegmentChange {
+            index: 3,
+            text: Some("x".to_string()),
+            ..Default::default()
+        };
+        assert!(db.edit_segments(id, &[out_of_range], None).is_err());
+        let backwards = SegmentChange {
+            index: 0,
+            end: Some(-1.0),
+            ..Default::default()
+        };
+        assert!(db.edit_segments(id, &[backwards], None).is_err());
+        assert!(db.edit_segments(99, &[], None).unwrap().is_empty());
+
+        // A batch with one invalid change applies none of them
+        let batch = [
+            SegmentChange {
+                index: 0,
+                text: Some("hello".to_string()),
+                ..Default::default()
+            },
+            SegmentChange {
+                index: 5,
+                text: Some("x".to_string()),
+                ..Default::default()
+            },
+        ];
+        assert!(db.edit_segments(id, &batch, None).is_err());
+        assert_eq!(texts(&db, id), ["helo", "wrld", "again"]);
+        assert!(db.transcript_edits(id).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_revert_refuses_changed_segments() {
+        let db = Database::open_in_memory().unwrap();
+        let id = saved(&db);
+        let change = SegmentChange {
+            index: 0,
+            text: Some("hello".to_string()),
+            ..Default::default()
+        };
+        let edit = db.edit_segments(id, &[change], None).unwrap().remove(0);
+
+        // Changed behind the history's back, e.g. by a retranscription
+        let mut stored = db.transcription(id).unwrap().unwrap();
+        stored.result.segments[0].text = "howdy".to_string();
+        db.write_segments(id, &stored.result.segments).unwrap();
+
+        assert!(db.revert_transcript_edits(id, edit.id).is_err());
+        assert_eq!(texts(&db, id), ["howdy", "wrld", "again"]);
+    }
+
+    #[test]
+    fn test_splice_refuses_changed_segments() {
+        let db = Database::open_in_memory().unwrap();
+        let id = saved(&db);
+        let read = db.existing_transcription(id).unwrap().result.segments;
+        let merged = TranscriptionSegment::new(0.0, 2.0, "hello world");
+
+        // Edited between reading the segments and splicing them
+        let change = SegmentChange {
+            index: 1,
+            text: Some("world".to_string()),
+            ..Default::default()
+        };
+        db.edit_segments(id, &[change], None).unwrap();
+        let stale = db.splice_segments(id, "merge", 0, &read[..2], vec![merged.clone()], None);
+        assert!(stale.is_err());
+        assert_eq!(texts(&db, id), ["helo", "world", "again"]);
+
+        let read = db.existing_transcription(id).unwrap().result.segments;
+        db.splice_segments(id, "merge", 0, &read[..2], vec![merged], None)
+            .unwrap();
+        assert_eq!(texts(&db, id), ["hello world", "again"]);
+        assert!(db
+            .splice_segments(id, "merge", 1, &read[1..3], Vec::new(), None)
+            .is_err());
+    }
+}
@@ -0,0 +1,267 @@
+use super::{now, Database};
+use crate::error::Result;
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+
+/// A cloud API call to add to the usage ledger
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApiUsageRecord {
+    pub provider: String,
+    pub model: String,
+    pub operation: String,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub audio_seconds: f64,
+    /// Estimated cost in USD, if the model's price is known
+    pub cost: Option<f64>,
+    /// Media file the call was made for
+    pub media_path: Option<String>,
+}
+
+/// A recorded cloud API call
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ApiUsageEntry {
+    pub id: i64,
+    pub provider: String,
+    pub model: String,
+    pub operation: String,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub audio_seconds: f64,
+    pub cost: Option<f64>,
+    pub media_path: Option<String>,
+    /// Unix seconds
+    pub created_at: u64,
+}
+
+/// How usage reports group the calls
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UsageGrouping {
+    /// Calendar month (local time), e.g. "2025-03"
+    Month,
+    Provider,
+    Model,
+    /// Media file; calls made for no file are grouped under an empty key
+    File,
+    /// Collection (project) of the media file; calls for files in no
+    /// collection are left out
+    Collection,
+}
+
+/// Which calls a usage report covers
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct UsageFilter {
+    /// Unix seconds, inclusive
+    #[serde(default)]
+    pub from: Option<u64>,
+    /// Unix seconds, exclusive
+    #[serde(default)]
+    pub to: Option<u64>,
+    #[serde(default)]
+    pub provider: Option<String>,
+    #[serde(default)]
+    pub media_path: Option<String>,
+    /// Only files in this collection
+    #[serde(default)]
+    pub collection_id: Option<i64>,
+}
+
+/// Totals of one group in a usage report
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UsageReportRow {
+    pub key: String,
+    pub calls: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub audio_seconds: f64,
+    /// Estimated cost in USD of the calls with a known price
+    pub cost: f64,
+    /// Calls whose cost couldn't be estimated (unknown model)
+    pub unpriced_calls: u64,
+}
+
+/// Conditions of a `UsageFilter` on `api_usage u`, taking parameters ?1 to ?5
+const FILTER_CONDITIONS: &str = "(?1 IS NULL OR u.created_at >= ?1)
+      AND (?2 IS NULL OR u.created_at < ?2)
+      AND (?3 IS NULL OR u.provider = ?3)
+      AND (?4 IS NULL OR u.media_path = ?4)
+      AND (?5 IS NULL OR u.media_path IN (
+          SELECT m.path FROM media_files m
+          JOIN collection_items ci ON ci.media_id = m.id
+          WHERE ci.collection_id = ?5))";
+
+impl Database {
+    /// Add a cloud API call to the usage ledger
+    pub fn record_api_usage(&self, record: &ApiUsageRecord) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO api_usage (provider, model, operation, input_tokens, output_tokens,
+                                    audio_seconds, cost, media_path, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                record.provider,
+                record.model,
+                record.operation,
+                record.input_tokens,
+                record.output_tokens,
+                record.audio_seconds,
+                record.cost,
+                record.media_path,
+                now()
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Recorded calls matching a filter, newest first
+    pub fn list_api_usage(&self, filter: &UsageFilter, limit: usize) -> Result<Vec<ApiUsageEntry>> {
+        let sql = format!(
+            "SELECT u.id, u.provider, u.model, u.operation, u.input_tokens, u.output_tokens,
+                    u.audio_seconds, u.cost, u.media_path, u.created_at
+             FROM api_usage u
+             WHERE {}
+             ORDER BY u.created_at DESC, u.id DESC
+             LIMIT ?6",
+            FILTER_CONDITIONS
+        );
+        let mut stmt = self.conn.prepare(&sql)?;
+        let entries = stmt
+            .query_map(
+                params![
+                    filter.from,
+                    filter.to,
+                    filter.provider,
+                    filter.media_path,
+                    filter.collection_id,
+                    limit as i64
+                ],
+                |row| {
+                    Ok(ApiUsageEntry {
+                        id: row.get(0)?,
+                        provider: row.get(1)?,
+                        model: row.get(2)?,
+                        operation: row.get(3)?,
+                        input_tokens: row.get(4)?,
+                        output_tokens: row.get(5)?,
+                        audio_seconds: row.get(6)?,
+                        cost: row.get(7)?,
+                        media_path: row.get(8)?,
+                        created_at: row.get(9)?,
+                    })
+                },
+            )?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(entries)
+    }
+
+    /// Usage totals per group, most expensive first
+    pub fn usage_report(
+        &self,
+        grouping: UsageGrouping,
+        filter: &UsageFilter,
+    ) -> Result<Vec<UsageReportRow>> {
+        let (key, join) = match grouping {
+            UsageGrouping::Month => {
+                ("strftime('%Y-%m', u.created_at, 'unixepoch', 'localtime')", "")
+            }
+            UsageGrouping::Provider => ("u.provider", ""),
+            UsageGrouping::Model => ("u.provider || '/' || u.model", ""),
+            UsageGrouping::File => ("COALESCE(u.media_path, '')", ""),
+            UsageGrouping::Collection => (
+                "c.name",
+                "JOIN media_files m ON m.path = u.media_path
+                 JOIN collection_items ci ON ci.media_id = m.id
+                 JOIN collections c ON c.id = ci.collection_id",
+            ),
+        };
+        let sql = format!(
+            "SELECT {key}, COUNT(*), SUM(u.input_tokens), SUM(u.output_tokens),
+                    SUM(u.audio_seconds), COALESCE(SUM(u.cost), 0), COUNT(*) - COUNT(u.cost)
+             FROM api_usage u {join}
+             WHERE {conditions}
+             GROUP BY 1
+             ORDER BY 6 DESC, 1",
+            key = key,
+            join = join,
+            conditions = FILTER_CONDITIONS
+        );
+        let mut stmt = self.conn.prepare(&sql)?;
+        let rows = stmt
+            .query_map(
+                params![
+                    filter.from,
+                    filter.to,
+                    filter.provider,
+                    filter.media_path,
+                    filter.collection_id
+                ],
+                |row| {
+                    Ok(UsageReportRow {
+                        key: row.get(0)?,
+                        calls: row.get(1)?,
+                        input_tokens: row.get(2)?,
+                        output_tokens: row.get(3)?,
+                        audio_seconds: row.get(4)?,
+                        cost: row.get(5)?,
+                        unpriced_calls: row.get(6)?,
+                    })
+                },
+            )?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(rows)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(provider: &str, cost: Option<f64>, media_path: Option<&str>) -> ApiUsageRecord {
+        ApiUsageRecord {
+            provider: provider.to_string(),
+            model: "model".to_string(),
+            operation: "chat".to_string(),
+            input_tokens: 100,
+            output_tokens: 20,
+            audio_seconds: 0.0,
+            cost,
+            media_path: media_path.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_usage_report() {
+        let db = Database::open_in_memory().unwrap();
+        db.record_api_usage(&record("openai", Some(0.5), Some("/m/a.mp4"))).unwrap();
+        db.record_api_usage(&record("openai", None, Some("/m/b.mp4"))).unwrap();
+        db.record_api_usage(&record("claude", Some(1.0), None)).unwrap();
+
+        let by_provider = db
+            .usage_report(UsageGrouping::Provider, &UsageFilter::default())
+            .unwrap();
+        assert_eq!(by_provider[0].key, "claude");
+        assert_eq!(
+            (by_provider[1].calls, by_provider[1].input_tokens, by_provider[1].unpriced_calls),
+            (2, 200, 1)
+        );
+        assert_eq!(by_provider[1].cost, 0.5);
+
+        let collection = db.create_collection("Series", None).unwrap();
+        db.add_to_collection(collection.id, "/m/a.mp4").unwrap();
+        let by_collection = db
+            .usage_report(UsageGrouping::Collection, &UsageFilter::default())
+            .unwrap();
+        assert_eq!(by_collection.len(), 1);
+        assert_eq!((by_collection[0].key.as_str(), by_collection[0].calls), ("Series", 1));
+
+        let filter = UsageFilter {
+            collection_id: Some(collection.id),
+            ..Default::default()
+        };
+        assert_eq!(db.list_api_usage(&filter, 10).unwrap().len(), 1);
+        assert_eq!(db.list_api_usage(&UsageFilter::default(), 2).unwrap().len(), 2);
+        let months = db.usage_report(UsageGrouping::Month, &UsageFilter::default()).unwrap();
+        assert_eq!(months.len(), 1);
+        assert_eq!(months[0].calls, 3);
+    }
+}
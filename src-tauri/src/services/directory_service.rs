@@ -1,7 +1,11 @@
+use crate::error::{AppError, Result};
+use crate::services::scan_index::IndexedDir;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::path::Path;
-use std::time::SystemTime;
-use walkdir::WalkDir;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
 
 /// Represents a file entry in the directory
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -11,7 +15,16 @@ pub struct FileEntry {
     pub size: u64,
     pub is_dir: bool,
     pub modified: Option<u64>,
+    /// Creation time (Unix seconds), when the filesystem records it
+    #[serde(default)]
+    pub created: Option<u64>,
     pub extension: Option<String>,
+    /// Duration in seconds, only filled when the scan probes media info
+    #[serde(default)]
+    pub duration: Option<f64>,
+    /// Whether the file has an audio stream, only filled when the scan probes media info
+    #[serde(default)]
+    pub has_audio: Option<bool>,
 }
 
 /// Represents a directory tree node
@@ -33,6 +46,150 @@ pub enum FileEvent {
     Created(String),
     Modified(String),
     Removed(String),
+    Renamed { from: String, to: String },
+}
+
+impl FileEvent {
+    /// Path the event applies to (the new path for renames)
+    pub fn path(&self) -> &str {
+        match self {
+            FileEvent::Created(path) | FileEvent::Modified(path) | FileEvent::Removed(path) => path,
+            FileEvent::Renamed { to, .. } => to,
+        }
+    }
+
+    /// Build the event for a rename, taking media filtering into account:
+    /// renaming a file into a media extension looks like a creation, out of one like a removal
+    pub fn rename(from: &Path, to: &Path) -> Option<FileEvent> {
+        let is_dir = to.is_dir();
+        let from_listed = is_dir || is_supported_media(from);
+        let to_listed = is_dir || is_supported_media(to);
+        let from = from.to_string_lossy().to_string();
+        let to = to.to_string_lossy().to_string();
+
+        match (from_listed, to_listed) {
+            (true, true) => Some(FileEvent::Renamed { from, to }),
+            (false, true) => Some(FileEvent::Created(to)),
+            (true, false) => Some(FileEvent::Removed(from)),
+            (false, false) => None,
+        }
+    }
+}
+
+/// Whether a path lies inside one of the excluded directories
+pub fn is_excluded(path: &Path, exclusions: &[std::path::PathBuf]) -> bool {
+    exclusions.iter().any(|excluded| path.starts_with(excluded))
+}
+
+/// Drop (or narrow) a watcher event that touches excluded directories.
+/// A rename out of an excluded folder becomes a creation, into one a removal.
+pub fn filter_excluded(event: FileEvent, exclusions: &[std::path::PathBuf]) -> Option<FileEvent> {
+    if exclusions.is_empty() {
+        return Some(event);
+    }
+
+    match event {
+        FileEvent::Renamed { from, to } => {
+            match (
+                is_excluded(Path::new(&from), exclusions),
+                is_excluded(Path::new(&to), exclusions),
+            ) {
+                (false, false) => Some(FileEvent::Renamed { from, to }),
+                (true, false) => Some(FileEvent::Created(to)),
+                (false, true) => Some(FileEvent::Removed(from)),
+                (true, true) => None,
+            }
+        }
+        event => (!is_excluded(Path::new(event.path()), exclusions)).then_some(event),
+    }
+}
+
+/// How long the watcher must be quiet before a batch of events is emitted
+pub const WATCH_QUIET_PERIOD: Duration = Duration::from_millis(500);
+
+/// Upper bound on how long events are held back during continuous activity
+pub const WATCH_MAX_BATCH_DELAY: Duration = Duration::from_secs(5);
+
+/// How often the watched directory is checked for still being there; an
+/// unmounted volume or deleted folder often produces no watcher error at all
+pub const WATCH_HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Longest wait between attempts to watch a lost directory again
+pub const WATCH_RETRY_MAX_DELAY: Duration = Duration::from_secs(60);
+
+/// Wait before the `attempt`th (from 0) attempt to watch a lost directory
+/// again: 1s, doubling up to [`WATCH_RETRY_MAX_DELAY`]
+pub fn rewatch_delay(attempt: u32) -> Duration {
+    Duration::from_secs(1u64 << attempt.min(16)).min(WATCH_RETRY_MAX_DELAY)
+}
+
+/// Coalesces raw watcher events into at most one event per path.
+/// A file that is created and then written to repeatedly (a recording in
+/// progress) is reported once as `Created`; created-then-removed files are dropped.
+#[derive(Debug, Default)]
+pub struct FileEventBatcher {
+    order: Vec<String>,
+    events: HashMap<String, FileEvent>,
+}
+
+impl FileEventBatcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Merge an event with any pending event for the same path
+    pub fn push(&mut self, event: FileEvent) {
+        // A rename carries whatever is pending for the old path over to the new one
+        let event = match event {
+            FileEvent::Renamed { from, to } => match self.take(&from) {
+                Some(FileEvent::Created(_)) => FileEvent::Created(to),
+                Some(FileEvent::Renamed { from: origin, .. }) => FileEvent::Renamed { from: origin, to },
+                _ => FileEvent::Renamed { from, to },
+            },
+            event => event,
+        };
+
+        let merged = match (self.take(event.path()), event) {
+            (None, event) => Some(event),
+            (Some(FileEvent::Created(_)), FileEvent::Removed(_)) => None,
+            (Some(FileEvent::Created(p)), _) => Some(FileEvent::Created(p)),
+            (Some(FileEvent::Removed(p)), FileEvent::Created(_) | FileEvent::Modified(_)) => {
+                Some(FileEvent::Modified(p))
+            }
+            (Some(renamed @ FileEvent::Renamed { .. }), FileEvent::Modified(_)) => Some(renamed),
+            (Some(FileEvent::Renamed { from, .. }), FileEvent::Removed(_)) => {
+                Some(FileEvent::Removed(from))
+            }
+            (Some(_), event) => Some(event),
+        };
+
+        if let Some(event) = merged {
+            let path = event.path().to_string();
+            if !self.order.contains(&path) {
+                self.order.push(path.clone());
+            }
+            self.events.insert(path, event);
+        }
+    }
+
+    fn take(&mut self, path: &str) -> Option<FileEvent> {
+        let event = self.events.remove(path)?;
+        self.order.retain(|p| p != path);
+        Some(event)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Take the pending events in first-seen order
+    pub fn drain(&mut self) -> Vec<FileEvent> {
+        let mut events = std::mem::take(&mut self.events);
+        std::mem::take(&mut self.order)
+            .into_iter()
+            .filter_map(|path| events.remove(&path))
+            .collect()
+    }
 }
 
 /// Supported media extensions
@@ -49,73 +206,673 @@ pub fn is_supported_media(path: &Path) -> bool {
         .unwrap_or(false)
 }
 
-/// Scan a directory and return all media files
-pub fn scan_directory(root_path: &Path) -> Result<Vec<FileEntry>, String> {
+/// Minimum time between two progress reports of a scan
+pub const SCAN_PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Progress of a running scan
+#[derive(Debug, Clone, Serialize)]
+pub struct ScanProgress {
+    /// Media files found so far
+    pub files_found: usize,
+    /// Directory currently being read
+    pub current_dir: String,
+}
+
+/// Cancellation flag and throttled progress reporting for a running scan
+pub struct ScanMonitor<'a> {
+    cancelled: &'a AtomicBool,
+    on_progress: Box<dyn FnMut(ScanProgress) + 'a>,
+    files_found: usize,
+    last_report: Option<Instant>,
+}
+
+impl<'a> ScanMonitor<'a> {
+    pub fn new(cancelled: &'a AtomicBool, on_progress: impl FnMut(ScanProgress) + 'a) -> Self {
+        Self {
+            cancelled,
+            on_progress: Box::new(on_progress),
+            files_found: 0,
+            last_report: None,
+        }
+    }
+
+    /// Monitor for scans that report nothing and cannot be cancelled
+    pub fn silent() -> ScanMonitor<'static> {
+        static NEVER_CANCELLED: AtomicBool = AtomicBool::new(false);
+        ScanMonitor::new(&NEVER_CANCELLED, |_| {})
+    }
+
+    fn file_found(&mut self) {
+        self.files_found += 1;
+    }
+
+    fn check_cancelled(&self) -> Result<()> {
+        if self.cancelled.load(Ordering::Relaxed) {
+            return Err(AppError::Cancelled);
+        }
+        Ok(())
+    }
+
+    /// Called for every directory; reports progress at most every `SCAN_PROGRESS_INTERVAL`
+    fn enter_dir(&mut self, dir: &Path) -> Result<()> {
+        self.check_cancelled()?;
+
+        if self.last_report.is_none_or(|t| t.elapsed() >= SCAN_PROGRESS_INTERVAL) {
+            self.last_report = Some(Instant::now());
+            (self.on_progress)(ScanProgress {
+                files_found: self.files_found,
+                current_dir: dir.to_string_lossy().to_string(),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Scan a directory and return all media files.
+/// `max_depth` limits how many levels below the root are visited (1 = direct children only).
+pub fn scan_directory(root_path: &Path, max_depth: Option<usize>) -> Result<Vec<FileEntry>> {
+    scan_directory_monitored(root_path, max_depth, &mut ScanMonitor::silent())
+}
+
+/// Scan a directory like [`scan_directory`], reporting progress and stopping
+/// with [`AppError::Cancelled`] once the monitor's flag is set.
+/// Directories are read in parallel on the rayon pool; this blocks, so async
+/// callers should run it with `spawn_blocking`.
+pub fn scan_directory_monitored(
+    root_path: &Path,
+    max_depth: Option<usize>,
+    monitor: &mut ScanMonitor,
+) -> Result<Vec<FileEntry>> {
     if !root_path.exists() {
-        return Err(format!("Directory does not exist: {:?}", root_path));
+        return Err(AppError::InvalidPath(format!("Directory does not exist: {:?}", root_path)));
     }
 
-    let mut files = Vec::new();
+    let mut visited = dir_identity(root_path).into_iter().collect();
+    let max_depth = max_depth.unwrap_or(usize::MAX);
+    let (mut files, _) = walk_indexed(root_path, max_depth, &mut visited, monitor)?;
+    monitor.check_cancelled()?;
 
-    for entry in WalkDir::new(root_path)
-        .follow_links(true)
-        .into_iter()
-        .filter_map(|e| e.ok())
-    {
+    // Sort by path
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+
+    Ok(files)
+}
+
+/// Directories read by a [`walk_indexed`] walk
+struct WalkedDirs {
+    /// Directory identities already walked, see [`dir_identity`]
+    visited: HashSet<String>,
+    dirs: HashMap<String, IndexedDir>,
+}
+
+/// Collect the media files in `root` and, `max_depth` levels down, its
+/// subdirectories, reading directories in parallel on the rayon pool.
+/// Directories whose identity is in `visited` are skipped and those walked are
+/// added to it. Returns the files and the directories read, indexed for a rescan.
+fn walk_indexed(
+    root: &Path,
+    max_depth: usize,
+    visited: &mut HashSet<String>,
+    monitor: &mut ScanMonitor,
+) -> Result<(Vec<FileEntry>, HashMap<String, IndexedDir>)> {
+    let walked = Arc::new(Mutex::new(WalkedDirs {
+        visited: std::mem::take(visited),
+        dirs: HashMap::new(),
+    }));
+
+    let walk = {
+        let walked = Arc::clone(&walked);
+        jwalk::WalkDir::new(root)
+            .follow_links(true)
+            .skip_hidden(false)
+            .max_depth(max_depth)
+            .process_read_dir(move |depth, dir, _, children| {
+                // Called for the root entry itself before any directory is read
+                if depth.is_none() {
+                    return;
+                }
+                // Enter each directory once, however many links or mounts lead to it
+                {
+                    let mut walked = walked.lock().unwrap_or_else(|e| e.into_inner());
+                    children.retain(|child| match child {
+                        Ok(e) if e.file_type().is_dir() => {
+                            dir_identity(&e.path()).is_none_or(|id| walked.visited.insert(id))
+                        }
+                        _ => true,
+                    });
+                }
+
+                let Some(modified) = dir_modified(dir) else {
+                    return;
+                };
+                let mut indexed = IndexedDir {
+                    modified,
+                    subdirs: Vec::new(),
+                    files: Vec::new(),
+                };
+                for child in children.iter().flatten() {
+                    let name = child.file_name().to_string_lossy().to_string();
+                    if child.file_type().is_dir() {
+                        indexed.subdirs.push(name);
+                    } else if is_supported_media(&child.path()) {
+                        indexed.files.push(name);
+                    }
+                }
+                let key = dir.to_string_lossy().to_string();
+                let mut walked = walked.lock().unwrap_or_else(|e| e.into_inner());
+                walked.dirs.insert(key, indexed);
+            })
+    };
+
+    let mut files = Vec::new();
+    for entry in walk.into_iter().filter_map(|e| e.ok()) {
         let path = entry.path();
 
         // Skip directories
         if path.is_dir() {
+            monitor.enter_dir(&path)?;
             continue;
         }
 
         // Only include supported media files
-        if !is_supported_media(path) {
+        if !is_supported_media(&path) {
             continue;
         }
 
         if let Ok(metadata) = entry.metadata() {
-            let modified = metadata
-                .modified()
-                .ok()
-                .and_then(|t| t.duration_since(SystemTime::UNIX_EPOCH).ok())
-                .map(|d| d.as_secs());
+            files.push(media_file_entry(&path, &metadata));
+            monitor.file_found();
+        }
+    }
 
-            files.push(FileEntry {
-                path: path.to_string_lossy().to_string(),
-                name: path
-                    .file_name()
-                    .map(|n| n.to_string_lossy().to_string())
-                    .unwrap_or_default(),
-                size: metadata.len(),
-                is_dir: false,
-                modified,
-                extension: path
-                    .extension()
-                    .and_then(|e| e.to_str())
-                    .map(|s| s.to_lowercase()),
-            });
+    let mut walked = walked.lock().unwrap_or_else(|e| e.into_inner());
+    *visited = std::mem::take(&mut walked.visited);
+    Ok((files, std::mem::take(&mut walked.dirs)))
+}
+
+/// Modification time of a directory in nanoseconds since the Unix epoch
+fn dir_modified(dir: &Path) -> Option<u64> {
+    std::fs::metadata(dir)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|t| t.duration_since(SystemTime::UNIX_EPOCH).ok())
+        .map(|d| d.as_nanos() as u64)
+}
+
+/// Entry of a media file found by a scan
+fn media_file_entry(path: &Path, metadata: &std::fs::Metadata) -> FileEntry {
+    let modified = metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(SystemTime::UNIX_EPOCH).ok())
+        .map(|d| d.as_secs());
+    let created = metadata
+        .created()
+        .ok()
+        .and_then(|t| t.duration_since(SystemTime::UNIX_EPOCH).ok())
+        .map(|d| d.as_secs());
+
+    FileEntry {
+        path: path.to_string_lossy().to_string(),
+        name: path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        size: metadata.len(),
+        is_dir: false,
+        modified,
+        created,
+        extension: path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|s| s.to_lowercase()),
+        duration: None,
+        has_audio: None,
+    }
+}
+
+/// Scan a directory like [`scan_directory_monitored`], but only read the
+/// directories whose modification time changed since they were indexed in
+/// `known`. A directory's modification time changes when entries are added,
+/// removed or renamed in it, not when a file in it is edited, so the files of an
+/// unchanged directory are still checked for their size and modification time.
+/// Directories not in `known` at all, like every directory on a first scan,
+/// are walked in parallel as [`scan_directory_monitored`] does.
+/// Returns the media files and the directories read, to index for the next rescan.
+pub fn rescan_directory_monitored(
+    root_path: &Path,
+    max_depth: Option<usize>,
+    known: &HashMap<String, IndexedDir>,
+    monitor: &mut ScanMonitor,
+) -> Result<(Vec<FileEntry>, HashMap<String, IndexedDir>)> {
+    if !root_path.exists() {
+        return Err(AppError::InvalidPath(format!("Directory does not exist: {:?}", root_path)));
+    }
+
+    let mut rescan = Rescan {
+        known,
+        visited: HashSet::new(),
+        files: Vec::new(),
+        dirs: HashMap::new(),
+    };
+    rescan.read_dir(root_path, max_depth.unwrap_or(usize::MAX), monitor)?;
+    monitor.check_cancelled()?;
+
+    let mut files = rescan.files;
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok((files, rescan.dirs))
+}
+
+/// State of a [`rescan_directory_monitored`] walk
+struct Rescan<'a> {
+    known: &'a HashMap<String, IndexedDir>,
+    /// Directory identities already walked, see [`dir_identity`]
+    visited: HashSet<String>,
+    files: Vec<FileEntry>,
+    dirs: HashMap<String, IndexedDir>,
+}
+
+impl Rescan<'_> {
+    /// Collect the media files in `dir` and, `depth` levels down, its subdirectories
+    fn read_dir(&mut self, dir: &Path, depth: usize, monitor: &mut ScanMonitor) -> Result<()> {
+        if depth == 0 {
+            return Ok(());
+        }
+        monitor.enter_dir(dir)?;
+        if dir_identity(dir).is_some_and(|id| !self.visited.insert(id)) {
+            return Ok(());
+        }
+
+        let key = dir.to_string_lossy().to_string();
+        let Some(modified) = dir_modified(dir) else {
+            return Ok(());
+        };
+
+        let indexed = match self.known.get(&key) {
+            Some(indexed) if indexed.modified == modified => indexed.clone(),
+            Some(_) => {
+                let Ok(entries) = std::fs::read_dir(dir) else {
+                    return Ok(());
+                };
+                let mut indexed = IndexedDir {
+                    modified,
+                    subdirs: Vec::new(),
+                    files: Vec::new(),
+                };
+                for entry in entries.filter_map(|e| e.ok()) {
+                    let path = entry.path();
+                    let name = entry.file_name().to_string_lossy().to_string();
+                    if path.is_dir() {
+                        indexed.subdirs.push(name);
+                    } else if is_supported_media(&path) {
+                        indexed.files.push(name);
+                    }
+                }
+                indexed
+            }
+            None => {
+                let (files, dirs) = walk_indexed(dir, depth, &mut self.visited, monitor)?;
+                self.files.extend(files);
+                self.dirs.extend(dirs);
+                return Ok(());
+            }
+        };
+
+        for name in &indexed.files {
+            let path = dir.join(name);
+            if let Ok(metadata) = std::fs::metadata(&path) {
+                self.files.push(media_file_entry(&path, &metadata));
+                monitor.file_found();
+            }
+        }
+        for name in &indexed.subdirs {
+            self.read_dir(&dir.join(name), depth - 1, monitor)?;
         }
+        self.dirs.insert(key, indexed);
+        Ok(())
     }
+}
 
-    // Sort by path
+/// Sort key for flat scan results
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ScanSortKey {
+    #[default]
+    Path,
+    Name,
+    Modified,
+    Size,
+    /// Requires `with_media_info`; files without a duration sort first
+    Duration,
+}
+
+/// Sorting and filtering applied to flat scan results
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScanOptions {
+    pub sort_by: ScanSortKey,
+    pub descending: bool,
+    /// Only include these extensions (case-insensitive, without the dot)
+    pub extensions: Option<Vec<String>>,
+    pub min_size: Option<u64>,
+    pub max_size: Option<u64>,
+    /// Unix timestamp (seconds), inclusive
+    pub modified_after: Option<u64>,
+    /// Unix timestamp (seconds), inclusive
+    pub modified_before: Option<u64>,
+    /// Probe each file with ffprobe to fill `duration` and `has_audio`
+    pub with_media_info: bool,
+    /// Drop files known to have no audio stream (requires `with_media_info`)
+    pub require_audio: bool,
+    /// Only include files carrying all of these tags (looked up in the project database)
+    pub tags: Option<Vec<String>>,
+    /// Only include files in this collection (looked up in the project database)
+    pub collection_id: Option<i64>,
+}
+
+impl ScanOptions {
+    fn matches(&self, file: &FileEntry) -> bool {
+        if let Some(extensions) = &self.extensions {
+            let ext = file.extension.as_deref().unwrap_or_default();
+            if !extensions
+                .iter()
+                .any(|e| e.trim_start_matches('.').eq_ignore_ascii_case(ext))
+            {
+                return false;
+            }
+        }
+
+        if self.min_size.is_some_and(|min| file.size < min)
+            || self.max_size.is_some_and(|max| file.size > max)
+        {
+            return false;
+        }
+
+        if self.require_audio && file.has_audio == Some(false) {
+            return false;
+        }
+
+        // Files without a modification time never match a date range
+        if self.modified_after.is_some() || self.modified_before.is_some() {
+            let Some(modified) = file.modified else {
+                return false;
+            };
+            if self.modified_after.is_some_and(|after| modified < after)
+                || self.modified_before.is_some_and(|before| modified > before)
+            {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+/// Filter and sort scan results
+pub fn apply_scan_options(mut files: Vec<FileEntry>, options: &ScanOptions) -> Vec<FileEntry> {
+    files.retain(|f| options.matches(f));
+
+    match options.sort_by {
+        ScanSortKey::Path => files.sort_by(|a, b| a.path.cmp(&b.path)),
+        ScanSortKey::Name => files.sort_by_cached_key(|f| f.name.to_lowercase()),
+        ScanSortKey::Modified => files.sort_by_key(|f| f.modified),
+        ScanSortKey::Size => files.sort_by_key(|f| f.size),
+        ScanSortKey::Duration => files.sort_by(|a, b| {
+            a.duration
+                .unwrap_or(-1.0)
+                .total_cmp(&b.duration.unwrap_or(-1.0))
+        }),
+    }
+
+    if options.descending {
+        files.reverse();
+    }
+    files
+}
+
+/// Which timestamp "recent" refers to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RecentKind {
+    /// Newest files first by creation time (copied in or recorded lately)
+    Added,
+    /// Newest files first by modification time
+    Modified,
+}
+
+/// The `limit` most recent files, newest first.
+/// Files without a creation time count as added when they were last modified.
+pub fn most_recent(mut files: Vec<FileEntry>, kind: RecentKind, limit: usize) -> Vec<FileEntry> {
+    let timestamp = |f: &FileEntry| match kind {
+        RecentKind::Added => f.created.or(f.modified),
+        RecentKind::Modified => f.modified,
+    };
+
+    // Overlapping roots may list the same file twice
     files.sort_by(|a, b| a.path.cmp(&b.path));
+    files.dedup_by(|a, b| a.path == b.path);
 
-    Ok(files)
+    files.sort_by_key(|f| std::cmp::Reverse(timestamp(f)));
+    files.truncate(limit);
+    files
+}
+
+/// Find the most recent media files across several directories
+pub fn recent_media_files(
+    roots: &[std::path::PathBuf],
+    kind: RecentKind,
+    limit: usize,
+) -> Result<Vec<FileEntry>> {
+    let mut files = Vec::new();
+    for root in roots {
+        files.extend(scan_directory(root, None)?);
+    }
+    Ok(most_recent(files, kind, limit))
+}
+
+/// File count and size for one extension
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ExtensionStats {
+    pub count: usize,
+    pub total_size: u64,
+}
+
+/// Summary of a media library for dashboard headers
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LibraryStats {
+    pub file_count: usize,
+    pub total_size: u64,
+    pub by_extension: BTreeMap<String, ExtensionStats>,
+    /// Most recent modification time (Unix seconds)
+    pub newest_modified: Option<u64>,
+    /// Oldest modification time (Unix seconds)
+    pub oldest_modified: Option<u64>,
+}
+
+impl LibraryStats {
+    pub fn from_entries(files: &[FileEntry]) -> Self {
+        let mut stats = Self::default();
+
+        for file in files {
+            stats.file_count += 1;
+            stats.total_size += file.size;
+
+            let ext = stats
+                .by_extension
+                .entry(file.extension.clone().unwrap_or_default())
+                .or_default();
+            ext.count += 1;
+            ext.total_size += file.size;
+
+            if let Some(modified) = file.modified {
+                stats.newest_modified = stats.newest_modified.max(Some(modified));
+                stats.oldest_modified =
+                    Some(stats.oldest_modified.map_or(modified, |o| o.min(modified)));
+            }
+        }
+
+        stats
+    }
+}
+
+/// Compute library statistics with a single directory walk
+pub fn library_stats(root_path: &Path) -> Result<LibraryStats> {
+    Ok(LibraryStats::from_entries(&scan_directory(root_path, None)?))
 }
 
-/// Scan a directory and return a tree structure
-pub fn scan_directory_tree(root_path: &Path) -> Result<DirectoryNode, String> {
+/// Scan a directory and return a tree structure.
+/// Directories at `max_depth` are listed without their contents.
+pub fn scan_directory_tree(root_path: &Path, max_depth: Option<usize>) -> Result<DirectoryNode> {
+    scan_directory_tree_monitored(root_path, max_depth, &mut ScanMonitor::silent())
+}
+
+/// Scan a directory tree like [`scan_directory_tree`], reporting progress and
+/// stopping with [`AppError::Cancelled`] once the monitor's flag is set
+pub fn scan_directory_tree_monitored(
+    root_path: &Path,
+    max_depth: Option<usize>,
+    monitor: &mut ScanMonitor,
+) -> Result<DirectoryNode> {
     if !root_path.exists() {
-        return Err(format!("Directory does not exist: {:?}", root_path));
+        return Err(AppError::InvalidPath(format!("Directory does not exist: {:?}", root_path)));
+    }
+
+    build_tree_node(root_path, max_depth, &mut HashSet::new(), monitor)
+}
+
+/// Identity of a directory, so one reached again through a symlink or
+/// recursive mount is not traversed twice
+#[cfg(unix)]
+fn dir_identity(path: &Path) -> Option<String> {
+    use std::os::unix::fs::MetadataExt;
+    let metadata = std::fs::metadata(path).ok()?;
+    Some(format!("{}:{}", metadata.dev(), metadata.ino()))
+}
+
+#[cfg(not(unix))]
+fn dir_identity(path: &Path) -> Option<String> {
+    std::fs::canonicalize(path)
+        .ok()
+        .map(|p| p.to_string_lossy().to_string())
+}
+
+/// Whether a path is shown in directory listings:
+/// hidden entries are skipped and files must be supported media
+fn is_listed(path: &Path) -> bool {
+    let hidden = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .map(|n| n.starts_with('.'))
+        .unwrap_or(false);
+
+    !hidden && (!path.is_file() || is_supported_media(path))
+}
+
+/// A single directory entry, without its children
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DirectoryChild {
+    pub path: String,
+    pub name: String,
+    pub is_dir: bool,
+    pub size: u64,
+    pub modified: Option<u64>,
+    pub extension: Option<String>,
+    /// Whether a directory has any listed entries (lets the UI show an expander)
+    pub has_children: bool,
+}
+
+/// One page of a directory's direct children
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DirectoryPage {
+    pub path: String,
+    pub children: Vec<DirectoryChild>,
+    pub offset: usize,
+    /// Total number of listed children, across all pages
+    pub total: usize,
+}
+
+/// List one level of a directory (directories first, then files, alphabetically).
+/// Use instead of `scan_directory_tree` to load huge libraries lazily.
+pub fn list_directory_children(
+    path: &Path,
+    offset: usize,
+    limit: Option<usize>,
+) -> Result<DirectoryPage> {
+    if !path.is_dir() {
+        return Err(AppError::InvalidPath(format!("Directory does not exist: {:?}", path)));
     }
 
-    build_tree_node(root_path)
+    let mut entries: Vec<(bool, String, std::path::PathBuf)> = std::fs::read_dir(path)?
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| is_listed(p))
+        .map(|p| {
+            let name = p
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            (p.is_dir(), name, p)
+        })
+        .collect();
+
+    entries.sort_by(|a, b| {
+        b.0.cmp(&a.0)
+            .then_with(|| a.1.to_lowercase().cmp(&b.1.to_lowercase()))
+    });
+
+    let total = entries.len();
+    let children = entries
+        .into_iter()
+        .skip(offset)
+        .take(limit.unwrap_or(usize::MAX))
+        .filter_map(|(is_dir, name, child_path)| {
+            let metadata = std::fs::metadata(&child_path).ok()?;
+            let modified = metadata
+                .modified()
+                .ok()
+                .and_then(|t| t.duration_since(SystemTime::UNIX_EPOCH).ok())
+                .map(|d| d.as_secs());
+
+            let has_children = is_dir
+                && std::fs::read_dir(&child_path)
+                    .map(|mut it| it.any(|e| e.is_ok_and(|e| is_listed(&e.path()))))
+                    .unwrap_or(false);
+
+            Some(DirectoryChild {
+                path: child_path.to_string_lossy().to_string(),
+                name,
+                is_dir,
+                size: if is_dir { 0 } else { metadata.len() },
+                modified,
+                extension: if is_dir {
+                    None
+                } else {
+                    child_path
+                        .extension()
+                        .and_then(|e| e.to_str())
+                        .map(|s| s.to_lowercase())
+                },
+                has_children,
+            })
+        })
+        .collect();
+
+    Ok(DirectoryPage {
+        path: path.to_string_lossy().to_string(),
+        children,
+        offset,
+        total,
+    })
 }
 
-fn build_tree_node(path: &Path) -> Result<DirectoryNode, String> {
-    let metadata = std::fs::metadata(path)
-        .map_err(|e| format!("Failed to read metadata for {:?}: {}", path, e))?;
+fn build_tree_node(
+    path: &Path,
+    depth_left: Option<usize>,
+    visited_dirs: &mut HashSet<String>,
+    monitor: &mut ScanMonitor,
+) -> Result<DirectoryNode> {
+    // Not logged: unreadable children are skipped
+    let metadata = std::fs::metadata(path).map_err(AppError::Io)?;
 
     let modified = metadata
         .modified()
@@ -134,32 +891,38 @@ fn build_tree_node(path: &Path) -> Result<DirectoryNode, String> {
         .map(|s| s.to_lowercase());
 
     if metadata.is_dir() {
+        monitor.enter_dir(path)?;
         let mut children = Vec::new();
 
-        if let Ok(entries) = std::fs::read_dir(path) {
+        // Already-visited directories (symlink loops) are listed without contents
+        let first_visit = dir_identity(path).is_none_or(|id| visited_dirs.insert(id));
+        let entries = match depth_left {
+            Some(0) => None,
+            _ if !first_visit => None,
+            _ => std::fs::read_dir(path).ok(),
+        };
+
+        if let Some(entries) = entries {
             for entry in entries.filter_map(|e| e.ok()) {
                 let child_path = entry.path();
 
-                // Skip hidden files/directories
-                if child_path
-                    .file_name()
-                    .and_then(|n| n.to_str())
-                    .map(|n| n.starts_with('.'))
-                    .unwrap_or(false)
-                {
+                if !is_listed(&child_path) {
                     continue;
                 }
 
-                // For files, only include supported media
-                if child_path.is_file() && !is_supported_media(&child_path) {
-                    continue;
-                }
-
-                if let Ok(child_node) = build_tree_node(&child_path) {
+                let child = build_tree_node(
+                    &child_path,
+                    depth_left.map(|d| d - 1),
+                    visited_dirs,
+                    monitor,
+                );
+                if let Ok(child_node) = child {
                     children.push(child_node);
                 }
             }
         }
+        // Unreadable children are skipped, but a cancellation must not be
+        monitor.check_cancelled()?;
 
         // Sort: directories first, then files, alphabetically
         children.sort_by(|a, b| {
@@ -180,6 +943,7 @@ fn build_tree_node(path: &Path) -> Result<DirectoryNode, String> {
             children,
         })
     } else {
+        monitor.file_found();
         Ok(DirectoryNode {
             path: path.to_string_lossy().to_string(),
             name,
@@ -237,17 +1001,140 @@ mod tests {
         assert!(!is_supported_media(Path::new("folder/")));
     }
 
+    #[test]
+    fn test_batcher_collapses_write_storm_into_created() {
+        let mut batcher = FileEventBatcher::new();
+        batcher.push(FileEvent::Created("/media/rec.mp4".to_string()));
+        for _ in 0..50 {
+            batcher.push(FileEvent::Modified("/media/rec.mp4".to_string()));
+        }
+        batcher.push(FileEvent::Modified("/media/other.mp3".to_string()));
+        batcher.push(FileEvent::Modified("/media/other.mp3".to_string()));
+
+        let events = batcher.drain();
+        assert_eq!(events.len(), 2);
+        assert!(matches!(&events[0], FileEvent::Created(p) if p == "/media/rec.mp4"));
+        assert!(matches!(&events[1], FileEvent::Modified(p) if p == "/media/other.mp3"));
+        assert!(batcher.is_empty());
+    }
+
+    #[test]
+    fn test_batcher_merges_create_remove_and_replace() {
+        let mut batcher = FileEventBatcher::new();
+
+        // Temporary file: created and removed within one batch
+        batcher.push(FileEvent::Created("/media/tmp.mp4".to_string()));
+        batcher.push(FileEvent::Modified("/media/tmp.mp4".to_string()));
+        batcher.push(FileEvent::Removed("/media/tmp.mp4".to_string()));
+
+        // Existing file replaced in place
+        batcher.push(FileEvent::Removed("/media/clip.mov".to_string()));
+        batcher.push(FileEvent::Created("/media/clip.mov".to_string()));
+
+        // Existing file edited then deleted
+        batcher.push(FileEvent::Modified("/media/old.wav".to_string()));
+        batcher.push(FileEvent::Removed("/media/old.wav".to_string()));
+
+        let events = batcher.drain();
+        assert_eq!(events.len(), 2);
+        assert!(matches!(&events[0], FileEvent::Modified(p) if p == "/media/clip.mov"));
+        assert!(matches!(&events[1], FileEvent::Removed(p) if p == "/media/old.wav"));
+    }
+
+    #[test]
+    fn test_batcher_merges_renames() {
+        let mut batcher = FileEventBatcher::new();
+
+        // Rename reported twice (paired From/To and Both) collapses into one
+        let rename = FileEvent::Renamed {
+            from: "/media/a.mp4".to_string(),
+            to: "/media/b.mp4".to_string(),
+        };
+        batcher.push(rename.clone());
+        batcher.push(rename);
+        batcher.push(FileEvent::Modified("/media/b.mp4".to_string()));
+
+        // New file renamed before the batch is emitted is just a creation
+        batcher.push(FileEvent::Created("/media/draft.mp4".to_string()));
+        batcher.push(FileEvent::Renamed {
+            from: "/media/draft.mp4".to_string(),
+            to: "/media/final.mp4".to_string(),
+        });
+
+        let events = batcher.drain();
+        assert_eq!(events.len(), 2);
+        assert!(matches!(&events[0], FileEvent::Renamed { from, to }
+            if from == "/media/a.mp4" && to == "/media/b.mp4"));
+        assert!(matches!(&events[1], FileEvent::Created(p) if p == "/media/final.mp4"));
+    }
+
+    #[test]
+    fn test_rewatch_delay_backs_off() {
+        assert_eq!(rewatch_delay(0), Duration::from_secs(1));
+        assert_eq!(rewatch_delay(3), Duration::from_secs(8));
+        assert_eq!(rewatch_delay(6), WATCH_RETRY_MAX_DELAY);
+        assert_eq!(rewatch_delay(u32::MAX), WATCH_RETRY_MAX_DELAY);
+    }
+
+    #[test]
+    fn test_filter_excluded() {
+        let exclusions = vec![std::path::PathBuf::from("/media/Exports")];
+
+        let created = |p: &str| FileEvent::Created(p.to_string());
+        assert!(filter_excluded(created("/media/Exports/out.mp4"), &exclusions).is_none());
+        assert!(filter_excluded(created("/media/Exports/sub/out.mp4"), &exclusions).is_none());
+        assert!(filter_excluded(created("/media/raw.mp4"), &exclusions).is_some());
+        // Prefix of a name is not a parent directory
+        assert!(filter_excluded(created("/media/Exports2/a.mp4"), &exclusions).is_some());
+
+        let rename = |from: &str, to: &str| FileEvent::Renamed {
+            from: from.to_string(),
+            to: to.to_string(),
+        };
+        assert!(matches!(
+            filter_excluded(rename("/media/Exports/a.mp4", "/media/a.mp4"), &exclusions),
+            Some(FileEvent::Created(p)) if p == "/media/a.mp4"
+        ));
+        assert!(matches!(
+            filter_excluded(rename("/media/a.mp4", "/media/Exports/a.mp4"), &exclusions),
+            Some(FileEvent::Removed(p)) if p == "/media/a.mp4"
+        ));
+    }
+
+    #[test]
+    fn test_rename_event_respects_media_filter() {
+        let media = |from: &str, to: &str| FileEvent::rename(Path::new(from), Path::new(to));
+
+        assert!(matches!(media("/m/a.mp4", "/m/b.mp4"), Some(FileEvent::Renamed { .. })));
+        assert!(matches!(media("/m/a.part", "/m/a.mp4"), Some(FileEvent::Created(p)) if p == "/m/a.mp4"));
+        assert!(matches!(media("/m/a.mp4", "/m/a.bak"), Some(FileEvent::Removed(p)) if p == "/m/a.mp4"));
+        assert!(media("/m/a.txt", "/m/b.txt").is_none());
+    }
+
+    #[test]
+    fn test_file_event_serialization() {
+        let json = serde_json::to_value(FileEvent::Renamed {
+            from: "/m/a.mp4".to_string(),
+            to: "/m/b.mp4".to_string(),
+        })
+        .unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({ "type": "Renamed", "path": { "from": "/m/a.mp4", "to": "/m/b.mp4" } })
+        );
+    }
+
     #[test]
     fn test_scan_directory_nonexistent() {
-        let result = scan_directory(Path::new("/nonexistent/path/12345"));
+        let result = scan_directory(Path::new("/nonexistent/path/12345"), None);
         assert!(result.is_err());
-        assert!(result.unwrap_err().contains("does not exist"));
+        assert!(result.unwrap_err().to_string().contains("does not exist"));
     }
 
     #[test]
     fn test_scan_directory_empty() {
         let temp_dir = TempDir::new().unwrap();
-        let result = scan_directory(temp_dir.path());
+        let result = scan_directory(temp_dir.path(), None);
         assert!(result.is_ok());
         assert!(result.unwrap().is_empty());
     }
@@ -261,7 +1148,7 @@ mod tests {
         File::create(temp_dir.path().join("audio.mp3")).unwrap();
         File::create(temp_dir.path().join("document.pdf")).unwrap(); // Should be ignored
 
-        let result = scan_directory(temp_dir.path());
+        let result = scan_directory(temp_dir.path(), None);
         assert!(result.is_ok());
 
         let files = result.unwrap();
@@ -271,16 +1158,310 @@ mod tests {
         assert!(!files.iter().any(|f| f.name == "document.pdf"));
     }
 
+    #[test]
+    fn test_scan_directory_nested_and_hidden_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+
+        for dir in ["a/b", "c", ".cache"] {
+            fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        File::create(root.join("a/b/deep.mp4")).unwrap();
+        File::create(root.join("c/clip.wav")).unwrap();
+        File::create(root.join(".cache/hidden.mp3")).unwrap();
+
+        let files = scan_directory(root, None).unwrap();
+        let names: Vec<_> = files.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names.len(), 3);
+        assert!(names.contains(&"deep.mp4"));
+        assert!(names.contains(&"clip.wav"));
+        assert!(names.contains(&"hidden.mp3"));
+        // Results are sorted by path however the walk was split across threads
+        assert!(files.windows(2).all(|w| w[0].path <= w[1].path));
+    }
+
+    fn sample_entry(name: &str, size: u64, modified: Option<u64>) -> FileEntry {
+        FileEntry {
+            path: format!("/media/{}", name),
+            name: name.to_string(),
+            size,
+            is_dir: false,
+            modified,
+            created: None,
+            extension: name.rsplit('.').next().map(|e| e.to_lowercase()),
+            duration: None,
+            has_audio: None,
+        }
+    }
+
+    #[test]
+    fn test_apply_scan_options_sorting() {
+        let files = vec![
+            sample_entry("b.mp4", 300, Some(10)),
+            sample_entry("C.mp3", 100, Some(30)),
+            sample_entry("a.wav", 200, Some(20)),
+        ];
+        let names = |files: Vec<FileEntry>| files.into_iter().map(|f| f.name).collect::<Vec<_>>();
+
+        let by_name = ScanOptions {
+            sort_by: ScanSortKey::Name,
+            ..Default::default()
+        };
+        assert_eq!(
+            names(apply_scan_options(files.clone(), &by_name)),
+            ["a.wav", "b.mp4", "C.mp3"]
+        );
+
+        let by_size_desc = ScanOptions {
+            sort_by: ScanSortKey::Size,
+            descending: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            names(apply_scan_options(files.clone(), &by_size_desc)),
+            ["b.mp4", "a.wav", "C.mp3"]
+        );
+
+        let by_modified = ScanOptions {
+            sort_by: ScanSortKey::Modified,
+            ..Default::default()
+        };
+        assert_eq!(
+            names(apply_scan_options(files, &by_modified)),
+            ["b.mp4", "a.wav", "C.mp3"]
+        );
+    }
+
+    #[test]
+    fn test_apply_scan_options_filters() {
+        let files = vec![
+            sample_entry("small.mp4", 10, Some(100)),
+            sample_entry("big.mp4", 5_000, Some(200)),
+            sample_entry("song.MP3", 500, Some(300)),
+            sample_entry("undated.mp4", 500, None),
+        ];
+        let names = |options: ScanOptions| {
+            apply_scan_options(files.clone(), &options)
+                .into_iter()
+                .map(|f| f.name)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            names(ScanOptions {
+                extensions: Some(vec![".mp3".to_string()]),
+                ..Default::default()
+            }),
+            ["song.MP3"]
+        );
+        assert_eq!(
+            names(ScanOptions {
+                min_size: Some(100),
+                max_size: Some(1_000),
+                ..Default::default()
+            }),
+            ["song.MP3", "undated.mp4"]
+        );
+        assert_eq!(
+            names(ScanOptions {
+                modified_after: Some(150),
+                modified_before: Some(300),
+                ..Default::default()
+            }),
+            ["big.mp4", "song.MP3"]
+        );
+    }
+
+    #[test]
+    fn test_apply_scan_options_media_info() {
+        let mut long = sample_entry("long.mp4", 1, None);
+        long.duration = Some(600.0);
+        long.has_audio = Some(true);
+        let mut silent = sample_entry("silent.mp4", 1, None);
+        silent.duration = Some(5.0);
+        silent.has_audio = Some(false);
+        let mut short = sample_entry("short.mp3", 1, None);
+        short.duration = Some(30.0);
+        short.has_audio = Some(true);
+        let unprobed = sample_entry("unprobed.wav", 1, None);
+
+        let options = ScanOptions {
+            sort_by: ScanSortKey::Duration,
+            require_audio: true,
+            ..Default::default()
+        };
+        let names: Vec<String> = apply_scan_options(vec![long, silent, short, unprobed], &options)
+            .into_iter()
+            .map(|f| f.name)
+            .collect();
+        assert_eq!(names, ["unprobed.wav", "short.mp3", "long.mp4"]);
+    }
+
+    #[test]
+    fn test_most_recent() {
+        let mut copied_in = sample_entry("old-recording.mp4", 1, Some(5));
+        copied_in.created = Some(100);
+        let files = vec![
+            sample_entry("a.mp4", 1, Some(50)),
+            copied_in,
+            sample_entry("b.mp4", 1, None),
+            sample_entry("c.mp4", 1, Some(70)),
+            sample_entry("a.mp4", 1, Some(50)),
+        ];
+        let names = |files: Vec<FileEntry>| files.into_iter().map(|f| f.name).collect::<Vec<_>>();
+
+        assert_eq!(
+            names(most_recent(files.clone(), RecentKind::Added, 3)),
+            ["old-recording.mp4", "c.mp4", "a.mp4"]
+        );
+        assert_eq!(
+            names(most_recent(files, RecentKind::Modified, 10)),
+            ["c.mp4", "a.mp4", "old-recording.mp4", "b.mp4"]
+        );
+    }
+
+    #[test]
+    fn test_library_stats() {
+        let files = vec![
+            sample_entry("a.mp4", 100, Some(50)),
+            sample_entry("b.mp4", 300, Some(10)),
+            sample_entry("c.wav", 1_000, None),
+            sample_entry("d.mp3", 5, Some(99)),
+        ];
+
+        let stats = LibraryStats::from_entries(&files);
+        assert_eq!(stats.file_count, 4);
+        assert_eq!(stats.total_size, 1_405);
+        assert_eq!(stats.newest_modified, Some(99));
+        assert_eq!(stats.oldest_modified, Some(10));
+        assert_eq!(
+            stats.by_extension["mp4"],
+            ExtensionStats {
+                count: 2,
+                total_size: 400
+            }
+        );
+        assert_eq!(stats.by_extension["wav"].count, 1);
+        assert_eq!(stats.by_extension.len(), 3);
+    }
+
+    #[test]
+    fn test_library_stats_empty() {
+        let temp_dir = TempDir::new().unwrap();
+        let stats = library_stats(temp_dir.path()).unwrap();
+        assert_eq!(stats, LibraryStats::default());
+    }
+
+    #[test]
+    fn test_scan_options_deserialize_defaults() {
+        let options: ScanOptions = serde_json::from_str(r#"{"sort_by": "size"}"#).unwrap();
+        assert_eq!(options.sort_by, ScanSortKey::Size);
+        assert!(!options.descending);
+        assert!(options.extensions.is_none());
+    }
+
+    #[test]
+    fn test_scan_reports_progress() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::create_dir(temp_dir.path().join("sub")).unwrap();
+        File::create(temp_dir.path().join("a.mp4")).unwrap();
+        File::create(temp_dir.path().join("sub").join("b.mp4")).unwrap();
+
+        let cancelled = AtomicBool::new(false);
+        let mut reports = Vec::new();
+        let mut monitor = ScanMonitor::new(&cancelled, |p| reports.push(p));
+        let files = scan_directory_monitored(temp_dir.path(), None, &mut monitor).unwrap();
+        drop(monitor);
+
+        assert_eq!(files.len(), 2);
+        // The first directory is always reported; later ones are throttled
+        assert!(!reports.is_empty());
+        assert_eq!(reports[0].current_dir, temp_dir.path().to_string_lossy());
+    }
+
+    #[test]
+    fn test_rescan_skips_unchanged_directories() {
+        let temp_dir = TempDir::new().unwrap();
+        let sub = temp_dir.path().join("sub");
+        fs::create_dir(&sub).unwrap();
+        File::create(temp_dir.path().join("a.mp4")).unwrap();
+        File::create(sub.join("b.mp4")).unwrap();
+        let names = |files: &[FileEntry]| files.iter().map(|f| f.name.clone()).collect::<Vec<_>>();
+
+        let (files, mut dirs) = rescan_directory_monitored(
+            temp_dir.path(),
+            None,
+            &HashMap::new(),
+            &mut ScanMonitor::silent(),
+        )
+        .unwrap();
+        assert_eq!(names(&files), ["a.mp4", "b.mp4"]);
+        // The first scan walks in parallel and indexes what it read
+        assert_eq!(dirs.len(), 2);
+        let root = &dirs[&temp_dir.path().to_string_lossy().to_string()];
+        assert_eq!(root.subdirs, ["sub"]);
+        assert_eq!(root.files, ["a.mp4"]);
+        let (again, _) =
+            rescan_directory_monitored(temp_dir.path(), None, &dirs, &mut ScanMonitor::silent())
+                .unwrap();
+        assert_eq!(names(&again), ["a.mp4", "b.mp4"]);
+
+        // An unchanged directory is not read again, but its files are checked
+        let sub_key = sub.to_string_lossy().to_string();
+        dirs.get_mut(&sub_key).unwrap().files.clear();
+        fs::write(temp_dir.path().join("a.mp4"), b"edited").unwrap();
+        let (files, _) =
+            rescan_directory_monitored(temp_dir.path(), None, &dirs, &mut ScanMonitor::silent())
+                .unwrap();
+        assert_eq!(names(&files), ["a.mp4"]);
+        assert_eq!(files[0].size, 6);
+
+        // A changed one is
+        dirs.get_mut(&sub_key).unwrap().modified += 1;
+        let (files, _) =
+            rescan_directory_monitored(temp_dir.path(), None, &dirs, &mut ScanMonitor::silent())
+                .unwrap();
+        assert_eq!(names(&files), ["a.mp4", "b.mp4"]);
+
+        let (shallow, dirs) = rescan_directory_monitored(
+            temp_dir.path(),
+            Some(1),
+            &HashMap::new(),
+            &mut ScanMonitor::silent(),
+        )
+        .unwrap();
+        assert_eq!(names(&shallow), ["a.mp4"]);
+        assert_eq!(dirs.len(), 1);
+    }
+
+    #[test]
+    fn test_cancelled_scan_fails() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::create_dir(temp_dir.path().join("sub")).unwrap();
+        File::create(temp_dir.path().join("sub").join("a.mp4")).unwrap();
+
+        let cancelled = AtomicBool::new(true);
+        let mut monitor = ScanMonitor::new(&cancelled, |_| {});
+        assert!(matches!(
+            scan_directory_monitored(temp_dir.path(), None, &mut monitor),
+            Err(AppError::Cancelled)
+        ));
+        assert!(matches!(
+            scan_directory_tree_monitored(temp_dir.path(), None, &mut monitor),
+            Err(AppError::Cancelled)
+        ));
+    }
+
     #[test]
     fn test_scan_directory_tree_nonexistent() {
-        let result = scan_directory_tree(Path::new("/nonexistent/path/12345"));
+        let result = scan_directory_tree(Path::new("/nonexistent/path/12345"), None);
         assert!(result.is_err());
     }
 
     #[test]
     fn test_scan_directory_tree_empty() {
         let temp_dir = TempDir::new().unwrap();
-        let result = scan_directory_tree(temp_dir.path());
+        let result = scan_directory_tree(temp_dir.path(), None);
         assert!(result.is_ok());
 
         let tree = result.unwrap();
@@ -296,7 +1477,7 @@ mod tests {
         File::create(temp_dir.path().join("video.mp4")).unwrap();
         File::create(temp_dir.path().join("audio.mp3")).unwrap();
 
-        let result = scan_directory_tree(temp_dir.path());
+        let result = scan_directory_tree(temp_dir.path(), None);
         assert!(result.is_ok());
 
         let tree = result.unwrap();
@@ -312,7 +1493,7 @@ mod tests {
         File::create(temp_dir.path().join("video.mp4")).unwrap();
         File::create(temp_dir.path().join(".hidden.mp4")).unwrap();
 
-        let result = scan_directory_tree(temp_dir.path());
+        let result = scan_directory_tree(temp_dir.path(), None);
         assert!(result.is_ok());
 
         let tree = result.unwrap();
@@ -329,7 +1510,7 @@ mod tests {
         File::create(temp_dir.path().join("video.mp4")).unwrap();
         File::create(temp_dir.path().join("subdir").join("audio.mp3")).unwrap();
 
-        let result = scan_directory_tree(temp_dir.path());
+        let result = scan_directory_tree(temp_dir.path(), None);
         assert!(result.is_ok());
 
         let tree = result.unwrap();
@@ -343,12 +1524,136 @@ mod tests {
         assert_eq!(subdir.unwrap().children.len(), 1);
     }
 
+    #[test]
+    fn test_list_directory_children_one_level() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+
+        fs::create_dir_all(root.join("full").join("deep")).unwrap();
+        File::create(root.join("full").join("deep").join("clip.mp4")).unwrap();
+        fs::create_dir(root.join("empty")).unwrap();
+        fs::create_dir(root.join("docs")).unwrap();
+        File::create(root.join("docs").join("notes.txt")).unwrap();
+        File::create(root.join("b.mp3")).unwrap();
+        File::create(root.join("A.mp4")).unwrap();
+        File::create(root.join(".hidden.mp4")).unwrap();
+        File::create(root.join("readme.md")).unwrap();
+
+        let page = list_directory_children(root, 0, None).unwrap();
+        let names: Vec<&str> = page.children.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["docs", "empty", "full", "A.mp4", "b.mp3"]);
+        assert_eq!(page.total, 5);
+
+        let child = |name: &str| page.children.iter().find(|c| c.name == name).unwrap();
+        assert!(child("full").has_children);
+        assert!(!child("empty").has_children);
+        assert!(!child("docs").has_children); // only non-media files inside
+        assert!(!child("A.mp4").has_children);
+        assert_eq!(child("A.mp4").extension, Some("mp4".to_string()));
+    }
+
+    #[test]
+    fn test_list_directory_children_pagination() {
+        let temp_dir = TempDir::new().unwrap();
+        for i in 0..5 {
+            File::create(temp_dir.path().join(format!("clip{}.mp4", i))).unwrap();
+        }
+
+        let page = list_directory_children(temp_dir.path(), 2, Some(2)).unwrap();
+        let names: Vec<&str> = page.children.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["clip2.mp4", "clip3.mp4"]);
+        assert_eq!(page.offset, 2);
+        assert_eq!(page.total, 5);
+
+        let past_end = list_directory_children(temp_dir.path(), 10, Some(2)).unwrap();
+        assert!(past_end.children.is_empty());
+    }
+
+    #[test]
+    fn test_list_directory_children_nonexistent() {
+        let result = list_directory_children(Path::new("/nonexistent/path/12345"), 0, None);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_scan_directory_max_depth() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("a").join("b")).unwrap();
+        File::create(root.join("top.mp4")).unwrap();
+        File::create(root.join("a").join("mid.mp4")).unwrap();
+        File::create(root.join("a").join("b").join("deep.mp4")).unwrap();
+
+        let names = |depth| {
+            scan_directory(root, depth)
+                .unwrap()
+                .into_iter()
+                .map(|f| f.name)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names(Some(1)), ["top.mp4"]);
+        assert_eq!(names(Some(2)).len(), 2);
+        assert_eq!(names(None).len(), 3);
+    }
+
+    #[test]
+    fn test_scan_directory_tree_max_depth() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("a").join("b")).unwrap();
+        File::create(root.join("top.mp4")).unwrap();
+        File::create(root.join("a").join("mid.mp4")).unwrap();
+        File::create(root.join("a").join("b").join("deep.mp4")).unwrap();
+
+        let tree = scan_directory_tree(root, Some(1)).unwrap();
+        assert_eq!(tree.children.len(), 2);
+        let a = tree.children.iter().find(|c| c.name == "a").unwrap();
+        assert!(a.is_dir);
+        assert!(a.children.is_empty());
+
+        let tree = scan_directory_tree(root, Some(2)).unwrap();
+        let a = tree.children.iter().find(|c| c.name == "a").unwrap();
+        assert_eq!(a.children.len(), 2);
+        let b = a.children.iter().find(|c| c.name == "b").unwrap();
+        assert!(b.children.is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_scan_directory_survives_symlink_loop() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir(root.join("a")).unwrap();
+        File::create(root.join("a").join("clip.mp4")).unwrap();
+        std::os::unix::fs::symlink(root, root.join("a").join("loop")).unwrap();
+
+        let files = scan_directory(root, None).unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].name, "clip.mp4");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_scan_directory_tree_survives_symlink_loop() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir(root.join("a")).unwrap();
+        File::create(root.join("a").join("clip.mp4")).unwrap();
+        std::os::unix::fs::symlink(root, root.join("a").join("loop")).unwrap();
+
+        let tree = scan_directory_tree(root, None).unwrap();
+        let a = tree.children.iter().find(|c| c.name == "a").unwrap();
+        let link = a.children.iter().find(|c| c.name == "loop").unwrap();
+        assert!(link.is_dir);
+        assert!(link.children.is_empty());
+    }
+
     #[test]
     fn test_file_entry_extension() {
         let temp_dir = TempDir::new().unwrap();
         File::create(temp_dir.path().join("test.MP4")).unwrap();
 
-        let result = scan_directory(temp_dir.path());
+        let result = scan_directory(temp_dir.path(), None);
         assert!(result.is_ok());
 
         let files = result.unwrap();
@@ -378,7 +1683,7 @@ mod tests {
         File::create(level4.join("level4.mov")).unwrap();
         File::create(level5.join("level5.mp4")).unwrap();
 
-        let result = scan_directory_tree(root);
+        let result = scan_directory_tree(root, None);
         assert!(result.is_ok());
 
         let tree = result.unwrap();
@@ -437,7 +1742,7 @@ mod tests {
         // Only create file at deepest level to test full traversal
         File::create(level7.join("deepest.mp4")).unwrap();
 
-        let result = scan_directory_tree(root);
+        let result = scan_directory_tree(root, None);
         assert!(result.is_ok());
 
         let tree = result.unwrap();
@@ -478,7 +1783,7 @@ mod tests {
         // Create non-media file in empty branch (should still be excluded as non-media file)
         File::create(empty_branch.join("document.txt")).unwrap();
 
-        let result = scan_directory_tree(root);
+        let result = scan_directory_tree(root, None);
         assert!(result.is_ok());
 
         let tree = result.unwrap();
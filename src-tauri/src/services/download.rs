@@ -1,9 +1,13 @@
 use crate::error::{AppError, Result};
+use crate::services::concurrency::{self, JobKind};
+use crate::services::http;
 use futures::StreamExt;
-use reqwest::Client;
-use std::path::PathBuf;
-use tokio::fs::{self, File};
+use reqwest::header::{HeaderMap, CONTENT_RANGE, ETAG, IF_RANGE, LAST_MODIFIED, RANGE};
+use reqwest::{Client, StatusCode};
+use std::path::{Path, PathBuf};
+use tokio::fs::{self, File, OpenOptions};
 use tokio::io::AsyncWriteExt;
+use tokio_util::sync::CancellationToken;
 
 /// Model information for Whisper models
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -98,20 +102,26 @@ impl WhisperModel {
 }
 
 /// Download service for managing model downloads
+#[derive(Clone)]
 pub struct DownloadService {
-    client: Client,
     models_dir: PathBuf,
 }
 
 impl DownloadService {
-    /// Create a new download service
-    pub fn new() -> Result<Self> {
-        let models_dir = Self::get_models_directory()?;
+    /// Create a new download service. Without a data directory, models are
+    /// kept in a folder in the system temp directory.
+    pub fn new() -> Self {
+        let models_dir = Self::get_models_directory().unwrap_or_else(|e| {
+            let fallback = std::env::temp_dir().join("clip-flow-models");
+            log::error!("[download] {}; keeping models in {}", e, fallback.display());
+            fallback
+        });
+        Self { models_dir }
+    }
 
-        Ok(Self {
-            client: Client::new(),
-            models_dir,
-        })
+    /// Folder the models of this service are kept in
+    pub fn models_dir(&self) -> &Path {
+        &self.models_dir
     }
 
     /// Get the models directory path
@@ -161,10 +171,14 @@ impl DownloadService {
         self.models_dir.join(format!("ggml-{}.bin", model_id))
     }
 
-    /// Download a Whisper model with progress callback
+    /// Download a Whisper model with progress callback.
+    /// Cancelling the token stops the download and removes the partial file;
+    /// triggering `pause` stops it and keeps the partial file to resume from.
     pub async fn download_model<F>(
         &self,
         model_id: &str,
+        cancel: &CancellationToken,
+        pause: Option<&CancellationToken>,
         on_progress: F,
     ) -> Result<PathBuf>
     where
@@ -179,52 +193,240 @@ impl DownloadService {
             .ok_or_else(|| AppError::ModelNotFound(model_id.to_string()))?;
 
         let output_path = self.get_model_path(model_id);
-        let temp_path = output_path.with_extension("bin.tmp");
-
-        // Start download
-        let response = self.client
-            .get(&model.url)
-            .send()
-            .await?;
-
-        let total_size = response.content_length().unwrap_or(model.size_bytes);
-        let mut downloaded: u64 = 0;
-
-        // Create temp file
-        let mut file = File::create(&temp_path).await?;
-        let mut stream = response.bytes_stream();
-
-        while let Some(chunk) = stream.next().await {
-            let chunk = chunk.map_err(|e| AppError::Download(e.to_string()))?;
-            file.write_all(&chunk).await?;
-
-            downloaded += chunk.len() as u64;
-            let progress = DownloadProgress {
-                downloaded,
-                total: total_size,
-                percent: (downloaded as f64 / total_size as f64 * 100.0) as f32,
-                model_id: model_id.to_string(),
-            };
-            on_progress(progress);
-        }
-
-        file.flush().await?;
-        drop(file);
-
-        // Rename temp file to final name
-        fs::rename(&temp_path, &output_path).await?;
+        download_file(
+            &http::download_client(),
+            &model.url,
+            &output_path,
+            model.size_bytes,
+            model_id,
+            cancel,
+            pause,
+            on_progress,
+        )
+        .await?;
 
         Ok(output_path)
     }
 
-    /// Delete a downloaded model
+    /// Delete a downloaded model, and the partial file of a paused download
     pub async fn delete_model(&self, model_id: &str) -> Result<()> {
         let model_path = self.get_model_path(model_id);
         if model_path.exists() {
             fs::remove_file(&model_path).await?;
         }
+        remove_partial(&model_path).await?;
         Ok(())
     }
+
+    /// Remove the partial file a paused download of a model left behind.
+    /// Returns whether there was one.
+    pub async fn discard_partial(&self, model_id: &str) -> Result<bool> {
+        remove_partial(&self.get_model_path(model_id)).await
+    }
+}
+
+/// File a download of `output_path` is written to until it completes
+pub(crate) fn partial_path(output_path: &Path) -> PathBuf {
+    with_suffix(output_path, ".tmp")
+}
+
+/// File next to the partial file keeping the server's validator (a strong ETag
+/// or Last-Modified) of the download, sent as `If-Range` when resuming
+fn validator_path(output_path: &Path) -> PathBuf {
+    with_suffix(output_path, ".tmp.etag")
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// Remove the partial file of a download of `output_path` and its validator.
+/// Returns whether there was a partial file.
+pub(crate) async fn remove_partial(output_path: &Path) -> Result<bool> {
+    let _ = fs::remove_file(validator_path(output_path)).await;
+    match fs::remove_file(partial_path(output_path)).await {
+        Ok(()) => Ok(true),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Validator of a response to resume it with later. Weak ETags can't be used
+/// with `If-Range`.
+fn response_validator(headers: &HeaderMap) -> Option<String> {
+    let etag = headers
+        .get(ETAG)
+        .and_then(|v| v.to_str().ok())
+        .filter(|etag| !etag.starts_with("W/"));
+    etag.or_else(|| headers.get(LAST_MODIFIED).and_then(|v| v.to_str().ok()))
+        .map(str::to_string)
+}
+
+/// Full length of the file from a `Content-Range` header
+/// (`bytes 0-99/1234` or `bytes */1234`)
+fn content_range_total(headers: &HeaderMap) -> Option<u64> {
+    let range = headers.get(CONTENT_RANGE)?.to_str().ok()?;
+    range.rsplit_once('/')?.1.trim().parse().ok()
+}
+
+/// Move a complete partial file to `output_path`
+async fn finish_partial(output_path: &Path) -> Result<()> {
+    fs::rename(partial_path(output_path), output_path).await?;
+    let _ = fs::remove_file(validator_path(output_path)).await;
+    Ok(())
+}
+
+/// Download `url` to `output_path` through a `.tmp` file, so an interrupted
+/// download never leaves a truncated file behind. A `.tmp` file left by an
+/// earlier download is resumed with a range request if the file on the server
+/// is unchanged (checked with `If-Range`); otherwise it starts over.
+/// `expected_size` is only shown as the total while the server announces none;
+/// the download fails if it ends short of the length the server announced.
+/// Cancelling the token stops the download and removes the partial file;
+/// triggering `pause` stops it with [`AppError::Cancelled`] as well, but keeps
+/// the partial file.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn download_file<F>(
+    client: &Client,
+    url: &str,
+    output_path: &Path,
+    expected_size: u64,
+    model_id: &str,
+    cancel: &CancellationToken,
+    pause: Option<&CancellationToken>,
+    on_progress: F,
+) -> Result<()>
+where
+    F: Fn(DownloadProgress) + Send + 'static,
+{
+    let temp_path = partial_path(output_path);
+    let paused = async {
+        match pause {
+            Some(pause) => pause.cancelled().await,
+            None => std::future::pending().await,
+        }
+    };
+    tokio::pin!(paused);
+
+    // A pause while waiting for a free slot stops the download before it starts
+    let _slot = tokio::select! {
+        biased;
+        _ = &mut paused => {
+            log::info!("[download] Paused {} before it started", model_id);
+            return Err(AppError::Cancelled);
+        }
+        slot = concurrency::acquire(JobKind::Download, cancel) => slot?,
+    };
+
+    // Start download, from where a paused one stopped
+    let (response, partial) = loop {
+        let validator = fs::read_to_string(validator_path(output_path)).await.ok();
+        let partial = match validator {
+            Some(_) => fs::metadata(&temp_path).await.map(|m| m.len()).unwrap_or(0),
+            None => 0,
+        };
+        let mut request = client.get(url);
+        if let (true, Some(validator)) = (partial > 0, &validator) {
+            request = request
+                .header(RANGE, format!("bytes={}-", partial))
+                .header(IF_RANGE, validator.trim());
+        }
+        let response = request.send().await?;
+        if partial == 0 || response.status() != StatusCode::RANGE_NOT_SATISFIABLE {
+            let response = response
+                .error_for_status()
+                .map_err(|e| AppError::Download(e.to_string()))?;
+            break (response, partial);
+        }
+
+        // Nothing is left after the partial file: it is either the whole file,
+        // paused right at the end, or longer than the file now is
+        if content_range_total(response.headers()) == Some(partial) {
+            log::info!("[download] {} was already complete", model_id);
+            return finish_partial(output_path).await;
+        }
+        log::warn!(
+            "[download] Partial {} doesn't match the file, starting over",
+            model_id
+        );
+        remove_partial(output_path).await?;
+    };
+
+    // A server that ignores the range, or whose file changed, sends the whole file again
+    let resumed = partial > 0 && response.status() == StatusCode::PARTIAL_CONTENT;
+    let mut downloaded: u64 = if resumed { partial } else { 0 };
+    let announced = if resumed {
+        content_range_total(response.headers()).or_else(|| {
+            response
+                .content_length()
+                .map(|remaining| partial + remaining)
+        })
+    } else {
+        response.content_length()
+    };
+    let total_size = announced.unwrap_or(expected_size);
+
+    let mut file = if resumed {
+        log::info!("[download] Resuming {} at {} bytes", model_id, partial);
+        OpenOptions::new().append(true).open(&temp_path).await?
+    } else {
+        match response_validator(response.headers()) {
+            Some(validator) => fs::write(validator_path(output_path), validator).await?,
+            None => {
+                let _ = fs::remove_file(validator_path(output_path)).await;
+            }
+        }
+        File::create(&temp_path).await?
+    };
+    let mut stream = response.bytes_stream();
+
+    loop {
+        let chunk = tokio::select! {
+            biased;
+            _ = cancel.cancelled() => {
+                drop(file);
+                remove_partial(output_path).await?;
+                return Err(AppError::Cancelled);
+            }
+            _ = &mut paused => {
+                file.flush().await?;
+                log::info!("[download] Paused {} at {} bytes", model_id, downloaded);
+                return Err(AppError::Cancelled);
+            }
+            chunk = stream.next() => chunk,
+        };
+        let Some(chunk) = chunk else { break };
+        let chunk = chunk.map_err(|e| AppError::Download(e.to_string()))?;
+        file.write_all(&chunk).await?;
+
+        downloaded += chunk.len() as u64;
+        let progress = DownloadProgress {
+            downloaded,
+            total: total_size,
+            percent: if total_size > 0 {
+                (downloaded as f64 / total_size as f64 * 100.0) as f32
+            } else {
+                0.0
+            },
+            model_id: model_id.to_string(),
+        };
+        on_progress(progress);
+    }
+
+    file.flush().await?;
+    drop(file);
+
+    // The partial file is kept, so downloading again resumes the rest
+    if let Some(total) = announced.filter(|&total| total != downloaded) {
+        return Err(AppError::Download(format!(
+            "Download of {} ended at {} of {} bytes",
+            model_id, downloaded, total
+        )));
+    }
+
+    finish_partial(output_path).await
 }
 
 #[derive(Debug, Clone, serde::Serialize)]
@@ -244,3 +446,207 @@ pub struct ModelStatus {
     pub installed: bool,
     pub path: Option<String>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use tokio::io::AsyncReadExt;
+    use tokio::net::TcpListener;
+
+    const ETAG_V1: &str = "\"v1\"";
+
+    /// Range headers of the requests a test server received
+    type Requests = Arc<Mutex<Vec<Option<String>>>>;
+
+    /// Serve `body` on localhost like a CDN would: with an ETag, honoring
+    /// `Range` when `If-Range` matches it. Returns the URL and the requests.
+    async fn serve(body: Vec<u8>, etag: &'static str) -> (String, Requests) {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).await.unwrap();
+        let url = format!("http://{}/model.bin", listener.local_addr().unwrap());
+        let requests = Requests::default();
+        let seen = Arc::clone(&requests);
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let mut head = Vec::new();
+                let mut buf = [0u8; 1024];
+                while !head.windows(4).any(|w| w == b"\r\n\r\n") {
+                    match socket.read(&mut buf).await {
+                        Ok(0) | Err(_) => break,
+                        Ok(n) => head.extend_from_slice(&buf[..n]),
+                    }
+                }
+                let head = String::from_utf8_lossy(&head).to_lowercase();
+                let header = |name: &str| {
+                    head.lines()
+                        .find_map(|line| line.strip_prefix(name))
+                        .map(|value| value.trim().to_string())
+                };
+                let range = header("range:");
+                let if_range = header("if-range:");
+                seen.lock().unwrap().push(range.clone());
+
+                let start = range
+                    .filter(|_| if_range.is_none_or(|v| v == etag))
+                    .and_then(|r| r.strip_prefix("bytes=")?.strip_suffix('-')?.parse().ok());
+                let len = body.len();
+                let (status, extra, content) = match start {
+                    Some(start) if start >= len => (
+                        "416 Range Not Satisfiable",
+                        format!("Content-Range: bytes */{}\r\n", len),
+                        &body[..0],
+                    ),
+                    Some(start) => (
+                        "206 Partial Content",
+                        format!("Content-Range: bytes {}-{}/{}\r\n", start, len - 1, len),
+                        &body[start..],
+                    ),
+                    None => ("200 OK", String::new(), &body[..]),
+                };
+                let response = format!(
+                    "HTTP/1.1 {}\r\nETag: {}\r\n{}Content-Length: {}\r\nConnection: close\r\n\r\n",
+                    status,
+                    etag,
+                    extra,
+                    content.len()
+                );
+                let _ = socket.write_all(response.as_bytes()).await;
+                let _ = socket.write_all(content).await;
+            }
+        });
+        (url, requests)
+    }
+
+    fn body() -> Vec<u8> {
+        (0..1_000_000u32).map(|i| (i % 251) as u8).collect()
+    }
+
+    async fn download(
+        url: &str,
+        output_path: &Path,
+        pause: Option<&CancellationToken>,
+        on_progress: impl Fn(DownloadProgress) + Send + 'static,
+    ) -> Result<()> {
+        let cancel = CancellationToken::new();
+        download_file(
+            &Client::new(),
+            url,
+            output_path,
+            0,
+            "test",
+            &cancel,
+            pause,
+            on_progress,
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_pause_keeps_partial_and_resume_finishes() {
+        let temp = tempfile::tempdir().unwrap();
+        let output = temp.path().join("model.bin");
+        let (url, requests) = serve(body(), ETAG_V1).await;
+
+        let pause = CancellationToken::new();
+        let pause_on_first_chunk = pause.clone();
+        let result = download(&url, &output, Some(&pause), move |_| {
+            pause_on_first_chunk.cancel()
+        })
+        .await;
+        assert!(matches!(result, Err(AppError::Cancelled)));
+        assert!(!output.exists());
+        let partial = std::fs::metadata(partial_path(&output)).unwrap().len();
+        assert!(partial > 0 && partial < body().len() as u64);
+        assert_eq!(
+            std::fs::read_to_string(validator_path(&output)).unwrap(),
+            ETAG_V1
+        );
+
+        download(&url, &output, None, |_| {}).await.unwrap();
+        assert_eq!(std::fs::read(&output).unwrap(), body());
+        assert!(!partial_path(&output).exists());
+        assert!(!validator_path(&output).exists());
+        let requests = requests.lock().unwrap();
+        assert_eq!(
+            requests[1].as_deref(),
+            Some(format!("bytes={}-", partial).as_str())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_pause_before_start_sends_no_request() {
+        let temp = tempfile::tempdir().unwrap();
+        let output = temp.path().join("model.bin");
+        let (url, requests) = serve(body(), ETAG_V1).await;
+
+        let pause = CancellationToken::new();
+        pause.cancel();
+        let result = download(&url, &output, Some(&pause), |_| {}).await;
+        assert!(matches!(result, Err(AppError::Cancelled)));
+        assert!(requests.lock().unwrap().is_empty());
+        assert!(!partial_path(&output).exists());
+    }
+
+    #[tokio::test]
+    async fn test_changed_file_starts_over() {
+        let temp = tempfile::tempdir().unwrap();
+        let output = temp.path().join("model.bin");
+        std::fs::write(partial_path(&output), b"stale bytes").unwrap();
+        std::fs::write(validator_path(&output), "\"v0\"").unwrap();
+        let (url, _) = serve(body(), ETAG_V1).await;
+
+        download(&url, &output, None, |_| {}).await.unwrap();
+        assert_eq!(std::fs::read(&output).unwrap(), body());
+    }
+
+    #[tokio::test]
+    async fn test_partial_without_validator_starts_over() {
+        let temp = tempfile::tempdir().unwrap();
+        let output = temp.path().join("model.bin");
+        std::fs::write(partial_path(&output), b"stale bytes").unwrap();
+        let (url, requests) = serve(body(), ETAG_V1).await;
+
+        download(&url, &output, None, |_| {}).await.unwrap();
+        assert_eq!(std::fs::read(&output).unwrap(), body());
+        assert_eq!(requests.lock().unwrap()[0], None);
+    }
+
+    #[tokio::test]
+    async fn test_complete_partial_is_finished_on_416() {
+        let temp = tempfile::tempdir().unwrap();
+        let output = temp.path().join("model.bin");
+        std::fs::write(partial_path(&output), body()).unwrap();
+        std::fs::write(validator_path(&output), ETAG_V1).unwrap();
+        let (url, requests) = serve(body(), ETAG_V1).await;
+
+        download(&url, &output, None, |_| {}).await.unwrap();
+        assert_eq!(std::fs::read(&output).unwrap(), body());
+        assert_eq!(requests.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_overlong_partial_starts_over_on_416() {
+        let temp = tempfile::tempdir().unwrap();
+        let output = temp.path().join("model.bin");
+        std::fs::write(partial_path(&output), vec![0u8; body().len() + 10]).unwrap();
+        std::fs::write(validator_path(&output), ETAG_V1).unwrap();
+        let (url, requests) = serve(body(), ETAG_V1).await;
+
+        download(&url, &output, None, |_| {}).await.unwrap();
+        assert_eq!(std::fs::read(&output).unwrap(), body());
+        assert_eq!(requests.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_remove_partial() {
+        let temp = tempfile::tempdir().unwrap();
+        let output = temp.path().join("model.bin");
+        assert!(!remove_partial(&output).await.unwrap());
+
+        std::fs::write(partial_path(&output), b"partial").unwrap();
+        std::fs::write(validator_path(&output), ETAG_V1).unwrap();
+        assert!(remove_partial(&output).await.unwrap());
+        assert!(!partial_path(&output).exists());
+        assert!(!validator_path(&output).exists());
+    }
+}
@@ -0,0 +1,222 @@
+use crate::error::{AppError, Result};
+use crate::services::database::{Database, IndexedSegment};
+use crate::services::cancellation::cancellable;
+use crate::services::llm::OLLAMA;
+use crate::services::ollama::OllamaService;
+use crate::services::openai::OpenAIService;
+use crate::services::openai_compatible::normalize_base_url;
+use crate::services::providers;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use tokio_util::sync::CancellationToken;
+
+/// Texts sent per embedding request
+const EMBED_BATCH_SIZE: usize = 64;
+
+/// A text-embedding backend
+#[async_trait]
+pub trait Embedder: Send + Sync {
+    /// Provider id, e.g. `ollama` or `openai`
+    fn id(&self) -> &'static str;
+
+    /// Model used
+    fn model(&self) -> &str;
+
+    /// Embed texts, one vector per text in input order
+    async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>>;
+
+    /// Key the store files this embedder's vectors under. Vectors of different
+    /// models are not comparable, so the provider is part of it.
+    fn store_key(&self) -> String {
+        format!("{}/{}", self.id(), self.model())
+    }
+}
+
+/// An embedding model served by a local Ollama server
+pub struct OllamaEmbedder {
+    service: OllamaService,
+    model: String,
+}
+
+impl OllamaEmbedder {
+    pub fn new(model: &str) -> Self {
+        Self {
+            service: OllamaService::new(),
+            model: model.to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl Embedder for OllamaEmbedder {
+    fn id(&self) -> &'static str {
+        OLLAMA
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        self.service.embed(&self.model, texts).await
+    }
+}
+
+/// An embedding model behind an OpenAI-protocol API (OpenAI or a self-hosted server)
+pub struct OpenAIEmbedder {
+    id: &'static str,
+    service: OpenAIService,
+    model: String,
+}
+
+impl OpenAIEmbedder {
+    pub fn openai(api_key: &str, model: &str) -> Self {
+        Self {
+            id: providers::OPENAI,
+            service: OpenAIService::new(api_key),
+            model: model.to_string(),
+        }
+    }
+
+    pub fn openai_compatible(base_url: &str, api_key: Option<&str>, model: &str) -> Result<Self> {
+        let base_url = normalize_base_url(base_url)?;
+        Ok(Self {
+            id: providers::OPENAI_COMPATIBLE,
+            service: OpenAIService::with_base_url(api_key.unwrap_or_default(), &base_url),
+            model: model.to_string(),
+        })
+    }
+}
+
+#[async_trait]
+impl Embedder for OpenAIEmbedder {
+    fn id(&self) -> &'static str {
+        self.id
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        self.service.embed(&self.model, texts).await
+    }
+}
+
+/// Result of indexing transcripts into the embedding store
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct IndexStats {
+    pub files: usize,
+    pub segments: usize,
+    /// Segments whose text had to be embedded
+    pub embedded: usize,
+    /// Segments whose vector was already stored
+    pub cached: usize,
+}
+
+/// Hash identifying a segment's text in the store
+pub fn content_hash(text: &str) -> String {
+    Sha256::digest(text.trim().as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Store a vector as little-endian f32s
+pub(crate) fn encode_vector(vector: &[f32]) -> Vec<u8> {
+    vector.iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+pub(crate) fn decode_vector(bytes: &[u8]) -> Vec<f32> {
+    bytes
+        .chunks_exact(4)
+        .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+        .collect()
+}
+
+/// Embed the latest transcription of a media file into the store. Segments whose
+/// text was embedded before with the same model are not embedded again.
+pub async fn index_media(
+    embedder: &dyn Embedder,
+    media_path: &str,
+    cancel: &CancellationToken,
+) -> Result<IndexStats> {
+    let key = embedder.store_key();
+    // The database is reopened after each request: connections can't be held across awaits
+    let (segments, missing) = {
+        let db = Database::open()?;
+        let transcription = db.latest_transcription(media_path)?.ok_or_else(|| {
+            AppError::InvalidInput(format!("No saved transcription for {}", media_path))
+        })?;
+        let segments: Vec<IndexedSegment> = transcription
+            .result
+            .segments
+            .iter()
+            .enumerate()
+            .filter(|(_, s)| !s.text.trim().is_empty())
+            .map(|(i, s)| IndexedSegment {
+                segment: i,
+                start: s.start,
+                end: s.end,
+                content_hash: content_hash(&s.text),
+            })
+            .collect();
+        let texts = transcription.result.segments;
+
+        let mut seen = HashSet::new();
+        let mut missing = Vec::new();
+        for indexed in &segments {
+            if seen.insert(indexed.content_hash.clone())
+                && !db.has_embedding(&indexed.content_hash, &key)?
+            {
+                let text = texts[indexed.segment].text.trim().to_string();
+                missing.push((indexed.content_hash.clone(), text));
+            }
+        }
+        (segments, missing)
+    };
+
+    for batch in missing.chunks(EMBED_BATCH_SIZE) {
+        let texts: Vec<String> = batch.iter().map(|(_, text)| text.clone()).collect();
+        let vectors = cancellable(cancel, embedder.embed(&texts)).await?;
+        if vectors.len() != batch.len() {
+            return Err(AppError::ProcessFailed(format!(
+                "Expected {} embeddings, got {}",
+                batch.len(),
+                vectors.len()
+            )));
+        }
+        let db = Database::open()?;
+        for ((hash, _), vector) in batch.iter().zip(&vectors) {
+            db.save_embedding(hash, &key, vector)?;
+        }
+    }
+
+    Database::open()?.set_indexed_segments(media_path, &key, &segments)?;
+    Ok(IndexStats {
+        files: 1,
+        segments: segments.len(),
+        embedded: missing.len(),
+        cached: segments.len() - missing.len(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_content_hash_ignores_surrounding_whitespace() {
+        assert_eq!(content_hash(" hello "), content_hash("hello"));
+        assert_ne!(content_hash("hello"), content_hash("Hello"));
+        assert_eq!(content_hash("").len(), 64);
+    }
+
+    #[test]
+    fn test_vector_round_trip() {
+        let vector = vec![0.5, -1.25, 3.0e-8];
+        assert_eq!(decode_vector(&encode_vector(&vector)), vector);
+    }
+}
@@ -0,0 +1,203 @@
+use crate::error::Result;
+use crate::services::assemblyai::DetectedEntity;
+use crate::services::llm::{parse_json_response, LlmProvider};
+use crate::services::whisper::TranscriptionSegment;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use tokio_util::sync::CancellationToken;
+
+const ENTITIES_SYSTEM: &str = "You are an archivist who indexes the people, organizations, \
+                               places and products mentioned in recordings. Respond with the \
+                               JSON array only, without any explanation.";
+
+/// Reply budget: a JSON array of names with segment indexes
+const ENTITIES_MAX_TOKENS: u32 = 3000;
+
+/// Entity kinds recorded in the project database
+pub const ENTITY_KINDS: [&str; 4] = ["person", "organization", "location", "product"];
+
+/// A mention of a named entity in a transcript
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EntityMention {
+    /// Name as written in the transcript, e.g. "Ada Lovelace"
+    pub name: String,
+    /// `person`, `organization`, `location` or `product`
+    pub kind: String,
+    pub start: f64,
+    pub end: f64,
+}
+
+/// An entity as returned by the LLM
+#[derive(Debug, Deserialize)]
+struct RawEntity {
+    name: String,
+    kind: String,
+    #[serde(default)]
+    segments: Vec<usize>,
+}
+
+/// Extract the people, organizations, places and products mentioned in
+/// transcript segments, one mention per segment they occur in
+pub async fn extract_entities(
+    llm: &dyn LlmProvider,
+    segments: &[TranscriptionSegment],
+    cancel: &CancellationToken,
+) -> Result<Vec<EntityMention>> {
+    log::info!("[entities] Extracting entities with {} ({})", llm.id(), llm.model());
+    let prompt = build_entities_prompt(segments);
+    let response = llm
+        .complete(ENTITIES_SYSTEM, &prompt, ENTITIES_MAX_TOKENS, cancel)
+        .await?;
+    parse_entities_response(&response, segments)
+}
+
+fn build_entities_prompt(segments: &[TranscriptionSegment]) -> String {
+    let segments_text: Vec<String> = segments
+        .iter()
+        .enumerate()
+        .map(|(i, s)| format!("[{}] {}", i, s.text.trim()))
+        .collect();
+
+    format!(
+        "List the named entities mentioned in this transcript.\n\n\
+         Rules:\n\
+         - kind is one of: {}\n\
+         - name is written as in the transcript, one entry per entity\n\
+         - segments lists the indexes of the segments that mention the entity\n\
+         - Skip generic words and the speakers' labels\n\n\
+         Segments:\n{}\n\n\
+         Response format: [{{\"name\": \"Ada Lovelace\", \"kind\": \"person\", \
+         \"segments\": [2, 7]}}, ...]",
+        ENTITY_KINDS.join(", "),
+        segments_text.join("\n")
+    )
+}
+
+/// Parse the LLM's entities into mentions: unknown kinds and out-of-range
+/// segments are dropped, and segments that literally contain the name are added
+fn parse_entities_response(
+    response: &str,
+    segments: &[TranscriptionSegment],
+) -> Result<Vec<EntityMention>> {
+    let raw: Vec<RawEntity> = parse_json_response(response, "entities")?;
+
+    let mut seen = HashSet::new();
+    let mut mentions = Vec::new();
+    for entity in raw {
+        let name = entity.name.split_whitespace().collect::<Vec<_>>().join(" ");
+        let Some(kind) = normalize_kind(&entity.kind) else {
+            continue;
+        };
+        if name.is_empty() {
+            continue;
+        }
+
+        let needle = name.to_lowercase();
+        let indexes = entity
+            .segments
+            .into_iter()
+            .filter(|&i| i < segments.len())
+            .chain(
+                segments
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, s)| s.text.to_lowercase().contains(&needle))
+                    .map(|(i, _)| i),
+            );
+        for i in indexes {
+            if seen.insert((needle.clone(), kind, i)) {
+                mentions.push(EntityMention {
+                    name: name.clone(),
+                    kind: kind.to_string(),
+                    start: segments[i].start,
+                    end: segments[i].end,
+                });
+            }
+        }
+    }
+
+    mentions.sort_by(|a, b| a.start.total_cmp(&b.start));
+    Ok(mentions)
+}
+
+/// Map the kinds models and AssemblyAI use onto `ENTITY_KINDS`
+fn normalize_kind(kind: &str) -> Option<&'static str> {
+    match kind.trim().to_lowercase().as_str() {
+        "person" | "person_name" | "people" | "per" => Some("person"),
+        "organization" | "organisation" | "org" | "company" => Some("organization"),
+        "location" | "place" | "loc" | "gpe" => Some("location"),
+        "product" | "brand" => Some("product"),
+        _ => None,
+    }
+}
+
+/// Mentions from AssemblyAI's entity detection, keeping the kinds the project
+/// database records
+pub fn from_detected(entities: &[DetectedEntity]) -> Vec<EntityMention> {
+    entities
+        .iter()
+        .filter_map(|e| {
+            Some(EntityMention {
+                name: e.text.trim().to_string(),
+                kind: normalize_kind(&e.kind)?.to_string(),
+                start: e.start,
+                end: e.end,
+            })
+        })
+        .filter(|m| !m.name.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_entities_response() {
+        let segments = vec![
+            TranscriptionSegment::new(0.0, 5.0, "Ada Lovelace worked with Charles Babbage."),
+            TranscriptionSegment::new(5.0, 10.0, "They met in London."),
+            TranscriptionSegment::new(10.0, 15.0, "Lovelace wrote the first program."),
+        ];
+        let response = r#"[
+            {"name": "Ada  Lovelace", "kind": "PERSON", "segments": [0, 2, 9]},
+            {"name": "London", "kind": "place", "segments": []},
+            {"name": "Tuesday", "kind": "date", "segments": [1]}
+        ]"#;
+
+        let mentions = parse_entities_response(response, &segments).unwrap();
+        let found: Vec<(&str, &str, f64)> = mentions
+            .iter()
+            .map(|m| (m.name.as_str(), m.kind.as_str(), m.start))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("Ada Lovelace", "person", 0.0),
+                ("London", "location", 5.0),
+                ("Ada Lovelace", "person", 10.0)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_from_detected() {
+        let detected = vec![
+            DetectedEntity {
+                kind: "person_name".to_string(),
+                text: "Ann".to_string(),
+                start: 1.0,
+                end: 1.4,
+            },
+            DetectedEntity {
+                kind: "phone_number".to_string(),
+                text: "555-0100".to_string(),
+                start: 2.0,
+                end: 3.0,
+            },
+        ];
+        let mentions = from_detected(&detected);
+        assert_eq!(mentions.len(), 1);
+        assert_eq!(mentions[0].kind, "person");
+    }
+}
@@ -0,0 +1,83 @@
+use crate::services::providers::SecretProvider;
+use std::path::{Path, PathBuf};
+
+/// Candidate `.env` files, in lookup order:
+/// the working directory, then the app config directory.
+fn dotenv_paths() -> Vec<PathBuf> {
+    let mut paths = vec![PathBuf::from(".env")];
+    if let Some(config_dir) = dirs::config_dir() {
+        paths.push(config_dir.join("clip-flow").join(".env"));
+    }
+    paths
+}
+
+/// Look up a provider key in the process environment or a `.env` file.
+/// Only consulted when the `env_key_fallback` setting is enabled.
+pub fn lookup(provider: &SecretProvider) -> Option<String> {
+    let key = lookup_var(provider.env_var, &dotenv_paths());
+    if key.is_some() {
+        log::debug!(
+            "[env_keys::lookup] Using {} from environment for {}",
+            provider.env_var,
+            provider.display_name
+        );
+    }
+    key
+}
+
+/// Read a variable from the process environment, falling back to dotenv files.
+/// The process environment is never modified.
+fn lookup_var(name: &str, dotenv_paths: &[PathBuf]) -> Option<String> {
+    if let Some(value) = std::env::var(name).ok().filter(|v| !v.trim().is_empty()) {
+        return Some(value);
+    }
+
+    dotenv_paths
+        .iter()
+        .find_map(|path| read_dotenv_var(path, name))
+}
+
+fn read_dotenv_var(path: &Path, name: &str) -> Option<String> {
+    let iter = dotenvy::from_path_iter(path).ok()?;
+    iter.filter_map(|item| item.ok())
+        .find(|(key, value)| key == name && !value.trim().is_empty())
+        .map(|(_, value)| value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_reads_key_from_dotenv_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join(".env");
+        std::fs::write(
+            &path,
+            "# comment\nCLIP_FLOW_TEST_DOTENV_KEY=\"sk-from-dotenv\"\nOTHER=1\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            lookup_var("CLIP_FLOW_TEST_DOTENV_KEY", &[path]),
+            Some("sk-from-dotenv".to_string())
+        );
+    }
+
+    #[test]
+    fn test_missing_files_and_empty_values_are_skipped() {
+        let temp_dir = TempDir::new().unwrap();
+        let empty = temp_dir.path().join("empty.env");
+        let filled = temp_dir.path().join("filled.env");
+        std::fs::write(&empty, "CLIP_FLOW_TEST_EMPTY_KEY=\n").unwrap();
+        std::fs::write(&filled, "CLIP_FLOW_TEST_EMPTY_KEY=sk-second\n").unwrap();
+
+        let paths = [temp_dir.path().join("missing.env"), empty, filled];
+        assert_eq!(
+            lookup_var("CLIP_FLOW_TEST_EMPTY_KEY", &paths),
+            Some("sk-second".to_string())
+        );
+        assert_eq!(lookup_var("CLIP_FLOW_TEST_UNSET_KEY", &paths), None);
+    }
+}
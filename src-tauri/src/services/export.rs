@@ -0,0 +1,948 @@
+use crate::error::{AppError, Result};
+use crate::services::whisper::{TranscriptionSegment, TranscriptionWord};
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+
+/// Horizontal alignment of a WebVTT cue
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CueAlign {
+    Start,
+    Center,
+    End,
+    Left,
+    Right,
+}
+
+impl CueAlign {
+    fn as_str(self) -> &'static str {
+        match self {
+            CueAlign::Start => "start",
+            CueAlign::Center => "center",
+            CueAlign::End => "end",
+            CueAlign::Left => "left",
+            CueAlign::Right => "right",
+        }
+    }
+}
+
+/// Options for WebVTT export
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VttOptions {
+    /// Number cues (1, 2, ...) so players and editors can reference them
+    pub cue_ids: bool,
+    /// Wrap text of segments with a known speaker in `<v Speaker>` voice tags
+    pub voice_tags: bool,
+    /// Vertical position as a percentage of the video height
+    pub line: Option<f32>,
+    /// Horizontal position as a percentage of the video width
+    pub position: Option<f32>,
+    pub align: Option<CueAlign>,
+}
+
+impl VttOptions {
+    /// Cue settings appended after the timing line, e.g. ` line:90% align:center`
+    fn cue_settings(&self) -> String {
+        let mut settings = String::new();
+        if let Some(line) = self.line {
+            let _ = write!(settings, " line:{}%", line.clamp(0.0, 100.0));
+        }
+        if let Some(position) = self.position {
+            let _ = write!(settings, " position:{}%", position.clamp(0.0, 100.0));
+        }
+        if let Some(align) = self.align {
+            let _ = write!(settings, " align:{}", align.as_str());
+        }
+        settings
+    }
+}
+
+/// Format seconds as a WebVTT timestamp (`HH:MM:SS.mmm`)
+pub fn vtt_timestamp(seconds: f64) -> String {
+    let millis = (seconds.max(0.0) * 1000.0).round() as u64;
+    format!(
+        "{:02}:{:02}:{:02}.{:03}",
+        millis / 3_600_000,
+        (millis / 60_000) % 60,
+        (millis / 1000) % 60,
+        millis % 1000
+    )
+}
+
+/// Escape the characters WebVTT cue text reserves for markup
+fn escape_vtt(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Render segments as a WebVTT document
+pub fn to_vtt(segments: &[TranscriptionSegment], options: &VttOptions) -> String {
+    let settings = options.cue_settings();
+    let mut vtt = String::from("WEBVTT\n");
+
+    // Empty segments are skipped, so cues are numbered by themselves
+    let cues = segments
+        .iter()
+        .map(|segment| (segment, segment.text.trim()))
+        .filter(|(_, text)| !text.is_empty());
+    for (i, (segment, text)) in cues.enumerate() {
+        vtt.push('\n');
+        if options.cue_ids {
+            let _ = writeln!(vtt, "{}", i + 1);
+        }
+        let _ = writeln!(
+            vtt,
+            "{} --> {}{}",
+            vtt_timestamp(segment.start),
+            vtt_timestamp(segment.end),
+            settings
+        );
+
+        match segment.speaker.as_deref().filter(|_| options.voice_tags) {
+            // Voice tag annotations end at the closing `>`, so it is dropped from names
+            Some(speaker) => {
+                let _ = writeln!(vtt, "<v {}>{}", speaker.replace('>', ""), escape_vtt(text));
+            }
+            None => {
+                let _ = writeln!(vtt, "{}", escape_vtt(text));
+            }
+        }
+    }
+
+    vtt
+}
+
+/// Options for SRT export
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SrtOptions {
+    /// Wrap text at this many characters per line
+    pub max_line_chars: usize,
+    /// Lines per subtitle; longer text is split into subtitles that share the
+    /// segment's time in proportion to their length
+    pub max_lines: usize,
+}
+
+impl Default for SrtOptions {
+    fn default() -> Self {
+        Self {
+            max_line_chars: 42,
+            max_lines: 2,
+        }
+    }
+}
+
+/// Format seconds as an SRT timestamp (`HH:MM:SS,mmm`)
+pub fn srt_timestamp(seconds: f64) -> String {
+    vtt_timestamp(seconds).replace('.', ",")
+}
+
+/// Break text into lines of at most `max_chars` characters at spaces. Words
+/// longer than a line get a line of their own.
+fn wrap_lines(text: &str, max_chars: usize) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for word in text.split_whitespace() {
+        match lines.last_mut() {
+            Some(line) if line.chars().count() + 1 + word.chars().count() <= max_chars => {
+                line.push(' ');
+                line.push_str(word);
+            }
+            _ => lines.push(word.to_string()),
+        }
+    }
+    lines
+}
+
+/// Render segments as a SubRip (SRT) document, numbering subtitles from 1
+pub fn to_srt(segments: &[TranscriptionSegment], options: &SrtOptions) -> String {
+    let max_chars = options.max_line_chars.max(1);
+    let max_lines = options.max_lines.max(1);
+    let mut srt = String::new();
+    let mut number = 0;
+
+    for segment in segments {
+        let lines = wrap_lines(segment.text.trim(), max_chars);
+        let total_chars: usize = lines.iter().map(|line| line.chars().count()).sum();
+        let duration = (segment.end - segment.start).max(0.0);
+        let mut start = segment.start;
+
+        for block in lines.chunks(max_lines) {
+            let chars: usize = block.iter().map(|line| line.chars().count()).sum();
+            let end = start + duration * chars as f64 / total_chars as f64;
+            number += 1;
+            if number > 1 {
+                srt.push('\n');
+            }
+            let _ = writeln!(srt, "{}", number);
+            let _ = writeln!(srt, "{} --> {}", srt_timestamp(start), srt_timestamp(end));
+            let _ = writeln!(srt, "{}", block.join("\n"));
+            start = end;
+        }
+    }
+
+    srt
+}
+
+/// Options for Markdown export
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MarkdownOptions {
+    /// Document title (usually the media file name)
+    pub title: Option<String>,
+    /// Summary placed before the transcript
+    pub summary: Option<String>,
+    /// Start a new timestamp heading once a section spans this many seconds
+    pub section_seconds: f64,
+    /// Bookmarked moments listed before the transcript
+    pub bookmarks: Vec<Moment>,
+}
+
+impl Default for MarkdownOptions {
+    fn default() -> Self {
+        Self {
+            title: None,
+            summary: None,
+            section_seconds: 60.0,
+            bookmarks: Vec::new(),
+        }
+    }
+}
+
+/// Format seconds as a clock position (`M:SS`, or `H:MM:SS` past an hour)
+pub fn clock_timestamp(seconds: f64) -> String {
+    let total = seconds.max(0.0) as u64;
+    let (hours, minutes, secs) = (total / 3600, (total / 60) % 60, total % 60);
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, secs)
+    } else {
+        format!("{}:{:02}", minutes, secs)
+    }
+}
+
+/// Group consecutive segments of the same speaker into paragraphs
+fn paragraphs(segments: &[TranscriptionSegment]) -> Vec<(Option<&str>, String)> {
+    let mut paragraphs: Vec<(Option<&str>, String)> = Vec::new();
+
+    for segment in segments {
+        let text = segment.text.trim();
+        if text.is_empty() {
+            continue;
+        }
+        let speaker = segment.speaker.as_deref();
+
+        match paragraphs.last_mut() {
+            Some((last_speaker, paragraph)) if *last_speaker == speaker => {
+                paragraph.push(' ');
+                paragraph.push_str(text);
+            }
+            _ => paragraphs.push((speaker, text.to_string())),
+        }
+    }
+    paragraphs
+}
+
+/// Render the transcript as plain text, one paragraph per speaker turn
+pub fn to_text(segments: &[TranscriptionSegment]) -> String {
+    let mut text = String::new();
+    for (speaker, paragraph) in paragraphs(segments) {
+        if !text.is_empty() {
+            text.push('\n');
+        }
+        match speaker {
+            Some(speaker) => {
+                let _ = writeln!(text, "{}: {}", speaker, paragraph);
+            }
+            None => {
+                let _ = writeln!(text, "{}", paragraph);
+            }
+        }
+    }
+    text
+}
+
+/// Render the transcript as Markdown: optional title and summary,
+/// then the transcript under timestamp headings with bold speaker labels
+pub fn to_markdown(segments: &[TranscriptionSegment], options: &MarkdownOptions) -> String {
+    let mut md = String::new();
+
+    if let Some(title) = options.title.as_deref().filter(|t| !t.trim().is_empty()) {
+        let _ = writeln!(md, "# {}\n", title.trim());
+    }
+    if let Some(summary) = options.summary.as_deref().filter(|s| !s.trim().is_empty()) {
+        let _ = writeln!(md, "## Summary\n\n{}\n", summary.trim());
+    }
+    if !options.bookmarks.is_empty() {
+        md.push_str("## Bookmarks\n\n");
+        let mut bookmarks: Vec<_> = options.bookmarks.iter().collect();
+        bookmarks.sort_by(|a, b| a.start.total_cmp(&b.start));
+        for bookmark in bookmarks {
+            let time = match bookmark.end {
+                Some(end) => {
+                    format!("{}-{}", clock_timestamp(bookmark.start), clock_timestamp(end))
+                }
+                None => clock_timestamp(bookmark.start),
+            };
+            let _ = writeln!(md, "- **{}** {}", time, bookmark.label.trim());
+        }
+        md.push('\n');
+    }
+    md.push_str("## Transcript\n");
+
+    // Split into sections of roughly `section_seconds` each
+    let mut sections = Vec::new();
+    let mut first = 0;
+    for i in 1..segments.len() {
+        if segments[i].start - segments[first].start >= options.section_seconds {
+            sections.push(&segments[first..i]);
+            first = i;
+        }
+    }
+    if first < segments.len() {
+        sections.push(&segments[first..]);
+    }
+
+    for section in sections {
+        let body: Vec<String> = paragraphs(section)
+            .into_iter()
+            .map(|(speaker, paragraph)| match speaker {
+                Some(speaker) => format!("**{}:** {}", speaker, paragraph),
+                None => paragraph,
+            })
+            .collect();
+        if body.is_empty() {
+            continue;
+        }
+        let _ = writeln!(md, "\n### {}\n", clock_timestamp(section[0].start));
+        let _ = writeln!(md, "{}", body.join("\n\n"));
+    }
+
+    md
+}
+
+/// Round seconds to whole milliseconds, the precision every export format uses
+fn round_millis(seconds: f64) -> f64 {
+    (seconds.max(0.0) * 1000.0).round() / 1000.0
+}
+
+/// Quote a CSV field when it contains a delimiter, quote or line break (RFC 4180)
+pub(crate) fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Render segments as CSV with a `start,end,speaker,text,confidence` header.
+/// Times are in seconds; unknown speakers and confidences are left empty.
+pub fn to_csv(segments: &[TranscriptionSegment]) -> String {
+    let mut csv = String::from("start,end,speaker,text,confidence\r\n");
+
+    for segment in segments {
+        let text = segment.text.trim();
+        if text.is_empty() {
+            continue;
+        }
+        let _ = write!(
+            csv,
+            "{:.3},{:.3},{},{},",
+            round_millis(segment.start),
+            round_millis(segment.end),
+            csv_field(segment.speaker.as_deref().unwrap_or("")),
+            csv_field(text)
+        );
+        if let Some(confidence) = segment.confidence {
+            let _ = write!(csv, "{:.4}", confidence);
+        }
+        csv.push_str("\r\n");
+    }
+
+    csv
+}
+
+/// A segment in the canonical JSON export. Every key is always present,
+/// with `null` for unknown values, so consumers can rely on a fixed shape.
+#[derive(Serialize)]
+struct SegmentRecord<'a> {
+    start: f64,
+    end: f64,
+    speaker: Option<&'a str>,
+    text: &'a str,
+    confidence: Option<f64>,
+}
+
+/// Render segments as a JSON array of `{start, end, speaker, text, confidence}` objects
+pub fn to_segments_json(segments: &[TranscriptionSegment]) -> Result<String> {
+    let records: Vec<SegmentRecord> = segments
+        .iter()
+        .filter(|segment| !segment.text.trim().is_empty())
+        .map(|segment| SegmentRecord {
+            start: round_millis(segment.start),
+            end: round_millis(segment.end),
+            speaker: segment.speaker.as_deref(),
+            text: segment.text.trim(),
+            confidence: segment.confidence,
+        })
+        .collect();
+
+    Ok(serde_json::to_string_pretty(&records)?)
+}
+
+/// Words of a segment with their timings. Recorded timings are used while
+/// they still match the segment's words (edits may have changed the text);
+/// otherwise the segment's time is shared out in proportion to word length.
+pub fn timed_words(segment: &TranscriptionSegment) -> Vec<TranscriptionWord> {
+    let words: Vec<&str> = segment.text.split_whitespace().collect();
+    if !segment.words.is_empty() && segment.words.len() == words.len() {
+        return segment
+            .words
+            .iter()
+            .zip(words)
+            .map(|(timed, text)| TranscriptionWord {
+                start: timed.start,
+                end: timed.end.max(timed.start),
+                text: text.to_string(),
+            })
+            .collect();
+    }
+
+    let total: usize = words.iter().map(|word| word.chars().count()).sum();
+    let duration = (segment.end - segment.start).max(0.0);
+    let mut start = segment.start;
+    words
+        .into_iter()
+        .map(|text| {
+            let share = text.chars().count() as f64 / total as f64;
+            let end = start + duration * share;
+            let word = TranscriptionWord {
+                start,
+                end,
+                text: text.to_string(),
+            };
+            start = end;
+            word
+        })
+        .collect()
+}
+
+/// A segment in the word-timed JSON export
+#[derive(Serialize)]
+struct WordSegmentRecord<'a> {
+    start: f64,
+    end: f64,
+    speaker: Option<&'a str>,
+    text: &'a str,
+    words: Vec<WordRecord>,
+}
+
+#[derive(Serialize)]
+struct WordRecord {
+    start: f64,
+    end: f64,
+    text: String,
+}
+
+/// Render segments as a JSON array of `{start, end, speaker, text, words}`
+/// objects, `words` holding `{start, end, text}` for animated captions
+pub fn to_words_json(segments: &[TranscriptionSegment]) -> Result<String> {
+    let records: Vec<WordSegmentRecord> = segments
+        .iter()
+        .filter(|segment| !segment.text.trim().is_empty())
+        .map(|segment| WordSegmentRecord {
+            start: round_millis(segment.start),
+            end: round_millis(segment.end),
+            speaker: segment.speaker.as_deref(),
+            text: segment.text.trim(),
+            words: timed_words(segment)
+                .into_iter()
+                .map(|word| WordRecord {
+                    start: round_millis(word.start),
+                    end: round_millis(word.end),
+                    text: word.text,
+                })
+                .collect(),
+        })
+        .collect();
+
+    Ok(serde_json::to_string_pretty(&records)?)
+}
+
+/// Options for karaoke ASS export
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KaraokeOptions {
+    pub font: String,
+    /// Font size in pixels of the script resolution
+    pub font_size: u32,
+    /// Colour of words already spoken, as `#RRGGBB`
+    pub highlight_color: String,
+    /// Colour of words not spoken yet, as `#RRGGBB`
+    pub base_color: String,
+    /// Video size the font size and margin refer to (vertical 1080x1920 by default)
+    pub width: u32,
+    pub height: u32,
+    /// Distance of the captions from the bottom edge
+    pub margin_bottom: u32,
+    /// Start a new caption after this many words; 0 keeps segments whole
+    pub max_words: usize,
+}
+
+impl Default for KaraokeOptions {
+    fn default() -> Self {
+        Self {
+            font: "Arial".to_string(),
+            font_size: 80,
+            highlight_color: "#FFD400".to_string(),
+            base_color: "#FFFFFF".to_string(),
+            width: 1080,
+            height: 1920,
+            margin_bottom: 320,
+            max_words: 4,
+        }
+    }
+}
+
+/// Format seconds as an ASS timestamp (`H:MM:SS.cc`)
+fn ass_timestamp(seconds: f64) -> String {
+    let centis = centiseconds(seconds);
+    format!(
+        "{}:{:02}:{:02}.{:02}",
+        centis / 360_000,
+        (centis / 6000) % 60,
+        (centis / 100) % 60,
+        centis % 100
+    )
+}
+
+fn centiseconds(seconds: f64) -> u64 {
+    (seconds.max(0.0) * 100.0).round() as u64
+}
+
+/// `#RRGGBB` as an ASS colour (`&H00BBGGRR`)
+fn ass_color(color: &str) -> Result<String> {
+    let hex = color.trim().trim_start_matches('#');
+    if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(AppError::InvalidInput(format!("Invalid colour: {}", color)));
+    }
+    let hex = hex.to_ascii_uppercase();
+    Ok(format!("&H00{}{}{}", &hex[4..6], &hex[2..4], &hex[0..2]))
+}
+
+/// Make text safe for ASS: braces would open override tags and a backslash
+/// start an escape
+fn ass_text(text: &str) -> String {
+    text.replace('\\', "/").replace('{', "(").replace('}', ")")
+}
+
+/// Render segments as an Advanced SubStation Alpha script with `\k` karaoke
+/// tags, so each word lights up as it is spoken
+pub fn to_karaoke_ass(segments: &[TranscriptionSegment], options: &KaraokeOptions) -> Result<String> {
+    let highlight = ass_color(&options.highlight_color)?;
+    let base = ass_color(&options.base_color)?;
+    // Commas end the style and name fields
+    let field = |value: &str| ass_text(value).replace(',', " ");
+
+    let mut ass = String::from("[Script Info]\nScriptType: v4.00+\n");
+    let _ = writeln!(ass, "PlayResX: {}\nPlayResY: {}", options.width, options.height);
+    ass.push_str("WrapStyle: 0\nScaledBorderAndShadow: yes\n\n");
+    ass.push_str(
+        "[V4+ Styles]\n\
+         Format: Name, Fontname, Fontsize, PrimaryColour, SecondaryColour, OutlineColour, \
+         BackColour, Bold, Italic, Underline, StrikeOut, ScaleX, ScaleY, Spacing, Angle, \
+         BorderStyle, Outline, Shadow, Alignment, MarginL, MarginR, MarginV, Encoding\n",
+    );
+    let _ = writeln!(
+        ass,
+        "Style: Karaoke,{},{},{},{},&H00000000,&H80000000,-1,0,0,0,100,100,0,0,1,4,0,2,60,60,{},1\n",
+        field(&options.font),
+        options.font_size,
+        highlight,
+        base,
+        options.margin_bottom
+    );
+    ass.push_str(
+        "[Events]\nFormat: Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text\n",
+    );
+
+    for segment in segments {
+        let words = timed_words(segment);
+        let chunk_size = if options.max_words == 0 { words.len().max(1) } else { options.max_words };
+        let speaker = field(segment.speaker.as_deref().unwrap_or(""));
+
+        for chunk in words.chunks(chunk_size) {
+            let (Some(first), Some(last)) = (chunk.first(), chunk.last()) else {
+                continue;
+            };
+            // Each word is highlighted until the next one starts, so pauses
+            // don't shift the later words
+            let text: Vec<String> = chunk
+                .iter()
+                .enumerate()
+                .map(|(i, word)| {
+                    let until = chunk.get(i + 1).map_or(word.end, |next| next.start);
+                    let duration = centiseconds(until).saturating_sub(centiseconds(word.start));
+                    format!("{{\\k{}}}{}", duration, ass_text(&word.text))
+                })
+                .collect();
+            let _ = writeln!(
+                ass,
+                "Dialogue: 0,{},{},Karaoke,{},0,0,0,,{}",
+                ass_timestamp(first.start),
+                ass_timestamp(last.end),
+                speaker,
+                text.join(" ")
+            );
+        }
+    }
+
+    Ok(ass)
+}
+
+/// A notable moment marked alongside the segment boundaries in marker exports
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Moment {
+    pub start: f64,
+    /// End of the moment; point markers have none
+    #[serde(default)]
+    pub end: Option<f64>,
+    pub label: String,
+}
+
+/// A marker of an audio editor export, in seconds
+struct AudioMarker {
+    start: f64,
+    end: f64,
+    label: String,
+}
+
+/// One marker per segment plus the given moments, ordered by start time.
+/// Labels are kept on one line since both formats are line and tab based.
+fn audio_markers(segments: &[TranscriptionSegment], moments: &[Moment]) -> Vec<AudioMarker> {
+    let single_line = |text: &str| text.split_whitespace().collect::<Vec<_>>().join(" ");
+
+    let mut markers: Vec<AudioMarker> = segments
+        .iter()
+        .filter(|segment| !segment.text.trim().is_empty())
+        .map(|segment| AudioMarker {
+            start: segment.start,
+            end: segment.end.max(segment.start),
+            label: match &segment.speaker {
+                Some(speaker) => format!("{}: {}", speaker, single_line(&segment.text)),
+                None => single_line(&segment.text),
+            },
+        })
+        .chain(moments.iter().map(|moment| AudioMarker {
+            start: moment.start,
+            end: moment.end.unwrap_or(moment.start).max(moment.start),
+            label: single_line(&moment.label),
+        }))
+        .collect();
+
+    markers.sort_by(|a, b| a.start.total_cmp(&b.start));
+    markers
+}
+
+/// Render segments and moments as an Audacity label track
+/// (`start<TAB>end<TAB>label` per line, in seconds)
+pub fn to_audacity_labels(segments: &[TranscriptionSegment], moments: &[Moment]) -> String {
+    let mut labels = String::new();
+    for marker in audio_markers(segments, moments) {
+        let _ = writeln!(
+            labels,
+            "{:.6}\t{:.6}\t{}",
+            marker.start.max(0.0),
+            marker.end.max(0.0),
+            marker.label
+        );
+    }
+    labels
+}
+
+/// Format seconds in Audition's decimal marker notation (`M:SS.mmm`, or `H:MM:SS.mmm`)
+fn audition_time(seconds: f64) -> String {
+    let millis = (seconds.max(0.0) * 1000.0).round() as u64;
+    let (hours, minutes, secs) = (millis / 3_600_000, (millis / 60_000) % 60, (millis / 1000) % 60);
+    if hours > 0 {
+        format!("{}:{:02}:{:02}.{:03}", hours, minutes, secs, millis % 1000)
+    } else {
+        format!("{}:{:02}.{:03}", minutes, secs, millis % 1000)
+    }
+}
+
+/// Render segments and moments as an Adobe Audition marker list, the
+/// tab-separated "CSV" Audition reads from Markers > Import Markers
+pub fn to_audition_markers(segments: &[TranscriptionSegment], moments: &[Moment]) -> String {
+    let mut csv = String::from("Name\tStart\tDuration\tTime Format\tType\tDescription\n");
+    for marker in audio_markers(segments, moments) {
+        let _ = writeln!(
+            csv,
+            "{}\t{}\t{}\tdecimal\tCue\t",
+            marker.label,
+            audition_time(marker.start),
+            audition_time(marker.end - marker.start)
+        );
+    }
+    csv
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn word(start: f64, end: f64, text: &str) -> TranscriptionWord {
+        TranscriptionWord {
+            start,
+            end,
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_to_csv() {
+        let mut first =
+            TranscriptionSegment::new(0.0, 1.25, "Hello, \"world\"").with_speaker("Alex");
+        first.confidence = Some(0.91234);
+        let segments = vec![first, TranscriptionSegment::new(1.25, 3.0, "Bye")];
+
+        assert_eq!(
+            to_csv(&segments),
+            "start,end,speaker,text,confidence\r\n\
+             0.000,1.250,Alex,\"Hello, \"\"world\"\"\",0.9123\r\n\
+             1.250,3.000,,Bye,\r\n"
+        );
+    }
+
+    #[test]
+    fn test_to_segments_json() {
+        let mut first = TranscriptionSegment::new(0.0, 1.2504, " Hi ").with_speaker("Alex");
+        first.confidence = Some(0.5);
+        let segments = vec![first, TranscriptionSegment::new(1.25, 2.0, "Bye")];
+
+        let json: serde_json::Value =
+            serde_json::from_str(&to_segments_json(&segments).unwrap()).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!([
+                {"start": 0.0, "end": 1.25, "speaker": "Alex", "text": "Hi", "confidence": 0.5},
+                {"start": 1.25, "end": 2.0, "speaker": null, "text": "Bye", "confidence": null},
+            ])
+        );
+    }
+
+    #[test]
+    fn test_timed_words() {
+        let mut timed = TranscriptionSegment::new(1.0, 3.0, "Hello brave world");
+        timed.words = vec![
+            word(1.0, 1.4, "Helo"),
+            word(1.5, 2.0, "brave"),
+            word(2.2, 3.0, "world"),
+        ];
+        let words = timed_words(&timed);
+        assert_eq!(words[0].text, "Hello");
+        assert_eq!((words[2].start, words[2].end), (2.2, 3.0));
+
+        // Without matching timings the time is shared out by word length
+        let words = timed_words(&TranscriptionSegment::new(0.0, 3.0, "a bb"));
+        assert_eq!((words[0].start, words[0].end), (0.0, 1.0));
+        assert_eq!((words[1].start, words[1].end), (1.0, 3.0));
+    }
+
+    #[test]
+    fn test_to_karaoke_ass() {
+        let mut first = TranscriptionSegment::new(0.0, 2.0, "Hi {there} you").with_speaker("Alex");
+        first.words = vec![
+            word(0.0, 0.4, "Hi"),
+            word(0.5, 1.2, "{there}"),
+            word(1.2, 1.9, "you"),
+        ];
+        let options = KaraokeOptions {
+            max_words: 2,
+            ..Default::default()
+        };
+
+        let ass = to_karaoke_ass(&[first], &options).unwrap();
+        assert!(ass.contains("PlayResY: 1920\n"));
+        assert!(ass.contains("Style: Karaoke,Arial,80,&H0000D4FF,&H00FFFFFF,"));
+        assert!(ass.ends_with(
+            "Dialogue: 0,0:00:00.00,0:00:01.20,Karaoke,Alex,0,0,0,,{\\k50}Hi {\\k70}(there)\n\
+             Dialogue: 0,0:00:01.20,0:00:01.90,Karaoke,Alex,0,0,0,,{\\k70}you\n"
+        ));
+
+        let bad_color = KaraokeOptions {
+            highlight_color: "yellow".to_string(),
+            ..Default::default()
+        };
+        assert!(to_karaoke_ass(&[], &bad_color).is_err());
+    }
+
+    #[test]
+    fn test_to_words_json() {
+        let segments = vec![TranscriptionSegment::new(0.0, 1.0, " Hi you ")];
+        let json: serde_json::Value =
+            serde_json::from_str(&to_words_json(&segments).unwrap()).unwrap();
+        assert_eq!(json[0]["text"], "Hi you");
+        assert_eq!(
+            json[0]["words"],
+            serde_json::json!([
+                {"start": 0.0, "end": 0.5, "text": "Hi"},
+                {"start": 0.5, "end": 1.0, "text": "you"},
+            ])
+        );
+    }
+
+    #[test]
+    fn test_clock_timestamp() {
+        assert_eq!(clock_timestamp(0.0), "0:00");
+        assert_eq!(clock_timestamp(75.9), "1:15");
+        assert_eq!(clock_timestamp(3723.0), "1:02:03");
+    }
+
+    #[test]
+    fn test_to_text_groups_speaker_turns() {
+        let segments = vec![
+            TranscriptionSegment::new(0.0, 1.0, "Hi.").with_speaker("Alex"),
+            TranscriptionSegment::new(1.0, 2.0, "How are you?").with_speaker("Alex"),
+            TranscriptionSegment::new(2.0, 3.0, "Fine.").with_speaker("Sam"),
+        ];
+        assert_eq!(to_text(&segments), "Alex: Hi. How are you?\n\nSam: Fine.\n");
+
+        let unlabeled = vec![
+            TranscriptionSegment::new(0.0, 1.0, "One."),
+            TranscriptionSegment::new(1.0, 2.0, "Two."),
+        ];
+        assert_eq!(to_text(&unlabeled), "One. Two.\n");
+    }
+
+    #[test]
+    fn test_to_markdown() {
+        let segments = vec![
+            TranscriptionSegment::new(0.0, 10.0, "Welcome.").with_speaker("Alex"),
+            TranscriptionSegment::new(10.0, 20.0, "Thanks.").with_speaker("Sam"),
+            TranscriptionSegment::new(65.0, 70.0, "Next topic.").with_speaker("Alex"),
+        ];
+        let options = MarkdownOptions {
+            title: Some("interview.mp4".to_string()),
+            summary: Some("Two people talk.".to_string()),
+            bookmarks: vec![
+                Moment {
+                    start: 65.0,
+                    end: Some(70.0),
+                    label: "New topic".to_string(),
+                },
+                Moment {
+                    start: 3.0,
+                    end: None,
+                    label: "Intro".to_string(),
+                },
+            ],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            to_markdown(&segments, &options),
+            "# interview.mp4\n\n\
+             ## Summary\n\nTwo people talk.\n\n\
+             ## Bookmarks\n\n- **0:03** Intro\n- **1:05-1:10** New topic\n\n\
+             ## Transcript\n\
+             \n### 0:00\n\n**Alex:** Welcome.\n\n**Sam:** Thanks.\n\
+             \n### 1:05\n\n**Alex:** Next topic.\n"
+        );
+    }
+
+    #[test]
+    fn test_vtt_timestamp() {
+        assert_eq!(vtt_timestamp(0.0), "00:00:00.000");
+        assert_eq!(vtt_timestamp(61.5), "00:01:01.500");
+        assert_eq!(vtt_timestamp(3723.0456), "01:02:03.046");
+        assert_eq!(vtt_timestamp(-1.0), "00:00:00.000");
+    }
+
+    #[test]
+    fn test_to_srt() {
+        let segments = vec![
+            TranscriptionSegment::new(0.0, 1.5, " Hello world "),
+            TranscriptionSegment::new(1.5, 1.6, "  "),
+            TranscriptionSegment::new(3661.0, 3665.0, "one two three four five six"),
+        ];
+        let options = SrtOptions {
+            max_line_chars: 9,
+            max_lines: 2,
+        };
+
+        assert_eq!(
+            to_srt(&segments, &options),
+            "1\n00:00:00,000 --> 00:00:01,500\nHello\nworld\n\
+             \n2\n01:01:01,000 --> 01:01:03,000\none two\nthree\n\
+             \n3\n01:01:03,000 --> 01:01:05,000\nfour five\nsix\n"
+        );
+    }
+
+    #[test]
+    fn test_to_vtt_plain() {
+        let segments = vec![
+            TranscriptionSegment::new(0.0, 1.5, " Hello & welcome "),
+            TranscriptionSegment::new(1.5, 2.0, "   "),
+            TranscriptionSegment::new(2.0, 4.25, "Use <b> tags"),
+        ];
+
+        assert_eq!(
+            to_vtt(&segments, &VttOptions::default()),
+            "WEBVTT\n\
+             \n00:00:00.000 --> 00:00:01.500\nHello &amp; welcome\n\
+             \n00:00:02.000 --> 00:00:04.250\nUse &lt;b&gt; tags\n"
+        );
+    }
+
+    #[test]
+    fn test_to_vtt_with_options() {
+        let segments = vec![
+            TranscriptionSegment::new(0.0, 1.0, "Hi there").with_speaker("Alex"),
+            TranscriptionSegment::new(1.0, 1.0, "  "),
+            TranscriptionSegment::new(1.0, 2.0, "Hello"),
+        ];
+        let options = VttOptions {
+            cue_ids: true,
+            voice_tags: true,
+            line: Some(90.0),
+            position: None,
+            align: Some(CueAlign::Center),
+        };
+
+        assert_eq!(
+            to_vtt(&segments, &options),
+            "WEBVTT\n\
+             \n1\n00:00:00.000 --> 00:00:01.000 line:90% align:center\n<v Alex>Hi there\n\
+             \n2\n00:00:01.000 --> 00:00:02.000 line:90% align:center\nHello\n"
+        );
+    }
+
+    #[test]
+    fn test_audio_marker_exports() {
+        let segments = vec![
+            TranscriptionSegment::new(0.0, 2.5, "Welcome\tback").with_speaker("Alex"),
+            TranscriptionSegment::new(65.25, 70.0, "Sponsor read"),
+        ];
+        let moments = vec![Moment {
+            start: 30.0,
+            end: None,
+            label: "Great\nquote".to_string(),
+        }];
+
+        assert_eq!(
+            to_audacity_labels(&segments, &moments),
+            "0.000000\t2.500000\tAlex: Welcome back\n\
+             30.000000\t30.000000\tGreat quote\n\
+             65.250000\t70.000000\tSponsor read\n"
+        );
+        assert_eq!(
+            to_audition_markers(&segments, &moments),
+            "Name\tStart\tDuration\tTime Format\tType\tDescription\n\
+             Alex: Welcome back\t0:00.000\t0:02.500\tdecimal\tCue\t\n\
+             Great quote\t0:30.000\t0:00.000\tdecimal\tCue\t\n\
+             Sponsor read\t1:05.250\t0:04.750\tdecimal\tCue\t\n"
+        );
+        assert_eq!(audition_time(3723.5), "1:02:03.500");
+    }
+}
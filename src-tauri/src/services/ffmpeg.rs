@@ -1,11 +1,16 @@
 use crate::error::{AppError, Result};
+use crate::services::audio_filters::AudioFilters;
+use crate::services::concurrency::{self, JobKind};
+use crate::services::os_path;
+use crate::services::processes::{self, ProcessKind};
 use std::path::{Path, PathBuf};
 use std::process::Stdio;
 use tokio::io::{AsyncBufReadExt, BufReader};
 use tokio::process::Command;
+use tokio_util::sync::CancellationToken;
 
 /// Find FFmpeg binary path, checking common installation locations
-fn find_ffmpeg_path() -> PathBuf {
+pub(crate) fn find_ffmpeg_path() -> PathBuf {
     let binary_name = if cfg!(target_os = "windows") { "ffmpeg.exe" } else { "ffmpeg" };
     
     let mut possible_paths: Vec<PathBuf> = Vec::new();
@@ -129,7 +134,7 @@ impl FFmpegService {
             .arg("-version")
             .output()
             .await
-            .map_err(|e| AppError::FFmpeg(format!("Failed to run ffmpeg: {}", e)))?;
+            .map_err(|e| AppError::spawn_failed("ffmpeg", e, AppError::FFmpeg))?;
 
         if output.status.success() {
             let version = String::from_utf8_lossy(&output.stdout);
@@ -140,42 +145,63 @@ impl FFmpegService {
         }
     }
 
-    /// Extract audio from a video/audio file to WAV format (16kHz mono for Whisper)
+    /// Extract audio from a video/audio file to WAV format (16kHz mono for Whisper),
+    /// running it through `filters` on the way.
+    /// Cancelling the token kills ffmpeg and removes the partial output.
     pub async fn extract_audio<F>(
         input_path: &Path,
         output_path: &Path,
+        filters: &AudioFilters,
+        cancel: &CancellationToken,
         on_progress: F,
     ) -> Result<PathBuf>
     where
         F: Fn(f32) + Send + 'static,
     {
+        let filter_chain = filters.filter_chain()?;
+        let _slot = concurrency::acquire(JobKind::Extraction, cancel).await?;
+
         // First get duration for progress calculation
         let duration = Self::get_duration(input_path).await?;
 
         let ffmpeg_path = find_ffmpeg_path();
         let mut child = Command::new(&ffmpeg_path)
+            .arg("-i")
+            .arg(os_path::command_arg(input_path))
+            .args(filter_chain.iter().flat_map(|chain| ["-af", chain.as_str()]))
             .args([
-                "-i",
-                input_path.to_str().ok_or_else(|| AppError::InvalidPath("Invalid input path".to_string()))?,
                 "-vn",                    // No video
                 "-acodec", "pcm_s16le",   // PCM 16-bit
                 "-ar", "16000",           // 16kHz sample rate (required for Whisper)
                 "-ac", "1",               // Mono
                 "-y",                     // Overwrite output
                 "-progress", "pipe:1",    // Output progress to stdout
-                output_path.to_str().ok_or_else(|| AppError::InvalidPath("Invalid output path".to_string()))?,
             ])
+            .arg(os_path::command_arg(output_path))
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
+            .kill_on_drop(true)
             .spawn()
-            .map_err(|e| AppError::FFmpeg(format!("Failed to start ffmpeg: {}", e)))?;
+            .map_err(|e| AppError::spawn_failed("ffmpeg", e, AppError::FFmpeg))?;
+        let file = Some(input_path.to_string_lossy().to_string());
+        let _tracked = processes::track(&child, ProcessKind::Ffmpeg, file, cancel);
 
         // Read progress from stdout
         if let Some(stdout) = child.stdout.take() {
             let reader = BufReader::new(stdout);
             let mut lines = reader.lines();
 
-            while let Ok(Some(line)) = lines.next_line().await {
+            loop {
+                let line = tokio::select! {
+                    _ = cancel.cancelled() => {
+                        let _ = child.kill().await;
+                        let _ = tokio::fs::remove_file(output_path).await;
+                        return Err(AppError::Cancelled);
+                    }
+                    line = lines.next_line() => line,
+                };
+                let Ok(Some(line)) = line else { break };
+
                 if line.starts_with("out_time_ms=") {
                     if let Ok(time_ms) = line.trim_start_matches("out_time_ms=").parse::<i64>() {
                         let time_sec = time_ms as f64 / 1_000_000.0;
@@ -197,6 +223,95 @@ impl FFmpegService {
         }
     }
 
+    /// Extract the audio between `start` and `end` seconds to WAV (16kHz mono
+    /// for Whisper) through `filters`, e.g. to transcribe part of a file again.
+    /// Cancelling the token kills ffmpeg and removes the partial output.
+    pub async fn extract_audio_clip(
+        input_path: &Path,
+        output_path: &Path,
+        start: f64,
+        end: f64,
+        filters: &AudioFilters,
+        cancel: &CancellationToken,
+    ) -> Result<PathBuf> {
+        let filter_chain = filters.filter_chain()?;
+        let _slot = concurrency::acquire(JobKind::Extraction, cancel).await?;
+
+        let ffmpeg_path = find_ffmpeg_path();
+        let mut child = Command::new(&ffmpeg_path)
+            .args([
+                "-ss", &format!("{:.3}", start),
+                "-t", &format!("{:.3}", (end - start).max(0.0)),
+                "-i",
+            ])
+            .arg(os_path::command_arg(input_path))
+            .args(filter_chain.iter().flat_map(|chain| ["-af", chain.as_str()]))
+            .args([
+                "-vn",
+                "-acodec", "pcm_s16le",
+                "-ar", "16000",
+                "-ac", "1",
+                "-y",
+            ])
+            .arg(os_path::command_arg(output_path))
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| AppError::spawn_failed("ffmpeg", e, AppError::FFmpeg))?;
+        let file = Some(input_path.to_string_lossy().to_string());
+        let _tracked = processes::track(&child, ProcessKind::Ffmpeg, file, cancel);
+
+        let status = tokio::select! {
+            _ = cancel.cancelled() => {
+                let _ = child.kill().await;
+                let _ = tokio::fs::remove_file(output_path).await;
+                return Err(AppError::Cancelled);
+            }
+            status = child.wait() => status
+                .map_err(|e| AppError::FFmpeg(format!("FFmpeg process error: {}", e)))?,
+        };
+
+        if status.success() && output_path.exists() {
+            Ok(output_path.to_path_buf())
+        } else {
+            Err(AppError::FFmpeg("Audio clip extraction failed".to_string()))
+        }
+    }
+
+    /// Grab a single video frame as a JPEG, scaled to `width` (aspect ratio kept).
+    /// For audio files with embedded cover art, the cover is used.
+    pub async fn extract_frame(
+        input_path: &Path,
+        output_path: &Path,
+        at_seconds: f64,
+        width: u32,
+    ) -> Result<PathBuf> {
+        let ffmpeg_path = find_ffmpeg_path();
+        let output = Command::new(&ffmpeg_path)
+            .args([
+                "-ss", &format!("{:.3}", at_seconds),  // Seek before input (fast)
+                "-i",
+            ])
+            .arg(os_path::command_arg(input_path))
+            .args([
+                "-frames:v", "1",
+                "-vf", &format!("scale={}:-2", width),
+                "-q:v", "4",
+                "-y",
+            ])
+            .arg(os_path::command_arg(output_path))
+            .output()
+            .await
+            .map_err(|e| AppError::spawn_failed("ffmpeg", e, AppError::FFmpeg))?;
+
+        if output.status.success() && output_path.exists() {
+            Ok(output_path.to_path_buf())
+        } else {
+            Err(AppError::FFmpeg("Frame extraction failed".to_string()))
+        }
+    }
+
     /// Get media file duration in seconds
     pub async fn get_duration(path: &Path) -> Result<f64> {
         let ffprobe_path = find_ffprobe_path();
@@ -205,11 +320,11 @@ impl FFmpegService {
                 "-v", "error",
                 "-show_entries", "format=duration",
                 "-of", "default=noprint_wrappers=1:nokey=1",
-                path.to_str().ok_or_else(|| AppError::InvalidPath("Invalid path".to_string()))?,
             ])
+            .arg(os_path::command_arg(path))
             .output()
             .await
-            .map_err(|e| AppError::FFmpeg(format!("Failed to run ffprobe: {}", e)))?;
+            .map_err(|e| AppError::spawn_failed("ffprobe", e, AppError::FFmpeg))?;
 
         if output.status.success() {
             let duration_str = String::from_utf8_lossy(&output.stdout);
@@ -231,11 +346,11 @@ impl FFmpegService {
                 "-print_format", "json",
                 "-show_format",
                 "-show_streams",
-                path.to_str().ok_or_else(|| AppError::InvalidPath("Invalid path".to_string()))?,
             ])
+            .arg(os_path::command_arg(path))
             .output()
             .await
-            .map_err(|e| AppError::FFmpeg(format!("Failed to run ffprobe: {}", e)))?;
+            .map_err(|e| AppError::spawn_failed("ffprobe", e, AppError::FFmpeg))?;
 
         if output.status.success() {
             let json_str = String::from_utf8_lossy(&output.stdout);
@@ -261,11 +376,26 @@ impl FFmpegService {
                 .map(|streams| streams.iter().any(|s| s.get("codec_type").and_then(|t| t.as_str()) == Some("audio")))
                 .unwrap_or(false);
 
+            let video_stream = info.get("streams")
+                .and_then(|s| s.as_array())
+                .and_then(|streams| streams.iter().find(|s| s.get("codec_type").and_then(|t| t.as_str()) == Some("video")));
+            let dimension = |key: &str| video_stream
+                .and_then(|s| s.get(key))
+                .and_then(|v| v.as_u64())
+                .map(|v| v as u32);
+            let frame_rate = video_stream
+                .and_then(|s| s.get("r_frame_rate"))
+                .and_then(|v| v.as_str())
+                .and_then(FrameRate::parse);
+
             Ok(MediaInfo {
                 format,
                 duration,
                 has_video,
                 has_audio,
+                width: dimension("width"),
+                height: dimension("height"),
+                frame_rate,
             })
         } else {
             Err(AppError::FFmpeg("Failed to get media info".to_string()))
@@ -279,4 +409,36 @@ pub struct MediaInfo {
     pub duration: f64,
     pub has_video: bool,
     pub has_audio: bool,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub frame_rate: Option<FrameRate>,
+}
+
+/// Video frame rate as a fraction, e.g. 30000/1001 for 29.97 fps
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct FrameRate {
+    pub num: u32,
+    pub den: u32,
+}
+
+impl FrameRate {
+    pub const fn new(num: u32, den: u32) -> Self {
+        Self { num, den }
+    }
+
+    /// Parse ffprobe's `30000/1001` notation (or a plain integer rate)
+    pub fn parse(value: &str) -> Option<Self> {
+        let (num, den) = value.split_once('/').unwrap_or((value, "1"));
+        let rate = Self::new(num.trim().parse().ok()?, den.trim().parse().ok()?);
+        (rate.num > 0 && rate.den > 0).then_some(rate)
+    }
+
+    pub fn fps(self) -> f64 {
+        self.num as f64 / self.den as f64
+    }
+
+    /// Whole frames nearest to a time in seconds
+    pub fn frames(self, seconds: f64) -> u64 {
+        (seconds.max(0.0) * self.fps()).round() as u64
+    }
 }
@@ -0,0 +1,129 @@
+use crate::error::{AppError, Result};
+use std::path::{Path, PathBuf};
+
+/// Move a file into another directory, keeping its name.
+/// Falls back to copy + delete when the target is on another filesystem.
+pub fn move_file(source: &Path, dest_dir: &Path) -> Result<PathBuf> {
+    check_source(source)?;
+    if !dest_dir.is_dir() {
+        return Err(AppError::InvalidPath(format!(
+            "Destination is not a directory: {}",
+            dest_dir.display()
+        )));
+    }
+
+    let name = source
+        .file_name()
+        .ok_or_else(|| AppError::InvalidPath(format!("No file name: {}", source.display())))?;
+    relocate(source, &dest_dir.join(name))
+}
+
+/// Rename a file within its directory
+pub fn rename_file(source: &Path, new_name: &str) -> Result<PathBuf> {
+    check_source(source)?;
+
+    let new_name = new_name.trim();
+    let is_plain_name = Path::new(new_name).file_name().is_some_and(|n| n == new_name);
+    if !is_plain_name {
+        return Err(AppError::InvalidPath(format!("Invalid file name: {}", new_name)));
+    }
+
+    relocate(source, &source.with_file_name(new_name))
+}
+
+/// Move a file to the system trash so the deletion can be undone from the OS
+pub fn trash_file(source: &Path) -> Result<()> {
+    check_source(source)?;
+    trash::delete(source)
+        .map_err(|e| AppError::ProcessFailed(format!("Failed to trash file: {}", e)))
+}
+
+fn check_source(source: &Path) -> Result<()> {
+    if !source.is_file() {
+        return Err(AppError::InvalidPath(format!(
+            "File does not exist: {}",
+            source.display()
+        )));
+    }
+    Ok(())
+}
+
+/// Rename `source` to `target`, never overwriting an existing file
+fn relocate(source: &Path, target: &Path) -> Result<PathBuf> {
+    if target.exists() {
+        return Err(AppError::InvalidPath(format!(
+            "Target already exists: {}",
+            target.display()
+        )));
+    }
+
+    match std::fs::rename(source, target) {
+        Ok(()) => {}
+        // Another filesystem: copy, then delete the original
+        Err(e) if e.kind() == std::io::ErrorKind::CrossesDevices => {
+            let copied = std::fs::copy(source, target).and_then(|_| std::fs::remove_file(source));
+            if let Err(e) = copied {
+                let _ = std::fs::remove_file(target);
+                return Err(e.into());
+            }
+        }
+        Err(e) => return Err(e.into()),
+    }
+
+    Ok(target.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_move_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let source = temp_dir.path().join("clip.mp4");
+        let dest_dir = temp_dir.path().join("sorted");
+        File::create(&source).unwrap();
+        std::fs::create_dir(&dest_dir).unwrap();
+
+        let moved = move_file(&source, &dest_dir).unwrap();
+        assert_eq!(moved, dest_dir.join("clip.mp4"));
+        assert!(moved.is_file());
+        assert!(!source.exists());
+
+        // Missing source and missing destination are rejected
+        assert!(move_file(&source, &dest_dir).is_err());
+        assert!(move_file(&moved, &temp_dir.path().join("missing")).is_err());
+    }
+
+    #[test]
+    fn test_rename_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let source = temp_dir.path().join("clip.mp4");
+        File::create(&source).unwrap();
+
+        let renamed = rename_file(&source, "interview.mp4").unwrap();
+        assert_eq!(renamed, temp_dir.path().join("interview.mp4"));
+        assert!(renamed.is_file());
+        assert!(!source.exists());
+
+        // Names must not escape the directory
+        assert!(rename_file(&renamed, "../escape.mp4").is_err());
+        assert!(rename_file(&renamed, "sub/clip.mp4").is_err());
+        assert!(rename_file(&renamed, "").is_err());
+    }
+
+    #[test]
+    fn test_never_overwrites() {
+        let temp_dir = TempDir::new().unwrap();
+        let source = temp_dir.path().join("a.mp4");
+        let existing = temp_dir.path().join("b.mp4");
+        std::fs::write(&source, b"a").unwrap();
+        std::fs::write(&existing, b"b").unwrap();
+
+        assert!(rename_file(&source, "b.mp4").is_err());
+        assert_eq!(std::fs::read(&existing).unwrap(), b"b");
+        assert!(source.exists());
+    }
+}
@@ -0,0 +1,647 @@
+use crate::error::{AppError, Result};
+use crate::services::chapters::{build_chapters_prompt, parse_chapters_response, Chapter};
+use crate::services::http;
+use crate::services::ollama::{
+    build_story_order_prompt, parse_story_order_response, summary_instructions, StorySegment,
+};
+use crate::services::providers;
+use crate::services::rate_limit::{self, estimate_tokens};
+use crate::services::usage::{record_usage, ApiUsage};
+use crate::services::whisper::TranscriptionSegment;
+use reqwest::{Client, Response};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::Duration;
+
+const GEMINI_API_BASE: &str = "https://generativelanguage.googleapis.com/v1beta";
+const GEMINI_UPLOAD_BASE: &str = "https://generativelanguage.googleapis.com/upload/v1beta";
+
+/// How often an uploaded file is checked while Gemini processes it
+const FILE_POLL_INTERVAL: Duration = Duration::from_secs(2);
+/// How long to wait for an uploaded file to become usable
+const FILE_PROCESSING_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// Google Gemini API service for LLM and media understanding operations
+pub struct GeminiService {
+    client: Client,
+    api_key: String,
+}
+
+// ============================================================================
+// Gemini API Types
+// ============================================================================
+
+/// A chat message; `assistant` is accepted as an alias of Gemini's `model` role
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GeminiMessage {
+    pub role: String,
+    pub content: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct Content {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    role: Option<String>,
+    parts: Vec<Part>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Part {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    text: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    file_data: Option<FileData>,
+}
+
+impl Part {
+    fn text(text: impl Into<String>) -> Self {
+        Self {
+            text: Some(text.into()),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FileData {
+    mime_type: String,
+    file_uri: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GenerateRequest {
+    contents: Vec<Content>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    system_instruction: Option<Content>,
+    generation_config: GenerationConfig,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GenerationConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+    max_output_tokens: u32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GenerateResponse {
+    #[serde(default)]
+    candidates: Vec<Candidate>,
+    #[serde(default)]
+    usage_metadata: Option<UsageMetadata>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UsageMetadata {
+    #[serde(default)]
+    prompt_token_count: u64,
+    #[serde(default)]
+    candidates_token_count: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Candidate {
+    content: Option<CandidateContent>,
+    finish_reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct CandidateContent {
+    #[serde(default)]
+    parts: Vec<Part>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct GeminiErrorResponse {
+    error: GeminiError,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct GeminiError {
+    message: String,
+}
+
+/// A file uploaded through the Files API
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UploadedFile {
+    name: String,
+    uri: String,
+    mime_type: String,
+    #[serde(default)]
+    state: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct UploadResponse {
+    file: UploadedFile,
+}
+
+/// Deletes an uploaded file when dropped, so it is removed on every way out
+/// of the request using it, including errors and cancellation
+struct UploadedFileGuard {
+    client: Client,
+    api_key: String,
+    name: String,
+}
+
+impl Drop for UploadedFileGuard {
+    /// Gemini expires files after two days anyway, so a failure is only logged
+    fn drop(&mut self) {
+        let client = self.client.clone();
+        let api_key = std::mem::take(&mut self.api_key);
+        let name = std::mem::take(&mut self.name);
+        tauri::async_runtime::spawn(async move {
+            let result = client
+                .delete(format!("{}/{}", GEMINI_API_BASE, name))
+                .header("x-goog-api-key", &api_key)
+                .send()
+                .await;
+            if let Err(e) = result {
+                log::warn!("[gemini] Failed to delete uploaded file {}: {}", name, e);
+            }
+        });
+    }
+}
+
+// ============================================================================
+// Gemini Service Implementation
+// ============================================================================
+
+impl GeminiService {
+    /// Create a new Gemini service with API key
+    pub fn new(api_key: &str) -> Self {
+        Self {
+            client: http::client(),
+            api_key: api_key.to_string(),
+        }
+    }
+
+    /// Turn an unsuccessful response into an error with Gemini's message
+    async fn api_error(response: Response, context: &str) -> AppError {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        let message = serde_json::from_str::<GeminiErrorResponse>(&body)
+            .map(|e| e.error.message)
+            .unwrap_or_else(|_| format!("HTTP {}", status));
+        AppError::for_status(status, AppError::ProcessFailed(format!("{}: {}", context, message)))
+    }
+
+    /// Generate content from a conversation
+    async fn generate(
+        &self,
+        model: &str,
+        contents: Vec<Content>,
+        system: Option<&str>,
+        temperature: Option<f32>,
+        max_tokens: u32,
+    ) -> Result<String> {
+        let url = format!("{}/models/{}:generateContent", GEMINI_API_BASE, model);
+        let prompt_chars = contents
+            .iter()
+            .flat_map(|c| &c.parts)
+            .filter_map(|p| p.text.as_ref())
+            .map(String::len)
+            .sum::<usize>()
+            + system.map_or(0, str::len);
+        let tokens = estimate_tokens(prompt_chars, Some(max_tokens));
+        rate_limit::acquire(providers::GEMINI, tokens).await;
+
+        let request = GenerateRequest {
+            contents,
+            system_instruction: system.map(|s| Content {
+                role: None,
+                parts: vec![Part::text(s)],
+            }),
+            generation_config: GenerationConfig {
+                temperature,
+                max_output_tokens: max_tokens,
+            },
+        };
+
+        let response = self
+            .client
+            .post(&url)
+            .header("x-goog-api-key", &self.api_key)
+            .json(&request)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(Self::api_error(response, "Gemini API error").await);
+        }
+
+        let result: GenerateResponse = response.json().await?;
+        if let Some(usage) = &result.usage_metadata {
+            record_usage(ApiUsage::tokens(
+                providers::GEMINI,
+                model,
+                "chat",
+                usage.prompt_token_count,
+                usage.candidates_token_count,
+            ))
+            .await;
+        }
+        response_text(result)
+    }
+
+    /// Chat with Gemini
+    pub async fn chat(
+        &self,
+        model: &str,
+        messages: Vec<GeminiMessage>,
+        system: Option<&str>,
+        temperature: Option<f32>,
+        max_tokens: u32,
+    ) -> Result<String> {
+        let contents = messages
+            .into_iter()
+            .map(|m| Content {
+                role: Some(gemini_role(&m.role).to_string()),
+                parts: vec![Part::text(m.content)],
+            })
+            .collect();
+
+        self.generate(model, contents, system, temperature, max_tokens).await
+    }
+
+    /// Send a single user prompt
+    async fn prompt(
+        &self,
+        model: &str,
+        prompt: String,
+        system: &str,
+        temperature: f32,
+        max_tokens: u32,
+    ) -> Result<String> {
+        let message = GeminiMessage {
+            role: "user".to_string(),
+            content: prompt,
+        };
+        self.chat(model, vec![message], Some(system), Some(temperature), max_tokens).await
+    }
+
+    /// Summarize text using Gemini
+    pub async fn summarize(&self, model: &str, text: &str, language: &str) -> Result<String> {
+        let system = summary_instructions(language);
+        let prompt = format!("Summarize the following transcription:\n\n{}", text);
+        self.prompt(model, prompt, &system, 0.3, 1000).await
+    }
+
+    /// Summarize an audio or video file directly, without a transcription.
+    /// The file is uploaded to the Files API and deleted again afterwards,
+    /// also when summarizing fails or is cancelled.
+    pub async fn summarize_media(
+        &self,
+        model: &str,
+        media_path: &Path,
+        language: &str,
+    ) -> Result<String> {
+        let file = self.upload_file(media_path).await?;
+        let _uploaded = UploadedFileGuard {
+            client: self.client.clone(),
+            api_key: self.api_key.clone(),
+            name: file.name.clone(),
+        };
+        let file = self.wait_until_active(file).await?;
+
+        let contents = vec![Content {
+            role: Some("user".to_string()),
+            parts: vec![
+                Part {
+                    file_data: Some(FileData {
+                        mime_type: file.mime_type.clone(),
+                        file_uri: file.uri.clone(),
+                    }),
+                    ..Default::default()
+                },
+                Part::text("Summarize the spoken content of this recording."),
+            ],
+        }];
+        let system = summary_instructions(language);
+        self.generate(model, contents, Some(&system), Some(0.3), 2048).await
+    }
+
+    /// Upload a media file with the resumable upload protocol
+    async fn upload_file(&self, path: &Path) -> Result<UploadedFile> {
+        let mime_type = media_mime_type(path).ok_or_else(|| {
+            AppError::InvalidInput(format!("Unsupported media type: {}", path.display()))
+        })?;
+        let size = tokio::fs::metadata(path).await?.len();
+        let display_name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| "media".to_string());
+
+        let response = self
+            .client
+            .post(format!("{}/files", GEMINI_UPLOAD_BASE))
+            .header("x-goog-api-key", &self.api_key)
+            .header("X-Goog-Upload-Protocol", "resumable")
+            .header("X-Goog-Upload-Command", "start")
+            .header("X-Goog-Upload-Header-Content-Length", size)
+            .header("X-Goog-Upload-Header-Content-Type", mime_type)
+            .json(&serde_json::json!({ "file": { "display_name": display_name } }))
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(Self::api_error(response, "Gemini upload failed").await);
+        }
+        let upload_url = response
+            .headers()
+            .get("x-goog-upload-url")
+            .and_then(|v| v.to_str().ok())
+            .ok_or_else(|| AppError::ProcessFailed("Gemini returned no upload URL".to_string()))?
+            .to_string();
+
+        let file = tokio::fs::File::open(path).await?;
+        let response = self
+            .client
+            .post(&upload_url)
+            .header("Content-Length", size)
+            .header("X-Goog-Upload-Offset", 0)
+            .header("X-Goog-Upload-Command", "upload, finalize")
+            .body(file)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(Self::api_error(response, "Gemini upload failed").await);
+        }
+        let uploaded: UploadResponse = response.json().await?;
+        Ok(uploaded.file)
+    }
+
+    /// Wait until Gemini has processed an uploaded file (videos take a while)
+    async fn wait_until_active(&self, mut file: UploadedFile) -> Result<UploadedFile> {
+        let started = std::time::Instant::now();
+        loop {
+            match file.state.as_deref() {
+                Some("PROCESSING") => {}
+                Some("FAILED") => {
+                    return Err(AppError::ProcessFailed(
+                        "Gemini could not process the media file".to_string(),
+                    ));
+                }
+                _ => return Ok(file),
+            }
+            if started.elapsed() > FILE_PROCESSING_TIMEOUT {
+                return Err(AppError::ProcessFailed(
+                    "Timed out waiting for Gemini to process the media file".to_string(),
+                ));
+            }
+
+            tokio::time::sleep(FILE_POLL_INTERVAL).await;
+            let response = self
+                .client
+                .get(format!("{}/{}", GEMINI_API_BASE, file.name))
+                .header("x-goog-api-key", &self.api_key)
+                .send()
+                .await?;
+            if !response.status().is_success() {
+                return Err(Self::api_error(response, "Gemini file status failed").await);
+            }
+            file = response.json().await?;
+        }
+    }
+
+    /// Extract story order / timeline from transcription segments
+    pub async fn extract_story_order(
+        &self,
+        model: &str,
+        segments: &[TranscriptionSegment],
+    ) -> Result<Vec<StorySegment>> {
+        let system = "You are an expert video editor who restructures interview and \
+                      vlog footage into a compelling narrative. \
+                      Respond with the JSON array only, without any explanation.";
+
+        let response = self
+            .prompt(model, build_story_order_prompt(segments), system, 0.2, 4096)
+            .await?;
+
+        parse_story_order_response(&response)
+    }
+
+    /// Split transcription segments into YouTube chapters with generated titles
+    pub async fn generate_chapters(
+        &self,
+        model: &str,
+        segments: &[TranscriptionSegment],
+        language: &str,
+    ) -> Result<Vec<Chapter>> {
+        let system = "You are an expert YouTube editor who writes clear, searchable \
+                      chapter titles. Respond with the JSON array only, without any explanation.";
+
+        let response = self
+            .prompt(model, build_chapters_prompt(segments, language), system, 0.3, 2048)
+            .await?;
+
+        parse_chapters_response(&response, segments)
+    }
+
+    /// Check if API key is valid
+    pub async fn validate_api_key(&self) -> Result<bool> {
+        let response = self
+            .client
+            .get(format!("{}/models", GEMINI_API_BASE))
+            .header("x-goog-api-key", &self.api_key)
+            .send()
+            .await?;
+
+        Ok(response.status().is_success())
+    }
+
+    /// Get available Gemini models (static fallback list)
+    pub fn available_models() -> Vec<GeminiModel> {
+        vec![
+            GeminiModel {
+                id: "gemini-2.0-flash".to_string(),
+                name: "Gemini 2.0 Flash".to_string(),
+                description: "Fast and affordable".to_string(),
+            },
+            GeminiModel {
+                id: "gemini-1.5-flash".to_string(),
+                name: "Gemini 1.5 Flash".to_string(),
+                description: "Fast, long context".to_string(),
+            },
+            GeminiModel {
+                id: "gemini-1.5-pro".to_string(),
+                name: "Gemini 1.5 Pro".to_string(),
+                description: "Most capable".to_string(),
+            },
+        ]
+    }
+
+    /// Fetch the models that support content generation from the Gemini API
+    pub async fn fetch_models(&self) -> Result<Vec<GeminiModel>> {
+        let response = self
+            .client
+            .get(format!("{}/models", GEMINI_API_BASE))
+            .query(&[("pageSize", "1000")])
+            .header("x-goog-api-key", &self.api_key)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(Self::api_error(response, "Failed to fetch Gemini models").await);
+        }
+
+        let data: GeminiModelsResponse = response.json().await?;
+        Ok(data
+            .models
+            .into_iter()
+            .filter(|m| m.supported_generation_methods.iter().any(|g| g == "generateContent"))
+            .map(|m| GeminiModel {
+                id: m.name.trim_start_matches("models/").to_string(),
+                name: m.display_name,
+                description: m.description,
+            })
+            .collect())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GeminiModel {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+}
+
+// ============================================================================
+// Models API Types
+// ============================================================================
+
+#[derive(Debug, Clone, Deserialize)]
+struct GeminiModelsResponse {
+    #[serde(default)]
+    models: Vec<GeminiModelData>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiModelData {
+    name: String,
+    #[serde(default)]
+    display_name: String,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    supported_generation_methods: Vec<String>,
+}
+
+/// Map chat roles to Gemini's, which calls the assistant `model`
+fn gemini_role(role: &str) -> &str {
+    match role {
+        "assistant" | "model" => "model",
+        _ => "user",
+    }
+}
+
+/// Join the text of the first candidate, or explain why there is none
+fn response_text(response: GenerateResponse) -> Result<String> {
+    let candidate = response.candidates.into_iter().next().ok_or_else(|| {
+        AppError::ProcessFailed("Gemini returned no response (the prompt may be blocked)".into())
+    })?;
+
+    let text: String = candidate
+        .content
+        .map(|c| c.parts.into_iter().filter_map(|p| p.text).collect())
+        .unwrap_or_default();
+    if text.is_empty() {
+        if let Some(reason) = candidate.finish_reason.filter(|r| r != "STOP") {
+            return Err(AppError::ProcessFailed(format!(
+                "Gemini stopped without a response: {}",
+                reason
+            )));
+        }
+    }
+    Ok(text)
+}
+
+/// MIME type Gemini expects for a media file, by extension
+fn media_mime_type(path: &Path) -> Option<&'static str> {
+    let extension = path.extension()?.to_str()?.to_lowercase();
+    Some(match extension.as_str() {
+        "mp4" | "m4v" => "video/mp4",
+        "mov" => "video/quicktime",
+        "webm" => "video/webm",
+        "mkv" => "video/x-matroska",
+        "avi" => "video/x-msvideo",
+        "mpeg" | "mpg" => "video/mpeg",
+        "wmv" => "video/x-ms-wmv",
+        "flv" => "video/x-flv",
+        "3gp" => "video/3gpp",
+        "mp3" => "audio/mp3",
+        "wav" => "audio/wav",
+        "m4a" => "audio/mp4",
+        "aac" => "audio/aac",
+        "flac" => "audio/flac",
+        "ogg" | "opus" => "audio/ogg",
+        "aiff" | "aif" => "audio/aiff",
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_serialization() {
+        let request = GenerateRequest {
+            contents: vec![Content {
+                role: Some(gemini_role("assistant").to_string()),
+                parts: vec![Part::text("Hi")],
+            }],
+            system_instruction: None,
+            generation_config: GenerationConfig {
+                temperature: Some(0.5),
+                max_output_tokens: 100,
+            },
+        };
+
+        let json = serde_json::to_value(&request).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "contents": [{"role": "model", "parts": [{"text": "Hi"}]}],
+                "generationConfig": {"temperature": 0.5, "maxOutputTokens": 100}
+            })
+        );
+    }
+
+    #[test]
+    fn test_response_text() {
+        let response: GenerateResponse = serde_json::from_str(
+            r#"{"candidates": [{"content": {"parts": [{"text": "Hello "}, {"text": "world"}],
+                "role": "model"}, "finishReason": "STOP"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(response_text(response).unwrap(), "Hello world");
+
+        let blocked: GenerateResponse =
+            serde_json::from_str(r#"{"candidates": [{"finishReason": "SAFETY"}]}"#).unwrap();
+        assert!(response_text(blocked).is_err());
+        let empty = GenerateResponse {
+            candidates: vec![],
+            usage_metadata: None,
+        };
+        assert!(response_text(empty).is_err());
+    }
+
+    #[test]
+    fn test_media_mime_type() {
+        assert_eq!(media_mime_type(Path::new("/a/clip.MOV")), Some("video/quicktime"));
+        assert_eq!(media_mime_type(Path::new("talk.m4a")), Some("audio/mp4"));
+        assert_eq!(media_mime_type(Path::new("notes.txt")), None);
+        assert_eq!(media_mime_type(Path::new("noext")), None);
+    }
+}
@@ -0,0 +1,132 @@
+use crate::error::Result;
+use crate::services::chapters::Chapter;
+use crate::services::ollama::StorySegment;
+use crate::services::openai::{ChatMessage, OpenAIModel, OpenAIService};
+use crate::services::providers;
+use crate::services::whisper::TranscriptionSegment;
+
+/// Groq's OpenAI-compatible API
+pub const GROQ_API_BASE: &str = "https://api.groq.com/openai/v1";
+
+/// Default Groq transcription model
+pub const GROQ_DEFAULT_TRANSCRIPTION_MODEL: &str = "whisper-large-v3";
+
+/// Model families Groq lists that cannot be used for chat
+const NON_CHAT_KEYWORDS: &[&str] = &["whisper", "guard", "tts", "playai"];
+
+/// Groq API service: fast hosted Whisper and open-weight LLMs (Llama and others)
+/// through the OpenAI request code
+pub struct GroqService {
+    api: OpenAIService,
+}
+
+impl GroqService {
+    /// Create a new Groq service with API key
+    pub fn new(api_key: &str) -> Self {
+        Self {
+            api: OpenAIService::hosted(api_key, providers::GROQ, GROQ_API_BASE),
+        }
+    }
+
+    /// Chat completion
+    pub async fn chat(
+        &self,
+        model: &str,
+        messages: Vec<ChatMessage>,
+        temperature: Option<f32>,
+        max_tokens: Option<u32>,
+    ) -> Result<String> {
+        self.api.chat(model, messages, temperature, max_tokens).await
+    }
+
+    /// Summarize text
+    pub async fn summarize(&self, model: &str, text: &str, language: &str) -> Result<String> {
+        self.api.summarize(model, text, language).await
+    }
+
+    /// Extract story order / timeline from transcription segments
+    pub async fn extract_story_order(
+        &self,
+        model: &str,
+        segments: &[TranscriptionSegment],
+    ) -> Result<Vec<StorySegment>> {
+        self.api.extract_story_order(model, segments).await
+    }
+
+    /// Split transcription segments into YouTube chapters with generated titles
+    pub async fn generate_chapters(
+        &self,
+        model: &str,
+        segments: &[TranscriptionSegment],
+        language: &str,
+    ) -> Result<Vec<Chapter>> {
+        self.api.generate_chapters(model, segments, language).await
+    }
+
+    /// Check if API key is valid
+    pub async fn validate_api_key(&self) -> Result<bool> {
+        self.api.validate_api_key().await
+    }
+
+    /// Get available Groq chat models (static fallback list)
+    pub fn available_models() -> Vec<OpenAIModel> {
+        vec![
+            OpenAIModel {
+                id: "llama-3.1-8b-instant".to_string(),
+                name: "Llama 3.1 8B Instant".to_string(),
+                description: "Fastest and cheapest".to_string(),
+                created: 0,
+            },
+            OpenAIModel {
+                id: "llama-3.3-70b-versatile".to_string(),
+                name: "Llama 3.3 70B Versatile".to_string(),
+                description: "Most capable".to_string(),
+                created: 0,
+            },
+        ]
+    }
+
+    /// Groq transcription models
+    pub fn transcription_models() -> Vec<OpenAIModel> {
+        vec![
+            OpenAIModel {
+                id: GROQ_DEFAULT_TRANSCRIPTION_MODEL.to_string(),
+                name: "Whisper Large v3".to_string(),
+                description: "Most accurate, multilingual".to_string(),
+                created: 0,
+            },
+            OpenAIModel {
+                id: "whisper-large-v3-turbo".to_string(),
+                name: "Whisper Large v3 Turbo".to_string(),
+                description: "Faster and cheaper, multilingual".to_string(),
+                created: 0,
+            },
+        ]
+    }
+
+    /// Fetch the chat models Groq currently serves (newest first)
+    pub async fn fetch_models(&self) -> Result<Vec<OpenAIModel>> {
+        let models = self.api.list_models().await?;
+        Ok(models.into_iter().filter(|m| is_chat_model(&m.id)).collect())
+    }
+}
+
+/// Whether a Groq model id is a chat model
+fn is_chat_model(model_id: &str) -> bool {
+    let id = model_id.to_lowercase();
+    !NON_CHAT_KEYWORDS.iter().any(|keyword| id.contains(keyword))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_chat_model() {
+        assert!(is_chat_model("llama-3.3-70b-versatile"));
+        assert!(is_chat_model("openai/gpt-oss-120b"));
+        assert!(!is_chat_model("whisper-large-v3-turbo"));
+        assert!(!is_chat_model("meta-llama/llama-guard-4-12b"));
+        assert!(!is_chat_model("playai-tts"));
+    }
+}
@@ -0,0 +1,237 @@
+use crate::error::{AppError, Result};
+use crate::services::processes::{self, ProcessKind};
+use crate::services::whisper::TranscriptionResult;
+use serde::{Deserialize, Serialize};
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+use tokio_util::sync::CancellationToken;
+
+/// Seconds a hook may run when it doesn't set its own timeout
+const DEFAULT_HOOK_TIMEOUT_SECS: u64 = 300;
+
+/// Pipeline stage a hook runs after
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HookStage {
+    /// After transcription; the hook may replace the transcription
+    Transcription,
+    /// After summarizing; the hook may replace the summary
+    Summary,
+    /// After the exports were written, e.g. to upload them
+    Export,
+}
+
+/// A user-defined executable run after a pipeline stage.
+///
+/// It receives a `HookPayload` as JSON on stdin and may print a modified
+/// payload as JSON on stdout. Printing nothing leaves the payload unchanged;
+/// a non-zero exit status fails the pipeline.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PostProcessingHook {
+    pub name: String,
+    /// Path of the script or executable
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    pub stage: HookStage,
+    #[serde(default = "enabled_by_default")]
+    pub enabled: bool,
+    /// Seconds before the hook is killed (5 minutes by default)
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+}
+
+fn enabled_by_default() -> bool {
+    true
+}
+
+/// What a hook receives on stdin
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HookPayload {
+    pub stage: HookStage,
+    pub file_path: String,
+    pub transcription: TranscriptionResult,
+    #[serde(default)]
+    pub summary: Option<String>,
+    /// Paths of the written exports (set for the export stage)
+    #[serde(default)]
+    pub exports: Vec<String>,
+}
+
+/// What a hook may print on stdout. Fields left out keep their value, so a hook
+/// can echo the whole payload back with its changes.
+#[derive(Debug, Default, Deserialize)]
+struct HookOutput {
+    #[serde(default)]
+    transcription: Option<TranscriptionResult>,
+    #[serde(default)]
+    summary: Option<String>,
+}
+
+/// Whether any enabled hook runs after `stage`
+pub fn has_hooks(hooks: &[PostProcessingHook], stage: HookStage) -> bool {
+    hooks.iter().any(|h| h.enabled && h.stage == stage)
+}
+
+/// Run the enabled hooks of the payload's stage in order, each one receiving
+/// the previous one's output
+pub async fn run_hooks(
+    hooks: &[PostProcessingHook],
+    mut payload: HookPayload,
+    cancel: &CancellationToken,
+) -> Result<HookPayload> {
+    let stage = payload.stage;
+    for hook in hooks.iter().filter(|h| h.enabled && h.stage == stage) {
+        log::info!("[hooks] Running '{}' after {:?}", hook.name, stage);
+        let input = serde_json::to_vec(&payload)?;
+        let output = run_hook(hook, &payload.file_path, input, cancel).await?;
+        apply_output(&mut payload, &output).map_err(|e| {
+            AppError::ProcessFailed(format!("Hook '{}' printed invalid JSON: {}", hook.name, e))
+        })?;
+    }
+    Ok(payload)
+}
+
+/// Merge a hook's stdout into the payload
+fn apply_output(payload: &mut HookPayload, stdout: &[u8]) -> serde_json::Result<()> {
+    if stdout.iter().all(u8::is_ascii_whitespace) {
+        return Ok(());
+    }
+    let output: HookOutput = serde_json::from_slice(stdout)?;
+    if let Some(transcription) = output.transcription {
+        payload.transcription = transcription;
+    }
+    if output.summary.is_some() {
+        payload.summary = output.summary;
+    }
+    Ok(())
+}
+
+/// Run one hook on `file_path` with `input` on stdin and return its stdout
+async fn run_hook(
+    hook: &PostProcessingHook,
+    file_path: &str,
+    input: Vec<u8>,
+    cancel: &CancellationToken,
+) -> Result<Vec<u8>> {
+    let mut child = Command::new(&hook.command)
+        .args(&hook.args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| {
+            AppError::ProcessFailed(format!("Failed to start hook '{}': {}", hook.name, e))
+        })?;
+    let _tracked = processes::track(&child, ProcessKind::Hook, Some(file_path.to_string()), cancel);
+
+    // Write stdin in the background so a hook that prints before reading all
+    // of it can't deadlock; hooks that don't read stdin at all are fine too
+    let writer = child.stdin.take().map(|mut stdin| {
+        tokio::spawn(async move {
+            let _ = stdin.write_all(&input).await;
+        })
+    });
+
+    let timeout = Duration::from_secs(hook.timeout_secs.unwrap_or(DEFAULT_HOOK_TIMEOUT_SECS));
+    // Dropping the child on cancel or timeout kills the hook
+    let output = tokio::select! {
+        biased;
+        _ = cancel.cancelled() => return Err(AppError::Cancelled),
+        output = tokio::time::timeout(timeout, child.wait_with_output()) => match output {
+            Ok(output) => output?,
+            Err(_) => {
+                return Err(AppError::ProcessFailed(format!(
+                    "Hook '{}' timed out after {}s",
+                    hook.name,
+                    timeout.as_secs()
+                )))
+            }
+        },
+    };
+    if let Some(writer) = writer {
+        let _ = writer.await;
+    }
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(AppError::ProcessFailed(format!(
+            "Hook '{}' failed ({}): {}",
+            hook.name,
+            output.status,
+            stderr.trim()
+        )));
+    }
+    Ok(output.stdout)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::whisper::TranscriptionSegment;
+
+    fn payload(stage: HookStage) -> HookPayload {
+        HookPayload {
+            stage,
+            file_path: "/media/talk.mp4".to_string(),
+            transcription: TranscriptionResult {
+                segments: vec![TranscriptionSegment::new(0.0, 2.0, "darn it")],
+                full_text: "darn it".to_string(),
+                language: Some("en".to_string()),
+                duration: 2.0,
+            },
+            summary: Some("A summary".to_string()),
+            exports: Vec::new(),
+        }
+    }
+
+    fn hook(stage: HookStage, script: &str) -> PostProcessingHook {
+        PostProcessingHook {
+            name: "test".to_string(),
+            command: "sh".to_string(),
+            args: vec!["-c".to_string(), script.to_string()],
+            stage,
+            enabled: true,
+            timeout_secs: Some(10),
+        }
+    }
+
+    #[test]
+    fn test_apply_output() {
+        let mut payload = payload(HookStage::Summary);
+        apply_output(&mut payload, b"  \n").unwrap();
+        assert_eq!(payload.summary.as_deref(), Some("A summary"));
+
+        apply_output(&mut payload, br#"{"summary": "Shorter", "stage": "summary"}"#).unwrap();
+        assert_eq!(payload.summary.as_deref(), Some("Shorter"));
+        assert_eq!(payload.transcription.full_text, "darn it");
+
+        assert!(apply_output(&mut payload, b"not json").is_err());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_run_hooks() {
+        let cancel = CancellationToken::new();
+        let hooks = vec![
+            // Echo the payload back with the summary replaced
+            hook(HookStage::Summary, r#"sed 's/"summary":"A summary"/"summary":"Edited"/'"#),
+            // Other stages and disabled hooks don't run
+            hook(HookStage::Transcription, "exit 1"),
+            PostProcessingHook {
+                enabled: false,
+                ..hook(HookStage::Summary, "exit 1")
+            },
+        ];
+        let result = run_hooks(&hooks, payload(HookStage::Summary), &cancel).await.unwrap();
+        assert_eq!(result.summary.as_deref(), Some("Edited"));
+
+        let failing = vec![hook(HookStage::Export, "echo oops >&2; exit 3")];
+        let err = run_hooks(&failing, payload(HookStage::Export), &cancel).await.unwrap_err();
+        assert!(err.to_string().contains("oops"));
+    }
+}
@@ -0,0 +1,139 @@
+//! The HTTP clients shared by all services, so connections are pooled across
+//! requests and network settings apply everywhere.
+
+use crate::services::app_settings::AppSettings;
+use reqwest::{Client, Proxy};
+use serde::{Deserialize, Serialize};
+use std::sync::RwLock;
+use std::time::Duration;
+
+/// Sent with every request; some APIs (GitHub) reject requests without one
+const USER_AGENT: &str = concat!("clip-flow/", env!("CARGO_PKG_VERSION"));
+
+/// Idle pooled connections are closed after this long
+const POOL_IDLE_TIMEOUT: Duration = Duration::from_secs(90);
+
+/// Timeouts of network requests, in seconds (at least one each), so a hung
+/// connection fails instead of leaving a command waiting forever
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct NetworkTimeouts {
+    /// Establishing a connection
+    pub connect_secs: u64,
+    /// Waiting for the next data of a response, including its headers
+    /// (local models and long transcriptions answer slowly)
+    pub read_secs: u64,
+    /// A whole API request; downloads have no overall limit
+    pub request_secs: u64,
+}
+
+impl Default for NetworkTimeouts {
+    fn default() -> Self {
+        Self {
+            connect_secs: 15,
+            read_secs: 300,
+            request_secs: 900,
+        }
+    }
+}
+
+fn secs(value: u64) -> Duration {
+    Duration::from_secs(value.max(1))
+}
+
+/// Clients for API requests and for downloads, which may take arbitrarily long
+struct Clients {
+    api: Client,
+    download: Client,
+}
+
+impl Clients {
+    fn new(proxy: Option<&str>, timeouts: &NetworkTimeouts) -> Self {
+        Self {
+            api: build(proxy, timeouts, Some(secs(timeouts.request_secs))),
+            download: build(proxy, timeouts, None),
+        }
+    }
+}
+
+static CLIENTS: RwLock<Option<Clients>> = RwLock::new(None);
+
+/// Build a client with the app's defaults and the proxy from the settings.
+/// An invalid proxy is logged and left out rather than failing every request.
+fn build(proxy: Option<&str>, timeouts: &NetworkTimeouts, overall: Option<Duration>) -> Client {
+    let mut builder = Client::builder()
+        .user_agent(USER_AGENT)
+        .connect_timeout(secs(timeouts.connect_secs))
+        .read_timeout(secs(timeouts.read_secs))
+        .pool_idle_timeout(POOL_IDLE_TIMEOUT);
+    if let Some(overall) = overall {
+        builder = builder.timeout(overall);
+    }
+    if let Some(url) = proxy.map(str::trim).filter(|url| !url.is_empty()) {
+        match Proxy::all(url) {
+            Ok(proxy) => builder = builder.proxy(proxy),
+            Err(e) => log::warn!("[http] Ignoring invalid proxy {}: {}", url, e),
+        }
+    }
+    builder.build().unwrap_or_else(|e| {
+        log::error!("[http] Failed to build the HTTP client: {}", e);
+        Client::new()
+    })
+}
+
+/// Rebuild the shared clients for changed settings
+pub fn configure(settings: &AppSettings) {
+    let clients = Clients::new(settings.http_proxy.as_deref(), &settings.network_timeouts);
+    *CLIENTS.write().unwrap_or_else(|e| e.into_inner()) = Some(clients);
+}
+
+/// Configure the shared clients from the saved settings
+pub fn init() {
+    configure(&AppSettings::load().unwrap_or_default());
+}
+
+fn shared(pick: fn(&Clients) -> &Client) -> Client {
+    if let Some(clients) = CLIENTS.read().unwrap_or_else(|e| e.into_inner()).as_ref() {
+        return pick(clients).clone();
+    }
+    let mut shared = CLIENTS.write().unwrap_or_else(|e| e.into_inner());
+    let clients = shared.get_or_insert_with(|| Clients::new(None, &NetworkTimeouts::default()));
+    pick(clients).clone()
+}
+
+/// The shared client for API requests. Clones are cheap and share the
+/// connection pool.
+pub fn client() -> Client {
+    shared(|clients| &clients.api)
+}
+
+/// The shared client for file downloads and other long streams: like
+/// [`client`], but without a limit on the whole request
+pub fn download_client() -> Client {
+    shared(|clients| &clients.download)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_invalid_proxy_is_ignored() {
+        // Neither panics nor fails: requests go out without the proxy
+        let timeouts = NetworkTimeouts::default();
+        build(Some("not a proxy url"), &timeouts, None);
+        build(Some("http://127.0.0.1:8080"), &timeouts, None);
+        build(Some("  "), &timeouts, None);
+    }
+
+    #[test]
+    fn test_zero_timeouts_are_raised() {
+        let timeouts = NetworkTimeouts {
+            connect_secs: 0,
+            read_secs: 0,
+            request_secs: 0,
+        };
+        assert_eq!(secs(timeouts.connect_secs), Duration::from_secs(1));
+        Clients::new(None, &timeouts);
+    }
+}
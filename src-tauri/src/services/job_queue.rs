@@ -0,0 +1,220 @@
+use crate::services::audio_filters::AudioFilters;
+use crate::services::temp_files;
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::path::PathBuf;
+
+tokio::task_local! {
+    /// Id of the queued job the current task runs
+    static JOB_ID: String;
+}
+
+/// Work a background job performs
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum JobSpec {
+    /// Extract the audio of a media file and transcribe it with a transcription
+    /// provider (local whisper.cpp by default)
+    Transcription {
+        file_path: String,
+        model_id: String,
+        #[serde(default)]
+        language: Option<String>,
+        #[serde(default)]
+        provider: Option<String>,
+        #[serde(default)]
+        profile: Option<String>,
+        /// Cleanup of the audio before transcription
+        #[serde(default, skip_serializing_if = "AudioFilters::is_off")]
+        audio_filters: AudioFilters,
+    },
+    /// Extract the audio of a media file to WAV
+    AudioExtraction {
+        input_path: String,
+        #[serde(default)]
+        output_path: Option<String>,
+    },
+    /// Download a Whisper model
+    ModelDownload { model_id: String },
+    /// Summarize text with an LLM provider ("ollama", "openai", "claude", "gemini",
+    /// "groq", "openai_compatible" or the embedded "llama"), saving the summary
+    /// for `media_path` when given
+    Summary {
+        provider: String,
+        model: String,
+        text: String,
+        language: String,
+        #[serde(default)]
+        media_path: Option<String>,
+        #[serde(default)]
+        profile: Option<String>,
+    },
+}
+
+impl JobSpec {
+    /// Job kind as recorded in the job history
+    pub fn kind(&self) -> &'static str {
+        match self {
+            JobSpec::Transcription { .. } => "transcription",
+            JobSpec::AudioExtraction { .. } => "audio_extraction",
+            JobSpec::ModelDownload { .. } => "model_download",
+            JobSpec::Summary { .. } => "summary",
+        }
+    }
+
+    /// Media file the job works on, if any
+    pub fn media_path(&self) -> Option<&str> {
+        match self {
+            JobSpec::Transcription { file_path, .. } => Some(file_path),
+            JobSpec::AudioExtraction { input_path, .. } => Some(input_path),
+            JobSpec::ModelDownload { .. } => None,
+            JobSpec::Summary { media_path, .. } => media_path.as_deref(),
+        }
+    }
+}
+
+/// Lifecycle of a queued job
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    /// Waiting for its turn
+    Queued,
+    /// Kept in the queue but skipped until resumed
+    Paused,
+    Running,
+    /// Was running when the app quit or crashed; waits to be resumed or requeued
+    Interrupted,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+impl JobStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            JobStatus::Queued => "queued",
+            JobStatus::Paused => "paused",
+            JobStatus::Running => "running",
+            JobStatus::Interrupted => "interrupted",
+            JobStatus::Completed => "completed",
+            JobStatus::Failed => "failed",
+            JobStatus::Cancelled => "cancelled",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Some(match value {
+            "queued" => JobStatus::Queued,
+            "paused" => JobStatus::Paused,
+            "running" => JobStatus::Running,
+            "interrupted" => JobStatus::Interrupted,
+            "completed" => JobStatus::Completed,
+            "failed" => JobStatus::Failed,
+            "cancelled" => JobStatus::Cancelled,
+            _ => return None,
+        })
+    }
+
+    /// Whether the job has reached an end state
+    pub fn is_finished(self) -> bool {
+        matches!(self, JobStatus::Completed | JobStatus::Failed | JobStatus::Cancelled)
+    }
+}
+
+/// A job in the persistent background queue
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QueuedJob {
+    pub id: String,
+    pub spec: JobSpec,
+    pub status: JobStatus,
+    /// Order in the queue; lower runs first
+    pub position: i64,
+    /// How many times the job has been started
+    pub attempts: u32,
+    pub error: Option<String>,
+    /// Output of a completed job (transcription, file path or summary)
+    pub result: Option<serde_json::Value>,
+    /// Unix seconds
+    pub created_at: u64,
+    pub started_at: Option<u64>,
+    pub finished_at: Option<u64>,
+}
+
+/// A status change in the life of a job
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JobEvent {
+    pub status: JobStatus,
+    /// Error of a failed job, or why the status changed
+    pub detail: Option<String>,
+    /// Unix seconds
+    pub created_at: u64,
+}
+
+/// Run `future` as the work of the queued job `id`, so the temp files it
+/// creates go to the job's own folder
+pub async fn in_job<F: Future>(id: &str, future: F) -> F::Output {
+    JOB_ID.scope(id.to_string(), future).await
+}
+
+/// Folder of the temp files of job `id`, removed when the job is recovered
+/// after a crash
+pub fn job_temp_dir(id: &str) -> PathBuf {
+    temp_files::root().join("jobs").join(id)
+}
+
+/// Folder for temp files: the running job's own folder, or the shared one
+/// outside of queued jobs
+pub fn temp_dir() -> PathBuf {
+    JOB_ID
+        .try_with(|id| job_temp_dir(id))
+        .unwrap_or_else(|_| temp_files::root())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_job_spec_serialization() {
+        let spec = JobSpec::Transcription {
+            file_path: "/m/a.mp4".to_string(),
+            model_id: "base".to_string(),
+            language: None,
+            provider: None,
+            profile: None,
+            audio_filters: AudioFilters::default(),
+        };
+        let json = serde_json::to_value(&spec).unwrap();
+        assert_eq!(json["type"], "transcription");
+        assert_eq!(json["file_path"], "/m/a.mp4");
+
+        let parsed: JobSpec =
+            serde_json::from_str(r#"{"type": "model_download", "model_id": "small"}"#).unwrap();
+        assert_eq!(parsed.kind(), "model_download");
+        assert_eq!(parsed.media_path(), None);
+    }
+
+    #[test]
+    fn test_job_status_round_trip() {
+        for status in [
+            JobStatus::Queued,
+            JobStatus::Paused,
+            JobStatus::Running,
+            JobStatus::Interrupted,
+            JobStatus::Completed,
+            JobStatus::Failed,
+            JobStatus::Cancelled,
+        ] {
+            assert_eq!(JobStatus::parse(status.as_str()), Some(status));
+        }
+        assert_eq!(JobStatus::parse("unknown"), None);
+    }
+
+    #[tokio::test]
+    async fn test_job_temp_dir() {
+        let shared = temp_dir();
+        let in_job_dir = in_job("job-1", async { temp_dir() }).await;
+        assert_eq!(in_job_dir, job_temp_dir("job-1"));
+        assert!(in_job_dir.starts_with(&shared));
+    }
+}
@@ -0,0 +1,97 @@
+use crate::error::{AppError, Result};
+use crate::services::http;
+use crate::services::providers::{self, SecretProvider};
+use crate::services::{ClaudeService, OpenAIService};
+use reqwest::{RequestBuilder, StatusCode};
+
+const GEMINI_MODELS_URL: &str = "https://generativelanguage.googleapis.com/v1beta/models";
+const GROQ_MODELS_URL: &str = "https://api.groq.com/openai/v1/models";
+const DEEPGRAM_PROJECTS_URL: &str = "https://api.deepgram.com/v1/projects";
+const ASSEMBLYAI_TRANSCRIPTS_URL: &str = "https://api.assemblyai.com/v2/transcript?limit=1";
+
+/// Performs a cheap, read-only API call to check whether a key is accepted by its provider
+pub struct KeyValidator;
+
+impl KeyValidator {
+    /// Validate an API key against its provider.
+    /// Returns `Ok(false)` when the provider rejects the key and `Err` when
+    /// the check itself could not be completed (network error, outage).
+    pub async fn validate(provider: &SecretProvider, api_key: &str) -> Result<bool> {
+        let client = http::client();
+
+        match provider.id {
+            providers::OPENAI => OpenAIService::new(api_key).validate_api_key().await,
+            providers::CLAUDE => ClaudeService::new(api_key).validate_api_key().await,
+            providers::GEMINI => {
+                Self::check(client.get(GEMINI_MODELS_URL).query(&[("key", api_key)])).await
+            }
+            providers::GROQ => Self::check(client.get(GROQ_MODELS_URL).bearer_auth(api_key)).await,
+            providers::DEEPGRAM => {
+                Self::check(
+                    client
+                        .get(DEEPGRAM_PROJECTS_URL)
+                        .header("Authorization", format!("Token {}", api_key)),
+                )
+                .await
+            }
+            providers::ASSEMBLYAI => {
+                Self::check(
+                    client
+                        .get(ASSEMBLYAI_TRANSCRIPTS_URL)
+                        .header("Authorization", api_key),
+                )
+                .await
+            }
+            _ => Err(AppError::Keychain(format!(
+                "Key validation is not supported for {}",
+                provider.display_name
+            ))),
+        }
+    }
+
+    async fn check(request: RequestBuilder) -> Result<bool> {
+        let response = request.send().await?;
+        status_to_validity(response.status())
+    }
+}
+
+/// Map a provider response status to a validation result
+fn status_to_validity(status: StatusCode) -> Result<bool> {
+    if status.is_success() {
+        Ok(true)
+    } else if matches!(
+        status,
+        StatusCode::BAD_REQUEST | StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN
+    ) {
+        // Gemini answers 400 for malformed keys, the others 401/403
+        Ok(false)
+    } else {
+        Err(AppError::ProcessFailed(format!(
+            "Could not validate API key: HTTP {}",
+            status
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_success_status_is_valid() {
+        assert!(status_to_validity(StatusCode::OK).unwrap());
+    }
+
+    #[test]
+    fn test_auth_errors_are_invalid() {
+        assert!(!status_to_validity(StatusCode::UNAUTHORIZED).unwrap());
+        assert!(!status_to_validity(StatusCode::FORBIDDEN).unwrap());
+        assert!(!status_to_validity(StatusCode::BAD_REQUEST).unwrap());
+    }
+
+    #[test]
+    fn test_server_errors_are_inconclusive() {
+        assert!(status_to_validity(StatusCode::INTERNAL_SERVER_ERROR).is_err());
+        assert!(status_to_validity(StatusCode::TOO_MANY_REQUESTS).is_err());
+    }
+}
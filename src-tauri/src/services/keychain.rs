@@ -1,130 +1,226 @@
 use crate::error::{AppError, Result};
+use crate::redact;
+use crate::services::app_settings::AppSettings;
+use crate::services::credential_profiles::{validate_profile_name, CredentialProfiles, DEFAULT_PROFILE};
+use crate::services::env_keys;
+use crate::services::providers::{self, SecretProvider};
+use crate::services::secret_file::EncryptedFileStore;
 use keyring::Entry;
 
 const SERVICE_NAME: &str = "clip-flow";
 
-/// API key types that can be stored securely
-#[derive(Debug, Clone, Copy)]
-pub enum ApiKeyType {
-    OpenAI,
-    Claude,
-}
+/// Whether keyring has a native credential store for this platform. Elsewhere
+/// it uses an in-memory mock that forgets every key when the app quits.
+const HAS_NATIVE_STORE: bool = cfg!(any(
+    target_os = "macos",
+    target_os = "windows",
+    target_os = "linux",
+    target_os = "freebsd",
+    target_os = "openbsd"
+));
 
-impl ApiKeyType {
-    fn as_str(&self) -> &'static str {
-        match self {
-            ApiKeyType::OpenAI => "openai_api_key",
-            ApiKeyType::Claude => "claude_api_key",
-        }
+/// Keychain service for secure API key storage using keyring crate
+/// Supports Windows (Credential Manager), macOS (Keychain), and Linux (Secret Service).
+/// Falls back to an encrypted secrets file when no OS keychain is available.
+/// Entries keep the service and account names of earlier versions, so keys
+/// already in the macOS Keychain resolve without migration.
+pub struct KeychainService;
+
+/// The OS keychain entry of an account. On platforms without a native store
+/// this fails as unavailable, so keys go to the encrypted file instead.
+fn entry(account: &str) -> keyring::Result<Entry> {
+    if !HAS_NATIVE_STORE {
+        return Err(keyring::Error::NoStorageAccess(
+            "no native credential store on this platform".into(),
+        ));
     }
+    Entry::new(SERVICE_NAME, account)
 }
 
-/// Keychain service for secure API key storage using keyring crate
-/// Supports Windows (Credential Manager), macOS (Keychain), and Linux (Secret Service)
-pub struct KeychainService;
+/// Whether a keyring error means there is no usable OS credential store
+fn is_keychain_unavailable(error: &keyring::Error) -> bool {
+    matches!(
+        error,
+        keyring::Error::PlatformFailure(_) | keyring::Error::NoStorageAccess(_)
+    )
+}
 
 impl KeychainService {
-    /// Store an API key securely in the system keychain
-    pub fn store_api_key(key_type: ApiKeyType, api_key: &str) -> Result<()> {
-        let account = key_type.as_str();
-        println!(
-            "[KeychainService::store_api_key] Storing key for service: {}, account: {}",
-            SERVICE_NAME, account
-        );
+    /// Check if the OS keychain can be used on this machine
+    pub fn is_keychain_available() -> bool {
+        match entry("availability_probe").and_then(|e| e.get_password()) {
+            Ok(_) | Err(keyring::Error::NoEntry) => true,
+            Err(e) => !is_keychain_unavailable(&e),
+        }
+    }
 
-        let entry = Entry::new(SERVICE_NAME, account)
-            .map_err(|e| AppError::Keychain(format!("Failed to create keyring entry: {}", e)))?;
+    /// Store a provider secret for the active profile
+    pub fn store_secret(provider_id: &str, value: &str) -> Result<()> {
+        Self::store_profile_secret(provider_id, &Self::active_profile(provider_id), value)
+    }
 
-        entry
-            .set_password(api_key)
-            .map_err(|e| AppError::Keychain(format!("Failed to store API key: {}", e)))?;
+    /// Retrieve the provider secret of the active profile.
+    /// Falls back to environment variables / `.env` when enabled in settings.
+    pub fn get_secret(provider_id: &str) -> Result<Option<String>> {
+        let secret = Self::get_profile_secret(provider_id, &Self::active_profile(provider_id))?;
+        if secret.is_some() || !AppSettings::load().is_ok_and(|s| s.env_key_fallback) {
+            return Ok(secret);
+        }
+
+        let key = env_keys::lookup(Self::provider(provider_id)?);
+        if let Some(ref key) = key {
+            redact::register_secret(key);
+        }
+        Ok(key)
+    }
+
+    /// Delete the provider secret of the active profile
+    pub fn delete_secret(provider_id: &str) -> Result<()> {
+        Self::delete_profile_secret(provider_id, &Self::active_profile(provider_id))
+    }
+
+    /// Check if a provider secret is stored for the active profile
+    pub fn has_secret(provider_id: &str) -> Result<bool> {
+        Ok(Self::get_secret(provider_id)?.is_some())
+    }
 
-        println!("[KeychainService::store_api_key] Successfully stored key");
+    /// Get the active credential profile for a provider
+    pub fn active_profile(provider_id: &str) -> String {
+        CredentialProfiles::load()
+            .map(|profiles| profiles.active_profile(&provider_id.to_lowercase()))
+            .unwrap_or_else(|_| DEFAULT_PROFILE.to_string())
+    }
+
+    /// Store a provider secret under a named credential profile
+    pub fn store_profile_secret(provider_id: &str, profile: &str, value: &str) -> Result<()> {
+        let provider = Self::provider(provider_id)?;
+        validate_profile_name(profile)?;
+        Self::store_account(&provider.account(profile), value)?;
+
+        if profile != DEFAULT_PROFILE {
+            let mut profiles = CredentialProfiles::load()?;
+            profiles.add_profile(provider.id, profile)?;
+            profiles.save()?;
+        }
         Ok(())
     }
 
-    /// Retrieve an API key from the system keychain
-    pub fn get_api_key(key_type: ApiKeyType) -> Result<Option<String>> {
-        let account = key_type.as_str();
-        println!(
-            "[KeychainService::get_api_key] Getting key for service: {}, account: {}",
+    /// Retrieve the provider secret of a named credential profile
+    pub fn get_profile_secret(provider_id: &str, profile: &str) -> Result<Option<String>> {
+        let provider = Self::provider(provider_id)?;
+        validate_profile_name(profile)?;
+        Self::get_account(&provider.account(profile))
+    }
+
+    /// Delete the provider secret of a named credential profile and unregister it
+    pub fn delete_profile_secret(provider_id: &str, profile: &str) -> Result<()> {
+        let provider = Self::provider(provider_id)?;
+        validate_profile_name(profile)?;
+        Self::delete_account(&provider.account(profile))?;
+
+        if profile != DEFAULT_PROFILE {
+            let mut profiles = CredentialProfiles::load()?;
+            profiles.remove_profile(provider.id, profile);
+            profiles.save()?;
+        }
+        Ok(())
+    }
+
+    /// Resolve a provider id against the registry of known providers
+    fn provider(provider_id: &str) -> Result<&'static SecretProvider> {
+        providers::find(provider_id)
+            .ok_or_else(|| AppError::Keychain(format!("Unknown provider: {}", provider_id)))
+    }
+
+    /// Store a secret securely in the system keychain
+    fn store_account(account: &str, api_key: &str) -> Result<()> {
+        redact::register_secret(api_key);
+        log::debug!(
+            "[KeychainService::store_api_key] Storing key for service: {}, account: {}",
             SERVICE_NAME, account
         );
 
-        let entry = Entry::new(SERVICE_NAME, account)
-            .map_err(|e| AppError::Keychain(format!("Failed to create keyring entry: {}", e)))?;
+        match entry(account).and_then(|entry| entry.set_password(api_key)) {
+            Ok(()) => {
+                log::debug!("[KeychainService::store_api_key] Successfully stored key");
+                Ok(())
+            }
+            Err(e) if is_keychain_unavailable(&e) => {
+                log::warn!("[KeychainService::store_api_key] Keychain unavailable, using encrypted file");
+                EncryptedFileStore::set(account, api_key)
+            }
+            Err(e) => Err(AppError::Keychain(format!("Failed to store API key: {}", e))),
+        }
+    }
 
-        match entry.get_password() {
+    /// Retrieve a secret from the system keychain
+    fn get_account(account: &str) -> Result<Option<String>> {
+        log::debug!(
+            "[KeychainService::get_api_key] Getting key for service: {}, account: {}",
+            SERVICE_NAME, account
+        );
+
+        match entry(account).and_then(|entry| entry.get_password()) {
             Ok(password) => {
                 if password.is_empty() {
-                    println!("[KeychainService::get_api_key] Empty password");
+                    log::debug!("[KeychainService::get_api_key] Empty password");
                     return Ok(None);
                 }
-                println!(
-                    "[KeychainService::get_api_key] Found key, length: {}",
-                    password.len()
-                );
+                log::debug!("[KeychainService::get_api_key] Found key");
+                redact::register_secret(&password);
                 Ok(Some(password))
             }
             Err(keyring::Error::NoEntry) => {
-                println!("[KeychainService::get_api_key] No entry found");
-                Ok(None)
+                log::debug!("[KeychainService::get_api_key] No entry found");
+                Self::get_fallback_key(account)
+            }
+            Err(e) if is_keychain_unavailable(&e) => {
+                log::warn!("[KeychainService::get_api_key] Keychain unavailable, using encrypted file");
+                Self::get_fallback_key(account)
             }
             Err(e) => Err(AppError::Keychain(format!("Failed to get API key: {}", e))),
         }
     }
 
-    /// Delete an API key from the system keychain
-    pub fn delete_api_key(key_type: ApiKeyType) -> Result<()> {
-        let account = key_type.as_str();
-        println!(
+    /// Delete a secret from the system keychain
+    fn delete_account(account: &str) -> Result<()> {
+        log::debug!(
             "[KeychainService::delete_api_key] Deleting key for service: {}, account: {}",
             SERVICE_NAME, account
         );
 
-        let entry = Entry::new(SERVICE_NAME, account)
-            .map_err(|e| AppError::Keychain(format!("Failed to create keyring entry: {}", e)))?;
-
-        match entry.delete_credential() {
+        let result = match entry(account).and_then(|entry| entry.delete_credential()) {
             Ok(()) => {
-                println!("[KeychainService::delete_api_key] Successfully deleted key");
+                log::debug!("[KeychainService::delete_api_key] Successfully deleted key");
                 Ok(())
             }
             Err(keyring::Error::NoEntry) => {
                 // Ignore "not found" errors - key is already deleted
-                println!("[KeychainService::delete_api_key] No entry found (already deleted)");
+                log::debug!("[KeychainService::delete_api_key] No entry found (already deleted)");
                 Ok(())
             }
+            Err(e) if is_keychain_unavailable(&e) => Ok(()),
             Err(e) => Err(AppError::Keychain(format!(
                 "Failed to delete API key: {}",
                 e
             ))),
-        }
-    }
-
-    /// Check if an API key is stored
-    pub fn has_api_key(key_type: ApiKeyType) -> Result<bool> {
-        Ok(Self::get_api_key(key_type)?.is_some())
-    }
-
-    /// Store OpenAI API key
-    pub fn store_openai_key(api_key: &str) -> Result<()> {
-        Self::store_api_key(ApiKeyType::OpenAI, api_key)
-    }
+        };
 
-    /// Get OpenAI API key
-    pub fn get_openai_key() -> Result<Option<String>> {
-        Self::get_api_key(ApiKeyType::OpenAI)
-    }
+        // Also remove any copy kept in the encrypted file
+        if result.is_ok() && EncryptedFileStore::is_unlocked() {
+            EncryptedFileStore::remove(account)?;
+        }
 
-    /// Store Claude API key
-    pub fn store_claude_key(api_key: &str) -> Result<()> {
-        Self::store_api_key(ApiKeyType::Claude, api_key)
+        result
     }
 
-    /// Get Claude API key
-    pub fn get_claude_key() -> Result<Option<String>> {
-        Self::get_api_key(ApiKeyType::Claude)
+    /// Read a key from the encrypted file fallback, if it is unlocked
+    fn get_fallback_key(account: &str) -> Result<Option<String>> {
+        if EncryptedFileStore::is_unlocked() {
+            EncryptedFileStore::get(account)
+        } else {
+            Ok(None)
+        }
     }
 }
 
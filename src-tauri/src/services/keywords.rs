@@ -0,0 +1,199 @@
+use crate::error::Result;
+use crate::services::llm::{parse_json_response, LlmProvider};
+use crate::services::ollama::language_code_to_name;
+use crate::services::whisper::TranscriptionSegment;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use tokio_util::sync::CancellationToken;
+
+/// Keywords returned when the caller does not ask for a number
+pub const DEFAULT_MAX_KEYWORDS: usize = 15;
+
+const KEYWORDS_SYSTEM: &str = "You are an SEO editor who picks the terms people would search \
+                               for to find a recording. Respond with the JSON array only, \
+                               without any explanation.";
+
+/// Reply budget: a short JSON array
+const KEYWORDS_MAX_TOKENS: u32 = 1500;
+
+/// A keyword or key phrase of a transcript, with the segments it occurs in
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Keyword {
+    pub phrase: String,
+    /// 0-1, higher is more central to the recording
+    pub relevance: f32,
+    pub occurrences: Vec<KeywordOccurrence>,
+}
+
+/// A segment a keyword occurs in
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KeywordOccurrence {
+    /// Index into the transcript segments
+    pub segment: usize,
+    pub start: f64,
+    pub end: f64,
+}
+
+/// A keyword as returned by the LLM
+#[derive(Debug, Deserialize)]
+struct RawKeyword {
+    phrase: String,
+    #[serde(default)]
+    relevance: Option<f32>,
+    #[serde(default)]
+    segments: Vec<usize>,
+}
+
+/// Extract ranked keywords and key phrases from transcript segments
+pub async fn extract_keywords(
+    llm: &dyn LlmProvider,
+    segments: &[TranscriptionSegment],
+    language: &str,
+    max_keywords: usize,
+    cancel: &CancellationToken,
+) -> Result<Vec<Keyword>> {
+    log::info!("[keywords] Extracting keywords with {} ({})", llm.id(), llm.model());
+    let prompt = build_keywords_prompt(segments, language, max_keywords);
+    let response = llm
+        .complete(KEYWORDS_SYSTEM, &prompt, KEYWORDS_MAX_TOKENS, cancel)
+        .await?;
+    parse_keywords_response(&response, segments, max_keywords)
+}
+
+fn build_keywords_prompt(
+    segments: &[TranscriptionSegment],
+    language: &str,
+    max_keywords: usize,
+) -> String {
+    let segments_text: Vec<String> = segments
+        .iter()
+        .enumerate()
+        .map(|(i, s)| format!("[{}] {}", i, s.text.trim()))
+        .collect();
+
+    format!(
+        "List up to {} keywords and key phrases of this transcript, most relevant first, \
+         for tagging and search metadata. Write them in {} as they would be searched.\n\n\
+         Rules:\n\
+         - Prefer specific names, topics and multi-word phrases over generic words\n\
+         - relevance is between 0 and 1\n\
+         - segments lists the indexes of the segments that mention the keyword\n\n\
+         Segments:\n{}\n\n\
+         Response format: [{{\"phrase\": \"color grading\", \"relevance\": 0.9, \
+         \"segments\": [3, 12]}}, ...]",
+        max_keywords,
+        language_code_to_name(language),
+        segments_text.join("\n")
+    )
+}
+
+/// Parse the LLM's keywords: drop duplicates and out-of-range segments, add the
+/// segments that literally contain the phrase, and rank by relevance
+fn parse_keywords_response(
+    response: &str,
+    segments: &[TranscriptionSegment],
+    max_keywords: usize,
+) -> Result<Vec<Keyword>> {
+    let raw: Vec<RawKeyword> = parse_json_response(response, "keywords")?;
+    let count = raw.len().max(1) as f32;
+
+    let mut seen = HashSet::new();
+    let mut keywords: Vec<Keyword> = raw
+        .into_iter()
+        .enumerate()
+        .filter_map(|(rank, keyword)| {
+            let phrase = keyword.phrase.split_whitespace().collect::<Vec<_>>().join(" ");
+            if phrase.is_empty() || !seen.insert(phrase.to_lowercase()) {
+                return None;
+            }
+            // Without a score the model's order is the ranking
+            let relevance = keyword
+                .relevance
+                .unwrap_or(1.0 - rank as f32 / count)
+                .clamp(0.0, 1.0);
+            let occurrences = occurrences(&phrase, &keyword.segments, segments);
+            Some(Keyword {
+                phrase,
+                relevance,
+                occurrences,
+            })
+        })
+        .collect();
+
+    keywords.sort_by(|a, b| b.relevance.total_cmp(&a.relevance));
+    keywords.truncate(max_keywords);
+    Ok(keywords)
+}
+
+/// Segments the model pointed at plus those containing the phrase, in transcript order
+fn occurrences(
+    phrase: &str,
+    suggested: &[usize],
+    segments: &[TranscriptionSegment],
+) -> Vec<KeywordOccurrence> {
+    let needle = phrase.to_lowercase();
+    let mut indexes: Vec<usize> = suggested
+        .iter()
+        .copied()
+        .filter(|&i| i < segments.len())
+        .chain(
+            segments
+                .iter()
+                .enumerate()
+                .filter(|(_, s)| s.text.to_lowercase().contains(&needle))
+                .map(|(i, _)| i),
+        )
+        .collect();
+    indexes.sort_unstable();
+    indexes.dedup();
+
+    indexes
+        .into_iter()
+        .map(|i| KeywordOccurrence {
+            segment: i,
+            start: segments[i].start,
+            end: segments[i].end,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_keywords_response() {
+        let segments = vec![
+            TranscriptionSegment::new(0.0, 5.0, "Welcome to the color grading basics."),
+            TranscriptionSegment::new(5.0, 10.0, "We start with DaVinci Resolve."),
+            TranscriptionSegment::new(10.0, 15.0, "Color grading sets the mood."),
+        ];
+        let response = r#"Keywords:
+[
+  {"phrase": "DaVinci Resolve", "relevance": 0.6, "segments": [1, 40]},
+  {"phrase": "Color  grading", "relevance": 0.95, "segments": [0]},
+  {"phrase": "color grading", "relevance": 0.2, "segments": []},
+  {"phrase": "  ", "relevance": 1.0}
+]"#;
+
+        let keywords = parse_keywords_response(response, &segments, 10).unwrap();
+        assert_eq!(keywords.len(), 2);
+        assert_eq!(keywords[0].phrase, "Color grading");
+        // Literal matches are added to the segments the model named
+        let indexes: Vec<usize> = keywords[0].occurrences.iter().map(|o| o.segment).collect();
+        assert_eq!(indexes, [0, 2]);
+        // Out-of-range segment indexes are dropped
+        assert_eq!(keywords[1].occurrences.len(), 1);
+        assert_eq!(keywords[1].occurrences[0].start, 5.0);
+
+        assert_eq!(parse_keywords_response(response, &segments, 1).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_missing_relevance_keeps_model_order() {
+        let response = r#"[{"phrase": "first"}, {"phrase": "second"}]"#;
+        let keywords = parse_keywords_response(response, &[], 10).unwrap();
+        assert_eq!(keywords[0].phrase, "first");
+        assert!(keywords[0].relevance > keywords[1].relevance);
+    }
+}
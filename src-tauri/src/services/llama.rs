@@ -0,0 +1,393 @@
+use crate::error::{AppError, Result};
+use crate::services::download::{download_file, DownloadProgress};
+use crate::services::http;
+use crate::services::ollama::summary_instructions;
+use std::path::PathBuf;
+use tokio::fs;
+use tokio_util::sync::CancellationToken;
+
+/// A GGUF chat model for the embedded llama.cpp backend
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct GgufModel {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub size_bytes: u64,
+    pub size_display: String,
+    pub url: String,
+}
+
+impl GgufModel {
+    /// Get downloadable GGUF models (Q4_K_M quantizations of small instruct models)
+    pub fn available_models() -> Vec<GgufModel> {
+        vec![
+            GgufModel {
+                id: "qwen2.5-1.5b-instruct".to_string(),
+                name: "Qwen 2.5 1.5B Instruct".to_string(),
+                description: "Fast, runs on any machine".to_string(),
+                size_bytes: 1_120_000_000,
+                size_display: "1.1 GB".to_string(),
+                url: "https://huggingface.co/Qwen/Qwen2.5-1.5B-Instruct-GGUF/resolve/main/qwen2.5-1.5b-instruct-q4_k_m.gguf".to_string(),
+            },
+            GgufModel {
+                id: "gemma-2-2b-it".to_string(),
+                name: "Gemma 2 2B Instruct".to_string(),
+                description: "Small, good English summaries".to_string(),
+                size_bytes: 1_710_000_000,
+                size_display: "1.7 GB".to_string(),
+                url: "https://huggingface.co/bartowski/gemma-2-2b-it-GGUF/resolve/main/gemma-2-2b-it-Q4_K_M.gguf".to_string(),
+            },
+            GgufModel {
+                id: "llama-3.2-3b-instruct".to_string(),
+                name: "Llama 3.2 3B Instruct".to_string(),
+                description: "Balanced, multilingual".to_string(),
+                size_bytes: 2_020_000_000,
+                size_display: "2.0 GB".to_string(),
+                url: "https://huggingface.co/bartowski/Llama-3.2-3B-Instruct-GGUF/resolve/main/Llama-3.2-3B-Instruct-Q4_K_M.gguf".to_string(),
+            },
+            GgufModel {
+                id: "qwen2.5-7b-instruct".to_string(),
+                name: "Qwen 2.5 7B Instruct".to_string(),
+                description: "Most capable, needs 8 GB of memory".to_string(),
+                size_bytes: 4_680_000_000,
+                size_display: "4.7 GB".to_string(),
+                url: "https://huggingface.co/bartowski/Qwen2.5-7B-Instruct-GGUF/resolve/main/Qwen2.5-7B-Instruct-Q4_K_M.gguf".to_string(),
+            },
+        ]
+    }
+}
+
+/// Status of a GGUF model (available + installed info)
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct GgufModelStatus {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub size_display: String,
+    pub installed: bool,
+    pub path: Option<String>,
+}
+
+/// Embedded llama.cpp backend: GGUF model downloads and offline summarization
+pub struct LlamaService {
+    models_dir: PathBuf,
+}
+
+impl LlamaService {
+    /// Create a new llama.cpp service
+    pub fn new() -> Result<Self> {
+        Ok(Self {
+            models_dir: Self::get_models_directory()?,
+        })
+    }
+
+    /// Whether this build includes the llama.cpp engine (the `llama-cpp` feature)
+    pub fn is_available() -> bool {
+        cfg!(feature = "llama-cpp")
+    }
+
+    /// Get the GGUF models directory path
+    pub fn get_models_directory() -> Result<PathBuf> {
+        let data_dir = dirs::data_local_dir()
+            .ok_or_else(|| AppError::InvalidPath("Cannot find data directory".to_string()))?;
+
+        Ok(data_dir.join("clip-flow").join("llm-models"))
+    }
+
+    /// Get list of installed models
+    pub async fn get_installed_models(&self) -> Result<Vec<String>> {
+        fs::create_dir_all(&self.models_dir).await?;
+
+        let mut installed = Vec::new();
+        let mut entries = fs::read_dir(&self.models_dir).await?;
+
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            if path.extension().is_some_and(|e| e == "gguf") {
+                if let Some(stem) = path.file_stem() {
+                    installed.push(stem.to_string_lossy().to_string());
+                }
+            }
+        }
+
+        Ok(installed)
+    }
+
+    /// Get status of all models (available + installed info)
+    pub async fn get_models_status(&self) -> Result<Vec<GgufModelStatus>> {
+        let installed = self.get_installed_models().await?;
+
+        Ok(GgufModel::available_models()
+            .into_iter()
+            .map(|model| {
+                let is_installed = installed.contains(&model.id);
+                GgufModelStatus {
+                    path: is_installed
+                        .then(|| self.get_model_path(&model.id).to_string_lossy().to_string()),
+                    id: model.id,
+                    name: model.name,
+                    description: model.description,
+                    size_display: model.size_display,
+                    installed: is_installed,
+                }
+            })
+            .collect())
+    }
+
+    /// Get the path to a model file
+    pub fn get_model_path(&self, model_id: &str) -> PathBuf {
+        self.models_dir.join(format!("{}.gguf", model_id))
+    }
+
+    /// Download a GGUF model with progress callback.
+    /// Cancelling the token stops the download and removes the partial file.
+    pub async fn download_model<F>(
+        &self,
+        model_id: &str,
+        cancel: &CancellationToken,
+        on_progress: F,
+    ) -> Result<PathBuf>
+    where
+        F: Fn(DownloadProgress) + Send + 'static,
+    {
+        fs::create_dir_all(&self.models_dir).await?;
+
+        let model = GgufModel::available_models()
+            .into_iter()
+            .find(|m| m.id == model_id)
+            .ok_or_else(|| AppError::ModelNotFound(model_id.to_string()))?;
+
+        let output_path = self.get_model_path(model_id);
+        download_file(
+            &http::download_client(),
+            &model.url,
+            &output_path,
+            model.size_bytes,
+            model_id,
+            cancel,
+            None,
+            on_progress,
+        )
+        .await?;
+
+        Ok(output_path)
+    }
+
+    /// Delete a downloaded model
+    pub async fn delete_model(&self, model_id: &str) -> Result<()> {
+        let model_path = self.get_model_path(model_id);
+        if model_path.exists() {
+            fs::remove_file(&model_path).await?;
+        }
+        Ok(())
+    }
+
+    /// Summarize text fully offline with a downloaded GGUF model.
+    /// Generation runs on a blocking thread and stops when the token is cancelled.
+    pub async fn summarize(
+        &self,
+        model_id: &str,
+        text: &str,
+        language: &str,
+        cancel: &CancellationToken,
+    ) -> Result<String> {
+        let system = summary_instructions(language);
+        let user = format!("Summarize the following transcription:\n\n{}", text);
+        let summary = self
+            .complete(model_id, &system, &user, SUMMARY_MAX_TOKENS, cancel)
+            .await?;
+
+        Ok(summary.trim().to_string())
+    }
+
+    /// Answer a prompt with a downloaded GGUF model.
+    /// Generation runs on a blocking thread and stops when the token is cancelled.
+    pub async fn complete(
+        &self,
+        model_id: &str,
+        system: &str,
+        prompt: &str,
+        max_tokens: usize,
+        cancel: &CancellationToken,
+    ) -> Result<String> {
+        let model_path = self.get_model_path(model_id);
+        if !model_path.exists() {
+            return Err(AppError::ModelNotFound(model_id.to_string()));
+        }
+
+        let (system, prompt) = (system.to_string(), prompt.to_string());
+        let cancel = cancel.clone();
+
+        tokio::task::spawn_blocking(move || {
+            engine::generate(&model_path, &system, &prompt, max_tokens, &cancel)
+        })
+        .await
+        .map_err(|e| AppError::ProcessFailed(format!("llama.cpp task failed: {}", e)))?
+    }
+}
+
+/// Longest summary generated, in tokens
+const SUMMARY_MAX_TOKENS: usize = 1000;
+
+#[cfg(feature = "llama-cpp")]
+mod engine {
+    use crate::error::{AppError, Result};
+    use llama_cpp_2::context::params::LlamaContextParams;
+    use llama_cpp_2::llama_backend::LlamaBackend;
+    use llama_cpp_2::llama_batch::LlamaBatch;
+    use llama_cpp_2::model::params::LlamaModelParams;
+    use llama_cpp_2::model::{AddBos, LlamaChatMessage, LlamaModel, Special};
+    use llama_cpp_2::sampling::LlamaSampler;
+    use std::num::NonZeroU32;
+    use std::path::Path;
+    use std::sync::OnceLock;
+    use tokio_util::sync::CancellationToken;
+
+    /// Context window used for summaries, in tokens
+    const CONTEXT_TOKENS: u32 = 8192;
+    const SAMPLING_TEMPERATURE: f32 = 0.3;
+
+    fn llama_error(e: impl std::fmt::Display) -> AppError {
+        AppError::ProcessFailed(format!("llama.cpp: {}", e))
+    }
+
+    /// The llama.cpp backend can only be initialized once per process
+    fn backend() -> Result<&'static LlamaBackend> {
+        static BACKEND: OnceLock<LlamaBackend> = OnceLock::new();
+        if let Some(backend) = BACKEND.get() {
+            return Ok(backend);
+        }
+        let backend = LlamaBackend::init().map_err(llama_error)?;
+        Ok(BACKEND.get_or_init(|| backend))
+    }
+
+    /// Load a GGUF model and generate a chat response
+    pub fn generate(
+        model_path: &Path,
+        system: &str,
+        user: &str,
+        max_tokens: usize,
+        cancel: &CancellationToken,
+    ) -> Result<String> {
+        let backend = backend()?;
+        let model = LlamaModel::load_from_file(backend, model_path, &LlamaModelParams::default())
+            .map_err(llama_error)?;
+
+        let template = model.chat_template(None).map_err(llama_error)?;
+        let messages = [
+            LlamaChatMessage::new("system".to_string(), system.to_string()).map_err(llama_error)?,
+            LlamaChatMessage::new("user".to_string(), user.to_string()).map_err(llama_error)?,
+        ];
+        let prompt = model
+            .apply_chat_template(&template, &messages, true)
+            .map_err(llama_error)?;
+
+        let tokens = model.str_to_token(&prompt, AddBos::Always).map_err(llama_error)?;
+        if tokens.len() + max_tokens > CONTEXT_TOKENS as usize {
+            return Err(AppError::InvalidInput(format!(
+                "The text is too long for the local model ({} tokens, at most {} fit)",
+                tokens.len(),
+                CONTEXT_TOKENS as usize - max_tokens
+            )));
+        }
+
+        let context_params = LlamaContextParams::default()
+            .with_n_ctx(NonZeroU32::new(CONTEXT_TOKENS))
+            .with_n_batch(CONTEXT_TOKENS);
+        let mut context = model.new_context(backend, context_params).map_err(llama_error)?;
+
+        let mut batch = LlamaBatch::new(CONTEXT_TOKENS as usize, 1);
+        let last = tokens.len() as i32 - 1;
+        for (position, token) in (0_i32..).zip(tokens) {
+            batch.add(token, position, &[0], position == last).map_err(llama_error)?;
+        }
+        context.decode(&mut batch).map_err(llama_error)?;
+
+        let mut sampler = LlamaSampler::chain_simple([
+            LlamaSampler::temp(SAMPLING_TEMPERATURE),
+            LlamaSampler::dist(rand_seed()),
+        ]);
+        let mut position = batch.n_tokens();
+        let mut output = String::new();
+
+        for _ in 0..max_tokens {
+            if cancel.is_cancelled() {
+                return Err(AppError::Cancelled);
+            }
+
+            let token = sampler.sample(&context, batch.n_tokens() - 1);
+            sampler.accept(token);
+            if model.is_eog_token(token) {
+                break;
+            }
+            output.push_str(&model.token_to_str(token, Special::Tokenize).map_err(llama_error)?);
+
+            batch.clear();
+            batch.add(token, position, &[0], true).map_err(llama_error)?;
+            position += 1;
+            context.decode(&mut batch).map_err(llama_error)?;
+        }
+
+        Ok(output)
+    }
+
+    fn rand_seed() -> u32 {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.subsec_nanos())
+            .unwrap_or(0)
+    }
+}
+
+#[cfg(not(feature = "llama-cpp"))]
+mod engine {
+    use crate::error::{AppError, Result};
+    use std::path::Path;
+    use tokio_util::sync::CancellationToken;
+
+    pub fn generate(
+        _model_path: &Path,
+        _system: &str,
+        _user: &str,
+        _max_tokens: usize,
+        _cancel: &CancellationToken,
+    ) -> Result<String> {
+        Err(AppError::ProcessFailed(
+            "This build does not include the embedded llama.cpp backend".to_string(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_available_models_are_gguf() {
+        let models = GgufModel::available_models();
+        assert!(models.iter().all(|m| m.url.ends_with(".gguf")));
+
+        let ids: std::collections::HashSet<_> = models.iter().map(|m| m.id.as_str()).collect();
+        assert_eq!(ids.len(), models.len());
+    }
+
+    #[tokio::test]
+    async fn test_installed_models_and_status() {
+        let temp = tempfile::tempdir().unwrap();
+        let service = LlamaService {
+            models_dir: temp.path().to_path_buf(),
+        };
+        std::fs::write(service.get_model_path("gemma-2-2b-it"), b"gguf").unwrap();
+        std::fs::write(temp.path().join("gemma-2-2b-it.gguf.tmp"), b"partial").unwrap();
+
+        assert_eq!(service.get_installed_models().await.unwrap(), vec!["gemma-2-2b-it"]);
+        let status = service.get_models_status().await.unwrap();
+        let gemma = status.iter().find(|m| m.id == "gemma-2-2b-it").unwrap();
+        assert!(gemma.installed && gemma.path.is_some());
+        assert!(status.iter().filter(|m| m.installed).count() == 1);
+
+        let cancel = CancellationToken::new();
+        let result = service.summarize("qwen2.5-7b-instruct", "text", "en", &cancel).await;
+        assert!(matches!(result, Err(AppError::ModelNotFound(_))));
+    }
+}
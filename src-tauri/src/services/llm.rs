@@ -0,0 +1,340 @@
+use crate::error::{AppError, Result};
+use crate::services::claude::{ClaudeMessage, ClaudeService};
+use crate::services::gemini::{GeminiMessage, GeminiService};
+use crate::services::groq::GROQ_API_BASE;
+use crate::services::cancellation::cancellable;
+use crate::services::llama::LlamaService;
+use crate::services::ollama::{self, OllamaService};
+use crate::services::openai::{self, OpenAIService};
+use crate::services::openai_compatible::normalize_base_url;
+use crate::services::providers;
+use async_trait::async_trait;
+use serde::de::DeserializeOwned;
+use tokio_util::sync::CancellationToken;
+
+/// Provider id of a local Ollama server
+pub const OLLAMA: &str = "ollama";
+
+/// Provider id of the embedded llama.cpp backend
+pub const LLAMA: &str = "llama";
+
+/// Temperature for analysis prompts: low, so structured answers stay consistent
+const ANALYSIS_TEMPERATURE: f32 = 0.3;
+
+/// A text-generation backend. Transcript analyses (keywords, chapters, action
+/// items, ...) build one prompt and parse the reply, so every configured LLM
+/// provider can run all of them.
+#[async_trait]
+pub trait LlmProvider: Send + Sync {
+    /// Provider id, e.g. `ollama` or `claude`
+    fn id(&self) -> &'static str;
+
+    /// Model used, as recorded with the results
+    fn model(&self) -> &str;
+
+    /// Answer a prompt with system instructions. Cancelling the token stops the
+    /// request and returns `AppError::Cancelled`.
+    async fn complete(
+        &self,
+        system: &str,
+        prompt: &str,
+        max_tokens: u32,
+        cancel: &CancellationToken,
+    ) -> Result<String>;
+}
+
+/// A model served by a local Ollama server
+pub struct OllamaLlm {
+    service: OllamaService,
+    model: String,
+}
+
+impl OllamaLlm {
+    pub fn new(model: &str) -> Self {
+        Self {
+            service: OllamaService::new(),
+            model: model.to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl LlmProvider for OllamaLlm {
+    fn id(&self) -> &'static str {
+        OLLAMA
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    async fn complete(
+        &self,
+        system: &str,
+        prompt: &str,
+        _max_tokens: u32,
+        cancel: &CancellationToken,
+    ) -> Result<String> {
+        let messages = vec![
+            ollama::ChatMessage {
+                role: "system".to_string(),
+                content: system.to_string(),
+            },
+            ollama::ChatMessage {
+                role: "user".to_string(),
+                content: prompt.to_string(),
+            },
+        ];
+        cancellable(cancel, self.service.chat(&self.model, messages)).await
+    }
+}
+
+/// A chat model behind an OpenAI-protocol API (OpenAI, Groq or a self-hosted server)
+pub struct OpenAIChatLlm {
+    id: &'static str,
+    service: OpenAIService,
+    model: String,
+}
+
+impl OpenAIChatLlm {
+    pub fn openai(api_key: &str, model: &str) -> Self {
+        Self::with_service(providers::OPENAI, OpenAIService::new(api_key), model)
+    }
+
+    pub fn groq(api_key: &str, model: &str) -> Self {
+        let service = OpenAIService::hosted(api_key, providers::GROQ, GROQ_API_BASE);
+        Self::with_service(providers::GROQ, service, model)
+    }
+
+    pub fn openai_compatible(base_url: &str, api_key: Option<&str>, model: &str) -> Result<Self> {
+        let base_url = normalize_base_url(base_url)?;
+        let service = OpenAIService::with_base_url(api_key.unwrap_or_default(), &base_url);
+        Ok(Self::with_service(providers::OPENAI_COMPATIBLE, service, model))
+    }
+
+    fn with_service(id: &'static str, service: OpenAIService, model: &str) -> Self {
+        Self {
+            id,
+            service,
+            model: model.to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl LlmProvider for OpenAIChatLlm {
+    fn id(&self) -> &'static str {
+        self.id
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    async fn complete(
+        &self,
+        system: &str,
+        prompt: &str,
+        max_tokens: u32,
+        cancel: &CancellationToken,
+    ) -> Result<String> {
+        let messages = vec![
+            openai::ChatMessage {
+                role: "system".to_string(),
+                content: system.to_string(),
+            },
+            openai::ChatMessage {
+                role: "user".to_string(),
+                content: prompt.to_string(),
+            },
+        ];
+        let request = self.service.chat(
+            &self.model,
+            messages,
+            Some(ANALYSIS_TEMPERATURE),
+            Some(max_tokens),
+        );
+        cancellable(cancel, request).await
+    }
+}
+
+/// A Claude model
+pub struct ClaudeLlm {
+    service: ClaudeService,
+    model: String,
+}
+
+impl ClaudeLlm {
+    pub fn new(api_key: &str, model: &str) -> Self {
+        Self {
+            service: ClaudeService::new(api_key),
+            model: model.to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl LlmProvider for ClaudeLlm {
+    fn id(&self) -> &'static str {
+        providers::CLAUDE
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    async fn complete(
+        &self,
+        system: &str,
+        prompt: &str,
+        max_tokens: u32,
+        cancel: &CancellationToken,
+    ) -> Result<String> {
+        let messages = vec![ClaudeMessage {
+            role: "user".to_string(),
+            content: prompt.to_string(),
+        }];
+        let request = self.service.message(
+            &self.model,
+            messages,
+            Some(system),
+            Some(ANALYSIS_TEMPERATURE),
+            max_tokens,
+        );
+        cancellable(cancel, request).await
+    }
+}
+
+/// A Gemini model
+pub struct GeminiLlm {
+    service: GeminiService,
+    model: String,
+}
+
+impl GeminiLlm {
+    pub fn new(api_key: &str, model: &str) -> Self {
+        Self {
+            service: GeminiService::new(api_key),
+            model: model.to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl LlmProvider for GeminiLlm {
+    fn id(&self) -> &'static str {
+        providers::GEMINI
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    async fn complete(
+        &self,
+        system: &str,
+        prompt: &str,
+        max_tokens: u32,
+        cancel: &CancellationToken,
+    ) -> Result<String> {
+        let messages = vec![GeminiMessage {
+            role: "user".to_string(),
+            content: prompt.to_string(),
+        }];
+        let request = self.service.chat(
+            &self.model,
+            messages,
+            Some(system),
+            Some(ANALYSIS_TEMPERATURE),
+            max_tokens,
+        );
+        cancellable(cancel, request).await
+    }
+}
+
+/// A downloaded GGUF model run by the embedded llama.cpp backend
+pub struct LlamaLlm {
+    service: LlamaService,
+    model: String,
+}
+
+impl LlamaLlm {
+    pub fn new(model: &str) -> Result<Self> {
+        Ok(Self {
+            service: LlamaService::new()?,
+            model: model.to_string(),
+        })
+    }
+}
+
+#[async_trait]
+impl LlmProvider for LlamaLlm {
+    fn id(&self) -> &'static str {
+        LLAMA
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    async fn complete(
+        &self,
+        system: &str,
+        prompt: &str,
+        max_tokens: u32,
+        cancel: &CancellationToken,
+    ) -> Result<String> {
+        self.service
+            .complete(&self.model, system, prompt, max_tokens as usize, cancel)
+            .await
+    }
+}
+
+/// Parse the JSON an LLM was asked to answer with. Models often wrap it in prose
+/// or code fences, so only the outermost array or object (whichever `T` is) is read.
+pub(crate) fn parse_json_response<T: DeserializeOwned>(response: &str, what: &str) -> Result<T> {
+    let trimmed = response.trim();
+    let json = [('[', ']'), ('{', '}')]
+        .iter()
+        .filter_map(|&(open, close)| match (trimmed.find(open), trimmed.rfind(close)) {
+            (Some(start), Some(end)) if start < end => Some(&trimmed[start..=end]),
+            _ => None,
+        })
+        .find(|candidate| serde_json::from_str::<T>(candidate).is_ok())
+        .unwrap_or(trimmed);
+
+    serde_json::from_str(json)
+        .map_err(|_| AppError::ProcessFailed(format!("Failed to parse {} response", what)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Item {
+        name: String,
+    }
+
+    #[test]
+    fn test_parse_json_response_skips_surrounding_text() {
+        let items: Vec<Item> = parse_json_response(
+            "Here you go:\n```json\n[{\"name\": \"a\"}, {\"name\": \"b\"}]\n```",
+            "items",
+        )
+        .unwrap();
+        assert_eq!(items.len(), 2);
+
+        let item: Item =
+            parse_json_response("Sure! {\"name\": \"only\"} Hope that helps.", "item").unwrap();
+        assert_eq!(item.name, "only");
+
+        let nested: Item =
+            parse_json_response("{\"name\": \"x\", \"tags\": [1, 2]}", "item").unwrap();
+        assert_eq!(nested.name, "x");
+
+        let error = parse_json_response::<Vec<Item>>("no json here", "items").unwrap_err();
+        assert_eq!(error.to_string(), "Process failed: Failed to parse items response");
+    }
+}
@@ -0,0 +1,190 @@
+use crate::error::Result;
+use crate::services::database::Database;
+use crate::services::directory_service::is_supported_media;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+/// Bytes hashed from each end of a file
+const HASH_SAMPLE_SIZE: u64 = 4 * 1024 * 1024;
+
+/// What happened to a file of an import batch
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportStatus {
+    /// Added to the database
+    Imported,
+    /// Already in the database under this or another path
+    Duplicate,
+    /// Not a supported media file
+    Skipped,
+    /// Could not be read or registered
+    Failed,
+}
+
+/// Outcome of importing one file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ImportReport {
+    pub path: String,
+    pub status: ImportStatus,
+    /// Path the file is already indexed under, for duplicates
+    pub duplicate_of: Option<String>,
+    /// Queued processing job, when processing was requested
+    pub job_id: Option<String>,
+    /// Why the file was skipped or failed
+    pub message: Option<String>,
+}
+
+impl ImportReport {
+    fn new(path: &str, status: ImportStatus) -> Self {
+        Self {
+            path: path.to_string(),
+            status,
+            duplicate_of: None,
+            job_id: None,
+            message: None,
+        }
+    }
+
+    fn with_message(path: &str, status: ImportStatus, message: impl Into<String>) -> Self {
+        Self {
+            message: Some(message.into()),
+            ..Self::new(path, status)
+        }
+    }
+}
+
+/// Hash identifying a file's content: SHA-256 of its size and of the first and
+/// last few MiB, so large videos don't have to be read in full
+pub fn content_hash(path: &Path) -> Result<String> {
+    let mut file = File::open(path)?;
+    let size = file.metadata()?.len();
+    let mut hasher = Sha256::new();
+    hasher.update(size.to_le_bytes());
+
+    let mut buffer = Vec::new();
+    (&mut file)
+        .take(HASH_SAMPLE_SIZE)
+        .read_to_end(&mut buffer)?;
+    hasher.update(&buffer);
+    if size > HASH_SAMPLE_SIZE {
+        buffer.clear();
+        file.seek(SeekFrom::Start(
+            size.saturating_sub(HASH_SAMPLE_SIZE).max(HASH_SAMPLE_SIZE),
+        ))?;
+        file.read_to_end(&mut buffer)?;
+        hasher.update(&buffer);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Hash the indexed files of `size` bytes that have no hash yet, so a copy of
+/// them is recognized. Only files of the same size can have the same content,
+/// so few files are read. Files that can't be read anymore are left alone.
+fn backfill_hashes(db: &Database, size: u64) -> Result<()> {
+    for (id, path) in db.unhashed_media_files(size)? {
+        match content_hash(Path::new(&path)) {
+            Ok(hash) => db.set_content_hash(id, &hash)?,
+            Err(e) => log::debug!("[import] Cannot hash {}: {}", path, e),
+        }
+    }
+    Ok(())
+}
+
+/// Check a file and add it to the database unless it is already indexed by
+/// path or by content
+pub fn import_file(db: &Database, path: &str) -> ImportReport {
+    let file = Path::new(path);
+    if !file.is_file() {
+        return ImportReport::with_message(path, ImportStatus::Skipped, "Not a file");
+    }
+    if !is_supported_media(file) {
+        return ImportReport::with_message(path, ImportStatus::Skipped, "Unsupported file type");
+    }
+
+    let registered = (|| {
+        if db.has_media_file(path)? {
+            return Ok(ImportReport {
+                duplicate_of: Some(path.to_string()),
+                ..ImportReport::new(path, ImportStatus::Duplicate)
+            });
+        }
+        let hash = content_hash(file)?;
+        backfill_hashes(db, file.metadata()?.len())?;
+        if let Some(existing) = db.media_path_with_hash(&hash)? {
+            return Ok(ImportReport {
+                duplicate_of: Some(existing),
+                ..ImportReport::new(path, ImportStatus::Duplicate)
+            });
+        }
+        db.register_media_file(path, &hash)?;
+        Ok(ImportReport::new(path, ImportStatus::Imported))
+    })();
+    registered.unwrap_or_else(|e: crate::error::AppError| {
+        ImportReport::with_message(path, ImportStatus::Failed, e.to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_import_file() {
+        let temp = TempDir::new().unwrap();
+        let path = |name: &str| temp.path().join(name).to_string_lossy().into_owned();
+        std::fs::write(path("a.mp3"), b"audio").unwrap();
+        std::fs::write(path("copy.mp3"), b"audio").unwrap();
+        std::fs::write(path("b.wav"), b"other audio").unwrap();
+        std::fs::write(path("notes.txt"), b"text").unwrap();
+        let db = Database::open_in_memory().unwrap();
+
+        assert_eq!(
+            import_file(&db, &path("a.mp3")).status,
+            ImportStatus::Imported
+        );
+        assert_eq!(
+            import_file(&db, &path("b.wav")).status,
+            ImportStatus::Imported
+        );
+
+        let again = import_file(&db, &path("a.mp3"));
+        assert_eq!(again.status, ImportStatus::Duplicate);
+        let copy = import_file(&db, &path("copy.mp3"));
+        assert_eq!(copy.status, ImportStatus::Duplicate);
+        assert_eq!(copy.duplicate_of, Some(path("a.mp3")));
+
+        // Added by a transcription, without a hash
+        std::fs::write(path("c.m4a"), b"transcribed").unwrap();
+        std::fs::write(path("c copy.m4a"), b"transcribed").unwrap();
+        db.media_file_id(&path("c.m4a")).unwrap();
+        let copy = import_file(&db, &path("c copy.m4a"));
+        assert_eq!(copy.status, ImportStatus::Duplicate);
+        assert_eq!(copy.duplicate_of, Some(path("c.m4a")));
+
+        assert_eq!(
+            import_file(&db, &path("notes.txt")).status,
+            ImportStatus::Skipped
+        );
+        assert_eq!(
+            import_file(&db, &path("missing.mp4")).status,
+            ImportStatus::Skipped
+        );
+    }
+
+    #[test]
+    fn test_content_hash_covers_both_ends() {
+        let temp = TempDir::new().unwrap();
+        let size = HASH_SAMPLE_SIZE as usize * 3;
+        let (a, b) = (temp.path().join("a.mp4"), temp.path().join("b.mp4"));
+        let mut content = vec![0u8; size];
+        std::fs::write(&a, &content).unwrap();
+        content[size - 1] = 1;
+        std::fs::write(&b, &content).unwrap();
+        assert_ne!(content_hash(&a).unwrap(), content_hash(&b).unwrap());
+        assert_eq!(content_hash(&a).unwrap(), content_hash(&a).unwrap());
+    }
+}
@@ -0,0 +1,172 @@
+use crate::error::{AppError, Result};
+use crate::services::directory_service::FileEntry;
+use crate::services::ffmpeg::FFmpegService;
+use futures::stream::{self, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Maximum number of ffprobe processes running at once during a scan
+const PROBE_CONCURRENCY: usize = 4;
+
+/// Probe result remembered for a file, valid while its size and mtime are unchanged
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProbedMedia {
+    pub size: u64,
+    pub modified: Option<u64>,
+    pub duration: f64,
+    pub has_audio: bool,
+}
+
+/// Persistent cache of ffprobe results keyed by file path
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MediaProbeCache {
+    #[serde(default)]
+    entries: HashMap<String, ProbedMedia>,
+}
+
+impl MediaProbeCache {
+    /// Get the default cache file path
+    pub fn default_path() -> Result<PathBuf> {
+        let cache_dir = dirs::cache_dir()
+            .ok_or_else(|| AppError::InvalidPath("Cannot find cache directory".to_string()))?;
+        Ok(cache_dir.join("clip-flow").join("media_probe_cache.json"))
+    }
+
+    /// Load the cache from the default location
+    pub fn load() -> Result<Self> {
+        Self::load_from(&Self::default_path()?)
+    }
+
+    /// Load the cache from a file (empty if missing or corrupted)
+    pub fn load_from(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&content).unwrap_or_default())
+    }
+
+    /// Save the cache to the default location
+    pub fn save(&self) -> Result<()> {
+        self.save_to(&Self::default_path()?)
+    }
+
+    /// Save the cache to a file
+    pub fn save_to(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_vec(self)?)?;
+        Ok(())
+    }
+
+    /// Get the cached probe result if the file has not changed since it was probed
+    pub fn get(&self, file: &FileEntry) -> Option<&ProbedMedia> {
+        self.entries
+            .get(&file.path)
+            .filter(|p| p.size == file.size && p.modified == file.modified)
+    }
+
+    pub fn insert(&mut self, file: &FileEntry, duration: f64, has_audio: bool) {
+        self.entries.insert(
+            file.path.clone(),
+            ProbedMedia {
+                size: file.size,
+                modified: file.modified,
+                duration,
+                has_audio,
+            },
+        );
+    }
+}
+
+/// Fill `duration` and `has_audio` for scanned files.
+/// Cached results are reused; the rest are probed with bounded concurrency.
+/// Files ffprobe cannot read are left without media info.
+pub async fn enrich_with_media_info(files: &mut [FileEntry]) -> Result<()> {
+    let mut cache = MediaProbeCache::load()?;
+
+    let mut pending = Vec::new();
+    for (i, file) in files.iter_mut().enumerate() {
+        match cache.get(file) {
+            Some(probed) => {
+                file.duration = Some(probed.duration);
+                file.has_audio = Some(probed.has_audio);
+            }
+            None => pending.push(i),
+        }
+    }
+
+    if pending.is_empty() {
+        return Ok(());
+    }
+
+    let probes: Vec<(usize, Result<_>)> = stream::iter(pending)
+        .map(|i| {
+            let path = PathBuf::from(&files[i].path);
+            async move { (i, FFmpegService::get_media_info(&path).await) }
+        })
+        .buffer_unordered(PROBE_CONCURRENCY)
+        .collect()
+        .await;
+
+    for (i, probe) in probes {
+        match probe {
+            Ok(info) => {
+                cache.insert(&files[i], info.duration, info.has_audio);
+                files[i].duration = Some(info.duration);
+                files[i].has_audio = Some(info.has_audio);
+            }
+            Err(e) => log::debug!("[enrich_with_media_info] Skipping {}: {}", files[i].path, e),
+        }
+    }
+
+    cache.save()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn entry(path: &str, size: u64, modified: u64) -> FileEntry {
+        FileEntry {
+            path: path.to_string(),
+            name: path.rsplit('/').next().unwrap_or(path).to_string(),
+            size,
+            is_dir: false,
+            modified: Some(modified),
+            created: None,
+            extension: Some("mp4".to_string()),
+            duration: None,
+            has_audio: None,
+        }
+    }
+
+    #[test]
+    fn test_cache_hit_requires_unchanged_file() {
+        let mut cache = MediaProbeCache::default();
+        cache.insert(&entry("/m/a.mp4", 100, 1), 12.5, true);
+
+        assert_eq!(cache.get(&entry("/m/a.mp4", 100, 1)).unwrap().duration, 12.5);
+        assert!(cache.get(&entry("/m/a.mp4", 200, 1)).is_none());
+        assert!(cache.get(&entry("/m/a.mp4", 100, 2)).is_none());
+        assert!(cache.get(&entry("/m/b.mp4", 100, 1)).is_none());
+    }
+
+    #[test]
+    fn test_cache_persists() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("media_probe_cache.json");
+
+        let mut cache = MediaProbeCache::default();
+        cache.insert(&entry("/m/a.mp4", 100, 1), 3.0, false);
+        cache.save_to(&path).unwrap();
+
+        let loaded = MediaProbeCache::load_from(&path).unwrap();
+        let probed = loaded.get(&entry("/m/a.mp4", 100, 1)).unwrap();
+        assert_eq!(probed.duration, 3.0);
+        assert!(!probed.has_audio);
+    }
+}
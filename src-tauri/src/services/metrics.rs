@@ -0,0 +1,78 @@
+use crate::error::Result;
+use crate::services::app_settings::AppSettings;
+use crate::services::database::Database;
+use crate::services::llm::LlmProvider;
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::time::Instant;
+use tokio_util::sync::CancellationToken;
+
+/// Record a feature use, with its duration when `started` is given, if the user
+/// opted in to metrics. Metrics never leave the machine.
+///
+/// Attributes must not identify the user or their media: record providers,
+/// models, formats and sizes, never paths or transcript text.
+pub fn record(name: &str, started: Option<Instant>, attributes: Value) {
+    if !AppSettings::load().is_ok_and(|settings| settings.metrics_enabled) {
+        return;
+    }
+    let duration_ms = started.map(|started| started.elapsed().as_millis() as u64);
+    let saved = Database::open().and_then(|db| db.record_metric(name, duration_ms, &attributes));
+    if let Err(e) = saved {
+        log::warn!("[metrics] Failed to record {}: {}", name, e);
+    }
+}
+
+/// Record how long a stage took, timed from `started`
+pub fn record_timing(name: &str, started: Instant, attributes: Value) {
+    record(name, Some(started), attributes);
+}
+
+/// Record a feature use without a duration
+pub fn record_event(name: &str, attributes: Value) {
+    record(name, None, attributes);
+}
+
+/// An LLM provider whose completions are timed as `llm_completion` metrics
+pub struct MeasuredLlm {
+    inner: Box<dyn LlmProvider>,
+}
+
+impl MeasuredLlm {
+    pub fn new(inner: Box<dyn LlmProvider>) -> Self {
+        Self { inner }
+    }
+}
+
+#[async_trait]
+impl LlmProvider for MeasuredLlm {
+    fn id(&self) -> &'static str {
+        self.inner.id()
+    }
+
+    fn model(&self) -> &str {
+        self.inner.model()
+    }
+
+    async fn complete(
+        &self,
+        system: &str,
+        prompt: &str,
+        max_tokens: u32,
+        cancel: &CancellationToken,
+    ) -> Result<String> {
+        let started = Instant::now();
+        let response = self.inner.complete(system, prompt, max_tokens, cancel).await?;
+        record_timing(
+            "llm_completion",
+            started,
+            json!({
+                "provider": self.id(),
+                "model": self.model(),
+                "promptChars": prompt.len(),
+                "responseChars": response.len(),
+            }),
+        );
+        Ok(response)
+    }
+}
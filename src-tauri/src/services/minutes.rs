@@ -0,0 +1,523 @@
+use crate::error::{AppError, Result};
+use crate::services::action_items::{clean, resolve_follow_ups, ActionItem, Decision, RawFollowUps};
+use crate::services::export::clock_timestamp;
+use crate::services::llm::{parse_json_response, LlmProvider};
+use crate::services::ollama::language_code_to_name;
+use crate::services::timeline_export::escape_xml;
+use crate::services::whisper::TranscriptionSegment;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write as _;
+use std::io::{Cursor, Write as _};
+use tokio_util::sync::CancellationToken;
+
+const MINUTES_SYSTEM: &str = "You are a meeting secretary who writes concise, factual minutes \
+                              from a transcript. Respond with the JSON object only, without \
+                              any explanation.";
+
+/// Reply budget: minutes of a long meeting with every section
+const MINUTES_MAX_TOKENS: u32 = 6000;
+
+/// A section of the minutes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MinutesSection {
+    Attendees,
+    Agenda,
+    Discussion,
+    Decisions,
+    ActionItems,
+}
+
+impl MinutesSection {
+    fn heading(self) -> &'static str {
+        match self {
+            Self::Attendees => "Attendees",
+            Self::Agenda => "Agenda",
+            Self::Discussion => "Discussion",
+            Self::Decisions => "Decisions",
+            Self::ActionItems => "Action items",
+        }
+    }
+}
+
+/// Which sections the minutes have and in what order
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MinutesTemplate {
+    pub id: String,
+    pub name: String,
+    pub sections: Vec<MinutesSection>,
+    /// Extra instructions for the writer, e.g. "Use formal language"
+    #[serde(default)]
+    pub instructions: Option<String>,
+}
+
+impl Default for MinutesTemplate {
+    fn default() -> Self {
+        use MinutesSection::*;
+        Self {
+            id: "standard".to_string(),
+            name: "Standard".to_string(),
+            sections: vec![Attendees, Agenda, Discussion, Decisions, ActionItems],
+            instructions: None,
+        }
+    }
+}
+
+/// Templates offered in the minutes dialog. Callers can also pass their own.
+pub fn builtin_templates() -> Vec<MinutesTemplate> {
+    use MinutesSection::*;
+    vec![
+        MinutesTemplate::default(),
+        MinutesTemplate {
+            id: "brief".to_string(),
+            name: "Brief".to_string(),
+            sections: vec![Attendees, Decisions, ActionItems],
+            instructions: Some("Keep every entry to a single short line.".to_string()),
+        },
+        MinutesTemplate {
+            id: "status".to_string(),
+            name: "Status update".to_string(),
+            sections: vec![Attendees, Discussion, ActionItems],
+            instructions: Some(
+                "Write one discussion point per person or workstream, covering progress \
+                 and blockers."
+                    .to_string(),
+            ),
+        },
+    ]
+}
+
+/// A discussion point of the minutes
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DiscussionPoint {
+    pub topic: String,
+    pub summary: String,
+}
+
+/// Meeting minutes generated from a transcript
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MeetingMinutes {
+    pub title: String,
+    pub template: Vec<MinutesSection>,
+    pub attendees: Vec<String>,
+    pub agenda: Vec<String>,
+    pub discussion: Vec<DiscussionPoint>,
+    pub decisions: Vec<Decision>,
+    pub action_items: Vec<ActionItem>,
+}
+
+/// Minutes as returned by the LLM
+#[derive(Debug, Deserialize)]
+struct RawMinutes {
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    attendees: Vec<String>,
+    #[serde(default)]
+    agenda: Vec<String>,
+    #[serde(default)]
+    discussion: Vec<RawDiscussionPoint>,
+    #[serde(flatten)]
+    follow_ups: RawFollowUps,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawDiscussionPoint {
+    topic: String,
+    #[serde(default)]
+    summary: String,
+}
+
+/// Write meeting minutes from transcript segments with the sections of `template`
+pub async fn generate_minutes(
+    llm: &dyn LlmProvider,
+    segments: &[TranscriptionSegment],
+    language: &str,
+    template: &MinutesTemplate,
+    cancel: &CancellationToken,
+) -> Result<MeetingMinutes> {
+    if template.sections.is_empty() {
+        return Err(AppError::InvalidInput("The minutes template has no sections".to_string()));
+    }
+    log::info!(
+        "[minutes] Writing \"{}\" minutes with {} ({})",
+        template.id,
+        llm.id(),
+        llm.model()
+    );
+    let prompt = build_minutes_prompt(segments, language, template);
+    let response = llm
+        .complete(MINUTES_SYSTEM, &prompt, MINUTES_MAX_TOKENS, cancel)
+        .await?;
+    parse_minutes_response(&response, segments, template)
+}
+
+fn build_minutes_prompt(
+    segments: &[TranscriptionSegment],
+    language: &str,
+    template: &MinutesTemplate,
+) -> String {
+    let segments_text: Vec<String> = segments
+        .iter()
+        .enumerate()
+        .map(|(i, s)| match &s.speaker {
+            Some(speaker) => format!("[{}] {}: {}", i, speaker, s.text.trim()),
+            None => format!("[{}] {}", i, s.text.trim()),
+        })
+        .collect();
+
+    let fields: Vec<&str> = template
+        .sections
+        .iter()
+        .map(|section| match section {
+            MinutesSection::Attendees => {
+                "\"attendees\": names of the participants (speaker labels if no names are said)"
+            }
+            MinutesSection::Agenda => "\"agenda\": the agenda items, in order",
+            MinutesSection::Discussion => {
+                "\"discussion\": [{\"topic\": ..., \"summary\": ...}] for each topic discussed"
+            }
+            MinutesSection::Decisions => {
+                "\"decisions\": [{\"decision\": ..., \"segment\": index where it was taken}]"
+            }
+            MinutesSection::ActionItems => {
+                "\"action_items\": [{\"task\": ..., \"owner\": ..., \"due\": ..., \
+                 \"segment\": index where it was agreed}], owner and due null when not said"
+            }
+        })
+        .collect();
+
+    let mut prompt = format!(
+        "Write the minutes of this meeting in {}. Answer with a JSON object with a short \
+         \"title\" for the meeting and these fields:\n- {}\n\n\
+         Use empty lists for sections with nothing to report.\n",
+        language_code_to_name(language),
+        fields.join("\n- ")
+    );
+    let instructions = template.instructions.as_deref().unwrap_or_default().trim();
+    if !instructions.is_empty() {
+        let _ = writeln!(prompt, "{}", instructions);
+    }
+    let _ = write!(prompt, "\nSegments:\n{}", segments_text.join("\n"));
+    prompt
+}
+
+/// Parse the LLM's minutes, keeping only the sections of the template.
+/// Without named attendees, the transcript's speaker labels are listed.
+fn parse_minutes_response(
+    response: &str,
+    segments: &[TranscriptionSegment],
+    template: &MinutesTemplate,
+) -> Result<MeetingMinutes> {
+    let raw: RawMinutes = parse_json_response(response, "minutes")?;
+    let follow_ups = resolve_follow_ups(raw.follow_ups, segments);
+    let has = |section| template.sections.contains(&section);
+
+    let mut attendees: Vec<String> = raw.attendees.iter().filter_map(|a| clean(a)).collect();
+    if attendees.is_empty() {
+        for speaker in segments.iter().filter_map(|s| s.speaker.as_deref()) {
+            if !attendees.iter().any(|a| a == speaker) {
+                attendees.push(speaker.to_string());
+            }
+        }
+    }
+
+    Ok(MeetingMinutes {
+        title: clean(&raw.title).unwrap_or_else(|| "Meeting minutes".to_string()),
+        template: template.sections.clone(),
+        attendees: if has(MinutesSection::Attendees) { attendees } else { Vec::new() },
+        agenda: if has(MinutesSection::Agenda) {
+            raw.agenda.iter().filter_map(|a| clean(a)).collect()
+        } else {
+            Vec::new()
+        },
+        discussion: if has(MinutesSection::Discussion) {
+            raw.discussion
+                .into_iter()
+                .filter_map(|point| {
+                    Some(DiscussionPoint {
+                        topic: clean(&point.topic)?,
+                        summary: point.summary.trim().to_string(),
+                    })
+                })
+                .collect()
+        } else {
+            Vec::new()
+        },
+        decisions: if has(MinutesSection::Decisions) {
+            follow_ups.decisions
+        } else {
+            Vec::new()
+        },
+        action_items: if has(MinutesSection::ActionItems) {
+            follow_ups.action_items
+        } else {
+            Vec::new()
+        },
+    })
+}
+
+/// A rendered block of the minutes, shared by the Markdown and DOCX writers
+enum Block {
+    Heading(String),
+    /// A discussion topic, in bold
+    Topic(String),
+    Paragraph(String),
+    Bullet(String),
+}
+
+fn action_item_line(item: &ActionItem) -> String {
+    let mut line = item.task.clone();
+    let details: Vec<String> = [
+        item.owner.as_ref().map(|o| format!("owner: {}", o)),
+        item.due.as_ref().map(|d| format!("due: {}", d)),
+        item.start.map(|s| format!("at {}", clock_timestamp(s))),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+    if !details.is_empty() {
+        let _ = write!(line, " ({})", details.join(", "));
+    }
+    line
+}
+
+fn blocks(minutes: &MeetingMinutes) -> Vec<Block> {
+    let mut blocks = Vec::new();
+    for &section in &minutes.template {
+        blocks.push(Block::Heading(section.heading().to_string()));
+        let start = blocks.len();
+        match section {
+            MinutesSection::Attendees => {
+                blocks.extend(minutes.attendees.iter().cloned().map(Block::Bullet))
+            }
+            MinutesSection::Agenda => blocks.extend(
+                minutes
+                    .agenda
+                    .iter()
+                    .enumerate()
+                    .map(|(i, item)| Block::Paragraph(format!("{}. {}", i + 1, item))),
+            ),
+            MinutesSection::Discussion => {
+                for point in &minutes.discussion {
+                    blocks.push(Block::Topic(point.topic.clone()));
+                    if !point.summary.is_empty() {
+                        blocks.push(Block::Paragraph(point.summary.clone()));
+                    }
+                }
+            }
+            MinutesSection::Decisions => {
+                blocks.extend(minutes.decisions.iter().map(|d| match d.start {
+                    Some(start) => {
+                        Block::Bullet(format!("{} (at {})", d.decision, clock_timestamp(start)))
+                    }
+                    None => Block::Bullet(d.decision.clone()),
+                }))
+            }
+            MinutesSection::ActionItems => blocks.extend(
+                minutes
+                    .action_items
+                    .iter()
+                    .map(|item| Block::Bullet(action_item_line(item))),
+            ),
+        }
+        if blocks.len() == start {
+            blocks.push(Block::Paragraph("None".to_string()));
+        }
+    }
+    blocks
+}
+
+/// Render the minutes as Markdown
+pub fn to_minutes_markdown(minutes: &MeetingMinutes) -> String {
+    let mut md = format!("# {}\n", minutes.title);
+    for block in blocks(minutes) {
+        match block {
+            Block::Heading(text) => {
+                let _ = write!(md, "\n## {}\n\n", text);
+            }
+            Block::Topic(text) => {
+                let _ = write!(md, "**{}**\n\n", text);
+            }
+            Block::Paragraph(text) => {
+                let _ = write!(md, "{}\n\n", text);
+            }
+            Block::Bullet(text) => {
+                let _ = writeln!(md, "- {}", text);
+            }
+        }
+    }
+    // One trailing newline, however the last block ended
+    format!("{}\n", md.trim_end())
+}
+
+const DOCX_CONTENT_TYPES: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types">
+<Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/>
+<Default Extension="xml" ContentType="application/xml"/>
+<Override PartName="/word/document.xml" ContentType="application/vnd.openxmlformats-officedocument.wordprocessingml.document.main+xml"/>
+<Override PartName="/word/styles.xml" ContentType="application/vnd.openxmlformats-officedocument.wordprocessingml.styles+xml"/>
+</Types>"#;
+
+const DOCX_RELS: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">
+<Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="word/document.xml"/>
+</Relationships>"#;
+
+const DOCX_DOCUMENT_RELS: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">
+<Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/styles" Target="styles.xml"/>
+</Relationships>"#;
+
+const DOCX_STYLES: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<w:styles xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main">
+<w:style w:type="paragraph" w:default="1" w:styleId="Normal">
+  <w:name w:val="Normal"/>
+  <w:pPr><w:spacing w:after="120"/></w:pPr>
+  <w:rPr><w:sz w:val="22"/></w:rPr>
+</w:style>
+<w:style w:type="paragraph" w:styleId="Title">
+  <w:name w:val="Title"/>
+  <w:basedOn w:val="Normal"/>
+  <w:pPr><w:spacing w:after="240"/></w:pPr>
+  <w:rPr><w:b/><w:sz w:val="40"/></w:rPr>
+</w:style>
+<w:style w:type="paragraph" w:styleId="Heading1">
+  <w:name w:val="heading 1"/>
+  <w:basedOn w:val="Normal"/>
+  <w:pPr><w:keepNext/><w:spacing w:before="240"/><w:outlineLvl w:val="0"/></w:pPr>
+  <w:rPr><w:b/><w:sz w:val="28"/></w:rPr>
+</w:style>
+<w:style w:type="paragraph" w:styleId="ListBullet">
+  <w:name w:val="List Bullet"/>
+  <w:basedOn w:val="Normal"/>
+  <w:pPr><w:ind w:left="360" w:hanging="360"/></w:pPr>
+</w:style>
+</w:styles>"#;
+
+fn docx_paragraph(xml: &mut String, style: Option<&str>, text: &str, bold: bool) {
+    xml.push_str("<w:p>");
+    if let Some(style) = style {
+        let _ = write!(xml, "<w:pPr><w:pStyle w:val=\"{}\"/></w:pPr>", style);
+    }
+    xml.push_str("<w:r>");
+    if bold {
+        xml.push_str("<w:rPr><w:b/></w:rPr>");
+    }
+    let _ = write!(xml, "<w:t xml:space=\"preserve\">{}</w:t></w:r></w:p>", escape_xml(text));
+}
+
+/// Render the minutes as a Word document
+pub fn to_minutes_docx(minutes: &MeetingMinutes) -> Result<Vec<u8>> {
+    let mut body = String::new();
+    docx_paragraph(&mut body, Some("Title"), &minutes.title, false);
+    for block in blocks(minutes) {
+        match block {
+            Block::Heading(text) => docx_paragraph(&mut body, Some("Heading1"), &text, false),
+            Block::Topic(text) => docx_paragraph(&mut body, None, &text, true),
+            Block::Paragraph(text) => docx_paragraph(&mut body, None, &text, false),
+            Block::Bullet(text) => {
+                docx_paragraph(&mut body, Some("ListBullet"), &format!("•\t{}", text), false)
+            }
+        }
+    }
+    let document = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n\
+         <w:document xmlns:w=\"http://schemas.openxmlformats.org/wordprocessingml/2006/main\">\
+         <w:body>{}</w:body></w:document>",
+        body
+    );
+
+    let to_export_error = |e: zip::result::ZipError| AppError::Export(e.to_string());
+    let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    let options = zip::write::SimpleFileOptions::default();
+    for (name, content) in [
+        ("[Content_Types].xml", DOCX_CONTENT_TYPES),
+        ("_rels/.rels", DOCX_RELS),
+        ("word/_rels/document.xml.rels", DOCX_DOCUMENT_RELS),
+        ("word/styles.xml", DOCX_STYLES),
+        ("word/document.xml", document.as_str()),
+    ] {
+        zip.start_file(name, options).map_err(to_export_error)?;
+        zip.write_all(content.as_bytes())?;
+    }
+    Ok(zip.finish().map_err(to_export_error)?.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    fn sample_minutes() -> MeetingMinutes {
+        let segments = vec![
+            TranscriptionSegment::new(0.0, 5.0, "Welcome, let's review the launch.")
+                .with_speaker("SPEAKER_00"),
+            TranscriptionSegment::new(
+                65.0,
+                70.0,
+                "We agreed to ship in May. I'll write the notes.",
+            )
+            .with_speaker("SPEAKER_01"),
+        ];
+        let response = r#"{
+            "title": "Launch review",
+            "attendees": [],
+            "agenda": ["Launch date", " "],
+            "discussion": [{"topic": "Launch date", "summary": "May works for everyone."}],
+            "decisions": [{"decision": "Ship in May", "segment": 1}],
+            "action_items": [{"task": "Write release notes", "owner": "Sam", "segment": 1}]
+        }"#;
+        parse_minutes_response(response, &segments, &MinutesTemplate::default()).unwrap()
+    }
+
+    #[test]
+    fn test_parse_minutes_response() {
+        let minutes = sample_minutes();
+        assert_eq!(minutes.title, "Launch review");
+        // Speaker labels stand in for unnamed attendees
+        assert_eq!(minutes.attendees, ["SPEAKER_00", "SPEAKER_01"]);
+        assert_eq!(minutes.agenda, ["Launch date"]);
+        assert_eq!(minutes.decisions[0].start, Some(65.0));
+
+        let brief = &builtin_templates()[1];
+        let segments = [TranscriptionSegment::new(0.0, 5.0, "Hi").with_speaker("A")];
+        let minutes = parse_minutes_response(
+            r#"{"agenda": ["Dropped"], "action_items": [{"task": "Kept"}]}"#,
+            &segments,
+            brief,
+        )
+        .unwrap();
+        assert!(minutes.agenda.is_empty());
+        assert_eq!(minutes.action_items[0].task, "Kept");
+        assert_eq!(minutes.title, "Meeting minutes");
+    }
+
+    #[test]
+    fn test_to_minutes_markdown() {
+        let md = to_minutes_markdown(&sample_minutes());
+        assert!(md.starts_with("# Launch review\n\n## Attendees\n\n- SPEAKER_00\n"));
+        assert!(md.contains("## Agenda\n\n1. Launch date\n"));
+        assert!(md.contains("**Launch date**\n\nMay works for everyone.\n"));
+        assert!(md.contains("- Ship in May (at 1:05)\n"));
+        assert!(md.ends_with("- Write release notes (owner: Sam, at 1:05)\n"));
+    }
+
+    #[test]
+    fn test_to_minutes_docx() {
+        let docx = to_minutes_docx(&sample_minutes()).unwrap();
+        let mut archive = zip::ZipArchive::new(Cursor::new(docx)).unwrap();
+        let mut document = String::new();
+        archive
+            .by_name("word/document.xml")
+            .unwrap()
+            .read_to_string(&mut document)
+            .unwrap();
+        assert!(document.contains("<w:pStyle w:val=\"Title\"/></w:pPr><w:r><w:t"));
+        assert!(document.contains("Write release notes (owner: Sam, at 1:05)"));
+        // Discussion topics are bold runs
+        let topic = "<w:r><w:rPr><w:b/></w:rPr><w:t xml:space=\"preserve\">Launch date</w:t>";
+        assert!(document.contains(topic));
+        assert!(archive.by_name("[Content_Types].xml").is_ok());
+    }
+}
@@ -1,19 +1,76 @@
+pub mod action_items;
+pub mod app_settings;
+pub mod assemblyai;
+pub mod audio_filters;
+pub mod backup;
+pub mod benchmark;
+pub mod cancellation;
+pub mod chapters;
 pub mod claude;
+pub mod concurrency;
+pub mod credential_bundle;
+pub mod credential_profiles;
+pub mod database;
 pub mod directory_service;
 pub mod download;
+pub mod embeddings;
+pub mod entities;
+pub mod env_keys;
+pub mod export;
 pub mod ffmpeg;
+pub mod file_ops;
+pub mod gemini;
+pub mod groq;
+pub mod hooks;
+pub mod http;
+pub mod job_queue;
 pub mod keychain;
+pub mod key_validation;
+pub mod keywords;
+pub mod llama;
+pub mod llm;
+pub mod media_import;
+pub mod media_probe;
+pub mod metrics;
+pub mod minutes;
 pub mod ollama;
 pub mod openai;
+pub mod openai_compatible;
+pub mod os_path;
+pub mod path_scope;
+pub mod pdf_export;
+pub mod podcast;
+pub mod polish;
+pub mod processes;
+pub mod prompt_templates;
+pub mod providers;
+pub mod publish_metadata;
+pub mod rate_limit;
+pub mod scan_index;
+pub mod segment_ops;
+pub mod secret_file;
+pub mod sentiment;
+pub mod temp_files;
+pub mod thumbnail;
+pub mod timeline_export;
+pub mod topics;
+pub mod transcript_diff;
+pub mod transcription_provider;
+pub mod update_check;
+pub mod usage;
+pub mod volume;
 pub mod whisper;
+pub mod whisper_server;
+pub mod ytdlp;
 
 pub use claude::{ClaudeModel, ClaudeService};
 #[allow(unused_imports)]
 pub use directory_service::{DirectoryNode, FileEntry, FileEvent};
 pub use download::{DownloadService, ModelStatus, WhisperModel};
 pub use ffmpeg::{FFmpegService, MediaInfo};
+pub use gemini::{GeminiModel, GeminiService};
 #[allow(unused_imports)]
-pub use keychain::{ApiKeyType, KeychainService};
+pub use keychain::KeychainService;
 pub use ollama::{ChatMessage, OllamaModel, OllamaService, StorySegment};
 pub use openai::{OpenAIModel, OpenAIService};
 pub use whisper::{TranscriptionResult, TranscriptionSegment, WhisperService};
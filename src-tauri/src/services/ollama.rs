@@ -1,4 +1,6 @@
 use crate::error::{AppError, Result};
+use crate::services::chapters::{build_chapters_prompt, parse_chapters_response, Chapter};
+use crate::services::http;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 
@@ -56,11 +58,22 @@ struct ChatResponse {
     done: bool,
 }
 
+#[derive(Debug, Clone, Serialize)]
+struct EmbedRequest<'a> {
+    model: &'a str,
+    input: &'a [String],
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct EmbedResponse {
+    embeddings: Vec<Vec<f32>>,
+}
+
 impl OllamaService {
     /// Create a new Ollama service
     pub fn new() -> Self {
         Self {
-            client: Client::new(),
+            client: http::client(),
             base_url: OLLAMA_BASE_URL.to_string(),
         }
     }
@@ -85,7 +98,7 @@ impl OllamaService {
             let models_response: OllamaModelsResponse = response.json().await?;
             Ok(models_response.models)
         } else {
-            Err(AppError::Whisper("Failed to list Ollama models".to_string()))
+            Err(AppError::Llm("Failed to list Ollama models".to_string()))
         }
     }
 
@@ -109,12 +122,12 @@ impl OllamaService {
             let generate_response: GenerateResponse = response.json().await?;
             Ok(generate_response.response)
         } else if response.status() == reqwest::StatusCode::NOT_FOUND {
-            Err(AppError::Whisper(format!(
+            Err(AppError::Llm(format!(
                 "Model '{}' not found. Please install it by running: ollama pull {}",
                 model, model
             )))
         } else {
-            Err(AppError::Whisper(format!("Ollama generate failed: {}", response.status())))
+            Err(AppError::Llm(format!("Ollama generate failed: {}", response.status())))
         }
     }
 
@@ -138,34 +151,44 @@ impl OllamaService {
             let chat_response: ChatResponse = response.json().await?;
             Ok(chat_response.message.content)
         } else if response.status() == reqwest::StatusCode::NOT_FOUND {
-            Err(AppError::Whisper(format!(
+            Err(AppError::Llm(format!(
+                "Model '{}' not found. Please install it by running: ollama pull {}",
+                model, model
+            )))
+        } else {
+            Err(AppError::Llm(format!("Ollama chat failed: {}", response.status())))
+        }
+    }
+
+    /// Embed texts with an embedding model (e.g. nomic-embed-text), one vector per text
+    pub async fn embed(&self, model: &str, input: &[String]) -> Result<Vec<Vec<f32>>> {
+        let url = format!("{}/api/embed", self.base_url);
+
+        let response = self.client
+            .post(&url)
+            .json(&EmbedRequest { model, input })
+            .send()
+            .await?;
+
+        if response.status().is_success() {
+            let embed_response: EmbedResponse = response.json().await?;
+            Ok(embed_response.embeddings)
+        } else if response.status() == reqwest::StatusCode::NOT_FOUND {
+            Err(AppError::Llm(format!(
                 "Model '{}' not found. Please install it by running: ollama pull {}",
                 model, model
             )))
         } else {
-            Err(AppError::Whisper(format!("Ollama chat failed: {}", response.status())))
+            Err(AppError::Llm(format!("Ollama embed failed: {}", response.status())))
         }
     }
 
     /// Summarize text using Ollama
     pub async fn summarize(&self, model: &str, text: &str, language: &str) -> Result<String> {
-        let lang_instruction = language_code_to_name(language);
-
         let prompt = format!(
-            "You are an expert at summarizing transcribed audio/video content. \
-             Create a clear, well-structured summary in {}.\n\n\
-             Guidelines:\n\
-             - Start with a one-sentence overview of the main topic\n\
-             - Highlight key points, decisions, or action items\n\
-             - Preserve important names, dates, and specific details\n\
-             - Use bullet points for multiple items when appropriate\n\
-             - Keep the summary concise but comprehensive (aim for 20-30% of original length)\n\
-             - Maintain the original tone and context\n\n\
-             IMPORTANT: Output ONLY the summary itself. Do NOT include any introductory phrases \
-             like \"Here is a summary\" or concluding notes like \"Note:\". \
-             Start directly with the summary content.\n\n\
-             Transcription:\n{}\n\nSummary:",
-            lang_instruction, text
+            "{}\n\nTranscription:\n{}\n\nSummary:",
+            summary_instructions(language),
+            text
         );
 
         self.generate(model, &prompt).await
@@ -177,26 +200,25 @@ impl OllamaService {
         model: &str,
         segments: &[super::whisper::TranscriptionSegment],
     ) -> Result<Vec<StorySegment>> {
-        let segments_text: Vec<String> = segments.iter().enumerate().map(|(i, s)| {
-            format!("[{}] ({:.1}s - {:.1}s): {}", i, s.start, s.end, s.text)
-        }).collect();
-
-        let prompt = format!(
-            "Analyze these transcription segments and suggest the best story order. \
-             Return a JSON array of segment indices in the recommended order, \
-             with a brief reason for each segment's position.\n\n\
-             Segments:\n{}\n\n\
-             Response format: [{{\"index\": 0, \"reason\": \"Opening statement\"}}, ...]\n\nJSON:",
-            segments_text.join("\n")
-        );
+        let prompt = format!("{}\n\nJSON:", build_story_order_prompt(segments));
 
         let response = self.generate(model, &prompt).await?;
 
-        // Parse JSON response
-        let story_segments: Vec<StorySegment> = serde_json::from_str(&response)
-            .map_err(|_| AppError::Whisper("Failed to parse story order response".to_string()))?;
+        parse_story_order_response(&response)
+    }
+
+    /// Split a transcription into YouTube chapters with generated titles
+    pub async fn generate_chapters(
+        &self,
+        model: &str,
+        segments: &[super::whisper::TranscriptionSegment],
+        language: &str,
+    ) -> Result<Vec<Chapter>> {
+        let prompt = format!("{}\n\nJSON:", build_chapters_prompt(segments, language));
+
+        let response = self.generate(model, &prompt).await?;
 
-        Ok(story_segments)
+        parse_chapters_response(&response, segments)
     }
 
     /// Pull/download a model
@@ -204,7 +226,8 @@ impl OllamaService {
     pub async fn pull_model(&self, model_name: &str) -> Result<()> {
         let url = format!("{}/api/pull", self.base_url);
 
-        let response = self.client
+        // Pulls stream for as long as the download takes
+        let response = http::download_client()
             .post(&url)
             .json(&serde_json::json!({ "name": model_name, "stream": true }))
             .send()
@@ -277,8 +300,56 @@ pub struct StorySegment {
     pub reason: String,
 }
 
+/// Build the story order prompt shared by all LLM providers
+pub(crate) fn build_story_order_prompt(segments: &[super::whisper::TranscriptionSegment]) -> String {
+    let segments_text: Vec<String> = segments.iter().enumerate().map(|(i, s)| {
+        format!("[{}] ({:.1}s - {:.1}s): {}", i, s.start, s.end, s.text)
+    }).collect();
+
+    format!(
+        "Analyze these transcription segments and suggest the best story order. \
+         Return a JSON array of segment indices in the recommended order, \
+         with a brief reason for each segment's position.\n\n\
+         Segments:\n{}\n\n\
+         Response format: [{{\"index\": 0, \"reason\": \"Opening statement\"}}, ...]",
+        segments_text.join("\n")
+    )
+}
+
+/// Parse a story order response from an LLM.
+/// Cloud models often wrap JSON in markdown fences or add a sentence around it,
+/// so only the outermost JSON array is parsed.
+pub(crate) fn parse_story_order_response(response: &str) -> Result<Vec<StorySegment>> {
+    let json = match (response.find('['), response.rfind(']')) {
+        (Some(start), Some(end)) if start < end => &response[start..=end],
+        _ => response.trim(),
+    };
+
+    serde_json::from_str(json)
+        .map_err(|_| AppError::Llm("Failed to parse story order response".to_string()))
+}
+
+/// Summarization instructions shared by the LLM providers
+pub(crate) fn summary_instructions(language: &str) -> String {
+    format!(
+        "You are an expert at summarizing transcribed audio/video content. \
+         Create a clear, well-structured summary in {}.\n\n\
+         Guidelines:\n\
+         - Start with a one-sentence overview of the main topic\n\
+         - Highlight key points, decisions, or action items\n\
+         - Preserve important names, dates, and specific details\n\
+         - Use bullet points for multiple items when appropriate\n\
+         - Keep the summary concise but comprehensive (aim for 20-30% of original length)\n\
+         - Maintain the original tone and context\n\n\
+         IMPORTANT: Output ONLY the summary itself. Do NOT include any introductory phrases \
+         like \"Here is a summary\" or concluding notes like \"Note:\". \
+         Start directly with the summary content.",
+        language_code_to_name(language)
+    )
+}
+
 /// Convert language code to full language name for LLM prompts
-fn language_code_to_name(code: &str) -> String {
+pub(crate) fn language_code_to_name(code: &str) -> String {
     match code.to_lowercase().as_str() {
         "auto" => "the same language as the original transcription".to_string(),
         "ko" => "Korean".to_string(),
@@ -302,3 +373,43 @@ fn language_code_to_name(code: &str) -> String {
         _ => code.to_string(), // Return as-is if unknown
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::whisper::TranscriptionSegment;
+
+    #[test]
+    fn test_build_story_order_prompt_lists_segments() {
+        let segments = vec![
+            TranscriptionSegment::new(0.0, 2.5, "Hello"),
+            TranscriptionSegment::new(2.5, 5.0, "World"),
+        ];
+
+        let prompt = build_story_order_prompt(&segments);
+        assert!(prompt.contains("[0] (0.0s - 2.5s): Hello"));
+        assert!(prompt.contains("[1] (2.5s - 5.0s): World"));
+    }
+
+    #[test]
+    fn test_parse_story_order_plain_json() {
+        let response = r#"[{"index": 1, "reason": "Hook"}, {"index": 0, "reason": "Intro"}]"#;
+        let order = parse_story_order_response(response).unwrap();
+        assert_eq!(order.len(), 2);
+        assert_eq!(order[0].index, 1);
+        assert_eq!(order[1].reason, "Intro");
+    }
+
+    #[test]
+    fn test_parse_story_order_code_fence() {
+        let response = "Here is the order:\n```json\n[{\"index\": 2, \"reason\": \"Climax\"}]\n```";
+        let order = parse_story_order_response(response).unwrap();
+        assert_eq!(order.len(), 1);
+        assert_eq!(order[0].index, 2);
+    }
+
+    #[test]
+    fn test_parse_story_order_invalid() {
+        assert!(parse_story_order_response("I cannot help with that").is_err());
+    }
+}
@@ -1,5 +1,15 @@
 use crate::error::{AppError, Result};
-use reqwest::{multipart, Client};
+use crate::services::chapters::{build_chapters_prompt, parse_chapters_response, Chapter};
+use crate::services::app_settings::AppSettings;
+use crate::services::http;
+use crate::services::ollama::{build_story_order_prompt, parse_story_order_response, StorySegment};
+use crate::services::openai_compatible::normalize_base_url;
+use crate::services::providers;
+use crate::services::rate_limit::{self, estimate_tokens};
+use crate::services::usage::{record_usage, ApiUsage};
+use crate::services::whisper::{TranscriptionResult, TranscriptionSegment};
+use futures::StreamExt;
+use reqwest::{multipart, Client, RequestBuilder};
 use serde::{Deserialize, Serialize};
 use std::path::Path;
 use tokio::fs::File;
@@ -11,6 +21,9 @@ const OPENAI_API_BASE: &str = "https://api.openai.com/v1";
 pub struct OpenAIService {
     client: Client,
     api_key: String,
+    base_url: String,
+    /// Provider id the calls are recorded under in the usage ledger
+    provider: &'static str,
 }
 
 // ============================================================================
@@ -47,6 +60,34 @@ pub struct WhisperSegment {
     pub start: f64,
     pub end: f64,
     pub text: String,
+    /// Average log probability of the segment's tokens
+    #[serde(default)]
+    pub avg_logprob: Option<f64>,
+}
+
+impl From<WhisperVerboseResponse> for TranscriptionResult {
+    fn from(response: WhisperVerboseResponse) -> Self {
+        let segments = response
+            .segments
+            .unwrap_or_default()
+            .into_iter()
+            .map(|s| TranscriptionSegment {
+                start: s.start,
+                end: s.end,
+                text: s.text,
+                speaker: None,
+                confidence: s.avg_logprob.map(f64::exp),
+                words: Vec::new(),
+            })
+            .collect();
+
+        Self {
+            segments,
+            full_text: response.text,
+            language: response.language,
+            duration: response.duration.unwrap_or(0.0),
+        }
+    }
 }
 
 // ============================================================================
@@ -74,6 +115,67 @@ pub struct ChatRequest {
     pub max_completion_tokens: Option<u32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub stream: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stream_options: Option<StreamOptions>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StreamOptions {
+    /// Send the token usage in a last chunk before `[DONE]`
+    pub include_usage: bool,
+}
+
+/// One server-sent event of a streamed chat completion
+#[derive(Debug, Clone, Deserialize)]
+struct ChatStreamChunk {
+    #[serde(default)]
+    choices: Vec<ChatStreamChoice>,
+    #[serde(default)]
+    usage: Option<Usage>,
+    /// Set when the server fails after the stream has started
+    #[serde(default)]
+    error: Option<ChatStreamError>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct ChatStreamError {
+    message: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct ChatStreamChoice {
+    #[serde(default)]
+    delta: ChatStreamDelta,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+struct ChatStreamDelta {
+    #[serde(default)]
+    content: Option<String>,
+}
+
+/// Splits a server-sent event stream into the payloads of its `data:` lines.
+/// Bytes are buffered until a line is complete, so characters split across
+/// network chunks stay intact.
+#[derive(Debug, Default)]
+struct SseLines {
+    buffer: Vec<u8>,
+}
+
+impl SseLines {
+    /// Add received bytes, returning the payloads of the lines they complete
+    fn push(&mut self, bytes: &[u8]) -> Vec<String> {
+        self.buffer.extend_from_slice(bytes);
+        let mut payloads = Vec::new();
+        while let Some(newline) = self.buffer.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=newline).collect();
+            let line = String::from_utf8_lossy(&line);
+            if let Some(data) = line.trim_end().strip_prefix("data:") {
+                payloads.push(data.trim_start().to_string());
+            }
+        }
+        payloads
+    }
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -100,6 +202,40 @@ pub struct Usage {
     pub total_tokens: u32,
 }
 
+#[derive(Debug, Clone, Serialize)]
+struct EmbeddingRequest<'a> {
+    model: &'a str,
+    input: &'a [String],
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct EmbeddingResponse {
+    data: Vec<EmbeddingData>,
+    #[serde(default)]
+    usage: Option<EmbeddingUsage>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct EmbeddingUsage {
+    prompt_tokens: u32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct EmbeddingData {
+    index: usize,
+    embedding: Vec<f32>,
+}
+
+/// The base URL set for a provider in `provider_base_urls`, if any.
+/// An invalid one is logged and ignored.
+pub(crate) fn configured_base_url(provider: &str) -> Option<String> {
+    let settings = AppSettings::load().ok()?;
+    let base_url = settings.provider_base_urls.get(provider)?;
+    normalize_base_url(base_url)
+        .inspect_err(|e| log::warn!("[openai] Ignoring the {} base URL: {}", provider, e))
+        .ok()
+}
+
 // ============================================================================
 // OpenAI Service Implementation
 // ============================================================================
@@ -107,9 +243,41 @@ pub struct Usage {
 impl OpenAIService {
     /// Create a new OpenAI service with API key
     pub fn new(api_key: &str) -> Self {
+        Self::hosted(api_key, providers::OPENAI, OPENAI_API_BASE)
+    }
+
+    /// Create a service for a hosted provider that speaks the OpenAI protocol,
+    /// at the base URL configured for it in the settings or else `default_base_url`
+    pub fn hosted(api_key: &str, provider: &'static str, default_base_url: &str) -> Self {
+        let base_url =
+            configured_base_url(provider).unwrap_or_else(|| default_base_url.to_string());
+        Self {
+            provider,
+            ..Self::with_base_url(api_key, &base_url)
+        }
+    }
+
+    /// Create a service for a self-hosted server that speaks the OpenAI protocol
+    pub fn with_base_url(api_key: &str, base_url: &str) -> Self {
         Self {
-            client: Client::new(),
+            client: http::client(),
             api_key: api_key.to_string(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            provider: providers::OPENAI_COMPATIBLE,
+        }
+    }
+
+    fn provider_id(&self) -> &'static str {
+        self.provider
+    }
+
+    /// Attach the API key to a request. Self-hosted servers often run without
+    /// one, in which case no Authorization header is sent.
+    fn authorized(&self, request: RequestBuilder) -> RequestBuilder {
+        if self.api_key.is_empty() {
+            request
+        } else {
+            request.bearer_auth(&self.api_key)
         }
     }
 
@@ -120,7 +288,7 @@ impl OpenAIService {
         language: Option<&str>,
         model: Option<&str>,
     ) -> Result<WhisperVerboseResponse> {
-        let url = format!("{}/audio/transcriptions", OPENAI_API_BASE);
+        let url = format!("{}/audio/transcriptions", self.base_url);
 
         // Read audio file
         let mut file = File::open(audio_path).await?;
@@ -151,23 +319,26 @@ impl OpenAIService {
             form = form.text("language", lang.to_string());
         }
 
+        rate_limit::acquire(self.provider_id(), 0).await;
+
         let response: reqwest::Response = self
-            .client
-            .post(&url)
-            .bearer_auth(&self.api_key)
+            .authorized(self.client.post(&url))
             .multipart(form)
             .send()
             .await?;
 
         if response.status().is_success() {
             let result: WhisperVerboseResponse = response.json().await?;
+            let seconds = result.duration.unwrap_or_default();
+            record_usage(ApiUsage::audio(self.provider_id(), whisper_model, seconds)).await;
             Ok(result)
         } else {
-            let error_text: String = response.text().await.unwrap_or_default();
-            Err(AppError::Whisper(format!(
-                "OpenAI Whisper API error: {}",
-                error_text
-            )))
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_default();
+            Err(AppError::for_status(
+                status,
+                AppError::Whisper(format!("OpenAI Whisper API error: {}", error_text)),
+            ))
         }
     }
 
@@ -179,31 +350,30 @@ impl OpenAIService {
         temperature: Option<f32>,
         max_tokens: Option<u32>,
     ) -> Result<String> {
-        let url = format!("{}/chat/completions", OPENAI_API_BASE);
-
-        // Newer models (gpt-4o, gpt-5, o1, o3) use max_completion_tokens
-        // Legacy models (gpt-3.5, gpt-4) use max_tokens
-        let use_new_param = Self::uses_max_completion_tokens(model);
+        let url = format!("{}/chat/completions", self.base_url);
+        let prompt_chars = messages.iter().map(|m| m.content.len()).sum();
+        rate_limit::acquire(self.provider_id(), estimate_tokens(prompt_chars, max_tokens)).await;
 
-        let request = ChatRequest {
-            model: model.to_string(),
-            messages,
-            temperature,
-            max_tokens: if use_new_param { None } else { max_tokens },
-            max_completion_tokens: if use_new_param { max_tokens } else { None },
-            stream: Some(false),
-        };
+        let request = Self::chat_request(model, messages, temperature, max_tokens, false);
 
         let response = self
-            .client
-            .post(&url)
-            .bearer_auth(&self.api_key)
+            .authorized(self.client.post(&url))
             .json(&request)
             .send()
             .await?;
 
         if response.status().is_success() {
             let result: ChatResponse = response.json().await?;
+            if let Some(usage) = &result.usage {
+                record_usage(ApiUsage::tokens(
+                    self.provider_id(),
+                    model,
+                    "chat",
+                    usage.prompt_tokens.into(),
+                    usage.completion_tokens.into(),
+                ))
+                .await;
+            }
             let content = result
                 .choices
                 .first()
@@ -211,11 +381,156 @@ impl OpenAIService {
                 .unwrap_or_default();
             Ok(content)
         } else {
+            let status = response.status();
             let error_text = response.text().await.unwrap_or_default();
-            Err(AppError::Whisper(format!(
-                "OpenAI Chat API error: {}",
-                error_text
-            )))
+            Err(AppError::for_status(
+                status,
+                AppError::Llm(format!("OpenAI Chat API error: {}", error_text)),
+            ))
+        }
+    }
+
+    /// Chat completion streamed as server-sent events: `on_delta` receives
+    /// each piece of the reply as it is generated. Returns the whole reply.
+    pub async fn chat_stream(
+        &self,
+        model: &str,
+        messages: Vec<ChatMessage>,
+        temperature: Option<f32>,
+        max_tokens: Option<u32>,
+        mut on_delta: impl FnMut(&str),
+    ) -> Result<String> {
+        let url = format!("{}/chat/completions", self.base_url);
+        let prompt_chars = messages.iter().map(|m| m.content.len()).sum();
+        rate_limit::acquire(self.provider_id(), estimate_tokens(prompt_chars, max_tokens)).await;
+
+        let mut request = Self::chat_request(model, messages, temperature, max_tokens, true);
+        if !self.reports_stream_usage() {
+            request.stream_options = None;
+        }
+        let response = self
+            .authorized(self.client.post(&url))
+            .json(&request)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(AppError::for_status(
+                status,
+                AppError::Llm(format!("OpenAI Chat API error: {}", error_text)),
+            ));
+        }
+
+        let mut lines = SseLines::default();
+        let mut stream = response.bytes_stream();
+        let mut content = String::new();
+        let mut usage = None;
+        let mut done = false;
+        'stream: while let Some(bytes) = stream.next().await {
+            for data in lines.push(&bytes?) {
+                if data == "[DONE]" {
+                    done = true;
+                    break 'stream;
+                }
+                let chunk: ChatStreamChunk = serde_json::from_str(&data)?;
+                if let Some(error) = chunk.error {
+                    return Err(AppError::Llm(format!(
+                        "OpenAI Chat API error: {}",
+                        error.message
+                    )));
+                }
+                if let Some(delta) = chunk
+                    .choices
+                    .into_iter()
+                    .next()
+                    .and_then(|choice| choice.delta.content)
+                    .filter(|delta| !delta.is_empty())
+                {
+                    on_delta(&delta);
+                    content.push_str(&delta);
+                }
+                usage = chunk.usage.or(usage);
+            }
+        }
+        if !done {
+            return Err(AppError::Llm(
+                "OpenAI Chat API stream ended before the reply was complete".to_string(),
+            ));
+        }
+
+        if let Some(usage) = usage {
+            record_usage(ApiUsage::tokens(
+                self.provider_id(),
+                model,
+                "chat",
+                usage.prompt_tokens.into(),
+                usage.completion_tokens.into(),
+            ))
+            .await;
+        }
+        Ok(content)
+    }
+
+    /// Whether the server takes `stream_options`. Only OpenAI's own API is
+    /// asked for usage; other servers may reject fields they don't know.
+    fn reports_stream_usage(&self) -> bool {
+        self.provider == providers::OPENAI && self.base_url == OPENAI_API_BASE
+    }
+
+    fn chat_request(
+        model: &str,
+        messages: Vec<ChatMessage>,
+        temperature: Option<f32>,
+        max_tokens: Option<u32>,
+        stream: bool,
+    ) -> ChatRequest {
+        // Newer models (gpt-4o, gpt-5, o1, o3) use max_completion_tokens
+        // Legacy models (gpt-3.5, gpt-4) use max_tokens
+        let use_new_param = Self::uses_max_completion_tokens(model);
+        ChatRequest {
+            model: model.to_string(),
+            messages,
+            temperature,
+            max_tokens: if use_new_param { None } else { max_tokens },
+            max_completion_tokens: if use_new_param { max_tokens } else { None },
+            stream: Some(stream),
+            stream_options: stream.then_some(StreamOptions {
+                include_usage: true,
+            }),
+        }
+    }
+
+    /// Embed texts with an embedding model (e.g. text-embedding-3-small),
+    /// one vector per text in input order
+    pub async fn embed(&self, model: &str, input: &[String]) -> Result<Vec<Vec<f32>>> {
+        let url = format!("{}/embeddings", self.base_url);
+        let input_chars = input.iter().map(String::len).sum();
+        rate_limit::acquire(self.provider_id(), estimate_tokens(input_chars, None)).await;
+
+        let response = self
+            .authorized(self.client.post(&url))
+            .json(&EmbeddingRequest { model, input })
+            .send()
+            .await?;
+
+        if response.status().is_success() {
+            let mut result: EmbeddingResponse = response.json().await?;
+            if let Some(usage) = &result.usage {
+                let tokens = usage.prompt_tokens.into();
+                let provider = self.provider_id();
+                record_usage(ApiUsage::tokens(provider, model, "embedding", tokens, 0)).await;
+            }
+            result.data.sort_by_key(|d| d.index);
+            Ok(result.data.into_iter().map(|d| d.embedding).collect())
+        } else {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_default();
+            Err(AppError::for_status(
+                status,
+                AppError::Llm(format!("OpenAI Embeddings API error: {}", error_text)),
+            ))
         }
     }
 
@@ -254,14 +569,62 @@ impl OpenAIService {
         self.chat(model, messages, Some(0.3), Some(1000)).await
     }
 
+    /// Extract story order / timeline from transcription segments
+    pub async fn extract_story_order(
+        &self,
+        model: &str,
+        segments: &[TranscriptionSegment],
+    ) -> Result<Vec<StorySegment>> {
+        let messages = vec![
+            ChatMessage {
+                role: "system".to_string(),
+                content: "You are an expert video editor who restructures interview and \
+                          vlog footage into a compelling narrative. \
+                          Respond with the JSON array only, without any explanation."
+                    .to_string(),
+            },
+            ChatMessage {
+                role: "user".to_string(),
+                content: build_story_order_prompt(segments),
+            },
+        ];
+
+        let response = self.chat(model, messages, Some(0.2), Some(4096)).await?;
+
+        parse_story_order_response(&response)
+    }
+
+    /// Split transcription segments into YouTube chapters with generated titles
+    pub async fn generate_chapters(
+        &self,
+        model: &str,
+        segments: &[TranscriptionSegment],
+        language: &str,
+    ) -> Result<Vec<Chapter>> {
+        let messages = vec![
+            ChatMessage {
+                role: "system".to_string(),
+                content: "You are an expert YouTube editor who writes clear, searchable \
+                          chapter titles. Respond with the JSON array only, without any explanation."
+                    .to_string(),
+            },
+            ChatMessage {
+                role: "user".to_string(),
+                content: build_chapters_prompt(segments, language),
+            },
+        ];
+
+        let response = self.chat(model, messages, Some(0.3), Some(2048)).await?;
+
+        parse_chapters_response(&response, segments)
+    }
+
     /// Check if API key is valid
     pub async fn validate_api_key(&self) -> Result<bool> {
-        let url = format!("{}/models", OPENAI_API_BASE);
+        let url = format!("{}/models", self.base_url);
 
         let response = self
-            .client
-            .get(&url)
-            .bearer_auth(&self.api_key)
+            .authorized(self.client.get(&url))
             .send()
             .await?;
 
@@ -300,26 +663,38 @@ impl OpenAIService {
 
     /// Fetch available models from OpenAI API (sorted by created date, newest first)
     pub async fn fetch_models(&self) -> Result<Vec<OpenAIModel>> {
-        let url = format!("{}/models", OPENAI_API_BASE);
+        // Filter chat-compatible models only (whitelist approach)
+        let models = self
+            .list_models()
+            .await?
+            .into_iter()
+            .filter(|m| is_chat_compatible_model(&m.id))
+            .map(|m| OpenAIModel {
+                name: format_model_name(&m.id),
+                ..m
+            })
+            .collect();
+        Ok(models)
+    }
+
+    /// Fetch every model the API lists, unfiltered, newest first
+    pub async fn list_models(&self) -> Result<Vec<OpenAIModel>> {
+        let url = format!("{}/models", self.base_url);
 
         let response = self
-            .client
-            .get(&url)
-            .bearer_auth(&self.api_key)
+            .authorized(self.client.get(&url))
             .send()
             .await?;
 
         if response.status().is_success() {
             let data: OpenAIModelsResponse = response.json().await?;
 
-            // Filter chat-compatible models only (whitelist approach)
             let mut models: Vec<OpenAIModel> = data
                 .data
                 .into_iter()
-                .filter(|m| is_chat_compatible_model(&m.id))
                 .map(|m| OpenAIModel {
-                    id: m.id.clone(),
-                    name: format_model_name(&m.id),
+                    name: m.id.clone(),
+                    id: m.id,
                     description: String::new(),
                     created: m.created,
                 })
@@ -329,11 +704,12 @@ impl OpenAIService {
             models.sort_by(|a, b| b.created.cmp(&a.created));
             Ok(models)
         } else {
+            let status = response.status();
             let error_text = response.text().await.unwrap_or_default();
-            Err(AppError::Whisper(format!(
-                "Failed to fetch OpenAI models: {}",
-                error_text
-            )))
+            Err(AppError::for_status(
+                status,
+                AppError::Llm(format!("Failed to fetch models: {}", error_text)),
+            ))
         }
     }
 
@@ -385,6 +761,8 @@ struct OpenAIModelsResponse {
 #[derive(Debug, Clone, Deserialize)]
 struct OpenAIModelData {
     id: String,
+    // Some OpenAI-compatible servers leave this out
+    #[serde(default)]
     created: i64,
 }
 
@@ -759,4 +1137,73 @@ mod tests {
             assert!(OpenAIService::uses_max_completion_tokens("o4-mini"));
         }
     }
+
+    // =========================================================================
+    // Streamed chat tests
+    // =========================================================================
+
+    mod chat_stream {
+        use super::*;
+
+        #[test]
+        fn sse_lines_split_across_chunks() {
+            let mut lines = SseLines::default();
+            let event = "data: {\"choices\":[{\"delta\":{\"content\":\"안녕\"}}]}\n\n".as_bytes();
+            // Split inside the multi-byte characters
+            assert!(lines.push(&event[..40]).is_empty());
+            let payloads = lines.push(&event[40..]);
+            assert_eq!(payloads.len(), 1);
+
+            let chunk: ChatStreamChunk = serde_json::from_str(&payloads[0]).unwrap();
+            assert_eq!(chunk.choices[0].delta.content.as_deref(), Some("안녕"));
+
+            let payloads = lines.push(b": keep-alive\r\ndata: [DONE]\r\n");
+            assert_eq!(payloads, ["[DONE]"]);
+        }
+
+        #[test]
+        fn usage_chunk_has_no_choices() {
+            let data = r#"{"choices":[],"usage":{"prompt_tokens":9,"completion_tokens":3,"total_tokens":12}}"#;
+            let chunk: ChatStreamChunk = serde_json::from_str(data).unwrap();
+            assert!(chunk.choices.is_empty());
+            assert_eq!(chunk.usage.unwrap().completion_tokens, 3);
+        }
+
+        #[test]
+        fn error_chunk_is_parsed() {
+            let data = r#"{"error":{"message":"The server had an error","type":"server_error"}}"#;
+            let chunk: ChatStreamChunk = serde_json::from_str(data).unwrap();
+            assert!(chunk.choices.is_empty());
+            assert_eq!(chunk.error.unwrap().message, "The server had an error");
+        }
+
+        #[test]
+        fn stream_request_asks_for_usage() {
+            let request = OpenAIService::chat_request("gpt-4o", Vec::new(), None, Some(100), true);
+            let json = serde_json::to_value(&request).unwrap();
+            assert_eq!(json["stream"], true);
+            assert_eq!(json["stream_options"]["include_usage"], true);
+            assert_eq!(json["max_completion_tokens"], 100);
+
+            let request = OpenAIService::chat_request("gpt-4", Vec::new(), None, None, false);
+            assert!(serde_json::to_value(&request).unwrap().get("stream_options").is_none());
+        }
+
+        #[test]
+        fn only_openai_is_asked_for_usage() {
+            let openai = OpenAIService {
+                provider: providers::OPENAI,
+                ..OpenAIService::with_base_url("sk-test", OPENAI_API_BASE)
+            };
+            assert!(openai.reports_stream_usage());
+
+            let local = OpenAIService {
+                provider: providers::OPENAI,
+                ..OpenAIService::with_base_url("", "http://localhost:1234/v1")
+            };
+            assert!(!local.reports_stream_usage());
+            let compatible = OpenAIService::with_base_url("", OPENAI_API_BASE);
+            assert!(!compatible.reports_stream_usage());
+        }
+    }
 }
@@ -0,0 +1,119 @@
+use crate::error::{AppError, Result};
+use crate::services::chapters::Chapter;
+use crate::services::ollama::StorySegment;
+use crate::services::openai::{ChatMessage, OpenAIModel, OpenAIService};
+use crate::services::whisper::TranscriptionSegment;
+
+/// A self-hosted server that speaks the OpenAI API (LM Studio,
+/// text-generation-webui, vLLM, llama.cpp server, ...), reached through
+/// the OpenAI request code
+pub struct OpenAICompatibleService {
+    api: OpenAIService,
+}
+
+impl OpenAICompatibleService {
+    /// Create a service for a server base URL, with an optional API key
+    pub fn new(base_url: &str, api_key: Option<&str>) -> Result<Self> {
+        let base_url = normalize_base_url(base_url)?;
+        Ok(Self {
+            api: OpenAIService::with_base_url(api_key.unwrap_or_default(), &base_url),
+        })
+    }
+
+    /// Chat completion
+    pub async fn chat(
+        &self,
+        model: &str,
+        messages: Vec<ChatMessage>,
+        temperature: Option<f32>,
+        max_tokens: Option<u32>,
+    ) -> Result<String> {
+        self.api.chat(model, messages, temperature, max_tokens).await
+    }
+
+    /// Summarize text
+    pub async fn summarize(&self, model: &str, text: &str, language: &str) -> Result<String> {
+        self.api.summarize(model, text, language).await
+    }
+
+    /// Extract story order / timeline from transcription segments
+    pub async fn extract_story_order(
+        &self,
+        model: &str,
+        segments: &[TranscriptionSegment],
+    ) -> Result<Vec<StorySegment>> {
+        self.api.extract_story_order(model, segments).await
+    }
+
+    /// Split transcription segments into YouTube chapters with generated titles
+    pub async fn generate_chapters(
+        &self,
+        model: &str,
+        segments: &[TranscriptionSegment],
+        language: &str,
+    ) -> Result<Vec<Chapter>> {
+        self.api.generate_chapters(model, segments, language).await
+    }
+
+    /// Check if the server is reachable and accepts the key (if any)
+    pub async fn is_available(&self) -> bool {
+        self.api.validate_api_key().await.unwrap_or(false)
+    }
+
+    /// List the models the server has loaded or can serve
+    pub async fn list_models(&self) -> Result<Vec<OpenAIModel>> {
+        self.api.list_models().await
+    }
+}
+
+/// Normalize a user-entered server address.
+/// A missing scheme defaults to `http://` and a bare host gets the usual `/v1` prefix,
+/// so `localhost:1234` becomes `http://localhost:1234/v1`.
+pub(crate) fn normalize_base_url(base_url: &str) -> Result<String> {
+    let trimmed = base_url.trim();
+    if trimmed.is_empty() {
+        return Err(AppError::InvalidInput(
+            "OpenAI-compatible server URL is empty".to_string(),
+        ));
+    }
+
+    let (scheme, rest) = trimmed.split_once("://").unwrap_or(("http", trimmed));
+    let rest = rest.trim_end_matches('/');
+    if !matches!(scheme, "http" | "https") || rest.is_empty() {
+        return Err(AppError::InvalidInput(format!(
+            "Invalid OpenAI-compatible server URL: {}",
+            base_url
+        )));
+    }
+
+    let with_scheme = format!("{}://{}", scheme, rest);
+    if rest.contains('/') {
+        Ok(with_scheme)
+    } else {
+        Ok(format!("{}/v1", with_scheme))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_base_url() {
+        assert_eq!(
+            normalize_base_url("localhost:1234").unwrap(),
+            "http://localhost:1234/v1"
+        );
+        assert_eq!(
+            normalize_base_url("http://127.0.0.1:8000/").unwrap(),
+            "http://127.0.0.1:8000/v1"
+        );
+        assert_eq!(
+            normalize_base_url("https://llm.example.com/api/v1/").unwrap(),
+            "https://llm.example.com/api/v1"
+        );
+        assert!(normalize_base_url("  ").is_err());
+        assert!(normalize_base_url("ftp://localhost").is_err());
+        assert!(normalize_base_url("http://").is_err());
+    }
+}
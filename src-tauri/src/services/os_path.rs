@@ -0,0 +1,162 @@
+//! Passing paths to ffmpeg, ffprobe, yt-dlp and whisper.cpp without losing
+//! non-ASCII characters (Korean or Japanese file names) or breaking on
+//! Windows paths longer than MAX_PATH.
+
+use sha2::{Digest, Sha256};
+use std::ffi::OsString;
+use std::path::Path;
+
+/// Longest Windows path (in UTF-16 units, with the terminator) that works
+/// without the `\\?\` prefix
+const MAX_PATH: usize = 260;
+const VERBATIM: &str = r"\\?\";
+const VERBATIM_UNC: &str = r"\\?\UNC\";
+
+/// A path as a command-line argument, passed as an `OsStr` so it never has to
+/// be valid UTF-8. On Windows, long paths get the `\\?\` prefix and short ones
+/// lose it (as `canonicalize` adds it), since not every tool understands it.
+pub fn command_arg(path: &Path) -> OsString {
+    #[cfg(windows)]
+    if let Some(path) = path.to_str() {
+        return OsString::from(windows_arg(path));
+    }
+    path.as_os_str().to_owned()
+}
+
+/// Like [`command_arg`], for whisper.cpp: on Windows it reads its arguments
+/// in the ANSI code page, so paths with other characters are passed in their
+/// short 8.3 form. A path that doesn't exist yet (an output prefix) only has
+/// its folder shortened, so its file name should be ASCII (see [`ascii_stem`]).
+pub fn ascii_command_arg(path: &Path) -> OsString {
+    #[cfg(windows)]
+    if !path.to_str().is_some_and(|p| p.is_ascii()) {
+        let short = windows::short_path(path).or_else(|| {
+            let parent = windows::short_path(path.parent()?)?;
+            Some(parent.join(path.file_name()?))
+        });
+        if let Some(short) = short {
+            return command_arg(&short);
+        }
+    }
+    command_arg(path)
+}
+
+/// File stem of `path` that any tool can take: the stem itself when it is
+/// ASCII, else its ASCII characters plus a hash of the whole path, so two
+/// files differing only in non-ASCII characters don't get the same stem
+pub fn ascii_stem(path: &Path) -> Option<String> {
+    let stem = path.file_stem()?.to_string_lossy();
+    if stem.is_ascii() {
+        return Some(stem.into_owned());
+    }
+
+    let kept: String = stem
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | ' '))
+        .collect();
+    let digest = Sha256::digest(path.as_os_str().as_encoded_bytes());
+    let hash: String = digest[..4].iter().map(|b| format!("{:02x}", b)).collect();
+    match kept.trim() {
+        "" => Some(hash),
+        kept => Some(format!("{}-{}", kept, hash)),
+    }
+}
+
+/// `path` without a `\\?\` prefix if it fits in MAX_PATH, with one (and
+/// backslashes only) if it doesn't
+#[cfg_attr(not(windows), allow(dead_code))]
+fn windows_arg(path: &str) -> String {
+    let plain = if let Some(share) = path.strip_prefix(VERBATIM_UNC) {
+        format!(r"\\{}", share)
+    } else {
+        match path.strip_prefix(VERBATIM) {
+            // Device and volume paths only work verbatim
+            Some(rest) if has_drive(rest) => rest.to_string(),
+            Some(_) => return path.to_string(),
+            None => path.to_string(),
+        }
+    };
+
+    if plain.encode_utf16().count() < MAX_PATH {
+        return plain;
+    }
+    // Verbatim paths are taken literally: no `/` separators
+    let plain = plain.replace('/', "\\");
+    if let Some(share) = plain.strip_prefix(r"\\") {
+        format!("{}{}", VERBATIM_UNC, share)
+    } else if has_drive(&plain) {
+        format!("{}{}", VERBATIM, plain)
+    } else {
+        plain
+    }
+}
+
+/// Whether a path starts with a drive like `C:\`
+fn has_drive(path: &str) -> bool {
+    let bytes = path.as_bytes();
+    bytes.len() >= 3 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':' && bytes[2] == b'\\'
+}
+
+#[cfg(windows)]
+mod windows {
+    use std::ffi::OsString;
+    use std::os::windows::ffi::{OsStrExt, OsStringExt};
+    use std::path::{Path, PathBuf};
+    use windows_sys::Win32::Storage::FileSystem::GetShortPathNameW;
+
+    /// The 8.3 form of an existing path. Volumes with short names turned off
+    /// return the long path unchanged.
+    pub fn short_path(path: &Path) -> Option<PathBuf> {
+        let wide: Vec<u16> = path.as_os_str().encode_wide().chain(Some(0)).collect();
+        // SAFETY: `wide` is NUL-terminated; a zero-sized query writes nothing
+        let needed = unsafe { GetShortPathNameW(wide.as_ptr(), std::ptr::null_mut(), 0) };
+        if needed == 0 {
+            return None;
+        }
+        let mut buffer = vec![0u16; needed as usize];
+        // SAFETY: `buffer` holds `needed` UTF-16 units
+        let written = unsafe { GetShortPathNameW(wide.as_ptr(), buffer.as_mut_ptr(), needed) };
+        if written == 0 || written >= needed {
+            return None;
+        }
+        buffer.truncate(written as usize);
+        Some(PathBuf::from(OsString::from_wide(&buffer)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_windows_arg() {
+        // Short paths lose the verbatim prefix canonicalize adds
+        assert_eq!(windows_arg(r"\\?\C:\media\회의.mp4"), r"C:\media\회의.mp4");
+        assert_eq!(windows_arg(r"\\?\UNC\nas\media\a.mp4"), r"\\nas\media\a.mp4");
+        assert_eq!(windows_arg(r"C:\media\a.mp4"), r"C:\media\a.mp4");
+        assert_eq!(windows_arg(r"\\?\Volume{1234}\a.mp4"), r"\\?\Volume{1234}\a.mp4");
+
+        // Long ones get it
+        let long_dir = "フォルダ".repeat(70);
+        let long = format!(r"C:\{}/a.mp4", long_dir);
+        assert_eq!(windows_arg(&long), format!(r"\\?\C:\{}\a.mp4", long_dir));
+        let long_unc = format!(r"\\nas\{}\a.mp4", long_dir);
+        assert_eq!(windows_arg(&long_unc), format!(r"\\?\UNC\nas\{}\a.mp4", long_dir));
+        assert_eq!(windows_arg(&format!(r"\\?\{}", long)), format!(r"\\?\C:\{}\a.mp4", long_dir));
+    }
+
+    #[test]
+    fn test_ascii_stem() {
+        assert_eq!(ascii_stem(Path::new("/media/talk 01.mp4")).unwrap(), "talk 01");
+
+        let korean = ascii_stem(Path::new("/media/회의 녹음 2024.mp4")).unwrap();
+        assert!(korean.is_ascii());
+        assert!(korean.starts_with("2024-"));
+        // Same name in another folder, or another name, gets a different stem
+        assert_ne!(korean, ascii_stem(Path::new("/other/회의 녹음 2024.mp4")).unwrap());
+        assert_ne!(korean, ascii_stem(Path::new("/media/인터뷰 2024.mp4")).unwrap());
+        assert_eq!(ascii_stem(Path::new("/media/会議.wav")).unwrap().len(), 8);
+
+        assert_eq!(ascii_stem(Path::new("/")), None);
+    }
+}
@@ -0,0 +1,86 @@
+//! Validation of paths that arrive from the webview before the backend
+//! deletes, moves or writes anything at them.
+
+use crate::error::{AppError, Result};
+use std::path::{Path, PathBuf};
+
+/// Canonical, absolute form of a path received from the webview.
+/// The path itself may not exist yet (an output file), but its parent must.
+pub fn canonicalize(raw: &str) -> Result<PathBuf> {
+    if raw.trim().is_empty() {
+        return Err(AppError::InvalidPath("Empty path".to_string()));
+    }
+    if raw.contains('\0') {
+        return Err(AppError::InvalidPath(format!("Path contains a NUL byte: {:?}", raw)));
+    }
+    let path = Path::new(raw);
+    if !path.is_absolute() {
+        return Err(AppError::InvalidPath(format!("Path is not absolute: {}", raw)));
+    }
+
+    if path.exists() {
+        return Ok(std::fs::canonicalize(path)?);
+    }
+
+    // `file_name` is None for paths ending in `..`, which could escape the parent
+    let (Some(parent), Some(name)) = (path.parent(), path.file_name()) else {
+        return Err(AppError::InvalidPath(format!("Invalid path: {}", raw)));
+    };
+    let parent = std::fs::canonicalize(parent).map_err(|_| {
+        AppError::InvalidPath(format!("Folder does not exist: {}", parent.display()))
+    })?;
+    Ok(parent.join(name))
+}
+
+/// Whether a canonical `path` lies inside one of `roots`, which are
+/// canonicalized first; roots that don't exist are skipped
+pub fn is_within(path: &Path, roots: &[PathBuf]) -> bool {
+    roots
+        .iter()
+        .filter_map(|root| std::fs::canonicalize(root).ok())
+        .any(|root| path.starts_with(root))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_canonicalize() {
+        let temp = TempDir::new().unwrap();
+        let root = std::fs::canonicalize(temp.path()).unwrap();
+        std::fs::create_dir(root.join("media")).unwrap();
+        std::fs::write(root.join("media/clip.mp4"), b"").unwrap();
+
+        let dotted = root.join("media/../media/clip.mp4");
+        assert_eq!(
+            canonicalize(dotted.to_str().unwrap()).unwrap(),
+            root.join("media/clip.mp4")
+        );
+        // A file that doesn't exist yet resolves through its parent
+        let output = root.join("media/../out.wav");
+        assert_eq!(canonicalize(output.to_str().unwrap()).unwrap(), root.join("out.wav"));
+
+        assert!(canonicalize("").is_err());
+        assert!(canonicalize("media/clip.mp4").is_err());
+        assert!(canonicalize(root.join("a\0b").to_str().unwrap()).is_err());
+        assert!(canonicalize(root.join("missing/out.wav").to_str().unwrap()).is_err());
+        assert!(canonicalize(root.join("missing/..").to_str().unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_is_within() {
+        let temp = TempDir::new().unwrap();
+        let root = std::fs::canonicalize(temp.path()).unwrap();
+        let library = root.join("library");
+        std::fs::create_dir(&library).unwrap();
+
+        let roots = vec![library.clone(), root.join("missing")];
+        assert!(is_within(&library.join("clip.mp4"), &roots));
+        assert!(is_within(&library, &roots));
+        assert!(!is_within(&root.join("other/clip.mp4"), &roots));
+        // Prefixes only count at component boundaries
+        assert!(!is_within(&root.join("library-2/clip.mp4"), &roots));
+    }
+}
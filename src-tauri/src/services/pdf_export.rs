@@ -0,0 +1,289 @@
+use crate::error::{AppError, Result};
+use crate::services::export::clock_timestamp;
+use crate::services::whisper::TranscriptionSegment;
+use printpdf::{
+    BuiltinFont, Color, IndirectFontRef, Line, Mm, PdfDocument, PdfDocumentReference,
+    PdfLayerReference, Point, Rgb,
+};
+use serde::{Deserialize, Serialize};
+
+const PAGE_WIDTH: f32 = 210.0;
+const PAGE_HEIGHT: f32 = 297.0;
+const MARGIN: f32 = 20.0;
+const BODY_SIZE: f32 = 10.5;
+const POINTS_TO_MM: f32 = 0.3528;
+/// Average glyph width relative to the font size, used for line wrapping
+const AVERAGE_GLYPH_WIDTH: f32 = 0.5;
+
+/// Content and branding of a PDF transcript report
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PdfReportOptions {
+    /// Report title (usually the media file name)
+    pub title: Option<String>,
+    /// Summary printed before the transcript
+    pub summary: Option<String>,
+    /// Organization or author shown under the title
+    pub organization: Option<String>,
+    /// Accent color for the title and headings, as `#rrggbb`
+    pub accent_color: Option<String>,
+    /// Text printed at the bottom of every page next to the page number
+    pub footer: Option<String>,
+    /// TrueType font to embed. The built-in Helvetica only covers Latin-1,
+    /// so transcripts in other scripts need a font that supports them.
+    pub font_path: Option<String>,
+    /// Leave out the start time printed before each segment
+    pub hide_timestamps: bool,
+}
+
+/// Parse `#rrggbb` into a PDF color
+fn parse_hex_color(hex: &str) -> Option<Color> {
+    let hex = hex.trim().trim_start_matches('#');
+    if hex.len() != 6 {
+        return None;
+    }
+    let channel = |i: usize| {
+        u8::from_str_radix(hex.get(i..i + 2)?, 16)
+            .ok()
+            .map(|v| v as f32 / 255.0)
+    };
+    Some(Color::Rgb(Rgb::new(channel(0)?, channel(2)?, channel(4)?, None)))
+}
+
+/// Break text into lines of at most `max_chars` characters, at word boundaries where possible
+fn wrap(text: &str, max_chars: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+
+    for word in text.split_whitespace() {
+        let mut word = word.to_string();
+        // Words longer than a line are hard-split
+        while word.chars().count() > max_chars {
+            if !line.is_empty() {
+                lines.push(std::mem::take(&mut line));
+            }
+            let split = word.char_indices().nth(max_chars).map_or(word.len(), |(i, _)| i);
+            lines.push(word[..split].to_string());
+            word = word[split..].to_string();
+        }
+
+        if !line.is_empty() && line.chars().count() + 1 + word.chars().count() > max_chars {
+            lines.push(std::mem::take(&mut line));
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(&word);
+    }
+
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+/// Writes lines top to bottom, starting new pages as needed
+struct PageWriter {
+    doc: PdfDocumentReference,
+    font: IndirectFontRef,
+    bold: IndirectFontRef,
+    layer: PdfLayerReference,
+    y: f32,
+    page: usize,
+    footer: Option<String>,
+}
+
+impl PageWriter {
+    fn line_height(size: f32) -> f32 {
+        size * POINTS_TO_MM * 1.45
+    }
+
+    fn ensure_space(&mut self, height: f32) {
+        if self.y - height >= MARGIN + 10.0 {
+            return;
+        }
+        self.write_footer();
+        let (page, layer) = self.doc.add_page(Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Layer 1");
+        self.layer = self.doc.get_page(page).get_layer(layer);
+        self.page += 1;
+        self.y = PAGE_HEIGHT - MARGIN;
+    }
+
+    fn write_footer(&self) {
+        let gray = Color::Rgb(Rgb::new(0.45, 0.45, 0.45, None));
+        self.layer.set_fill_color(gray);
+        let text = match &self.footer {
+            Some(footer) => format!("{}  ·  {}", footer, self.page),
+            None => self.page.to_string(),
+        };
+        self.layer.use_text(text, 8.0, Mm(MARGIN), Mm(MARGIN - 8.0), &self.font);
+    }
+
+    /// Write wrapped text in the given size and color
+    fn paragraph(&mut self, text: &str, size: f32, bold: bool, color: &Color) {
+        let width_pt = (PAGE_WIDTH - 2.0 * MARGIN) / POINTS_TO_MM;
+        let max_chars = (width_pt / (size * AVERAGE_GLYPH_WIDTH)).max(10.0) as usize;
+        let height = Self::line_height(size);
+
+        for line in wrap(text, max_chars) {
+            self.ensure_space(height);
+            self.y -= height;
+            self.layer.set_fill_color(color.clone());
+            let font = if bold { &self.bold } else { &self.font };
+            self.layer.use_text(line, size, Mm(MARGIN), Mm(self.y), font);
+        }
+    }
+
+    fn gap(&mut self, mm: f32) {
+        self.y -= mm;
+    }
+
+    fn rule(&mut self, color: &Color) {
+        self.ensure_space(4.0);
+        self.y -= 2.0;
+        self.layer.set_outline_color(color.clone());
+        self.layer.set_outline_thickness(0.8);
+        self.layer.add_line(Line {
+            points: vec![
+                (Point::new(Mm(MARGIN), Mm(self.y)), false),
+                (Point::new(Mm(PAGE_WIDTH - MARGIN), Mm(self.y)), false),
+            ],
+            is_closed: false,
+        });
+        self.y -= 2.0;
+    }
+}
+
+fn pdf_error(e: printpdf::Error) -> AppError {
+    AppError::Export(format!("Failed to build PDF: {}", e))
+}
+
+/// Render a transcript (and optional summary) as a PDF report
+pub fn render_pdf_report(
+    segments: &[TranscriptionSegment],
+    options: &PdfReportOptions,
+) -> Result<Vec<u8>> {
+    let title = options.title.as_deref().unwrap_or("Transcript");
+    let (doc, page, layer) = PdfDocument::new(title, Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Layer 1");
+
+    let (font, bold) = match &options.font_path {
+        Some(path) => {
+            let font = doc
+                .add_external_font(std::fs::File::open(path)?)
+                .map_err(pdf_error)?;
+            (font.clone(), font)
+        }
+        None => (
+            doc.add_builtin_font(BuiltinFont::Helvetica).map_err(pdf_error)?,
+            doc.add_builtin_font(BuiltinFont::HelveticaBold).map_err(pdf_error)?,
+        ),
+    };
+
+    let layer = doc.get_page(page).get_layer(layer);
+    let mut writer = PageWriter {
+        doc,
+        font,
+        bold,
+        layer,
+        y: PAGE_HEIGHT - MARGIN,
+        page: 1,
+        footer: options.footer.clone(),
+    };
+
+    let accent = options
+        .accent_color
+        .as_deref()
+        .and_then(parse_hex_color)
+        .unwrap_or(Color::Rgb(Rgb::new(0.15, 0.35, 0.75, None)));
+    let text_color = Color::Rgb(Rgb::new(0.1, 0.1, 0.1, None));
+    let muted = Color::Rgb(Rgb::new(0.4, 0.4, 0.4, None));
+
+    writer.paragraph(title, 20.0, true, &accent);
+    if let Some(organization) = options.organization.as_deref() {
+        writer.gap(1.0);
+        writer.paragraph(organization, 10.0, false, &muted);
+    }
+    writer.rule(&accent);
+
+    if let Some(summary) = options.summary.as_deref().filter(|s| !s.trim().is_empty()) {
+        writer.gap(4.0);
+        writer.paragraph("Summary", 14.0, true, &accent);
+        writer.gap(2.0);
+        for block in summary.split("\n\n") {
+            writer.paragraph(block, BODY_SIZE, false, &text_color);
+            writer.gap(2.0);
+        }
+    }
+
+    writer.gap(4.0);
+    writer.paragraph("Transcript", 14.0, true, &accent);
+    writer.gap(2.0);
+
+    for segment in segments {
+        let text = segment.text.trim();
+        if text.is_empty() {
+            continue;
+        }
+        let mut line = String::new();
+        if !options.hide_timestamps {
+            line.push_str(&format!("[{}] ", clock_timestamp(segment.start)));
+        }
+        if let Some(speaker) = &segment.speaker {
+            line.push_str(&format!("{}: ", speaker));
+        }
+        line.push_str(text);
+
+        writer.paragraph(&line, BODY_SIZE, false, &text_color);
+        writer.gap(1.5);
+    }
+
+    writer.write_footer();
+    writer.doc.save_to_bytes().map_err(pdf_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wrap() {
+        assert_eq!(
+            wrap("the quick brown fox jumps", 10),
+            ["the quick", "brown fox", "jumps"]
+        );
+        assert_eq!(wrap("abcdefghijklmno", 6), ["abcdef", "ghijkl", "mno"]);
+        assert!(wrap("   ", 10).is_empty());
+    }
+
+    #[test]
+    fn test_parse_hex_color() {
+        assert!(parse_hex_color("#ff8800").is_some());
+        assert!(parse_hex_color("00ff00").is_some());
+        assert!(parse_hex_color("#fff").is_none());
+        assert!(parse_hex_color("#gg0000").is_none());
+    }
+
+    #[test]
+    fn test_render_pdf_report_spans_pages() {
+        let segments: Vec<TranscriptionSegment> = (0..200)
+            .map(|i| {
+                let text = format!("Segment number {} of a long interview transcript.", i);
+                let segment = TranscriptionSegment::new(i as f64 * 5.0, i as f64 * 5.0 + 5.0, &text);
+                if i % 2 == 0 {
+                    segment.with_speaker("Alex")
+                } else {
+                    segment
+                }
+            })
+            .collect();
+        let options = PdfReportOptions {
+            title: Some("interview.mp4".to_string()),
+            summary: Some("A long talk.".to_string()),
+            footer: Some("Clip Flow".to_string()),
+            ..Default::default()
+        };
+
+        let pdf = render_pdf_report(&segments, &options).unwrap();
+        assert!(pdf.starts_with(b"%PDF"));
+    }
+}
@@ -0,0 +1,268 @@
+use crate::error::{AppError, Result};
+use crate::services::download::{download_file, DownloadProgress};
+use crate::services::http;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tokio_util::sync::CancellationToken;
+
+/// Longest episode file name stem, in characters
+const MAX_FILE_STEM: usize = 120;
+
+/// A podcast feed as read from its RSS document
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ParsedFeed {
+    pub title: String,
+    pub description: Option<String>,
+    pub episodes: Vec<FeedItem>,
+}
+
+/// An episode of a feed that has an audio enclosure
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FeedItem {
+    /// Stable id from the feed (`<guid>`, or the audio URL when missing)
+    pub guid: String,
+    pub title: String,
+    pub description: Option<String>,
+    pub audio_url: String,
+    /// Size announced by the enclosure, in bytes (0 if unknown)
+    pub size: u64,
+    /// Unix seconds
+    pub published_at: Option<u64>,
+}
+
+/// Fetches podcast RSS feeds and downloads their episodes
+pub struct PodcastService {
+    client: Client,
+}
+
+impl PodcastService {
+    pub fn new() -> Self {
+        Self {
+            client: http::client(),
+        }
+    }
+
+    /// Fetch and parse a feed
+    pub async fn fetch_feed(&self, url: &str) -> Result<ParsedFeed> {
+        let response = self
+            .client
+            .get(url)
+            .send()
+            .await?
+            .error_for_status()
+            .map_err(|e| AppError::Download(e.to_string()))?;
+        let body = response.bytes().await?;
+        parse_feed(&body)
+    }
+
+    /// Download an episode's audio to `output_path`.
+    /// Cancelling the token stops the download and removes the partial file.
+    pub async fn download_episode<F>(
+        &self,
+        audio_url: &str,
+        expected_size: u64,
+        output_path: &Path,
+        cancel: &CancellationToken,
+        on_progress: F,
+    ) -> Result<()>
+    where
+        F: Fn(DownloadProgress) + Send + 'static,
+    {
+        if let Some(parent) = output_path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        download_file(
+            &http::download_client(),
+            audio_url,
+            output_path,
+            expected_size,
+            audio_url,
+            cancel,
+            None,
+            on_progress,
+        )
+        .await
+    }
+}
+
+impl Default for PodcastService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Parse an RSS feed, keeping only items with an audio or video enclosure
+pub fn parse_feed(xml: &[u8]) -> Result<ParsedFeed> {
+    let channel = rss::Channel::read_from(xml)
+        .map_err(|e| AppError::InvalidInput(format!("Not a valid RSS feed: {}", e)))?;
+
+    let episodes = channel
+        .items()
+        .iter()
+        .filter_map(|item| {
+            let enclosure = item.enclosure()?;
+            let mime_type = enclosure.mime_type();
+            if !(mime_type.is_empty()
+                || mime_type.starts_with("audio/")
+                || mime_type.starts_with("video/"))
+            {
+                return None;
+            }
+
+            let audio_url = enclosure.url().trim().to_string();
+            let guid = item
+                .guid()
+                .map(|g| g.value().trim().to_string())
+                .filter(|g| !g.is_empty())
+                .unwrap_or_else(|| audio_url.clone());
+            Some(FeedItem {
+                guid,
+                title: item.title().unwrap_or("Untitled episode").trim().to_string(),
+                description: non_empty(item.description()),
+                audio_url,
+                size: enclosure.length().trim().parse().unwrap_or(0),
+                published_at: item.pub_date().and_then(parse_pub_date),
+            })
+        })
+        .collect();
+
+    Ok(ParsedFeed {
+        title: channel.title().trim().to_string(),
+        description: non_empty(Some(channel.description())),
+        episodes,
+    })
+}
+
+fn non_empty(text: Option<&str>) -> Option<String> {
+    text.map(str::trim).filter(|t| !t.is_empty()).map(str::to_string)
+}
+
+/// Parse an RFC 2822 `<pubDate>` into Unix seconds
+fn parse_pub_date(date: &str) -> Option<u64> {
+    chrono::DateTime::parse_from_rfc2822(date.trim())
+        .ok()
+        .and_then(|d| u64::try_from(d.timestamp()).ok())
+}
+
+/// A feed or episode title made safe to use as a file or folder name on any file system
+pub fn safe_file_stem(title: &str) -> String {
+    let stem: String = title
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .take(MAX_FILE_STEM)
+        .collect();
+    stem.trim().trim_matches('.').to_string()
+}
+
+/// File name for a downloaded episode: the safe title with the extension of
+/// the audio URL (`.mp3` when it has none)
+pub fn episode_file_name(title: &str, audio_url: &str) -> String {
+    let stem = safe_file_stem(title);
+    let stem = if stem.is_empty() { "episode" } else { &stem };
+
+    let extension = audio_url
+        .split(['?', '#'])
+        .next()
+        .and_then(|path| path.rsplit('/').next())
+        .and_then(|name| name.rsplit_once('.'))
+        .map(|(_, ext)| ext.to_ascii_lowercase())
+        .filter(|ext| {
+            !ext.is_empty() && ext.len() <= 4 && ext.chars().all(|c| c.is_ascii_alphanumeric())
+        })
+        .unwrap_or_else(|| "mp3".to_string());
+
+    format!("{}.{}", stem, extension)
+}
+
+/// `file_name`, or with " (2)", " (3)"... added to its stem when a file of
+/// that name is already in `dir`, e.g. for episodes with the same title
+pub fn unused_file_name(dir: &Path, file_name: &str) -> String {
+    let (stem, extension) = match file_name.rsplit_once('.') {
+        Some((stem, extension)) => (stem, format!(".{}", extension)),
+        None => (file_name, String::new()),
+    };
+    let mut name = file_name.to_string();
+    let mut n = 1;
+    while dir.join(&name).exists() {
+        n += 1;
+        name = format!("{} ({}){}", stem, n, extension);
+    }
+    name
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FEED: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<rss version="2.0">
+  <channel>
+    <title>Clip Talk</title>
+    <description>Editing stories</description>
+    <item>
+      <title>Episode 2: Color</title>
+      <guid>ep-2</guid>
+      <pubDate>Tue, 02 Jan 2024 10:00:00 +0000</pubDate>
+      <enclosure url="https://cdn.example.com/ep2.m4a?token=1" length="2048" type="audio/x-m4a"/>
+    </item>
+    <item>
+      <title>Show notes only</title>
+      <guid>notes</guid>
+    </item>
+    <item>
+      <title>Episode 1</title>
+      <enclosure url="https://cdn.example.com/ep1.mp3" length="" type="audio/mpeg"/>
+    </item>
+  </channel>
+</rss>"#;
+
+    #[test]
+    fn test_parse_feed_keeps_items_with_audio() {
+        let feed = parse_feed(FEED.as_bytes()).unwrap();
+        assert_eq!(feed.title, "Clip Talk");
+        assert_eq!(feed.description.as_deref(), Some("Editing stories"));
+        assert_eq!(feed.episodes.len(), 2);
+
+        let latest = &feed.episodes[0];
+        assert_eq!(latest.guid, "ep-2");
+        assert_eq!(latest.size, 2048);
+        assert_eq!(latest.published_at, Some(1_704_189_600));
+
+        // Without a guid the audio URL identifies the episode
+        let first = &feed.episodes[1];
+        assert_eq!(first.guid, "https://cdn.example.com/ep1.mp3");
+        assert_eq!((first.size, first.published_at), (0, None));
+    }
+
+    #[test]
+    fn test_parse_feed_rejects_other_documents() {
+        assert!(parse_feed(b"<html><body>Not a feed</body></html>").is_err());
+    }
+
+    #[test]
+    fn test_episode_file_name() {
+        assert_eq!(
+            episode_file_name("Episode 2: Color", "https://cdn.example.com/ep2.m4a?token=1"),
+            "Episode 2_ Color.m4a"
+        );
+        assert_eq!(episode_file_name("  ", "https://example.com/stream"), "episode.mp3");
+        assert_eq!(episode_file_name("A/B", "https://example.com/a.MP3"), "A_B.mp3");
+    }
+
+    #[test]
+    fn test_unused_file_name() {
+        let dir = tempfile::TempDir::new().unwrap();
+        assert_eq!(unused_file_name(dir.path(), "Bonus.mp3"), "Bonus.mp3");
+
+        std::fs::write(dir.path().join("Bonus.mp3"), b"").unwrap();
+        assert_eq!(unused_file_name(dir.path(), "Bonus.mp3"), "Bonus (2).mp3");
+        std::fs::write(dir.path().join("Bonus (2).mp3"), b"").unwrap();
+        assert_eq!(unused_file_name(dir.path(), "Bonus.mp3"), "Bonus (3).mp3");
+        assert_eq!(unused_file_name(dir.path(), "Other.mp3"), "Other.mp3");
+    }
+}
@@ -0,0 +1,157 @@
+use crate::error::Result;
+use crate::services::llm::{parse_json_response, LlmProvider};
+use crate::services::whisper::TranscriptionSegment;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tokio_util::sync::CancellationToken;
+
+const POLISH_SYSTEM: &str = "You are a careful transcript editor who turns verbatim speech \
+                             into a clean read without changing what was said. Respond with \
+                             the JSON array only, without any explanation.";
+
+/// Segments rewritten per request; the reply repeats their text, so batches
+/// stay well inside the models' output budget
+const SEGMENTS_PER_REQUEST: usize = 40;
+
+/// Reply budget for one batch of segments
+const POLISH_MAX_TOKENS: u32 = 8192;
+
+/// A segment in its clean read version, next to the verbatim text
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PolishedSegment {
+    /// Index into the transcript segments
+    pub segment: usize,
+    pub start: f64,
+    pub end: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub speaker: Option<String>,
+    /// The verbatim text
+    pub original: String,
+    /// The clean read text; empty when the segment was only filler words
+    pub text: String,
+    /// Whether the clean read differs from the verbatim text
+    pub changed: bool,
+}
+
+/// A rewritten segment as returned by the LLM
+#[derive(Debug, Deserialize)]
+struct RawPolished {
+    segment: usize,
+    text: String,
+}
+
+/// Fix punctuation, casing, filler words and obvious mis-hearings of every
+/// segment, keeping its timestamps. Segments the model skips keep their text.
+pub async fn polish_transcript(
+    llm: &dyn LlmProvider,
+    segments: &[TranscriptionSegment],
+    cancel: &CancellationToken,
+) -> Result<Vec<PolishedSegment>> {
+    log::info!(
+        "[polish] Polishing {} segments with {} ({})",
+        segments.len(),
+        llm.id(),
+        llm.model()
+    );
+    let mut rewrites = HashMap::new();
+    for offset in (0..segments.len()).step_by(SEGMENTS_PER_REQUEST) {
+        let end = (offset + SEGMENTS_PER_REQUEST).min(segments.len());
+        let prompt = build_polish_prompt(&segments[offset..end], offset);
+        let response = llm
+            .complete(POLISH_SYSTEM, &prompt, POLISH_MAX_TOKENS, cancel)
+            .await?;
+        let raw: Vec<RawPolished> = parse_json_response(&response, "polished transcript")?;
+        rewrites.extend(
+            raw.into_iter()
+                .filter(|r| (offset..end).contains(&r.segment))
+                .map(|r| (r.segment, r.text)),
+        );
+    }
+    Ok(resolve_polished(segments, rewrites))
+}
+
+fn build_polish_prompt(segments: &[TranscriptionSegment], offset: usize) -> String {
+    let segments_text: Vec<String> = segments
+        .iter()
+        .enumerate()
+        .map(|(i, s)| match &s.speaker {
+            Some(speaker) => format!("[{}] {}: {}", offset + i, speaker, s.text.trim()),
+            None => format!("[{}] {}", offset + i, s.text.trim()),
+        })
+        .collect();
+
+    format!(
+        "Rewrite every segment of this transcript as a clean read.\n\n\
+         Rules:\n\
+         - Fix punctuation and capitalization\n\
+         - Remove filler words (um, uh, you know, like) and stutters\n\
+         - Fix words that were clearly misheard, judging from the context\n\
+         - Keep the meaning, wording and language; do not summarize or add anything\n\
+         - Keep every segment separate, one entry per segment; a segment of only filler \
+           words becomes \"\"\n\
+         - Leave out the speaker names\n\n\
+         Segments:\n{}\n\n\
+         Response format: [{{\"segment\": {}, \"text\": \"...\"}}, ...]",
+        segments_text.join("\n"),
+        offset
+    )
+}
+
+/// One clean read per segment, with the verbatim text where the model gave none
+fn resolve_polished(
+    segments: &[TranscriptionSegment],
+    mut rewrites: HashMap<usize, String>,
+) -> Vec<PolishedSegment> {
+    segments
+        .iter()
+        .enumerate()
+        .map(|(i, s)| {
+            let original = s.text.trim().to_string();
+            let text = rewrites
+                .remove(&i)
+                .map(|text| text.split_whitespace().collect::<Vec<_>>().join(" "))
+                .unwrap_or_else(|| original.clone());
+            PolishedSegment {
+                segment: i,
+                start: s.start,
+                end: s.end,
+                speaker: s.speaker.clone(),
+                changed: text != original,
+                original,
+                text,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_polished() {
+        let segments = vec![
+            TranscriptionSegment::new(0.0, 2.0, " so um we we shipped the the build "),
+            TranscriptionSegment::new(2.0, 4.0, "Um."),
+            TranscriptionSegment::new(4.0, 6.0, "See you."),
+        ];
+        let response = r#"[
+            {"segment": 0, "text": "So we shipped the  build."},
+            {"segment": 1, "text": ""},
+            {"segment": 7, "text": "out of range"}
+        ]"#;
+        let raw: Vec<RawPolished> = parse_json_response(response, "polished transcript").unwrap();
+        let rewrites = raw.into_iter().map(|r| (r.segment, r.text)).collect();
+
+        let polished = resolve_polished(&segments, rewrites);
+        assert_eq!(polished.len(), 3);
+        assert_eq!(polished[0].original, "so um we we shipped the the build");
+        assert_eq!(polished[0].text, "So we shipped the build.");
+        assert_eq!((polished[0].start, polished[0].end), (0.0, 2.0));
+        assert!(polished[0].changed);
+        // Filler-only segments may become empty, skipped ones stay verbatim
+        assert_eq!(polished[1].text, "");
+        assert_eq!(polished[2].text, "See you.");
+        assert!(!polished[2].changed);
+    }
+}
@@ -0,0 +1,193 @@
+//! Registry of the child processes the backend starts (ffmpeg, whisper.cpp,
+//! yt-dlp, hooks), so the UI can list and stop them and none is left running
+//! when the app quits.
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::Instant;
+use tokio::process::Child;
+use tokio_util::sync::CancellationToken;
+
+/// What a registered process is doing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProcessKind {
+    Ffmpeg,
+    Whisper,
+    YtDlp,
+    Hook,
+}
+
+/// A running child process, as reported to the UI
+#[derive(Debug, Clone, Serialize)]
+pub struct ActiveProcess {
+    pub kind: ProcessKind,
+    pub pid: u32,
+    /// The media file (or URL) the process works on
+    pub file: Option<String>,
+    pub elapsed_secs: f64,
+}
+
+struct Tracked {
+    kind: ProcessKind,
+    file: Option<String>,
+    started: Instant,
+    /// Cancels the work the process belongs to, so its owner can stop it
+    /// and clean up
+    cancel: CancellationToken,
+}
+
+fn registry() -> &'static Mutex<HashMap<u32, Tracked>> {
+    static REGISTRY: OnceLock<Mutex<HashMap<u32, Tracked>>> = OnceLock::new();
+    REGISTRY.get_or_init(Mutex::default)
+}
+
+/// Keeps a process registered until it is dropped, which the owner does once
+/// the process has exited or been killed
+#[must_use]
+pub struct ProcessGuard {
+    pid: Option<u32>,
+}
+
+impl Drop for ProcessGuard {
+    fn drop(&mut self) {
+        if let Some(pid) = self.pid {
+            registry().lock().unwrap_or_else(|e| e.into_inner()).remove(&pid);
+        }
+    }
+}
+
+/// Register a freshly spawned child working on `file`; `cancel` is the token
+/// its owner stops it with
+pub fn track(
+    child: &Child,
+    kind: ProcessKind,
+    file: Option<String>,
+    cancel: &CancellationToken,
+) -> ProcessGuard {
+    let pid = child.id();
+    if let Some(pid) = pid {
+        let tracked = Tracked {
+            kind,
+            file,
+            started: Instant::now(),
+            cancel: cancel.clone(),
+        };
+        registry().lock().unwrap_or_else(|e| e.into_inner()).insert(pid, tracked);
+    }
+    ProcessGuard { pid }
+}
+
+/// The registered processes, longest-running first
+pub fn active_processes() -> Vec<ActiveProcess> {
+    let registry = registry().lock().unwrap_or_else(|e| e.into_inner());
+    let mut processes: Vec<_> = registry
+        .iter()
+        .map(|(pid, tracked)| ActiveProcess {
+            kind: tracked.kind,
+            pid: *pid,
+            file: tracked.file.clone(),
+            elapsed_secs: tracked.started.elapsed().as_secs_f64(),
+        })
+        .collect();
+    processes.sort_by(|a, b| b.elapsed_secs.total_cmp(&a.elapsed_secs));
+    processes
+}
+
+/// Stop a registered process by cancelling the work it belongs to; its owner
+/// kills it and removes partial output. Returns false if no such process runs.
+pub fn terminate(pid: u32) -> bool {
+    let registry = registry().lock().unwrap_or_else(|e| e.into_inner());
+    match registry.get(&pid) {
+        Some(tracked) => {
+            log::info!("[processes] Terminating {:?} process {}", tracked.kind, pid);
+            tracked.cancel.cancel();
+            true
+        }
+        None => false,
+    }
+}
+
+/// Kill a process by id, without waiting for the async runtime
+fn kill_pid(pid: u32) -> std::io::Result<()> {
+    let mut command = if cfg!(windows) {
+        let mut command = std::process::Command::new("taskkill");
+        command.args(["/PID", &pid.to_string(), "/T", "/F"]);
+        command
+    } else {
+        let mut command = std::process::Command::new("kill");
+        command.arg(pid.to_string());
+        command
+    };
+    command
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status()
+        .map(|_| ())
+}
+
+/// Kill every registered process. Called when the app exits, where
+/// `kill_on_drop` never runs because the process ends without unwinding.
+pub fn kill_all() {
+    let processes: Vec<_> = registry()
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .drain()
+        .collect();
+    for (pid, tracked) in processes {
+        log::info!("[processes] Killing {:?} process {} on exit", tracked.kind, pid);
+        if let Err(e) = kill_pid(pid) {
+            log::warn!("[processes] Failed to kill process {}: {}", pid, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::process::Command;
+
+    /// The tests share the global registry, and `kill_all` empties it
+    static SERIAL: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_kill_all_stops_tracked_processes() {
+        let _serial = SERIAL.lock().await;
+        let mut child = Command::new("sleep").arg("30").kill_on_drop(true).spawn().unwrap();
+        let pid = child.id().unwrap();
+        let _guard = track(&child, ProcessKind::Ffmpeg, None, &CancellationToken::new());
+        assert!(registry().lock().unwrap().contains_key(&pid));
+
+        kill_all();
+        let status = tokio::time::timeout(std::time::Duration::from_secs(5), child.wait())
+            .await
+            .expect("process was killed")
+            .unwrap();
+        assert!(!status.success());
+        assert!(!registry().lock().unwrap().contains_key(&pid));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_list_and_terminate() {
+        let _serial = SERIAL.lock().await;
+        let child = Command::new("sleep").arg("30").kill_on_drop(true).spawn().unwrap();
+        let pid = child.id().unwrap();
+        let cancel = CancellationToken::new();
+        let guard = track(&child, ProcessKind::Whisper, Some("talk.wav".to_string()), &cancel);
+
+        let listed = active_processes();
+        let active = listed.iter().find(|p| p.pid == pid).unwrap();
+        assert_eq!(active.kind, ProcessKind::Whisper);
+        assert_eq!(active.file.as_deref(), Some("talk.wav"));
+
+        assert!(terminate(pid));
+        assert!(cancel.is_cancelled());
+
+        drop(guard);
+        assert!(!registry().lock().unwrap().contains_key(&pid));
+        assert!(!terminate(pid));
+    }
+}
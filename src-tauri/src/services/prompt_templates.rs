@@ -0,0 +1,170 @@
+use crate::error::{AppError, Result};
+use crate::services::llm::LlmProvider;
+use crate::services::ollama::language_code_to_name;
+use crate::services::whisper::TranscriptionSegment;
+use std::collections::HashMap;
+use tokio_util::sync::CancellationToken;
+
+/// System instructions for templates that don't set their own
+const DEFAULT_SYSTEM: &str = "You are an assistant that works with transcripts of audio and \
+                              video recordings. Answer with the requested result only.";
+
+/// Reply budget for template prompts
+const TEMPLATE_MAX_TOKENS: u32 = 4000;
+
+/// Variables filled in from a transcript: `{{transcript}}` has one
+/// `[start] text` line per segment, `{{text}}` the plain text
+pub const TRANSCRIPT_VARIABLE: &str = "transcript";
+pub const TEXT_VARIABLE: &str = "text";
+
+/// Filled in with the name of the output language, e.g. "Korean"
+pub const LANGUAGE_VARIABLE: &str = "language";
+
+/// Chapter templates also get `{{segments}}`: one `[index] (start): text` line
+/// per segment, the indices the reply must refer to
+pub const SEGMENTS_VARIABLE: &str = "segments";
+
+/// A piece of a parsed template
+#[derive(Debug, PartialEq)]
+enum Part<'a> {
+    Text(&'a str),
+    Variable(&'a str),
+}
+
+/// Split a template into text and `{{variable}}` placeholders. Variable names are
+/// letters, digits and underscores; whitespace inside the braces is ignored.
+fn parse(template: &str) -> Result<Vec<Part<'_>>> {
+    let mut parts = Vec::new();
+    let mut rest = template;
+    while let Some(open) = rest.find("{{") {
+        if open > 0 {
+            parts.push(Part::Text(&rest[..open]));
+        }
+        let after = &rest[open + 2..];
+        let close = after.find("}}").ok_or_else(|| {
+            AppError::InvalidInput("Unclosed \"{{\" in prompt template".to_string())
+        })?;
+        let name = after[..close].trim();
+        if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+            return Err(AppError::InvalidInput(format!(
+                "Invalid variable name in prompt template: \"{{{{{}}}}}\"",
+                &after[..close]
+            )));
+        }
+        parts.push(Part::Variable(name));
+        rest = &after[close + 2..];
+    }
+    if !rest.is_empty() {
+        parts.push(Part::Text(rest));
+    }
+    Ok(parts)
+}
+
+/// Names of the variables a template uses, in order of first use
+pub fn template_variables(template: &str) -> Result<Vec<String>> {
+    let mut names: Vec<String> = Vec::new();
+    for part in parse(template)? {
+        if let Part::Variable(name) = part {
+            if !names.iter().any(|n| n == name) {
+                names.push(name.to_string());
+            }
+        }
+    }
+    Ok(names)
+}
+
+/// Fill in a template's variables. Every variable it uses must be given.
+pub fn render(template: &str, variables: &HashMap<String, String>) -> Result<String> {
+    let parts = parse(template)?;
+    let missing: Vec<&str> = parts
+        .iter()
+        .filter_map(|part| match part {
+            Part::Variable(name) if !variables.contains_key(*name) => Some(*name),
+            _ => None,
+        })
+        .collect();
+    if !missing.is_empty() {
+        return Err(AppError::InvalidInput(format!(
+            "Missing prompt template variables: {}",
+            missing.join(", ")
+        )));
+    }
+
+    Ok(parts
+        .into_iter()
+        .map(|part| match part {
+            Part::Text(text) => text,
+            Part::Variable(name) => variables[name].as_str(),
+        })
+        .collect())
+}
+
+/// The transcript and language variables for a transcript
+pub fn transcript_variables(
+    segments: &[TranscriptionSegment],
+    language: Option<&str>,
+) -> HashMap<String, String> {
+    let transcript: Vec<String> = segments
+        .iter()
+        .map(|s| format!("[{:.1}s] {}", s.start, s.text.trim()))
+        .collect();
+    let text: Vec<&str> = segments.iter().map(|s| s.text.trim()).collect();
+
+    HashMap::from([
+        (TRANSCRIPT_VARIABLE.to_string(), transcript.join("\n")),
+        (TEXT_VARIABLE.to_string(), text.join(" ")),
+        (
+            LANGUAGE_VARIABLE.to_string(),
+            language_code_to_name(language.unwrap_or("auto")),
+        ),
+    ])
+}
+
+/// Send a rendered template prompt to an LLM
+pub async fn run_prompt(
+    llm: &dyn LlmProvider,
+    system: Option<&str>,
+    prompt: &str,
+    cancel: &CancellationToken,
+) -> Result<String> {
+    log::info!("[prompt_templates] Running template with {} ({})", llm.id(), llm.model());
+    let system = system.filter(|s| !s.trim().is_empty()).unwrap_or(DEFAULT_SYSTEM);
+    let response = llm.complete(system, prompt, TEMPLATE_MAX_TOKENS, cancel).await?;
+    Ok(response.trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let template = "In {{ language }} as {{style}}:\n{{transcript}}\nStyle: {{style}}";
+        assert_eq!(
+            template_variables(template).unwrap(),
+            ["language", "style", "transcript"]
+        );
+
+        let variables = HashMap::from([
+            ("language".to_string(), "German".to_string()),
+            ("style".to_string(), "bullets".to_string()),
+            ("transcript".to_string(), "[0.0s] Hi".to_string()),
+        ]);
+        assert_eq!(
+            render(template, &variables).unwrap(),
+            "In German as bullets:\n[0.0s] Hi\nStyle: bullets"
+        );
+
+        // Single braces, e.g. JSON examples, are kept as they are
+        let json = render("Reply as {\"title\": \"...\"}", &HashMap::new()).unwrap();
+        assert_eq!(json, "Reply as {\"title\": \"...\"}");
+    }
+
+    #[test]
+    fn test_render_errors() {
+        let err = render("{{a}} {{b}}", &HashMap::new()).unwrap_err();
+        assert!(err.to_string().contains("a, b"));
+        assert!(template_variables("Hello {{name").is_err());
+        assert!(template_variables("Hello {{first name}}").is_err());
+    }
+}
@@ -0,0 +1,109 @@
+use crate::services::credential_profiles::DEFAULT_PROFILE;
+use serde::Serialize;
+
+pub const OPENAI: &str = "openai";
+pub const CLAUDE: &str = "claude";
+pub const GEMINI: &str = "gemini";
+pub const GROQ: &str = "groq";
+pub const DEEPGRAM: &str = "deepgram";
+pub const ASSEMBLYAI: &str = "assemblyai";
+pub const OPENAI_COMPATIBLE: &str = "openai_compatible";
+
+/// A provider whose credentials can be kept in secure storage
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct SecretProvider {
+    /// Identifier used by the frontend and in keychain account names
+    pub id: &'static str,
+    /// Human-readable name for the UI and error messages
+    pub display_name: &'static str,
+    /// Environment variable that may hold the key (see `env_keys`)
+    pub env_var: &'static str,
+}
+
+/// Known providers. Adding an entry here is all a new integration needs
+/// for its key to be stored, masked, deleted and reported by the key commands.
+pub const PROVIDERS: &[SecretProvider] = &[
+    SecretProvider {
+        id: OPENAI,
+        display_name: "OpenAI",
+        env_var: "OPENAI_API_KEY",
+    },
+    SecretProvider {
+        id: CLAUDE,
+        display_name: "Claude",
+        env_var: "ANTHROPIC_API_KEY",
+    },
+    SecretProvider {
+        id: GEMINI,
+        display_name: "Gemini",
+        env_var: "GEMINI_API_KEY",
+    },
+    SecretProvider {
+        id: GROQ,
+        display_name: "Groq",
+        env_var: "GROQ_API_KEY",
+    },
+    SecretProvider {
+        id: DEEPGRAM,
+        display_name: "Deepgram",
+        env_var: "DEEPGRAM_API_KEY",
+    },
+    SecretProvider {
+        id: ASSEMBLYAI,
+        display_name: "AssemblyAI",
+        env_var: "ASSEMBLYAI_API_KEY",
+    },
+    SecretProvider {
+        id: OPENAI_COMPATIBLE,
+        display_name: "OpenAI-compatible server",
+        env_var: "OPENAI_COMPATIBLE_API_KEY",
+    },
+];
+
+/// Look up a provider by id (case-insensitive)
+pub fn find(id: &str) -> Option<&'static SecretProvider> {
+    PROVIDERS.iter().find(|p| p.id.eq_ignore_ascii_case(id))
+}
+
+impl SecretProvider {
+    /// Keychain account name for a credential profile.
+    /// The default profile keeps the original `<id>_api_key` account so existing keys still resolve.
+    pub fn account(&self, profile: &str) -> String {
+        if profile == DEFAULT_PROFILE {
+            format!("{}_api_key", self.id)
+        } else {
+            format!("{}_api_key:{}", self.id, profile)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_is_case_insensitive() {
+        assert_eq!(find("openai").unwrap().id, OPENAI);
+        assert_eq!(find("Claude").unwrap().id, CLAUDE);
+        assert_eq!(find("GEMINI").unwrap().id, GEMINI);
+        assert_eq!(find("AssemblyAI").unwrap().display_name, "AssemblyAI");
+        assert!(find("unknown").is_none());
+    }
+
+    #[test]
+    fn test_default_profile_keeps_legacy_account() {
+        let openai = find(OPENAI).unwrap();
+        assert_eq!(openai.account(DEFAULT_PROFILE), "openai_api_key");
+        assert_eq!(openai.account("work"), "openai_api_key:work");
+        assert_eq!(
+            find(CLAUDE).unwrap().account(DEFAULT_PROFILE),
+            "claude_api_key"
+        );
+    }
+
+    #[test]
+    fn test_provider_ids_are_unique() {
+        let ids: std::collections::HashSet<_> = PROVIDERS.iter().map(|p| p.id).collect();
+        assert_eq!(ids.len(), PROVIDERS.len());
+    }
+}
@@ -0,0 +1,203 @@
+use crate::error::{AppError, Result};
+use crate::services::llm::{parse_json_response, LlmProvider};
+use crate::services::ollama::language_code_to_name;
+use crate::services::whisper::TranscriptionSegment;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use tokio_util::sync::CancellationToken;
+
+const METADATA_SYSTEM: &str = "You are a social media editor who prepares recordings for \
+                               publishing. Respond with the JSON object only, without any \
+                               explanation.";
+
+/// Reply budget: a few short lists
+const METADATA_MAX_TOKENS: u32 = 1500;
+
+/// Transcript characters sent along with the summary; the start of a long
+/// recording says enough about it
+const MAX_TRANSCRIPT_CHARS: usize = 24_000;
+
+const MAX_HASHTAGS: usize = 15;
+const MAX_CATEGORIES: usize = 3;
+const MAX_SEO_KEYWORDS: usize = 20;
+
+/// YouTube's video categories, which most platforms' categories map onto
+pub const CATEGORIES: &[&str] = &[
+    "Autos & Vehicles",
+    "Comedy",
+    "Education",
+    "Entertainment",
+    "Film & Animation",
+    "Gaming",
+    "Howto & Style",
+    "Music",
+    "News & Politics",
+    "Nonprofits & Activism",
+    "People & Blogs",
+    "Pets & Animals",
+    "Science & Technology",
+    "Sports",
+    "Travel & Events",
+];
+
+/// Metadata suggested for publishing a recording
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PublishMetadata {
+    /// Hashtags with their `#`, most relevant first
+    pub hashtags: Vec<String>,
+    /// Best fitting entries of [`CATEGORIES`], best first
+    pub categories: Vec<String>,
+    /// Search terms for titles, descriptions and tags, most relevant first
+    pub seo_keywords: Vec<String>,
+}
+
+/// Metadata as returned by the LLM
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct RawMetadata {
+    hashtags: Vec<String>,
+    categories: Vec<String>,
+    seo_keywords: Vec<String>,
+}
+
+/// Suggest hashtags, categories and SEO keywords from a transcript and, when
+/// there is one, its summary
+pub async fn suggest_metadata(
+    llm: &dyn LlmProvider,
+    segments: &[TranscriptionSegment],
+    summary: Option<&str>,
+    language: &str,
+    cancel: &CancellationToken,
+) -> Result<PublishMetadata> {
+    let summary = summary.map(str::trim).filter(|s| !s.is_empty());
+    if segments.is_empty() && summary.is_none() {
+        return Err(AppError::InvalidInput(
+            "Nothing to suggest metadata for: no transcript or summary".to_string(),
+        ));
+    }
+    log::info!("[publish_metadata] Suggesting metadata with {} ({})", llm.id(), llm.model());
+
+    let prompt = build_metadata_prompt(segments, summary, language);
+    let response = llm
+        .complete(METADATA_SYSTEM, &prompt, METADATA_MAX_TOKENS, cancel)
+        .await?;
+    parse_metadata_response(&response)
+}
+
+fn build_metadata_prompt(
+    segments: &[TranscriptionSegment],
+    summary: Option<&str>,
+    language: &str,
+) -> String {
+    let mut transcript = String::new();
+    for segment in segments {
+        let text = segment.text.trim();
+        if transcript.len() + text.len() > MAX_TRANSCRIPT_CHARS {
+            break;
+        }
+        if !transcript.is_empty() {
+            transcript.push(' ');
+        }
+        transcript.push_str(text);
+    }
+
+    let summary = summary
+        .map(|summary| format!("Summary:\n{}\n\n", summary))
+        .unwrap_or_default();
+
+    format!(
+        "Suggest metadata for publishing this recording on YouTube, TikTok and Instagram. \
+         Write hashtags and keywords in {}.\n\n\
+         Rules:\n\
+         - Up to {} hashtags, most relevant first, mixing specific and broader ones\n\
+         - Up to {} categories, chosen only from: {}\n\
+         - Up to {} SEO keywords and phrases people would search for\n\n\
+         {}Transcript:\n{}\n\n\
+         Response format: {{\"hashtags\": [\"#colorgrading\", ...], \
+         \"categories\": [\"Film & Animation\"], \"seo_keywords\": [\"color grading tutorial\", ...]}}",
+        language_code_to_name(language),
+        MAX_HASHTAGS,
+        MAX_CATEGORIES,
+        CATEGORIES.join(", "),
+        MAX_SEO_KEYWORDS,
+        summary,
+        transcript
+    )
+}
+
+/// Parse the LLM's metadata: normalize hashtags, keep known categories only,
+/// and drop duplicates and empty entries
+fn parse_metadata_response(response: &str) -> Result<PublishMetadata> {
+    let raw: RawMetadata = parse_json_response(response, "metadata")?;
+
+    let hashtags = dedup(raw.hashtags.iter().filter_map(|tag| hashtag(tag)), MAX_HASHTAGS);
+    let categories = dedup(
+        raw.categories.iter().filter_map(|category| {
+            CATEGORIES
+                .iter()
+                .find(|known| known.eq_ignore_ascii_case(category.trim()))
+                .map(|known| known.to_string())
+        }),
+        MAX_CATEGORIES,
+    );
+    let seo_keywords = dedup(
+        raw.seo_keywords
+            .iter()
+            .map(|keyword| keyword.split_whitespace().collect::<Vec<_>>().join(" "))
+            .filter(|keyword| !keyword.is_empty()),
+        MAX_SEO_KEYWORDS,
+    );
+
+    Ok(PublishMetadata {
+        hashtags,
+        categories,
+        seo_keywords,
+    })
+}
+
+/// A tag as a hashtag: `#` followed by its letters, digits and underscores,
+/// since platforms end a hashtag at a space or punctuation
+fn hashtag(tag: &str) -> Option<String> {
+    let body: String = tag
+        .chars()
+        .filter(|c| c.is_alphanumeric() || *c == '_')
+        .collect();
+    (!body.is_empty()).then(|| format!("#{}", body))
+}
+
+/// The first `max` items, without case-insensitive duplicates
+fn dedup(items: impl Iterator<Item = String>, max: usize) -> Vec<String> {
+    let mut seen = HashSet::new();
+    items
+        .filter(|item| seen.insert(item.to_lowercase()))
+        .take(max)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_metadata_response() {
+        let response = r##"Here you go:
+{
+  "hashtags": ["#ColorGrading", "color grading", "#colorgrading", "#DaVinci-Resolve", "#"],
+  "categories": ["film & animation", "Cooking", "Education"],
+  "seo_keywords": ["color  grading tutorial", " ", "Color grading tutorial", "LUTs"]
+}"##;
+
+        let metadata = parse_metadata_response(response).unwrap();
+        assert_eq!(metadata.hashtags, ["#ColorGrading", "#DaVinciResolve"]);
+        assert_eq!(metadata.categories, ["Film & Animation", "Education"]);
+        assert_eq!(metadata.seo_keywords, ["color grading tutorial", "LUTs"]);
+    }
+
+    #[test]
+    fn test_missing_lists_are_empty() {
+        let metadata = parse_metadata_response(r##"{"hashtags": ["#한국어"]}"##).unwrap();
+        assert_eq!(metadata.hashtags, ["#한국어"]);
+        assert!(metadata.categories.is_empty());
+        assert!(metadata.seo_keywords.is_empty());
+    }
+}
@@ -0,0 +1,144 @@
+use crate::services::app_settings::AppSettings;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// Limits are enforced over a sliding window of this length
+const WINDOW: Duration = Duration::from_secs(60);
+
+/// Rough size of a token in characters, for estimating request sizes
+const CHARS_PER_TOKEN: usize = 4;
+
+/// Request limits for one provider, e.g. the account's published limits.
+/// Limits left unset aren't enforced.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RateLimit {
+    pub requests_per_minute: Option<u32>,
+    /// Input plus requested output tokens, as providers count them
+    pub tokens_per_minute: Option<u32>,
+}
+
+/// Requests sent within the last window, per provider: when, and their estimated tokens
+#[derive(Default)]
+struct RateLimiter {
+    windows: Mutex<HashMap<String, VecDeque<(Instant, u64)>>>,
+}
+
+impl RateLimiter {
+    /// Reserve room for a request at `now`, or return how long to wait before
+    /// trying again
+    fn try_reserve(
+        &self,
+        provider: &str,
+        limit: RateLimit,
+        tokens: u64,
+        now: Instant,
+    ) -> Result<(), Duration> {
+        let mut windows = self.windows.lock().unwrap_or_else(|e| e.into_inner());
+        let window = windows.entry(provider.to_string()).or_default();
+        while window
+            .front()
+            .is_some_and(|(sent, _)| now.duration_since(*sent) >= WINDOW)
+        {
+            window.pop_front();
+        }
+
+        let requests_full = limit
+            .requests_per_minute
+            .is_some_and(|max| window.len() >= max.max(1) as usize);
+        let used: u64 = window.iter().map(|(_, tokens)| tokens).sum();
+        // A request larger than the whole budget still goes out once the window is empty
+        let tokens_full = limit
+            .tokens_per_minute
+            .is_some_and(|max| !window.is_empty() && used + tokens > u64::from(max));
+
+        match window.front() {
+            // Try again when the oldest request leaves the window
+            Some((oldest, _)) if requests_full || tokens_full => {
+                Err(WINDOW.saturating_sub(now.duration_since(*oldest)))
+            }
+            _ => {
+                window.push_back((now, tokens));
+                Ok(())
+            }
+        }
+    }
+}
+
+fn limiter() -> &'static RateLimiter {
+    static LIMITER: OnceLock<RateLimiter> = OnceLock::new();
+    LIMITER.get_or_init(RateLimiter::default)
+}
+
+/// Tokens a request counts against a tokens-per-minute limit: its text plus
+/// the output it may produce
+pub fn estimate_tokens(prompt_chars: usize, max_output_tokens: Option<u32>) -> u64 {
+    (prompt_chars / CHARS_PER_TOKEN) as u64 + u64::from(max_output_tokens.unwrap_or_default())
+}
+
+/// Wait until a request to `provider` fits within its configured rate limits.
+/// Requests of all commands and jobs share the limits, so batches queue here
+/// instead of running into the provider's 429 responses.
+pub async fn acquire(provider: &str, tokens: u64) {
+    let limit = AppSettings::load()
+        .ok()
+        .and_then(|settings| settings.rate_limits.get(provider).copied());
+    let Some(limit) = limit else {
+        return;
+    };
+
+    while let Err(wait) = limiter().try_reserve(provider, limit, tokens, Instant::now()) {
+        log::debug!("[rate_limit] Waiting {:.1}s for {}", wait.as_secs_f32(), provider);
+        tokio::time::sleep(wait).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_requests_per_minute() {
+        let limiter = RateLimiter::default();
+        let limit = RateLimit {
+            requests_per_minute: Some(2),
+            tokens_per_minute: None,
+        };
+        let start = Instant::now();
+        assert!(limiter.try_reserve("openai", limit, 0, start).is_ok());
+        let later = start + Duration::from_secs(10);
+        assert!(limiter.try_reserve("openai", limit, 0, later).is_ok());
+        assert_eq!(
+            limiter.try_reserve("openai", limit, 0, later),
+            Err(Duration::from_secs(50))
+        );
+        // Other providers have their own window
+        assert!(limiter.try_reserve("claude", limit, 0, later).is_ok());
+        // The first request has left the window
+        assert!(limiter.try_reserve("openai", limit, 0, start + WINDOW).is_ok());
+    }
+
+    #[test]
+    fn test_tokens_per_minute() {
+        let limiter = RateLimiter::default();
+        let limit = RateLimit {
+            requests_per_minute: None,
+            tokens_per_minute: Some(1000),
+        };
+        let start = Instant::now();
+        // Larger than the budget, but the window is empty
+        assert!(limiter.try_reserve("groq", limit, 1500, start).is_ok());
+        assert!(limiter.try_reserve("groq", limit, 10, start).is_err());
+        assert!(limiter.try_reserve("groq", limit, 10, start + WINDOW).is_ok());
+        assert!(limiter.try_reserve("groq", limit, 900, start + WINDOW).is_ok());
+        assert!(limiter.try_reserve("groq", limit, 100, start + WINDOW).is_err());
+    }
+
+    #[test]
+    fn test_estimate_tokens() {
+        assert_eq!(estimate_tokens(4000, Some(1000)), 2000);
+        assert_eq!(estimate_tokens(3, None), 0);
+    }
+}
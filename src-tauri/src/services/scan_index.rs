@@ -0,0 +1,269 @@
+use crate::error::{AppError, Result};
+use crate::services::directory_service::FileEntry;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// What the index remembers about a scanned file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IndexedFile {
+    pub size: u64,
+    pub modified: Option<u64>,
+}
+
+/// What the index remembers about a scanned directory, so a rescan can skip
+/// reading it while its modification time stays the same
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IndexedDir {
+    /// Modification time in nanoseconds since the Unix epoch
+    pub modified: u64,
+    /// Names of the subdirectories
+    pub subdirs: Vec<String>,
+    /// Names of the media files
+    pub files: Vec<String>,
+}
+
+/// Difference between the last indexed scan of a directory and its current contents
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RescanResult {
+    pub added: Vec<FileEntry>,
+    pub changed: Vec<FileEntry>,
+    pub removed: Vec<String>,
+    /// Number of media files now in the directory
+    pub total: usize,
+}
+
+/// Persistent index of scanned media files (path, size, mtime) per root directory,
+/// so rescans can report only what changed since the previous scan
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ScanIndex {
+    #[serde(default)]
+    roots: HashMap<String, HashMap<String, IndexedFile>>,
+    /// Directories read by the last scan of each root
+    #[serde(default)]
+    dirs: HashMap<String, HashMap<String, IndexedDir>>,
+}
+
+impl ScanIndex {
+    /// Get the default index file path
+    pub fn default_path() -> Result<PathBuf> {
+        let cache_dir = dirs::cache_dir()
+            .ok_or_else(|| AppError::InvalidPath("Cannot find cache directory".to_string()))?;
+        Ok(cache_dir.join("clip-flow").join("scan_index.json"))
+    }
+
+    /// Load the index from the default location
+    pub fn load() -> Result<Self> {
+        Self::load_from(&Self::default_path()?)
+    }
+
+    /// Load the index from a file (empty if missing or unreadable)
+    pub fn load_from(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(path)?;
+        // The index is only a cache; a corrupted file just means a full rescan
+        Ok(serde_json::from_str(&content).unwrap_or_default())
+    }
+
+    /// Save the index to the default location
+    pub fn save(&self) -> Result<()> {
+        self.save_to(&Self::default_path()?)
+    }
+
+    /// Save the index to a file
+    pub fn save_to(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_vec(self)?)?;
+        Ok(())
+    }
+
+    /// Indexed directories of a root
+    pub fn dirs(&self, root: &str) -> HashMap<String, IndexedDir> {
+        self.dirs.get(root).cloned().unwrap_or_default()
+    }
+
+    /// Replace the indexed directories of a root with those of a fresh scan
+    pub fn set_dirs(&mut self, root: &str, dirs: HashMap<String, IndexedDir>) {
+        self.dirs.insert(root.to_string(), dirs);
+    }
+
+    /// Replace the indexed entries of a root with a fresh scan. Its indexed
+    /// directories are forgotten until [`ScanIndex::set_dirs`] records them again.
+    pub fn update(&mut self, root: &str, files: &[FileEntry]) {
+        self.dirs.remove(root);
+        let entries = files
+            .iter()
+            .map(|f| {
+                (
+                    f.path.clone(),
+                    IndexedFile {
+                        size: f.size,
+                        modified: f.modified,
+                    },
+                )
+            })
+            .collect();
+        self.roots.insert(root.to_string(), entries);
+    }
+
+    /// Compare a fresh scan against the indexed entries, then update the index
+    pub fn diff_and_update(&mut self, root: &str, files: &[FileEntry]) -> RescanResult {
+        let mut previous = self.roots.remove(root).unwrap_or_default();
+        let mut result = RescanResult {
+            total: files.len(),
+            ..Default::default()
+        };
+
+        for file in files {
+            match previous.remove(&file.path) {
+                None => result.added.push(file.clone()),
+                Some(indexed) if indexed.size != file.size || indexed.modified != file.modified => {
+                    result.changed.push(file.clone())
+                }
+                Some(_) => {}
+            }
+        }
+
+        // Anything left was not seen in this scan
+        result.removed = previous.into_keys().collect();
+        result.removed.sort();
+
+        self.update(root, files);
+        result
+    }
+
+    /// Carry the entry of a moved or renamed file over to its new path,
+    /// under every indexed root that contains the new path
+    pub fn rename_path(&mut self, from: &str, to: &str) {
+        let mut moved = None;
+        for entries in self.roots.values_mut() {
+            moved = entries.remove(from).or(moved);
+        }
+
+        let Some(indexed) = moved else {
+            return;
+        };
+        for (root, entries) in &mut self.roots {
+            if Path::new(to).starts_with(root) {
+                entries.insert(to.to_string(), indexed.clone());
+            }
+        }
+    }
+
+    /// Forget a deleted file
+    pub fn remove_path(&mut self, path: &str) {
+        for entries in self.roots.values_mut() {
+            entries.remove(path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn entry(path: &str, size: u64, modified: u64) -> FileEntry {
+        FileEntry {
+            path: path.to_string(),
+            name: path.rsplit('/').next().unwrap_or(path).to_string(),
+            size,
+            is_dir: false,
+            modified: Some(modified),
+            created: None,
+            extension: Some("mp4".to_string()),
+            duration: None,
+            has_audio: None,
+        }
+    }
+
+    #[test]
+    fn test_first_rescan_reports_everything_added() {
+        let mut index = ScanIndex::default();
+        let files = vec![entry("/m/a.mp4", 10, 1), entry("/m/b.mp4", 20, 1)];
+
+        let result = index.diff_and_update("/m", &files);
+        assert_eq!(result.added.len(), 2);
+        assert!(result.changed.is_empty());
+        assert!(result.removed.is_empty());
+        assert_eq!(result.total, 2);
+    }
+
+    #[test]
+    fn test_rescan_reports_only_differences() {
+        let mut index = ScanIndex::default();
+        index.update(
+            "/m",
+            &[
+                entry("/m/same.mp4", 10, 1),
+                entry("/m/grown.mp4", 10, 1),
+                entry("/m/gone.mp4", 10, 1),
+            ],
+        );
+
+        let files = vec![
+            entry("/m/same.mp4", 10, 1),
+            entry("/m/grown.mp4", 99, 2),
+            entry("/m/new.mp4", 5, 3),
+        ];
+        let result = index.diff_and_update("/m", &files);
+
+        assert_eq!(result.added.len(), 1);
+        assert_eq!(result.added[0].path, "/m/new.mp4");
+        assert_eq!(result.changed.len(), 1);
+        assert_eq!(result.changed[0].path, "/m/grown.mp4");
+        assert_eq!(result.removed, vec!["/m/gone.mp4".to_string()]);
+
+        // The index now matches the latest scan
+        let again = index.diff_and_update("/m", &files);
+        assert!(again.added.is_empty() && again.changed.is_empty() && again.removed.is_empty());
+    }
+
+    #[test]
+    fn test_index_persists_per_root() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("scan_index.json");
+
+        let mut index = ScanIndex::default();
+        index.update("/a", &[entry("/a/x.mp4", 1, 1)]);
+        index.update("/b", &[entry("/b/y.mp4", 1, 1)]);
+        index.save_to(&path).unwrap();
+
+        let mut loaded = ScanIndex::load_from(&path).unwrap();
+        let result = loaded.diff_and_update("/a", &[entry("/a/x.mp4", 1, 1)]);
+        assert!(result.added.is_empty());
+        assert!(result.removed.is_empty());
+    }
+
+    #[test]
+    fn test_rename_and_remove_path() {
+        let mut index = ScanIndex::default();
+        index.update("/m", &[entry("/m/a.mp4", 1, 1), entry("/m/b.mp4", 2, 1)]);
+        index.update("/other", &[]);
+
+        index.rename_path("/m/a.mp4", "/m/sorted/a.mp4");
+        index.remove_path("/m/b.mp4");
+        // Moving out of every indexed root just drops the entry
+        index.rename_path("/m/missing.mp4", "/elsewhere/x.mp4");
+
+        let result = index.diff_and_update("/m", &[entry("/m/sorted/a.mp4", 1, 1)]);
+        assert!(result.added.is_empty());
+        assert!(result.changed.is_empty());
+        assert!(result.removed.is_empty());
+    }
+
+    #[test]
+    fn test_corrupted_index_is_treated_as_empty() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("scan_index.json");
+        std::fs::write(&path, "not json").unwrap();
+
+        let mut index = ScanIndex::load_from(&path).unwrap();
+        let result = index.diff_and_update("/m", &[entry("/m/a.mp4", 1, 1)]);
+        assert_eq!(result.added.len(), 1);
+    }
+}
@@ -0,0 +1,282 @@
+use crate::error::{AppError, Result};
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use argon2::Argon2;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+const FILE_VERSION: u32 = 1;
+const SALT_LEN: usize = 16;
+
+/// Passphrase for the encrypted secrets file, held only for the current app session
+static SESSION_PASSPHRASE: Mutex<Option<String>> = Mutex::new(None);
+
+/// On-disk layout of the encrypted secrets file
+#[derive(Debug, Serialize, Deserialize)]
+struct EncryptedFile {
+    version: u32,
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+/// AES-256-GCM encrypted secrets file, used when no OS keychain is available
+/// (headless Linux without Secret Service, portable installs).
+/// The key is derived from a user passphrase with Argon2id.
+pub struct EncryptedFileStore {
+    path: PathBuf,
+}
+
+impl EncryptedFileStore {
+    /// Open the store at the default location in the app data directory
+    pub fn new() -> Result<Self> {
+        let data_dir = dirs::data_local_dir()
+            .ok_or_else(|| AppError::InvalidPath("Cannot find data directory".to_string()))?;
+
+        Ok(Self::with_path(data_dir.join("clip-flow").join("secrets.enc")))
+    }
+
+    /// Open the store at a specific path
+    pub fn with_path(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// Check if the secrets file has been created
+    pub fn exists(&self) -> bool {
+        self.path.exists()
+    }
+
+    /// Decrypt and return all stored secrets (empty if the file does not exist yet)
+    pub fn load(&self, passphrase: &str) -> Result<HashMap<String, String>> {
+        if !self.exists() {
+            return Ok(HashMap::new());
+        }
+
+        let content = std::fs::read(&self.path)?;
+        let plaintext = open(passphrase, &content)?;
+        Ok(serde_json::from_slice(&plaintext)?)
+    }
+
+    /// Encrypt and write all secrets, replacing the file atomically
+    pub fn save(&self, passphrase: &str, secrets: &HashMap<String, String>) -> Result<()> {
+        let sealed = seal(passphrase, &serde_json::to_vec(secrets)?)?;
+
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let temp_path = self.path.with_extension("enc.tmp");
+        std::fs::write(&temp_path, sealed)?;
+        restrict_permissions(&temp_path)?;
+        std::fs::rename(&temp_path, &self.path)?;
+
+        Ok(())
+    }
+
+    // ------------------------------------------------------------------------
+    // Session helpers (default store, unlocked passphrase)
+    // ------------------------------------------------------------------------
+
+    /// Unlock the default store for this session.
+    /// Creates an empty store if none exists; otherwise verifies the passphrase.
+    pub fn unlock(passphrase: &str) -> Result<()> {
+        if passphrase.is_empty() {
+            return Err(AppError::Keychain("Passphrase must not be empty".to_string()));
+        }
+
+        let store = Self::new()?;
+        if store.exists() {
+            store.load(passphrase)?;
+        } else {
+            store.save(passphrase, &HashMap::new())?;
+        }
+
+        *lock_session() = Some(passphrase.to_string());
+        Ok(())
+    }
+
+    /// Forget the session passphrase
+    pub fn lock() {
+        *lock_session() = None;
+    }
+
+    /// Check if the default store is unlocked for this session
+    pub fn is_unlocked() -> bool {
+        lock_session().is_some()
+    }
+
+    /// Read a secret from the default store
+    pub fn get(account: &str) -> Result<Option<String>> {
+        let passphrase = session_passphrase()?;
+        Ok(Self::new()?.load(&passphrase)?.remove(account))
+    }
+
+    /// Write a secret to the default store
+    pub fn set(account: &str, value: &str) -> Result<()> {
+        let passphrase = session_passphrase()?;
+        let store = Self::new()?;
+        let mut secrets = store.load(&passphrase)?;
+        secrets.insert(account.to_string(), value.to_string());
+        store.save(&passphrase, &secrets)
+    }
+
+    /// Remove a secret from the default store
+    pub fn remove(account: &str) -> Result<()> {
+        let passphrase = session_passphrase()?;
+        let store = Self::new()?;
+        let mut secrets = store.load(&passphrase)?;
+        if secrets.remove(account).is_some() {
+            store.save(&passphrase, &secrets)?;
+        }
+        Ok(())
+    }
+}
+
+fn lock_session() -> std::sync::MutexGuard<'static, Option<String>> {
+    // The guarded value is a plain Option, so a poisoned lock is still usable
+    SESSION_PASSPHRASE.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn session_passphrase() -> Result<String> {
+    lock_session().clone().ok_or_else(|| {
+        AppError::Keychain(
+            "System keychain is unavailable and the encrypted secrets file is locked".to_string(),
+        )
+    })
+}
+
+/// Encrypt data with a passphrase into the versioned JSON envelope
+pub(crate) fn seal(passphrase: &str, plaintext: &[u8]) -> Result<Vec<u8>> {
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+
+    let cipher = Aes256Gcm::new(&derive_key(passphrase, &salt)?);
+    let ciphertext = cipher
+        .encrypt(&nonce, plaintext)
+        .map_err(|_| AppError::Keychain("Failed to encrypt secrets".to_string()))?;
+
+    let file = EncryptedFile {
+        version: FILE_VERSION,
+        salt: BASE64.encode(salt),
+        nonce: BASE64.encode(nonce),
+        ciphertext: BASE64.encode(ciphertext),
+    };
+    Ok(serde_json::to_vec_pretty(&file)?)
+}
+
+/// Decrypt a JSON envelope produced by [`seal`]
+pub(crate) fn open(passphrase: &str, sealed: &[u8]) -> Result<Vec<u8>> {
+    let file: EncryptedFile = serde_json::from_slice(sealed)?;
+
+    if file.version != FILE_VERSION {
+        return Err(AppError::Keychain(format!(
+            "Unsupported secrets file version: {}",
+            file.version
+        )));
+    }
+
+    let salt = decode(&file.salt)?;
+    let nonce = decode(&file.nonce)?;
+    let ciphertext = decode(&file.ciphertext)?;
+
+    if nonce.len() != 12 {
+        return Err(AppError::Keychain("Corrupted secrets file".to_string()));
+    }
+
+    let cipher = Aes256Gcm::new(&derive_key(passphrase, &salt)?);
+    cipher
+        .decrypt(Nonce::from_slice(&nonce), ciphertext.as_ref())
+        .map_err(|_| AppError::Keychain("Wrong passphrase or corrupted secrets file".to_string()))
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<Key<Aes256Gcm>> {
+    let mut key = [0u8; 32];
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| AppError::Keychain(format!("Failed to derive key: {}", e)))?;
+    Ok(key.into())
+}
+
+fn decode(value: &str) -> Result<Vec<u8>> {
+    BASE64
+        .decode(value)
+        .map_err(|_| AppError::Keychain("Corrupted secrets file".to_string()))
+}
+
+/// Restrict a file to the current user
+#[cfg(unix)]
+pub(crate) fn restrict_permissions(path: &Path) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub(crate) fn restrict_permissions(_path: &Path) -> Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn sample_secrets() -> HashMap<String, String> {
+        let mut secrets = HashMap::new();
+        secrets.insert("openai_api_key".to_string(), "sk-test-123".to_string());
+        secrets.insert("claude_api_key".to_string(), "sk-ant-456".to_string());
+        secrets
+    }
+
+    #[test]
+    fn test_load_missing_file_is_empty() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = EncryptedFileStore::with_path(temp_dir.path().join("secrets.enc"));
+
+        assert!(!store.exists());
+        assert!(store.load("passphrase").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_save_and_load_roundtrip() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = EncryptedFileStore::with_path(temp_dir.path().join("secrets.enc"));
+
+        store.save("correct horse", &sample_secrets()).unwrap();
+        assert!(store.exists());
+
+        let loaded = store.load("correct horse").unwrap();
+        assert_eq!(loaded, sample_secrets());
+    }
+
+    #[test]
+    fn test_file_does_not_contain_plaintext() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("secrets.enc");
+        let store = EncryptedFileStore::with_path(path.clone());
+
+        store.save("correct horse", &sample_secrets()).unwrap();
+
+        let raw = std::fs::read_to_string(&path).unwrap();
+        assert!(!raw.contains("sk-test-123"));
+        assert!(!raw.contains("openai_api_key"));
+    }
+
+    #[test]
+    fn test_wrong_passphrase_fails() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = EncryptedFileStore::with_path(temp_dir.path().join("secrets.enc"));
+
+        store.save("correct horse", &sample_secrets()).unwrap();
+
+        let result = store.load("battery staple");
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("Wrong passphrase"));
+    }
+}
@@ -0,0 +1,166 @@
+use crate::error::{AppError, Result};
+use crate::services::whisper::TranscriptionSegment;
+
+/// Split a segment at `at` seconds. Without replacement texts, the words are
+/// divided in proportion to the time on each side. Both halves keep the
+/// segment's speaker and confidence; word timings go to the half they start in.
+pub fn split_segment(
+    segment: &TranscriptionSegment,
+    at: f64,
+    first_text: Option<String>,
+    second_text: Option<String>,
+) -> Result<(TranscriptionSegment, TranscriptionSegment)> {
+    if at <= segment.start || at >= segment.end {
+        return Err(AppError::InvalidInput(format!(
+            "Split point {:.3}s is not inside the segment ({:.3}s - {:.3}s)",
+            at, segment.start, segment.end
+        )));
+    }
+
+    let words: Vec<&str> = segment.text.split_whitespace().collect();
+    let share = (at - segment.start) / (segment.end - segment.start);
+    let split = ((words.len() as f64 * share).round() as usize).min(words.len());
+    let first_text = first_text.unwrap_or_else(|| words[..split].join(" "));
+    let second_text = second_text.unwrap_or_else(|| words[split..].join(" "));
+
+    let (first_words, second_words): (Vec<_>, Vec<_>) = segment
+        .words
+        .iter()
+        .cloned()
+        .partition(|word| word.start < at);
+    let first = TranscriptionSegment {
+        end: at,
+        text: first_text.trim().to_string(),
+        words: first_words,
+        ..segment.clone()
+    };
+    let second = TranscriptionSegment {
+        start: at,
+        text: second_text.trim().to_string(),
+        words: second_words,
+        ..segment.clone()
+    };
+    Ok((first, second))
+}
+
+/// Merge adjacent segments into one spanning all of them. Segments of
+/// different speakers are not merged.
+pub fn merge_segments(segments: &[TranscriptionSegment]) -> Result<TranscriptionSegment> {
+    let (Some(first), Some(last)) = (segments.first(), segments.last()) else {
+        return Err(AppError::InvalidInput("No segments to merge".to_string()));
+    };
+    if segments.len() < 2 {
+        return Err(AppError::InvalidInput(
+            "Merging needs at least two segments".to_string(),
+        ));
+    }
+    if segments
+        .iter()
+        .any(|segment| segment.speaker != first.speaker)
+    {
+        return Err(AppError::InvalidInput(
+            "Segments of different speakers cannot be merged".to_string(),
+        ));
+    }
+
+    let text = segments
+        .iter()
+        .map(|segment| segment.text.trim())
+        .filter(|text| !text.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    // Average of the known confidences, weighted by duration
+    let (weighted, total) = segments
+        .iter()
+        .filter_map(|segment| {
+            let duration = (segment.end - segment.start).max(0.0);
+            segment
+                .confidence
+                .map(|confidence| (confidence * duration, duration))
+        })
+        .fold((0.0, 0.0), |(sum, total), (value, duration)| {
+            (sum + value, total + duration)
+        });
+    let confidence = (total > 0.0).then(|| weighted / total);
+
+    Ok(TranscriptionSegment {
+        start: first.start,
+        end: last.end.max(first.start),
+        text,
+        speaker: first.speaker.clone(),
+        confidence,
+        words: segments
+            .iter()
+            .flat_map(|segment| segment.words.iter().cloned())
+            .collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::whisper::TranscriptionWord;
+
+    #[test]
+    fn test_split_segment() {
+        let original = TranscriptionSegment {
+            confidence: Some(0.9),
+            ..TranscriptionSegment::new(10.0, 14.0, "one two three four").with_speaker("Host")
+        };
+        let (first, second) = split_segment(&original, 11.0, None, None).unwrap();
+        assert_eq!(
+            (first.start, first.end, first.text.as_str()),
+            (10.0, 11.0, "one")
+        );
+        assert_eq!((second.start, second.end), (11.0, 14.0));
+        assert_eq!(second.text, "two three four");
+        assert_eq!(second.speaker.as_deref(), Some("Host"));
+
+        let (first, _) =
+            split_segment(&original, 12.0, Some(" one two ".to_string()), None).unwrap();
+        assert_eq!(first.text, "one two");
+
+        let mut timed = original.clone();
+        timed.words = ["one", "two", "three", "four"]
+            .iter()
+            .enumerate()
+            .map(|(i, text)| TranscriptionWord {
+                start: 10.0 + i as f64,
+                end: 11.0 + i as f64,
+                text: text.to_string(),
+            })
+            .collect();
+        let (first, second) = split_segment(&timed, 12.0, None, None).unwrap();
+        assert_eq!(first.words.len(), 2);
+        assert_eq!(second.words[0].text, "three");
+
+        assert!(split_segment(&original, 10.0, None, None).is_err());
+        assert!(split_segment(&original, 15.0, None, None).is_err());
+    }
+
+    #[test]
+    fn test_merge_segments() {
+        let merged = merge_segments(&[
+            TranscriptionSegment {
+                confidence: Some(0.5),
+                ..TranscriptionSegment::new(0.0, 1.0, "hello")
+            },
+            TranscriptionSegment {
+                confidence: Some(0.9),
+                ..TranscriptionSegment::new(1.0, 4.0, "world")
+            },
+        ])
+        .unwrap();
+        assert_eq!(
+            (merged.start, merged.end, merged.text.as_str()),
+            (0.0, 4.0, "hello world")
+        );
+        assert!((merged.confidence.unwrap() - 0.8).abs() < 1e-9);
+
+        let host = TranscriptionSegment::new(0.0, 1.0, "a").with_speaker("Host");
+        assert!(merge_segments(std::slice::from_ref(&host)).is_err());
+        let guest = TranscriptionSegment::new(1.0, 2.0, "b").with_speaker("Guest");
+        assert!(merge_segments(&[host, guest]).is_err());
+    }
+}
@@ -0,0 +1,191 @@
+use crate::error::Result;
+use crate::services::llm::{parse_json_response, LlmProvider};
+use crate::services::whisper::TranscriptionSegment;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tokio_util::sync::CancellationToken;
+
+const SENTIMENT_SYSTEM: &str = "You are an analyst who rates the tone of each line of a \
+                                conversation. Respond with the JSON array only, without any \
+                                explanation.";
+
+/// Segments rated per request, so long recordings fit the models' output budget
+const SEGMENTS_PER_REQUEST: usize = 60;
+
+/// Reply budget for one batch of segments
+const SENTIMENT_MAX_TOKENS: u32 = 4096;
+
+/// Emotions the model picks from
+const EMOTIONS: [&str; 7] = ["joy", "trust", "surprise", "neutral", "sadness", "anger", "fear"];
+
+/// Sentiment of a transcript segment
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SegmentSentiment {
+    /// Index into the transcript segments
+    pub segment: usize,
+    pub start: f64,
+    pub end: f64,
+    /// `positive`, `neutral` or `negative`
+    pub sentiment: String,
+    /// -1 (very negative) to 1 (very positive), for plotting a timeline
+    pub score: f32,
+    /// Dominant emotion, e.g. `joy` or `anger`
+    pub emotion: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub speaker: Option<String>,
+}
+
+/// A segment rating as returned by the LLM
+#[derive(Debug, Deserialize)]
+struct RawSentiment {
+    segment: usize,
+    #[serde(default)]
+    score: Option<f32>,
+    #[serde(default)]
+    sentiment: Option<String>,
+    #[serde(default)]
+    emotion: Option<String>,
+}
+
+/// Score the sentiment and emotion of every transcript segment. Segments the
+/// model skips are rated neutral.
+pub async fn analyze_sentiment(
+    llm: &dyn LlmProvider,
+    segments: &[TranscriptionSegment],
+    cancel: &CancellationToken,
+) -> Result<Vec<SegmentSentiment>> {
+    log::info!(
+        "[sentiment] Rating {} segments with {} ({})",
+        segments.len(),
+        llm.id(),
+        llm.model()
+    );
+    let mut ratings = HashMap::new();
+    for offset in (0..segments.len()).step_by(SEGMENTS_PER_REQUEST) {
+        let end = (offset + SEGMENTS_PER_REQUEST).min(segments.len());
+        let prompt = build_sentiment_prompt(&segments[offset..end], offset);
+        let response = llm
+            .complete(SENTIMENT_SYSTEM, &prompt, SENTIMENT_MAX_TOKENS, cancel)
+            .await?;
+        let raw: Vec<RawSentiment> = parse_json_response(&response, "sentiment")?;
+        ratings.extend(
+            raw.into_iter()
+                .filter(|r| (offset..end).contains(&r.segment))
+                .map(|r| (r.segment, r)),
+        );
+    }
+    Ok(resolve_sentiments(segments, ratings))
+}
+
+fn build_sentiment_prompt(segments: &[TranscriptionSegment], offset: usize) -> String {
+    let segments_text: Vec<String> = segments
+        .iter()
+        .enumerate()
+        .map(|(i, s)| match &s.speaker {
+            Some(speaker) => format!("[{}] {}: {}", offset + i, speaker, s.text.trim()),
+            None => format!("[{}] {}", offset + i, s.text.trim()),
+        })
+        .collect();
+
+    format!(
+        "Rate the sentiment of every segment of this transcript.\n\n\
+         Rules:\n\
+         - score is between -1 (very negative) and 1 (very positive), 0 is neutral\n\
+         - emotion is one of: {}\n\
+         - Rate each segment in the context of the conversation, one entry per segment\n\n\
+         Segments:\n{}\n\n\
+         Response format: [{{\"segment\": {}, \"score\": 0.6, \"emotion\": \"joy\"}}, ...]",
+        EMOTIONS.join(", "),
+        segments_text.join("\n"),
+        offset
+    )
+}
+
+/// One rating per segment: scores are clamped, labels follow the score and
+/// unknown emotions fall back to the label's default
+fn resolve_sentiments(
+    segments: &[TranscriptionSegment],
+    mut ratings: HashMap<usize, RawSentiment>,
+) -> Vec<SegmentSentiment> {
+    segments
+        .iter()
+        .enumerate()
+        .map(|(i, s)| {
+            let raw = ratings.remove(&i);
+            let score = raw
+                .as_ref()
+                .and_then(|r| {
+                    let label = r.sentiment.as_deref().unwrap_or_default().to_lowercase();
+                    r.score.or(match label.as_str() {
+                        "positive" => Some(0.5),
+                        "negative" => Some(-0.5),
+                        _ => None,
+                    })
+                })
+                .unwrap_or(0.0)
+                .clamp(-1.0, 1.0);
+            let sentiment = match score {
+                s if s >= 0.2 => "positive",
+                s if s <= -0.2 => "negative",
+                _ => "neutral",
+            };
+            let emotion = raw
+                .and_then(|r| r.emotion)
+                .map(|e| e.trim().to_lowercase())
+                .filter(|e| EMOTIONS.contains(&e.as_str()))
+                .unwrap_or_else(|| {
+                    match sentiment {
+                        "positive" => "joy",
+                        "negative" => "sadness",
+                        _ => "neutral",
+                    }
+                    .to_string()
+                });
+            SegmentSentiment {
+                segment: i,
+                start: s.start,
+                end: s.end,
+                sentiment: sentiment.to_string(),
+                score,
+                emotion,
+                speaker: s.speaker.clone(),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_sentiments() {
+        let segments = vec![
+            TranscriptionSegment::new(0.0, 5.0, "This is great!"),
+            TranscriptionSegment::new(5.0, 10.0, "I'm really upset about the delay."),
+            TranscriptionSegment::new(10.0, 15.0, "The meeting is at noon."),
+            TranscriptionSegment::new(15.0, 20.0, "Fine."),
+        ];
+        let raw: Vec<RawSentiment> = parse_json_response(
+            r#"[
+                {"segment": 0, "score": 1.7, "emotion": "Joy"},
+                {"segment": 1, "sentiment": "negative", "emotion": "anger"},
+                {"segment": 3, "score": 0.1, "emotion": "boredom"}
+            ]"#,
+            "sentiment",
+        )
+        .unwrap();
+        let ratings = raw.into_iter().map(|r| (r.segment, r)).collect();
+
+        let sentiments = resolve_sentiments(&segments, ratings);
+        assert_eq!(sentiments.len(), 4);
+        assert_eq!((sentiments[0].score, sentiments[0].emotion.as_str()), (1.0, "joy"));
+        assert_eq!(sentiments[1].sentiment, "negative");
+        assert_eq!(sentiments[1].emotion, "anger");
+        // Skipped segments are neutral
+        assert_eq!((sentiments[2].score, sentiments[2].sentiment.as_str()), (0.0, "neutral"));
+        assert_eq!(sentiments[2].start, 10.0);
+        // Unknown emotions fall back to the label's default
+        assert_eq!(sentiments[3].emotion, "neutral");
+    }
+}
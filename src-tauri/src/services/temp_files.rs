@@ -0,0 +1,140 @@
+//! Removal of extracted audio and whisper.cpp output that crashed or killed
+//! runs left in the temp folder.
+
+use crate::error::Result;
+use crate::services::app_settings::AppSettings;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// Extensions of the temp files the backend writes: extracted WAVs and
+/// whisper.cpp JSON output
+const SWEPT_EXTENSIONS: &[&str] = &["wav", "json"];
+
+/// The app's temp folder, shared by all jobs
+pub fn root() -> PathBuf {
+    std::env::temp_dir().join("clip-flow")
+}
+
+/// What a sweep removed
+#[derive(Debug, Default, PartialEq)]
+pub struct SweepStats {
+    pub files: usize,
+    pub bytes: u64,
+}
+
+fn is_swept(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| SWEPT_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+}
+
+/// Delete temp files under `dir` last modified more than `max_age` before
+/// `now`, and the folders they leave empty (but not `dir` itself)
+pub fn sweep(dir: &Path, max_age: Duration, now: SystemTime) -> Result<SweepStats> {
+    let mut stats = SweepStats::default();
+    if dir.is_dir() {
+        sweep_dir(dir, max_age, now, &mut stats)?;
+    }
+    Ok(stats)
+}
+
+fn sweep_dir(dir: &Path, max_age: Duration, now: SystemTime, stats: &mut SweepStats) -> Result<()> {
+    for entry in std::fs::read_dir(dir)?.flatten() {
+        let path = entry.path();
+        // Not followed: links don't point at anything of ours
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+
+        if file_type.is_dir() {
+            sweep_dir(&path, max_age, now, stats)?;
+            // Only succeeds once the folder is empty
+            let _ = std::fs::remove_dir(&path);
+            continue;
+        }
+        if !file_type.is_file() || !is_swept(&path) {
+            continue;
+        }
+
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        let age = metadata
+            .modified()
+            .ok()
+            .and_then(|modified| now.duration_since(modified).ok())
+            .unwrap_or_default();
+        if age <= max_age {
+            continue;
+        }
+        match std::fs::remove_file(&path) {
+            Ok(()) => {
+                stats.files += 1;
+                stats.bytes += metadata.len();
+            }
+            Err(e) => log::warn!("[temp_files] Failed to remove {:?}: {}", path, e),
+        }
+    }
+    Ok(())
+}
+
+/// Sweep the app's temp folder in the background at launch, using the maximum
+/// age from the settings
+pub fn start_startup_sweep() {
+    let max_age_hours = AppSettings::load().unwrap_or_default().temp_file_max_age_hours;
+    if max_age_hours == 0 {
+        return;
+    }
+    let max_age = Duration::from_secs(max_age_hours * 3600);
+    tauri::async_runtime::spawn_blocking(move || {
+        match sweep(&root(), max_age, SystemTime::now()) {
+            Ok(stats) if stats.files > 0 => log::info!(
+                "[temp_files] Removed {} stale temp files ({} bytes)",
+                stats.files,
+                stats.bytes
+            ),
+            Ok(_) => {}
+            Err(e) => log::warn!("[temp_files] Temp folder sweep failed: {}", e),
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    const HOUR: Duration = Duration::from_secs(3600);
+
+    #[test]
+    fn test_sweep_removes_old_temp_files() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        fs::create_dir_all(root.join("jobs/job-1")).unwrap();
+        fs::write(root.join("a.wav"), b"audio").unwrap();
+        fs::write(root.join("jobs/job-1/b.json"), b"{}").unwrap();
+        fs::write(root.join("notes.txt"), b"keep").unwrap();
+
+        // Nothing is old enough yet
+        let stats = sweep(root, HOUR, SystemTime::now()).unwrap();
+        assert_eq!(stats, SweepStats::default());
+        assert!(root.join("a.wav").exists());
+
+        let later = SystemTime::now() + 2 * HOUR;
+        let stats = sweep(root, HOUR, later).unwrap();
+        assert_eq!(stats.files, 2);
+        assert_eq!(stats.bytes, 7);
+        assert!(!root.join("a.wav").exists());
+        // Emptied folders go too; other files and the root stay
+        assert!(!root.join("jobs").exists());
+        assert!(root.join("notes.txt").exists());
+    }
+
+    #[test]
+    fn test_sweep_missing_dir() {
+        let temp = TempDir::new().unwrap();
+        let stats = sweep(&temp.path().join("missing"), HOUR, SystemTime::now()).unwrap();
+        assert_eq!(stats, SweepStats::default());
+    }
+}
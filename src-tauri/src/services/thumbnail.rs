@@ -0,0 +1,160 @@
+use crate::error::{AppError, Result};
+use crate::services::ffmpeg::FFmpegService;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+
+/// Width of generated thumbnails in pixels
+const THUMBNAIL_WIDTH: u32 = 320;
+
+/// Poster frames are taken 10% into the media, but never later than this
+const MAX_POSTER_OFFSET_SECS: f64 = 5.0;
+
+/// Cache of poster thumbnails (one JPEG per media file) in the app cache directory.
+/// A thumbnail is stale once its source file has been modified after it was generated.
+pub struct ThumbnailCache {
+    dir: PathBuf,
+}
+
+impl ThumbnailCache {
+    /// Open the cache at the default location
+    pub fn new() -> Result<Self> {
+        let cache_dir = dirs::cache_dir()
+            .ok_or_else(|| AppError::InvalidPath("Cannot find cache directory".to_string()))?;
+        Ok(Self::with_dir(cache_dir.join("clip-flow").join("thumbnails")))
+    }
+
+    /// Open the cache in a specific directory
+    pub fn with_dir(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// Location of the thumbnail for a source file
+    pub fn thumbnail_path(&self, source: &Path) -> PathBuf {
+        // Only needs to be stable between runs of the same build; a changed
+        // hash just means thumbnails are regenerated
+        let mut hasher = DefaultHasher::new();
+        source.hash(&mut hasher);
+        self.dir.join(format!("{:016x}.jpg", hasher.finish()))
+    }
+
+    /// Get the cached thumbnail if it is newer than the source file
+    pub fn cached(&self, source: &Path) -> Option<PathBuf> {
+        let thumbnail = self.thumbnail_path(source);
+        let thumb_modified = std::fs::metadata(&thumbnail).and_then(|m| m.modified()).ok()?;
+        let source_modified = std::fs::metadata(source).and_then(|m| m.modified()).ok()?;
+
+        (thumb_modified >= source_modified).then_some(thumbnail)
+    }
+
+    /// Get the thumbnail for a media file, generating it if needed.
+    /// Returns `None` when the file has no frame to show (audio without cover art).
+    pub async fn get_or_create(&self, source: &Path) -> Result<Option<PathBuf>> {
+        if !source.is_file() {
+            return Err(AppError::InvalidPath(format!(
+                "File does not exist: {}",
+                source.display()
+            )));
+        }
+
+        if let Some(thumbnail) = self.cached(source) {
+            return Ok(Some(thumbnail));
+        }
+
+        tokio::fs::create_dir_all(&self.dir).await?;
+        let thumbnail = self.thumbnail_path(source);
+
+        let duration = FFmpegService::get_duration(source).await.unwrap_or(0.0);
+        let at = (duration * 0.1).min(MAX_POSTER_OFFSET_SECS);
+
+        match FFmpegService::extract_frame(source, &thumbnail, at, THUMBNAIL_WIDTH).await {
+            Ok(path) => Ok(Some(path)),
+            Err(e) => {
+                log::debug!(
+                    "[ThumbnailCache::get_or_create] No thumbnail for {}: {}",
+                    source.display(),
+                    e
+                );
+                Ok(None)
+            }
+        }
+    }
+
+    /// Delete the thumbnail of a source file
+    pub fn invalidate(&self, source: &Path) -> Result<()> {
+        match std::fs::remove_file(self.thumbnail_path(source)) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Delete all cached thumbnails
+    pub fn clear(&self) -> Result<()> {
+        if self.dir.exists() {
+            std::fs::remove_dir_all(&self.dir)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_thumbnail_path_is_stable_per_source() {
+        let cache = ThumbnailCache::with_dir(PathBuf::from("/cache"));
+
+        let a = cache.thumbnail_path(Path::new("/media/a.mp4"));
+        assert_eq!(a, cache.thumbnail_path(Path::new("/media/a.mp4")));
+        assert_ne!(a, cache.thumbnail_path(Path::new("/media/b.mp4")));
+        assert_eq!(a.extension().unwrap(), "jpg");
+        assert!(a.starts_with("/cache"));
+    }
+
+    #[test]
+    fn test_cached_requires_fresh_thumbnail() {
+        let temp_dir = TempDir::new().unwrap();
+        let cache = ThumbnailCache::with_dir(temp_dir.path().join("thumbs"));
+        let source = temp_dir.path().join("clip.mp4");
+        File::create(&source).unwrap();
+
+        assert!(cache.cached(&source).is_none());
+
+        std::fs::create_dir_all(temp_dir.path().join("thumbs")).unwrap();
+        File::create(cache.thumbnail_path(&source)).unwrap();
+        assert!(cache.cached(&source).is_some());
+
+        // Source edited after the thumbnail was made
+        let later = std::time::SystemTime::now() + std::time::Duration::from_secs(60);
+        File::options()
+            .write(true)
+            .open(&source)
+            .unwrap()
+            .set_modified(later)
+            .unwrap();
+        assert!(cache.cached(&source).is_none());
+    }
+
+    #[test]
+    fn test_invalidate_and_clear() {
+        let temp_dir = TempDir::new().unwrap();
+        let cache = ThumbnailCache::with_dir(temp_dir.path().join("thumbs"));
+        let source = Path::new("/media/clip.mp4");
+
+        // Invalidating a missing thumbnail is not an error
+        cache.invalidate(source).unwrap();
+
+        std::fs::create_dir_all(temp_dir.path().join("thumbs")).unwrap();
+        File::create(cache.thumbnail_path(source)).unwrap();
+        cache.invalidate(source).unwrap();
+        assert!(!cache.thumbnail_path(source).exists());
+
+        File::create(cache.thumbnail_path(source)).unwrap();
+        cache.clear().unwrap();
+        assert!(!temp_dir.path().join("thumbs").exists());
+    }
+}
@@ -0,0 +1,678 @@
+use crate::services::export::csv_field;
+use crate::services::ffmpeg::{FrameRate, MediaInfo};
+use crate::services::whisper::TranscriptionSegment;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+use std::path::Path;
+
+/// Frame rate assumed for audio-only media, which has none of its own
+const AUDIO_ONLY_FRAME_RATE: FrameRate = FrameRate::new(30, 1);
+/// Longest clip name taken from segment text
+const CLIP_NAME_CHARS: usize = 48;
+
+/// Options shared by the editing timeline exports
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TimelineOptions {
+    /// Project / sequence name (defaults to the media file name)
+    pub name: Option<String>,
+    /// Extra media kept before and after each segment, in seconds
+    pub handles: f64,
+}
+
+/// The media file every clip of the timeline is cut from
+#[derive(Debug, Clone)]
+pub struct TimelineSource {
+    pub path: String,
+    pub duration: f64,
+    pub frame_rate: FrameRate,
+    pub width: u32,
+    pub height: u32,
+    pub has_video: bool,
+    pub has_audio: bool,
+}
+
+impl TimelineSource {
+    pub fn new(path: &str, info: &MediaInfo) -> Self {
+        Self {
+            path: path.to_string(),
+            duration: info.duration,
+            frame_rate: info.frame_rate.unwrap_or(AUDIO_ONLY_FRAME_RATE),
+            width: info.width.unwrap_or(1920),
+            height: info.height.unwrap_or(1080),
+            has_video: info.has_video,
+            has_audio: info.has_audio,
+        }
+    }
+
+    fn file_name(&self) -> String {
+        Path::new(&self.path)
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_else(|| self.path.clone())
+    }
+
+    fn duration_frames(&self) -> u64 {
+        self.frame_rate.frames(self.duration)
+    }
+}
+
+/// A segment placed on the timeline, with source in/out points in frames
+#[derive(Debug, Clone, PartialEq)]
+struct Clip {
+    name: String,
+    /// Full segment text, with the speaker when known
+    text: String,
+    source_in: u64,
+    source_out: u64,
+}
+
+impl Clip {
+    fn frames(&self) -> u64 {
+        self.source_out - self.source_in
+    }
+}
+
+/// Turn segments (already in timeline order) into frame-accurate clips
+fn timeline_clips(
+    segments: &[TranscriptionSegment],
+    source: &TimelineSource,
+    options: &TimelineOptions,
+) -> Vec<Clip> {
+    let rate = source.frame_rate;
+    let handles = options.handles.max(0.0);
+    let media_end = source.duration_frames();
+
+    segments
+        .iter()
+        .filter(|segment| !segment.text.trim().is_empty())
+        .filter_map(|segment| {
+            let source_in = rate.frames(segment.start - handles);
+            let mut source_out = rate.frames(segment.end + handles);
+            if media_end > 0 {
+                source_out = source_out.min(media_end);
+            }
+            let text = match &segment.speaker {
+                Some(speaker) => format!("{}: {}", speaker, segment.text.trim()),
+                None => segment.text.trim().to_string(),
+            };
+            (source_out > source_in).then(|| Clip {
+                name: clip_name(&segment.text),
+                text,
+                source_in,
+                source_out,
+            })
+        })
+        .collect()
+}
+
+/// Shorten segment text to a clip name
+fn clip_name(text: &str) -> String {
+    let text = text.trim();
+    if text.chars().count() <= CLIP_NAME_CHARS {
+        return text.to_string();
+    }
+    let short: String = text.chars().take(CLIP_NAME_CHARS - 1).collect();
+    format!("{}…", short.trim_end())
+}
+
+pub(crate) fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// `file://` URL of a local path, percent-encoding everything but unreserved characters
+fn file_url(path: &str) -> String {
+    let path = path.replace('\\', "/");
+    let mut url = String::from("file://");
+    // Windows drive paths (`C:/...`) need the extra slash of an empty host
+    if !path.starts_with('/') {
+        url.push('/');
+    }
+    for byte in path.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' | b':' => {
+                url.push(byte as char)
+            }
+            _ => {
+                let _ = write!(url, "%{:02X}", byte);
+            }
+        }
+    }
+    url
+}
+
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// FCPXML rational time (`1001/30000s`) of a frame count
+fn rational_time(frames: u64, rate: FrameRate) -> String {
+    let (num, den) = (frames * rate.den as u64, rate.num as u64);
+    let divisor = gcd(num, den).max(1);
+    match (num / divisor, den / divisor) {
+        (0, _) => "0s".to_string(),
+        (num, 1) => format!("{}s", num),
+        (num, den) => format!("{}/{}s", num, den),
+    }
+}
+
+/// Render the segments as a Final Cut Pro (FCPXML 1.9) project that cuts
+/// each segment from the original media, in order, with no gaps
+pub fn to_fcpxml(
+    segments: &[TranscriptionSegment],
+    source: &TimelineSource,
+    options: &TimelineOptions,
+) -> String {
+    let rate = source.frame_rate;
+    let clips = timeline_clips(segments, source, options);
+    let file_name = source.file_name();
+    let project_name = options.name.clone().unwrap_or_else(|| file_name.clone());
+    let total: u64 = clips.iter().map(Clip::frames).sum();
+
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<!DOCTYPE fcpxml>\n");
+    xml.push_str("<fcpxml version=\"1.9\">\n  <resources>\n");
+    let _ = writeln!(
+        xml,
+        "    <format id=\"r1\" frameDuration=\"{}\" width=\"{}\" height=\"{}\"/>",
+        rational_time(1, rate),
+        source.width,
+        source.height
+    );
+    let _ = writeln!(
+        xml,
+        "    <asset id=\"r2\" name=\"{}\" start=\"0s\" duration=\"{}\" hasVideo=\"{}\" \
+         hasAudio=\"{}\" format=\"r1\">",
+        escape_xml(&file_name),
+        rational_time(source.duration_frames(), rate),
+        source.has_video as u8,
+        source.has_audio as u8
+    );
+    let _ = writeln!(
+        xml,
+        "      <media-rep kind=\"original-media\" src=\"{}\"/>",
+        escape_xml(&file_url(&source.path))
+    );
+    xml.push_str("    </asset>\n  </resources>\n  <library>\n");
+    xml.push_str("    <event name=\"Clip Flow\">\n");
+    let _ = writeln!(xml, "      <project name=\"{}\">", escape_xml(&project_name));
+    let _ = writeln!(
+        xml,
+        "        <sequence format=\"r1\" duration=\"{}\" tcStart=\"0s\" tcFormat=\"NDF\">",
+        rational_time(total, rate)
+    );
+    xml.push_str("          <spine>\n");
+
+    let mut offset = 0;
+    for clip in &clips {
+        let _ = writeln!(
+            xml,
+            "            <asset-clip ref=\"r2\" name=\"{}\" offset=\"{}\" start=\"{}\" \
+             duration=\"{}\"/>",
+            escape_xml(&clip.name),
+            rational_time(offset, rate),
+            rational_time(clip.source_in, rate),
+            rational_time(clip.frames(), rate)
+        );
+        offset += clip.frames();
+    }
+
+    xml.push_str("          </spine>\n        </sequence>\n      </project>\n");
+    xml.push_str("    </event>\n  </library>\n</fcpxml>\n");
+    xml
+}
+
+/// Start of the record side of EDLs, the usual first hour of a sequence
+const RECORD_START_HOURS: u64 = 1;
+
+/// Non-drop-frame SMPTE timecode (`HH:MM:SS:FF`) of a frame count. NTSC rates
+/// count frames in their rounded timebase, as editors label them.
+fn timecode(frames: u64, rate: FrameRate) -> String {
+    let timebase = timebase(rate);
+    let seconds = frames / timebase;
+    format!(
+        "{:02}:{:02}:{:02}:{:02}",
+        seconds / 3600,
+        (seconds / 60) % 60,
+        seconds % 60,
+        frames % timebase
+    )
+}
+
+/// Whole-frame timebase of a rate (30 for 29.97)
+fn timebase(rate: FrameRate) -> u64 {
+    (rate.fps().round() as u64).max(1)
+}
+
+/// Render the segments as a CMX3600 EDL cut from the original media.
+/// The record side starts at 01:00:00:00; source timecode starts at zero.
+pub fn to_edl(
+    segments: &[TranscriptionSegment],
+    source: &TimelineSource,
+    options: &TimelineOptions,
+) -> String {
+    let rate = source.frame_rate;
+    let file_name = source.file_name();
+    let title = options.name.clone().unwrap_or_else(|| file_name.clone());
+    let channels = match (source.has_video, source.has_audio) {
+        (true, true) => "AA/V",
+        (true, false) => "V",
+        _ => "AA",
+    };
+
+    let mut edl = String::new();
+    let _ = writeln!(edl, "TITLE: {}", title);
+    edl.push_str("FCM: NON-DROP FRAME\n");
+
+    let mut record_in = RECORD_START_HOURS * 3600 * timebase(rate);
+    for (i, clip) in timeline_clips(segments, source, options).iter().enumerate() {
+        let record_out = record_in + clip.frames();
+        let _ = writeln!(
+            edl,
+            "\n{:03}  AX       {:<5} C        {} {} {} {}",
+            i + 1,
+            channels,
+            timecode(clip.source_in, rate),
+            timecode(clip.source_out, rate),
+            timecode(record_in, rate),
+            timecode(record_out, rate)
+        );
+        let _ = writeln!(edl, "* FROM CLIP NAME: {}", file_name);
+        let _ = writeln!(edl, "* COMMENT: {}", clip.name);
+        record_in = record_out;
+    }
+
+    edl
+}
+
+/// `<rate>` element of Final Cut Pro 7 XML
+fn xmeml_rate(rate: FrameRate, indent: &str) -> String {
+    let ntsc = if rate.den == 1001 { "TRUE" } else { "FALSE" };
+    format!(
+        "{indent}<rate>\n{indent}  <timebase>{}</timebase>\n{indent}  <ntsc>{}</ntsc>\n\
+         {indent}</rate>\n",
+        timebase(rate),
+        ntsc
+    )
+}
+
+/// Append one `<clipitem>`; the first one defines the source `<file>`,
+/// later ones reference it by id
+fn write_clipitem(
+    xml: &mut String,
+    id: &str,
+    clip: &Clip,
+    record_in: u64,
+    source: &TimelineSource,
+    define_file: bool,
+) {
+    let rate = source.frame_rate;
+    let _ = writeln!(xml, "          <clipitem id=\"{}\">", id);
+    let _ = writeln!(xml, "            <name>{}</name>", escape_xml(&clip.name));
+    let _ = writeln!(xml, "            <duration>{}</duration>", source.duration_frames());
+    xml.push_str(&xmeml_rate(rate, "            "));
+    let _ = writeln!(xml, "            <start>{}</start>", record_in);
+    let _ = writeln!(xml, "            <end>{}</end>", record_in + clip.frames());
+    let _ = writeln!(xml, "            <in>{}</in>", clip.source_in);
+    let _ = writeln!(xml, "            <out>{}</out>", clip.source_out);
+
+    if define_file {
+        xml.push_str("            <file id=\"file-1\">\n");
+        let _ = writeln!(xml, "              <name>{}</name>", escape_xml(&source.file_name()));
+        let _ = writeln!(
+            xml,
+            "              <pathurl>{}</pathurl>",
+            escape_xml(&file_url(&source.path))
+        );
+        xml.push_str(&xmeml_rate(rate, "              "));
+        let _ = writeln!(xml, "              <duration>{}</duration>", source.duration_frames());
+        xml.push_str("              <media>\n");
+        if source.has_video {
+            let _ = writeln!(
+                xml,
+                "                <video><samplecharacteristics><width>{}</width>\
+                 <height>{}</height></samplecharacteristics></video>",
+                source.width, source.height
+            );
+        }
+        if source.has_audio {
+            xml.push_str("                <audio><channelcount>2</channelcount></audio>\n");
+        }
+        xml.push_str("              </media>\n            </file>\n");
+    } else {
+        xml.push_str("            <file id=\"file-1\"/>\n");
+    }
+    xml.push_str("          </clipitem>\n");
+}
+
+/// Append a `<video>` or `<audio>` track holding every clip back to back
+fn write_track(
+    xml: &mut String,
+    kind: &str,
+    clips: &[Clip],
+    source: &TimelineSource,
+    define_file: bool,
+) {
+    let _ = writeln!(xml, "      <{}>", kind);
+    if kind == "video" {
+        let _ = writeln!(
+            xml,
+            "        <format><samplecharacteristics><width>{}</width><height>{}</height>\
+             </samplecharacteristics></format>",
+            source.width, source.height
+        );
+    }
+    xml.push_str("        <track>\n");
+    let mut record_in = 0;
+    for (i, clip) in clips.iter().enumerate() {
+        let id = format!("{}-clipitem-{}", kind, i + 1);
+        write_clipitem(xml, &id, clip, record_in, source, define_file && i == 0);
+        record_in += clip.frames();
+    }
+    xml.push_str("        </track>\n");
+    let _ = writeln!(xml, "      </{}>", kind);
+}
+
+/// Render the segments as a Final Cut Pro 7 XML (xmeml) sequence, the
+/// interchange format Premiere Pro imports
+pub fn to_premiere_xml(
+    segments: &[TranscriptionSegment],
+    source: &TimelineSource,
+    options: &TimelineOptions,
+) -> String {
+    xmeml(segments, source, options, false)
+}
+
+/// Render the segments as an FCP7 XML sequence for DaVinci Resolve, with a
+/// sequence marker carrying the transcript text at the start of every clip
+pub fn to_resolve_xml(
+    segments: &[TranscriptionSegment],
+    source: &TimelineSource,
+    options: &TimelineOptions,
+) -> String {
+    xmeml(segments, source, options, true)
+}
+
+fn xmeml(
+    segments: &[TranscriptionSegment],
+    source: &TimelineSource,
+    options: &TimelineOptions,
+    markers: bool,
+) -> String {
+    let clips = timeline_clips(segments, source, options);
+    let name = options.name.clone().unwrap_or_else(|| source.file_name());
+    let total: u64 = clips.iter().map(Clip::frames).sum();
+
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<!DOCTYPE xmeml>\n");
+    xml.push_str("<xmeml version=\"4\">\n  <sequence id=\"sequence-1\">\n");
+    let _ = writeln!(xml, "    <name>{}</name>", escape_xml(&name));
+    let _ = writeln!(xml, "    <duration>{}</duration>", total);
+    xml.push_str(&xmeml_rate(source.frame_rate, "    "));
+    xml.push_str("    <media>\n");
+    if source.has_video {
+        write_track(&mut xml, "video", &clips, source, true);
+    }
+    if source.has_audio {
+        write_track(&mut xml, "audio", &clips, source, !source.has_video);
+    }
+    xml.push_str("    </media>\n");
+
+    if markers {
+        let mut record_in = 0;
+        for clip in &clips {
+            xml.push_str("    <marker>\n");
+            let _ = writeln!(xml, "      <name>{}</name>", escape_xml(&clip.name));
+            let _ = writeln!(xml, "      <comment>{}</comment>", escape_xml(&clip.text));
+            let _ = writeln!(xml, "      <in>{}</in>\n      <out>-1</out>", record_in);
+            xml.push_str("    </marker>\n");
+            record_in += clip.frames();
+        }
+    }
+
+    xml.push_str("  </sequence>\n</xmeml>\n");
+    xml
+}
+
+/// Resolve marker color used for transcript markers
+const RESOLVE_MARKER_COLOR: &str = "Blue";
+
+/// Render one timeline marker per clip as an EDL that Resolve imports with
+/// "Import > Timeline Markers from EDL". Marker positions follow the cut
+/// timeline of [`to_edl`] / [`to_resolve_xml`], starting at 01:00:00:00.
+pub fn to_resolve_marker_edl(
+    segments: &[TranscriptionSegment],
+    source: &TimelineSource,
+    options: &TimelineOptions,
+) -> String {
+    let rate = source.frame_rate;
+    let title = options.name.clone().unwrap_or_else(|| source.file_name());
+
+    let mut edl = String::new();
+    let _ = writeln!(edl, "TITLE: {} Markers", title);
+    edl.push_str("FCM: NON-DROP FRAME\n");
+
+    let mut record_in = RECORD_START_HOURS * 3600 * timebase(rate);
+    for (i, clip) in timeline_clips(segments, source, options).iter().enumerate() {
+        let _ = writeln!(
+            edl,
+            "\n{:03}  001      V     C        {} {} {} {}",
+            i + 1,
+            timecode(record_in, rate),
+            timecode(record_in + 1, rate),
+            timecode(record_in, rate),
+            timecode(record_in + 1, rate)
+        );
+        // Marker fields are separated by `|`, so it cannot appear in the text
+        let _ = writeln!(
+            edl,
+            " |C:ResolveColor{} |M:{} |D:{}",
+            RESOLVE_MARKER_COLOR,
+            clip.text.replace('|', "/"),
+            clip.frames()
+        );
+        record_in += clip.frames();
+    }
+
+    edl
+}
+
+/// Render the timeline markers as CSV (number, name, record in/out, duration,
+/// color and notes) for reviewing or re-keying them in a spreadsheet
+pub fn to_marker_csv(
+    segments: &[TranscriptionSegment],
+    source: &TimelineSource,
+    options: &TimelineOptions,
+) -> String {
+    let rate = source.frame_rate;
+    let mut csv = String::from("#,Name,Record In,Record Out,Duration,Color,Notes\r\n");
+
+    let mut record_in = RECORD_START_HOURS * 3600 * timebase(rate);
+    for (i, clip) in timeline_clips(segments, source, options).iter().enumerate() {
+        let record_out = record_in + clip.frames();
+        let _ = write!(
+            csv,
+            "{},{},{},{},{},{},{}\r\n",
+            i + 1,
+            csv_field(&clip.name),
+            timecode(record_in, rate),
+            timecode(record_out, rate),
+            timecode(clip.frames(), rate),
+            RESOLVE_MARKER_COLOR,
+            csv_field(&clip.text)
+        );
+        record_in = record_out;
+    }
+
+    csv
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source(frame_rate: FrameRate) -> TimelineSource {
+        TimelineSource {
+            path: "/Users/me/Movies/My Interview.mov".to_string(),
+            duration: 120.0,
+            frame_rate,
+            width: 1920,
+            height: 1080,
+            has_video: true,
+            has_audio: true,
+        }
+    }
+
+    #[test]
+    fn test_frame_rate_parse() {
+        assert_eq!(FrameRate::parse("30000/1001"), Some(FrameRate::new(30000, 1001)));
+        assert_eq!(FrameRate::parse("25"), Some(FrameRate::new(25, 1)));
+        assert_eq!(FrameRate::parse("0/0"), None);
+        assert_eq!(FrameRate::new(30000, 1001).frames(10.0), 300);
+    }
+
+    #[test]
+    fn test_rational_time() {
+        let ntsc = FrameRate::new(30000, 1001);
+        assert_eq!(rational_time(0, ntsc), "0s");
+        assert_eq!(rational_time(1, ntsc), "1001/30000s");
+        assert_eq!(rational_time(30, ntsc), "1001/1000s");
+
+        let pal = FrameRate::new(25, 1);
+        assert_eq!(rational_time(1, pal), "1/25s");
+        assert_eq!(rational_time(50, pal), "2s");
+    }
+
+    #[test]
+    fn test_file_url() {
+        assert_eq!(
+            file_url("/Users/me/My Clip #1.mov"),
+            "file:///Users/me/My%20Clip%20%231.mov"
+        );
+        assert_eq!(file_url(r"C:\Media\clip.mp4"), "file:///C:/Media/clip.mp4");
+    }
+
+    #[test]
+    fn test_timeline_clips_handles_and_bounds() {
+        let segments = vec![
+            TranscriptionSegment::new(0.5, 2.0, "Intro"),
+            TranscriptionSegment::new(5.0, 6.0, "  "),
+            TranscriptionSegment::new(119.0, 125.0, "Outro"),
+        ];
+        let options = TimelineOptions {
+            handles: 1.0,
+            ..Default::default()
+        };
+        let clips = timeline_clips(&segments, &source(FrameRate::new(25, 1)), &options);
+
+        assert_eq!(clips.len(), 2);
+        assert_eq!((clips[0].source_in, clips[0].source_out), (0, 75));
+        assert_eq!((clips[1].source_in, clips[1].source_out), (2950, 3000));
+    }
+
+    #[test]
+    fn test_to_fcpxml() {
+        let segments = vec![
+            TranscriptionSegment::new(60.0, 62.0, "Best answer"),
+            TranscriptionSegment::new(10.0, 11.0, "Hook"),
+        ];
+        let options = TimelineOptions::default();
+        let xml = to_fcpxml(&segments, &source(FrameRate::new(25, 1)), &options);
+
+        assert!(xml.contains("<format id=\"r1\" frameDuration=\"1/25s\" width=\"1920\""));
+        assert!(xml.contains("src=\"file:///Users/me/Movies/My%20Interview.mov\""));
+        assert!(xml.contains("<project name=\"My Interview.mov\">"));
+        assert!(xml.contains("<sequence format=\"r1\" duration=\"3s\""));
+        assert!(xml.contains(
+            "<asset-clip ref=\"r2\" name=\"Best answer\" offset=\"0s\" start=\"60s\" duration=\"2s\"/>"
+        ));
+        assert!(xml.contains(
+            "<asset-clip ref=\"r2\" name=\"Hook\" offset=\"2s\" start=\"10s\" duration=\"1s\"/>"
+        ));
+    }
+
+    #[test]
+    fn test_timecode() {
+        assert_eq!(timecode(0, FrameRate::new(25, 1)), "00:00:00:00");
+        assert_eq!(timecode(3 * 3600 * 25 + 61 * 25 + 7, FrameRate::new(25, 1)), "03:01:01:07");
+        assert_eq!(timecode(30, FrameRate::new(30000, 1001)), "00:00:01:00");
+    }
+
+    #[test]
+    fn test_to_edl() {
+        let segments = vec![
+            TranscriptionSegment::new(60.0, 62.0, "Best answer"),
+            TranscriptionSegment::new(10.0, 11.0, "Hook"),
+        ];
+        let options = TimelineOptions::default();
+        let edl = to_edl(&segments, &source(FrameRate::new(25, 1)), &options);
+
+        assert_eq!(
+            edl,
+            "TITLE: My Interview.mov\n\
+             FCM: NON-DROP FRAME\n\
+             \n001  AX       AA/V  C        00:01:00:00 00:01:02:00 01:00:00:00 01:00:02:00\n\
+             * FROM CLIP NAME: My Interview.mov\n\
+             * COMMENT: Best answer\n\
+             \n002  AX       AA/V  C        00:00:10:00 00:00:11:00 01:00:02:00 01:00:03:00\n\
+             * FROM CLIP NAME: My Interview.mov\n\
+             * COMMENT: Hook\n"
+        );
+    }
+
+    #[test]
+    fn test_to_premiere_xml() {
+        let segments = vec![
+            TranscriptionSegment::new(60.0, 62.0, "Best answer"),
+            TranscriptionSegment::new(10.0, 11.0, "Hook"),
+        ];
+        let options = TimelineOptions {
+            name: Some("Rough cut".to_string()),
+            ..Default::default()
+        };
+        let xml = to_premiere_xml(&segments, &source(FrameRate::new(30000, 1001)), &options);
+
+        assert!(xml.contains("<name>Rough cut</name>\n    <duration>90</duration>"));
+        assert!(xml.contains("<timebase>30</timebase>\n      <ntsc>TRUE</ntsc>"));
+        assert!(xml.contains("<start>60</start>\n            <end>90</end>"));
+        assert!(xml.contains("<in>300</in>\n            <out>330</out>"));
+        // The file is defined once and referenced by every other clip item
+        assert_eq!(xml.matches("<pathurl>").count(), 1);
+        assert_eq!(xml.matches("<file id=\"file-1\"/>").count(), 3);
+    }
+
+    #[test]
+    fn test_resolve_markers() {
+        let segments = vec![
+            TranscriptionSegment::new(60.0, 62.0, "Best answer | part one").with_speaker("Alex"),
+            TranscriptionSegment::new(10.0, 11.0, "Hook, \"quoted\""),
+        ];
+        let source = source(FrameRate::new(25, 1));
+        let options = TimelineOptions::default();
+
+        let edl = to_resolve_marker_edl(&segments, &source, &options);
+        assert!(edl.contains(
+            "001  001      V     C        01:00:00:00 01:00:00:01 01:00:00:00 01:00:00:01\n \
+             |C:ResolveColorBlue |M:Alex: Best answer / part one |D:50\n"
+        ));
+        assert!(edl.contains("002  001      V     C        01:00:02:00 01:00:02:01"));
+
+        let csv = to_marker_csv(&segments, &source, &options);
+        let quoted = "\"Hook, \"\"quoted\"\"\"";
+        assert_eq!(
+            csv.lines().nth(2).unwrap(),
+            format!("2,{},01:00:02:00,01:00:03:00,00:00:01:00,Blue,{}", quoted, quoted)
+        );
+
+        let xml = to_resolve_xml(&segments, &source, &options);
+        assert_eq!(xml.matches("<marker>").count(), 2);
+        assert!(xml.contains("<comment>Alex: Best answer | part one</comment>\n      <in>0</in>"));
+        assert!(!to_premiere_xml(&segments, &source, &options).contains("<marker>"));
+    }
+}
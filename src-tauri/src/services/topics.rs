@@ -0,0 +1,174 @@
+use crate::error::{AppError, Result};
+use crate::services::chapters::{enforce_youtube_rules, Chapter, YouTubeChapters};
+use crate::services::llm::{parse_json_response, LlmProvider};
+use crate::services::ollama::language_code_to_name;
+use crate::services::whisper::TranscriptionSegment;
+use serde::{Deserialize, Serialize};
+use tokio_util::sync::CancellationToken;
+
+const TOPICS_SYSTEM: &str = "You are an editor who structures recordings into chapters for \
+                             navigation. Respond with the JSON array only, without any \
+                             explanation.";
+
+/// Reply budget: titles plus a sentence or two per chapter
+const TOPICS_MAX_TOKENS: u32 = 4096;
+
+/// A topical chapter of a transcript
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Topic {
+    pub title: String,
+    pub start: f64,
+    pub end: f64,
+    /// One or two sentences on what the chapter covers
+    pub summary: String,
+    /// Indexes of the first and last transcript segments of the chapter
+    pub first_segment: usize,
+    pub last_segment: usize,
+}
+
+/// Topical chapters plus the YouTube chapter list built from them
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TopicSegmentation {
+    pub topics: Vec<Topic>,
+    /// `None` when the chapters do not meet YouTube's rules (e.g. a short clip)
+    pub youtube: Option<YouTubeChapters>,
+}
+
+/// A chapter start as returned by the LLM
+#[derive(Debug, Deserialize)]
+struct RawTopic {
+    index: usize,
+    title: String,
+    #[serde(default)]
+    summary: String,
+}
+
+/// Split transcript segments into topical chapters with titles and summaries
+pub async fn detect_topics(
+    llm: &dyn LlmProvider,
+    segments: &[TranscriptionSegment],
+    language: &str,
+    cancel: &CancellationToken,
+) -> Result<TopicSegmentation> {
+    if segments.is_empty() {
+        return Err(AppError::InvalidInput("The transcript has no segments".to_string()));
+    }
+    log::info!("[topics] Detecting chapters with {} ({})", llm.id(), llm.model());
+
+    let prompt = build_topics_prompt(segments, language);
+    let response = llm
+        .complete(TOPICS_SYSTEM, &prompt, TOPICS_MAX_TOKENS, cancel)
+        .await?;
+    let topics = parse_topics_response(&response, segments)?;
+
+    let duration = segments.iter().map(|s| s.end).fold(0.0, f64::max);
+    let chapters = topics
+        .iter()
+        .map(|t| Chapter {
+            start: t.start,
+            title: t.title.clone(),
+        })
+        .collect();
+    let youtube = enforce_youtube_rules(chapters, duration)
+        .ok()
+        .map(YouTubeChapters::new);
+
+    Ok(TopicSegmentation { topics, youtube })
+}
+
+fn build_topics_prompt(segments: &[TranscriptionSegment], language: &str) -> String {
+    let segments_text: Vec<String> = segments
+        .iter()
+        .enumerate()
+        .map(|(i, s)| format!("[{}] ({:.1}s): {}", i, s.start, s.text.trim()))
+        .collect();
+
+    format!(
+        "Split this transcript into chapters wherever the topic changes. For each chapter \
+         give the index of the segment where it starts, a short title and a one or two \
+         sentence summary, both in {}.\n\n\
+         Rules:\n\
+         - The first chapter starts at segment 0\n\
+         - Chapters follow the order of the transcript and do not overlap\n\
+         - Titles are at most 60 characters, with no timestamps or numbering\n\n\
+         Segments:\n{}\n\n\
+         Response format: [{{\"index\": 0, \"title\": \"Introduction\", \
+         \"summary\": \"The host introduces the guest.\"}}, ...]",
+        language_code_to_name(language),
+        segments_text.join("\n")
+    )
+}
+
+/// Turn the LLM's chapter starts into contiguous chapters covering the whole transcript.
+/// Out-of-range and repeated starts are dropped and the first chapter starts at segment 0.
+fn parse_topics_response(response: &str, segments: &[TranscriptionSegment]) -> Result<Vec<Topic>> {
+    let mut raw: Vec<RawTopic> = parse_json_response(response, "chapters")?;
+    raw.retain(|t| t.index < segments.len() && !t.title.trim().is_empty());
+    raw.sort_by_key(|t| t.index);
+    raw.dedup_by_key(|t| t.index);
+
+    if raw.is_empty() {
+        return Err(AppError::ProcessFailed("The model returned no chapters".to_string()));
+    }
+    raw[0].index = 0;
+
+    let ends: Vec<usize> = raw
+        .iter()
+        .skip(1)
+        .map(|t| t.index - 1)
+        .chain(std::iter::once(segments.len() - 1))
+        .collect();
+
+    Ok(raw
+        .into_iter()
+        .zip(ends)
+        .map(|(topic, last)| Topic {
+            title: topic.title.split_whitespace().collect::<Vec<_>>().join(" "),
+            start: segments[topic.index].start,
+            end: segments[last].end,
+            summary: topic.summary.trim().to_string(),
+            first_segment: topic.index,
+            last_segment: last,
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segments(count: usize) -> Vec<TranscriptionSegment> {
+        (0..count)
+            .map(|i| {
+                TranscriptionSegment::new(i as f64 * 30.0, i as f64 * 30.0 + 30.0, &format!("Segment {}", i))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_topics_response_covers_transcript() {
+        let response = r#"[
+            {"index": 6, "title": "Q&A", "summary": "Questions from the audience."},
+            {"index": 2, "title": "Demo", "summary": "A live demo."},
+            {"index": 2, "title": "Duplicate"},
+            {"index": 1, "title": "Intro", "summary": "Welcome."},
+            {"index": 99, "title": "Bogus"}
+        ]"#;
+
+        let topics = parse_topics_response(response, &segments(10)).unwrap();
+        let titles: Vec<&str> = topics.iter().map(|t| t.title.as_str()).collect();
+        assert_eq!(titles, ["Intro", "Demo", "Q&A"]);
+
+        // The first chapter is moved to the start and chapters are contiguous
+        assert_eq!((topics[0].start, topics[0].end), (0.0, 60.0));
+        assert_eq!((topics[1].first_segment, topics[1].last_segment), (2, 5));
+        assert_eq!((topics[2].start, topics[2].end), (180.0, 300.0));
+        assert_eq!(topics[1].summary, "A live demo.");
+    }
+
+    #[test]
+    fn test_parse_topics_response_needs_a_chapter() {
+        assert!(parse_topics_response("[]", &segments(3)).is_err());
+        assert!(parse_topics_response("no chapters", &segments(3)).is_err());
+    }
+}
@@ -0,0 +1,172 @@
+//! Word-level comparison of two transcriptions of the same audio, to show
+//! where two models disagree.
+
+use crate::services::export::timed_words;
+use crate::services::whisper::{TranscriptionSegment, TranscriptionWord};
+use serde::Serialize;
+use similar::{capture_diff_slices, Algorithm, DiffOp};
+
+/// How the two transcriptions compare over a stretch of audio
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiffKind {
+    /// Both heard the same words
+    Same,
+    /// Both heard something, but different words
+    Changed,
+    /// Only the first transcription has words here
+    FirstOnly,
+    /// Only the second transcription has words here
+    SecondOnly,
+}
+
+/// A run of words on which the transcriptions agree or disagree
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DiffChunk {
+    pub kind: DiffKind,
+    pub start: f64,
+    pub end: f64,
+    pub first: String,
+    pub second: String,
+}
+
+/// The aligned transcriptions
+#[derive(Debug, Clone, Serialize)]
+pub struct TranscriptDiff {
+    pub chunks: Vec<DiffChunk>,
+    /// Share of words both transcriptions agree on (0-1)
+    pub agreement: f64,
+    /// Number of chunks that aren't [`DiffKind::Same`]
+    pub disagreements: usize,
+}
+
+/// A word as compared: case and punctuation don't count as disagreement
+fn normalize(word: &str) -> String {
+    word.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+fn words(segments: &[TranscriptionSegment]) -> Vec<TranscriptionWord> {
+    segments.iter().flat_map(timed_words).collect()
+}
+
+fn join(words: &[TranscriptionWord]) -> String {
+    words
+        .iter()
+        .map(|word| word.text.as_str())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Time span of the words, `None` without words
+fn span(words: &[TranscriptionWord]) -> Option<(f64, f64)> {
+    Some((words.first()?.start, words.last()?.end))
+}
+
+/// Align the words of two transcriptions and mark where they differ
+pub fn diff_transcripts(
+    first: &[TranscriptionSegment],
+    second: &[TranscriptionSegment],
+) -> TranscriptDiff {
+    let first_words = words(first);
+    let second_words = words(second);
+    let first_keys: Vec<String> = first_words.iter().map(|w| normalize(&w.text)).collect();
+    let second_keys: Vec<String> = second_words.iter().map(|w| normalize(&w.text)).collect();
+
+    let mut chunks = Vec::new();
+    let mut same_words = 0;
+    for op in capture_diff_slices(Algorithm::Myers, &first_keys, &second_keys) {
+        let (kind, first_range, second_range) = match op {
+            DiffOp::Equal {
+                old_index,
+                new_index,
+                len,
+            } => {
+                same_words += len;
+                (DiffKind::Same, old_index..old_index + len, new_index..new_index + len)
+            }
+            DiffOp::Replace {
+                old_index,
+                old_len,
+                new_index,
+                new_len,
+            } => (
+                DiffKind::Changed,
+                old_index..old_index + old_len,
+                new_index..new_index + new_len,
+            ),
+            DiffOp::Delete {
+                old_index,
+                old_len,
+                new_index,
+            } => (DiffKind::FirstOnly, old_index..old_index + old_len, new_index..new_index),
+            DiffOp::Insert {
+                old_index,
+                new_index,
+                new_len,
+            } => (DiffKind::SecondOnly, old_index..old_index, new_index..new_index + new_len),
+        };
+
+        let first_part = &first_words[first_range];
+        let second_part = &second_words[second_range];
+        let (start, end) = match (span(first_part), span(second_part)) {
+            (Some(a), Some(b)) => (a.0.min(b.0), a.1.max(b.1)),
+            (Some(span), None) | (None, Some(span)) => span,
+            (None, None) => continue,
+        };
+        chunks.push(DiffChunk {
+            kind,
+            start,
+            end,
+            first: join(first_part),
+            second: join(second_part),
+        });
+    }
+
+    let total = first_words.len().max(second_words.len());
+    TranscriptDiff {
+        disagreements: chunks.iter().filter(|c| c.kind != DiffKind::Same).count(),
+        agreement: if total == 0 { 1.0 } else { same_words as f64 / total as f64 },
+        chunks,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_transcripts() {
+        let first = vec![
+            TranscriptionSegment::new(0.0, 2.0, "Welcome to the show."),
+            TranscriptionSegment::new(2.0, 4.0, "Today we talk colour."),
+        ];
+        let second = vec![TranscriptionSegment::new(
+            0.0,
+            4.0,
+            "welcome to the show today we talk color",
+        )];
+
+        let diff = diff_transcripts(&first, &second);
+        let kinds: Vec<DiffKind> = diff.chunks.iter().map(|c| c.kind).collect();
+        assert_eq!(kinds, [DiffKind::Same, DiffKind::Changed]);
+        assert_eq!(diff.chunks[1].first, "colour.");
+        assert_eq!(diff.chunks[1].second, "color");
+        assert_eq!(diff.disagreements, 1);
+        assert!((diff.agreement - 7.0 / 8.0).abs() < 1e-9);
+
+        let diff = diff_transcripts(
+            &[TranscriptionSegment::new(0.0, 1.0, "hello there")],
+            &[TranscriptionSegment::new(0.0, 1.0, "hello")],
+        );
+        assert_eq!(diff.chunks[1].kind, DiffKind::FirstOnly);
+        assert_eq!(diff.chunks[1].second, "");
+        assert_eq!(diff.chunks[1].start, 0.5);
+
+        let empty = diff_transcripts(&[], &[]);
+        assert!(empty.chunks.is_empty());
+        assert_eq!(empty.agreement, 1.0);
+    }
+}
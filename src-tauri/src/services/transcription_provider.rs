@@ -0,0 +1,180 @@
+use crate::error::Result;
+use crate::services::cancellation::cancellable;
+use crate::services::groq::{GROQ_API_BASE, GROQ_DEFAULT_TRANSCRIPTION_MODEL};
+use crate::services::openai::OpenAIService;
+use crate::services::openai_compatible::normalize_base_url;
+use crate::services::providers;
+use crate::services::whisper::{TranscriptionResult, WhisperService};
+use async_trait::async_trait;
+use std::path::Path;
+use std::sync::Arc;
+use tokio_util::sync::CancellationToken;
+
+/// Provider id of the bundled whisper.cpp engine
+pub const LOCAL: &str = "local";
+
+/// Default model of the OpenAI transcription API
+pub const OPENAI_DEFAULT_MODEL: &str = "whisper-1";
+
+/// Receives transcription progress in percent (0-100)
+pub type ProgressFn = Box<dyn Fn(f32) + Send + Sync>;
+
+/// A speech-to-text engine. The media pipeline extracts the audio to a
+/// 16 kHz mono WAV file and hands it to a provider, so local and cloud
+/// engines share everything else (probing, cleanup, progress, history).
+#[async_trait]
+pub trait TranscriptionProvider: Send + Sync {
+    /// Provider id, e.g. `local` or `openai`
+    fn id(&self) -> &'static str;
+
+    /// Model used for transcription, as recorded in the history
+    fn model(&self) -> &str;
+
+    /// Transcribe a WAV file. Cancelling the token stops the engine and
+    /// returns `AppError::Cancelled`.
+    async fn transcribe(
+        &self,
+        audio_path: &Path,
+        language: Option<&str>,
+        cancel: &CancellationToken,
+        on_progress: ProgressFn,
+    ) -> Result<TranscriptionResult>;
+}
+
+/// Local whisper.cpp with a downloaded model
+pub struct LocalWhisperProvider {
+    service: Arc<WhisperService>,
+    model_id: String,
+}
+
+impl LocalWhisperProvider {
+    pub fn new(service: Arc<WhisperService>, model_id: &str) -> Self {
+        Self {
+            service,
+            model_id: model_id.to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl TranscriptionProvider for LocalWhisperProvider {
+    fn id(&self) -> &'static str {
+        LOCAL
+    }
+
+    fn model(&self) -> &str {
+        &self.model_id
+    }
+
+    async fn transcribe(
+        &self,
+        audio_path: &Path,
+        language: Option<&str>,
+        cancel: &CancellationToken,
+        on_progress: ProgressFn,
+    ) -> Result<TranscriptionResult> {
+        self.service
+            .transcribe(audio_path, &self.model_id, language, cancel, on_progress)
+            .await
+    }
+}
+
+/// A hosted transcription API that speaks the OpenAI protocol (OpenAI, Groq)
+pub struct OpenAIWhisperProvider {
+    id: &'static str,
+    service: OpenAIService,
+    model: String,
+}
+
+impl OpenAIWhisperProvider {
+    /// Create an OpenAI provider for `model`, or `whisper-1` when empty
+    pub fn new(api_key: &str, model: &str) -> Self {
+        let service = OpenAIService::new(api_key);
+        Self::with_service(providers::OPENAI, service, model, OPENAI_DEFAULT_MODEL)
+    }
+
+    /// Create a Groq provider for `model`, or `whisper-large-v3` when empty
+    pub fn groq(api_key: &str, model: &str) -> Self {
+        let service = OpenAIService::hosted(api_key, providers::GROQ, GROQ_API_BASE);
+        Self::with_service(providers::GROQ, service, model, GROQ_DEFAULT_TRANSCRIPTION_MODEL)
+    }
+
+    /// Create a provider for a self-hosted OpenAI-compatible server (llama.cpp
+    /// server, LM Studio, vLLM); servers with a single model ignore `model`
+    pub fn openai_compatible(base_url: &str, api_key: Option<&str>, model: &str) -> Result<Self> {
+        let base_url = normalize_base_url(base_url)?;
+        let service = OpenAIService::with_base_url(api_key.unwrap_or_default(), &base_url);
+        Ok(Self::with_service(
+            providers::OPENAI_COMPATIBLE,
+            service,
+            model,
+            OPENAI_DEFAULT_MODEL,
+        ))
+    }
+
+    fn with_service(id: &'static str, service: OpenAIService, model: &str, default: &str) -> Self {
+        let model = if model.is_empty() { default } else { model };
+        Self {
+            id,
+            service,
+            model: model.to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl TranscriptionProvider for OpenAIWhisperProvider {
+    fn id(&self) -> &'static str {
+        self.id
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    async fn transcribe(
+        &self,
+        audio_path: &Path,
+        language: Option<&str>,
+        cancel: &CancellationToken,
+        on_progress: ProgressFn,
+    ) -> Result<TranscriptionResult> {
+        // The API reports no progress; the upload either finishes or is dropped
+        let request = self.service.transcribe(audio_path, language, Some(&self.model));
+        let response = cancellable(cancel, request).await?;
+        on_progress(100.0);
+        Ok(response.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::AppError;
+
+    #[test]
+    fn test_hosted_providers_default_model() {
+        assert_eq!(OpenAIWhisperProvider::new("sk-test", "").model(), OPENAI_DEFAULT_MODEL);
+        let provider = OpenAIWhisperProvider::new("sk-test", "gpt-4o-transcribe");
+        assert_eq!((provider.id(), provider.model()), ("openai", "gpt-4o-transcribe"));
+
+        let groq = OpenAIWhisperProvider::groq("gsk-test", "");
+        assert_eq!((groq.id(), groq.model()), ("groq", "whisper-large-v3"));
+
+        let server = OpenAIWhisperProvider::openai_compatible("localhost:8080", None, "").unwrap();
+        assert_eq!((server.id(), server.model()), ("openai_compatible", "whisper-1"));
+        assert!(OpenAIWhisperProvider::openai_compatible(" ", None, "").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_openai_provider_cancelled_before_request() {
+        let provider = OpenAIWhisperProvider::new("sk-test", "");
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+
+        let result = provider
+            .transcribe(Path::new("/nonexistent.wav"), None, &cancel, Box::new(|_| {}))
+            .await;
+        assert!(matches!(result, Err(AppError::Cancelled)));
+    }
+}
@@ -0,0 +1,143 @@
+use crate::error::Result;
+use crate::services::http;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Latest published (non-draft, non-prerelease) release of the app
+const LATEST_RELEASE_URL: &str =
+    "https://api.github.com/repos/gprecious/clip-flow/releases/latest";
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Installer file extensions for this platform, most preferred first
+#[cfg(target_os = "macos")]
+const INSTALLER_EXTENSIONS: &[&str] = &[".dmg"];
+#[cfg(target_os = "windows")]
+const INSTALLER_EXTENSIONS: &[&str] = &["-setup.exe", ".msi"];
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+const INSTALLER_EXTENSIONS: &[&str] = &[".appimage", ".deb", ".rpm"];
+
+#[derive(Debug, Deserialize)]
+struct GithubRelease {
+    tag_name: String,
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    body: Option<String>,
+    html_url: String,
+    #[serde(default)]
+    published_at: Option<String>,
+    #[serde(default)]
+    assets: Vec<GithubAsset>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GithubAsset {
+    name: String,
+    browser_download_url: String,
+}
+
+/// Result of an update check
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UpdateInfo {
+    pub current_version: String,
+    pub latest_version: String,
+    pub update_available: bool,
+    pub release_name: Option<String>,
+    /// Release notes (Markdown)
+    pub release_notes: Option<String>,
+    /// Release page on GitHub
+    pub release_url: String,
+    /// Installer for this platform, or the release page when there is none
+    pub download_url: String,
+    /// RFC 3339 timestamp
+    pub published_at: Option<String>,
+}
+
+/// Numeric components of a version such as "v1.2.3" or "1.2.3-beta.1"
+/// (pre-release and build suffixes are ignored)
+fn version_parts(version: &str) -> Vec<u64> {
+    let version = version.trim().trim_start_matches(['v', 'V']);
+    let core = version.split(['-', '+']).next().unwrap_or_default();
+    core.split('.').map(|part| part.parse().unwrap_or(0)).collect()
+}
+
+/// Whether `latest` is a higher version than `current`
+pub fn is_newer(latest: &str, current: &str) -> bool {
+    let (mut latest, mut current) = (version_parts(latest), version_parts(current));
+    let len = latest.len().max(current.len());
+    latest.resize(len, 0);
+    current.resize(len, 0);
+    latest > current
+}
+
+fn installer_url(assets: &[GithubAsset]) -> Option<String> {
+    INSTALLER_EXTENSIONS.iter().find_map(|extension| {
+        assets
+            .iter()
+            .find(|asset| asset.name.to_lowercase().ends_with(extension))
+            .map(|asset| asset.browser_download_url.clone())
+    })
+}
+
+fn update_info(release: GithubRelease, current_version: &str) -> UpdateInfo {
+    let latest_version = release.tag_name.trim_start_matches(['v', 'V']).to_string();
+    UpdateInfo {
+        update_available: is_newer(&latest_version, current_version),
+        current_version: current_version.to_string(),
+        latest_version,
+        release_name: release.name.filter(|name| !name.is_empty()),
+        release_notes: release.body.filter(|body| !body.trim().is_empty()),
+        download_url: installer_url(&release.assets).unwrap_or_else(|| release.html_url.clone()),
+        release_url: release.html_url,
+        published_at: release.published_at,
+    }
+}
+
+/// Look up the latest release on GitHub and compare it with `current_version`
+pub async fn check_latest_release(current_version: &str) -> Result<UpdateInfo> {
+    let release: GithubRelease = http::client()
+        .get(LATEST_RELEASE_URL)
+        .header("Accept", "application/vnd.github+json")
+        .timeout(REQUEST_TIMEOUT)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    Ok(update_info(release, current_version))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_newer() {
+        assert!(is_newer("v0.2.0", "0.1.25"));
+        assert!(is_newer("0.1.26", "0.1.25"));
+        assert!(is_newer("1.0", "0.9.9"));
+        assert!(!is_newer("v0.1.25", "0.1.25"));
+        assert!(!is_newer("0.1.25-beta.1", "0.1.25"));
+        assert!(!is_newer("0.1.9", "0.1.25"));
+    }
+
+    #[test]
+    fn test_update_info() {
+        let release: GithubRelease = serde_json::from_value(serde_json::json!({
+            "tag_name": "v0.2.0",
+            "name": "",
+            "body": "- Faster transcription",
+            "html_url": "https://github.com/gprecious/clip-flow/releases/tag/v0.2.0",
+            "assets": [{ "name": "latest.json", "browser_download_url": "https://x/latest.json" }]
+        }))
+        .unwrap();
+        let info = update_info(release, "0.1.25");
+        assert!(info.update_available);
+        assert_eq!(info.latest_version, "0.2.0");
+        assert_eq!(info.release_name, None);
+        assert_eq!(info.release_notes.as_deref(), Some("- Faster transcription"));
+        // No installer for this platform: link the release page
+        assert_eq!(info.download_url, info.release_url);
+    }
+}
@@ -0,0 +1,172 @@
+use crate::services::database::{ApiUsageRecord, Database};
+use crate::services::providers;
+use std::future::Future;
+
+tokio::task_local! {
+    /// Media file the API calls of the current task work on
+    static MEDIA_PATH: String;
+}
+
+/// Prices in USD: per million input and output tokens for text models,
+/// per minute of audio for transcription models
+enum Price {
+    Tokens { input: f64, output: f64 },
+    AudioMinute(f64),
+}
+
+/// Published list prices, matched on the longest model name prefix.
+/// Estimates only: discounts, cached input and batch pricing are not taken into account.
+const PRICES: &[(&str, &str, Price)] = &[
+    (providers::OPENAI, "gpt-5-nano", Price::Tokens { input: 0.05, output: 0.4 }),
+    (providers::OPENAI, "gpt-5-mini", Price::Tokens { input: 0.25, output: 2.0 }),
+    (providers::OPENAI, "gpt-5", Price::Tokens { input: 1.25, output: 10.0 }),
+    (providers::OPENAI, "gpt-4.1-nano", Price::Tokens { input: 0.1, output: 0.4 }),
+    (providers::OPENAI, "gpt-4.1-mini", Price::Tokens { input: 0.4, output: 1.6 }),
+    (providers::OPENAI, "gpt-4.1", Price::Tokens { input: 2.0, output: 8.0 }),
+    (providers::OPENAI, "gpt-4o-mini-transcribe", Price::AudioMinute(0.003)),
+    (providers::OPENAI, "gpt-4o-transcribe", Price::AudioMinute(0.006)),
+    (providers::OPENAI, "gpt-4o-mini", Price::Tokens { input: 0.15, output: 0.6 }),
+    (providers::OPENAI, "gpt-4o", Price::Tokens { input: 2.5, output: 10.0 }),
+    (providers::OPENAI, "gpt-4-turbo", Price::Tokens { input: 10.0, output: 30.0 }),
+    (providers::OPENAI, "gpt-4", Price::Tokens { input: 30.0, output: 60.0 }),
+    (providers::OPENAI, "gpt-3.5-turbo", Price::Tokens { input: 0.5, output: 1.5 }),
+    (providers::OPENAI, "o4-mini", Price::Tokens { input: 1.1, output: 4.4 }),
+    (providers::OPENAI, "o3-mini", Price::Tokens { input: 1.1, output: 4.4 }),
+    (providers::OPENAI, "o3", Price::Tokens { input: 2.0, output: 8.0 }),
+    (providers::OPENAI, "o1-mini", Price::Tokens { input: 1.1, output: 4.4 }),
+    (providers::OPENAI, "o1", Price::Tokens { input: 15.0, output: 60.0 }),
+    (providers::OPENAI, "text-embedding-3-small", Price::Tokens { input: 0.02, output: 0.0 }),
+    (providers::OPENAI, "text-embedding-3-large", Price::Tokens { input: 0.13, output: 0.0 }),
+    (providers::OPENAI, "text-embedding-ada-002", Price::Tokens { input: 0.1, output: 0.0 }),
+    (providers::OPENAI, "whisper-1", Price::AudioMinute(0.006)),
+    (providers::CLAUDE, "claude-opus-4", Price::Tokens { input: 15.0, output: 75.0 }),
+    (providers::CLAUDE, "claude-sonnet-4", Price::Tokens { input: 3.0, output: 15.0 }),
+    (providers::CLAUDE, "claude-haiku-4", Price::Tokens { input: 1.0, output: 5.0 }),
+    (providers::CLAUDE, "claude-3-7-sonnet", Price::Tokens { input: 3.0, output: 15.0 }),
+    (providers::CLAUDE, "claude-3-5-sonnet", Price::Tokens { input: 3.0, output: 15.0 }),
+    (providers::CLAUDE, "claude-3-5-haiku", Price::Tokens { input: 0.8, output: 4.0 }),
+    (providers::CLAUDE, "claude-3-opus", Price::Tokens { input: 15.0, output: 75.0 }),
+    (providers::CLAUDE, "claude-3-haiku", Price::Tokens { input: 0.25, output: 1.25 }),
+    (providers::GEMINI, "gemini-2.5-pro", Price::Tokens { input: 1.25, output: 10.0 }),
+    (providers::GEMINI, "gemini-2.5-flash-lite", Price::Tokens { input: 0.1, output: 0.4 }),
+    (providers::GEMINI, "gemini-2.5-flash", Price::Tokens { input: 0.3, output: 2.5 }),
+    (providers::GEMINI, "gemini-2.0-flash-lite", Price::Tokens { input: 0.075, output: 0.3 }),
+    (providers::GEMINI, "gemini-2.0-flash", Price::Tokens { input: 0.1, output: 0.4 }),
+    (providers::GEMINI, "gemini-1.5-flash", Price::Tokens { input: 0.075, output: 0.3 }),
+    (providers::GEMINI, "gemini-1.5-pro", Price::Tokens { input: 1.25, output: 5.0 }),
+    (providers::GROQ, "whisper-large-v3-turbo", Price::AudioMinute(0.04 / 60.0)),
+    (providers::GROQ, "distil-whisper", Price::AudioMinute(0.02 / 60.0)),
+    (providers::GROQ, "whisper-large-v3", Price::AudioMinute(0.111 / 60.0)),
+    (providers::GROQ, "llama-3.1-8b-instant", Price::Tokens { input: 0.05, output: 0.08 }),
+    (providers::GROQ, "llama-3.3-70b-versatile", Price::Tokens { input: 0.59, output: 0.79 }),
+    (providers::GROQ, "openai/gpt-oss-120b", Price::Tokens { input: 0.15, output: 0.75 }),
+    (providers::GROQ, "openai/gpt-oss-20b", Price::Tokens { input: 0.1, output: 0.5 }),
+    (providers::ASSEMBLYAI, "", Price::AudioMinute(0.37 / 60.0)),
+];
+
+/// One call to a cloud API
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApiUsage {
+    pub provider: &'static str,
+    pub model: String,
+    /// "chat", "transcription" or "embedding"
+    pub operation: &'static str,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub audio_seconds: f64,
+}
+
+impl ApiUsage {
+    pub fn tokens(
+        provider: &'static str,
+        model: &str,
+        operation: &'static str,
+        input_tokens: u64,
+        output_tokens: u64,
+    ) -> Self {
+        Self {
+            provider,
+            model: model.to_string(),
+            operation,
+            input_tokens,
+            output_tokens,
+            audio_seconds: 0.0,
+        }
+    }
+
+    pub fn audio(provider: &'static str, model: &str, audio_seconds: f64) -> Self {
+        Self {
+            provider,
+            model: model.to_string(),
+            operation: "transcription",
+            input_tokens: 0,
+            output_tokens: 0,
+            audio_seconds,
+        }
+    }
+}
+
+/// Estimated cost of a call in USD, if the model's price is known
+pub fn estimate_cost(usage: &ApiUsage) -> Option<f64> {
+    let model = usage.model.to_lowercase();
+    let (_, _, price) = PRICES
+        .iter()
+        .filter(|(provider, prefix, _)| *provider == usage.provider && model.starts_with(prefix))
+        .max_by_key(|(_, prefix, _)| prefix.len())?;
+    Some(match price {
+        Price::Tokens { input, output } => {
+            (usage.input_tokens as f64 * input + usage.output_tokens as f64 * output) / 1e6
+        }
+        Price::AudioMinute(per_minute) => usage.audio_seconds / 60.0 * per_minute,
+    })
+}
+
+/// Attribute the cloud API calls made while running `future` to a media file
+pub async fn for_media<F: Future>(media_path: &str, future: F) -> F::Output {
+    MEDIA_PATH.scope(media_path.to_string(), future).await
+}
+
+/// Record a cloud API call in the usage ledger, writing it on the blocking
+/// pool. Failures are only logged: the call itself already succeeded.
+pub async fn record_usage(usage: ApiUsage) {
+    let record = ApiUsageRecord {
+        cost: estimate_cost(&usage),
+        media_path: MEDIA_PATH.try_with(|path| path.clone()).ok(),
+        provider: usage.provider.to_string(),
+        model: usage.model,
+        operation: usage.operation.to_string(),
+        input_tokens: usage.input_tokens,
+        output_tokens: usage.output_tokens,
+        audio_seconds: usage.audio_seconds,
+    };
+    let provider = record.provider.clone();
+    let recorded =
+        tokio::task::spawn_blocking(move || Database::open()?.record_api_usage(&record)).await;
+    match recorded {
+        Ok(Ok(())) => {}
+        Ok(Err(e)) => log::warn!("[usage] Failed to record {} usage: {}", provider, e),
+        Err(e) => log::warn!("[usage] Usage task for {} failed: {}", provider, e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimate_cost() {
+        let model = "gpt-4o-mini-2024-07-18";
+        let chat = ApiUsage::tokens(providers::OPENAI, model, "chat", 1_000_000, 0);
+        // The longest prefix wins over "gpt-4o"
+        assert_eq!(estimate_cost(&chat), Some(0.15));
+
+        let claude = ApiUsage::tokens(providers::CLAUDE, "claude-sonnet-4-5", "chat", 1000, 1000);
+        assert!((estimate_cost(&claude).unwrap() - 0.018).abs() < 1e-9);
+
+        let audio = ApiUsage::audio(providers::OPENAI, "whisper-1", 600.0);
+        assert!((estimate_cost(&audio).unwrap() - 0.06).abs() < 1e-9);
+
+        let unknown = ApiUsage::tokens(providers::OPENAI_COMPATIBLE, "qwen2.5", "chat", 10, 10);
+        assert_eq!(estimate_cost(&unknown), None);
+    }
+}
@@ -0,0 +1,130 @@
+use std::path::{Path, PathBuf};
+
+/// Filesystems whose native change notifications are missing or unreliable
+/// (network shares and exFAT), so watchers have to poll instead
+const POLLING_FILESYSTEMS: &[&str] = &[
+    "nfs", "nfs4", "cifs", "smb", "smbfs", "smb2", "smb3", "afpfs", "webdav", "davfs",
+    "fuse.sshfs", "sshfs", "9p", "exfat", "fuse.exfat",
+];
+
+/// Whether changes below `path` can only be noticed by polling
+pub fn needs_polling(path: &Path) -> bool {
+    if is_unc_path(path) {
+        return true;
+    }
+    filesystem_type(path).is_some_and(|fs| is_polling_filesystem(&fs))
+}
+
+pub fn is_polling_filesystem(fs_type: &str) -> bool {
+    POLLING_FILESYSTEMS.contains(&fs_type.to_lowercase().as_str())
+}
+
+/// Windows network paths (`\\server\share`) never get reliable notifications
+fn is_unc_path(path: &Path) -> bool {
+    let path = path.to_string_lossy();
+    path.starts_with(r"\\") && !path.starts_with(r"\\?\")
+}
+
+/// Filesystem type of the volume containing `path`, if it can be determined
+#[cfg(target_os = "linux")]
+pub fn filesystem_type(path: &Path) -> Option<String> {
+    let path = path.canonicalize().ok()?;
+    let mounts = std::fs::read_to_string("/proc/mounts").ok()?;
+    fs_type_from_proc_mounts(&mounts, &path)
+}
+
+/// Filesystem type of the volume containing `path`, if it can be determined
+#[cfg(target_os = "macos")]
+pub fn filesystem_type(path: &Path) -> Option<String> {
+    let path = path.canonicalize().ok()?;
+    let output = std::process::Command::new("mount").output().ok()?;
+    fs_type_from_mount_output(&String::from_utf8_lossy(&output.stdout), &path)
+}
+
+/// Filesystem type of the volume containing `path`, if it can be determined
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+pub fn filesystem_type(_path: &Path) -> Option<String> {
+    None
+}
+
+/// Pick the filesystem type of the deepest mount point containing `path`
+fn deepest_mount<'a>(
+    mounts: impl Iterator<Item = (PathBuf, &'a str)>,
+    path: &Path,
+) -> Option<String> {
+    mounts
+        .filter(|(mount_point, _)| path.starts_with(mount_point))
+        .max_by_key(|(mount_point, _)| mount_point.components().count())
+        .map(|(_, fs_type)| fs_type.to_string())
+}
+
+/// Parse `/proc/mounts` lines (`device mount_point fs_type options ...`)
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn fs_type_from_proc_mounts(mounts: &str, path: &Path) -> Option<String> {
+    let entries = mounts.lines().filter_map(|line| {
+        let mut fields = line.split_whitespace();
+        let mount_point = fields.nth(1)?.replace("\\040", " ");
+        Some((PathBuf::from(mount_point), fields.next()?))
+    });
+    deepest_mount(entries, path)
+}
+
+/// Parse macOS `mount` output (`device on /mount/point (fs_type, options...)`)
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn fs_type_from_mount_output(output: &str, path: &Path) -> Option<String> {
+    let entries = output.lines().filter_map(|line| {
+        let (_, rest) = line.split_once(" on ")?;
+        let (mount_point, details) = rest.rsplit_once(" (")?;
+        let fs_type = details.split([',', ')']).next()?.trim();
+        Some((PathBuf::from(mount_point), fs_type))
+    });
+    deepest_mount(entries, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fs_type_from_proc_mounts() {
+        let mounts = "\
+/dev/sda1 / ext4 rw,relatime 0 0
+//nas/media /mnt/nas cifs rw,vers=3.0 0 0
+/dev/sdb1 /media/usb\\040stick exfat rw 0 0
+";
+        let fs = |p: &str| fs_type_from_proc_mounts(mounts, Path::new(p));
+
+        assert_eq!(fs("/home/user/clips").as_deref(), Some("ext4"));
+        assert_eq!(fs("/mnt/nas/footage").as_deref(), Some("cifs"));
+        assert_eq!(fs("/media/usb stick/DCIM").as_deref(), Some("exfat"));
+        // A sibling with a common string prefix is not inside the mount
+        assert_eq!(fs("/mnt/nas2").as_deref(), Some("ext4"));
+    }
+
+    #[test]
+    fn test_fs_type_from_mount_output() {
+        let output = "\
+/dev/disk3s1s1 on / (apfs, sealed, local, read-only, journaled)
+//user@nas._smb._tcp.local/Media on /Volumes/Media (smbfs, nodev, nosuid, mounted by user)
+/dev/disk4s1 on /Volumes/CARD (exfat, local, nodev, nosuid, noowners)
+";
+        let fs = |p: &str| fs_type_from_mount_output(output, Path::new(p));
+
+        assert_eq!(fs("/Users/me/Movies").as_deref(), Some("apfs"));
+        assert_eq!(fs("/Volumes/Media/raw").as_deref(), Some("smbfs"));
+        assert_eq!(fs("/Volumes/CARD").as_deref(), Some("exfat"));
+    }
+
+    #[test]
+    fn test_polling_filesystems() {
+        assert!(is_polling_filesystem("cifs"));
+        assert!(is_polling_filesystem("SMBFS"));
+        assert!(is_polling_filesystem("exfat"));
+        assert!(!is_polling_filesystem("ext4"));
+        assert!(!is_polling_filesystem("apfs"));
+
+        assert!(is_unc_path(Path::new(r"\\nas\media\clip.mp4")));
+        assert!(!is_unc_path(Path::new(r"\\?\C:\media")));
+        assert!(!is_unc_path(Path::new("/mnt/media")));
+    }
+}
@@ -1,11 +1,18 @@
 use crate::error::{AppError, Result};
+use crate::services::app_settings::AppSettings;
+use crate::services::concurrency::{self, JobKind};
+use crate::services::processes::{self, ProcessKind};
 use crate::services::download::DownloadService;
+use crate::services::http;
+use crate::services::os_path;
+use crate::services::whisper_server;
 use futures::StreamExt;
 use std::path::{Path, PathBuf};
 use std::process::Stdio;
 use tokio::fs::{self, File};
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
 use tokio::process::Command;
+use tokio_util::sync::CancellationToken;
 
 /// Whisper transcription service
 pub struct WhisperService {
@@ -19,6 +26,46 @@ pub struct TranscriptionSegment {
     pub start: f64,
     pub end: f64,
     pub text: String,
+    /// Speaker label, when known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub speaker: Option<String>,
+    /// Model confidence between 0 and 1, when the engine reports one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f64>,
+    /// Timing of each word, when the engine reports it
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub words: Vec<TranscriptionWord>,
+}
+
+#[cfg(test)]
+impl TranscriptionSegment {
+    /// A segment with only timing and text, for tests
+    pub(crate) fn new(start: f64, end: f64, text: &str) -> Self {
+        Self {
+            start,
+            end,
+            text: text.to_string(),
+            speaker: None,
+            confidence: None,
+            words: Vec::new(),
+        }
+    }
+
+    /// The segment labeled as said by `speaker`
+    pub(crate) fn with_speaker(self, speaker: &str) -> Self {
+        Self {
+            speaker: Some(speaker.to_string()),
+            ..self
+        }
+    }
+}
+
+/// A word of a segment with its own timestamps
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct TranscriptionWord {
+    pub start: f64,
+    pub end: f64,
+    pub text: String,
 }
 
 /// Full transcription result
@@ -31,17 +78,15 @@ pub struct TranscriptionResult {
 }
 
 impl WhisperService {
-    /// Create a new Whisper service
-    pub fn new() -> Result<Self> {
-        let download_service = DownloadService::new()?;
-
+    /// Create a new Whisper service that finds models with `download_service`
+    pub fn new(download_service: DownloadService) -> Self {
         // Try to find whisper.cpp binary
         let whisper_cpp_path = Self::find_whisper_cpp();
 
-        Ok(Self {
+        Self {
             whisper_cpp_path,
             download_service,
-        })
+        }
     }
 
     /// Find whisper.cpp binary in common locations
@@ -111,42 +156,74 @@ impl WhisperService {
         None
     }
 
+    /// whisper.cpp's `whisper-server`, for server mode
+    pub fn server_path(&self) -> Option<PathBuf> {
+        whisper_server::find_server(self.whisper_cpp_path.as_ref()?)
+    }
+
+    /// Model file of an installed model
+    pub async fn installed_model_path(&self, model_id: &str) -> Result<PathBuf> {
+        if !self.download_service.is_model_installed(model_id).await? {
+            return Err(AppError::ModelNotFound(format!("Model '{}' is not installed", model_id)));
+        }
+        Ok(self.download_service.get_model_path(model_id))
+    }
+
     /// Check if Whisper.cpp is available
     pub fn is_available(&self) -> bool {
         self.whisper_cpp_path.is_some()
     }
 
-    /// Transcribe an audio file using whisper.cpp
+    /// Transcribe an audio file using whisper.cpp.
+    /// Cancelling the token kills whisper.cpp and removes its partial output.
     pub async fn transcribe<F>(
         &self,
         audio_path: &Path,
         model_id: &str,
         language: Option<&str>,
+        cancel: &CancellationToken,
         on_progress: F,
     ) -> Result<TranscriptionResult>
     where
         F: Fn(f32) + Send + 'static,
     {
         let whisper_path = self.whisper_cpp_path.as_ref()
-            .ok_or_else(|| AppError::Whisper("whisper.cpp not found".to_string()))?;
-
-        // Check if model is installed
-        if !self.download_service.is_model_installed(model_id).await? {
-            return Err(AppError::ModelNotFound(format!("Model '{}' is not installed", model_id)));
+            .ok_or_else(|| AppError::MissingBinary("whisper.cpp".to_string()))?;
+
+        let model_path = self.installed_model_path(model_id).await?;
+        // whisper.cpp can only write to ASCII file names on Windows
+        let output_stem = os_path::ascii_stem(audio_path).unwrap_or_else(|| "transcript".to_string());
+        let output_path = audio_path.with_file_name(format!("{}.json", output_stem));
+
+        let _slot = concurrency::acquire(JobKind::Transcription, cancel).await?;
+
+        if AppSettings::load().unwrap_or_default().whisper_server_mode {
+            if let Some(server_path) = self.server_path() {
+                on_progress(0.0);
+                let result = whisper_server::transcribe(
+                    &server_path, &model_path, model_id, audio_path, language, cancel,
+                ).await;
+                match result {
+                    Ok(result) => {
+                        on_progress(100.0);
+                        return Ok(result);
+                    }
+                    Err(AppError::Cancelled) => return Err(AppError::Cancelled),
+                    Err(e) => log::warn!("[whisper.rs] whisper-server failed, using the CLI: {}", e),
+                }
+            }
         }
 
-        let model_path = self.download_service.get_model_path(model_id);
-        let output_path = audio_path.with_extension("json");
-
         // Build whisper.cpp command
         let mut cmd = Command::new(whisper_path);
-        cmd.args([
-            "-m", model_path.to_str().unwrap(),
-            "-f", audio_path.to_str().unwrap(),
-            "-oj",  // Output JSON
-            "-of", output_path.to_str().unwrap().trim_end_matches(".json"),
-            "-pp", // Print progress
-        ]);
+        cmd.arg("-m")
+            .arg(os_path::ascii_command_arg(&model_path))
+            .arg("-f")
+            .arg(os_path::ascii_command_arg(audio_path))
+            .arg("-ojf") // Output JSON with token probabilities
+            .arg("-of")
+            .arg(os_path::ascii_command_arg(&output_path.with_extension("")))
+            .arg("-pp"); // Print progress
 
         // Add language if specified
         if let Some(lang) = language {
@@ -156,15 +233,28 @@ impl WhisperService {
         let mut child = cmd
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
+            .kill_on_drop(true)
             .spawn()
-            .map_err(|e| AppError::Whisper(format!("Failed to start whisper: {}", e)))?;
+            .map_err(|e| AppError::spawn_failed("whisper.cpp", e, AppError::Whisper))?;
+        let file = Some(audio_path.to_string_lossy().to_string());
+        let _tracked = processes::track(&child, ProcessKind::Whisper, file, cancel);
 
         // Read progress from stderr
         if let Some(stderr) = child.stderr.take() {
             let reader = BufReader::new(stderr);
             let mut lines = reader.lines();
 
-            while let Ok(Some(line)) = lines.next_line().await {
+            loop {
+                let line = tokio::select! {
+                    _ = cancel.cancelled() => {
+                        let _ = child.kill().await;
+                        let _ = fs::remove_file(&output_path).await;
+                        return Err(AppError::Cancelled);
+                    }
+                    line = lines.next_line() => line,
+                };
+                let Ok(Some(line)) = line else { break };
+
                 // whisper.cpp outputs progress like "progress = 50%"
                 if line.contains("progress") {
                     if let Some(percent_str) = line.split('=').nth(1) {
@@ -189,6 +279,76 @@ impl WhisperService {
         self.parse_whisper_output(&output_path).await
     }
 
+    /// Mean probability of a segment's text tokens in whisper.cpp full JSON output.
+    /// Special tokens such as `[_BEG_]` and timestamps are left out.
+    fn segment_confidence(segment: &serde_json::Value) -> Option<f64> {
+        let probabilities: Vec<f64> = segment
+            .get("tokens")?
+            .as_array()?
+            .iter()
+            .filter(|token| {
+                token
+                    .get("text")
+                    .and_then(|t| t.as_str())
+                    .is_some_and(|t| !t.starts_with("[_"))
+            })
+            .filter_map(|token| token.get("p").and_then(|p| p.as_f64()))
+            .collect();
+
+        if probabilities.is_empty() {
+            return None;
+        }
+        Some(probabilities.iter().sum::<f64>() / probabilities.len() as f64)
+    }
+
+    /// Words of a segment in whisper.cpp full JSON output, joined from its text
+    /// tokens: a token starting with a space begins a new word.
+    fn segment_words(segment: &serde_json::Value) -> Vec<TranscriptionWord> {
+        let Some(tokens) = segment.get("tokens").and_then(|t| t.as_array()) else {
+            return Vec::new();
+        };
+
+        let mut words: Vec<TranscriptionWord> = Vec::new();
+        for token in tokens {
+            let Some(text) = token.get("text").and_then(|t| t.as_str()) else {
+                continue;
+            };
+            if text.starts_with("[_") {
+                continue;
+            }
+            let offset = |key: &str| {
+                token
+                    .get("offsets")
+                    .and_then(|o| o.get(key))
+                    .and_then(|ms| ms.as_i64())
+                    .map(|ms| ms as f64 / 1000.0)
+            };
+            let (Some(start), Some(end)) = (offset("from"), offset("to")) else {
+                continue;
+            };
+
+            match words.last_mut() {
+                Some(word) if !text.starts_with(' ') => {
+                    word.text.push_str(text);
+                    word.end = end;
+                }
+                _ => words.push(TranscriptionWord {
+                    start,
+                    end,
+                    text: text.to_string(),
+                }),
+            }
+        }
+
+        words
+            .into_iter()
+            .filter_map(|mut word| {
+                word.text = word.text.trim().to_string();
+                (!word.text.is_empty()).then_some(word)
+            })
+            .collect()
+    }
+
     /// Parse whisper.cpp JSON output
     async fn parse_whisper_output(&self, json_path: &Path) -> Result<TranscriptionResult> {
         let content = tokio::fs::read_to_string(json_path).await?;
@@ -240,7 +400,14 @@ impl WhisperService {
                     full_text.push_str(&text);
                     full_text.push(' ');
 
-                    segments.push(TranscriptionSegment { start, end, text });
+                    segments.push(TranscriptionSegment {
+                        start,
+                        end,
+                        text,
+                        speaker: None,
+                        confidence: Self::segment_confidence(segment),
+                        words: Self::segment_words(segment),
+                    });
                 }
             }
         } else {
@@ -327,7 +494,7 @@ impl WhisperService {
         }
         log::info!("[whisper.rs] Bin directory created/verified");
 
-        let client = reqwest::Client::new();
+        let client = http::download_client();
 
         // Download the zip file
         on_progress(5.0, "Downloading whisper.cpp...".to_string());
@@ -522,3 +689,91 @@ impl WhisperService {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A segment of `whisper-cli -ojf` output (full JSON with token details)
+    fn captured_segment() -> serde_json::Value {
+        serde_json::json!({
+            "timestamps": {"from": "00:00:00,000", "to": "00:00:02,560"},
+            "offsets": {"from": 0, "to": 2560},
+            "text": " Hello, world. It's fine.",
+            "tokens": [
+                {"text": "[_BEG_]", "timestamps": {"from": "00:00:00,000", "to": "00:00:00,000"},
+                 "offsets": {"from": 0, "to": 0}, "id": 50364, "p": 0.98, "t_dtw": -1},
+                {"text": " Hello", "timestamps": {"from": "00:00:00,000", "to": "00:00:00,480"},
+                 "offsets": {"from": 0, "to": 480}, "id": 2425, "p": 0.91, "t_dtw": -1},
+                {"text": ",", "timestamps": {"from": "00:00:00,480", "to": "00:00:00,560"},
+                 "offsets": {"from": 480, "to": 560}, "id": 11, "p": 0.87, "t_dtw": -1},
+                {"text": " world", "timestamps": {"from": "00:00:00,560", "to": "00:00:01,040"},
+                 "offsets": {"from": 560, "to": 1040}, "id": 1002, "p": 0.95, "t_dtw": -1},
+                {"text": ".", "timestamps": {"from": "00:00:01,040", "to": "00:00:01,120"},
+                 "offsets": {"from": 1040, "to": 1120}, "id": 13, "p": 0.99, "t_dtw": -1},
+                {"text": " It", "timestamps": {"from": "00:00:01,400", "to": "00:00:01,600"},
+                 "offsets": {"from": 1400, "to": 1600}, "id": 467, "p": 0.82, "t_dtw": -1},
+                {"text": "'s", "timestamps": {"from": "00:00:01,600", "to": "00:00:01,760"},
+                 "offsets": {"from": 1600, "to": 1760}, "id": 311, "p": 0.9, "t_dtw": -1},
+                {"text": " fine", "timestamps": {"from": "00:00:01,760", "to": "00:00:02,400"},
+                 "offsets": {"from": 1760, "to": 2400}, "id": 2489, "p": 0.77, "t_dtw": -1},
+                {"text": ".", "timestamps": {"from": "00:00:02,400", "to": "00:00:02,560"},
+                 "offsets": {"from": 2400, "to": 2560}, "id": 13, "p": 0.96, "t_dtw": -1},
+                {"text": "[_TT_128]", "timestamps": {"from": "00:00:02,560", "to": "00:00:02,560"},
+                 "offsets": {"from": 2560, "to": 2560}, "id": 50492, "p": 0.41, "t_dtw": -1}
+            ]
+        })
+    }
+
+    #[test]
+    fn test_segment_words_joins_tokens() {
+        let words = WhisperService::segment_words(&captured_segment());
+        let words: Vec<(&str, f64, f64)> = words
+            .iter()
+            .map(|w| (w.text.as_str(), w.start, w.end))
+            .collect();
+        assert_eq!(
+            words,
+            [
+                ("Hello,", 0.0, 0.56),
+                ("world.", 0.56, 1.12),
+                ("It's", 1.4, 1.76),
+                ("fine.", 1.76, 2.56),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_segment_words_without_leading_space() {
+        // The first token of a segment does not always start with a space,
+        // and a lone space token makes no word
+        let segment = serde_json::json!({
+            "tokens": [
+                {"text": "Okay", "offsets": {"from": 0, "to": 300}, "p": 0.9},
+                {"text": " ", "offsets": {"from": 300, "to": 320}, "p": 0.5},
+                {"text": " then", "offsets": {"from": 320, "to": 600}, "p": 0.8}
+            ]
+        });
+        let words = WhisperService::segment_words(&segment);
+        let texts: Vec<&str> = words.iter().map(|w| w.text.as_str()).collect();
+        assert_eq!(texts, ["Okay", "then"]);
+        assert!(WhisperService::segment_words(&serde_json::json!({})).is_empty());
+    }
+
+    #[test]
+    fn test_segment_confidence_averages_text_tokens() {
+        // `[_BEG_]` (0.98) and `[_TT_128]` (0.41) are left out
+        let confidence = WhisperService::segment_confidence(&captured_segment()).unwrap();
+        let expected = (0.91 + 0.87 + 0.95 + 0.99 + 0.82 + 0.9 + 0.77 + 0.96) / 8.0;
+        assert!((confidence - expected).abs() < 1e-9);
+
+        // No text tokens, or no tokens at all: no confidence
+        let special_only = serde_json::json!({
+            "tokens": [{"text": "[_BEG_]", "p": 0.98}, {"text": "[_TT_50]", "p": 0.5}]
+        });
+        assert_eq!(WhisperService::segment_confidence(&special_only), None);
+        let empty = serde_json::json!({"tokens": []});
+        assert_eq!(WhisperService::segment_confidence(&empty), None);
+        assert_eq!(WhisperService::segment_confidence(&serde_json::json!({})), None);
+    }
+}
@@ -0,0 +1,501 @@
+//! whisper.cpp's `whisper-server`, kept running in the background so the model
+//! is loaded once instead of for every transcription.
+
+use crate::error::{AppError, Result};
+use crate::services::os_path;
+use crate::services::processes::{self, ProcessGuard, ProcessKind};
+use crate::services::whisper::{TranscriptionResult, TranscriptionSegment, TranscriptionWord};
+use reqwest::multipart;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::{Duration, Instant};
+use tokio::process::{Child, Command};
+use tokio::sync::Mutex;
+use tokio_util::sync::CancellationToken;
+
+/// How long a server may take to load its model and start listening
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(180);
+
+/// Pause between checks whether a starting server listens yet
+const STARTUP_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+struct RunningServer {
+    child: Child,
+    pid: u32,
+    port: u16,
+    model_id: String,
+    /// Cancelled to stop the server, e.g. from the process list
+    cancel: CancellationToken,
+    _tracked: ProcessGuard,
+}
+
+/// The running server. Held during a request, so requests run one at a time
+/// as whisper-server handles them anyway.
+static SERVER: Mutex<Option<RunningServer>> = Mutex::const_new(None);
+
+/// What the UI shows about the server
+#[derive(Debug, Clone, Serialize)]
+pub struct ServerStatus {
+    pub pid: u32,
+    pub port: u16,
+    pub model_id: String,
+}
+
+/// `whisper-server` next to the whisper.cpp CLI, else from PATH
+pub fn find_server(cli_path: &Path) -> Option<PathBuf> {
+    let name = if cfg!(windows) { "whisper-server.exe" } else { "whisper-server" };
+    cli_path
+        .parent()
+        .map(|dir| dir.join(name))
+        .filter(|path| path.exists())
+        .or_else(|| which::which(name).ok())
+}
+
+/// Client for the local server: never through the configured proxy, and
+/// without a request timeout since long files take a while
+fn client() -> Result<reqwest::Client> {
+    Ok(reqwest::Client::builder().no_proxy().build()?)
+}
+
+fn free_port() -> Result<u16> {
+    let listener = std::net::TcpListener::bind(("127.0.0.1", 0))?;
+    Ok(listener.local_addr()?.port())
+}
+
+async fn spawn_server(server_path: &Path, model_path: &Path, model_id: &str) -> Result<RunningServer> {
+    let port = free_port()?;
+    log::info!("[whisper_server] Starting {:?} with model {} on port {}", server_path, model_id, port);
+    let child = Command::new(server_path)
+        .arg("-m")
+        .arg(os_path::ascii_command_arg(model_path))
+        .args(["--host", "127.0.0.1", "--port", &port.to_string()])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| AppError::spawn_failed("whisper-server", e, AppError::Whisper))?;
+
+    let cancel = CancellationToken::new();
+    let tracked = processes::track(&child, ProcessKind::Whisper, None, &cancel);
+    let pid = child.id().unwrap_or_default();
+
+    // Stop the server when its token is cancelled while it is idle
+    let token = cancel.clone();
+    tauri::async_runtime::spawn(async move {
+        token.cancelled().await;
+        stop_pid(pid).await;
+    });
+
+    Ok(RunningServer {
+        child,
+        pid,
+        port,
+        model_id: model_id.to_string(),
+        cancel,
+        _tracked: tracked,
+    })
+}
+
+/// Wait until the server answers HTTP, failing if it exits or takes too long
+async fn wait_until_ready(server: &mut RunningServer, client: &reqwest::Client) -> Result<()> {
+    let url = format!("http://127.0.0.1:{}/", server.port);
+    let started = Instant::now();
+    loop {
+        if let Some(status) = server.child.try_wait()? {
+            return Err(AppError::Whisper(format!("whisper-server exited on startup ({})", status)));
+        }
+        if client.get(&url).send().await.is_ok() {
+            log::info!("[whisper_server] Ready after {:.1}s", started.elapsed().as_secs_f64());
+            return Ok(());
+        }
+        if started.elapsed() > STARTUP_TIMEOUT {
+            return Err(AppError::Whisper("whisper-server did not start in time".to_string()));
+        }
+        tokio::select! {
+            _ = server.cancel.cancelled() => return Err(AppError::Cancelled),
+            _ = tokio::time::sleep(STARTUP_POLL_INTERVAL) => {}
+        }
+    }
+}
+
+/// Make sure a server with `model_id` runs, replacing one with another model
+async fn ensure_running<'a>(
+    slot: &'a mut Option<RunningServer>,
+    server_path: &Path,
+    model_path: &Path,
+    model_id: &str,
+    client: &reqwest::Client,
+) -> Result<&'a mut RunningServer> {
+    let reusable = match slot.as_mut() {
+        Some(server) => {
+            server.model_id == model_id
+                && !server.cancel.is_cancelled()
+                && matches!(server.child.try_wait(), Ok(None))
+        }
+        None => false,
+    };
+    if !reusable {
+        if let Some(mut old) = slot.take() {
+            let _ = old.child.kill().await;
+        }
+        let mut server = spawn_server(server_path, model_path, model_id).await?;
+        if let Err(e) = wait_until_ready(&mut server, client).await {
+            let _ = server.child.kill().await;
+            return Err(e);
+        }
+        *slot = Some(server);
+    }
+    Ok(slot.as_mut().expect("server was just started"))
+}
+
+/// Transcribe a WAV file with a server running `model_id`, starting it (or
+/// switching its model) first if needed. Cancelling kills the server, since
+/// it can't drop a request in progress.
+pub async fn transcribe(
+    server_path: &Path,
+    model_path: &Path,
+    model_id: &str,
+    audio_path: &Path,
+    language: Option<&str>,
+    cancel: &CancellationToken,
+) -> Result<TranscriptionResult> {
+    let client = client()?;
+    let mut slot = tokio::select! {
+        _ = cancel.cancelled() => return Err(AppError::Cancelled),
+        slot = SERVER.lock() => slot,
+    };
+
+    let started = tokio::select! {
+        _ = cancel.cancelled() => None,
+        started = ensure_running(&mut slot, server_path, model_path, model_id, &client) => Some(started),
+    };
+    let server = match started {
+        Some(started) => started?,
+        None => {
+            if let Some(mut server) = slot.take() {
+                let _ = server.child.kill().await;
+            }
+            return Err(AppError::Cancelled);
+        }
+    };
+
+    let audio = tokio::fs::read(audio_path).await?;
+    let file_name = audio_path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "audio.wav".to_string());
+    let mut form = multipart::Form::new()
+        .part("file", multipart::Part::bytes(audio).file_name(file_name))
+        .text("response_format", "verbose_json")
+        .text("temperature", "0.0");
+    if let Some(language) = language {
+        form = form.text("language", language.to_string());
+    }
+
+    let url = format!("http://127.0.0.1:{}/inference", server.port);
+    let request = client.post(&url).multipart(form).send();
+    let response = tokio::select! {
+        _ = cancel.cancelled() => None,
+        _ = server.cancel.cancelled() => None,
+        response = request => Some(response),
+    };
+    let Some(response) = response else {
+        if let Some(mut server) = slot.take() {
+            let _ = server.child.kill().await;
+        }
+        return Err(AppError::Cancelled);
+    };
+
+    let response = response?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        return Err(AppError::Whisper(format!("whisper-server error {}: {}", status, body)));
+    }
+    let json: serde_json::Value = response.json().await?;
+    // whisper-server reports failures as `{"error": ...}` with status 200
+    if let Some(error) = json.get("error").and_then(|e| e.as_str()) {
+        return Err(AppError::Whisper(format!("whisper-server error: {}", error)));
+    }
+    Ok(parse_verbose_json(&json))
+}
+
+/// Languages by the full name whisper-server reports, with the code whisper.cpp
+/// uses for them elsewhere (e.g. in `-oj` output and the `language` option)
+const LANGUAGE_CODES: &[(&str, &str)] = &[
+    ("english", "en"),
+    ("chinese", "zh"),
+    ("german", "de"),
+    ("spanish", "es"),
+    ("russian", "ru"),
+    ("korean", "ko"),
+    ("french", "fr"),
+    ("japanese", "ja"),
+    ("portuguese", "pt"),
+    ("turkish", "tr"),
+    ("polish", "pl"),
+    ("catalan", "ca"),
+    ("dutch", "nl"),
+    ("arabic", "ar"),
+    ("swedish", "sv"),
+    ("italian", "it"),
+    ("indonesian", "id"),
+    ("hindi", "hi"),
+    ("finnish", "fi"),
+    ("vietnamese", "vi"),
+    ("hebrew", "he"),
+    ("ukrainian", "uk"),
+    ("greek", "el"),
+    ("malay", "ms"),
+    ("czech", "cs"),
+    ("romanian", "ro"),
+    ("danish", "da"),
+    ("hungarian", "hu"),
+    ("tamil", "ta"),
+    ("norwegian", "no"),
+    ("thai", "th"),
+    ("urdu", "ur"),
+    ("croatian", "hr"),
+    ("bulgarian", "bg"),
+    ("lithuanian", "lt"),
+    ("latin", "la"),
+    ("maori", "mi"),
+    ("malayalam", "ml"),
+    ("welsh", "cy"),
+    ("slovak", "sk"),
+    ("telugu", "te"),
+    ("persian", "fa"),
+    ("latvian", "lv"),
+    ("bengali", "bn"),
+    ("serbian", "sr"),
+    ("azerbaijani", "az"),
+    ("slovenian", "sl"),
+    ("kannada", "kn"),
+    ("estonian", "et"),
+    ("macedonian", "mk"),
+    ("breton", "br"),
+    ("basque", "eu"),
+    ("icelandic", "is"),
+    ("armenian", "hy"),
+    ("nepali", "ne"),
+    ("mongolian", "mn"),
+    ("bosnian", "bs"),
+    ("kazakh", "kk"),
+    ("albanian", "sq"),
+    ("swahili", "sw"),
+    ("galician", "gl"),
+    ("marathi", "mr"),
+    ("punjabi", "pa"),
+    ("sinhala", "si"),
+    ("khmer", "km"),
+    ("shona", "sn"),
+    ("yoruba", "yo"),
+    ("somali", "so"),
+    ("afrikaans", "af"),
+    ("occitan", "oc"),
+    ("georgian", "ka"),
+    ("belarusian", "be"),
+    ("tajik", "tg"),
+    ("sindhi", "sd"),
+    ("gujarati", "gu"),
+    ("amharic", "am"),
+    ("yiddish", "yi"),
+    ("lao", "lo"),
+    ("uzbek", "uz"),
+    ("faroese", "fo"),
+    ("haitian creole", "ht"),
+    ("pashto", "ps"),
+    ("turkmen", "tk"),
+    ("nynorsk", "nn"),
+    ("maltese", "mt"),
+    ("sanskrit", "sa"),
+    ("luxembourgish", "lb"),
+    ("myanmar", "my"),
+    ("tibetan", "bo"),
+    ("tagalog", "tl"),
+    ("malagasy", "mg"),
+    ("assamese", "as"),
+    ("tatar", "tt"),
+    ("hawaiian", "haw"),
+    ("lingala", "ln"),
+    ("hausa", "ha"),
+    ("bashkir", "ba"),
+    ("javanese", "jw"),
+    ("sundanese", "su"),
+    ("cantonese", "yue"),
+];
+
+/// Code of a language whisper-server reports by name, e.g. "en" for "english".
+/// Names it does not know are kept.
+fn language_code(name: &str) -> String {
+    let name = name.to_lowercase();
+    LANGUAGE_CODES
+        .iter()
+        .find(|(full, _)| *full == name)
+        .map_or(name, |(_, code)| code.to_string())
+}
+
+/// Turn whisper-server's `verbose_json` response into a transcription
+fn parse_verbose_json(json: &serde_json::Value) -> TranscriptionResult {
+    let segments: Vec<TranscriptionSegment> = json
+        .get("segments")
+        .and_then(|s| s.as_array())
+        .map(|segments| {
+            segments
+                .iter()
+                .filter_map(|segment| {
+                    let text = segment.get("text")?.as_str()?.trim().to_string();
+                    if text.is_empty() {
+                        return None;
+                    }
+                    Some(TranscriptionSegment {
+                        start: segment.get("start")?.as_f64()?,
+                        end: segment.get("end")?.as_f64()?,
+                        text,
+                        speaker: None,
+                        // Mean log probability of the tokens, as a probability
+                        confidence: segment
+                            .get("avg_logprob")
+                            .and_then(|p| p.as_f64())
+                            .map(|p| p.exp().clamp(0.0, 1.0)),
+                        words: parse_words(segment),
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let full_text = segments
+        .iter()
+        .map(|s| s.text.as_str())
+        .collect::<Vec<_>>()
+        .join(" ");
+    let duration = json
+        .get("duration")
+        .and_then(|d| d.as_f64())
+        .or_else(|| segments.last().map(|s| s.end))
+        .unwrap_or(0.0);
+
+    TranscriptionResult {
+        segments,
+        full_text,
+        language: json
+            .get("language")
+            .and_then(|l| l.as_str())
+            .map(language_code),
+        duration,
+    }
+}
+
+/// Word timings of a `verbose_json` segment
+fn parse_words(segment: &serde_json::Value) -> Vec<TranscriptionWord> {
+    let Some(words) = segment.get("words").and_then(|w| w.as_array()) else {
+        return Vec::new();
+    };
+    words
+        .iter()
+        .filter_map(|word| {
+            let text = word.get("word")?.as_str()?.trim().to_string();
+            if text.is_empty() {
+                return None;
+            }
+            Some(TranscriptionWord {
+                start: word.get("start")?.as_f64()?,
+                end: word.get("end")?.as_f64()?,
+                text,
+            })
+        })
+        .collect()
+}
+
+/// Start a server with `model_id` ahead of the first transcription
+pub async fn preload(server_path: &Path, model_path: &Path, model_id: &str) -> Result<()> {
+    let client = client()?;
+    let mut slot = SERVER.lock().await;
+    ensure_running(&mut slot, server_path, model_path, model_id, &client).await?;
+    Ok(())
+}
+
+/// The running server, if any
+pub async fn status() -> Option<ServerStatus> {
+    let mut slot = SERVER.lock().await;
+    let server = slot.as_mut()?;
+    if !matches!(server.child.try_wait(), Ok(None)) {
+        *slot = None;
+        return None;
+    }
+    Some(ServerStatus {
+        pid: server.pid,
+        port: server.port,
+        model_id: server.model_id.clone(),
+    })
+}
+
+/// Stop the running server. Returns false if none was running.
+pub async fn stop() -> bool {
+    let Some(mut server) = SERVER.lock().await.take() else {
+        return false;
+    };
+    log::info!("[whisper_server] Stopping server {}", server.pid);
+    let _ = server.child.kill().await;
+    true
+}
+
+/// Stop the server if it is still the one with `pid`
+async fn stop_pid(pid: u32) {
+    let mut slot = SERVER.lock().await;
+    if slot.as_ref().is_some_and(|server| server.pid == pid) {
+        if let Some(mut server) = slot.take() {
+            let _ = server.child.kill().await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_language_code() {
+        assert_eq!(language_code("korean"), "ko");
+        assert_eq!(language_code("Haitian Creole"), "ht");
+        assert_eq!(language_code("en"), "en");
+    }
+
+    #[test]
+    fn test_parse_verbose_json() {
+        let response = json!({
+            "task": "transcribe",
+            "language": "english",
+            "duration": 12.5,
+            "segments": [
+                {
+                    "start": 0.0, "end": 4.2, "text": " Hello there.", "avg_logprob": -0.1,
+                    "words": [
+                        { "word": " Hello", "start": 0.0, "end": 1.5 },
+                        { "word": " there.", "start": 1.5, "end": 4.2 }
+                    ]
+                },
+                { "start": 4.2, "end": 5.0, "text": " " },
+                { "start": 5.0, "end": 9.8, "text": " General Kenobi." }
+            ]
+        });
+        let result = parse_verbose_json(&response);
+        assert_eq!(result.segments.len(), 2);
+        assert_eq!(result.full_text, "Hello there. General Kenobi.");
+        assert_eq!(result.duration, 12.5);
+        assert_eq!(result.language.as_deref(), Some("en"));
+        let confidence = result.segments[0].confidence.unwrap();
+        assert!((confidence - (-0.1f64).exp()).abs() < 1e-9);
+        assert_eq!(result.segments[1].confidence, None);
+        assert_eq!(result.segments[0].words.len(), 2);
+        assert_eq!(result.segments[0].words[1].text, "there.");
+        assert!(result.segments[1].words.is_empty());
+
+        // Without a duration, the end of the last segment
+        let result = parse_verbose_json(&json!({ "segments": [{ "start": 1.0, "end": 2.0, "text": "Hi" }] }));
+        assert_eq!(result.duration, 2.0);
+    }
+}
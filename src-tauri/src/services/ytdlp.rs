@@ -0,0 +1,277 @@
+use crate::error::{AppError, Result};
+use crate::services::concurrency::{self, JobKind};
+use crate::services::ffmpeg::find_ffmpeg_path;
+use crate::services::os_path;
+use crate::services::processes::{self, ProcessKind};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::process::Command;
+use tokio_util::sync::CancellationToken;
+
+/// Output file name template: `<title> [<id>].<ext>`, so repeated downloads of
+/// the same video land on the same file
+const OUTPUT_TEMPLATE: &str = "%(title).150B [%(id)s].%(ext)s";
+
+/// Find yt-dlp binary path: a copy bundled next to the app or installed into
+/// the app data directory first, then common installation locations
+fn find_ytdlp_path() -> PathBuf {
+    let binary_name = if cfg!(target_os = "windows") { "yt-dlp.exe" } else { "yt-dlp" };
+
+    let mut possible_paths: Vec<PathBuf> = Vec::new();
+
+    // Bundled alongside the app executable
+    if let Ok(exe) = std::env::current_exe() {
+        if let Some(exe_dir) = exe.parent() {
+            possible_paths.push(exe_dir.join(binary_name));
+        }
+    }
+
+    // Installed into the app data directory
+    if let Some(data_dir) = dirs::data_local_dir() {
+        possible_paths.push(data_dir.join("clip-flow").join("bin").join(binary_name));
+    }
+
+    // macOS: Homebrew paths
+    #[cfg(target_os = "macos")]
+    {
+        possible_paths.push(PathBuf::from("/opt/homebrew/bin/yt-dlp")); // Apple Silicon
+        possible_paths.push(PathBuf::from("/usr/local/bin/yt-dlp"));    // Intel Mac
+    }
+
+    // Linux: Standard paths
+    #[cfg(target_os = "linux")]
+    {
+        possible_paths.push(PathBuf::from("/usr/bin/yt-dlp"));
+        possible_paths.push(PathBuf::from("/usr/local/bin/yt-dlp"));
+    }
+
+    for path in possible_paths {
+        if path.exists() {
+            log::info!("[ytdlp.rs] Found yt-dlp at: {:?}", path);
+            return path;
+        }
+    }
+
+    // Fallback: Try PATH (pip/pipx installs, winget, dev mode)
+    if let Ok(path) = which::which(binary_name) {
+        log::info!("[ytdlp.rs] Found yt-dlp in PATH: {:?}", path);
+        return path;
+    }
+
+    log::warn!("[ytdlp.rs] yt-dlp not found, using default: {}", binary_name);
+    PathBuf::from(binary_name)
+}
+
+/// Progress of a URL download, emitted as `ytdlp:progress`
+#[derive(Debug, Clone, Serialize)]
+pub struct MediaDownloadProgress {
+    pub url: String,
+    /// Percentage of the current file (0-100)
+    pub progress: f32,
+    /// "downloading" or "processing" (merging/remuxing after download)
+    pub stage: String,
+}
+
+/// yt-dlp service for downloading media from YouTube, Vimeo, podcasts and
+/// the other sites yt-dlp supports
+pub struct YtDlpService;
+
+impl YtDlpService {
+    /// Check if yt-dlp is available on the system
+    pub async fn check_availability() -> Result<bool> {
+        let output = Command::new(find_ytdlp_path())
+            .arg("--version")
+            .output()
+            .await;
+
+        match output {
+            Ok(o) => Ok(o.status.success()),
+            Err(_) => Ok(false),
+        }
+    }
+
+    /// Get yt-dlp version string
+    pub async fn get_version() -> Result<String> {
+        let output = Command::new(find_ytdlp_path())
+            .arg("--version")
+            .output()
+            .await
+            .map_err(|e| AppError::spawn_failed("yt-dlp", e, AppError::Download))?;
+
+        if output.status.success() {
+            Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+        } else {
+            Err(AppError::Download("yt-dlp not available".to_string()))
+        }
+    }
+
+    /// Download the media at `url` into `output_dir` and return the saved file.
+    /// With `audio_only`, only the best audio stream is fetched (enough for
+    /// transcription and much smaller). Cancelling the token kills yt-dlp.
+    pub async fn download<F>(
+        url: &str,
+        output_dir: &Path,
+        audio_only: bool,
+        cancel: &CancellationToken,
+        on_progress: F,
+    ) -> Result<PathBuf>
+    where
+        F: Fn(MediaDownloadProgress) + Send + 'static,
+    {
+        if !url.starts_with("http://") && !url.starts_with("https://") {
+            return Err(AppError::InvalidInput(format!("Not a web URL: {}", url)));
+        }
+        tokio::fs::create_dir_all(output_dir).await?;
+
+        let _slot = concurrency::acquire(JobKind::Download, cancel).await?;
+
+        let output_template = output_dir.join(OUTPUT_TEMPLATE);
+        let format = if audio_only { "bestaudio/best" } else { "bv*+ba/b" };
+
+        let mut child = Command::new(find_ytdlp_path())
+            .args(["--newline", "--progress", "--no-playlist", "--no-part"])
+            // Print file paths as UTF-8, not in the console code page
+            .args(["--encoding", "utf-8"])
+            .args(["--print", "after_move:filepath"])
+            .args(["-f", format])
+            .arg("--ffmpeg-location")
+            .arg(find_ffmpeg_path())
+            .arg("-o")
+            .arg(os_path::command_arg(&output_template))
+            .arg("--")
+            .arg(url)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| AppError::spawn_failed("yt-dlp", e, AppError::Download))?;
+        let _tracked = processes::track(&child, ProcessKind::YtDlp, Some(url.to_string()), cancel);
+
+        // Collect stderr in the background so a full pipe never blocks yt-dlp
+        let stderr = child.stderr.take().map(|stderr| {
+            tokio::spawn(async move {
+                let mut lines = BufReader::new(stderr).lines();
+                let mut last_error = String::new();
+                while let Ok(Some(line)) = lines.next_line().await {
+                    if line.starts_with("ERROR:") {
+                        last_error = line;
+                    }
+                }
+                last_error
+            })
+        });
+
+        let mut saved_path = None;
+        if let Some(stdout) = child.stdout.take() {
+            let mut lines = BufReader::new(stdout).lines();
+
+            loop {
+                let line = tokio::select! {
+                    _ = cancel.cancelled() => {
+                        let _ = child.kill().await;
+                        return Err(AppError::Cancelled);
+                    }
+                    line = lines.next_line() => line,
+                };
+                let Ok(Some(line)) = line else { break };
+
+                match parse_output_line(&line) {
+                    OutputLine::Progress(progress) => on_progress(MediaDownloadProgress {
+                        url: url.to_string(),
+                        progress,
+                        stage: "downloading".to_string(),
+                    }),
+                    OutputLine::Processing => on_progress(MediaDownloadProgress {
+                        url: url.to_string(),
+                        progress: 100.0,
+                        stage: "processing".to_string(),
+                    }),
+                    OutputLine::Other => {
+                        let candidate = PathBuf::from(line.trim());
+                        if candidate.starts_with(output_dir) {
+                            saved_path = Some(candidate);
+                        }
+                    }
+                }
+            }
+        }
+
+        let status = child.wait().await
+            .map_err(|e| AppError::Download(format!("yt-dlp process error: {}", e)))?;
+        let last_error = match stderr {
+            Some(task) => task.await.unwrap_or_default(),
+            None => String::new(),
+        };
+
+        if !status.success() {
+            return Err(AppError::Download(if last_error.is_empty() {
+                "yt-dlp download failed".to_string()
+            } else {
+                last_error
+            }));
+        }
+
+        saved_path
+            .filter(|p| p.exists())
+            .ok_or_else(|| AppError::Download("yt-dlp did not report the saved file".to_string()))
+    }
+}
+
+/// A line of yt-dlp's stdout
+#[derive(Debug, PartialEq)]
+enum OutputLine {
+    /// `[download]  42.3% of ...`
+    Progress(f32),
+    /// Post-processing after the download, e.g. `[Merger] Merging formats into ...`
+    Processing,
+    /// Anything else, including the printed final file path
+    Other,
+}
+
+fn parse_output_line(line: &str) -> OutputLine {
+    let line = line.trim();
+    if let Some(rest) = line.strip_prefix("[download]") {
+        return rest
+            .split_whitespace()
+            .next()
+            .and_then(|token| token.strip_suffix('%'))
+            .and_then(|percent| percent.parse::<f32>().ok())
+            .map(|percent| OutputLine::Progress(percent.clamp(0.0, 100.0)))
+            .unwrap_or(OutputLine::Other);
+    }
+    if ["[Merger]", "[ExtractAudio]", "[FixupM3u8]", "[VideoRemuxer]"]
+        .iter()
+        .any(|prefix| line.starts_with(prefix))
+    {
+        return OutputLine::Processing;
+    }
+    OutputLine::Other
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_output_line() {
+        assert_eq!(
+            parse_output_line("[download]  42.3% of   10.00MiB at    2.00MiB/s ETA 00:03"),
+            OutputLine::Progress(42.3)
+        );
+        assert_eq!(
+            parse_output_line("[download] 100% of   10.00MiB in 00:00:05"),
+            OutputLine::Progress(100.0)
+        );
+        assert_eq!(
+            parse_output_line("[download] Destination: /tmp/video [abc].webm"),
+            OutputLine::Other
+        );
+        assert_eq!(
+            parse_output_line("[Merger] Merging formats into \"/tmp/video [abc].mkv\""),
+            OutputLine::Processing
+        );
+        assert_eq!(parse_output_line("/tmp/video [abc].mkv"), OutputLine::Other);
+    }
+}
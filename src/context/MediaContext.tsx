@@ -12,6 +12,7 @@ import {
   startWatchingDirectory,
   stopWatchingDirectory,
   onFileChange,
+  getErrorMessage,
   type DirectoryNode,
   type FileChangeEvent,
 } from '@/lib/tauri';
@@ -475,7 +476,7 @@ export function MediaProvider({ children }: MediaProviderProps) {
       dispatch({ type: 'SET_ROOT_FOLDER', payload: folder });
       dispatch({ type: 'SET_ERROR', payload: null });
     } catch (error) {
-      dispatch({ type: 'SET_ERROR', payload: error instanceof Error ? error.message : 'Failed to scan directory' });
+      dispatch({ type: 'SET_ERROR', payload: getErrorMessage(error) });
     } finally {
       dispatch({ type: 'SET_LOADING', payload: false });
     }
@@ -612,7 +613,7 @@ export function MediaProvider({ children }: MediaProviderProps) {
 
       dispatch({ type: 'SET_ERROR', payload: null });
     } catch (error) {
-      dispatch({ type: 'SET_ERROR', payload: error instanceof Error ? error.message : 'Failed to set directory' });
+      dispatch({ type: 'SET_ERROR', payload: getErrorMessage(error) });
       dispatch({ type: 'SET_ROOT_PATH', payload: null });
       dispatch({ type: 'SET_ROOT_FOLDER', payload: null });
     } finally {
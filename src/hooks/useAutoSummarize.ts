@@ -8,6 +8,7 @@ import {
 	claudeSummarize,
 	checkOllama,
 	getApiKeyStatus,
+	getErrorMessage,
 } from "@/lib/tauri";
 
 type SummarizationMethod = "ollama" | "openai" | "claude" | "none";
@@ -176,16 +177,7 @@ export function useAutoSummarize() {
 				setSummary(filePath, summary);
 			} catch (error) {
 				console.error("[AutoSummarize] Error:", error);
-				// Handle both Error instances and string errors (from Tauri)
-				let errorMessage = "Unknown error";
-				if (error instanceof Error) {
-					errorMessage = error.message;
-				} else if (typeof error === "string") {
-					errorMessage = error;
-				} else if (error && typeof error === "object" && "message" in error) {
-					errorMessage = String((error as { message: unknown }).message);
-				}
-				updateSummaryStatus(filePath, "error", errorMessage);
+				updateSummaryStatus(filePath, "error", getErrorMessage(error));
 			} finally {
 				processingRef.current.delete(filePath);
 			}
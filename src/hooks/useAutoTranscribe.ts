@@ -11,6 +11,8 @@ import {
   getApiKeyStatus,
   openaiTranscribe,
   type TranscriptionProgress,
+  getErrorCode,
+  getErrorMessage,
 } from '@/lib/tauri';
 
 const DEFAULT_MODEL_ID = 'base';
@@ -35,18 +37,11 @@ export function useAutoTranscribe() {
 
   // Helper function to get localized error message
   const getLocalizedErrorMessage = useCallback((error: unknown): string => {
-    let errorMessage = 'Unknown error';
-
-    if (error instanceof Error) {
-      errorMessage = error.message;
-    } else if (typeof error === 'string') {
-      errorMessage = error;
-    } else if (error && typeof error === 'object' && 'message' in error) {
-      errorMessage = String((error as { message: unknown }).message);
-    }
+    const errorMessage = getErrorMessage(error);
+    const errorCode = getErrorCode(error);
 
-    // Map known error messages to localized versions
-    if (errorMessage.includes('does not contain an audio stream')) {
+    // Map known errors to localized versions
+    if (errorCode === 'no_audio_stream' || errorMessage.includes('does not contain an audio stream')) {
       return t('errors.noAudioStream', 'This video does not contain an audio stream');
     }
 
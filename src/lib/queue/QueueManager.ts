@@ -1,3 +1,5 @@
+import { toError } from '../tauri/errors';
+
 export interface QueueStats {
   pending: number;
   active: number;
@@ -136,7 +138,7 @@ export class QueueManager {
     } catch (error) {
       // Move to errors
       this.active.delete(id);
-      this.errors.set(id, error instanceof Error ? error : new Error(String(error)));
+      this.errors.set(id, toError(error));
     } finally {
       this.notifyStatsChange();
       // Process next item
@@ -455,7 +455,7 @@ export async function scanMediaDirectoryTree(path: string): Promise<DirectoryNod
 
 /**
  * Start watching a directory for file changes
- * Listen for 'file-change' events for updates
+ * Listen for 'file-changes' events (batched) for updates
  */
 export async function startWatchingDirectory(path: string): Promise<void> {
   return invoke<void>('start_watching_directory', { path });
@@ -0,0 +1,30 @@
+import { describe, it, expect } from 'vitest';
+import { CommandError, getErrorCode, getErrorMessage, toError } from './errors';
+
+describe('command errors', () => {
+  const rejection = {
+    code: 'missing_api_key',
+    message: 'Add an OpenAI API key in Settings',
+    detail: 'openai',
+  };
+
+  it('should read the message of a command rejection', () => {
+    expect(getErrorMessage(rejection)).toBe('Add an OpenAI API key in Settings');
+    expect(getErrorCode(rejection)).toBe('missing_api_key');
+  });
+
+  it('should keep code and detail when converting to an Error', () => {
+    const error = toError(rejection);
+    expect(error).toBeInstanceOf(CommandError);
+    expect((error as CommandError).code).toBe('missing_api_key');
+    expect((error as CommandError).detail).toBe('openai');
+  });
+
+  it('should handle errors and strings', () => {
+    const error = new Error('Test error');
+    expect(toError(error)).toBe(error);
+    expect(getErrorMessage('Failed to scan')).toBe('Failed to scan');
+    expect(getErrorCode('Failed to scan')).toBeUndefined();
+    expect(getErrorMessage(undefined)).toBe('Unknown error');
+  });
+});
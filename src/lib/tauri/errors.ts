@@ -0,0 +1,61 @@
+/**
+ * What a failed command rejects with (`AppError` on the Rust side)
+ */
+export interface CommandErrorPayload {
+  code: string;
+  message: string;
+  detail: string | null;
+}
+
+/**
+ * A command rejection as an `Error`, keeping its code and detail
+ */
+export class CommandError extends Error {
+  readonly code: string;
+  readonly detail: string | null;
+
+  constructor(payload: CommandErrorPayload) {
+    super(payload.message);
+    this.name = 'CommandError';
+    this.code = payload.code;
+    this.detail = payload.detail;
+  }
+}
+
+function isCommandErrorPayload(error: unknown): error is CommandErrorPayload {
+  return (
+    typeof error === 'object' &&
+    error !== null &&
+    typeof (error as { code?: unknown }).code === 'string' &&
+    typeof (error as { message?: unknown }).message === 'string'
+  );
+}
+
+/**
+ * Turn anything a command or task rejected with into an `Error`.
+ * Command rejections become a `CommandError` with their code.
+ */
+export function toError(error: unknown): Error {
+  if (error instanceof Error) {
+    return error;
+  }
+  if (isCommandErrorPayload(error)) {
+    return new CommandError(error);
+  }
+  return new Error(typeof error === 'string' ? error : 'Unknown error');
+}
+
+/**
+ * The error code of a command rejection, e.g. "missing_api_key"
+ */
+export function getErrorCode(error: unknown): string | undefined {
+  const converted = toError(error);
+  return converted instanceof CommandError ? converted.code : undefined;
+}
+
+/**
+ * The message to show for anything a command or task rejected with
+ */
+export function getErrorMessage(error: unknown): string {
+  return toError(error).message;
+}
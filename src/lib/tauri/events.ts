@@ -36,12 +36,13 @@ export function onTranscriptionProgress(
 
 /**
  * Listen for file change events from directory watcher
+ * The backend emits deduplicated batches; the callback is invoked once per change
  */
 export function onFileChange(
   callback: (event: FileChangeEvent) => void
 ): Promise<UnlistenFn> {
-  return listen<FileChangeEvent>('file-change', (event) => {
-    callback(event.payload);
+  return listen<FileChangeEvent[]>('file-changes', (event) => {
+    event.payload.forEach(callback);
   });
 }
 
@@ -23,6 +23,15 @@ export type {
   FileChangeEvent,
 } from './types';
 
+// Errors
+export {
+  CommandError,
+  type CommandErrorPayload,
+  toError,
+  getErrorCode,
+  getErrorMessage,
+} from './errors';
+
 // Commands
 export {
   // FFmpeg
@@ -4,6 +4,9 @@ export interface MediaInfo {
   duration: number;
   has_video: boolean;
   has_audio: boolean;
+  width: number | null;
+  height: number | null;
+  frame_rate: { num: number; den: number } | null;
 }
 
 // Whisper model types
@@ -130,4 +133,5 @@ export interface DirectoryNode {
 export type FileChangeEvent =
   | { type: 'Created'; path: string }
   | { type: 'Modified'; path: string }
-  | { type: 'Removed'; path: string };
+  | { type: 'Removed'; path: string }
+  | { type: 'Renamed'; path: { from: string; to: string } };
@@ -44,6 +44,7 @@ import {
 	fetchClaudeModelsDirect,
 	type OpenAIModel,
 	type ClaudeModel,
+	getErrorMessage,
 } from "@/lib/tauri";
 
 
@@ -195,8 +196,7 @@ function TranscriptionSection() {
 			setInstallProgress(null);
 			loadData();
 		} catch (error) {
-			const errorMessage =
-				error instanceof Error ? error.message : String(error);
+			const errorMessage = getErrorMessage(error);
 			console.error("[Install] Failed to install whisper.cpp:", errorMessage);
 			setIsInstallingWhisper(false);
 			setInstallProgress(null);